@@ -5,6 +5,12 @@
 // ============================================
 console.log('[Background] VERSION: 2024-11-29-v10 - Single message (64MB limit Chrome→Native)');
 
+// Hosts (and their subdomains) of client-rendered sites whose pages keep
+// loading content after the tab reports complete; extraction is repeated
+// until their text stops growing. Other pages are only re-extracted when they
+// come back nearly empty.
+const SLOW_RENDER_DOMAINS = [];
+
 // PDF processing using Offscreen API
 let offscreenCreated = false;
 
@@ -40,6 +46,13 @@ class ParallelContentFetcher {
     this.contentWaitTime = options.contentWaitTime || 5000;
     this.retryAttempts = options.retryAttempts || 2;
     this.progressCallback = options.progressCallback || null;
+    // Pages with less text than this are treated as still rendering
+    this.minContentLength = options.minContentLength ?? 200;
+    this.renderRetries = options.renderRetries ?? 3;
+    this.renderRetryDelay = options.renderRetryDelay ?? 2000;
+    this.slowRenderDomains = options.slowRenderDomains || SLOW_RENDER_DOMAINS;
+    // Scanned PDF pages sent to the native host for OCR (0 when it can't)
+    this.ocrPages = options.ocrPages || 0;
     
     this.activeJobs = new Map();
     this.queue = [];
@@ -176,7 +189,7 @@ class ParallelContentFetcher {
       });
       
       await this.waitForTabLoad(tabId);
-      const content = await this.extractContent(tabId);
+      const content = await this.extractRenderedContent(tabId);
       
       this.cleanupJob(tabId);
       this.metrics.successCount++;
@@ -233,6 +246,97 @@ class ParallelContentFetcher {
            (url.startsWith('file:///') && lowerUrl.includes('.pdf'));
   }
 
+  isYouTubeVideoUrl(url) {
+    try {
+      const parsed = new URL(url);
+      const host = parsed.hostname.toLowerCase();
+      return (host === 'youtube.com' || host.endsWith('.youtube.com')) &&
+        (parsed.pathname === '/watch' || parsed.pathname.startsWith('/live/'));
+    } catch {
+      return false;
+    }
+  }
+
+  // Transcript of a YouTube video, one caption per line with the offset (in
+  // code points) and start time of each; null when the video has no captions
+  async extractYouTubeTranscript(tabId) {
+    const results = await chrome.scripting.executeScript({
+      target: { tabId },
+      // The player response is a page global, invisible to the isolated world
+      world: 'MAIN',
+      func: async () => {
+        const tracks = window.ytInitialPlayerResponse?.captions
+          ?.playerCaptionsTracklistRenderer?.captionTracks || [];
+        // Prefer the auto-generated track, which follows the spoken audio
+        const track = tracks.find(t => t.kind === 'asr') || tracks[0];
+        if (!track?.baseUrl) return null;
+
+        const response = await fetch(`${track.baseUrl}&fmt=json3`);
+        if (!response.ok) return null;
+        const data = await response.json();
+
+        const lines = [];
+        const cues = [];
+        let offset = 0;
+        for (const event of data.events || []) {
+          const text = (event.segs || []).map(s => s.utf8 || '').join('').replace(/\s+/g, ' ').trim();
+          if (!text) continue;
+          cues.push({ start_ms: event.tStartMs || 0, offset });
+          lines.push(text);
+          offset += Array.from(text).length + 1;
+        }
+        if (!lines.length) return null;
+
+        const description = document.querySelector('meta[name="description"]')?.getAttribute('content') || '';
+        return {
+          title: document.title || '',
+          content: lines.join('\n'),
+          description,
+          transcript: cues,
+          url: document.location.href,
+          isPDF: false
+        };
+      }
+    });
+    return results[0]?.result || null;
+  }
+
+  isSlowRenderUrl(url) {
+    try {
+      const host = new URL(url).hostname.toLowerCase();
+      return this.slowRenderDomains.some(domain =>
+        host === domain || host.endsWith(`.${domain}`)
+      );
+    } catch {
+      return false;
+    }
+  }
+
+  // Extract a page, giving client-side rendering more time when the text is
+  // (nearly) empty or the site is listed in SLOW_RENDER_DOMAINS
+  async extractRenderedContent(tabId) {
+    let content = await this.extractContent(tabId);
+    if (content.isPDF || content.transcript) return content;
+
+    const slow = this.isSlowRenderUrl(content.url);
+    let previousLength = -1;
+    for (let attempt = 1; attempt <= this.renderRetries; attempt++) {
+      const length = (content.content || '').length;
+      const rendering = length < this.minContentLength || (slow && length > previousLength);
+      if (!rendering) break;
+
+      console.log(`[Parallel] Waiting for ${content.url} to render (${length} chars, attempt ${attempt}/${this.renderRetries})`);
+      previousLength = length;
+      await new Promise(r => setTimeout(r, this.renderRetryDelay));
+      const next = await this.extractContent(tabId);
+      // Keep the fuller extraction in case the page swaps content out again
+      if ((next.content || '').length >= length) {
+        content = next;
+      }
+    }
+    return content;
+  }
+
   async extractContent(tabId) {
     // Get tab information to check if it's a PDF
     const tab = await chrome.tabs.get(tabId);
@@ -268,6 +372,19 @@ class ParallelContentFetcher {
       }
     }
     
+    // YouTube videos are indexed by their transcript, falling back to the page text
+    if (this.isYouTubeVideoUrl(url)) {
+      try {
+        const transcript = await this.extractYouTubeTranscript(tabId);
+        if (transcript) {
+          console.log(`[Parallel] Extracted ${transcript.transcript.length} captions from ${url}`);
+          return transcript;
+        }
+      } catch (error) {
+        console.error(`[Parallel] Failed to extract YouTube transcript:`, error);
+      }
+    }
+
     // Regular web page extraction (unchanged)
     const results = await chrome.scripting.executeScript({
       target: { tabId },
@@ -297,6 +414,39 @@ class ParallelContentFetcher {
         
         content = content.replace(/\s+/g, ' ').trim();
         
+        // Headings with their offsets (in code points) into the flattened text
+        const headings = [];
+        let searchFrom = 0;
+        if (contentArea) {
+          contentArea.querySelectorAll('h1, h2, h3, h4, h5, h6').forEach(heading => {
+            const text = (heading.innerText || heading.textContent || '').replace(/\s+/g, ' ').trim();
+            const index = text ? content.indexOf(text, searchFrom) : -1;
+            if (index === -1) return;
+            headings.push({
+              level: Number(heading.tagName[1]),
+              text,
+              offset: Array.from(content.slice(0, index)).length
+            });
+            searchFrom = index + text.length;
+          });
+        }
+        
+        // Code blocks keep their line breaks, unlike the flattened text
+        const codeBlocks = contentArea
+          ? Array.from(contentArea.querySelectorAll('pre'))
+              .map(pre => pre.innerText || pre.textContent || '')
+              .filter(text => text.trim())
+          : [];
+        
+        // Markup of the content area, converted to Markdown by the native host
+        let html = '';
+        if (contentArea) {
+          const markup = contentArea.cloneNode(true);
+          markup.querySelectorAll('script, style, noscript, iframe, svg, canvas')
+            .forEach(el => el.remove());
+          html = markup.innerHTML;
+        }
+        
         const metaDesc = document.querySelector('meta[name="description"]');
         const description = metaDesc ? metaDesc.getAttribute('content') : '';
         
@@ -304,6 +454,11 @@ class ParallelContentFetcher {
           title,
           content,
           description,
+          headings,
+          codeBlocks,
+          html,
+          // Kept by the native host only when [index] store_html is enabled
+          rawHtml: document.documentElement.outerHTML,
           url: document.location.href,
           isPDF: false
         };
@@ -343,20 +498,15 @@ class ParallelContentFetcher {
       await ensureOffscreenDocument();
 
       // Send message to offscreen document to extract PDF text
-      return new Promise((resolve, reject) => {
+      const response = await new Promise((resolve, reject) => {
         chrome.runtime.sendMessage(
-          { type: 'extract-pdf-text', url },
+          { type: 'extract-pdf-text', url, ocrPages: this.ocrPages },
           (response) => {
             if (chrome.runtime.lastError) {
               console.error(`[PDF] Error: ${chrome.runtime.lastError.message}`);
               reject(new Error(chrome.runtime.lastError.message));
             } else if (response && response.success) {
-              console.log(`[PDF] Successfully extracted ${response.text.length} characters from ${response.page_info?.page_count || 0} pages`);
-              // Return both text and page_info
-              resolve({
-                text: response.text,
-                page_info: response.page_info
-              });
+              resolve(response);
             } else {
               console.error(`[PDF] Failed to extract text: ${response?.error || 'Unknown error'}`);
               reject(new Error(response?.error || 'Failed to extract PDF text'));
@@ -365,6 +515,28 @@ class ParallelContentFetcher {
         );
       });
 
+      // Pages without a text layer get the text the native host recognizes in them
+      const pages = response.pages;
+      let ocr = false;
+      if (response.scanned_pages?.length) {
+        try {
+          const { texts } = await sendToNative('ocr_images', {
+            images: response.scanned_pages.map(p => p.image)
+          }, 600000);
+          response.scanned_pages.forEach((p, i) => {
+            pages[p.page - 1] = (texts[i] || '').trim();
+          });
+          ocr = true;
+          console.log(`[PDF] OCRed ${texts.length} scanned pages of ${url}`);
+        } catch (error) {
+          console.error(`[PDF] OCR failed for ${url}:`, error);
+        }
+      }
+
+      const result = buildPdfText(pages, ocr);
+      console.log(`[PDF] Successfully extracted ${result.text.length} characters from ${pages.length} pages`);
+      return result;
+
     } catch (error) {
       console.error(`[PDF] Error extracting text from ${url}:`, error);
       throw error;
@@ -378,7 +550,7 @@ class ParallelContentFetcher {
       try {
         const tab = await chrome.tabs.create({ url, active: false });
         await this.waitForTabLoad(tab.id);
-        const content = await this.extractContent(tab.id);
+        const content = await this.extractRenderedContent(tab.id);
         await chrome.tabs.remove(tab.id).catch(() => {});
         return content;
       } catch (error) {
@@ -460,6 +632,40 @@ class ParallelContentFetcher {
 }
 
 // Native Communication
+// Join page texts behind [PAGE:n] markers, with the offset of each page
+function buildPdfText(pages, ocr = false) {
+  const fullText = [];
+  const pageOffsets = [];
+  let currentOffset = 0;
+
+  pages.forEach((pageText, i) => {
+    // Record offset before adding page marker
+    pageOffsets.push(currentOffset);
+
+    const pageMarker = `[PAGE:${i + 1}]`;
+    const pageContent = pageText ? `${pageMarker}\n${pageText}` : pageMarker;
+    fullText.push(pageContent);
+
+    // Update offset: marker + newline + content + double newline (if not last page)
+    currentOffset += pageContent.length;
+    if (i < pages.length - 1) {
+      currentOffset += 2; // for '\n\n' separator
+    }
+  });
+
+  const text = fullText.join('\n\n');
+  return {
+    text: text || 'PDF document (no extractable text)',
+    page_info: {
+      page_count: pages.length,
+      page_offsets: pageOffsets,
+      content_type: 'pdf',
+      total_chars: text.length,
+      ocr
+    }
+  };
+}
+
 function sendToNative(method, params = {}, timeoutMs = 300000) {
   return new Promise((resolve, reject) => {
     const port = chrome.runtime.connectNative('com.mcp_bookmark');
@@ -506,10 +712,14 @@ async function indexFolderParallel(folderId, folderName, indexName) {
     await ensureOffscreenDocument();
   }
   
+  // The native host reports whether it can OCR scanned PDF pages ([ocr] enabled, ocr feature)
+  const ocr = await sendToNative('ping').then(r => r?.ocr, () => null);
+
   try {
     // Step 1: Fetch all content in parallel
     const fetcher = new ParallelContentFetcher({
       maxConcurrent: bookmarks.length <= 2 ? 1 : 5,
+      ocrPages: ocr?.max_pages || 0,
       tabTimeout: 30000,
       contentWaitTime: 5000,
       progressCallback: (indexed, total, failed) => {
@@ -553,11 +763,35 @@ async function indexFolderParallel(folderId, folderName, indexName) {
         bookmarkData.page_info = content.page_info;
       }
 
+      // Add headings, shifted past the description prepended to the content
+      if (content?.headings?.length) {
+        const shift = content.description ? Array.from(`${content.description}\n\n`).length : 0;
+        bookmarkData.headings = content.headings.map(h => ({ ...h, offset: h.offset + shift }));
+      }
+
+      // Caption offsets, shifted the same way
+      if (content?.transcript?.length) {
+        const shift = content.description ? Array.from(`${content.description}\n\n`).length : 0;
+        bookmarkData.transcript = content.transcript.map(c => ({ ...c, offset: c.offset + shift }));
+      }
+
+      if (content?.codeBlocks?.length) {
+        bookmarkData.code_blocks = content.codeBlocks;
+      }
+
+      if (content?.html) {
+        bookmarkData.html = content.html;
+      }
+
+      if (content?.rawHtml) {
+        bookmarkData.raw_html = content.rawHtml;
+      }
+
       bookmarksWithContent.push(bookmarkData);
     }
     
     // Add failed items with empty content
-    for (const { bookmark } of results.failed) {
+    for (const { bookmark, error } of results.failed) {
       bookmarksWithContent.push({
         id: bookmark.id,
         url: bookmark.url,
@@ -565,7 +799,8 @@ async function indexFolderParallel(folderId, folderName, indexName) {
         folder_path: bookmark.folder_path || [],
         date_added: bookmark.dateAdded,
         date_modified: bookmark.dateModified || bookmark.dateAdded,
-        content: ''
+        content: '',
+        fetch_error: error || 'Failed to fetch'
       });
     }
     
@@ -577,6 +812,28 @@ async function indexFolderParallel(folderId, folderName, indexName) {
 
     let successCount = 0;
     let errorCount = 0;
+    // The native host counts each run's progress for the MCP server's get_indexing_status
+    const run = { id: `run_${Date.now()}`, total: bookmarksWithContent.length };
+    const indexParams = bookmark => ({
+      index_name: finalIndexName,
+      id: bookmark.id,
+      url: bookmark.url,
+      title: bookmark.title,
+      folder_path: bookmark.folder_path,
+      date_added: bookmark.date_added,
+      date_modified: bookmark.date_modified,
+      content: bookmark.content,
+      page_info: bookmark.page_info,
+      headings: bookmark.headings,
+      code_blocks: bookmark.code_blocks,
+      html: bookmark.html,
+      raw_html: bookmark.raw_html,
+      transcript: bookmark.transcript,
+      fetch_error: bookmark.fetch_error,
+      run
+    });
+    // Bookmarks the native host failed to index, e.g. while the index was locked
+    const failedSends = [];
 
     for (let i = 0; i < bookmarksWithContent.length; i++) {
       const bookmark = bookmarksWithContent[i];
@@ -585,26 +842,29 @@ async function indexFolderParallel(folderId, folderName, indexName) {
       console.log(`[Simple] Processing bookmark ${i + 1}/${bookmarksWithContent.length}: ${bookmark.title} (${(contentSize / 1024).toFixed(1)} KB)`);
 
       try {
-        await sendToNative('index_bookmark', {
-          index_name: finalIndexName,
-          id: bookmark.id,
-          url: bookmark.url,
-          title: bookmark.title,
-          folder_path: bookmark.folder_path,
-          date_added: bookmark.date_added,
-          date_modified: bookmark.date_modified,
-          content: bookmark.content,
-          page_info: bookmark.page_info
-        }, 120000); // 2 minutes timeout for large content
+        await sendToNative('index_bookmark', indexParams(bookmark), 120000); // 2 minutes timeout for large content
 
         successCount++;
         console.log(`[Simple] Bookmark ${i + 1} indexed successfully`);
       } catch (error) {
         errorCount++;
+        failedSends.push(bookmark);
         console.error(`[Simple] Failed to index bookmark ${i + 1}:`, error);
       }
     }
 
+    // Retry just the failed items once; the host replaces their recorded errors on success
+    for (const bookmark of failedSends) {
+      try {
+        await sendToNative('index_bookmark', indexParams(bookmark), 120000);
+        successCount++;
+        errorCount--;
+        console.log(`[Simple] Retried ${bookmark.url} successfully`);
+      } catch (error) {
+        console.error(`[Simple] Retry failed for ${bookmark.url}:`, error);
+      }
+    }
+
     console.log(`[Simple] All bookmarks sent: ${successCount} success, ${errorCount} errors`);
     
     // Send final progress update to ensure UI shows 100%
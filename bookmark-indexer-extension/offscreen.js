@@ -4,17 +4,21 @@
 // Initialize PDF.js
 pdfjsLib.GlobalWorkerOptions.workerSrc = chrome.runtime.getURL('pdfjs/pdf.worker.js');
 
+// Scanned pages are rendered at 2x (about 144 DPI) for OCR
+const OCR_RENDER_SCALE = 2;
+
 // Listen for messages from the background script
 chrome.runtime.onMessage.addListener((request, sender, sendResponse) => {
   if (request.type === 'extract-pdf-text') {
-    extractPdfText(request.url)
+    extractPdfText(request.url, request.ocrPages || 0)
       .then(result => sendResponse({ success: true, ...result }))
       .catch(error => sendResponse({ success: false, error: error.message }));
     return true; // Keep the message channel open for async response
   }
 });
 
-async function extractPdfText(url) {
+// Text of each page, plus PNG renderings of up to `ocrPages` pages without a text layer
+async function extractPdfText(url, ocrPages) {
   try {
     console.log(`[Offscreen] Fetching PDF from: ${url}`);
 
@@ -68,11 +72,10 @@ async function extractPdfText(url) {
     });
     console.log(`[Offscreen] Loaded PDF with ${pdf.numPages} pages`);
 
-    let fullText = [];
-    let pageOffsets = [];
-    let currentOffset = 0;
+    const pages = [];
+    const scannedPages = [];
 
-    // Extract text from each page with page markers
+    // Extract text from each page
     for (let pageNum = 1; pageNum <= pdf.numPages; pageNum++) {
       const page = await pdf.getPage(pageNum);
       const textContent = await page.getTextContent();
@@ -82,38 +85,19 @@ async function extractPdfText(url) {
         .map(item => item.str)
         .join(' ')
         .trim();
+      pages.push(pageText);
 
-      // Record offset before adding page marker
-      pageOffsets.push(currentOffset);
-
-      const pageMarker = `[PAGE:${pageNum}]`;
-      const pageContent = pageText ? `${pageMarker}\n${pageText}` : pageMarker;
-
-      fullText.push(pageContent);
-
-      // Update offset: marker + newline + content + double newline (if not last page)
-      currentOffset += pageContent.length;
-      if (pageNum < pdf.numPages) {
-        currentOffset += 2; // for '\n\n' separator
+      // No text layer: render the page for the native host to OCR
+      if (!pageText && scannedPages.length < ocrPages) {
+        scannedPages.push({ page: pageNum, image: await renderPage(page) });
       }
     }
 
     // Clean up
     await pdf.destroy();
 
-    const text = fullText.join('\n\n');
-    console.log(`[Offscreen] Extracted ${text.length} total characters from ${pdf.numPages} pages`);
-
-    // Return text with page metadata
-    return {
-      text: text || 'PDF document (no extractable text)',
-      page_info: {
-        page_count: pdf.numPages,
-        page_offsets: pageOffsets,
-        content_type: 'pdf',
-        total_chars: text.length
-      }
-    };
+    console.log(`[Offscreen] Extracted text from ${pdf.numPages} pages (${scannedPages.length} rendered for OCR)`);
+    return { pages, scanned_pages: scannedPages };
 
   } catch (error) {
     console.error(`[Offscreen] Error extracting text from ${url}:`, error);
@@ -121,4 +105,16 @@ async function extractPdfText(url) {
   }
 }
 
+// PNG data URL of a page
+async function renderPage(page) {
+  const viewport = page.getViewport({ scale: OCR_RENDER_SCALE });
+  const canvas = document.createElement('canvas');
+  canvas.width = Math.ceil(viewport.width);
+  canvas.height = Math.ceil(viewport.height);
+  await page.render({ canvasContext: canvas.getContext('2d'), viewport }).promise;
+  const image = canvas.toDataURL('image/png');
+  page.cleanup();
+  return image;
+}
+
 console.log('[Offscreen] PDF processor ready');
\ No newline at end of file
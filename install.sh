@@ -162,23 +162,13 @@ setup_extension() {
     fi
     
     # Setup native messaging host
-    MANIFEST_DIR="$HOME/Library/Application Support/Google/Chrome/NativeMessagingHosts"
-    MANIFEST_PATH="$MANIFEST_DIR/com.mcp_bookmark.json"
-    
     print_info "Setting up native messaging host..."
-    mkdir -p "$MANIFEST_DIR"
-    
-    cat > "$MANIFEST_PATH" <<EOF
-{
-  "name": "com.mcp_bookmark",
-  "description": "MCP Bookmark Native Messaging Host",
-  "path": "$(pwd)/target/release/mcp-bookmark-native",
-  "type": "stdio",
-  "allowed_origins": ["chrome-extension://$EXT_ID/"]
-}
-EOF
-    
-    print_success "Native messaging host configured at: $MANIFEST_PATH"
+    if ./target/release/mcp-bookmark install-extension-host --extension-id "$EXT_ID" --browser chrome; then
+        print_success "Native messaging host configured"
+    else
+        print_error "Failed to configure native messaging host"
+        exit 1
+    fi
     echo
 }
 
@@ -202,7 +192,7 @@ setup_index_and_config() {
     # List available indexes
     print_info "Checking available indexes..."
     echo
-    ./target/release/mcp-bookmark --list-indexes
+    ./target/release/mcp-bookmark list
     echo
     
     # Get the index name from user
@@ -222,16 +212,15 @@ setup_index_and_config() {
   "mcpServers": {
     "mcp-bookmark": {
       "command": "$(pwd)/target/release/mcp-bookmark",
-      "args": [],
+      "args": ["--index", "$INDEX_NAME"],
       "env": {
-        "RUST_LOG": "info",
-        "INDEX_NAME": "$INDEX_NAME"
+        "RUST_LOG": "info"
       }
     }
   }
 }
 EOF
-    print_success "Created .mcp.json with INDEX_NAME: $INDEX_NAME"
+    print_success "Created .mcp.json with --index $INDEX_NAME"
     echo
 }
 
@@ -278,15 +267,15 @@ print_next_steps() {
     echo "4. Try searching your bookmarks!"
     echo
     echo -e "${BLUE}Test the server:${NC}"
-    echo "  INDEX_NAME=\"$INDEX_NAME\" ./target/release/mcp-bookmark"
+    echo "  ./target/release/mcp-bookmark --index \"$INDEX_NAME\""
     echo
     echo -e "${BLUE}Useful commands:${NC}"
-    echo "  List all indexes:     ./target/release/mcp-bookmark --list-indexes"
-    echo "  Clear an index:       ./target/release/mcp-bookmark --clear-index"
-    echo "  Clear all indexes:    ./target/release/mcp-bookmark --clear-all-indexes"
+    echo "  List all indexes:     ./target/release/mcp-bookmark list"
+    echo "  Clear an index:       ./target/release/mcp-bookmark clear <name>"
+    echo "  Clear all indexes:    ./target/release/mcp-bookmark clear --all"
     echo
     echo -e "${BLUE}Configuration:${NC}"
-    echo "  Local MCP config:     .mcp.json (INDEX_NAME: $INDEX_NAME)"
+    echo "  Local MCP config:     .mcp.json (--index $INDEX_NAME)"
     echo "  Indexes stored at:    ~/Library/Application Support/mcp-bookmark/"
     echo
     echo -e "${BLUE}To index more bookmarks:${NC}"
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, SecondsFormat};
+use serde::Deserialize;
+use url::Url;
+
+use crate::content::{ContentFetcher, FetchedPage};
+
+/// Wayback Machine availability API, answering with the latest snapshot of a URL
+pub const AVAILABILITY_URL: &str = "https://archive.org/wayback/available";
+
+/// Statuses of live pages that are looked up in the archive instead
+pub fn is_fallback_status(status: u16) -> bool {
+    matches!(status, 403 | 404 | 410)
+}
+
+/// An Internet Archive capture of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Capture time as the Wayback Machine writes it, `YYYYMMDDhhmmss` in UTC
+    pub timestamp: String,
+    /// URL of the page that was captured
+    pub original: String,
+}
+
+impl Snapshot {
+    /// The capture as originally served, without the Wayback toolbar or rewritten links
+    pub fn raw_url(&self) -> String {
+        format!(
+            "https://web.archive.org/web/{}id_/{}",
+            self.timestamp, self.original
+        )
+    }
+
+    /// Capture time in RFC 3339
+    pub fn captured_at(&self) -> Option<String> {
+        NaiveDateTime::parse_from_str(&self.timestamp, "%Y%m%d%H%M%S")
+            .ok()
+            .map(|time| time.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+}
+
+#[derive(Deserialize)]
+struct Availability {
+    #[serde(default)]
+    archived_snapshots: ArchivedSnapshots,
+}
+
+#[derive(Default, Deserialize)]
+struct ArchivedSnapshots {
+    closest: Option<Closest>,
+}
+
+#[derive(Deserialize)]
+struct Closest {
+    #[serde(default)]
+    available: bool,
+    #[serde(default)]
+    status: String,
+    timestamp: String,
+}
+
+/// The snapshot in an availability response, if there is a successful one
+pub fn parse_availability(json: &str, original: &str) -> Option<Snapshot> {
+    let closest = serde_json::from_str::<Availability>(json)
+        .ok()?
+        .archived_snapshots
+        .closest?;
+    (closest.available && closest.status.starts_with('2')).then(|| Snapshot {
+        timestamp: closest.timestamp,
+        original: original.to_string(),
+    })
+}
+
+/// The latest snapshot of a URL and its content, or None if the archive has none
+pub async fn fetch_latest(
+    fetcher: &ContentFetcher,
+    url: &str,
+) -> Result<Option<(Snapshot, FetchedPage)>> {
+    let mut api = Url::parse(AVAILABILITY_URL)?;
+    api.query_pairs_mut().append_pair("url", url);
+    let response = fetcher.fetch(api.as_str()).await?;
+    anyhow::ensure!(
+        response.is_success(),
+        "Wayback Machine returned HTTP {}",
+        response.status
+    );
+    let Some(snapshot) = parse_availability(&response.text(), url) else {
+        return Ok(None);
+    };
+    let page = fetcher
+        .fetch(&snapshot.raw_url())
+        .await
+        .context("Failed to fetch the archived snapshot")?;
+    anyhow::ensure!(
+        page.is_success(),
+        "Archived snapshot returned HTTP {}",
+        page.status
+    );
+    Ok(Some((snapshot, page)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_availability() {
+        let url = "https://docs.example.com/guide/";
+        let snapshot = parse_availability(
+            r#"{"url": "docs.example.com/guide/", "archived_snapshots": {"closest": {"status": "200",
+                "available": true, "url": "http://web.archive.org/web/20240501093000/https://docs.example.com/guide/",
+                "timestamp": "20240501093000"}}}"#,
+            url,
+        )
+        .unwrap();
+        assert_eq!(
+            snapshot.raw_url(),
+            "https://web.archive.org/web/20240501093000id_/https://docs.example.com/guide/"
+        );
+        assert_eq!(
+            snapshot.captured_at().as_deref(),
+            Some("2024-05-01T09:30:00Z")
+        );
+
+        assert_eq!(
+            parse_availability(r#"{"archived_snapshots": {}}"#, url),
+            None
+        );
+        assert_eq!(
+            parse_availability(
+                r#"{"archived_snapshots": {"closest": {"status": "404", "available": true, "timestamp": "20240501093000"}}}"#,
+                url
+            ),
+            None
+        );
+        assert!(is_fallback_status(404) && is_fallback_status(403));
+        assert!(!is_fallback_status(500));
+    }
+}
@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Directory under the data directory holding one audit log per index
+///
+/// Kept outside the index directories so the history of a cleared or purged
+/// index survives it.
+pub const AUDIT_DIR: &str = "audit";
+
+/// What changed an index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MutationSource {
+    /// The Chrome extension, through the native messaging host
+    Extension,
+    /// A command-line subcommand (`index`, `index site`, `index apply`, `import`, `ingest`, `feeds refresh`, `clear`, `restore`)
+    Cli,
+    /// An MCP tool of the running server (`add_bookmark`)
+    Server,
+    /// A scheduled task of `mcp-bookmark daemon`
+    Daemon,
+}
+
+impl MutationSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Extension => "extension",
+            Self::Cli => "cli",
+            Self::Server => "server",
+            Self::Daemon => "daemon",
+        }
+    }
+}
+
+/// Kind of index mutation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mutation {
+    /// One page indexed or re-indexed, replacing its earlier documents
+    IndexBookmark,
+    /// Index rebuilt from a browser or an import in one batch commit
+    Build,
+    /// Pages of a documentation site crawled by `index site`, replacing earlier copies
+    IndexSite,
+    /// Index moved to the trash
+    Clear,
+    /// Index deleted permanently
+    Purge,
+    /// Index brought back from the trash
+    Restore,
+    /// A URL fetched and indexed by the `add_bookmark` tool
+    AddBookmark,
+    /// A list of URLs fetched and indexed by `ingest`, replacing earlier copies
+    Ingest,
+    /// New entries of an index's RSS/Atom feeds indexed by `feeds refresh` or the daemon
+    FeedRefresh,
+    /// Stale server-fetched pages fetched again by the daemon
+    Recrawl,
+    /// A changeset from another machine's index merged by `index apply`
+    Apply,
+}
+
+impl Mutation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IndexBookmark => "index_bookmark",
+            Self::Build => "build",
+            Self::IndexSite => "index_site",
+            Self::Clear => "clear",
+            Self::Purge => "purge",
+            Self::Restore => "restore",
+            Self::AddBookmark => "add_bookmark",
+            Self::Ingest => "ingest",
+            Self::FeedRefresh => "feed_refresh",
+            Self::Recrawl => "recrawl",
+            Self::Apply => "apply",
+        }
+    }
+}
+
+/// One recorded mutation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub source: MutationSource,
+    pub mutation: Mutation,
+    /// Documents written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<usize>,
+    /// Documents deleted or moved away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<usize>,
+    /// Bookmark URL, for single-page mutations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl AuditEntry {
+    /// Entry for a mutation happening now
+    pub fn new(source: MutationSource, mutation: Mutation) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            source,
+            mutation,
+            added: None,
+            removed: None,
+            url: None,
+        }
+    }
+
+    pub fn with_added(mut self, added: usize) -> Self {
+        self.added = Some(added);
+        self
+    }
+
+    pub fn with_removed(mut self, removed: Option<usize>) -> Self {
+        self.removed = removed;
+        self
+    }
+
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+}
+
+/// Append-only JSON lines log of an index's mutations
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Log of an index in the default data directory
+    pub fn for_index(index: &str) -> Self {
+        Self::at(
+            crate::config::data_dir()
+                .join(AUDIT_DIR)
+                .join(format!("{index}.jsonl")),
+        )
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Add an entry at the end of the log
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {:?}", self.path))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Add an entry, logging instead of failing so a mutation never fails on its audit record
+    pub fn record(index: &str, entry: AuditEntry) {
+        if let Err(e) = Self::for_index(index).append(&entry) {
+            warn!(
+                "Failed to record {} in audit log: {:#}",
+                entry.mutation.name(),
+                e
+            );
+        }
+    }
+
+    /// All entries, oldest first (empty if nothing was recorded)
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", self.path)),
+        };
+        let mut skipped = 0;
+        let entries = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(_) => {
+                    skipped += 1;
+                    None
+                }
+            })
+            .collect();
+        if skipped > 0 {
+            warn!("Skipped {} unreadable lines in {:?}", skipped, self.path);
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::at(temp_dir.path().join(AUDIT_DIR).join("work.jsonl"));
+        assert!(log.entries().unwrap().is_empty());
+
+        log.append(
+            &AuditEntry::new(MutationSource::Cli, Mutation::Build)
+                .with_added(120)
+                .with_removed(Some(100)),
+        )
+        .unwrap();
+        log.append(
+            &AuditEntry::new(MutationSource::Extension, Mutation::IndexBookmark)
+                .with_added(1)
+                .with_url("https://example.com"),
+        )
+        .unwrap();
+
+        let entries = log.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].mutation, Mutation::Build);
+        assert_eq!(entries[0].removed, Some(100));
+        assert_eq!(entries[1].source, MutationSource::Extension);
+        assert_eq!(entries[1].url.as_deref(), Some("https://example.com"));
+
+        let line =
+            std::fs::read_to_string(temp_dir.path().join(AUDIT_DIR).join("work.jsonl")).unwrap();
+        assert!(line.contains(r#""mutation":"index_bookmark""#));
+        assert!(line.contains(r#""source":"extension""#));
+    }
+}
@@ -1,27 +1,33 @@
-use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use anyhow::Result;
 use serde_json::{Value, json};
-use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // Import Tantivy integration from main crate
+use mcp_bookmark::audit::{AuditEntry, AuditLog, Mutation, MutationSource};
 use mcp_bookmark::bookmark::FlatBookmark;
-use mcp_bookmark::search::indexer::{BookmarkIndexer, PageInfo};
+use mcp_bookmark::config::ContentSettings;
+use mcp_bookmark::search::embeddings::{
+    EmbeddingCache, EmbeddingStore, embed_bookmark, shared_embedder,
+};
+use mcp_bookmark::search::index_lock::{IndexLock, IndexLockedError};
+use mcp_bookmark::search::indexer::{BookmarkIndexer, PageInfo, PageStructure, truncate_page};
 use mcp_bookmark::search::schema::BookmarkSchema;
-use tantivy::schema::Value as TantivyValue;
+use mcp_bookmark::search::sync_metadata::{RunOutcome, SyncMetadata};
+use mcp_bookmark::search::tokenizer::{
+    index_dictionary, register_index_tokenizer, save_index_analyzer,
+};
 use tantivy::Index;
-
-// Import Lindera tokenizer
-use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
-use lindera::mode::{Mode, Penalty};
-use lindera::segmenter::Segmenter;
-use lindera_tantivy::tokenizer::LinderaTokenizer;
+use tantivy::schema::Value as TantivyValue;
 
 // Configuration constants
 const LOG_FILE_PATH: &str = "/tmp/mcp-bookmark-native.log";
 const INDEX_WRITER_HEAP_SIZE: usize = 50_000_000;
+/// How long to wait for a CLI rebuild or another host writing the same index
+const INDEX_LOCK_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
+/// JSON-RPC error code for an index another process is writing; the request can be retried
+const INDEX_LOCKED_ERROR: i64 = -32001;
 
 fn log_to_file(msg: &str) {
     if let Ok(mut file) = OpenOptions::new()
@@ -37,25 +43,16 @@ fn log_to_file(msg: &str) {
     }
 }
 
-// Metadata for tracking indexed bookmarks
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct BookmarkMetadata {
-    url: String,
-    date_modified: Option<String>,
-    indexed_at: u64,
-    content_hash: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct IndexMetadata {
-    bookmarks: HashMap<String, BookmarkMetadata>, // key: bookmark ID
-    last_full_sync: u64,
-}
-
 struct NativeMessagingHost {
     indexer: Option<BookmarkIndexer>,
     index_name: String,
-    metadata: Option<IndexMetadata>,
+    metadata: Option<SyncMetadata>,
+    /// Chunk vectors of the current index (loaded when embeddings are enabled)
+    embeddings: Option<EmbeddingStore>,
+    /// Vectors of already embedded texts, shared by all indexes
+    embedding_cache: Option<EmbeddingCache>,
+    /// `[content]` limits, which the extension can override with `initialize`
+    content_limits: ContentSettings,
 }
 
 impl NativeMessagingHost {
@@ -64,45 +61,29 @@ impl NativeMessagingHost {
             indexer: None,
             index_name: "Extension_Bookmarks".to_string(),
             metadata: None,
+            embeddings: None,
+            embedding_cache: None,
+            content_limits: mcp_bookmark::config::settings().content.clone(),
         }
     }
 
-    fn metadata_path(&self) -> PathBuf {
-        dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("mcp-bookmark")
-            .join(&self.index_name)
-            .join("index_metadata.json")
+    fn index_dir(&self) -> PathBuf {
+        mcp_bookmark::config::data_dir().join(&self.index_name)
     }
 
     fn load_metadata(&mut self) -> Result<()> {
-        let path = self.metadata_path();
-        if path.exists() {
-            let content = std::fs::read_to_string(&path)?;
-            self.metadata = Some(serde_json::from_str(&content)?);
-            log_to_file(&format!(
-                "Loaded metadata with {} bookmarks",
-                self.metadata
-                    .as_ref()
-                    .map(|m| m.bookmarks.len())
-                    .unwrap_or(0)
-            ));
-        } else {
-            self.metadata = Some(IndexMetadata {
-                bookmarks: HashMap::new(),
-                last_full_sync: 0,
-            });
-            log_to_file("Created new metadata");
-        }
+        let metadata = SyncMetadata::load(&self.index_dir())?;
+        log_to_file(&format!(
+            "Loaded metadata with {} bookmarks",
+            metadata.bookmarks.len()
+        ));
+        self.metadata = Some(metadata);
         Ok(())
     }
 
     fn save_metadata(&self) -> Result<()> {
         if let Some(metadata) = &self.metadata {
-            let path = self.metadata_path();
-            std::fs::create_dir_all(path.parent().unwrap())?;
-            let content = serde_json::to_string_pretty(metadata)?;
-            std::fs::write(&path, content)?;
+            metadata.save(&self.index_dir())?;
             log_to_file(&format!(
                 "Saved metadata with {} bookmarks",
                 metadata.bookmarks.len()
@@ -111,21 +92,23 @@ impl NativeMessagingHost {
         Ok(())
     }
 
-    fn calculate_content_hash(content: Option<&str>) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        content.unwrap_or("").hash(&mut hasher);
-        hasher.finish().to_string()
+    /// Count the bookmark towards the extension's run sent as `params["run"]` (`id`, `total`)
+    ///
+    /// Returns whether the message is part of a run; the caller saves the metadata.
+    fn count_progress(&mut self, params: &Value, outcome: RunOutcome) -> bool {
+        let (Some(run_id), Some(metadata)) = (params["run"]["id"].as_str(), &mut self.metadata)
+        else {
+            return false;
+        };
+        let total = params["run"]["total"].as_u64().unwrap_or(0) as usize;
+        let url = params["url"].as_str().unwrap_or("");
+        metadata.record_progress(run_id, total, url, outcome);
+        true
     }
 
     fn init_tantivy(&mut self) -> Result<()> {
         // Use the same directory as MCP server with index name
-        let index_path = dirs::data_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("mcp-bookmark")
-            .join(&self.index_name);
+        let index_path = mcp_bookmark::config::data_dir().join(&self.index_name);
 
         // Create directory if it doesn't exist
         std::fs::create_dir_all(&index_path)?;
@@ -137,11 +120,14 @@ impl NativeMessagingHost {
         let index = if index_path.join("meta.json").exists() {
             Index::open_in_dir(&index_path)?
         } else {
+            // New indexes use the configured dictionary; existing ones keep theirs
+            let dictionary = mcp_bookmark::config::settings().tokenizer.dictionary;
+            save_index_analyzer(&index_path, dictionary)?;
             Index::create_in_dir(&index_path, schema.schema.clone())?
         };
 
         // Register Lindera tokenizer for Japanese text processing
-        Self::register_lindera_tokenizer(&index)?;
+        Self::register_lindera_tokenizer(&index, &index_path)?;
 
         self.indexer = Some(BookmarkIndexer::new(index, schema));
 
@@ -155,26 +141,22 @@ impl NativeMessagingHost {
         Ok(())
     }
 
-    /// Register Lindera tokenizer for Japanese text
-    fn register_lindera_tokenizer(index: &Index) -> Result<()> {
-        log_to_file("Registering Lindera tokenizer for Japanese text processing");
-
-        // Load IPADIC dictionary
-        let dictionary = load_dictionary_from_kind(DictionaryKind::IPADIC)
-            .context("Failed to load IPADIC dictionary")?;
-
-        // Use Decompose mode for better search results
-        let mode = Mode::Decompose(Penalty::default());
-        let user_dictionary = None;
-
-        // Create Segmenter with the dictionary
-        let segmenter = Segmenter::new(mode, dictionary, user_dictionary);
-
-        // Create Lindera tokenizer from segmenter
-        let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    /// Register the Lindera tokenizer matching the analyzer the index was built with
+    fn register_lindera_tokenizer(index: &Index, index_path: &Path) -> Result<()> {
+        let dictionary = index_dictionary(index_path)?;
+        log_to_file(&format!(
+            "Registering Lindera tokenizer ({}) for Japanese text processing",
+            dictionary.name()
+        ));
+        if let Some(path) = &mcp_bookmark::config::settings().tokenizer.user_dictionary {
+            log_to_file(&format!("Using user dictionary: {path:?}"));
+        }
 
-        // Register the tokenizer with name "lang_ja"
-        index.tokenizers().register("lang_ja", tokenizer);
+        // Fails if the index was built with different tokenizer settings
+        if let Err(e) = register_index_tokenizer(index, index_path) {
+            log_to_file(&format!("Failed to register tokenizer: {e:#}"));
+            return Err(e);
+        }
 
         log_to_file("Lindera tokenizer registered successfully");
         Ok(())
@@ -193,17 +175,24 @@ impl NativeMessagingHost {
                     "result": {
                         "status": "ok",
                         "tantivy_initialized": self.indexer.is_some(),
-                        "index_name": self.index_name
+                        "index_name": self.index_name,
+                        // Lets the extension send scanned PDF pages for OCR
+                        "ocr": mcp_bookmark::ocr::enabled().then(|| json!({
+                            "max_pages": mcp_bookmark::config::settings().ocr.max_pages
+                        }))
                     }
                 })
             }
 
+            "ocr_images" => self.ocr_images(&message["params"], id),
+
             "index_bookmark" => {
                 log_to_file("handle_message: index_bookmark branch");
                 // Update index name if provided in params
                 if let Some(index_name) = message["params"]["index_name"].as_str() {
                     self.index_name = index_name.to_string();
                     self.indexer = None; // Reset indexer to use new index
+                    self.embeddings = None;
                     log_to_file(&format!("Index name updated to: {}", self.index_name));
                 }
                 log_to_file("handle_message: before init_tantivy check");
@@ -235,6 +224,7 @@ impl NativeMessagingHost {
 
             // Legacy MCP methods for compatibility
             "initialize" => {
+                self.apply_content_limits(&message["params"]["content_limits"]);
                 json!({
                     "jsonrpc": "2.0",
                     "id": id,
@@ -246,7 +236,9 @@ impl NativeMessagingHost {
                         "serverInfo": {
                             "name": "mcp-bookmark-native",
                             "version": "0.2.0"
-                        }
+                        },
+                        // Limits in effect, so the extension can warn before sending huge pages
+                        "contentLimits": self.content_limits
                     }
                 })
             }
@@ -264,6 +256,32 @@ impl NativeMessagingHost {
         }
     }
 
+    /// Override the configured content limits with those sent by the extension
+    ///
+    /// Accepts the keys of `[content]`; missing or invalid values keep the configured limit.
+    fn apply_content_limits(&mut self, limits: &Value) {
+        if limits.is_null() {
+            return;
+        }
+        let as_usize = |value: &Value| value.as_u64().map(|n| n as usize);
+        if let Some(max_chars_per_doc) = as_usize(&limits["max_chars_per_doc"]) {
+            self.content_limits.max_chars_per_doc = max_chars_per_doc;
+        }
+        if let Some(max_chars) = as_usize(&limits["max_chars"]) {
+            self.content_limits.max_chars = max_chars;
+        }
+        if let Some(by_type) = limits["max_chars_by_type"].as_object() {
+            for (content_type, max_chars) in by_type {
+                if let Some(max_chars) = as_usize(max_chars) {
+                    self.content_limits
+                        .max_chars_by_type
+                        .insert(content_type.clone(), max_chars);
+                }
+            }
+        }
+        log_to_file(&format!("Content limits: {:?}", self.content_limits));
+    }
+
     fn index_bookmark(&mut self, params: Value, id: Value) -> Value {
         log_to_file("index_bookmark: START");
 
@@ -272,6 +290,7 @@ impl NativeMessagingHost {
             if self.index_name != index_name {
                 self.index_name = index_name.to_string();
                 self.indexer = None; // Reset indexer to use new index
+                self.embeddings = None;
                 log_to_file(&format!("Index name updated to: {}", self.index_name));
             }
         }
@@ -318,15 +337,35 @@ impl NativeMessagingHost {
                         .collect()
                 })
                 .unwrap_or_default(),
+            tags: params["tags"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            profile: params["profile"].as_str().map(String::from),
             date_added: params["date_added"].as_str().map(String::from),
             date_modified: params["date_modified"].as_str().map(String::from),
         };
 
-        let content = params["content"].as_str();
+        // Pages of domains excluded by `[domains]` are indexed by title and URL alone
+        let content_allowed = mcp_bookmark::config::settings()
+            .domains
+            .allows_content(&bookmark.url);
+        if !content_allowed {
+            log_to_file(&format!(
+                "Dropping content of {}: its domain is excluded by [domains]",
+                bookmark.url
+            ));
+        }
+        let content = params["content"].as_str().filter(|_| content_allowed);
         let skip_if_unchanged = params["skip_if_unchanged"].as_bool().unwrap_or(false);
 
         // Parse page_info if available (for PDFs)
-        let page_info = params["page_info"].as_object().and_then(|obj| {
+        let mut page_info = params["page_info"].as_object().and_then(|obj| {
             let page_count = obj.get("page_count")?.as_u64()? as usize;
             let page_offsets = obj
                 .get("page_offsets")?
@@ -336,37 +375,98 @@ impl NativeMessagingHost {
                 .collect::<Vec<_>>();
             let content_type = obj.get("content_type")?.as_str()?.to_string();
             let total_chars = obj.get("total_chars")?.as_u64()? as usize;
+            let ocr = obj.get("ocr").and_then(|v| v.as_bool()).unwrap_or(false);
 
             Some(PageInfo {
                 page_count,
                 page_offsets,
                 content_type,
                 total_chars,
+                ocr,
+                part: None,
+                truncated_from: None,
             })
         });
 
+        // Headings (with character offsets into content), <pre> blocks and HTML of web pages
+        let mut structure = PageStructure {
+            headings: params
+                .get("headings")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+            code_blocks: params
+                .get("code_blocks")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+            html: params["html"].as_str().map(String::from),
+            // Only kept when `[index] store_html` is enabled
+            raw_html: mcp_bookmark::config::settings()
+                .index
+                .store_html
+                .then(|| params["raw_html"].as_str().map(String::from))
+                .flatten(),
+            // Caption start times when the content is a video transcript
+            transcript: params
+                .get("transcript")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+            archived_at: None,
+            truncated_from: None,
+        };
+        if !content_allowed {
+            page_info = None;
+            structure = PageStructure::default();
+        }
+
         // Check if we should skip this bookmark
         if skip_if_unchanged {
             if let Some(metadata) = &self.metadata {
-                if let Some(existing) = metadata.bookmarks.get(&bookmark.id) {
-                    let content_hash = Self::calculate_content_hash(content);
-                    if existing.date_modified == bookmark.date_modified
-                        && existing.content_hash == Some(content_hash)
-                    {
-                        log_to_file(&format!("Skipping unchanged bookmark: {}", bookmark.url));
-                        return json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "result": {
-                                "status": "skipped",
-                                "url": bookmark.url
-                            }
-                        });
+                if metadata.is_unchanged(&bookmark, content) {
+                    log_to_file(&format!("Skipping unchanged bookmark: {}", bookmark.url));
+                    if self.count_progress(&params, RunOutcome::Known) {
+                        let _ = self.save_metadata();
                     }
+                    return json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "status": "skipped",
+                            "url": bookmark.url
+                        }
+                    });
                 }
             }
         }
 
+        // The extension opens a connection per message, so limits can come with each bookmark too
+        self.apply_content_limits(&params["content_limits"]);
+
+        // Content over the limit for its type is cut off; the document records its original length
+        let content_type = page_info
+            .as_ref()
+            .map_or("html", |pi| pi.content_type.as_str());
+        let truncated = content
+            .zip(self.content_limits.max_chars_for(content_type))
+            .and_then(|(content, max_chars)| {
+                truncate_page(content, page_info.as_ref(), &structure, max_chars)
+            });
+        let mut indexed_content = content;
+        let mut truncated_from = None;
+        let truncated_content;
+        if let Some(page) = truncated {
+            log_to_file(&format!(
+                "Truncating content of {} from {} to {} chars",
+                bookmark.url,
+                page.original_chars(),
+                page.content.chars().count()
+            ));
+            truncated_from = Some(page.original_chars());
+            page_info = page.page_info;
+            structure = page.structure;
+            truncated_content = page.content;
+            indexed_content = Some(truncated_content.as_str());
+        }
+
         log_to_file(&format!(
             "Indexing bookmark: {} with content: {} chars, page_info: {}",
             bookmark.url,
@@ -374,49 +474,97 @@ impl NativeMessagingHost {
             page_info.is_some()
         ));
 
+        // Held until the metadata and embeddings are written too
+        let index_path = mcp_bookmark::config::data_dir().join(&self.index_name);
+        let _lock = match IndexLock::acquire(
+            &index_path,
+            "the native messaging host",
+            Some(INDEX_LOCK_WAIT),
+        ) {
+            Ok(lock) => lock,
+            Err(e) => {
+                log_to_file(&format!("Failed to lock index: {e:#}"));
+                if self.count_progress(&params, RunOutcome::Errored(format!("{e:#}"))) {
+                    let _ = self.save_metadata();
+                }
+                let code = if e.is::<IndexLockedError>() {
+                    INDEX_LOCKED_ERROR
+                } else {
+                    -32603
+                };
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": code,
+                        "message": format!("{e:#}")
+                    }
+                });
+            }
+        };
+
         // Index the bookmark with page info if available
         match self.index_single_bookmark_with_page_info(
             indexer,
             &bookmark,
-            content,
+            indexed_content,
             page_info.as_ref(),
+            &structure,
         ) {
-            Ok(_) => {
+            Ok(documents) => {
+                let entry = AuditEntry::new(MutationSource::Extension, Mutation::IndexBookmark)
+                    .with_added(documents)
+                    .with_url(&bookmark.url);
+                if let Err(e) = AuditLog::for_index(&self.index_name).append(&entry) {
+                    log_to_file(&format!("Failed to record audit entry: {e:#}"));
+                }
+
+                // Pages the extension couldn't fetch arrive without content and count as errors
+                let outcome = match params["fetch_error"].as_str() {
+                    Some(error) => RunOutcome::Errored(error.to_string()),
+                    None => RunOutcome::Indexed,
+                };
+                let in_run = self.count_progress(&params, outcome);
+
                 // Update metadata
                 if let Some(metadata) = &mut self.metadata {
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs();
-
-                    metadata.bookmarks.insert(
-                        bookmark.id.clone(),
-                        BookmarkMetadata {
-                            url: bookmark.url.clone(),
-                            date_modified: bookmark.date_modified.clone(),
-                            indexed_at: now,
-                            content_hash: Some(Self::calculate_content_hash(content)),
-                        },
-                    );
+                    metadata.record(&bookmark, content);
 
-                    // Save metadata periodically (every 10 bookmarks) or always for small collections
-                    if metadata.bookmarks.len() % 10 == 0 || metadata.bookmarks.len() <= 5 {
+                    // Save metadata periodically (every 10 bookmarks) or always for small collections,
+                    // and after every bookmark of a run so the server can report its progress
+                    if in_run || metadata.bookmarks.len() % 10 == 0 || metadata.bookmarks.len() <= 5
+                    {
                         let _ = self.save_metadata();
                     }
                 }
 
+                if mcp_bookmark::config::settings().embeddings.enabled {
+                    // Semantic search is optional; a failure here keeps the full-text index
+                    if let Err(e) =
+                        self.update_embeddings(&bookmark, indexed_content, page_info.as_ref())
+                    {
+                        log_to_file(&format!("Failed to embed bookmark {}: {e}", bookmark.url));
+                    }
+                }
+
                 log_to_file(&format!("Successfully indexed bookmark: {}", bookmark.url));
                 json!({
                     "jsonrpc": "2.0",
                     "id": id,
                     "result": {
                         "status": "indexed",
-                        "url": bookmark.url
+                        "url": bookmark.url,
+                        "content_indexed": content.is_some(),
+                        // Set when the content was cut to the limit, so the extension can warn
+                        "truncated_from": truncated_from
                     }
                 })
             }
             Err(e) => {
                 log_to_file(&format!("Failed to index bookmark: {e}"));
+                if self.count_progress(&params, RunOutcome::Errored(format!("{e:#}"))) {
+                    let _ = self.save_metadata();
+                }
                 json!({
                     "jsonrpc": "2.0",
                     "id": id,
@@ -429,20 +577,58 @@ impl NativeMessagingHost {
         }
     }
 
+    /// Re-embed a bookmark's chunks (per page for PDFs) and save the index's embedding store
+    fn update_embeddings(
+        &mut self,
+        bookmark: &FlatBookmark,
+        content: Option<&str>,
+        page_info: Option<&PageInfo>,
+    ) -> Result<()> {
+        let index_dir = mcp_bookmark::config::data_dir().join(&self.index_name);
+        let embedder = shared_embedder()?;
+        let cache_path = EmbeddingCache::path();
+        if self.embedding_cache.is_none() {
+            self.embedding_cache = Some(EmbeddingCache::load(&cache_path)?);
+        }
+        let cache = self.embedding_cache.get_or_insert_default();
+        let chunks = embed_bookmark(
+            embedder,
+            cache,
+            &bookmark.id,
+            &bookmark.url,
+            &bookmark.name,
+            content.unwrap_or(""),
+            page_info.map(|pi| pi.page_offsets.as_slice()),
+        )?;
+        cache.save(&cache_path)?;
+
+        if self.embeddings.is_none() {
+            self.embeddings = Some(EmbeddingStore::load(&index_dir)?);
+        }
+        let store = self.embeddings.get_or_insert_default();
+        if store.model != embedder.model() {
+            // Vectors from different models can't be compared; start over
+            *store = EmbeddingStore {
+                model: embedder.model().to_string(),
+                ..Default::default()
+            };
+        }
+        store.replace(&bookmark.id, chunks);
+        store.save(&index_dir)?;
+        log_to_file(&format!("Embedded bookmark: {}", bookmark.url));
+        Ok(())
+    }
+
     fn index_single_bookmark_with_page_info(
         &self,
         indexer: &BookmarkIndexer,
         bookmark: &FlatBookmark,
         content: Option<&str>,
         page_info: Option<&PageInfo>,
-    ) -> Result<()> {
+        structure: &PageStructure,
+    ) -> Result<usize> {
         log_to_file("index_single_bookmark_with_page_info: START");
 
-        // Max chars per document to prevent Lindera tokenizer from hanging
-        // 100K chars is a safe limit for Japanese text tokenization
-        // (~300KB in UTF-8, tokenizable in reasonable time)
-        const MAX_CHARS_PER_DOC: usize = 100_000;
-
         // Create a writer for this single bookmark
         log_to_file("index_single_bookmark_with_page_info: creating writer...");
         let mut writer = indexer.create_writer(INDEX_WRITER_HEAP_SIZE)?;
@@ -461,6 +647,7 @@ impl NativeMessagingHost {
         log_to_file("index_single_bookmark_with_page_info: existing documents deleted");
 
         // Index with page-based splitting if we have page info and large content
+        let mut documents = 1;
         if let (Some(content_str), Some(pi)) = (content, page_info) {
             let char_count = content_str.chars().count();
             log_to_file(&format!(
@@ -468,7 +655,8 @@ impl NativeMessagingHost {
                 char_count, pi.page_count
             ));
 
-            if char_count > MAX_CHARS_PER_DOC && pi.page_count > 1 {
+            let max_chars_per_doc = self.content_limits.max_chars_per_doc;
+            if char_count > max_chars_per_doc && pi.page_count > 1 {
                 // Use page-based splitting for large PDFs
                 log_to_file("index_single_bookmark_with_page_info: using page-based splitting");
                 let doc_count = indexer.index_bookmark_with_page_splitting(
@@ -476,11 +664,12 @@ impl NativeMessagingHost {
                     bookmark,
                     content_str,
                     pi,
-                    MAX_CHARS_PER_DOC,
+                    max_chars_per_doc,
                 )?;
                 log_to_file(&format!(
                     "index_single_bookmark_with_page_info: created {doc_count} documents via page splitting"
                 ));
+                documents = doc_count;
             } else {
                 // Small content or single page - use regular indexing
                 log_to_file(&format!(
@@ -509,16 +698,69 @@ impl NativeMessagingHost {
             );
         } else {
             // No page info - regular indexing
-            log_to_file("index_single_bookmark_with_page_info: indexing without page_info");
-            indexer.index_bookmark(&mut writer, bookmark, content)?;
-            log_to_file("index_single_bookmark_with_page_info: index_bookmark completed");
+            log_to_file(&format!(
+                "index_single_bookmark_with_page_info: indexing without page_info ({} headings, {} code blocks)",
+                structure.headings.len(),
+                structure.code_blocks.len()
+            ));
+            indexer.index_bookmark_with_structure(&mut writer, bookmark, content, structure)?;
+            log_to_file(
+                "index_single_bookmark_with_page_info: index_bookmark_with_structure completed",
+            );
         }
 
         // Commit
         log_to_file("index_single_bookmark_with_page_info: committing...");
         writer.commit()?;
         log_to_file("index_single_bookmark_with_page_info: commit completed");
-        Ok(())
+        Ok(documents)
+    }
+
+    /// Recognize the text of scanned PDF pages rendered by the extension, in order
+    fn ocr_images(&self, params: &Value, id: Value) -> Value {
+        let Some(images) = params["images"].as_array() else {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": -32602,
+                    "message": "Missing images"
+                }
+            });
+        };
+
+        let started = std::time::Instant::now();
+        let texts: anyhow::Result<Vec<String>> = images
+            .iter()
+            .map(|image| mcp_bookmark::ocr::recognize_image(image.as_str().unwrap_or_default()))
+            .collect();
+        match texts {
+            Ok(texts) => {
+                log_to_file(&format!(
+                    "ocr_images: recognized {} pages in {:?}",
+                    texts.len(),
+                    started.elapsed()
+                ));
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "texts": texts
+                    }
+                })
+            }
+            Err(e) => {
+                log_to_file(&format!("ocr_images: {e:#}"));
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": -32603,
+                        "message": format!("{e:#}")
+                    }
+                })
+            }
+        }
     }
 
     fn get_index_stats(&self, id: Value) -> Value {
@@ -557,10 +799,7 @@ impl NativeMessagingHost {
         let bookmark_count = self.count_unique_bookmarks(&searcher, indexer.schema());
 
         // Calculate index size
-        let index_path = dirs::data_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("mcp-bookmark")
-            .join(&self.index_name);
+        let index_path = mcp_bookmark::config::data_dir().join(&self.index_name);
         let index_size_bytes = Self::calculate_dir_size(&index_path).unwrap_or(0);
 
         json!({
@@ -610,9 +849,7 @@ impl NativeMessagingHost {
     }
 
     fn list_indexes(&self, id: Value) -> Value {
-        let base_path = dirs::data_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("mcp-bookmark");
+        let base_path = mcp_bookmark::config::data_dir();
 
         let mut indexes = Vec::new();
 
@@ -631,7 +868,7 @@ impl NativeMessagingHost {
                             // Count documents (simplified - just check if index can be opened)
                             let doc_count = if let Ok(index) = Index::open_in_dir(&path) {
                                 // Register Lindera tokenizer for the opened index
-                                let _ = Self::register_lindera_tokenizer(&index);
+                                let _ = Self::register_lindera_tokenizer(&index, &path);
 
                                 index
                                     .reader()
@@ -682,6 +919,13 @@ impl NativeMessagingHost {
 fn main() -> io::Result<()> {
     log_to_file("Native messaging host started");
 
+    // Must agree with the server on where indexes live
+    let settings = mcp_bookmark::config::Settings::load().unwrap_or_else(|e| {
+        log_to_file(&format!("Ignoring config: {e:#}"));
+        mcp_bookmark::config::Settings::default()
+    });
+    mcp_bookmark::config::init(settings);
+
     let mut host = NativeMessagingHost::new();
 
     loop {
@@ -1,6 +1,8 @@
 use crate::config::Config;
+use crate::search::BookmarkEntry;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +80,8 @@ impl BookmarkNode {
                     date_added: self.date_added.clone(),
                     date_modified: self.date_modified.clone(),
                     folder_path: self.folder_path.clone(),
+                    tags: vec![],
+                    profile: None,
                 });
             }
         }
@@ -126,6 +130,74 @@ impl BookmarkNode {
     }
 }
 
+impl BookmarkNode {
+    /// Create an empty folder node
+    fn new_folder(name: &str) -> Self {
+        Self {
+            children: Some(Vec::new()),
+            date_added: None,
+            date_last_used: None,
+            date_modified: None,
+            guid: String::new(),
+            id: String::new(),
+            name: name.to_string(),
+            node_type: "folder".to_string(),
+            url: None,
+            folder_path: vec![],
+        }
+    }
+
+    /// Get the child folder with the given name, creating it if missing
+    fn child_folder_mut(&mut self, name: &str) -> &mut BookmarkNode {
+        let children = self.children.get_or_insert_with(Vec::new);
+        let pos = match children
+            .iter()
+            .position(|c| c.is_folder() && c.name == name)
+        {
+            Some(pos) => pos,
+            None => {
+                children.push(Self::new_folder(name));
+                children.len() - 1
+            }
+        };
+        &mut children[pos]
+    }
+
+    /// Rebuild a folder tree from bookmarks stored in the index
+    ///
+    /// The index only keeps the "/"-joined folder path of each bookmark, so
+    /// folders are reconstructed from those paths under a single root folder.
+    pub fn from_indexed_entries(entries: &[BookmarkEntry]) -> Self {
+        let mut root = Self::new_folder("Bookmarks");
+
+        for entry in entries {
+            let mut folder = &mut root;
+            for name in entry.folder_path.split('/').filter(|n| !n.is_empty()) {
+                folder = folder.child_folder_mut(name);
+            }
+
+            folder
+                .children
+                .get_or_insert_with(Vec::new)
+                .push(BookmarkNode {
+                    children: None,
+                    date_added: None,
+                    date_last_used: None,
+                    date_modified: None,
+                    guid: String::new(),
+                    id: entry.id.clone(),
+                    name: entry.title.clone(),
+                    node_type: "url".to_string(),
+                    url: Some(entry.url.clone()),
+                    folder_path: vec![],
+                });
+        }
+
+        root.set_folder_paths(vec![]);
+        root
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlatBookmark {
     pub id: String,
@@ -134,6 +206,87 @@ pub struct FlatBookmark {
     pub date_added: Option<String>,
     pub date_modified: Option<String>,
     pub folder_path: Vec<String>,
+    /// Tags from bookmark managers that support them (Firefox, Pocket, ...)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Browser profile the bookmark was read from, when indexing several profiles
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// Caps on the number of bookmarks put into an index
+///
+/// Lets an enormous folder be indexed partially on purpose instead of
+/// crowding out everything else. Bookmarks are kept in reading order, so the
+/// first ones of a folder are indexed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookmarkLimits {
+    /// Bookmarks in total (0 is unlimited)
+    pub max_bookmarks: usize,
+    /// Bookmarks directly in any one folder (0 is unlimited)
+    pub max_per_folder: usize,
+    /// Bookmarks in a folder and its subfolders, by path such as `Bookmarks bar/Reading`
+    pub folder_limits: BTreeMap<String, usize>,
+}
+
+impl BookmarkLimits {
+    /// The limits in the `[index]` settings
+    pub fn from_settings() -> Self {
+        let index = &crate::config::settings().index;
+        Self {
+            max_bookmarks: index.max_bookmarks,
+            max_per_folder: index.max_per_folder,
+            folder_limits: index.folder_limits.clone(),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bookmarks == 0 && self.max_per_folder == 0 && self.folder_limits.is_empty()
+    }
+
+    /// The bookmarks within the limits, in their original order
+    pub fn select<'a>(&self, bookmarks: &'a [FlatBookmark]) -> Vec<&'a FlatBookmark> {
+        let folder_limits: Vec<(&str, usize)> = self
+            .folder_limits
+            .iter()
+            .map(|(folder, limit)| (folder.trim_matches('/'), *limit))
+            .collect();
+        let mut per_folder: HashMap<&[String], usize> = HashMap::new();
+        let mut per_limit = vec![0; folder_limits.len()];
+
+        let mut selected = Vec::new();
+        for bookmark in bookmarks {
+            if self.max_bookmarks > 0 && selected.len() >= self.max_bookmarks {
+                break;
+            }
+            let folder = bookmark.folder_path.as_slice();
+            let count = per_folder.get(folder).copied().unwrap_or(0);
+            if self.max_per_folder > 0 && count >= self.max_per_folder {
+                continue;
+            }
+            let path = folder.join("/");
+            let matching: Vec<usize> = folder_limits
+                .iter()
+                .enumerate()
+                .filter(|(_, (limit_folder, _))| {
+                    path == *limit_folder
+                        || path
+                            .strip_prefix(limit_folder)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .map(|(i, _)| i)
+                .collect();
+            if matching.iter().any(|&i| per_limit[i] >= folder_limits[i].1) {
+                continue;
+            }
+            for i in matching {
+                per_limit[i] += 1;
+            }
+            per_folder.insert(folder, count + 1);
+            selected.push(bookmark);
+        }
+        selected
+    }
 }
 
 /// Chrome bookmark reader
@@ -166,7 +319,7 @@ impl BookmarkReader {
         )
     }
 
-    #[cfg(test)]
+    /// Create a reader for a specific Chrome-format Bookmarks file
     pub fn new_with_path(bookmarks_path: PathBuf, config: Config) -> Self {
         Self {
             bookmarks_path,
@@ -174,6 +327,26 @@ impl BookmarkReader {
         }
     }
 
+    /// Parse the Bookmarks file and flatten every root folder
+    ///
+    /// Works for any Chromium-based browser (Chrome, Edge, Brave, Vivaldi, ...).
+    pub fn read_bookmarks_file(&self) -> Result<Vec<FlatBookmark>> {
+        let content = std::fs::read_to_string(&self.bookmarks_path)?;
+        let mut bookmarks: ChromeBookmarks = serde_json::from_str(&content)?;
+
+        let roots = &mut bookmarks.roots;
+        let mut result = Vec::new();
+        for root in [&mut roots.bookmark_bar, &mut roots.other, &mut roots.synced] {
+            root.set_folder_paths(vec![]);
+            result.extend(root.flatten());
+        }
+
+        if self.config.max_bookmarks > 0 {
+            result.truncate(self.config.max_bookmarks);
+        }
+        Ok(result)
+    }
+
     #[allow(dead_code)]
     pub fn read(&self) -> Result<ChromeBookmarks> {
         // This should not be called when using INDEX_NAME
@@ -292,6 +465,102 @@ mod tests {
         assert_eq!(flat[1].folder_path, vec!["Bookmarks Bar", "Folder 1"]);
     }
 
+    #[test]
+    fn test_read_bookmarks_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("Bookmarks");
+        std::fs::write(
+            &path,
+            serde_json::to_string(&create_test_bookmarks()).unwrap(),
+        )
+        .unwrap();
+
+        let reader = BookmarkReader::new_with_path(path, Config::default());
+        let flat = reader.read_bookmarks_file().unwrap();
+        assert_eq!(flat.len(), 2);
+        assert_eq!(flat[1].folder_path, vec!["Bookmarks Bar", "Folder 1"]);
+    }
+
+    #[test]
+    fn test_bookmark_limits() {
+        let bookmark = |id: usize, folder: &[&str]| FlatBookmark {
+            id: id.to_string(),
+            name: format!("Bookmark {id}"),
+            url: format!("https://example.com/{id}"),
+            date_added: None,
+            date_modified: None,
+            folder_path: folder.iter().map(|f| f.to_string()).collect(),
+            tags: vec![],
+            profile: None,
+        };
+        let mut bookmarks: Vec<_> = (0..5).map(|i| bookmark(i, &["Bar", "Reading"])).collect();
+        bookmarks.extend((5..8).map(|i| bookmark(i, &["Bar", "Reading", "Later"])));
+        bookmarks.extend((8..10).map(|i| bookmark(i, &["Bar"])));
+        let ids = |limits: &BookmarkLimits| {
+            limits
+                .select(&bookmarks)
+                .iter()
+                .map(|b| b.id.parse::<usize>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut limits = BookmarkLimits::default();
+        assert!(limits.is_unlimited());
+        assert_eq!(ids(&limits).len(), 10);
+
+        limits.max_per_folder = 2;
+        assert_eq!(ids(&limits), [0, 1, 5, 6, 8, 9]);
+
+        // A folder limit covers subfolders; other folders are unaffected
+        limits.max_per_folder = 0;
+        limits.folder_limits.insert("Bar/Reading/".to_string(), 6);
+        assert_eq!(ids(&limits), [0, 1, 2, 3, 4, 5, 8, 9]);
+
+        limits.max_bookmarks = 3;
+        assert_eq!(ids(&limits), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_from_indexed_entries() {
+        let entry = |id: &str, folder: &str| BookmarkEntry {
+            id: id.to_string(),
+            url: format!("https://example.com/{id}"),
+            title: format!("Bookmark {id}"),
+            folder_path: folder.to_string(),
+            content_hash: String::new(),
+            simhash: 0,
+            date_added: 0,
+            date_modified: 0,
+            tags: vec![],
+        };
+        let entries = vec![
+            entry("1", "Bookmarks Bar/Tech"),
+            entry("2", "Bookmarks Bar/Tech/Rust"),
+            entry("3", "Bookmarks Bar"),
+            entry("4", ""),
+        ];
+
+        let tree = BookmarkNode::from_indexed_entries(&entries);
+        let flat = tree.flatten();
+        assert_eq!(flat.len(), 4);
+
+        let rust = tree
+            .find_folder(&[
+                "Bookmarks Bar".to_string(),
+                "Tech".to_string(),
+                "Rust".to_string(),
+            ])
+            .unwrap();
+        assert_eq!(rust.children.as_ref().unwrap()[0].id, "2");
+
+        let find = |id: &str| flat.iter().find(|b| b.id == id).unwrap();
+        assert_eq!(
+            find("1").folder_path,
+            vec!["Bookmarks", "Bookmarks Bar", "Tech"]
+        );
+        assert_eq!(find("4").folder_path, vec!["Bookmarks"]);
+    }
+
     #[test]
     fn test_find_folder() {
         let mut bookmarks = create_test_bookmarks();
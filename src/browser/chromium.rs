@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::bookmark::{BookmarkReader, FlatBookmark};
+use crate::config::Config;
+
+/// Chromium-based browsers sharing Chrome's profile layout and Bookmarks format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChromiumBrowser {
+    Chrome,
+    Edge,
+    Brave,
+    Vivaldi,
+    Chromium,
+}
+
+/// A browser profile that has a Bookmarks file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChromiumProfile {
+    /// Directory name, e.g. "Default" or "Profile 1"
+    pub dir_name: String,
+    /// Name shown in the browser's profile menu
+    pub display_name: String,
+    pub bookmarks_path: PathBuf,
+}
+
+impl ChromiumBrowser {
+    pub const ALL: [Self; 5] = [
+        Self::Chrome,
+        Self::Edge,
+        Self::Brave,
+        Self::Vivaldi,
+        Self::Chromium,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Chrome => "chrome",
+            Self::Edge => "edge",
+            Self::Brave => "brave",
+            Self::Vivaldi => "vivaldi",
+            Self::Chromium => "chromium",
+        }
+    }
+
+    /// Path components of the user data directory under the platform base directory
+    fn vendor_path(&self) -> &'static [&'static str] {
+        let windows = cfg!(target_os = "windows");
+        let macos = cfg!(target_os = "macos");
+        match self {
+            Self::Chrome if windows => &["Google", "Chrome", "User Data"],
+            Self::Chrome if macos => &["Google", "Chrome"],
+            Self::Chrome => &["google-chrome"],
+            Self::Edge if windows => &["Microsoft", "Edge", "User Data"],
+            Self::Edge if macos => &["Microsoft Edge"],
+            Self::Edge => &["microsoft-edge"],
+            Self::Brave if windows => &["BraveSoftware", "Brave-Browser", "User Data"],
+            Self::Brave => &["BraveSoftware", "Brave-Browser"],
+            Self::Vivaldi if windows => &["Vivaldi", "User Data"],
+            Self::Vivaldi if macos => &["Vivaldi"],
+            Self::Vivaldi => &["vivaldi"],
+            Self::Chromium if windows => &["Chromium", "User Data"],
+            Self::Chromium if macos => &["Chromium"],
+            Self::Chromium => &["chromium"],
+        }
+    }
+
+    /// The browser's user data directory for the current platform
+    pub fn user_data_dir(&self) -> Option<PathBuf> {
+        let base = if cfg!(target_os = "windows") {
+            dirs::data_local_dir()?
+        } else {
+            // ~/Library/Application Support on macOS, ~/.config on Linux
+            dirs::config_dir()?
+        };
+        Some(
+            self.vendor_path()
+                .iter()
+                .fold(base, |path, part| path.join(part)),
+        )
+    }
+
+    /// List profiles that have a Bookmarks file
+    pub fn list_profiles(&self) -> Vec<ChromiumProfile> {
+        match self.user_data_dir() {
+            Some(dir) => list_profiles_in(&dir),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// List profiles in a user data directory, "Default" first
+pub fn list_profiles_in(user_data_dir: &Path) -> Vec<ChromiumProfile> {
+    // Display names live in "Local State" under profile.info_cache
+    let local_state: serde_json::Value = std::fs::read_to_string(user_data_dir.join("Local State"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let Ok(entries) = std::fs::read_dir(user_data_dir) else {
+        return Vec::new();
+    };
+
+    let mut profiles: Vec<ChromiumProfile> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let bookmarks_path = entry.path().join("Bookmarks");
+            if !bookmarks_path.is_file() {
+                return None;
+            }
+            let dir_name = entry.file_name().to_str()?.to_string();
+            let display_name = local_state["profile"]["info_cache"][&dir_name]["name"]
+                .as_str()
+                .unwrap_or(&dir_name)
+                .to_string();
+            Some(ChromiumProfile {
+                dir_name,
+                display_name,
+                bookmarks_path,
+            })
+        })
+        .collect();
+
+    profiles.sort_by(|a, b| {
+        (a.dir_name != "Default", &a.dir_name).cmp(&(b.dir_name != "Default", &b.dir_name))
+    });
+    profiles
+}
+
+/// Pick a profile by directory or display name (case-insensitive), defaulting to the first
+pub fn find_profile<'a>(
+    profiles: &'a [ChromiumProfile],
+    name: Option<&str>,
+) -> Option<&'a ChromiumProfile> {
+    match name {
+        Some(name) => profiles.iter().find(|p| {
+            p.dir_name.eq_ignore_ascii_case(name) || p.display_name.eq_ignore_ascii_case(name)
+        }),
+        None => profiles.first(),
+    }
+}
+
+/// Read and flatten a Chrome-format Bookmarks file
+pub fn read_bookmarks_file(path: &Path) -> Result<Vec<FlatBookmark>> {
+    BookmarkReader::new_with_path(path.to_path_buf(), Config::default())
+        .read_bookmarks_file()
+        .with_context(|| format!("Failed to read bookmarks from {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_profile(dir: &Path, name: &str) {
+        let profile_dir = dir.join(name);
+        std::fs::create_dir_all(&profile_dir).unwrap();
+        std::fs::write(profile_dir.join("Bookmarks"), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_list_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        create_profile(temp_dir.path(), "Profile 1");
+        create_profile(temp_dir.path(), "Default");
+        std::fs::create_dir_all(temp_dir.path().join("System Profile")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("Local State"),
+            r#"{"profile":{"info_cache":{"Profile 1":{"name":"Work"}}}}"#,
+        )
+        .unwrap();
+
+        let profiles = list_profiles_in(temp_dir.path());
+        let names: Vec<_> = profiles.iter().map(|p| p.dir_name.as_str()).collect();
+        assert_eq!(names, vec!["Default", "Profile 1"]);
+        assert_eq!(profiles[0].display_name, "Default");
+        assert_eq!(profiles[1].display_name, "Work");
+
+        assert_eq!(
+            find_profile(&profiles, Some("work")).unwrap().dir_name,
+            "Profile 1"
+        );
+        assert_eq!(find_profile(&profiles, None).unwrap().dir_name, "Default");
+        assert!(find_profile(&profiles, Some("Missing")).is_none());
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::chrome_timestamp_from_unix_micros;
+use crate::bookmark::FlatBookmark;
+
+/// moz_bookmarks.type for a bookmark entry
+const TYPE_BOOKMARK: i64 = 1;
+
+/// GUID of the hidden root folder
+const ROOT_GUID: &str = "root________";
+
+/// GUID of the tags folder (tags are stored as folders and aren't bookmarks)
+const TAGS_GUID: &str = "tags________";
+
+/// Display names for Firefox's built-in root folders
+fn root_folder_name(guid: &str) -> Option<&'static str> {
+    match guid {
+        "menu________" => Some("Bookmarks Menu"),
+        "toolbar_____" => Some("Bookmarks Toolbar"),
+        "unfiled_____" => Some("Other Bookmarks"),
+        "mobile______" => Some("Mobile Bookmarks"),
+        _ => None,
+    }
+}
+
+/// Find places.sqlite of the most recently used Firefox profile
+pub fn default_places_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let candidates = [
+        home.join("Library/Application Support/Firefox/Profiles"),
+        home.join(".mozilla/firefox"),
+        dirs::config_dir()
+            .unwrap_or_default()
+            .join("Mozilla/Firefox/Profiles"),
+    ];
+
+    candidates
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path().join("places.sqlite"))
+        .filter(|path| path.exists())
+        .max_by_key(|path| path.metadata().and_then(|m| m.modified()).ok())
+}
+
+/// Read all bookmarks from a Firefox places.sqlite database
+///
+/// Firefox keeps the database locked while running, so it is copied to a
+/// temporary directory (together with its WAL) before being opened.
+pub fn read_places(places_path: &Path) -> Result<Vec<FlatBookmark>> {
+    let temp_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+    let copy_path = temp_dir.path().join("places.sqlite");
+    std::fs::copy(places_path, &copy_path)
+        .with_context(|| format!("Failed to copy {places_path:?}"))?;
+
+    let wal_path = places_path.with_extension("sqlite-wal");
+    if wal_path.exists() {
+        std::fs::copy(&wal_path, copy_path.with_extension("sqlite-wal")).ok();
+    }
+
+    let conn = Connection::open_with_flags(&copy_path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .context("Failed to open places.sqlite")?;
+    read_bookmarks_from(&conn)
+}
+
+/// Folder row from moz_bookmarks
+struct Folder {
+    parent: Option<i64>,
+    title: String,
+    guid: String,
+}
+
+fn read_bookmarks_from(conn: &Connection) -> Result<Vec<FlatBookmark>> {
+    let mut folders: HashMap<i64, Folder> = HashMap::new();
+    let mut stmt = conn
+        .prepare("SELECT id, parent, IFNULL(title, ''), guid FROM moz_bookmarks WHERE type = 2")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            Folder {
+                parent: row.get(1)?,
+                title: row.get(2)?,
+                guid: row.get(3)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (id, folder) = row?;
+        folders.insert(id, folder);
+    }
+
+    let tags = read_tags(conn)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT b.id, b.fk, b.parent, IFNULL(b.title, IFNULL(p.title, '')), p.url, b.dateAdded, b.lastModified
+         FROM moz_bookmarks b JOIN moz_places p ON b.fk = p.id
+         WHERE b.type = ?1
+         ORDER BY b.parent, b.position",
+    )?;
+    let rows = stmt.query_map([TYPE_BOOKMARK], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, Option<i64>>(5)?,
+            row.get::<_, Option<i64>>(6)?,
+        ))
+    })?;
+
+    let mut bookmarks = Vec::new();
+    for row in rows {
+        let (id, place, parent, title, url, date_added, last_modified) = row?;
+
+        let Some(folder_path) = folder_path(&folders, parent) else {
+            // Tag entries live under the tags folder
+            continue;
+        };
+        // Skip internal URLs such as place: queries
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            continue;
+        }
+
+        bookmarks.push(FlatBookmark {
+            id: id.to_string(),
+            name: title,
+            url,
+            date_added: date_added
+                .filter(|&t| t > 0)
+                .map(chrome_timestamp_from_unix_micros),
+            date_modified: last_modified
+                .filter(|&t| t > 0)
+                .map(chrome_timestamp_from_unix_micros),
+            folder_path,
+            tags: tags.get(&place).cloned().unwrap_or_default(),
+            profile: None,
+        });
+    }
+
+    debug!("Read {} bookmarks from places.sqlite", bookmarks.len());
+    Ok(bookmarks)
+}
+
+/// Tag names by place id
+///
+/// Each tag is a folder under the tags folder, holding one bookmark entry
+/// per tagged place.
+fn read_tags(conn: &Connection) -> Result<HashMap<i64, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.fk, IFNULL(t.title, '')
+         FROM moz_bookmarks b
+         JOIN moz_bookmarks t ON b.parent = t.id
+         JOIN moz_bookmarks r ON t.parent = r.id
+         WHERE b.type = ?1 AND r.guid = ?2
+         ORDER BY t.title",
+    )?;
+    let rows = stmt.query_map((TYPE_BOOKMARK, TAGS_GUID), |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (place, tag) = row?;
+        if !tag.is_empty() {
+            tags.entry(place).or_default().push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// Resolve the folder path of a folder id, or None if it is under the tags folder
+fn folder_path(folders: &HashMap<i64, Folder>, folder_id: i64) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    let mut current = Some(folder_id);
+
+    while let Some(id) = current {
+        let Some(folder) = folders.get(&id) else {
+            break;
+        };
+        if folder.guid == TAGS_GUID {
+            return None;
+        }
+        if folder.guid != ROOT_GUID {
+            let name = root_folder_name(&folder.guid).unwrap_or(&folder.title);
+            path.push(name.to_string());
+        }
+        current = folder.parent;
+    }
+
+    path.reverse();
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_places(conn: &Connection) {
+        conn.execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT);
+             CREATE TABLE moz_bookmarks (
+                 id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, parent INTEGER,
+                 position INTEGER, title TEXT, dateAdded INTEGER, lastModified INTEGER, guid TEXT
+             );
+             INSERT INTO moz_bookmarks VALUES (1, 2, NULL, 0, 0, '', 0, 0, 'root________');
+             INSERT INTO moz_bookmarks VALUES (2, 2, NULL, 1, 0, 'toolbar', 0, 0, 'toolbar_____');
+             INSERT INTO moz_bookmarks VALUES (3, 2, NULL, 1, 1, 'tags', 0, 0, 'tags________');
+             INSERT INTO moz_bookmarks VALUES (4, 2, NULL, 2, 0, 'Rust', 0, 0, 'folder000001');
+             INSERT INTO moz_bookmarks VALUES (5, 2, NULL, 3, 0, 'lang', 0, 0, 'folder000002');
+             INSERT INTO moz_places VALUES (1, 'https://www.rust-lang.org/', 'Rust');
+             INSERT INTO moz_places VALUES (2, 'place:sort=8', 'Recent');
+             INSERT INTO moz_bookmarks VALUES (10, 1, 1, 4, 0, 'Rust Home', 1700000000000000, 1700000000000000, 'bookmark0001');
+             INSERT INTO moz_bookmarks VALUES (11, 1, 1, 5, 0, NULL, 0, 0, 'bookmark0002');
+             INSERT INTO moz_bookmarks VALUES (12, 1, 2, 2, 1, 'Recent', 0, 0, 'bookmark0003');",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_read_bookmarks() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_places(&conn);
+
+        let bookmarks = read_bookmarks_from(&conn).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+
+        let bookmark = &bookmarks[0];
+        assert_eq!(bookmark.id, "10");
+        assert_eq!(bookmark.name, "Rust Home");
+        assert_eq!(bookmark.url, "https://www.rust-lang.org/");
+        assert_eq!(bookmark.folder_path, vec!["Bookmarks Toolbar", "Rust"]);
+        assert_eq!(bookmark.tags, vec!["lang"]);
+        assert_eq!(bookmark.date_added.as_deref(), Some("13344473600000000"));
+    }
+}
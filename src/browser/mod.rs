@@ -0,0 +1,241 @@
+// Bookmark readers for other browsers. Each produces `FlatBookmark`s so the
+// result goes through the same indexing pipeline as Chrome bookmarks.
+pub mod chromium;
+pub mod firefox;
+pub mod pinboard;
+pub mod raindrop;
+pub mod read_later;
+pub mod safari;
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::bookmark::FlatBookmark;
+use chromium::ChromiumBrowser;
+use read_later::ReadLaterService;
+
+/// Microseconds between 1601-01-01 (Chrome's epoch) and 1970-01-01
+const CHROME_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+/// `--profile` value that merges all profiles of a Chromium-based browser
+pub const ALL_PROFILES: &str = "all";
+
+/// Browser or read-later service to import bookmarks from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Browser {
+    Chromium(ChromiumBrowser),
+    Firefox,
+    Safari,
+    /// Export file of a read-later service (requires --bookmarks-file)
+    ReadLater(ReadLaterService),
+    /// Raindrop.io CSV/JSON export (requires --bookmarks-file)
+    Raindrop,
+    /// Pinboard JSON export, or the API when no file is given
+    Pinboard,
+}
+
+impl Browser {
+    /// Parse a browser name as given on the command line
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "chrome" => Some(Self::Chromium(ChromiumBrowser::Chrome)),
+            "edge" => Some(Self::Chromium(ChromiumBrowser::Edge)),
+            "brave" => Some(Self::Chromium(ChromiumBrowser::Brave)),
+            "vivaldi" => Some(Self::Chromium(ChromiumBrowser::Vivaldi)),
+            "chromium" => Some(Self::Chromium(ChromiumBrowser::Chromium)),
+            "firefox" => Some(Self::Firefox),
+            "safari" => Some(Self::Safari),
+            "pocket" => Some(Self::ReadLater(ReadLaterService::Pocket)),
+            "instapaper" => Some(Self::ReadLater(ReadLaterService::Instapaper)),
+            "raindrop" => Some(Self::Raindrop),
+            "pinboard" => Some(Self::Pinboard),
+            _ => None,
+        }
+    }
+
+    /// Whether bookmarks come from a locally installed browser rather than an export or API
+    pub fn is_local(&self) -> bool {
+        matches!(self, Self::Chromium(_) | Self::Firefox | Self::Safari)
+    }
+
+    /// Names accepted by `parse`
+    pub fn supported_names() -> &'static [&'static str] {
+        &[
+            "chrome",
+            "edge",
+            "brave",
+            "vivaldi",
+            "chromium",
+            "firefox",
+            "safari",
+            "pocket",
+            "instapaper",
+            "raindrop",
+            "pinboard",
+        ]
+    }
+
+    /// Read bookmarks from the given file, or from the browser's default location
+    ///
+    /// `profile` selects a profile of Chromium-based browsers by directory or
+    /// display name, or `ALL_PROFILES` to merge every profile; other browsers
+    /// ignore it. Chromium bookmarks are tagged with the profile's display name.
+    pub fn read_bookmarks(
+        &self,
+        path: Option<&Path>,
+        profile: Option<&str>,
+    ) -> Result<Vec<FlatBookmark>> {
+        match self {
+            Self::Chromium(browser) => {
+                if let Some(path) = path {
+                    let mut bookmarks = chromium::read_bookmarks_file(path)?;
+                    tag_profile(&mut bookmarks, profile);
+                    return Ok(bookmarks);
+                }
+
+                let profiles = browser.list_profiles();
+                if profile.is_some_and(|p| p.eq_ignore_ascii_case(ALL_PROFILES)) {
+                    let mut bookmarks = Vec::new();
+                    for selected in &profiles {
+                        let mut profile_bookmarks =
+                            chromium::read_bookmarks_file(&selected.bookmarks_path)?;
+                        // Bookmark ids are only unique within a profile
+                        for bookmark in &mut profile_bookmarks {
+                            bookmark.id = format!("{}:{}", selected.dir_name, bookmark.id);
+                        }
+                        tag_profile(&mut profile_bookmarks, Some(&selected.display_name));
+                        bookmarks.extend(profile_bookmarks);
+                    }
+                    return Ok(bookmarks);
+                }
+
+                let selected = chromium::find_profile(&profiles, profile).ok_or_else(|| {
+                    match profile {
+                        Some(name) => anyhow::anyhow!("Profile not found: {name}"),
+                        None => anyhow::anyhow!(
+                            "No {browser:?} profile with bookmarks found. Use --bookmarks-file to specify the Bookmarks file"
+                        ),
+                    }
+                })?;
+                let mut bookmarks = chromium::read_bookmarks_file(&selected.bookmarks_path)?;
+                tag_profile(&mut bookmarks, Some(&selected.display_name));
+                Ok(bookmarks)
+            }
+            Self::Firefox => {
+                let path = match path {
+                    Some(path) => path.to_path_buf(),
+                    None => firefox::default_places_path().ok_or_else(|| {
+                        anyhow::anyhow!("Firefox profile not found. Use --bookmarks-file to specify places.sqlite")
+                    })?,
+                };
+                firefox::read_places(&path)
+            }
+            Self::Safari => {
+                let path = match path {
+                    Some(path) => path.to_path_buf(),
+                    None => safari::default_bookmarks_path().ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Safari bookmarks not found. Use --bookmarks-file to specify Bookmarks.plist"
+                        )
+                    })?,
+                };
+                safari::read_bookmarks_plist(&path)
+            }
+            Self::ReadLater(service) => {
+                let path = path.ok_or_else(|| {
+                    anyhow::anyhow!("{service:?} has no local data. Use --bookmarks-file to specify the export file")
+                })?;
+                read_later::read_export(*service, path)
+            }
+            Self::Raindrop => {
+                let path = path.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Raindrop has no local data. Use --bookmarks-file to specify the CSV/JSON export"
+                    )
+                })?;
+                raindrop::read_export(path)
+            }
+            // Fetching from the API is async; see `pinboard::fetch_posts`
+            Self::Pinboard => {
+                let path = path.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Use --bookmarks-file to specify a Pinboard JSON export, or set {}",
+                        pinboard::TOKEN_ENV
+                    )
+                })?;
+                pinboard::read_export(path)
+            }
+        }
+    }
+}
+
+fn tag_profile(bookmarks: &mut [FlatBookmark], profile: Option<&str>) {
+    if let Some(profile) = profile {
+        for bookmark in bookmarks {
+            bookmark.profile = Some(profile.to_string());
+        }
+    }
+}
+
+/// Convert a Unix timestamp in microseconds to Chrome's bookmark date format
+pub fn chrome_timestamp_from_unix_micros(micros: i64) -> String {
+    (micros + CHROME_EPOCH_OFFSET_MICROS).to_string()
+}
+
+/// Convert an RFC 3339 date (e.g. "2024-01-31T12:00:00Z") to Chrome's bookmark date format
+pub fn chrome_timestamp_from_rfc3339(date: &str) -> Option<String> {
+    let datetime = chrono::DateTime::parse_from_rfc3339(date.trim()).ok()?;
+    Some(chrome_timestamp_from_unix_micros(
+        datetime.timestamp_micros(),
+    ))
+}
+
+/// Split a tag list: "a,b", "a|b" or ["a","b"]
+pub(crate) fn split_tags(tags: &str) -> Vec<String> {
+    tags.trim_matches(|c| c == '[' || c == ']')
+        .split([',', '|'])
+        .map(|tag| tag.trim().trim_matches('"').trim())
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_browser() {
+        assert_eq!(Browser::parse("firefox"), Some(Browser::Firefox));
+        assert_eq!(Browser::parse("Firefox"), Some(Browser::Firefox));
+        assert_eq!(Browser::parse("safari"), Some(Browser::Safari));
+        assert_eq!(
+            Browser::parse("edge"),
+            Some(Browser::Chromium(ChromiumBrowser::Edge))
+        );
+        assert_eq!(
+            Browser::parse("pocket"),
+            Some(Browser::ReadLater(ReadLaterService::Pocket))
+        );
+        assert_eq!(Browser::parse("netscape"), None);
+        assert!(Browser::parse("brave").unwrap().is_local());
+        assert!(!Browser::parse("raindrop").unwrap().is_local());
+    }
+
+    #[test]
+    fn test_chrome_timestamp_from_rfc3339() {
+        assert_eq!(
+            chrome_timestamp_from_rfc3339("2023-11-14T22:13:20Z").as_deref(),
+            Some("13344473600000000")
+        );
+        assert_eq!(chrome_timestamp_from_rfc3339("yesterday"), None);
+    }
+
+    #[test]
+    fn test_split_tags() {
+        assert_eq!(split_tags("rust, lang"), vec!["rust", "lang"]);
+        assert_eq!(split_tags("news|tech"), vec!["news", "tech"]);
+        assert_eq!(split_tags(r#"["a","b"]"#), vec!["a", "b"]);
+        assert!(split_tags("").is_empty());
+    }
+}
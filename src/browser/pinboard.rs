@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+use tracing::debug;
+
+use super::chrome_timestamp_from_rfc3339;
+use crate::bookmark::FlatBookmark;
+use crate::search::common::url_bookmark_id;
+
+/// Top-level folder for imported Pinboard bookmarks
+const ROOT_FOLDER: &str = "Pinboard";
+
+/// Environment variable holding the API token ("username:TOKEN")
+pub const TOKEN_ENV: &str = "PINBOARD_TOKEN";
+
+const POSTS_ALL_URL: &str = "https://api.pinboard.in/v1/posts/all";
+
+/// posts/all can take a while for large accounts
+const API_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Post as returned by posts/all and in Pinboard's JSON export
+#[derive(Debug, Deserialize)]
+struct PinboardPost {
+    href: String,
+    #[serde(default)]
+    description: String,
+    hash: Option<String>,
+    time: Option<String>,
+    /// Space-separated
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    toread: String,
+}
+
+/// Fetch all posts from the Pinboard API as JSON
+pub async fn fetch_posts(token: &str) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(API_TIMEOUT)
+        .user_agent(concat!("mcp-bookmark/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let response = client
+        .get(POSTS_ALL_URL)
+        .query(&[("auth_token", token), ("format", "json")])
+        .send()
+        .await
+        .context("Failed to reach the Pinboard API")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("Pinboard API returned {status} (check {TOKEN_ENV})");
+    }
+    response
+        .text()
+        .await
+        .context("Failed to read Pinboard API response")
+}
+
+/// Read a Pinboard JSON export
+pub fn read_export(path: &Path) -> Result<Vec<FlatBookmark>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+    parse_posts(&content)
+}
+
+/// Convert posts/all JSON into bookmarks
+///
+/// Pinboard has no folders; unread posts go into "Pinboard/Unread".
+pub fn parse_posts(json: &str) -> Result<Vec<FlatBookmark>> {
+    let posts: Vec<PinboardPost> = serde_json::from_str(json).context("Invalid Pinboard JSON")?;
+
+    let bookmarks: Vec<FlatBookmark> = posts
+        .into_iter()
+        .map(|post| {
+            let mut folder_path = vec![ROOT_FOLDER.to_string()];
+            if post.toread == "yes" {
+                folder_path.push("Unread".to_string());
+            }
+
+            FlatBookmark {
+                id: post.hash.unwrap_or_else(|| url_bookmark_id(&post.href)),
+                name: if post.description.is_empty() {
+                    post.href.clone()
+                } else {
+                    post.description
+                },
+                url: post.href,
+                date_added: post.time.as_deref().and_then(chrome_timestamp_from_rfc3339),
+                date_modified: None,
+                folder_path,
+                tags: post.tags.split_whitespace().map(str::to_string).collect(),
+                profile: None,
+            }
+        })
+        .collect();
+
+    debug!("Read {} posts from Pinboard", bookmarks.len());
+    Ok(bookmarks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_posts() {
+        let json = r#"[
+            {"href": "https://www.rust-lang.org/", "description": "Rust", "extended": "",
+             "hash": "abc123", "time": "2023-11-14T22:13:20Z", "shared": "yes",
+             "toread": "no", "tags": "rust lang"},
+            {"href": "https://example.com/", "description": "", "time": "2023-11-14T22:13:20Z",
+             "toread": "yes", "tags": ""}
+        ]"#;
+
+        let bookmarks = parse_posts(json).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].id, "abc123");
+        assert_eq!(bookmarks[0].tags, vec!["rust", "lang"]);
+        assert_eq!(bookmarks[0].folder_path, vec!["Pinboard"]);
+        assert_eq!(
+            bookmarks[0].date_added.as_deref(),
+            Some("13344473600000000")
+        );
+        assert_eq!(bookmarks[1].id, "https://example.com/");
+        assert_eq!(bookmarks[1].name, "https://example.com/");
+        assert_eq!(bookmarks[1].folder_path, vec!["Pinboard", "Unread"]);
+        assert!(bookmarks[1].tags.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use tracing::debug;
+
+use super::{chrome_timestamp_from_rfc3339, split_tags};
+use crate::bookmark::FlatBookmark;
+use crate::search::common::url_bookmark_id;
+
+/// Top-level folder for imported Raindrop.io bookmarks
+const ROOT_FOLDER: &str = "Raindrop";
+
+/// Raindrop.io item as returned by its API (`{"items": [...]}`)
+#[derive(Debug, Deserialize)]
+struct RaindropItem {
+    #[serde(rename = "_id")]
+    id: Option<serde_json::Value>,
+    #[serde(default)]
+    title: String,
+    link: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    created: Option<String>,
+    #[serde(rename = "lastUpdate")]
+    last_update: Option<String>,
+    collection: Option<RaindropCollection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RaindropCollection {
+    #[serde(rename = "$id")]
+    id: Option<i64>,
+    title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RaindropJson {
+    Items { items: Vec<RaindropItem> },
+    List(Vec<RaindropItem>),
+}
+
+/// Read a Raindrop.io export (JSON or CSV, chosen by file extension)
+pub fn read_export(path: &Path) -> Result<Vec<FlatBookmark>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+
+    let is_json = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let bookmarks = if is_json {
+        parse_json(&content)?
+    } else {
+        parse_csv(&content)?
+    };
+
+    debug!("Read {} items from Raindrop.io export", bookmarks.len());
+    Ok(bookmarks)
+}
+
+fn parse_json(content: &str) -> Result<Vec<FlatBookmark>> {
+    let items = match serde_json::from_str(content).context("Invalid Raindrop.io JSON")? {
+        RaindropJson::Items { items } => items,
+        RaindropJson::List(items) => items,
+    };
+
+    Ok(items
+        .into_iter()
+        .map(|item| {
+            // Items only carry the collection title when exported with it
+            let collection = match &item.collection {
+                Some(RaindropCollection { title: Some(t), .. }) if !t.is_empty() => t.clone(),
+                Some(RaindropCollection { id: Some(id), .. }) if *id > 0 => id.to_string(),
+                _ => "Unsorted".to_string(),
+            };
+            let id = match item.id {
+                Some(serde_json::Value::String(id)) => id,
+                Some(serde_json::Value::Number(id)) => id.to_string(),
+                _ => item.link.clone(),
+            };
+
+            FlatBookmark {
+                id,
+                name: if item.title.is_empty() {
+                    item.link.clone()
+                } else {
+                    item.title
+                },
+                url: item.link,
+                date_added: item
+                    .created
+                    .as_deref()
+                    .and_then(chrome_timestamp_from_rfc3339),
+                date_modified: item
+                    .last_update
+                    .as_deref()
+                    .and_then(chrome_timestamp_from_rfc3339),
+                folder_path: folder_path(&collection),
+                tags: item.tags,
+                profile: None,
+            }
+        })
+        .collect())
+}
+
+/// Parse a CSV export: id,title,note,excerpt,url,folder,tags,created,...
+fn parse_csv(content: &str) -> Result<Vec<FlatBookmark>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV header")?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+
+    let url_column = column("url").context("CSV export has no url column")?;
+    let (id_column, title_column, folder_column, tags_column, created_column) = (
+        column("id"),
+        column("title"),
+        column("folder"),
+        column("tags"),
+        column("created"),
+    );
+
+    let mut bookmarks = Vec::new();
+    for record in reader.records() {
+        let record = record.context("Failed to read CSV record")?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or("").trim();
+
+        let url = field(Some(url_column));
+        if url.is_empty() {
+            continue;
+        }
+        let id = field(id_column);
+        let title = field(title_column);
+        let folder = field(folder_column);
+
+        bookmarks.push(FlatBookmark {
+            id: if id.is_empty() {
+                url_bookmark_id(url)
+            } else {
+                id.to_string()
+            },
+            name: if title.is_empty() { url } else { title }.to_string(),
+            url: url.to_string(),
+            date_added: chrome_timestamp_from_rfc3339(field(created_column)),
+            date_modified: None,
+            folder_path: folder_path(if folder.is_empty() {
+                "Unsorted"
+            } else {
+                folder
+            }),
+            tags: split_tags(field(tags_column)),
+            profile: None,
+        });
+    }
+
+    Ok(bookmarks)
+}
+
+/// Nested collections are exported as "Parent/Child"
+fn folder_path(collection: &str) -> Vec<String> {
+    std::iter::once(ROOT_FOLDER.to_string())
+        .chain(
+            collection
+                .split('/')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::to_string),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite\n\
+                   101,Rust,,,https://www.rust-lang.org/,Dev/Rust,\"rust, lang\",2023-11-14T22:13:20.000Z,,,false\n\
+                   102,,,,https://example.com/,,,,,,false\n";
+
+        let bookmarks = parse_csv(csv).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].id, "101");
+        assert_eq!(bookmarks[0].folder_path, vec!["Raindrop", "Dev", "Rust"]);
+        assert_eq!(bookmarks[0].tags, vec!["rust", "lang"]);
+        assert_eq!(
+            bookmarks[0].date_added.as_deref(),
+            Some("13344473600000000")
+        );
+        assert_eq!(bookmarks[1].name, "https://example.com/");
+        assert_eq!(bookmarks[1].folder_path, vec!["Raindrop", "Unsorted"]);
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = r#"{"items": [
+            {"_id": 1, "title": "Docs", "link": "https://docs.rs/", "tags": ["rust"],
+             "created": "2023-11-14T22:13:20Z", "collection": {"$id": 5, "title": "Reading"}},
+            {"_id": 2, "title": "Example", "link": "https://example.com/", "collection": {"$id": -1}}
+        ]}"#;
+
+        let bookmarks = parse_json(json).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].id, "1");
+        assert_eq!(bookmarks[0].folder_path, vec!["Raindrop", "Reading"]);
+        assert_eq!(bookmarks[0].tags, vec!["rust"]);
+        assert_eq!(bookmarks[1].folder_path, vec!["Raindrop", "Unsorted"]);
+        assert!(bookmarks[1].tags.is_empty());
+    }
+}
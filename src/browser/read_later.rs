@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+use tracing::debug;
+
+use super::{chrome_timestamp_from_unix_micros, split_tags};
+use crate::bookmark::FlatBookmark;
+use crate::search::common::url_bookmark_id;
+
+/// Section headings and links of an HTML export, in document order
+static HTML_ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<h1[^>]*>(.*?)</h1>|<a\s([^>]*)>(.*?)</a>").expect("valid regex")
+});
+
+static HTML_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)([a-z_]+)\s*=\s*"([^"]*)""#).expect("valid regex"));
+
+static HTML_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").expect("valid regex"));
+
+/// Read-later service an export file comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadLaterService {
+    Pocket,
+    Instapaper,
+}
+
+impl ReadLaterService {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Pocket => "Pocket",
+            Self::Instapaper => "Instapaper",
+        }
+    }
+
+    /// Folder for an export section or status, e.g. "Read Archive" or "archive"
+    fn folder_path(&self, section: &str) -> Vec<String> {
+        let folder = match section.trim().to_lowercase().as_str() {
+            "" | "unread" => "Unread".to_string(),
+            "archive" | "archived" | "read archive" => "Archive".to_string(),
+            // Instapaper's Starred and user-created folders
+            _ => section.trim().to_string(),
+        };
+        vec![self.name().to_string(), folder]
+    }
+}
+
+/// Read a Pocket or Instapaper export (HTML or CSV, chosen by file extension)
+pub fn read_export(service: ReadLaterService, path: &Path) -> Result<Vec<FlatBookmark>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+
+    let is_html = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+    let bookmarks = if is_html {
+        parse_html(service, &content)
+    } else {
+        parse_csv(service, &content)?
+    };
+
+    debug!(
+        "Read {} items from {} export",
+        bookmarks.len(),
+        service.name()
+    );
+    Ok(bookmarks)
+}
+
+/// Parse an HTML export where each <h1> starts a section (Unread, Read Archive, ...)
+fn parse_html(service: ReadLaterService, content: &str) -> Vec<FlatBookmark> {
+    let mut section = String::new();
+    let mut bookmarks = Vec::new();
+
+    for caps in HTML_ITEM.captures_iter(content) {
+        if let Some(heading) = caps.get(1) {
+            section = unescape_html(&strip_tags(heading.as_str()));
+            continue;
+        }
+
+        let attributes = caps.get(2).map_or("", |m| m.as_str());
+        let attribute = |name: &str| {
+            HTML_ATTRIBUTE
+                .captures_iter(attributes)
+                .find(|attr| attr[1].eq_ignore_ascii_case(name))
+                .map(|attr| unescape_html(&attr[2]))
+                .unwrap_or_default()
+        };
+
+        let url = attribute("href");
+        if url.is_empty() {
+            continue;
+        }
+        let title = unescape_html(&strip_tags(caps.get(3).map_or("", |m| m.as_str())));
+        bookmarks.push(to_bookmark(
+            service,
+            &url,
+            &title,
+            &attribute("time_added"),
+            &attribute("tags"),
+            &section,
+        ));
+    }
+
+    bookmarks
+}
+
+/// Parse a CSV export
+///
+/// Pocket: title,url,time_added,tags,status
+/// Instapaper: URL,Title,Selection,Folder,Timestamp[,Tags]
+fn parse_csv(service: ReadLaterService, content: &str) -> Result<Vec<FlatBookmark>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV header")?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+
+    let (time_column, section_column) = match service {
+        ReadLaterService::Pocket => (column("time_added"), column("status")),
+        ReadLaterService::Instapaper => (column("timestamp"), column("folder")),
+    };
+    let url_column = column("url").context("CSV export has no url column")?;
+    let title_column = column("title");
+    let tags_column = column("tags");
+
+    let mut bookmarks = Vec::new();
+    for record in reader.records() {
+        let record = record.context("Failed to read CSV record")?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or("").trim();
+
+        let url = field(Some(url_column));
+        if url.is_empty() {
+            continue;
+        }
+        bookmarks.push(to_bookmark(
+            service,
+            url,
+            field(title_column),
+            field(time_column),
+            field(tags_column),
+            field(section_column),
+        ));
+    }
+
+    Ok(bookmarks)
+}
+
+fn to_bookmark(
+    service: ReadLaterService,
+    url: &str,
+    title: &str,
+    time_added: &str,
+    tags: &str,
+    section: &str,
+) -> FlatBookmark {
+    // Exports use Unix seconds
+    let date_added = time_added
+        .parse::<i64>()
+        .ok()
+        .filter(|&secs| secs > 0)
+        .map(|secs| chrome_timestamp_from_unix_micros(secs * 1_000_000));
+
+    FlatBookmark {
+        // Exports have no ids; the URL is unique within a read-later queue
+        id: url_bookmark_id(url),
+        name: if title.is_empty() { url } else { title }.to_string(),
+        url: url.to_string(),
+        date_added,
+        date_modified: None,
+        folder_path: service.folder_path(section),
+        tags: split_tags(tags),
+        profile: None,
+    }
+}
+
+fn strip_tags(html: &str) -> String {
+    HTML_TAG.replace_all(html, "").trim().to_string()
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pocket_html() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Pocket Export</title></head><body>
+<h1>Unread</h1>
+<ul>
+<li><a href="https://www.rust-lang.org/" time_added="1700000000" tags="rust,lang">Rust &amp; Cargo</a></li>
+</ul>
+<h1>Read Archive</h1>
+<ul>
+<li><a href="https://docs.rs/" time_added="1600000000" tags="">docs.rs</a></li>
+</ul>
+</body></html>"#;
+
+        let bookmarks = parse_html(ReadLaterService::Pocket, html);
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].name, "Rust & Cargo");
+        assert_eq!(bookmarks[0].folder_path, vec!["Pocket", "Unread"]);
+        assert_eq!(bookmarks[0].tags, vec!["rust", "lang"]);
+        assert_eq!(
+            bookmarks[0].date_added.as_deref(),
+            Some("13344473600000000")
+        );
+        assert_eq!(bookmarks[1].url, "https://docs.rs/");
+        assert_eq!(bookmarks[1].folder_path, vec!["Pocket", "Archive"]);
+        assert!(bookmarks[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_pocket_csv() {
+        let csv = "title,url,time_added,tags,status\n\
+                   \"Hello, World\",https://example.com/,1700000000,news|tech,archive\n\
+                   ,https://example.org/,,,unread\n";
+
+        let bookmarks = parse_csv(ReadLaterService::Pocket, csv).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].name, "Hello, World");
+        assert_eq!(bookmarks[0].tags, vec!["news", "tech"]);
+        assert_eq!(bookmarks[0].folder_path, vec!["Pocket", "Archive"]);
+        assert_eq!(bookmarks[1].name, "https://example.org/");
+        assert_eq!(bookmarks[1].date_added, None);
+        assert_eq!(bookmarks[1].folder_path, vec!["Pocket", "Unread"]);
+    }
+
+    #[test]
+    fn test_parse_instapaper_csv() {
+        let csv = "URL,Title,Selection,Folder,Timestamp,Tags\n\
+                   https://example.com/,Example,,Starred,1700000000,\"[\"\"rust\"\"]\"\n\
+                   https://example.org/,Other,,Archive,1700000000,[]\n";
+
+        let bookmarks = parse_csv(ReadLaterService::Instapaper, csv).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].folder_path, vec!["Instapaper", "Starred"]);
+        assert_eq!(bookmarks[0].tags, vec!["rust"]);
+        assert_eq!(bookmarks[1].folder_path, vec!["Instapaper", "Archive"]);
+        assert!(bookmarks[1].tags.is_empty());
+    }
+}
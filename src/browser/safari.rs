@@ -0,0 +1,159 @@
+use anyhow::{Context, Result};
+use plist::{Dictionary, Value};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::chrome_timestamp_from_unix_micros;
+use crate::bookmark::FlatBookmark;
+use crate::search::common::url_bookmark_id;
+
+/// Default location of Safari's bookmarks file
+pub fn default_bookmarks_path() -> Option<PathBuf> {
+    let path = dirs::home_dir()?.join("Library/Safari/Bookmarks.plist");
+    path.exists().then_some(path)
+}
+
+/// Read all bookmarks from Safari's Bookmarks.plist (binary or XML)
+pub fn read_bookmarks_plist(path: &Path) -> Result<Vec<FlatBookmark>> {
+    let root = Value::from_file(path).with_context(|| format!("Failed to read {path:?}"))?;
+    let root = root
+        .as_dictionary()
+        .context("Bookmarks.plist root is not a dictionary")?;
+
+    let mut bookmarks = Vec::new();
+    collect(root, &[], &mut bookmarks);
+
+    debug!("Read {} bookmarks from Bookmarks.plist", bookmarks.len());
+    Ok(bookmarks)
+}
+
+/// Display names for Safari's built-in top-level folders
+fn folder_name(title: &str) -> &str {
+    match title {
+        "BookmarksBar" => "Favorites",
+        "BookmarksMenu" => "Bookmarks Menu",
+        "com.apple.ReadingList" => "Reading List",
+        other => other,
+    }
+}
+
+fn collect(node: &Dictionary, folder_path: &[String], bookmarks: &mut Vec<FlatBookmark>) {
+    let get_str =
+        |dict: &Dictionary, key: &str| dict.get(key).and_then(Value::as_string).map(str::to_string);
+
+    match node.get("WebBookmarkType").and_then(Value::as_string) {
+        Some("WebBookmarkTypeList") => {
+            let mut path = folder_path.to_vec();
+            // The root list has no title
+            if let Some(title) = get_str(node, "Title").filter(|t| !t.is_empty()) {
+                path.push(folder_name(&title).to_string());
+            }
+
+            if let Some(children) = node.get("Children").and_then(Value::as_array) {
+                for child in children.iter().filter_map(Value::as_dictionary) {
+                    collect(child, &path, bookmarks);
+                }
+            }
+        }
+        Some("WebBookmarkTypeLeaf") => {
+            let Some(url) = get_str(node, "URLString") else {
+                return;
+            };
+            let title = node
+                .get("URIDictionary")
+                .and_then(Value::as_dictionary)
+                .and_then(|d| get_str(d, "title"))
+                .unwrap_or_else(|| url.clone());
+            // Only Reading List items carry a date
+            let date_added = node
+                .get("ReadingList")
+                .and_then(Value::as_dictionary)
+                .and_then(|d| d.get("DateAdded"))
+                .and_then(Value::as_date)
+                .map(|date| {
+                    let added: std::time::SystemTime = date.into();
+                    let micros = added
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_micros() as i64)
+                        .unwrap_or(0);
+                    chrome_timestamp_from_unix_micros(micros)
+                });
+
+            bookmarks.push(FlatBookmark {
+                id: get_str(node, "WebBookmarkUUID").unwrap_or_else(|| url_bookmark_id(&url)),
+                name: title,
+                url,
+                date_added,
+                date_modified: None,
+                folder_path: folder_path.to_vec(),
+                tags: vec![],
+                profile: None,
+            });
+        }
+        // Proxies (History, etc.) and unknown types
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(uuid: &str, url: &str, title: &str) -> Value {
+        let mut uri = Dictionary::new();
+        uri.insert("title".to_string(), Value::String(title.to_string()));
+
+        let mut dict = Dictionary::new();
+        dict.insert(
+            "WebBookmarkType".to_string(),
+            Value::String("WebBookmarkTypeLeaf".to_string()),
+        );
+        dict.insert(
+            "WebBookmarkUUID".to_string(),
+            Value::String(uuid.to_string()),
+        );
+        dict.insert("URLString".to_string(), Value::String(url.to_string()));
+        dict.insert("URIDictionary".to_string(), Value::Dictionary(uri));
+        Value::Dictionary(dict)
+    }
+
+    fn list(title: &str, children: Vec<Value>) -> Value {
+        let mut dict = Dictionary::new();
+        dict.insert(
+            "WebBookmarkType".to_string(),
+            Value::String("WebBookmarkTypeList".to_string()),
+        );
+        dict.insert("Title".to_string(), Value::String(title.to_string()));
+        dict.insert("Children".to_string(), Value::Array(children));
+        Value::Dictionary(dict)
+    }
+
+    #[test]
+    fn test_read_bookmarks_plist() {
+        let root = list(
+            "",
+            vec![
+                list(
+                    "BookmarksBar",
+                    vec![
+                        leaf("A", "https://www.rust-lang.org/", "Rust"),
+                        list("Docs", vec![leaf("B", "https://docs.rs/", "Docs.rs")]),
+                    ],
+                ),
+                list("BookmarksMenu", vec![]),
+            ],
+        );
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("Bookmarks.plist");
+        root.to_file_binary(&path).unwrap();
+
+        let bookmarks = read_bookmarks_plist(&path).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].id, "A");
+        assert_eq!(bookmarks[0].name, "Rust");
+        assert_eq!(bookmarks[0].folder_path, vec!["Favorites"]);
+        assert_eq!(bookmarks[1].url, "https://docs.rs/");
+        assert_eq!(bookmarks[1].folder_path, vec!["Favorites", "Docs"]);
+    }
+}
@@ -1,4 +1,20 @@
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::robots;
+use crate::search::index_aliases::IndexAliases;
+use crate::search::index_registry::IndexRegistry;
+use crate::search::indexer::MAX_CHARS_PER_DOC;
+use crate::search::tokenizer::LinderaDictionary;
+
+/// Environment variable pointing at a config file other than the default
+pub const CONFIG_PATH_ENV: &str = "MCP_BOOKMARK_CONFIG";
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -6,6 +22,10 @@ pub struct Config {
     #[serde(default)]
     pub index_name: Option<String>,
 
+    /// Directory of the index when it lives outside the data directory (opened read-only)
+    #[serde(default)]
+    pub index_path: Option<PathBuf>,
+
     /// Maximum number of bookmarks to fetch (0 is unlimited)
     #[serde(default)]
     pub max_bookmarks: usize,
@@ -13,6 +33,22 @@ pub struct Config {
     /// Maximum snippet length for search results
     #[serde(default = "default_max_snippet_length")]
     pub max_snippet_length: usize,
+
+    /// Address to serve MCP over Streamable HTTP (stdio is used when None)
+    #[serde(default)]
+    pub http_addr: Option<String>,
+
+    /// Maximum number of indexes kept open for per-call `index` selection
+    #[serde(default = "default_max_open_indexes")]
+    pub max_open_indexes: usize,
+
+    /// Timeout in seconds for server-side requests such as link checks
+    #[serde(default = "default_fetch_timeout_secs")]
+    pub fetch_timeout_secs: u64,
+
+    /// Dictionary for Japanese text in newly created indexes
+    #[serde(default)]
+    pub dictionary: LinderaDictionary,
 }
 
 /// Default maximum snippet length for search results
@@ -22,20 +58,37 @@ fn default_max_snippet_length() -> usize {
     DEFAULT_MAX_SNIPPET_LENGTH
 }
 
+fn default_max_open_indexes() -> usize {
+    crate::search::index_registry::DEFAULT_MAX_OPEN_INDEXES
+}
+
+fn default_fetch_timeout_secs() -> u64 {
+    crate::content::DEFAULT_FETCH_TIMEOUT.as_secs()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             index_name: None,
+            index_path: None,
             max_bookmarks: 0,
             max_snippet_length: default_max_snippet_length(),
+            http_addr: None,
+            max_open_indexes: default_max_open_indexes(),
+            fetch_timeout_secs: default_fetch_timeout_secs(),
+            dictionary: LinderaDictionary::default(),
         }
     }
 }
 
 impl Config {
     /// Parse index names from comma-separated string
+    ///
+    /// Aliases from the aliases file, `ALL` and wildcard names such as `work_*`
+    /// expand to the matching indexes in the data directory each time this is called.
     pub fn parse_index_names(&self) -> Vec<String> {
-        self.index_name
+        let names = self
+            .index_name
             .as_ref()
             .map(|s| {
                 s.split(',')
@@ -44,7 +97,15 @@ impl Config {
                     .map(|name| name.to_string())
                     .collect()
             })
-            .unwrap_or_default()
+            .unwrap_or_default();
+        let names = match IndexAliases::load_configured() {
+            Ok(aliases) => aliases.expand(names),
+            Err(e) => {
+                tracing::warn!("Ignoring index aliases: {:#}", e);
+                names
+            }
+        };
+        IndexRegistry::expand_index_names(names, &data_dir())
     }
 
     /// Check if multiple indices are configured
@@ -52,3 +113,1049 @@ impl Config {
         self.parse_index_names().len() > 1
     }
 }
+
+/// Settings read from `~/.config/mcp-bookmark/config.toml`
+///
+/// Every value is optional in the file; environment variables take precedence
+/// over the file and command-line flags take precedence over both.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Directory holding indexes and logs (defaults to the OS data directory)
+    pub data_dir: Option<PathBuf>,
+    pub log: LogSettings,
+    pub index: IndexSettings,
+    pub snippet: SnippetSettings,
+    pub boosts: SearchBoosts,
+    pub fetch: FetchSettings,
+    pub tokenizer: TokenizerSettings,
+    pub embeddings: EmbeddingSettings,
+    pub query_log: QueryLogSettings,
+    pub ocr: OcrSettings,
+    pub daemon: DaemonSettings,
+    pub domains: DomainSettings,
+    pub redaction: RedactionSettings,
+    pub content: ContentSettings,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSettings {
+    /// Log filter used when RUST_LOG is not set, e.g. "info" or "debug"
+    pub level: Option<String>,
+    pub destination: LogDestination,
+    /// Daily log files to keep (0 keeps all)
+    pub max_files: usize,
+    /// Delete log files older than this many days (0 disables)
+    pub max_age_days: u64,
+}
+
+/// Default number of daily log files kept
+pub const DEFAULT_MAX_LOG_FILES: usize = 14;
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: None,
+            destination: LogDestination::default(),
+            max_files: DEFAULT_MAX_LOG_FILES,
+            max_age_days: 0,
+        }
+    }
+}
+
+/// Where log output goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogDestination {
+    /// Daily files under `<data dir>/logs` and stderr
+    #[default]
+    Both,
+    File,
+    /// stderr only; nothing is written to the data directory
+    Console,
+}
+
+impl LogDestination {
+    pub fn to_file(self) -> bool {
+        self != Self::Console
+    }
+
+    pub fn to_console(self) -> bool {
+        self != Self::File
+    }
+}
+
+impl FromStr for LogDestination {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "both" => Ok(Self::Both),
+            "file" => Ok(Self::File),
+            "console" => Ok(Self::Console),
+            _ => Err(format!("unknown log destination: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexSettings {
+    /// Index to use when neither --index nor INDEX_NAME is given
+    pub default: Option<String>,
+    /// Maximum number of indexes kept open for per-call `index` selection
+    pub max_open: usize,
+    /// Keep a compressed copy of each page's original HTML (pages indexed by the extension)
+    pub store_html: bool,
+    /// Keywords extracted from each page and added to its tags (0 disables)
+    pub keywords: usize,
+    /// Names for groups of indexes (defaults to `aliases.toml` next to the config file)
+    pub aliases_file: Option<PathBuf>,
+    /// When several indexes hold the same URL, show the most recently modified copy
+    pub prefer_newer_duplicates: bool,
+    /// Bookmarks read into an index built from a browser (0 is unlimited)
+    pub max_bookmarks: usize,
+    /// Bookmarks indexed directly from any one folder (0 is unlimited)
+    pub max_per_folder: usize,
+    /// Bookmarks indexed from a folder and its subfolders, by folder path
+    pub folder_limits: BTreeMap<String, usize>,
+}
+
+/// Default number of keywords added to each page's tags
+pub const DEFAULT_KEYWORDS: usize = 5;
+
+impl Default for IndexSettings {
+    fn default() -> Self {
+        Self {
+            default: None,
+            max_open: default_max_open_indexes(),
+            store_html: false,
+            keywords: DEFAULT_KEYWORDS,
+            aliases_file: None,
+            prefer_newer_duplicates: true,
+            max_bookmarks: 0,
+            max_per_folder: 0,
+            folder_limits: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SnippetSettings {
+    /// Maximum snippet length for search results
+    pub max_length: usize,
+}
+
+impl Default for SnippetSettings {
+    fn default() -> Self {
+        Self {
+            max_length: DEFAULT_MAX_SNIPPET_LENGTH,
+        }
+    }
+}
+
+/// Per-field score multipliers for full-text search
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchBoosts {
+    pub title: f32,
+    pub url: f32,
+    /// Page headings (h1-h3) sent by the extension
+    pub headings: f32,
+    pub content: f32,
+}
+
+impl Default for SearchBoosts {
+    fn default() -> Self {
+        Self {
+            title: 3.0,
+            url: 2.0,
+            headings: 1.5,
+            content: 1.0,
+        }
+    }
+}
+
+/// Characters of text below which a page counts as empty for `render_empty_pages`
+pub const DEFAULT_RENDER_MIN_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchSettings {
+    /// Timeout in seconds for server-side requests such as link checks
+    pub timeout_secs: u64,
+    /// Per-domain cookies and headers (defaults to `credentials.toml` next to the config file)
+    pub credentials_file: Option<PathBuf>,
+    /// Follow robots.txt when crawling
+    pub respect_robots: bool,
+    /// Domains (and their subdomains) crawled regardless of robots.txt
+    pub ignore_robots: Vec<String>,
+    /// Index the latest Internet Archive snapshot of pages that return 403/404/410
+    pub archive_fallback: bool,
+    /// Domains (and their subdomains) whose pages are rendered in headless Chrome
+    /// before extraction (needs the `render` feature)
+    pub render_domains: Vec<String>,
+    /// Also render pages whose HTML has less than `render_min_chars` characters of text
+    pub render_empty_pages: bool,
+    pub render_min_chars: usize,
+    /// Chrome or Chromium to render with (default: looked up on the PATH)
+    pub chrome_path: Option<PathBuf>,
+    /// Proxy for all server-side requests, e.g. `http://proxy.corp:8080`
+    /// (without it, `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` are honoured)
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy`, in `NO_PROXY` syntax
+    pub no_proxy: Option<String>,
+    /// PEM bundle of extra root certificates, for proxies that intercept TLS
+    pub ca_bundle: Option<PathBuf>,
+    /// User-Agent sent instead of `mcp-bookmark/<version>`
+    pub user_agent: Option<String>,
+    /// Extra headers sent with every request
+    pub headers: BTreeMap<String, String>,
+    /// User-Agent and headers for particular domains (and their subdomains)
+    pub domains: BTreeMap<String, FetchDomainSettings>,
+}
+
+/// Request settings for one domain under `[fetch.domains."example.com"]`
+///
+/// Secrets belong in the credentials file instead, which is never printed.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchDomainSettings {
+    pub user_agent: Option<String>,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_fetch_timeout_secs(),
+            credentials_file: None,
+            respect_robots: true,
+            ignore_robots: Vec::new(),
+            archive_fallback: false,
+            render_domains: Vec::new(),
+            render_empty_pages: false,
+            render_min_chars: DEFAULT_RENDER_MIN_CHARS,
+            chrome_path: None,
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+            user_agent: None,
+            headers: BTreeMap::new(),
+            domains: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenizerSettings {
+    /// Dictionary for Japanese text in new indexes; existing indexes keep theirs
+    pub dictionary: LinderaDictionary,
+    /// Lindera user dictionary CSV with terms to keep as single tokens
+    pub user_dictionary: Option<PathBuf>,
+}
+
+/// Default number of queries kept per index
+pub const DEFAULT_QUERY_LOG_MAX_ENTRIES: usize = 1000;
+
+/// Default number of days queries are kept
+pub const DEFAULT_QUERY_LOG_MAX_AGE_DAYS: u64 = 30;
+
+/// Local log of searches behind `get_search_analytics`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryLogSettings {
+    /// Record searches under `<data dir>/query_logs`
+    pub enabled: bool,
+    /// Most recent queries kept per index (0 keeps all)
+    pub max_entries: usize,
+    /// Drop queries older than this many days (0 disables)
+    pub max_age_days: u64,
+}
+
+impl Default for QueryLogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: DEFAULT_QUERY_LOG_MAX_ENTRIES,
+            max_age_days: DEFAULT_QUERY_LOG_MAX_AGE_DAYS,
+        }
+    }
+}
+
+/// Default number of scanned pages OCRed per PDF
+pub const DEFAULT_OCR_MAX_PAGES: usize = 50;
+
+/// Text recognition for PDF pages without a text layer (needs the `ocr` feature)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OcrSettings {
+    /// OCR pages of image-only PDFs the extension indexes
+    pub enabled: bool,
+    /// Pages OCRed per PDF; later scanned pages are indexed without text
+    pub max_pages: usize,
+    /// ocrs text detection model (default: `<data dir>/models/ocrs/text-detection.rten`)
+    pub detection_model: Option<PathBuf>,
+    /// ocrs text recognition model (default: `<data dir>/models/ocrs/text-recognition.rten`)
+    pub recognition_model: Option<PathBuf>,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pages: DEFAULT_OCR_MAX_PAGES,
+            detection_model: None,
+            recognition_model: None,
+        }
+    }
+}
+
+/// Default days before a fetched page is re-crawled by the daemon
+pub const DEFAULT_STALE_AFTER_DAYS: u64 = 30;
+
+/// Default number of stale pages re-crawled per index and run
+pub const DEFAULT_RECRAWL_LIMIT: usize = 100;
+
+/// Maintenance schedules for `mcp-bookmark daemon`
+///
+/// Schedules are cron expressions (`minute hour day month weekday`, in local
+/// time) or `@hourly`, `@daily`, `@weekly`, `@monthly`; an empty one turns the
+/// task off.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonSettings {
+    /// Re-fetch pages the server fetched more than `stale_after_days` ago
+    pub recrawl: String,
+    /// Index new entries of the served indexes' feeds
+    pub feeds: String,
+    /// Merge index segments
+    pub merge: String,
+    /// Refresh the index summaries shown by `status`
+    pub metadata: String,
+    pub stale_after_days: u64,
+    /// Pages re-crawled per index and run, oldest first
+    pub recrawl_limit: usize,
+}
+
+impl Default for DaemonSettings {
+    fn default() -> Self {
+        Self {
+            recrawl: "0 3 * * *".to_string(),
+            feeds: "@hourly".to_string(),
+            merge: "30 4 * * 0".to_string(),
+            metadata: "*/15 * * * *".to_string(),
+            stale_after_days: DEFAULT_STALE_AFTER_DAYS,
+            recrawl_limit: DEFAULT_RECRAWL_LIMIT,
+        }
+    }
+}
+
+/// Domains whose page content may be indexed, from `[domains]`
+///
+/// Rules cover subdomains. Bookmarks of other domains are still indexed by
+/// title and URL, but their pages are never fetched and content sent by the
+/// extension is dropped.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DomainSettings {
+    /// Domains whose content is never indexed, e.g. banking or webmail
+    pub deny: Vec<String>,
+    /// When not empty, only these domains have their content indexed
+    pub allow: Vec<String>,
+    /// Also leave bookmarks of denied domains out of search results
+    pub hide_denied: bool,
+}
+
+impl DomainSettings {
+    /// Whether the page content of a URL may be fetched and indexed
+    pub fn allows_content(&self, url: &str) -> bool {
+        match url_host(url) {
+            Some(host) => {
+                !robots::host_in(&host, &self.deny)
+                    && (self.allow.is_empty() || robots::host_in(&host, &self.allow))
+            }
+            // Only an allow list can rule out URLs without a host
+            None => self.allow.is_empty(),
+        }
+    }
+
+    /// Whether search results leave out a URL
+    pub fn hides(&self, url: &str) -> bool {
+        self.hide_denied && url_host(url).is_some_and(|host| robots::host_in(&host, &self.deny))
+    }
+
+    /// Domains left out of search results
+    pub fn hidden(&self) -> &[String] {
+        if self.hide_denied { &self.deny } else { &[] }
+    }
+}
+
+/// Personal data replaced in page content before it is indexed, from `[redaction]`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedactionSettings {
+    pub enabled: bool,
+    pub emails: bool,
+    pub phone_numbers: bool,
+    /// Card numbers passing the Luhn check
+    pub credit_cards: bool,
+    /// Extra regular expressions, e.g. employee or customer numbers
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            emails: true,
+            phone_numbers: true,
+            credit_cards: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// How much of a page's content is indexed, from `[content]`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentSettings {
+    /// Characters per document above which multi-page PDFs are split into parts
+    pub max_chars_per_doc: usize,
+    /// Characters of content indexed per bookmark, the rest is cut off (0 is unlimited)
+    pub max_chars: usize,
+    /// Limits replacing `max_chars` for content types such as "html" or "pdf"
+    pub max_chars_by_type: BTreeMap<String, usize>,
+}
+
+impl Default for ContentSettings {
+    fn default() -> Self {
+        Self {
+            max_chars_per_doc: MAX_CHARS_PER_DOC,
+            max_chars: 0,
+            max_chars_by_type: BTreeMap::new(),
+        }
+    }
+}
+
+impl ContentSettings {
+    /// Characters of content of this type that are indexed, None when unlimited
+    pub fn max_chars_for(&self, content_type: &str) -> Option<usize> {
+        let max_chars = self
+            .max_chars_by_type
+            .get(content_type)
+            .copied()
+            .unwrap_or(self.max_chars);
+        (max_chars > 0).then_some(max_chars)
+    }
+}
+
+fn url_host(url: &str) -> Option<String> {
+    url::Url::parse(url).ok()?.host_str().map(str::to_string)
+}
+
+/// Comma-separated list from an environment variable
+fn list_from(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl OcrSettings {
+    pub fn detection_model(&self) -> PathBuf {
+        self.detection_model
+            .clone()
+            .unwrap_or_else(|| data_dir().join("models/ocrs/text-detection.rten"))
+    }
+
+    pub fn recognition_model(&self) -> PathBuf {
+        self.recognition_model
+            .clone()
+            .unwrap_or_else(|| data_dir().join("models/ocrs/text-recognition.rten"))
+    }
+}
+
+/// Default model for semantic search (multilingual, so Japanese pages work too)
+pub const DEFAULT_EMBEDDING_MODEL: &str = "intfloat/multilingual-e5-small";
+
+/// Default endpoint for the `openai` embedding provider
+pub const DEFAULT_EMBEDDING_URL: &str = "https://api.openai.com/v1/embeddings";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmbeddingSettings {
+    /// Compute embeddings when the extension indexes a page
+    pub enabled: bool,
+    pub provider: EmbeddingProvider,
+    /// fastembed model code for `local`, or the model name sent to the endpoint
+    pub model: String,
+    /// OpenAI-compatible embeddings endpoint for the `openai` provider
+    pub url: String,
+    /// Bearer token for the endpoint (never written out by `config show`)
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+    /// Texts sent per embeddings request
+    pub batch_size: usize,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: EmbeddingProvider::default(),
+            model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            url: DEFAULT_EMBEDDING_URL.to_string(),
+            api_key: None,
+            batch_size: 32,
+        }
+    }
+}
+
+/// Where embeddings are computed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// ONNX model run in-process (needs the `embeddings` feature)
+    #[default]
+    Local,
+    /// OpenAI-compatible `/v1/embeddings` endpoint
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+impl FromStr for EmbeddingProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "openai" => Ok(Self::OpenAi),
+            _ => Err(format!("unknown embedding provider: {s}")),
+        }
+    }
+}
+
+impl Settings {
+    /// Config file location: $MCP_BOOKMARK_CONFIG or ~/.config/mcp-bookmark/config.toml
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_PATH_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        dirs::home_dir().map(|home| home.join(".config/mcp-bookmark/config.toml"))
+    }
+
+    /// Load the config file (if any) and apply environment overrides
+    pub fn load() -> Result<Self> {
+        let mut settings = match Self::path() {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {path:?}"))?;
+                Self::parse(&content).with_context(|| format!("Invalid config file {path:?}"))?
+            }
+            _ => Self::default(),
+        };
+        settings.apply_env(|name| std::env::var(name).ok())?;
+        Ok(settings)
+    }
+
+    /// Parse settings from TOML
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Override settings from environment variables (looked up through `var`)
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+        if let Some(dir) = var("MCP_BOOKMARK_DATA_DIR") {
+            self.data_dir = Some(PathBuf::from(dir));
+        }
+        if let Some(level) = var("MCP_BOOKMARK_LOG_LEVEL") {
+            self.log.level = Some(level);
+        }
+        override_from(
+            &var,
+            "MCP_BOOKMARK_LOG_DESTINATION",
+            &mut self.log.destination,
+        )?;
+        override_from(&var, "MCP_BOOKMARK_LOG_MAX_FILES", &mut self.log.max_files)?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_LOG_MAX_AGE_DAYS",
+            &mut self.log.max_age_days,
+        )?;
+        if let Some(name) = var("INDEX_NAME") {
+            self.index.default = Some(name);
+        }
+        override_from(
+            &var,
+            "MCP_BOOKMARK_MAX_OPEN_INDEXES",
+            &mut self.index.max_open,
+        )?;
+        override_from(&var, "MCP_BOOKMARK_STORE_HTML", &mut self.index.store_html)?;
+        override_from(&var, "MCP_BOOKMARK_KEYWORDS", &mut self.index.keywords)?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_PREFER_NEWER_DUPLICATES",
+            &mut self.index.prefer_newer_duplicates,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_MAX_BOOKMARKS",
+            &mut self.index.max_bookmarks,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_MAX_PER_FOLDER",
+            &mut self.index.max_per_folder,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_SNIPPET_LENGTH",
+            &mut self.snippet.max_length,
+        )?;
+        override_from(&var, "MCP_BOOKMARK_TITLE_BOOST", &mut self.boosts.title)?;
+        override_from(&var, "MCP_BOOKMARK_URL_BOOST", &mut self.boosts.url)?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_HEADINGS_BOOST",
+            &mut self.boosts.headings,
+        )?;
+        override_from(&var, "MCP_BOOKMARK_CONTENT_BOOST", &mut self.boosts.content)?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_FETCH_TIMEOUT",
+            &mut self.fetch.timeout_secs,
+        )?;
+        if let Some(path) = var("MCP_BOOKMARK_CREDENTIALS") {
+            self.fetch.credentials_file = Some(PathBuf::from(path));
+        }
+        if let Some(path) = var("MCP_BOOKMARK_ALIASES") {
+            self.index.aliases_file = Some(PathBuf::from(path));
+        }
+        override_from(
+            &var,
+            "MCP_BOOKMARK_RESPECT_ROBOTS",
+            &mut self.fetch.respect_robots,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_ARCHIVE_FALLBACK",
+            &mut self.fetch.archive_fallback,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_RENDER_EMPTY_PAGES",
+            &mut self.fetch.render_empty_pages,
+        )?;
+        if let Some(path) = var("MCP_BOOKMARK_CHROME") {
+            self.fetch.chrome_path = Some(PathBuf::from(path));
+        }
+        if let Some(proxy) = var("MCP_BOOKMARK_PROXY") {
+            self.fetch.proxy = Some(proxy);
+        }
+        if let Some(path) = var("MCP_BOOKMARK_CA_BUNDLE") {
+            self.fetch.ca_bundle = Some(PathBuf::from(path));
+        }
+        if let Some(user_agent) = var("MCP_BOOKMARK_USER_AGENT") {
+            self.fetch.user_agent = Some(user_agent);
+        }
+        override_from(
+            &var,
+            "MCP_BOOKMARK_DICTIONARY",
+            &mut self.tokenizer.dictionary,
+        )?;
+        if let Some(path) = var("MCP_BOOKMARK_USER_DICTIONARY") {
+            self.tokenizer.user_dictionary = Some(PathBuf::from(path));
+        }
+        override_from(
+            &var,
+            "MCP_BOOKMARK_EMBEDDINGS",
+            &mut self.embeddings.enabled,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_EMBEDDING_PROVIDER",
+            &mut self.embeddings.provider,
+        )?;
+        if let Some(model) = var("MCP_BOOKMARK_EMBEDDING_MODEL") {
+            self.embeddings.model = model;
+        }
+        if let Some(url) = var("MCP_BOOKMARK_EMBEDDING_URL") {
+            self.embeddings.url = url;
+        }
+        if let Some(key) = var("MCP_BOOKMARK_EMBEDDING_API_KEY") {
+            self.embeddings.api_key = Some(key);
+        }
+        override_from(&var, "MCP_BOOKMARK_QUERY_LOG", &mut self.query_log.enabled)?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_QUERY_LOG_MAX_ENTRIES",
+            &mut self.query_log.max_entries,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_QUERY_LOG_MAX_AGE_DAYS",
+            &mut self.query_log.max_age_days,
+        )?;
+        override_from(&var, "MCP_BOOKMARK_OCR", &mut self.ocr.enabled)?;
+        if let Some(domains) = var("MCP_BOOKMARK_DENY_DOMAINS") {
+            self.domains.deny = list_from(&domains);
+        }
+        if let Some(domains) = var("MCP_BOOKMARK_ALLOW_DOMAINS") {
+            self.domains.allow = list_from(&domains);
+        }
+        override_from(
+            &var,
+            "MCP_BOOKMARK_HIDE_DENIED",
+            &mut self.domains.hide_denied,
+        )?;
+        override_from(&var, "MCP_BOOKMARK_REDACT", &mut self.redaction.enabled)?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_MAX_CHARS_PER_DOC",
+            &mut self.content.max_chars_per_doc,
+        )?;
+        override_from(
+            &var,
+            "MCP_BOOKMARK_MAX_CONTENT_CHARS",
+            &mut self.content.max_chars,
+        )?;
+        Ok(())
+    }
+
+    /// Index to use given the `--index` flag
+    ///
+    /// Precedence: `--index`, then INDEX_NAME, then `index.default` in the
+    /// config file. Blank values are skipped.
+    pub fn resolve_index_name(&self, flag: Option<String>) -> Option<String> {
+        flag.or_else(|| self.index.default.clone())
+            .filter(|name| !name.trim().is_empty())
+    }
+
+    /// Directory holding indexes and logs
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone().unwrap_or_else(|| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("mcp-bookmark")
+        })
+    }
+
+    /// Log filter to use when RUST_LOG is not set
+    pub fn log_level(&self) -> &str {
+        self.log.level.as_deref().unwrap_or("info")
+    }
+
+    /// Server configuration with these settings as defaults
+    pub fn to_config(&self) -> Config {
+        Config {
+            index_name: self.index.default.clone(),
+            max_snippet_length: self.snippet.max_length,
+            max_open_indexes: self.index.max_open,
+            max_bookmarks: self.index.max_bookmarks,
+            fetch_timeout_secs: self.fetch.timeout_secs,
+            dictionary: self.tokenizer.dictionary,
+            ..Config::default()
+        }
+    }
+
+    /// Render as TOML, e.g. for `config show`
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+fn override_from<T: FromStr>(
+    var: impl Fn(&str) -> Option<String>,
+    name: &str,
+    target: &mut T,
+) -> Result<()> {
+    if let Some(value) = var(name) {
+        *target = value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid value for {name}: {value}"))?;
+    }
+    Ok(())
+}
+
+/// Install the process-wide settings (only the first call takes effect)
+pub fn init(settings: Settings) {
+    SETTINGS.set(settings).ok();
+}
+
+/// Process-wide settings, or the defaults if `init` was never called
+pub fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(Settings::default)
+}
+
+/// Directory holding indexes and logs
+pub fn data_dir() -> PathBuf {
+    settings().data_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_settings() {
+        let settings = Settings::parse(
+            r#"
+            data_dir = "/tmp/bookmarks"
+
+            [log]
+            level = "debug"
+            destination = "console"
+
+            [index]
+            default = "work"
+
+            [index.folder_limits]
+            "Bookmarks bar/Reading" = 100
+
+            [boosts]
+            title = 5.0
+
+            [tokenizer]
+            dictionary = "unidic"
+            user_dictionary = "/tmp/userdic.csv"
+
+            [embeddings]
+            enabled = true
+            provider = "openai"
+            api_key = "sk-test"
+
+            [query_log]
+            max_age_days = 7
+
+            [ocr]
+            enabled = true
+            detection_model = "/opt/ocrs/detection.rten"
+
+            [daemon]
+            merge = ""
+            stale_after_days = 7
+
+            [fetch]
+            headers = { "Accept-Language" = "ja" }
+            render_domains = ["app.example.com"]
+
+            [fetch.domains."news.example.com"]
+            user_agent = "Mozilla/5.0"
+
+            [content]
+            max_chars = 200000
+
+            [content.max_chars_by_type]
+            pdf = 0
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.data_dir(), PathBuf::from("/tmp/bookmarks"));
+        assert_eq!(settings.log_level(), "debug");
+        assert_eq!(settings.log.destination, LogDestination::Console);
+        assert_eq!(settings.log.max_files, DEFAULT_MAX_LOG_FILES);
+        assert_eq!(settings.index.default.as_deref(), Some("work"));
+        assert_eq!(settings.index.max_open, default_max_open_indexes());
+        assert!(!settings.index.store_html);
+        assert_eq!(settings.index.keywords, DEFAULT_KEYWORDS);
+        assert_eq!(settings.index.folder_limits["Bookmarks bar/Reading"], 100);
+        assert_eq!(settings.content.max_chars_for("html"), Some(200_000));
+        assert_eq!(settings.content.max_chars_for("pdf"), None);
+        assert_eq!(settings.content.max_chars_per_doc, MAX_CHARS_PER_DOC);
+        assert_eq!(settings.boosts.title, 5.0);
+        assert_eq!(settings.boosts.url, 2.0);
+        assert_eq!(settings.boosts.headings, 1.5);
+        assert_eq!(settings.snippet.max_length, DEFAULT_MAX_SNIPPET_LENGTH);
+        assert_eq!(settings.tokenizer.dictionary, LinderaDictionary::Unidic);
+        assert_eq!(
+            settings.tokenizer.user_dictionary,
+            Some(PathBuf::from("/tmp/userdic.csv"))
+        );
+        assert!(settings.embeddings.enabled);
+        assert_eq!(settings.embeddings.model, DEFAULT_EMBEDDING_MODEL);
+        assert_eq!(settings.embeddings.provider, EmbeddingProvider::OpenAi);
+        assert_eq!(settings.embeddings.url, DEFAULT_EMBEDDING_URL);
+        assert!(!settings.to_toml().unwrap().contains("sk-test"));
+        assert!(settings.query_log.enabled);
+        assert_eq!(
+            settings.query_log.max_entries,
+            DEFAULT_QUERY_LOG_MAX_ENTRIES
+        );
+        assert_eq!(settings.query_log.max_age_days, 7);
+        assert!(settings.ocr.enabled);
+        assert_eq!(settings.ocr.max_pages, DEFAULT_OCR_MAX_PAGES);
+        assert_eq!(
+            settings.ocr.detection_model(),
+            PathBuf::from("/opt/ocrs/detection.rten")
+        );
+        assert_eq!(settings.daemon.feeds, "@hourly");
+        assert!(settings.daemon.merge.is_empty());
+        assert_eq!(settings.daemon.stale_after_days, 7);
+        assert_eq!(settings.daemon.recrawl_limit, DEFAULT_RECRAWL_LIMIT);
+        assert!(settings.fetch.respect_robots);
+        assert!(!settings.fetch.archive_fallback);
+        assert_eq!(settings.fetch.render_domains, vec!["app.example.com"]);
+        assert!(!settings.fetch.render_empty_pages);
+        assert_eq!(settings.fetch.render_min_chars, DEFAULT_RENDER_MIN_CHARS);
+        assert!(settings.fetch.proxy.is_none());
+        assert_eq!(settings.fetch.headers["Accept-Language"], "ja");
+        assert_eq!(
+            settings.fetch.domains["news.example.com"]
+                .user_agent
+                .as_deref(),
+            Some("Mozilla/5.0")
+        );
+        assert!(
+            Settings::parse(&settings.to_toml().unwrap())
+                .unwrap()
+                .fetch
+                .domains
+                .contains_key("news.example.com")
+        );
+
+        assert!(Settings::parse("[boosts]\ntitel = 1.0").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let env: HashMap<&str, &str> = [
+            ("INDEX_NAME", "personal"),
+            ("MCP_BOOKMARK_SNIPPET_LENGTH", "200"),
+            ("MCP_BOOKMARK_FETCH_TIMEOUT", "30"),
+            ("MCP_BOOKMARK_LOG_LEVEL", ""),
+            ("MCP_BOOKMARK_LOG_DESTINATION", "File"),
+            ("MCP_BOOKMARK_DICTIONARY", "UniDic"),
+            ("MCP_BOOKMARK_EMBEDDING_MODEL", "BAAI/bge-small-en-v1.5"),
+            ("MCP_BOOKMARK_KEYWORDS", "0"),
+            ("MCP_BOOKMARK_ALIASES", "/tmp/aliases.toml"),
+            ("MCP_BOOKMARK_PREFER_NEWER_DUPLICATES", "false"),
+            ("MCP_BOOKMARK_MAX_BOOKMARKS", "5000"),
+            ("MCP_BOOKMARK_MAX_PER_FOLDER", "200"),
+            ("MCP_BOOKMARK_QUERY_LOG", "false"),
+            ("MCP_BOOKMARK_CREDENTIALS", "/tmp/credentials.toml"),
+            ("MCP_BOOKMARK_RESPECT_ROBOTS", "false"),
+            ("MCP_BOOKMARK_ARCHIVE_FALLBACK", "true"),
+            ("MCP_BOOKMARK_RENDER_EMPTY_PAGES", "true"),
+            ("MCP_BOOKMARK_CHROME", "/usr/bin/chromium"),
+            ("MCP_BOOKMARK_PROXY", "http://proxy.corp:8080"),
+            ("MCP_BOOKMARK_CA_BUNDLE", "/etc/ssl/corp-ca.pem"),
+            ("MCP_BOOKMARK_USER_AGENT", "Mozilla/5.0"),
+            ("MCP_BOOKMARK_OCR", "true"),
+            (
+                "MCP_BOOKMARK_DENY_DOMAINS",
+                "bank.example.com, mail.example.com",
+            ),
+            ("MCP_BOOKMARK_HIDE_DENIED", "true"),
+            ("MCP_BOOKMARK_REDACT", "true"),
+            ("MCP_BOOKMARK_MAX_CHARS_PER_DOC", "50000"),
+        ]
+        .into_iter()
+        .collect();
+        let lookup = |name: &str| env.get(name).map(|v| v.to_string());
+
+        let mut settings =
+            Settings::parse("[log]\nlevel = \"warn\"\n[index]\ndefault = \"work\"").unwrap();
+        settings.apply_env(lookup).unwrap();
+
+        let config = settings.to_config();
+        assert_eq!(config.index_name.as_deref(), Some("personal"));
+        assert_eq!(config.max_snippet_length, 200);
+        assert_eq!(config.fetch_timeout_secs, 30);
+        assert_eq!(config.dictionary, LinderaDictionary::Unidic);
+        assert_eq!(settings.log_level(), "warn");
+        assert_eq!(settings.log.destination, LogDestination::File);
+        assert_eq!(settings.embeddings.model, "BAAI/bge-small-en-v1.5");
+        assert_eq!(settings.index.keywords, 0);
+        assert_eq!(
+            settings.index.aliases_file,
+            Some(PathBuf::from("/tmp/aliases.toml"))
+        );
+        assert!(!settings.index.prefer_newer_duplicates);
+        assert_eq!(config.max_bookmarks, 5000);
+        assert_eq!(settings.index.max_per_folder, 200);
+        assert!(!settings.query_log.enabled);
+        assert_eq!(
+            settings.fetch.credentials_file,
+            Some(PathBuf::from("/tmp/credentials.toml"))
+        );
+        assert!(!settings.fetch.respect_robots);
+        assert!(settings.fetch.archive_fallback);
+        assert!(settings.fetch.render_empty_pages);
+        assert_eq!(
+            settings.fetch.chrome_path,
+            Some(PathBuf::from("/usr/bin/chromium"))
+        );
+        assert_eq!(
+            settings.fetch.proxy.as_deref(),
+            Some("http://proxy.corp:8080")
+        );
+        assert_eq!(
+            settings.fetch.ca_bundle,
+            Some(PathBuf::from("/etc/ssl/corp-ca.pem"))
+        );
+        assert_eq!(settings.fetch.user_agent.as_deref(), Some("Mozilla/5.0"));
+        assert!(settings.ocr.enabled);
+        assert_eq!(
+            settings.domains.deny,
+            ["bank.example.com", "mail.example.com"]
+        );
+        assert!(settings.domains.hide_denied);
+        assert!(settings.redaction.enabled);
+        assert!(settings.redaction.emails);
+        assert_eq!(settings.content.max_chars_per_doc, 50_000);
+
+        assert_eq!(
+            settings
+                .resolve_index_name(Some("a,b".to_string()))
+                .as_deref(),
+            Some("a,b")
+        );
+        assert_eq!(
+            settings.resolve_index_name(None).as_deref(),
+            Some("personal")
+        );
+        assert_eq!(
+            Settings::default().resolve_index_name(Some(" ".to_string())),
+            None
+        );
+
+        let mut settings = Settings::default();
+        let invalid = |name: &str| (name == "MCP_BOOKMARK_URL_BOOST").then(|| "high".to_string());
+        assert!(settings.apply_env(invalid).is_err());
+    }
+
+    #[test]
+    fn test_domain_rules() {
+        let mut domains = DomainSettings {
+            deny: vec!["bank.example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(domains.allows_content("https://docs.example.com/guide"));
+        assert!(!domains.allows_content("https://bank.example.com/accounts"));
+        assert!(!domains.allows_content("https://login.bank.example.com/"));
+        assert!(domains.allows_content("https://mybank.example.com/"));
+        assert!(!domains.hides("https://bank.example.com/accounts"));
+        assert!(domains.hidden().is_empty());
+
+        domains.hide_denied = true;
+        assert!(domains.hides("https://bank.example.com/accounts"));
+        assert!(!domains.hides("https://docs.example.com/guide"));
+
+        domains.allow = vec!["example.com".to_string()];
+        assert!(domains.allows_content("https://docs.example.com/guide"));
+        assert!(!domains.allows_content("https://bank.example.com/accounts"));
+        assert!(!domains.allows_content("https://example.org/"));
+        assert!(!domains.allows_content("file:///home/me/notes.html"));
+    }
+}
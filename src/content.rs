@@ -0,0 +1,480 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::config::FetchDomainSettings;
+use crate::credentials::{self, Credentials};
+use crate::robots::{self, RobotsRules};
+
+/// Default timeout for a single request
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest response body [`ContentFetcher::fetch`] reads
+pub const MAX_FETCH_BYTES: usize = 20 * 1024 * 1024;
+
+/// User-Agent sent unless the settings name another
+pub const DEFAULT_USER_AGENT: &str = concat!("mcp-bookmark/", env!("CARGO_PKG_VERSION"));
+
+/// How long a robots.txt that couldn't be fetched keeps disallowing its host
+pub const ROBOTS_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Outcome of checking whether a bookmarked URL is still reachable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkState {
+    /// Responded with a non-error status
+    Ok,
+    /// 404 Not Found or 410 Gone
+    Dead,
+    /// No response within the timeout
+    Timeout,
+    /// Any other failure (DNS, TLS, 5xx, ...)
+    Error,
+}
+
+/// Result of a link check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCheck {
+    pub url: String,
+    pub state: LinkState,
+    pub http_status: Option<u16>,
+    pub error: Option<String>,
+    pub checked_at: DateTime<Local>,
+}
+
+/// A response read in full by [`ContentFetcher::fetch`]
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    /// URL after redirects
+    pub url: String,
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl FetchedPage {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// HTML or XHTML, or no content type at all
+    pub fn is_html(&self) -> bool {
+        self.content_type
+            .as_deref()
+            .is_none_or(|kind| kind.contains("html"))
+    }
+
+    /// The body as text, replacing invalid UTF-8
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// robots.txt rules cached for an origin
+#[derive(Debug)]
+struct CachedRobots {
+    rules: Arc<RobotsRules>,
+    /// When rules standing in for a robots.txt that couldn't be fetched run out
+    expires: Option<Instant>,
+}
+
+impl CachedRobots {
+    /// The rules, unless they have run out at `now`
+    fn rules_at(&self, now: Instant) -> Option<Arc<RobotsRules>> {
+        self.expires
+            .is_none_or(|expires| now < expires)
+            .then(|| self.rules.clone())
+    }
+}
+
+/// HTTP client for server-side requests to bookmarked pages
+///
+/// Bookmarked pages are rendered and extracted by the Chrome extension; this
+/// is used for link checks, for crawling documentation sites with
+/// `index site` and for pages added with `add_bookmark`, which don't need
+/// the user's browser session. Every
+/// request carries the configured User-Agent and headers, overridden by those
+/// of its domain; pages behind a login also get the domain's credentials.
+///
+/// Bulk crawls ask [`ContentFetcher::robots_allows`] before each page; the
+/// robots.txt of every host is fetched once and cached for the fetcher's lifetime,
+/// except that one which couldn't be fetched is tried again after [`ROBOTS_RETRY_AFTER`].
+#[derive(Debug, Clone)]
+pub struct ContentFetcher {
+    client: reqwest::Client,
+    /// User-Agent and headers from `[fetch.domains]`, keyed by lowercase domain
+    domain_headers: Arc<BTreeMap<String, HeaderMap>>,
+    credentials: Arc<Credentials>,
+    /// Parsed robots.txt per origin (`scheme://host:port`)
+    robots: Arc<Mutex<HashMap<String, CachedRobots>>>,
+    respect_robots: bool,
+    /// Domains (and their subdomains) whose robots.txt is not consulted
+    ignore_robots: Vec<String>,
+}
+
+impl ContentFetcher {
+    /// Create a fetcher with the default timeout
+    pub fn new() -> Result<Self> {
+        Self::with_timeout(DEFAULT_FETCH_TIMEOUT)
+    }
+
+    /// Create a fetcher with a custom timeout and the configured credentials file
+    ///
+    /// Also applies the configured User-Agent, headers, proxy and extra root certificates.
+    pub fn with_timeout(timeout: Duration) -> Result<Self> {
+        let fetch = &crate::config::settings().fetch;
+        let mut builder = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(DEFAULT_USER_AGENT)
+            .default_headers(header_map(
+                "[fetch]",
+                fetch.user_agent.as_deref(),
+                &fetch.headers,
+            )?);
+        if let Some(proxy) = &fetch.proxy {
+            let mut proxy = reqwest::Proxy::all(proxy)
+                .with_context(|| format!("Invalid proxy URL {proxy:?}"))?;
+            if let Some(no_proxy) = &fetch.no_proxy {
+                proxy = proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+            }
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &fetch.ca_bundle {
+            for certificate in load_ca_bundle(path)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        let client = builder.build().context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            domain_headers: Arc::new(domain_header_maps(&fetch.domains)?),
+            credentials: Arc::new(Credentials::load_configured()?),
+            robots: Arc::default(),
+            respect_robots: fetch.respect_robots,
+            ignore_robots: fetch.ignore_robots.clone(),
+        })
+    }
+
+    /// Use these credentials instead of the configured ones
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Arc::new(credentials);
+        self
+    }
+
+    /// Stop consulting robots.txt for a domain and its subdomains
+    pub fn ignore_robots_for(mut self, domain: &str) -> Self {
+        self.ignore_robots.push(domain.to_string());
+        self
+    }
+
+    /// Whether a crawl may fetch a URL according to its host's robots.txt
+    ///
+    /// Always true when robots.txt is disabled in the settings or the host is
+    /// exempted; URLs that don't parse are refused.
+    pub async fn robots_allows(&self, url: &str) -> bool {
+        let Ok(parsed) = url::Url::parse(url) else {
+            return false;
+        };
+        let host = parsed.host_str().unwrap_or("");
+        if !self.respect_robots || robots::host_in(host, &self.ignore_robots) {
+            return true;
+        }
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        self.robots_rules(&parsed).await.allows(&path)
+    }
+
+    /// Delay a host asks crawlers to keep between requests
+    pub async fn crawl_delay(&self, url: &str) -> Option<Duration> {
+        let parsed = url::Url::parse(url).ok()?;
+        if !self.respect_robots || robots::host_in(parsed.host_str()?, &self.ignore_robots) {
+            return None;
+        }
+        self.robots_rules(&parsed).await.crawl_delay()
+    }
+
+    /// The cached robots.txt rules of a URL's origin, fetching them the first time
+    ///
+    /// A missing robots.txt (4xx) allows everything; one that can't be fetched
+    /// (5xx, network errors) disallows everything, as RFC 9309 asks, until
+    /// it is tried again after [`ROBOTS_RETRY_AFTER`]. The cache isn't locked
+    /// during the request, so a slow host doesn't hold up fetches of others.
+    async fn robots_rules(&self, url: &url::Url) -> Arc<RobotsRules> {
+        let origin = url.origin().ascii_serialization();
+        let cached = |now| {
+            let cache = self.robots.lock().unwrap_or_else(PoisonError::into_inner);
+            cache.get(&origin).and_then(|cached| cached.rules_at(now))
+        };
+        if let Some(rules) = cached(Instant::now()) {
+            return rules;
+        }
+
+        let robots_url = format!("{origin}/robots.txt");
+        let (rules, failed) = match self.request(reqwest::Method::GET, &robots_url).send().await {
+            Ok(response) if response.status().is_success() => (
+                RobotsRules::parse(&response.text().await.unwrap_or_default()),
+                false,
+            ),
+            Ok(response) if response.status().is_client_error() => {
+                (RobotsRules::allow_all(), false)
+            }
+            Ok(response) => {
+                debug!("{} returned {}", robots_url, response.status());
+                (RobotsRules::disallow_all(), true)
+            }
+            Err(e) => {
+                debug!("Failed to fetch {}: {}", robots_url, e);
+                (RobotsRules::disallow_all(), true)
+            }
+        };
+
+        let now = Instant::now();
+        let mut cache = self.robots.lock().unwrap_or_else(PoisonError::into_inner);
+        // Another request for the same origin may have finished first
+        if let Some(rules) = cache.get(&origin).and_then(|cached| cached.rules_at(now)) {
+            return rules;
+        }
+        let rules = Arc::new(rules);
+        cache.insert(
+            origin,
+            CachedRobots {
+                rules: rules.clone(),
+                expires: failed.then(|| now + ROBOTS_RETRY_AFTER),
+            },
+        );
+        rules
+    }
+
+    /// Start a request, adding the headers and credentials of the URL's domain
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.request(method, url);
+        if let Some(headers) = credentials::for_domain(&self.domain_headers, url) {
+            request = request.headers(headers.clone());
+        }
+        match self.credentials.headers_for(url) {
+            Some(headers) => request.headers(headers.clone()),
+            None => request,
+        }
+    }
+
+    /// GET a URL and read its body, whatever the status
+    ///
+    /// Fails on network errors and on bodies over [`MAX_FETCH_BYTES`].
+    pub async fn fetch(&self, url: &str) -> Result<FetchedPage> {
+        let mut response = self
+            .request(reqwest::Method::GET, url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {url}"))?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_lowercase());
+        let mut page = FetchedPage {
+            url: response.url().to_string(),
+            status: response.status().as_u16(),
+            content_type,
+            body: Vec::new(),
+        };
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .with_context(|| format!("Failed to read {url}"))?
+        {
+            anyhow::ensure!(
+                page.body.len() + chunk.len() <= MAX_FETCH_BYTES,
+                "{url} is larger than {} MB",
+                MAX_FETCH_BYTES / (1024 * 1024)
+            );
+            page.body.extend_from_slice(&chunk);
+        }
+        Ok(page)
+    }
+
+    /// Check whether a URL is still reachable
+    ///
+    /// Sends HEAD first and falls back to GET for servers that reject HEAD.
+    pub async fn check_link(&self, url: &str) -> LinkCheck {
+        let response = match self.request(reqwest::Method::HEAD, url).send().await {
+            Ok(response)
+                if response.status() == StatusCode::METHOD_NOT_ALLOWED
+                    || response.status() == StatusCode::NOT_IMPLEMENTED =>
+            {
+                debug!("HEAD not supported by {}, retrying with GET", url);
+                self.request(reqwest::Method::GET, url).send().await
+            }
+            other => other,
+        };
+
+        let (state, http_status, error) = match response {
+            Ok(response) => {
+                let status = response.status();
+                (Self::classify_status(status), Some(status.as_u16()), None)
+            }
+            Err(e) if e.is_timeout() => (LinkState::Timeout, None, Some(e.to_string())),
+            Err(e) => (LinkState::Error, None, Some(e.to_string())),
+        };
+
+        LinkCheck {
+            url: url.to_string(),
+            state,
+            http_status,
+            error,
+            checked_at: Local::now(),
+        }
+    }
+
+    /// Map an HTTP status to a link state
+    fn classify_status(status: StatusCode) -> LinkState {
+        match status {
+            StatusCode::NOT_FOUND | StatusCode::GONE => LinkState::Dead,
+            s if s.is_server_error() => LinkState::Error,
+            // 401/403 etc. mean the page exists but needs the browser session
+            _ => LinkState::Ok,
+        }
+    }
+}
+
+/// Headers for one settings scope, with its User-Agent if it sets one
+fn header_map(
+    scope: &str,
+    user_agent: Option<&str>,
+    headers: &BTreeMap<String, String>,
+) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("Invalid header name {name:?} in {scope}"))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("Invalid value for header {name} in {scope}"))?;
+        map.insert(name, value);
+    }
+    if let Some(user_agent) = user_agent {
+        let value = HeaderValue::from_str(user_agent)
+            .with_context(|| format!("Invalid user_agent in {scope}"))?;
+        map.insert(USER_AGENT, value);
+    }
+    Ok(map)
+}
+
+fn domain_header_maps(
+    domains: &BTreeMap<String, FetchDomainSettings>,
+) -> Result<BTreeMap<String, HeaderMap>> {
+    domains
+        .iter()
+        .map(|(domain, settings)| {
+            let scope = format!("[fetch.domains.{domain:?}]");
+            let headers = header_map(&scope, settings.user_agent.as_deref(), &settings.headers)?;
+            Ok((domain.trim_start_matches('.').to_lowercase(), headers))
+        })
+        .collect()
+}
+
+/// Read the root certificates of a PEM bundle, which may hold several
+fn load_ca_bundle(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    let pem = std::fs::read(path).with_context(|| format!("Failed to read CA bundle {path:?}"))?;
+    let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("Invalid CA bundle {path:?}"))?;
+    anyhow::ensure!(
+        !certificates.is_empty(),
+        "No certificates found in CA bundle {path:?}"
+    );
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_domain_header_maps() {
+        let mut domains = BTreeMap::new();
+        domains.insert(
+            ".News.example.com".to_string(),
+            FetchDomainSettings {
+                user_agent: Some("Mozilla/5.0".to_string()),
+                headers: [("Accept-Language".to_string(), "ja".to_string())].into(),
+            },
+        );
+        let maps = domain_header_maps(&domains).unwrap();
+        let headers =
+            credentials::for_domain(&maps, "https://www.news.example.com/article").unwrap();
+        assert_eq!(headers[USER_AGENT], "Mozilla/5.0");
+        assert_eq!(headers["accept-language"], "ja");
+        assert!(credentials::for_domain(&maps, "https://example.com/").is_none());
+
+        domains.insert(
+            "bad.example.com".to_string(),
+            FetchDomainSettings {
+                user_agent: Some("line\nbreak".to_string()),
+                headers: BTreeMap::new(),
+            },
+        );
+        let err = domain_header_maps(&domains).unwrap_err();
+        assert!(err.to_string().contains("bad.example.com"));
+    }
+
+    #[test]
+    fn test_load_ca_bundle_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("ca.pem");
+        assert!(load_ca_bundle(&path).is_err());
+
+        std::fs::write(&path, "not a certificate\n").unwrap();
+        let err = load_ca_bundle(&path).unwrap_err();
+        assert!(err.to_string().contains("ca.pem"));
+    }
+
+    #[test]
+    fn test_cached_robots_expiry() {
+        let now = Instant::now();
+        let fetched = CachedRobots {
+            rules: Arc::new(RobotsRules::allow_all()),
+            expires: None,
+        };
+        assert!(fetched.rules_at(now + ROBOTS_RETRY_AFTER * 10).is_some());
+
+        let failed = CachedRobots {
+            rules: Arc::new(RobotsRules::disallow_all()),
+            expires: Some(now + ROBOTS_RETRY_AFTER),
+        };
+        assert!(failed.rules_at(now).is_some());
+        assert!(failed.rules_at(now + ROBOTS_RETRY_AFTER).is_none());
+    }
+
+    #[test]
+    fn test_classify_status() {
+        assert_eq!(
+            ContentFetcher::classify_status(StatusCode::OK),
+            LinkState::Ok
+        );
+        assert_eq!(
+            ContentFetcher::classify_status(StatusCode::FORBIDDEN),
+            LinkState::Ok
+        );
+        assert_eq!(
+            ContentFetcher::classify_status(StatusCode::NOT_FOUND),
+            LinkState::Dead
+        );
+        assert_eq!(
+            ContentFetcher::classify_status(StatusCode::GONE),
+            LinkState::Dead
+        );
+        assert_eq!(
+            ContentFetcher::classify_status(StatusCode::BAD_GATEWAY),
+            LinkState::Error
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use reqwest::header::{COOKIE, HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Cookies or header tokens for one domain, as written in the credentials file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DomainEntry {
+    cookie: Option<String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+/// Per-domain authentication for server-side requests
+///
+/// Loaded from a TOML file of the form
+///
+/// ```toml
+/// ["wiki.example.com"]
+/// cookie = "JSESSIONID=..."
+///
+/// ["confluence.example.com"]
+/// headers = { Authorization = "Bearer ..." }
+/// ```
+///
+/// A domain also covers its subdomains; the most specific one wins. The file
+/// is read on its own and never copied into an index.
+#[derive(Default)]
+pub struct Credentials {
+    domains: BTreeMap<String, HeaderMap>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secrets themselves
+        f.debug_struct("Credentials")
+            .field("domains", &self.domains.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Default location: `credentials.toml` next to the config file
+    pub fn default_path() -> Option<PathBuf> {
+        Some(
+            crate::config::Settings::path()?
+                .parent()?
+                .join("credentials.toml"),
+        )
+    }
+
+    /// The file from the settings (or the default location), empty if there is none
+    pub fn load_configured() -> Result<Self> {
+        match crate::config::settings()
+            .fetch
+            .credentials_file
+            .clone()
+            .or_else(Self::default_path)
+        {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load a credentials file, returning no credentials if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+        };
+        warn_if_shared(path);
+        Self::parse(&content).with_context(|| format!("Invalid credentials file {path:?}"))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let entries: BTreeMap<String, DomainEntry> = toml::from_str(content)?;
+        let mut domains = BTreeMap::new();
+        for (domain, entry) in entries {
+            let mut headers = HeaderMap::new();
+            if let Some(cookie) = entry.cookie {
+                headers.insert(COOKIE, header_value(&domain, &cookie)?);
+            }
+            for (name, value) in &entry.headers {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("Invalid header name {name:?} for {domain}"))?;
+                headers.insert(name, header_value(&domain, value)?);
+            }
+            domains.insert(domain.trim_start_matches('.').to_lowercase(), headers);
+        }
+        Ok(Self { domains })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Headers to send to a URL: those of the most specific domain covering its host
+    pub fn headers_for(&self, url: &str) -> Option<&HeaderMap> {
+        for_domain(&self.domains, url)
+    }
+}
+
+/// The entry of the most specific domain covering a URL's host
+///
+/// Keys must be lowercase domains without a leading dot.
+pub(crate) fn for_domain<'a, T>(domains: &'a BTreeMap<String, T>, url: &str) -> Option<&'a T> {
+    let host = url::Url::parse(url).ok()?.host_str()?.to_lowercase();
+    domains
+        .iter()
+        .filter(|(domain, _)| {
+            host == **domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        })
+        .max_by_key(|(domain, _)| domain.len())
+        .map(|(_, entry)| entry)
+}
+
+fn header_value(domain: &str, value: &str) -> Result<HeaderValue> {
+    let mut value = HeaderValue::from_str(value)
+        .with_context(|| format!("Invalid header value for {domain}"))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Credentials readable by other users are worth a warning
+#[cfg(unix)]
+fn warn_if_shared(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.permissions().mode() & 0o077 != 0 {
+            warn!(
+                "{:?} is readable by other users; restrict it with chmod 600",
+                path
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_shared(_path: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_for() {
+        let credentials = Credentials::parse(
+            r#"
+            ["example.com"]
+            cookie = "session=outer"
+
+            ["wiki.example.com"]
+            cookie = "session=wiki"
+            headers = { Authorization = "Bearer token", "X-Team" = "docs" }
+            "#,
+        )
+        .unwrap();
+
+        let wiki = credentials
+            .headers_for("https://wiki.example.com/display/DOC")
+            .unwrap();
+        assert_eq!(wiki[COOKIE], "session=wiki");
+        assert_eq!(wiki["authorization"], "Bearer token");
+        assert_eq!(wiki["x-team"], "docs");
+        assert!(wiki[COOKIE].is_sensitive());
+
+        let outer = credentials.headers_for("https://EXAMPLE.com/").unwrap();
+        assert_eq!(outer[COOKIE], "session=outer");
+        assert_eq!(
+            credentials
+                .headers_for("https://blog.example.com/")
+                .unwrap()[COOKIE],
+            "session=outer"
+        );
+        assert!(credentials.headers_for("https://notexample.com/").is_none());
+        assert!(credentials.headers_for("not a url").is_none());
+        assert!(!format!("{credentials:?}").contains("session"));
+
+        assert!(Credentials::parse("[\"a.com\"]\npassword = \"x\"").is_err());
+        assert!(Credentials::parse("[\"a.com\"]\ncookie = \"bad\\nvalue\"").is_err());
+    }
+}
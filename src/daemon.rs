@@ -0,0 +1,737 @@
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone,
+    Timelike, Utc,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::audit::{Mutation, MutationSource};
+use crate::config::{self, DaemonSettings};
+use crate::content::ContentFetcher;
+use crate::feeds::{self, FeedList};
+use crate::ingest::{self, IngestFailure};
+use crate::search::common::INDEX_METADATA_FILE;
+use crate::search::index_registry::IndexInfo;
+use crate::search::sync_metadata::SyncMetadata;
+use crate::search::unified_searcher::UnifiedSearcher;
+use crate::search::{IndexLock, SearchManager};
+
+/// State file in the data directory, read by `mcp-bookmark status`
+pub const STATE_FILE: &str = "daemon_state.json";
+
+/// Interval at which a running daemon rewrites its state file
+pub const HEARTBEAT: Duration = Duration::from_secs(60);
+
+/// How long a task waits for another process writing an index
+const LOCK_WAIT: Duration = Duration::from_secs(600);
+
+/// Holder recorded in the index lock while a task writes
+const LOCK_HOLDER: &str = "mcp-bookmark daemon";
+
+/// A cron-like schedule: `minute hour day month weekday`, in local time
+///
+/// Fields take `*`, values, ranges (`1-5`), lists (`1,15`) and steps (`*/15`,
+/// `0-30/10`); weekdays run from 0 (Sunday) to 7 (Sunday again). As in cron,
+/// a day is matched by either the day or the weekday field when both are set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    day_or_weekday: bool,
+}
+
+impl Schedule {
+    /// Parse a schedule, also accepting `@hourly`, `@daily`, `@weekly` and `@monthly`
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        let fields = match expression {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            fields => fields,
+        };
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            anyhow::bail!(
+                "Invalid schedule {expression:?}: expected minute hour day month weekday"
+            );
+        };
+        let field = |text: &str, min, max, name| {
+            parse_field(text, min, max)
+                .with_context(|| format!("Invalid {name} field in schedule {expression:?}"))
+        };
+
+        let mut weekdays = field(weekday, 0, 7, "weekday")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: field(minute, 0, 59, "minute")?,
+            hours: field(hour, 0, 23, "hour")?,
+            days: field(day, 1, 31, "day")?,
+            months: field(month, 1, 12, "month")?,
+            weekdays,
+            day_or_weekday: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+
+    /// The schedule as written
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.day_or_weekday {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// First minute after `after` the schedule fires at
+    ///
+    /// None if it never does within five years (e.g. on February 30th).
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let limit = after + ChronoDuration::days(5 * 366);
+        while time < limit {
+            if self.months & (1 << time.month()) == 0 {
+                let (year, month) = match time.month() {
+                    12 => (time.year() + 1, 1),
+                    month => (time.year(), month + 1),
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_day(time.date()) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + ChronoDuration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += ChronoDuration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    /// Next time the schedule fires after `after`, in local time
+    pub fn next_local(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut time = after.naive_local();
+        loop {
+            time = self.next_after(time)?;
+            // Local times skipped by a daylight saving change don't exist
+            if let Some(local) = Local.from_local_datetime(&time).earliest() {
+                if local > after {
+                    return Some(local);
+                }
+            }
+        }
+    }
+}
+
+/// Bitmask of the values a cron field allows
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let value = |text: &str| {
+        text.parse::<u32>()
+            .with_context(|| format!("{text:?} is not a number"))
+    };
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, value(step)?),
+            None => (part, 1),
+        };
+        anyhow::ensure!(step > 0, "Step of {part:?} must be positive");
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            // `5/15` runs from 5 to the end of the field's range
+            let start = value(range)?;
+            (start, if part.contains('/') { max } else { start })
+        };
+        anyhow::ensure!(
+            min <= start && start <= end && end <= max,
+            "{part:?} is outside {min}-{max}"
+        );
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// A maintenance task of the daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Task {
+    /// Fetch stale server-fetched pages again
+    Recrawl,
+    /// Index new feed entries
+    Feeds,
+    /// Merge index segments
+    Merge,
+    /// Refresh the index summaries in the state file
+    Metadata,
+}
+
+impl Task {
+    pub const ALL: [Task; 4] = [Task::Recrawl, Task::Feeds, Task::Merge, Task::Metadata];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Recrawl => "recrawl",
+            Self::Feeds => "feeds",
+            Self::Merge => "merge",
+            Self::Metadata => "metadata",
+        }
+    }
+
+    /// The task's schedule in the `[daemon]` settings (empty when off)
+    fn schedule(self, settings: &DaemonSettings) -> &str {
+        match self {
+            Self::Recrawl => &settings.recrawl,
+            Self::Feeds => &settings.feeds,
+            Self::Merge => &settings.merge,
+            Self::Metadata => &settings.metadata,
+        }
+    }
+}
+
+/// Schedule and last run of a task
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskState {
+    pub schedule: String,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_started: Option<DateTime<Utc>>,
+    pub last_finished: Option<DateTime<Utc>>,
+    /// What the last run did, one line per index
+    #[serde(default)]
+    pub last_result: Vec<String>,
+    /// Indexes the last run failed on
+    #[serde(default)]
+    pub last_errors: Vec<String>,
+    #[serde(default)]
+    pub runs: u64,
+}
+
+impl TaskState {
+    /// Whether the task is running now
+    pub fn is_running(&self) -> bool {
+        self.last_started > self.last_finished
+    }
+}
+
+/// `daemon_state.json`: what the daemon serves and what its tasks did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonState {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// Rewritten every `HEARTBEAT` while the daemon runs
+    pub heartbeat: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    /// `http://ADDR/mcp`, or `stdio`
+    pub serving: String,
+    /// Indexes served and maintained
+    pub indexes: Vec<String>,
+    pub tasks: BTreeMap<Task, TaskState>,
+    /// Index summaries from the last metadata run
+    #[serde(default)]
+    pub index_info: Vec<IndexInfo>,
+}
+
+impl DaemonState {
+    pub fn path() -> PathBuf {
+        config::data_dir().join(STATE_FILE)
+    }
+
+    /// State of the last daemon started, if any
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map(Some)
+                .with_context(|| format!("Invalid {path:?}")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {path:?}")),
+        }
+    }
+
+    /// Write the state file, replacing it in one step so `status` never reads half of it
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {temp:?}"))?;
+        std::fs::rename(&temp, &path).with_context(|| format!("Failed to write {path:?}"))
+    }
+
+    /// Whether the daemon is still running: not stopped, and its heartbeat recent
+    ///
+    /// A daemon that was killed leaves `stopped_at` unset; its heartbeat stops.
+    pub fn is_running(&self, now: DateTime<Utc>) -> bool {
+        let deadline = ChronoDuration::from_std(HEARTBEAT * 3).unwrap_or_default();
+        self.stopped_at.is_none() && now - self.heartbeat < deadline
+    }
+}
+
+fn lock(state: &Mutex<DaemonState>) -> MutexGuard<'_, DaemonState> {
+    state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Save the state, logging a failure: the daemon keeps running without it
+fn save(state: &DaemonState) {
+    if let Err(e) = state.save() {
+        warn!("Failed to save daemon state: {:#}", e);
+    }
+}
+
+/// Maintenance running in the background of `mcp-bookmark daemon`
+pub struct Daemon {
+    state: Arc<Mutex<DaemonState>>,
+    scheduler: tokio::task::JoinHandle<()>,
+    heartbeat: tokio::task::JoinHandle<()>,
+}
+
+impl Daemon {
+    /// Write the state file and start the scheduled tasks for `indexes`
+    ///
+    /// Fails on an invalid schedule before anything runs.
+    pub fn start(indexes: Vec<String>, serving: String, settings: &DaemonSettings) -> Result<Self> {
+        let mut schedules = Vec::new();
+        for task in Task::ALL {
+            let expression = task.schedule(settings);
+            if expression.trim().is_empty() {
+                continue;
+            }
+            let schedule = Schedule::parse(expression)
+                .with_context(|| format!("Invalid [daemon] {} schedule", task.name()))?;
+            schedules.push((task, schedule));
+        }
+
+        let now = Local::now();
+        let tasks = schedules
+            .iter()
+            .map(|(task, schedule)| {
+                let state = TaskState {
+                    schedule: schedule.expression().to_string(),
+                    next_run: schedule
+                        .next_local(now)
+                        .map(|time| time.with_timezone(&Utc)),
+                    ..TaskState::default()
+                };
+                (*task, state)
+            })
+            .collect();
+        let state = DaemonState {
+            pid: std::process::id(),
+            started_at: now.with_timezone(&Utc),
+            heartbeat: now.with_timezone(&Utc),
+            stopped_at: None,
+            serving,
+            indexes: indexes.clone(),
+            tasks,
+            index_info: Vec::new(),
+        };
+        state.save()?;
+        for (task, schedule) in &schedules {
+            info!(
+                "Daemon task {} scheduled at {}",
+                task.name(),
+                schedule.expression()
+            );
+        }
+
+        let maintenance = Maintenance {
+            indexes,
+            fetcher: ContentFetcher::with_timeout(Duration::from_secs(
+                config::settings().fetch.timeout_secs,
+            ))?,
+            stale_after: Duration::from_secs(settings.stale_after_days * 24 * 60 * 60),
+            recrawl_limit: settings.recrawl_limit,
+        };
+        let state = Arc::new(Mutex::new(state));
+        let scheduler = tokio::spawn(run_schedules(schedules, maintenance, state.clone()));
+        let heartbeat = tokio::spawn(beat(state.clone()));
+        Ok(Self {
+            state,
+            scheduler,
+            heartbeat,
+        })
+    }
+
+    /// Stop the tasks and record the daemon as stopped
+    pub fn stop(self) -> Result<()> {
+        self.scheduler.abort();
+        self.heartbeat.abort();
+        let mut state = lock(&self.state);
+        state.stopped_at = Some(Utc::now());
+        state.save()
+    }
+}
+
+/// Rewrite the heartbeat, also while a long task runs
+async fn beat(state: Arc<Mutex<DaemonState>>) {
+    let mut interval = tokio::time::interval(HEARTBEAT);
+    loop {
+        interval.tick().await;
+        let mut current = lock(&state);
+        current.heartbeat = Utc::now();
+        save(&current);
+    }
+}
+
+/// Run each task when its schedule fires, one task at a time
+async fn run_schedules(
+    schedules: Vec<(Task, Schedule)>,
+    maintenance: Maintenance,
+    state: Arc<Mutex<DaemonState>>,
+) {
+    let mut next_runs: Vec<_> = schedules
+        .into_iter()
+        .map(|(task, schedule)| {
+            let next = schedule.next_local(Local::now());
+            (task, schedule, next)
+        })
+        .collect();
+
+    loop {
+        let Some(next) = next_runs.iter().filter_map(|(_, _, next)| *next).min() else {
+            info!("No daemon task is scheduled to run again");
+            return;
+        };
+        let wait = (next - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        for (task, schedule, next) in &mut next_runs {
+            if next.is_none_or(|next| next > Local::now()) {
+                continue;
+            }
+            {
+                let mut current = lock(&state);
+                current.tasks.entry(*task).or_default().last_started = Some(Utc::now());
+                save(&current);
+            }
+
+            info!("Running daemon task {}", task.name());
+            let outcome = maintenance.run(*task).await;
+            *next = schedule.next_local(Local::now());
+
+            let mut current = lock(&state);
+            if let Some(index_info) = outcome.index_info {
+                current.index_info = index_info;
+            }
+            let task_state = current.tasks.entry(*task).or_default();
+            task_state.last_finished = Some(Utc::now());
+            task_state.next_run = next.map(|time| time.with_timezone(&Utc));
+            task_state.last_result = outcome.done;
+            task_state.last_errors = outcome.errors;
+            task_state.runs += 1;
+            for error in &task_state.last_errors {
+                warn!("Daemon task {}: {}", task.name(), error);
+            }
+            save(&current);
+        }
+    }
+}
+
+/// What one run of a task did
+#[derive(Debug, Default)]
+struct Outcome {
+    done: Vec<String>,
+    errors: Vec<String>,
+    index_info: Option<Vec<IndexInfo>>,
+}
+
+/// The tasks and what they act on
+struct Maintenance {
+    indexes: Vec<String>,
+    fetcher: ContentFetcher,
+    stale_after: Duration,
+    recrawl_limit: usize,
+}
+
+impl Maintenance {
+    async fn run(&self, task: Task) -> Outcome {
+        let mut outcome = Outcome::default();
+        if task == Task::Metadata {
+            outcome.index_info = Some(Vec::new());
+        }
+        for index in &self.indexes {
+            if !config::data_dir()
+                .join(index)
+                .join(INDEX_METADATA_FILE)
+                .exists()
+            {
+                outcome.errors.push(format!("{index}: index not found"));
+                continue;
+            }
+            let result = match task {
+                Task::Recrawl => self.recrawl(index).await,
+                Task::Feeds => self.refresh_feeds(index).await,
+                Task::Merge => merge(index).await,
+                Task::Metadata => {
+                    IndexInfo::read(index, &config::data_dir().join(index)).map(|info| {
+                        let summary = format!(
+                            "{index}: {} bookmarks, {} documents",
+                            info.bookmark_count, info.document_count
+                        );
+                        if let Some(index_info) = &mut outcome.index_info {
+                            index_info.push(info);
+                        }
+                        Some(summary)
+                    })
+                }
+            };
+            match result {
+                Ok(Some(done)) => outcome.done.push(done),
+                Ok(None) => {}
+                Err(e) => outcome.errors.push(format!("{index}: {e:#}")),
+            }
+        }
+        outcome
+    }
+
+    /// Fetch the oldest pages indexed more than `stale_after` ago again
+    async fn recrawl(&self, index: &str) -> Result<Option<String>> {
+        let report = recrawl_stale(
+            &self.fetcher,
+            index,
+            self.stale_after,
+            self.recrawl_limit,
+            Some(LOCK_WAIT),
+        )
+        .await?;
+        if report.recrawled == 0 && report.failed.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(format!(
+            "{index}: {} re-crawled, {} failed",
+            report.recrawled,
+            report.failed.len()
+        )))
+    }
+
+    /// Index new entries of the index's feeds, if it has any
+    async fn refresh_feeds(&self, index: &str) -> Result<Option<String>> {
+        if !FeedList::path_for(index).exists() {
+            return Ok(None);
+        }
+        let refreshes = feeds::refresh_index(
+            &self.fetcher,
+            index,
+            feeds::DEFAULT_MAX_ENTRIES,
+            MutationSource::Daemon,
+            Some(LOCK_WAIT),
+        )
+        .await?;
+        let indexed: usize = refreshes.iter().map(|refresh| refresh.indexed).sum();
+        let failed = refreshes
+            .iter()
+            .filter(|refresh| refresh.error.is_some())
+            .count();
+        Ok(Some(format!(
+            "{index}: {indexed} new entries from {} feeds ({failed} unreachable)",
+            refreshes.len()
+        )))
+    }
+}
+
+/// Merge an index's segments under its lock
+async fn merge(index: &str) -> Result<Option<String>> {
+    let index_dir = config::data_dir().join(index);
+    let segments = tokio::task::spawn_blocking(move || {
+        let _lock = IndexLock::acquire(&index_dir, LOCK_HOLDER, Some(LOCK_WAIT))?;
+        let manager =
+            SearchManager::new_with_dictionary(index_dir, config::settings().tokenizer.dictionary)?;
+        manager.merge_segments()
+    })
+    .await
+    .context("Merge task panicked")??;
+    Ok((segments > 1).then(|| format!("{index}: merged {segments} segments")))
+}
+
+/// Result of re-crawling an index's stale pages
+#[derive(Debug, Default, Serialize)]
+pub struct RecrawlReport {
+    pub recrawled: usize,
+    pub skipped_robots: Vec<String>,
+    pub failed: Vec<IngestFailure>,
+}
+
+/// Fetch pages the server indexed more than `stale_after` ago again, oldest first
+///
+/// Only pages fetched by the server (`add_bookmark`, `ingest`, feeds) are
+/// re-crawled; the extension keeps its own pages current. Titles, folders,
+/// tags and dates added are kept. Pages that fail stay as they are and are
+/// tried again next time.
+pub async fn recrawl_stale(
+    fetcher: &ContentFetcher,
+    index: &str,
+    stale_after: Duration,
+    limit: usize,
+    wait: Option<Duration>,
+) -> Result<RecrawlReport> {
+    let index_dir = config::data_dir().join(index);
+    let cutoff = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .saturating_sub(stale_after)
+        .as_secs();
+    let metadata = SyncMetadata::load(&index_dir)?;
+    let stale = metadata.fetched_before(cutoff);
+    let mut report = RecrawlReport::default();
+    if stale.is_empty() {
+        return Ok(report);
+    }
+
+    let searcher = UnifiedSearcher::open_readonly(&index_dir)?;
+    let mut pages = Vec::new();
+    for (id, synced) in stale.into_iter().take(limit) {
+        // Deleted from the index since; the extension's next sync drops the metadata
+        let Some(entry) = searcher.get_bookmark_by_id(id)? else {
+            continue;
+        };
+        if !fetcher.robots_allows(&synced.url).await {
+            report.skipped_robots.push(synced.url.clone());
+            continue;
+        }
+        let folder_path = entry
+            .folder_path
+            .split('/')
+            .filter(|folder| !folder.is_empty())
+            .map(str::to_string)
+            .collect();
+        match ingest::fetch_page(fetcher, &synced.url, Some(&entry.title), folder_path).await {
+            Ok(mut page) => {
+                page.bookmark.id = id.to_string();
+                page.bookmark.tags = entry.tags;
+                page.bookmark.date_added =
+                    (entry.date_added != 0).then(|| entry.date_added.to_string());
+                pages.push(page);
+            }
+            Err(e) => report.failed.push(IngestFailure {
+                url: synced.url.clone(),
+                error: format!("{e:#}"),
+            }),
+        }
+    }
+
+    report.recrawled = pages.len();
+    if !pages.is_empty() {
+        let index = index.to_string();
+        tokio::task::spawn_blocking(move || {
+            ingest::write_pages(
+                &index,
+                config::settings().tokenizer.dictionary,
+                &pages,
+                MutationSource::Daemon,
+                Mutation::Recrawl,
+                LOCK_HOLDER,
+                wait,
+            )
+        })
+        .await
+        .context("Indexing task panicked")??;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expression: &str, after: &str) -> Option<NaiveDateTime> {
+        Schedule::parse(expression).unwrap().next_after(at(after))
+    }
+
+    #[test]
+    fn test_schedule_next_after() {
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01 10:07"),
+            Some(at("2024-01-01 10:15"))
+        );
+        // The current minute has already fired
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01 10:15"),
+            Some(at("2024-01-01 10:30"))
+        );
+        assert_eq!(
+            next("@hourly", "2024-01-01 23:59"),
+            Some(at("2024-01-02 00:00"))
+        );
+        assert_eq!(
+            next("30 4 * * 0", "2024-01-01 12:00"),
+            Some(at("2024-01-07 04:30"))
+        );
+        assert_eq!(
+            next("0 9 * * 1-5", "2024-01-05 10:00"),
+            Some(at("2024-01-08 09:00"))
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01 00:00"),
+            Some(at("2028-02-29 00:00"))
+        );
+        // Either the day or the weekday, as in cron
+        assert_eq!(
+            next("0 0 15 * 7", "2024-01-01 00:00"),
+            Some(at("2024-01-07 00:00"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2024-01-01 00:00"), None);
+    }
+
+    #[test]
+    fn test_schedule_parse_errors() {
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("@yearly").is_err());
+        assert!(Schedule::parse("0 8 * * mon").is_err());
+        assert_eq!(
+            Schedule::parse("0,30 8-18/2 * * *").unwrap().hours,
+            (8..=18).step_by(2).map(|hour| 1 << hour).sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_state_is_running() {
+        let now = Utc::now();
+        let mut state = DaemonState {
+            pid: 1,
+            started_at: now,
+            heartbeat: now,
+            stopped_at: None,
+            serving: "stdio".to_string(),
+            indexes: vec!["work".to_string()],
+            tasks: BTreeMap::new(),
+            index_info: Vec::new(),
+        };
+        assert!(state.is_running(now));
+        assert!(!state.is_running(now + ChronoDuration::minutes(10)));
+        state.stopped_at = Some(now);
+        assert!(!state.is_running(now));
+    }
+}
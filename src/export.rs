@@ -0,0 +1,431 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use tracing::debug;
+
+use crate::search::common::timestamp_to_datetime;
+use crate::search::query_parser::{QueryParser, QueryTerm};
+use crate::search::search_manager_trait::SearchManagerTrait;
+use crate::search::{BookmarkEntry, SearchResult};
+
+/// Longest file name stem for vault notes (in characters)
+const MAX_NOTE_NAME_CHARS: usize = 100;
+
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_DIM: &str = "\x1b[2m";
+// Underlined yellow, ended without a full reset so bold titles stay bold
+const ANSI_HIGHLIGHT: &str = "\x1b[4;33m";
+const ANSI_HIGHLIGHT_END: &str = "\x1b[24;39m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Output format for exported search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Human-readable listing for the terminal
+    Text,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Parse a format name as given on the command line
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "text" | "txt" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Write search results in the given format
+pub fn write_results<W: Write>(
+    results: &[SearchResult],
+    format: ExportFormat,
+    mut writer: W,
+) -> Result<()> {
+    match format {
+        ExportFormat::Text => write_text(results, None, &mut writer)?,
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, results)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            csv.write_record([
+                "title",
+                "url",
+                "folder_path",
+                "score",
+                "page_number",
+                "snippet",
+            ])?;
+            for result in results {
+                csv.write_record([
+                    result.title.as_str(),
+                    result.url.as_str(),
+                    result.folder_path.as_str(),
+                    &format!("{:.3}", result.score),
+                    &result
+                        .page_number
+                        .map(|p| p.to_string())
+                        .unwrap_or_default(),
+                    &single_line(&result.snippet),
+                ])?;
+            }
+            csv.flush().context("Failed to write CSV")?;
+        }
+        ExportFormat::Markdown => {
+            for (i, result) in results.iter().enumerate() {
+                let title = if result.title.is_empty() {
+                    &result.url
+                } else {
+                    &result.title
+                };
+                writeln!(
+                    writer,
+                    "{}. [{}]({})",
+                    i + 1,
+                    title.replace('[', "\\[").replace(']', "\\]"),
+                    result.url
+                )?;
+                if !result.folder_path.is_empty() {
+                    writeln!(writer, "   - Folder: {}", result.folder_path)?;
+                }
+                if let Some(page) = result.page_number {
+                    writeln!(writer, "   - Page: {page}")?;
+                }
+                let snippet = single_line(&result.snippet);
+                if !snippet.is_empty() {
+                    writeln!(writer, "   > {snippet}")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a plain-text listing of search results
+///
+/// With a highlighter (see [`Highlighter::new`]) titles are bold, folders dim
+/// and query terms in titles and snippets highlighted with ANSI escapes.
+pub fn write_text<W: Write>(
+    results: &[SearchResult],
+    highlighter: Option<&Highlighter>,
+    mut writer: W,
+) -> Result<()> {
+    let style = |code: &str, text: &str| match highlighter {
+        Some(_) => format!("{code}{text}{ANSI_RESET}"),
+        None => text.to_string(),
+    };
+    let highlight = |text: &str| match highlighter {
+        Some(h) => h.highlight(text),
+        None => text.to_string(),
+    };
+
+    for (i, result) in results.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        let title = if result.title.is_empty() {
+            &result.url
+        } else {
+            &result.title
+        };
+        writeln!(writer, "{}. {}", i + 1, style(ANSI_BOLD, &highlight(title)))?;
+        writeln!(writer, "   {}", result.url)?;
+        if !result.folder_path.is_empty() {
+            writeln!(writer, "   {}", style(ANSI_DIM, &result.folder_path))?;
+        }
+        if let Some(page) = result.page_number {
+            writeln!(writer, "   Page {page}")?;
+        }
+        let snippet = single_line(&result.snippet);
+        if !snippet.is_empty() {
+            writeln!(writer, "   {}", highlight(&snippet))?;
+        }
+    }
+    Ok(())
+}
+
+/// Highlights the words and phrases of a search query in text
+#[derive(Debug)]
+pub struct Highlighter {
+    pattern: Option<Regex>,
+}
+
+impl Highlighter {
+    /// Build a case-insensitive highlighter for the terms of `query`
+    pub fn new(query: &str) -> Self {
+        let mut terms: Vec<String> = QueryParser::parse(query)
+            .into_iter()
+            .map(|term| match term {
+                QueryTerm::Phrase(text) | QueryTerm::Word(text) | QueryTerm::Code(text) => text,
+            })
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        // Longer terms first so "rustacean" wins over "rust"
+        terms.sort_by_key(|term| std::cmp::Reverse(term.chars().count()));
+
+        let pattern = (!terms.is_empty()).then(|| {
+            let alternatives: Vec<String> = terms.iter().map(|t| regex::escape(t)).collect();
+            Regex::new(&format!("(?i){}", alternatives.join("|"))).expect("escaped terms")
+        });
+        Self { pattern }
+    }
+
+    /// Wrap every match in ANSI highlight escapes
+    pub fn highlight(&self, text: &str) -> String {
+        match &self.pattern {
+            Some(pattern) => pattern
+                .replace_all(text, |caps: &regex::Captures| {
+                    format!("{ANSI_HIGHLIGHT}{}{ANSI_HIGHLIGHT_END}", &caps[0])
+                })
+                .into_owned(),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Write one Markdown note per indexed bookmark into `dir`
+///
+/// Notes are named after the bookmark title; existing notes with the same
+/// name are overwritten so a vault can be refreshed by exporting again.
+pub async fn export_vault(manager: &dyn SearchManagerTrait, dir: &Path) -> Result<usize> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {dir:?}"))?;
+
+    let entries = manager.list_bookmarks().await?;
+    let mut used_names = HashSet::new();
+    for entry in &entries {
+        let content = manager.get_content_by_url(&entry.url).await?;
+        let path = dir.join(note_file_name(entry, &mut used_names));
+        std::fs::write(&path, bookmark_note(entry, content.as_deref()))
+            .with_context(|| format!("Failed to write {path:?}"))?;
+    }
+
+    debug!("Exported {} notes to {:?}", entries.len(), dir);
+    Ok(entries.len())
+}
+
+/// Render a bookmark as Markdown with YAML frontmatter
+pub fn bookmark_note(entry: &BookmarkEntry, content: Option<&str>) -> String {
+    // JSON strings are valid YAML scalars, which takes care of quoting
+    let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let date = |timestamp| timestamp_to_datetime(timestamp).map(|d| d.to_rfc3339());
+
+    let mut note = String::from("---\n");
+    note.push_str(&format!("title: {}\n", quote(&entry.title)));
+    note.push_str(&format!("url: {}\n", quote(&entry.url)));
+    if !entry.folder_path.is_empty() {
+        note.push_str(&format!("folder: {}\n", quote(&entry.folder_path)));
+    }
+    if let Some(added) = date(entry.date_added) {
+        note.push_str(&format!("date_added: {added}\n"));
+    }
+    if let Some(modified) = date(entry.date_modified) {
+        note.push_str(&format!("date_modified: {modified}\n"));
+    }
+    if !entry.tags.is_empty() {
+        let tags: Vec<String> = entry.tags.iter().map(|t| quote(t)).collect();
+        note.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    }
+    note.push_str("---\n\n");
+
+    let title = if entry.title.is_empty() {
+        &entry.url
+    } else {
+        &entry.title
+    };
+    note.push_str(&format!("# {title}\n\n"));
+    if let Some(content) = content.map(str::trim).filter(|c| !c.is_empty()) {
+        note.push_str(content);
+        note.push('\n');
+    }
+    note
+}
+
+/// File name for a bookmark's note, unique among `used` (case-insensitive)
+fn note_file_name(entry: &BookmarkEntry, used: &mut HashSet<String>) -> String {
+    let source = if entry.title.trim().is_empty() {
+        &entry.url
+    } else {
+        &entry.title
+    };
+    let stem: String = source
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .take(MAX_NOTE_NAME_CHARS)
+        .collect();
+    let stem = stem.trim().trim_matches('.').to_string();
+    let stem = if stem.is_empty() {
+        entry.id.clone()
+    } else {
+        stem
+    };
+
+    let mut name = format!("{stem}.md");
+    if !used.insert(name.to_lowercase()) {
+        name = format!("{stem} ({}).md", entry.id);
+        used.insert(name.to_lowercase());
+    }
+    name
+}
+
+/// Collapse whitespace so a snippet fits on one line
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str, url: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            id: "1".to_string(),
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: snippet.to_string(),
+            score: 1.5,
+            folder_path: "Bookmarks Bar/Rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn export(results: &[SearchResult], format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        write_results(results, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(ExportFormat::parse("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("md"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse("text"), Some(ExportFormat::Text));
+        assert_eq!(ExportFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let results = vec![result(
+            "Rust, the book",
+            "https://doc.rust-lang.org/book/",
+            "a\nb",
+        )];
+        let csv = export(&results, ExportFormat::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("title,url,folder_path,score,page_number,snippet")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "\"Rust, the book\",https://doc.rust-lang.org/book/,Bookmarks Bar/Rust,1.500,,a b"
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_markdown() {
+        let results = vec![result("[RFC] Async", "https://example.com/", "snippet")];
+        let md = export(&results, ExportFormat::Markdown);
+        assert_eq!(
+            md,
+            "1. [\\[RFC\\] Async](https://example.com/)\n   - Folder: Bookmarks Bar/Rust\n   > snippet\n"
+        );
+    }
+
+    #[test]
+    fn test_write_text() {
+        let results = vec![result(
+            "Rust Book",
+            "https://doc.rust-lang.org/book/",
+            "Learn rust\nfast",
+        )];
+        assert_eq!(
+            export(&results, ExportFormat::Text),
+            "1. Rust Book\n   https://doc.rust-lang.org/book/\n   Bookmarks Bar/Rust\n   Learn rust fast\n"
+        );
+
+        let highlighter = Highlighter::new("RUST");
+        let mut out = Vec::new();
+        write_text(&results, Some(&highlighter), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("1. \x1b[1m\x1b[4;33mRust\x1b[24;39m Book\x1b[0m\n"));
+        assert!(text.ends_with("   Learn \x1b[4;33mrust\x1b[24;39m fast\n"));
+    }
+
+    #[test]
+    fn test_highlighter() {
+        let highlighter = Highlighter::new("\"async fn\" a.b");
+        assert_eq!(
+            highlighter.highlight("Async fn in a.b, not axb"),
+            "\x1b[4;33mAsync fn\x1b[24;39m in \x1b[4;33ma.b\x1b[24;39m, not axb"
+        );
+        assert_eq!(Highlighter::new("").highlight("text"), "text");
+    }
+
+    fn entry(id: &str, title: &str) -> BookmarkEntry {
+        BookmarkEntry {
+            id: id.to_string(),
+            url: format!("https://example.com/{id}"),
+            title: title.to_string(),
+            folder_path: "Bookmarks Bar/Rust".to_string(),
+            content_hash: String::new(),
+            simhash: 0,
+            date_added: 13_344_473_600_000_000,
+            date_modified: 0,
+            tags: vec!["rust".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_bookmark_note() {
+        let note = bookmark_note(&entry("1", "Rust: \"The Book\""), Some("Chapter 1\n"));
+        assert_eq!(
+            note,
+            "---\n\
+             title: \"Rust: \\\"The Book\\\"\"\n\
+             url: \"https://example.com/1\"\n\
+             folder: \"Bookmarks Bar/Rust\"\n\
+             date_added: 2023-11-14T22:13:20+00:00\n\
+             tags: [\"rust\"]\n\
+             ---\n\n\
+             # Rust: \"The Book\"\n\n\
+             Chapter 1\n"
+        );
+    }
+
+    #[test]
+    fn test_note_file_name() {
+        let mut used = HashSet::new();
+        assert_eq!(
+            note_file_name(&entry("1", "Rust: A/B"), &mut used),
+            "Rust- A-B.md"
+        );
+        assert_eq!(
+            note_file_name(&entry("2", "rust: a/b"), &mut used),
+            "rust- a-b (2).md"
+        );
+        assert_eq!(note_file_name(&entry("3", "..."), &mut used), "3.md");
+    }
+
+    #[test]
+    fn test_write_json() {
+        let results = vec![result("Rust", "https://www.rust-lang.org/", "")];
+        let json: serde_json::Value =
+            serde_json::from_str(&export(&results, ExportFormat::Json)).unwrap();
+        assert_eq!(json[0]["url"], "https://www.rust-lang.org/");
+    }
+}
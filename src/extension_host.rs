@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::browser::chromium::ChromiumBrowser;
+
+/// Name the extension connects to with `chrome.runtime.connectNative`
+pub const HOST_NAME: &str = "com.mcp_bookmark";
+
+/// File name of the native messaging host binary, installed next to `mcp-bookmark`
+pub const HOST_BINARY: &str = if cfg!(target_os = "windows") {
+    "mcp-bookmark-native.exe"
+} else {
+    "mcp-bookmark-native"
+};
+
+/// Native messaging host manifest, as Chrome reads it
+#[derive(Debug, Clone, Serialize)]
+pub struct HostManifest {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub allowed_origins: Vec<String>,
+}
+
+impl HostManifest {
+    /// Manifest letting the given extensions start the host binary
+    pub fn new(host_path: PathBuf, extension_ids: &[String]) -> Self {
+        Self {
+            name: HOST_NAME,
+            description: "MCP Bookmark Native Messaging Host",
+            path: host_path,
+            kind: "stdio",
+            allowed_origins: extension_ids
+                .iter()
+                .map(|id| format!("chrome-extension://{id}/"))
+                .collect(),
+        }
+    }
+}
+
+/// Where one browser's manifest was written
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledManifest {
+    pub browser: &'static str,
+    pub path: PathBuf,
+    /// Registry key to point at the manifest (Windows only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry_key: Option<String>,
+}
+
+/// Check an extension ID: 32 letters from `a` to `p`, as shown on chrome://extensions
+pub fn validate_extension_id(id: &str) -> Result<()> {
+    anyhow::ensure!(
+        id.len() == 32 && id.bytes().all(|b| (b'a'..=b'p').contains(&b)),
+        "Invalid extension ID {id:?}: expected 32 letters a-p, as shown on chrome://extensions"
+    );
+    Ok(())
+}
+
+/// The host binary installed next to the running executable
+pub fn default_host_path() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to locate the running executable")?;
+    let dir = exe
+        .parent()
+        .context("The running executable has no parent directory")?;
+    Ok(dir.join(HOST_BINARY))
+}
+
+/// Directory a browser reads per-user native messaging manifests from
+///
+/// On Windows the browser finds the manifest through the registry instead, so
+/// it is kept in the data directory.
+pub fn manifest_dir(browser: ChromiumBrowser) -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        return Some(crate::config::data_dir().join("native-messaging"));
+    }
+    Some(browser.user_data_dir()?.join("NativeMessagingHosts"))
+}
+
+/// Per-user registry key under which a browser looks up native messaging hosts
+fn registry_key(browser: ChromiumBrowser) -> String {
+    let vendor = match browser {
+        ChromiumBrowser::Chrome => r"Google\Chrome",
+        ChromiumBrowser::Edge => r"Microsoft\Edge",
+        ChromiumBrowser::Brave => r"BraveSoftware\Brave-Browser",
+        ChromiumBrowser::Vivaldi => r"Vivaldi",
+        ChromiumBrowser::Chromium => r"Chromium",
+    };
+    format!(r"HKCU\Software\{vendor}\NativeMessagingHosts\{HOST_NAME}")
+}
+
+/// Write the manifest for one browser into `dir`
+pub fn install_manifest(
+    browser: ChromiumBrowser,
+    dir: &Path,
+    manifest: &HostManifest,
+) -> Result<InstalledManifest> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {dir:?}"))?;
+    let path = dir.join(format!("{HOST_NAME}.json"));
+    std::fs::write(&path, serde_json::to_string_pretty(manifest)?)
+        .with_context(|| format!("Failed to write {path:?}"))?;
+    Ok(InstalledManifest {
+        browser: browser.name(),
+        path,
+        registry_key: cfg!(target_os = "windows").then(|| registry_key(browser)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const ID: &str = "abcdefghijklmnopabcdefghijklmnop";
+
+    #[test]
+    fn test_validate_extension_id() {
+        assert!(validate_extension_id(ID).is_ok());
+        assert!(validate_extension_id("abc").is_err());
+        assert!(validate_extension_id(&ID.to_uppercase()).is_err());
+        assert!(validate_extension_id("qrstuvwxyzqrstuvwxyzqrstuvwxyzqr").is_err());
+    }
+
+    #[test]
+    fn test_install_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("NativeMessagingHosts");
+        let manifest = HostManifest::new(
+            PathBuf::from("/opt/mcp-bookmark/mcp-bookmark-native"),
+            &[ID.to_string()],
+        );
+
+        let installed = install_manifest(ChromiumBrowser::Edge, &dir, &manifest).unwrap();
+        assert_eq!(installed.browser, "edge");
+        assert_eq!(installed.path, dir.join("com.mcp_bookmark.json"));
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&installed.path).unwrap()).unwrap();
+        assert_eq!(written["name"], HOST_NAME);
+        assert_eq!(written["type"], "stdio");
+        assert_eq!(written["path"], "/opt/mcp-bookmark/mcp-bookmark-native");
+        assert_eq!(
+            written["allowed_origins"][0],
+            format!("chrome-extension://{ID}/")
+        );
+    }
+}
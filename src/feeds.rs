@@ -0,0 +1,584 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tracing::info;
+use url::Url;
+
+use crate::audit::{Mutation, MutationSource};
+use crate::browser::{chrome_timestamp_from_rfc3339, chrome_timestamp_from_unix_micros};
+use crate::content::ContentFetcher;
+use crate::ingest::{self, IngestFailure, IngestedPage, UrlEntry};
+use crate::site::{fragment_text, single_line, unescape_html};
+
+/// Directory under the data directory holding the feed subscriptions of each index
+pub const FEEDS_DIR: &str = "feeds";
+
+/// Top folder of the entries indexed from feeds; each feed gets a folder below it
+pub const FEEDS_FOLDER: &str = "Feeds";
+
+/// Default number of new entries `feeds refresh` indexes per feed
+pub const DEFAULT_MAX_ENTRIES: usize = 50;
+
+/// Entry ids remembered per feed; older ones may be indexed again if the feed still lists them
+const MAX_SEEN: usize = 2000;
+
+static ROOT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(?:rss|feed|rdf:rdf)\b").expect("valid regex"));
+static ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<item\b[^>]*>(.*?)</item\s*>|<entry\b[^>]*>(.*?)</entry\s*>")
+        .expect("valid regex")
+});
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").expect("valid regex"));
+static RSS_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<link\b[^>]*>([^<]+)</link\s*>").expect("valid regex"));
+static ATOM_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<link\b([^>]*)>").expect("valid regex"));
+static HREF: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)\bhref\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+});
+static REL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)\brel\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+});
+static ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<guid\b[^>]*>(.*?)</guid\s*>|<id\b[^>]*>(.*?)</id\s*>").expect("valid regex")
+});
+static DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<pubDate\b[^>]*>(.*?)</pubDate\s*>|<published\b[^>]*>(.*?)</published\s*>|<updated\b[^>]*>(.*?)</updated\s*>|<dc:date\b[^>]*>(.*?)</dc:date\s*>",
+    )
+    .expect("valid regex")
+});
+static CONTENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<content:encoded\b[^>]*>(.*?)</content:encoded\s*>|<content\b[^>]*>(.*?)</content\s*>",
+    )
+    .expect("valid regex")
+});
+static SUMMARY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<description\b[^>]*>(.*?)</description\s*>|<summary\b[^>]*>(.*?)</summary\s*>",
+    )
+    .expect("valid regex")
+});
+
+/// One entry of a feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    /// `<guid>` or `<id>`, or else the link
+    pub id: String,
+    /// Link to the entry's page, as written
+    pub url: String,
+    pub title: Option<String>,
+    /// Publication date in Chrome's bookmark format
+    pub published: Option<String>,
+    /// Text of the entry's content or summary
+    pub summary: Option<String>,
+}
+
+/// An RSS 2.0, RSS 1.0 or Atom feed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedFeed {
+    pub title: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl ParsedFeed {
+    pub fn parse(xml: &str) -> Result<Self> {
+        anyhow::ensure!(ROOT.is_match(xml), "Not an RSS or Atom feed");
+        // The feed's own title comes before its first entry
+        let head = ITEM.find(xml).map_or(xml, |item| &xml[..item.start()]);
+        let entries = ITEM
+            .captures_iter(xml)
+            .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .filter_map(|item| parse_entry(item.as_str()))
+            .collect();
+        Ok(Self {
+            title: first_text(&TITLE, head),
+            entries,
+        })
+    }
+}
+
+fn parse_entry(item: &str) -> Option<FeedEntry> {
+    let url = RSS_LINK
+        .captures(item)
+        .map(|caps| unescape_html(caps[1].trim()))
+        .or_else(|| atom_link(item))
+        .filter(|url| !url.is_empty())?;
+    let published = first_text(&DATE, item).and_then(|date| {
+        chrome_timestamp_from_rfc3339(&date).or_else(|| {
+            DateTime::parse_from_rfc2822(&date)
+                .ok()
+                .map(|date| chrome_timestamp_from_unix_micros(date.timestamp_micros()))
+        })
+    });
+    Some(FeedEntry {
+        id: first_text(&ID, item).unwrap_or_else(|| url.clone()),
+        url,
+        title: first_text(&TITLE, item),
+        published,
+        // Full content when the feed has it, else the summary
+        summary: [&*CONTENT, &*SUMMARY]
+            .into_iter()
+            .filter_map(|re| re.captures(item))
+            .filter_map(|caps| caps.iter().skip(1).flatten().next())
+            .map(|body| fragment_text(&element_html(body.as_str())))
+            .find(|text| !text.is_empty()),
+    })
+}
+
+/// `href` of an Atom entry's alternate link (a link without `rel` is one)
+fn atom_link(item: &str) -> Option<String> {
+    let attribute = |re: &Regex, attributes: &str| {
+        re.captures(attributes)
+            .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .map(|value| unescape_html(value.as_str().trim()))
+    };
+    let links: Vec<_> = ATOM_LINK
+        .captures_iter(item)
+        .filter_map(|caps| {
+            let attributes = caps.get(1)?.as_str();
+            Some((attribute(&HREF, attributes)?, attribute(&REL, attributes)))
+        })
+        .collect();
+    links
+        .iter()
+        .find(|(_, rel)| rel.as_deref().is_none_or(|rel| rel == "alternate"))
+        .or(links.first())
+        .map(|(href, _)| href.clone())
+}
+
+/// HTML of an element's content: CDATA as is, otherwise with its entities decoded
+fn element_html(inner: &str) -> String {
+    let inner = inner.trim();
+    match inner
+        .strip_prefix("<![CDATA[")
+        .and_then(|rest| rest.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_string(),
+        None => unescape_html(inner),
+    }
+}
+
+/// Single-line text of the first match of `re`, from whichever group matched
+fn first_text(re: &Regex, xml: &str) -> Option<String> {
+    re.captures(xml)
+        .and_then(|caps| caps.iter().skip(1).flatten().next())
+        .map(|inner| single_line(&fragment_text(&element_html(inner.as_str()))))
+        .filter(|text| !text.is_empty())
+}
+
+/// A feed an index is subscribed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub url: String,
+    /// Feed title from its last refresh, naming its folder
+    pub title: Option<String>,
+    pub added_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_refreshed: Option<DateTime<Utc>>,
+    /// Ids of the entries already indexed, oldest first
+    #[serde(default)]
+    pub seen: Vec<String>,
+}
+
+impl Subscription {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            title: None,
+            added_at: Utc::now(),
+            last_refreshed: None,
+            seen: Vec::new(),
+        }
+    }
+
+    /// `["Feeds", feed title]`, the folder its entries are indexed under
+    pub fn folder_path(&self) -> Vec<String> {
+        vec![
+            FEEDS_FOLDER.to_string(),
+            self.title.clone().unwrap_or_else(|| self.url.clone()),
+        ]
+    }
+
+    /// Remember entries as indexed, forgetting the oldest beyond `MAX_SEEN`
+    pub fn mark_seen(&mut self, ids: &[String]) {
+        self.seen.extend(ids.iter().cloned());
+        let excess = self.seen.len().saturating_sub(MAX_SEEN);
+        self.seen.drain(..excess);
+    }
+}
+
+/// Feed subscriptions of one index, stored in `<data dir>/feeds/<index>.json`
+///
+/// Kept outside the index directory, like the audit log, so rebuilding an
+/// index from a browser keeps its feeds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedList {
+    pub feeds: Vec<Subscription>,
+}
+
+impl FeedList {
+    pub fn path_for(index: &str) -> PathBuf {
+        crate::config::data_dir()
+            .join(FEEDS_DIR)
+            .join(format!("{index}.json"))
+    }
+
+    /// Names of the indexes with a feed list
+    pub fn indexes() -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(crate::config::data_dir().join(FEEDS_DIR))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                path.file_stem()?.to_str().map(str::to_string)
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Load a list, empty if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str(&content).with_context(|| format!("Invalid {path:?}"))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {path:?}")),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {path:?}"))
+    }
+
+    pub fn get_mut(&mut self, url: &str) -> Option<&mut Subscription> {
+        self.feeds.iter_mut().find(|feed| feed.url == url)
+    }
+
+    /// Subscribe to a feed; false if the list already has it
+    pub fn add(&mut self, subscription: Subscription) -> bool {
+        if self.feeds.iter().any(|feed| feed.url == subscription.url) {
+            return false;
+        }
+        self.feeds.push(subscription);
+        true
+    }
+
+    /// Unsubscribe from a feed; false if the list doesn't have it
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.feeds.len();
+        self.feeds.retain(|feed| feed.url != url);
+        self.feeds.len() < before
+    }
+}
+
+/// Fetch and parse a feed
+pub async fn fetch_feed(fetcher: &ContentFetcher, url: &str) -> Result<ParsedFeed> {
+    let page = fetcher.fetch(url).await?;
+    anyhow::ensure!(page.is_success(), "{url} returned HTTP {}", page.status);
+    ParsedFeed::parse(&page.text()).with_context(|| format!("Failed to read the feed at {url}"))
+}
+
+/// New entries of one feed, fetched and ready to index
+#[derive(Debug, Default, Serialize)]
+pub struct FeedRefresh {
+    pub url: String,
+    pub title: Option<String>,
+    /// Entries not indexed before, up to the per-feed limit
+    pub new_entries: usize,
+    /// Entries written to the index
+    pub indexed: usize,
+    #[serde(skip)]
+    pub pages: Vec<IngestedPage>,
+    /// Entries indexed from the text in the feed because their page couldn't be fetched
+    pub from_feed: Vec<String>,
+    pub skipped_robots: Vec<String>,
+    pub failed: Vec<IngestFailure>,
+    /// Why the feed itself couldn't be read
+    pub error: Option<String>,
+    /// Ids to mark as seen once the pages are written
+    #[serde(skip)]
+    pub entry_ids: Vec<String>,
+}
+
+/// Fetch the entries of a feed that aren't indexed yet, newest feed title included
+///
+/// Entries whose page can't be fetched are indexed from the content or
+/// summary the feed carries, if any. Pages keep the entry's title and
+/// publication date and are filed under `["Feeds", feed title]`.
+pub async fn refresh(
+    fetcher: &ContentFetcher,
+    subscription: &Subscription,
+    max_entries: usize,
+) -> FeedRefresh {
+    let mut result = FeedRefresh {
+        url: subscription.url.clone(),
+        title: subscription.title.clone(),
+        ..FeedRefresh::default()
+    };
+    let feed = match fetch_feed(fetcher, &subscription.url).await {
+        Ok(feed) => feed,
+        Err(e) => {
+            result.error = Some(format!("{e:#}"));
+            return result;
+        }
+    };
+    if feed.title.is_some() {
+        result.title = feed.title;
+    }
+    let folder_path = Subscription {
+        title: result.title.clone(),
+        ..subscription.clone()
+    }
+    .folder_path();
+
+    let base = Url::parse(&subscription.url).ok();
+    let seen: HashSet<&str> = subscription.seen.iter().map(String::as_str).collect();
+    let mut urls = HashSet::new();
+    let entries: Vec<FeedEntry> = feed
+        .entries
+        .into_iter()
+        .filter(|entry| !seen.contains(entry.id.as_str()))
+        .map(|mut entry| {
+            if let Some(url) = base.as_ref().and_then(|base| base.join(&entry.url).ok()) {
+                entry.url = url.to_string();
+            }
+            entry
+        })
+        .filter(|entry| urls.insert(entry.url.clone()))
+        .take(max_entries)
+        .collect();
+    result.new_entries = entries.len();
+    if entries.is_empty() {
+        return result;
+    }
+
+    let urls = entries
+        .iter()
+        .map(|entry| UrlEntry {
+            url: entry.url.clone(),
+            title: entry.title.clone(),
+        })
+        .collect();
+    let report = ingest::fetch_all(
+        fetcher,
+        urls,
+        &folder_path,
+        ingest::DEFAULT_CONCURRENCY,
+        |_, _| {},
+    )
+    .await;
+    result.skipped_robots = report.skipped_robots;
+
+    let failed: HashMap<String, IngestFailure> = report
+        .failed
+        .into_iter()
+        .map(|failure| (failure.url.clone(), failure))
+        .collect();
+    let mut pages = report.pages.into_iter();
+    for entry in entries {
+        let mut page = if let Some(failure) = failed.get(&entry.url) {
+            let Some(summary) = &entry.summary else {
+                result.failed.push(failure.clone());
+                continue;
+            };
+            result.from_feed.push(entry.url.clone());
+            IngestedPage {
+                content: summary.clone(),
+                ..IngestedPage::without_content(
+                    &entry.url,
+                    entry.title.as_deref(),
+                    folder_path.clone(),
+                )
+            }
+        } else if result.skipped_robots.contains(&entry.url) {
+            // Not asked for again on the next refresh
+            result.entry_ids.push(entry.id);
+            continue;
+        } else {
+            // Fetched pages come back in the order of the entries
+            match pages.next() {
+                Some(page) => page,
+                None => continue,
+            }
+        };
+        if entry.published.is_some() {
+            page.bookmark.date_added = entry.published.clone();
+        }
+        result.pages.push(page);
+        result.entry_ids.push(entry.id);
+    }
+    result
+}
+
+/// Refresh every feed of an index and index their new entries in one commit
+///
+/// Entries are only remembered as seen once they are written, so a failed
+/// write fetches them again next time.
+pub async fn refresh_index(
+    fetcher: &ContentFetcher,
+    index: &str,
+    max_entries: usize,
+    source: MutationSource,
+    wait: Option<Duration>,
+) -> Result<Vec<FeedRefresh>> {
+    let path = FeedList::path_for(index);
+    let mut list = FeedList::load(&path)?;
+    let mut refreshes = Vec::new();
+    for subscription in &list.feeds {
+        info!("Refreshing feed {}", subscription.url);
+        refreshes.push(refresh(fetcher, subscription, max_entries).await);
+    }
+
+    let mut pages = Vec::new();
+    for refresh in &mut refreshes {
+        refresh.indexed = refresh.pages.len();
+        pages.append(&mut refresh.pages);
+    }
+    if !pages.is_empty() {
+        let index = index.to_string();
+        tokio::task::spawn_blocking(move || {
+            ingest::write_pages(
+                &index,
+                crate::config::settings().tokenizer.dictionary,
+                &pages,
+                source,
+                Mutation::FeedRefresh,
+                "mcp-bookmark feeds refresh",
+                wait,
+            )
+        })
+        .await
+        .context("Indexing task panicked")??;
+    }
+
+    let now = Utc::now();
+    for refresh in &refreshes {
+        if let Some(subscription) = list.get_mut(&refresh.url) {
+            if refresh.error.is_none() {
+                subscription.last_refreshed = Some(now);
+            }
+            if refresh.title.is_some() {
+                subscription.title = refresh.title.clone();
+            }
+            subscription.mark_seen(&refresh.entry_ids);
+        }
+    }
+    list.save(&path)?;
+    Ok(refreshes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = ParsedFeed::parse(
+            r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+<channel>
+  <title>Example &amp; Co Blog</title>
+  <link>https://blog.example.com/</link>
+  <item>
+    <title><![CDATA[Release <b>2.0</b>]]></title>
+    <link>https://blog.example.com/posts/2-0</link>
+    <guid isPermaLink="false">post-20</guid>
+    <pubDate>Tue, 02 Jan 2024 10:00:00 +0000</pubDate>
+    <description>&lt;p&gt;Short &amp;amp; sweet&lt;/p&gt;</description>
+    <content:encoded><![CDATA[<p>Full text</p><p>Second paragraph</p>]]></content:encoded>
+  </item>
+  <item>
+    <title>No body</title>
+    <link>/posts/relative</link>
+  </item>
+</channel>
+</rss>"#,
+        )
+        .unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example & Co Blog"));
+        assert_eq!(feed.entries.len(), 2);
+        let first = &feed.entries[0];
+        assert_eq!(first.id, "post-20");
+        assert_eq!(first.url, "https://blog.example.com/posts/2-0");
+        assert_eq!(first.title.as_deref(), Some("Release 2.0"));
+        assert_eq!(
+            first.published,
+            chrome_timestamp_from_rfc3339("2024-01-02T10:00:00Z")
+        );
+        assert_eq!(
+            first.summary.as_deref(),
+            Some("Full text\nSecond paragraph")
+        );
+        assert_eq!(feed.entries[1].id, "/posts/relative");
+        assert_eq!(feed.entries[1].summary, None);
+
+        assert!(ParsedFeed::parse("<html><body>not a feed</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let feed = ParsedFeed::parse(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Research notes</title>
+  <link rel="self" href="https://notes.example.com/atom.xml"/>
+  <entry>
+    <title>Attention, revisited</title>
+    <link rel="replies" href="https://notes.example.com/attention#comments"/>
+    <link href="https://notes.example.com/attention"/>
+    <id>tag:notes.example.com,2024:attention</id>
+    <updated>2024-03-01T08:30:00Z</updated>
+    <summary type="html">&lt;em&gt;Why&lt;/em&gt; it works</summary>
+  </entry>
+</feed>"#,
+        )
+        .unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Research notes"));
+        let entry = &feed.entries[0];
+        assert_eq!(entry.url, "https://notes.example.com/attention");
+        assert_eq!(entry.id, "tag:notes.example.com,2024:attention");
+        assert_eq!(entry.summary.as_deref(), Some("Why it works"));
+        assert!(entry.published.is_some());
+    }
+
+    #[test]
+    fn test_feed_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("research.json");
+        let mut list = FeedList::load(&path).unwrap();
+        assert!(list.add(Subscription::new("https://blog.example.com/feed")));
+        assert!(!list.add(Subscription::new("https://blog.example.com/feed")));
+
+        let feed = list.get_mut("https://blog.example.com/feed").unwrap();
+        assert_eq!(
+            feed.folder_path(),
+            vec!["Feeds", "https://blog.example.com/feed"]
+        );
+        feed.title = Some("Example Blog".to_string());
+        feed.mark_seen(&["a".to_string(), "b".to_string()]);
+        list.save(&path).unwrap();
+
+        let mut list = FeedList::load(&path).unwrap();
+        assert_eq!(list.feeds[0].folder_path(), vec!["Feeds", "Example Blog"]);
+        assert_eq!(list.feeds[0].seen, vec!["a", "b"]);
+        assert!(list.remove("https://blog.example.com/feed"));
+        assert!(!list.remove("https://blog.example.com/feed"));
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::search::search_manager_trait::SearchManagerTrait;
+
+/// Text run through the index's analyzer; the Japanese part exercises the dictionary
+const PROBE_TEXT: &str = "全文検索 full-text search";
+
+/// Query run against the index; any number of results passes
+const PROBE_QUERY: &str = "search";
+
+/// Outcome of one health check
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+    /// `index`, `tokenizer`, `query` or `data_dir`
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+    pub ms: f64,
+}
+
+/// Structured status of the server's dependencies
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Every check passed
+    pub healthy: bool,
+    pub index: Option<String>,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Check that the index opened, its tokenizer analyzes text, a trivial
+    /// query runs and the data directory is writable
+    ///
+    /// Checks that need the index are reported as failed when it didn't open.
+    pub async fn run(
+        index: Option<String>,
+        manager: Result<Arc<dyn SearchManagerTrait>>,
+        data_dir: &Path,
+    ) -> Self {
+        let mut checks = Vec::new();
+        match manager {
+            Ok(manager) => {
+                checks.push(timed("index", || {
+                    manager
+                        .get_stats()
+                        .map(|stats| format!("{} documents", stats.total_documents))
+                }));
+                checks.push(timed("tokenizer", || {
+                    let tokens = manager.tokenize(PROBE_TEXT, "content")?;
+                    anyhow::ensure!(!tokens.is_empty(), "analyzer produced no terms");
+                    Ok(format!("{} terms from {:?}", tokens.len(), PROBE_TEXT))
+                }));
+                let started = Instant::now();
+                let query = manager
+                    .search(PROBE_QUERY, 1)
+                    .await
+                    .map(|results| format!("{} results for {:?}", results.len(), PROBE_QUERY));
+                checks.push(finish("query", started, query));
+            }
+            Err(e) => {
+                checks.push(HealthCheck {
+                    name: "index",
+                    ok: false,
+                    detail: format!("{e:#}"),
+                    ms: 0.0,
+                });
+                for name in ["tokenizer", "query"] {
+                    checks.push(HealthCheck {
+                        name,
+                        ok: false,
+                        detail: "skipped: index did not open".to_string(),
+                        ms: 0.0,
+                    });
+                }
+            }
+        }
+        checks.push(timed("data_dir", || check_writable(data_dir)));
+
+        Self {
+            healthy: checks.iter().all(|c| c.ok),
+            index,
+            checks,
+        }
+    }
+
+    /// Checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|c| !c.ok)
+    }
+}
+
+/// Create the directory if needed and write and remove a probe file in it
+fn check_writable(dir: &Path) -> Result<String> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".health-check-{}", std::process::id()));
+    std::fs::write(&probe, b"ok")?;
+    std::fs::remove_file(&probe)?;
+    Ok(format!("{} is writable", dir.display()))
+}
+
+fn timed(name: &'static str, check: impl FnOnce() -> Result<String>) -> HealthCheck {
+    let started = Instant::now();
+    let result = check();
+    finish(name, started, result)
+}
+
+fn finish(name: &'static str, started: Instant, result: Result<String>) -> HealthCheck {
+    let ms = (started.elapsed().as_secs_f64() * 100_000.0).round() / 100.0;
+    match result {
+        Ok(detail) => HealthCheck {
+            name,
+            ok: true,
+            detail,
+            ms,
+        },
+        Err(e) => HealthCheck {
+            name,
+            ok: false,
+            detail: format!("{e:#}"),
+            ms,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchManager;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_health_report() {
+        let temp_dir = TempDir::new().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let manager = SearchManager::new_for_testing(temp_dir.path().join("index")).unwrap();
+        manager.commit().unwrap();
+        let manager: Arc<dyn SearchManagerTrait> = Arc::new(manager);
+        let report = runtime.block_on(HealthReport::run(
+            Some("test".to_string()),
+            Ok(manager),
+            temp_dir.path(),
+        ));
+        assert!(report.healthy, "{report:?}");
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["index", "tokenizer", "query", "data_dir"]);
+
+        let report = runtime.block_on(HealthReport::run(
+            None,
+            Err(anyhow::anyhow!("Index not found: missing")),
+            temp_dir.path(),
+        ));
+        assert!(!report.healthy);
+        assert_eq!(report.failures().count(), 3);
+        assert!(report.checks[0].detail.contains("missing"));
+        assert!(report.checks[3].ok);
+    }
+}
@@ -0,0 +1,433 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::audit::{AuditEntry, AuditLog, Mutation, MutationSource};
+use crate::bookmark::FlatBookmark;
+use crate::browser::chrome_timestamp_from_unix_micros;
+use crate::config;
+use crate::content::{ContentFetcher, FetchedPage};
+use crate::search::common::url_bookmark_id;
+use crate::search::embeddings::{EmbeddingCache, EmbeddingStore, embed_bookmark, shared_embedder};
+use crate::search::indexer::{PageInfo, PageStructure};
+use crate::search::sync_metadata::SyncMetadata;
+use crate::search::tokenizer::LinderaDictionary;
+use crate::search::{IndexLock, SearchManager};
+
+/// A page fetched by the server, ready to index
+#[derive(Debug, Clone)]
+pub struct IngestedPage {
+    pub bookmark: FlatBookmark,
+    pub content: String,
+    pub structure: PageStructure,
+    /// Page offsets, for PDFs
+    pub page_info: Option<PageInfo>,
+}
+
+impl IngestedPage {
+    /// A bookmark indexed by its title and URL alone
+    pub fn without_content(url: &str, title: Option<&str>, folder_path: Vec<String>) -> Self {
+        Self {
+            bookmark: new_bookmark(url, title.unwrap_or(url), folder_path),
+            content: String::new(),
+            structure: PageStructure::default(),
+            page_info: None,
+        }
+    }
+
+    /// "pdf" or "html"
+    pub fn content_type(&self) -> &str {
+        self.page_info
+            .as_ref()
+            .map_or("html", |page_info| page_info.content_type.as_str())
+    }
+}
+
+/// A bookmark for a URL the browser doesn't know, added now
+///
+/// Its id is made from the URL, as for crawled pages.
+fn new_bookmark(url: &str, title: &str, folder_path: Vec<String>) -> FlatBookmark {
+    let now = chrono::Utc::now().timestamp_micros();
+    FlatBookmark {
+        id: url_bookmark_id(url),
+        name: title.to_string(),
+        url: url.to_string(),
+        date_added: Some(chrome_timestamp_from_unix_micros(now)),
+        date_modified: None,
+        folder_path,
+        tags: Vec::new(),
+        profile: None,
+    }
+}
+
+/// Whether a response is a PDF, by its content type or else its path
+pub fn is_pdf(page: &FetchedPage) -> bool {
+    match page.content_type.as_deref() {
+        Some(kind) if kind.starts_with("application/pdf") => true,
+        Some(kind) if !kind.starts_with("application/octet-stream") => false,
+        _ => Url::parse(&page.url).is_ok_and(|url| url.path().to_lowercase().ends_with(".pdf")),
+    }
+}
+
+/// Fetch a URL and extract its text: HTML pages like `index site` does, PDFs per page
+///
+/// The bookmark gets the page's title unless `title` is given, and the URL
+/// after redirects. Pages of domains excluded by `[domains]` are not fetched
+/// and get their title and URL alone.
+pub async fn fetch_page(
+    fetcher: &ContentFetcher,
+    url: &str,
+    title: Option<&str>,
+    folder_path: Vec<String>,
+) -> Result<IngestedPage> {
+    let domains = &config::settings().domains;
+    if !domains.allows_content(url) {
+        debug!("Not fetching {}: its domain is excluded by [domains]", url);
+        return Ok(IngestedPage::without_content(url, title, folder_path));
+    }
+    let fetched = fetcher.fetch(url).await?;
+    anyhow::ensure!(
+        fetched.is_success(),
+        "{url} returned HTTP {}",
+        fetched.status
+    );
+    if !domains.allows_content(&fetched.url) {
+        debug!(
+            "Not indexing {}: its domain is excluded by [domains]",
+            fetched.url
+        );
+        return Ok(IngestedPage::without_content(
+            &fetched.url,
+            title,
+            folder_path,
+        ));
+    }
+
+    if is_pdf(&fetched) {
+        let body = fetched.body;
+        let (content, page_info) = tokio::task::spawn_blocking(move || crate::pdf::extract(&body))
+            .await
+            .context("PDF extraction panicked")??;
+        debug!(
+            "Fetched {} ({} PDF pages)",
+            fetched.url, page_info.page_count
+        );
+        let name = title.map_or_else(|| pdf_title(&fetched.url), str::to_string);
+        return Ok(IngestedPage {
+            bookmark: new_bookmark(&fetched.url, &name, folder_path),
+            content,
+            structure: PageStructure::default(),
+            page_info: Some(page_info),
+        });
+    }
+    if !fetched.is_html() {
+        let kind = fetched.content_type.unwrap_or_default();
+        anyhow::bail!("{url} is neither an HTML page nor a PDF ({kind})");
+    }
+
+    let mut html = fetched.text();
+    let mut extracted = crate::site::extract_html(&html);
+    let text_chars = extracted.text.chars().count();
+    if let Some(rendered) = crate::render::render_if_wanted(&fetched.url, text_chars).await {
+        extracted = crate::site::extract_html(&rendered);
+        html = rendered;
+    }
+    debug!("Fetched {}", fetched.url);
+    let name = title
+        .map(str::to_string)
+        .or(extracted.title)
+        .unwrap_or_else(|| fetched.url.clone());
+    let mut structure = extracted.structure;
+    // Only kept when `[index] store_html` is enabled
+    structure.raw_html = config::settings().index.store_html.then_some(html);
+    Ok(IngestedPage {
+        bookmark: new_bookmark(&fetched.url, &name, folder_path),
+        content: extracted.text,
+        structure,
+        page_info: None,
+    })
+}
+
+/// File name of a PDF URL, the title when the caller gives none
+fn pdf_title(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()?
+                .filter(|segment| !segment.is_empty())
+                .next_back()
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Default number of URLs `ingest` fetches at once
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A URL to ingest, with the title to give it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlEntry {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// Parse a URL list: one URL per line, optionally followed by a title
+///
+/// Blank lines and `#` comments are skipped, and so are repeated URLs.
+pub fn parse_url_list(text: &str) -> Vec<UrlEntry> {
+    let mut seen = std::collections::HashSet::new();
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (url, title) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(url, title)| (url, title.trim()));
+            seen.insert(url.to_string()).then(|| UrlEntry {
+                url: url.to_string(),
+                title: (!title.is_empty()).then(|| title.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// A URL that was not indexed
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestFailure {
+    pub url: String,
+    pub error: String,
+}
+
+/// Outcome of fetching a URL list
+#[derive(Debug, Default, Serialize)]
+pub struct IngestReport {
+    /// Pages in the order of the list
+    #[serde(skip)]
+    pub pages: Vec<IngestedPage>,
+    /// URLs robots.txt asked crawlers to leave alone
+    pub skipped_robots: Vec<String>,
+    pub failed: Vec<IngestFailure>,
+}
+
+/// Fetch URLs, at most `concurrency` at a time, honouring robots.txt
+///
+/// `progress` is called after each URL with the number done so far.
+pub async fn fetch_all(
+    fetcher: &ContentFetcher,
+    entries: Vec<UrlEntry>,
+    folder_path: &[String],
+    concurrency: usize,
+    mut progress: impl FnMut(usize, &IngestReport),
+) -> IngestReport {
+    enum Outcome {
+        Page(Box<IngestedPage>),
+        Robots,
+        Failed(String),
+    }
+
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for (position, entry) in entries.into_iter().enumerate() {
+        let fetcher = fetcher.clone();
+        let semaphore = semaphore.clone();
+        let folder_path = folder_path.to_vec();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let outcome = if !fetcher.robots_allows(&entry.url).await {
+                Outcome::Robots
+            } else {
+                match fetch_page(&fetcher, &entry.url, entry.title.as_deref(), folder_path).await {
+                    Ok(page) => Outcome::Page(Box::new(page)),
+                    Err(e) => Outcome::Failed(format!("{e:#}")),
+                }
+            };
+            (position, entry.url, outcome)
+        });
+    }
+
+    let mut report = IngestReport::default();
+    let mut pages = Vec::new();
+    let mut done = 0;
+    while let Some(result) = tasks.join_next().await {
+        done += 1;
+        match result {
+            Ok((position, _, Outcome::Page(page))) => pages.push((position, *page)),
+            Ok((_, url, Outcome::Robots)) => {
+                debug!("robots.txt disallows {}", url);
+                report.skipped_robots.push(url);
+            }
+            Ok((_, url, Outcome::Failed(error))) => {
+                debug!("Skipping {}: {}", url, error);
+                report.failed.push(IngestFailure { url, error });
+            }
+            Err(e) => warn!("Fetch task failed: {}", e),
+        }
+        progress(done, &report);
+    }
+    pages.sort_by_key(|(position, _)| *position);
+    report.pages = pages.into_iter().map(|(_, page)| page).collect();
+    report
+}
+
+/// One-line progress bar, e.g. `[#####-----] 5/10`
+pub fn progress_bar(done: usize, total: usize, width: usize) -> String {
+    let filled = (done * width)
+        .checked_div(total)
+        .unwrap_or(width)
+        .min(width);
+    format!(
+        "[{}{}] {done}/{total}",
+        "#".repeat(filled),
+        "-".repeat(width - filled)
+    )
+}
+
+/// Index fetched pages into an index, replacing earlier copies
+///
+/// Takes the index lock (waiting up to `wait`), records the pages in
+/// `index_metadata.json` and the audit log and, with `[embeddings]` enabled,
+/// embeds them. Returns the number of documents written.
+pub fn write_pages(
+    index_name: &str,
+    dictionary: LinderaDictionary,
+    pages: &[IngestedPage],
+    source: MutationSource,
+    mutation: Mutation,
+    holder: &str,
+    wait: Option<Duration>,
+) -> Result<usize> {
+    let index_dir = config::data_dir().join(index_name);
+    let _lock = IndexLock::acquire(&index_dir, holder, wait)?;
+    let manager = SearchManager::new_with_dictionary(index_dir.clone(), dictionary)?;
+    let documents: Vec<_> = pages
+        .iter()
+        .map(|page| {
+            (
+                &page.bookmark,
+                page.content.as_str(),
+                &page.structure,
+                page.page_info.as_ref(),
+            )
+        })
+        .collect();
+    let written = manager.replace_documents(&documents)?;
+
+    let mut metadata = SyncMetadata::load(&index_dir)?;
+    for page in pages {
+        metadata.record_fetched(&page.bookmark, Some(&page.content));
+    }
+    metadata.save(&index_dir)?;
+
+    let mut entry = AuditEntry::new(source, mutation).with_added(written);
+    if let [page] = pages {
+        entry = entry.with_url(&page.bookmark.url);
+    }
+    AuditLog::record(index_name, entry);
+
+    if config::settings().embeddings.enabled {
+        // Semantic search is optional; a failure here keeps the full-text index
+        if let Err(e) = embed_pages(&index_dir, pages) {
+            warn!("Failed to embed pages added to {}: {:#}", index_name, e);
+        }
+    }
+    Ok(written)
+}
+
+/// Re-embed pages (per page for PDFs) into the index's embedding store
+fn embed_pages(index_dir: &std::path::Path, pages: &[IngestedPage]) -> Result<()> {
+    let embedder = shared_embedder()?;
+    let cache_path = EmbeddingCache::path();
+    let mut cache = EmbeddingCache::load(&cache_path)?;
+    let mut store = EmbeddingStore::load(index_dir)?;
+    if store.model != embedder.model() {
+        // Vectors from different models can't be compared; start over
+        store = EmbeddingStore {
+            model: embedder.model().to_string(),
+            ..Default::default()
+        };
+    }
+    for page in pages {
+        let chunks = embed_bookmark(
+            embedder,
+            &mut cache,
+            &page.bookmark.id,
+            &page.bookmark.url,
+            &page.bookmark.name,
+            &page.content,
+            page.page_info
+                .as_ref()
+                .map(|page_info| page_info.page_offsets.as_slice()),
+        )?;
+        store.replace(&page.bookmark.id, chunks);
+    }
+    cache.save(&cache_path)?;
+    store.save(index_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_pdf() {
+        let page = |url: &str, content_type: Option<&str>| FetchedPage {
+            url: url.to_string(),
+            status: 200,
+            content_type: content_type.map(str::to_string),
+            body: Vec::new(),
+        };
+        assert!(is_pdf(&page(
+            "https://example.com/download",
+            Some("application/pdf")
+        )));
+        assert!(is_pdf(&page("https://example.com/Paper.PDF", None)));
+        assert!(is_pdf(&page(
+            "https://example.com/paper.pdf",
+            Some("application/octet-stream")
+        )));
+        assert!(!is_pdf(&page(
+            "https://example.com/paper.pdf",
+            Some("text/html")
+        )));
+        assert!(!is_pdf(&page("https://example.com/guide", None)));
+
+        assert_eq!(
+            pdf_title("https://example.com/papers/attention.pdf"),
+            "attention.pdf"
+        );
+        assert_eq!(pdf_title("https://example.com/"), "https://example.com/");
+    }
+
+    #[test]
+    fn test_parse_url_list() {
+        let entries = parse_url_list(
+            "# papers\n\
+             https://arxiv.org/pdf/1706.03762\tAttention Is All You Need\n\
+             \n\
+             https://example.com/guide\n\
+             https://example.com/guide duplicate\n",
+        );
+        assert_eq!(
+            entries,
+            vec![
+                UrlEntry {
+                    url: "https://arxiv.org/pdf/1706.03762".to_string(),
+                    title: Some("Attention Is All You Need".to_string()),
+                },
+                UrlEntry {
+                    url: "https://example.com/guide".to_string(),
+                    title: None,
+                },
+            ]
+        );
+
+        assert_eq!(progress_bar(0, 4, 8), "[--------] 0/4");
+        assert_eq!(progress_bar(3, 4, 8), "[######--] 3/4");
+        assert_eq!(progress_bar(0, 0, 4), "[####] 0/0");
+    }
+}
@@ -1,4 +1,25 @@
+pub mod archive;
+pub mod audit;
 pub mod bookmark;
+pub mod browser;
 pub mod config;
+pub mod content;
+pub mod credentials;
+pub mod daemon;
+pub mod export;
+pub mod extension_host;
+pub mod feeds;
+pub mod health;
+pub mod ingest;
+pub mod link_status;
+pub mod logs;
 pub mod mcp_server;
+pub mod metrics;
+pub mod ocr;
+pub mod pdf;
+pub mod query_log;
+pub mod render;
+pub mod robots;
 pub mod search;
+pub mod site;
+pub mod tui;
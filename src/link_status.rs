@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::content::{LinkCheck, LinkState};
+
+/// Link status file in each index directory
+pub const LINK_STATUS_FILE: &str = "link_status.json";
+
+/// Persistent record of an index's link check results, keyed by URL
+///
+/// Kept in a sidecar file in the index directory rather than in the
+/// documents, so a check doesn't rewrite them or need the index lock the
+/// extension's native host may be holding. The file moves with the index.
+#[derive(Debug, Default)]
+pub struct LinkStatusStore {
+    path: PathBuf,
+    checks: HashMap<String, LinkCheck>,
+}
+
+impl LinkStatusStore {
+    /// Load the store of the index in `index_dir`
+    pub fn load_for(index_dir: &Path) -> Self {
+        Self::load(index_dir.join(LINK_STATUS_FILE))
+    }
+
+    /// Load the store from a file, starting empty if it is missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let checks = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring corrupt link status file {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { path, checks }
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.checks)?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {:?}", self.path))
+    }
+
+    /// Record a check result, replacing any previous one for the URL
+    pub fn record(&mut self, check: LinkCheck) {
+        self.checks.insert(check.url.clone(), check);
+    }
+
+    /// Get the last check result for a URL
+    pub fn get(&self, url: &str) -> Option<&LinkCheck> {
+        self.checks.get(url)
+    }
+
+    /// Check whether a URL was found dead (404/410) on its last check
+    pub fn is_dead(&self, url: &str) -> bool {
+        self.get(url).is_some_and(|c| c.state == LinkState::Dead)
+    }
+
+    /// URLs found dead on their last check
+    pub fn dead_urls(&self) -> impl Iterator<Item = &str> {
+        self.checks
+            .values()
+            .filter(|c| c.state == LinkState::Dead)
+            .map(|c| c.url.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+    use tempfile::TempDir;
+
+    fn check(url: &str, state: LinkState) -> LinkCheck {
+        LinkCheck {
+            url: url.to_string(),
+            state,
+            http_status: None,
+            error: None,
+            checked_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = LinkStatusStore::load_for(temp_dir.path());
+        store.record(check("https://example.com/gone", LinkState::Dead));
+        store.record(check("https://example.com/slow", LinkState::Timeout));
+        store.save().unwrap();
+
+        let store = LinkStatusStore::load_for(temp_dir.path());
+        assert!(store.is_dead("https://example.com/gone"));
+        assert!(!store.is_dead("https://example.com/slow"));
+        assert!(!store.is_dead("https://example.com/unknown"));
+        assert_eq!(
+            store.dead_urls().collect::<Vec<_>>(),
+            vec!["https://example.com/gone"]
+        );
+    }
+}
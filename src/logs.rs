@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Prefix of the daily log files, e.g. `mcp-bookmark.log.2025-12-01`
+pub const LOG_FILE_PREFIX: &str = "mcp-bookmark.log";
+
+/// Log directory under the data directory
+pub fn log_dir() -> PathBuf {
+    crate::config::data_dir().join("logs")
+}
+
+/// Log files in `dir`, oldest first
+pub fn log_files(dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(PathBuf, SystemTime)> = entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(LOG_FILE_PREFIX)
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((entry.path(), metadata.modified().ok()?))
+        })
+        .collect();
+    // Daily file names sort by date; break ties (and odd names) by mtime
+    files.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    files
+}
+
+/// Log files that exceed the retention limits (0 disables a limit)
+pub fn expired_log_files(dir: &Path, max_files: usize, max_age_days: u64) -> Vec<PathBuf> {
+    let files = log_files(dir);
+    let over_count = if max_files > 0 {
+        files.len().saturating_sub(max_files)
+    } else {
+        0
+    };
+    let cutoff = if max_age_days > 0 {
+        SystemTime::now().checked_sub(Duration::from_secs(max_age_days * 24 * 60 * 60))
+    } else {
+        None
+    };
+
+    files
+        .into_iter()
+        .enumerate()
+        .filter(|(i, (_, modified))| *i < over_count || cutoff.is_some_and(|c| *modified < c))
+        .map(|(_, (path, _))| path)
+        .collect()
+}
+
+/// Delete log files that exceed the retention limits, returning what was removed
+pub fn prune(dir: &Path, max_files: usize, max_age_days: u64) -> Result<Vec<PathBuf>> {
+    let expired = expired_log_files(dir, max_files, max_age_days);
+    for path in &expired {
+        std::fs::remove_file(path).with_context(|| format!("Failed to delete {path:?}"))?;
+    }
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_log(dir: &Path, date: &str, age_days: u64) {
+        let path = dir.join(format!("{LOG_FILE_PREFIX}.{date}"));
+        std::fs::write(&path, "log").unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        write_log(dir.path(), "2025-11-01", 40);
+        write_log(dir.path(), "2025-12-01", 10);
+        write_log(dir.path(), "2025-12-09", 2);
+        write_log(dir.path(), "2025-12-10", 1);
+        std::fs::write(dir.path().join("other.txt"), "keep").unwrap();
+
+        let name = |path: &PathBuf| path.file_name().unwrap().to_string_lossy().to_string();
+        let expired: Vec<String> = expired_log_files(dir.path(), 3, 0)
+            .iter()
+            .map(name)
+            .collect();
+        assert_eq!(expired, vec!["mcp-bookmark.log.2025-11-01"]);
+        let expired: Vec<String> = expired_log_files(dir.path(), 0, 7)
+            .iter()
+            .map(name)
+            .collect();
+        assert_eq!(
+            expired,
+            vec!["mcp-bookmark.log.2025-11-01", "mcp-bookmark.log.2025-12-01"]
+        );
+        assert!(expired_log_files(dir.path(), 0, 0).is_empty());
+
+        let removed = prune(dir.path(), 2, 0).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(log_files(dir.path()).len(), 2);
+        assert!(dir.path().join("other.txt").exists());
+    }
+}
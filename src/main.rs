@@ -1,95 +1,1957 @@
-use anyhow::Result;
-use mcp_bookmark::bookmark::BookmarkReader;
-use mcp_bookmark::config::Config;
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use mcp_bookmark::audit::{AuditEntry, AuditLog, Mutation, MutationSource};
+use mcp_bookmark::bookmark::{BookmarkLimits, BookmarkReader};
+use mcp_bookmark::browser::{ALL_PROFILES, Browser, chromium::ChromiumBrowser, pinboard};
+use mcp_bookmark::config::{self, Config, Settings};
+use mcp_bookmark::content::ContentFetcher;
+use mcp_bookmark::daemon::{Daemon, DaemonState};
+use mcp_bookmark::export::{self, ExportFormat, Highlighter};
+use mcp_bookmark::extension_host::{self, HostManifest};
+use mcp_bookmark::feeds::{self, FeedList, Subscription};
+use mcp_bookmark::health::HealthReport;
 use mcp_bookmark::mcp_server::BookmarkServer;
+use mcp_bookmark::search::changeset::Changeset;
+use mcp_bookmark::search::index_aliases::IndexAliases;
+use mcp_bookmark::search::index_defaults::IndexDefaults;
+use mcp_bookmark::search::index_lock::{IndexLock, IndexLockedError};
+use mcp_bookmark::search::index_registry::IndexInfo;
+use mcp_bookmark::search::query_parser::QueryParser;
 use mcp_bookmark::search::search_manager_trait::SearchManagerTrait;
-use rmcp::{ServiceExt, transport::stdio};
-use std::env;
+use mcp_bookmark::search::tokenizer::{LinderaDictionary, index_dictionary};
+use mcp_bookmark::search::{
+    IndexRegistry, IndexTrash, MultiIndexSearchManager, SearchManager, SearchParams,
+};
+use mcp_bookmark::site::{self, Discovery, SiteCrawl};
+use mcp_bookmark::{ingest, logs, tui};
+use rmcp::{
+    ServiceExt,
+    transport::stdio,
+    transport::streamable_http_server::{
+        StreamableHttpService, session::local::LocalSessionManager,
+    },
+};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing_appender::{non_blocking, rolling};
 use tracing_subscriber::{self, EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-/// Parse command-line arguments and build configuration
-fn parse_args() -> Result<Config> {
-    let args: Vec<String> = env::args().collect();
-    let mut config = Config::default();
-    let mut i = 1;
+/// Chrome Bookmark MCP Server
+///
+/// Without a subcommand, serves the index given by --index, INDEX_NAME or the config file.
+#[derive(Debug, Parser)]
+#[command(name = "mcp-bookmark", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    while i < args.len() {
-        let arg = &args[i];
+    #[command(flatten)]
+    serve: ServeArgs,
 
-        match arg.as_str() {
-            "--help" | "-h" => {
-                print_help();
-                std::process::exit(0);
+    /// Print machine-readable JSON instead of human-readable text
+    #[arg(long, global = true)]
+    json: bool,
+
+    // Flags from before subcommands existed, kept for existing scripts
+    #[arg(long, hide = true)]
+    list_indexes: bool,
+    #[arg(long, hide = true, value_name = "NAME")]
+    clear_index: Option<String>,
+    #[arg(long, hide = true)]
+    clear_all_indexes: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Start the MCP server (the default)
+    Serve(ServeArgs),
+    /// Start the MCP server and run scheduled maintenance: re-crawls, feed refreshes,
+    /// segment merges and index summaries (`[daemon]` in the config file)
+    Daemon(DaemonArgs),
+    /// Show what the daemon serves and when its tasks last ran and run next
+    Status,
+    /// Build an index from a browser's bookmarks (titles and URLs), or add a site with `index site`;
+    /// `index diff` and `index apply` sync an index between machines
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Index(IndexArgs),
+    /// Build an index from a bookmark manager or read-later service
+    Import(ImportArgs),
+    /// Fetch a list of URLs (web pages and PDFs) and add them to an index, without a browser
+    Ingest(IngestArgs),
+    /// Subscribe indexes to RSS/Atom feeds and index their new entries
+    #[command(subcommand)]
+    Feeds(FeedsCommand),
+    /// Search an index and print the results
+    Search(SearchArgs),
+    /// Browse and search an index interactively
+    Tui(TuiArgs),
+    /// List available indexes
+    List,
+    /// Move an index to the trash
+    Clear(ClearArgs),
+    /// Restore an index from the trash
+    Restore(RestoreArgs),
+    /// Export indexed bookmarks as Markdown notes
+    Export(ExportArgs),
+    /// Show index and collection statistics
+    Stats(StatsArgs),
+    /// Show what changed an index: pages indexed, rebuilds, clears and restores
+    History(HistoryArgs),
+    /// Show how an index's analyzer splits text into terms
+    Tokenize(TokenizeArgs),
+    /// Register the native messaging host so the Chrome extension can index pages
+    InstallExtensionHost(InstallExtensionHostArgs),
+    /// Inspect the configuration file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Manage log files
+    #[command(subcommand)]
+    Logs(LogsCommand),
+    /// Print a shell completion script
+    Completions(CompletionsArgs),
+    /// Print index names for shell completion
+    #[command(hide = true)]
+    CompleteIndexes {
+        /// Names of trashed indexes instead
+        #[arg(long)]
+        trash: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum LogsCommand {
+    /// Delete log files beyond the retention limits
+    Prune(PruneArgs),
+}
+
+#[derive(Debug, Args)]
+struct PruneArgs {
+    /// Daily log files to keep [default: log.max_files from the config]
+    #[arg(long)]
+    max_files: Option<usize>,
+    /// Delete files older than this many days [default: log.max_age_days from the config]
+    #[arg(long)]
+    max_age_days: Option<u64>,
+    /// Show what would be deleted without deleting
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+struct CompletionsArgs {
+    /// Shell to generate the script for
+    shell: CompletionShell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print the effective settings (config file plus environment overrides)
+    Show,
+}
+
+#[derive(Debug, Args)]
+struct ServeArgs {
+    /// Index to serve (comma-separated for multi-index search)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Serve the index in this directory instead, e.g. a shared index on a network volume
+    /// (absolute path, opened read-only; takes precedence over --index)
+    #[arg(long, env = "INDEX_PATH", value_name = "DIR")]
+    index_path: Option<PathBuf>,
+    /// Serve over Streamable HTTP at this address instead of stdio (e.g. 127.0.0.1:8080)
+    #[arg(long, value_name = "ADDR")]
+    http: Option<String>,
+    /// Also serve Prometheus metrics at http://<ADDR>/metrics
+    #[arg(long, requires = "http")]
+    metrics: bool,
+    /// Maximum number of bookmarks to read (0 is unlimited)
+    #[arg(long, default_value_t = 0)]
+    max_bookmarks: usize,
+    /// Check the index, tokenizer, a trivial query and the data directory, then exit
+    /// (exit code 1 when a check fails)
+    #[arg(long, conflicts_with_all = ["http", "metrics"])]
+    health: bool,
+}
+
+#[derive(Debug, Args)]
+struct DaemonArgs {
+    /// Index to serve and maintain (comma-separated for several)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Serve over Streamable HTTP at this address instead of stdio (e.g. 127.0.0.1:8080)
+    #[arg(long, value_name = "ADDR")]
+    http: Option<String>,
+    /// Also serve Prometheus metrics at http://<ADDR>/metrics
+    #[arg(long, requires = "http")]
+    metrics: bool,
+}
+
+#[derive(Debug, Args)]
+struct IndexArgs {
+    #[command(subcommand)]
+    command: Option<IndexCommand>,
+    /// Name of the index to build
+    #[arg(required = true)]
+    name: Option<String>,
+    /// Browser to read bookmarks from
+    #[arg(long, value_parser = parse_local_browser, required = true)]
+    browser: Option<Browser>,
+    /// Profile (directory or display name, or "all") for Chromium-based browsers
+    #[arg(long)]
+    profile: Option<String>,
+    /// Bookmarks file to read instead of the browser's default location
+    #[arg(long, value_name = "PATH")]
+    bookmarks_file: Option<PathBuf>,
+    /// Tokenizer dictionary for a new index: ipadic, unidic, ko-dic or cc-cedict
+    /// [default: from the config file]
+    #[arg(long)]
+    dictionary: Option<LinderaDictionary>,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+    #[command(flatten)]
+    limits: LimitArgs,
+}
+
+/// Caps on the bookmarks put into an index built from a browser or export
+#[derive(Debug, Args)]
+struct LimitArgs {
+    /// Index at most N bookmarks, 0 for all [default: max_bookmarks in the config file]
+    #[arg(long, value_name = "N")]
+    max_bookmarks: Option<usize>,
+    /// Index at most N bookmarks directly from any one folder, 0 for all
+    /// [default: max_per_folder in the config file]
+    #[arg(long, value_name = "N")]
+    max_per_folder: Option<usize>,
+    /// Index at most N bookmarks from a folder and its subfolders, e.g. "Bookmarks bar/Reading=200"
+    /// (repeatable; added to folder_limits in the config file)
+    #[arg(long, value_name = "FOLDER=N", value_parser = parse_folder_limit)]
+    folder_limit: Vec<(String, usize)>,
+}
+
+impl LimitArgs {
+    fn resolve(self) -> BookmarkLimits {
+        let mut limits = BookmarkLimits::from_settings();
+        if let Some(max) = self.max_bookmarks {
+            limits.max_bookmarks = max;
+        }
+        if let Some(max) = self.max_per_folder {
+            limits.max_per_folder = max;
+        }
+        limits.folder_limits.extend(self.folder_limit);
+        limits
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexCommand {
+    /// Crawl the documentation site below a URL (found via sitemap.xml, or by following links)
+    /// and add its pages to an index
+    Site(IndexSiteArgs),
+    /// Write what changed in an index since a time as a changeset for `index apply`
+    Diff(IndexDiffArgs),
+    /// Merge a changeset written by `index diff` (e.g. on another machine) into an index
+    Apply(IndexApplyArgs),
+    /// Show or change the search defaults stored with an index: excluded domains,
+    /// snippet length and field boosts
+    Defaults(IndexDefaultsArgs),
+}
+
+#[derive(Debug, Args)]
+struct IndexSiteArgs {
+    /// Root of the site, e.g. a bookmarked documentation page; only pages below it are indexed
+    url: String,
+    /// Index to add the pages to (created if it doesn't exist)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Levels below the root: link hops when crawling, path segments for sitemap entries
+    #[arg(long, default_value_t = site::DEFAULT_DEPTH)]
+    depth: usize,
+    /// Stop after this many pages
+    #[arg(long, default_value_t = site::DEFAULT_MAX_PAGES)]
+    max_pages: usize,
+    /// Follow links from the root even if the site has a sitemap
+    #[arg(long)]
+    no_sitemap: bool,
+    /// Index the latest Internet Archive snapshot of pages that return 403/404/410
+    /// [default: `archive_fallback` in the config file]
+    #[arg(long)]
+    archive_fallback: bool,
+    /// Tokenizer dictionary for a new index: ipadic, unidic, ko-dic or cc-cedict
+    /// [default: from the config file]
+    #[arg(long)]
+    dictionary: Option<LinderaDictionary>,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct IndexDiffArgs {
+    /// Index to take the changes from
+    name: String,
+    /// Only bookmarks indexed after this time: RFC 3339, a date (YYYY-MM-DD) or Unix seconds;
+    /// pass the `created_at` of the previous changeset to sync incrementally
+    #[arg(long, value_parser = parse_since)]
+    since: chrono::DateTime<chrono::Utc>,
+    /// File to write the changeset to [default: standard output]
+    #[arg(long, short, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct IndexApplyArgs {
+    /// Changeset written by `index diff`; `-` reads standard input
+    file: PathBuf,
+    /// Index to merge into (created if it doesn't exist) [default: the changeset's index]
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Tokenizer dictionary for a new index: ipadic, unidic, ko-dic or cc-cedict
+    /// [default: the changeset's dictionary]
+    #[arg(long)]
+    dictionary: Option<LinderaDictionary>,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct IndexDefaultsArgs {
+    /// Index to show or change
+    name: String,
+    /// Leave this host out of results unless a search filters on it (repeatable)
+    #[arg(long, value_name = "HOST")]
+    exclude_domain: Vec<String>,
+    /// Stop leaving this host out of results (repeatable)
+    #[arg(long, value_name = "HOST")]
+    include_domain: Vec<String>,
+    /// Snippet length for searches that don't pass one (0 goes back to the configured length)
+    #[arg(long)]
+    snippet_length: Option<usize>,
+    /// Boost of a field (title, url, headings or content) instead of the configured one,
+    /// e.g. title=5 (repeatable)
+    #[arg(long, value_name = "FIELD=BOOST", value_parser = parse_boost)]
+    boost: Vec<(String, f32)>,
+    /// Remove all stored defaults before applying the other options
+    #[arg(long)]
+    reset: bool,
+}
+
+#[derive(Debug, Args)]
+struct ImportArgs {
+    /// Name of the index to build
+    name: String,
+    /// Service the export comes from
+    #[arg(long, value_parser = parse_import_source)]
+    from: Browser,
+    /// Export file (Pinboard uses PINBOARD_TOKEN when omitted)
+    #[arg(long, value_name = "PATH")]
+    file: Option<PathBuf>,
+    /// Tokenizer dictionary for a new index: ipadic, unidic, ko-dic or cc-cedict
+    /// [default: from the config file]
+    #[arg(long)]
+    dictionary: Option<LinderaDictionary>,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+    #[command(flatten)]
+    limits: LimitArgs,
+}
+
+#[derive(Debug, Args)]
+struct IngestArgs {
+    /// File with one URL per line, optionally followed by a title; `-` reads standard input
+    file: PathBuf,
+    /// Index to add the pages to (created if it doesn't exist)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Folder to file the pages in, with '/' between levels (e.g. "Research/LLM")
+    #[arg(long)]
+    folder: Option<String>,
+    /// Number of URLs fetched at once
+    #[arg(long, default_value_t = ingest::DEFAULT_CONCURRENCY)]
+    concurrency: usize,
+    /// Write the URLs that could not be indexed to this file, to retry them later
+    #[arg(long, value_name = "PATH")]
+    failed_urls: Option<PathBuf>,
+    /// Tokenizer dictionary for a new index: ipadic, unidic, ko-dic or cc-cedict
+    /// [default: from the config file]
+    #[arg(long)]
+    dictionary: Option<LinderaDictionary>,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+}
+
+#[derive(Debug, Subcommand)]
+enum FeedsCommand {
+    /// Subscribe an index to a feed (checked by fetching it once)
+    Add(FeedArgs),
+    /// Unsubscribe an index from a feed; its indexed entries stay
+    Remove(FeedArgs),
+    /// List the feeds of an index, or of every index
+    List(FeedListArgs),
+    /// Fetch the feeds' new entries and index them under Feeds/<feed title>
+    Refresh(FeedRefreshArgs),
+}
+
+#[derive(Debug, Args)]
+struct FeedArgs {
+    /// URL of the RSS or Atom feed
+    url: String,
+    /// Index the feed belongs to
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct FeedListArgs {
+    /// Index whose feeds to list [default: every index with feeds]
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct FeedRefreshArgs {
+    /// Index whose feeds to refresh
+    #[arg(long, env = "INDEX_NAME", conflicts_with = "all")]
+    index: Option<String>,
+    /// Refresh the feeds of every index
+    #[arg(long)]
+    all: bool,
+    /// New entries indexed per feed at most
+    #[arg(long, default_value_t = feeds::DEFAULT_MAX_ENTRIES)]
+    max_entries: usize,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct SearchArgs {
+    /// Search query
+    query: String,
+    /// Index to search (comma-separated for multiple)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Output format: text, json, csv or md [default: text on a terminal, json otherwise]
+    #[arg(long, value_parser = parse_format)]
+    format: Option<ExportFormat>,
+    /// Maximum number of results
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+    /// Only bookmarks in this folder
+    #[arg(long)]
+    folder: Option<String>,
+    /// Only bookmarks from this domain
+    #[arg(long)]
+    domain: Option<String>,
+    /// Only bookmarks from this browser profile
+    #[arg(long)]
+    profile: Option<String>,
+    /// Fail on reserved characters or stray quotes instead of searching them as text
+    #[arg(long)]
+    strict: bool,
+    /// Write to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct TuiArgs {
+    /// Index to browse (comma-separated for multiple)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct ClearArgs {
+    /// Index to delete
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    name: Option<String>,
+    /// Delete all indexes
+    #[arg(long)]
+    all: bool,
+    /// Show what would be cleared without changing anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    /// Delete permanently instead of moving to the trash
+    #[arg(long)]
+    purge: bool,
+    /// Wait for another process writing the index to finish instead of failing
+    /// (`--wait=SECONDS` gives up after that long)
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, require_equals = true, default_missing_value = "0")]
+    wait: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct RestoreArgs {
+    /// Index to restore (the most recently cleared copy)
+    #[arg(required_unless_present = "list", conflicts_with = "list")]
+    name: Option<String>,
+    /// List trashed indexes
+    #[arg(long)]
+    list: bool,
+}
+
+#[derive(Debug, Args)]
+struct ExportArgs {
+    /// Directory to write notes to
+    #[arg(long, value_name = "DIR")]
+    vault: PathBuf,
+    /// Index to export
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct StatsArgs {
+    /// Index to inspect (comma-separated for multiple)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct HistoryArgs {
+    /// Index whose history to show (it doesn't need to exist any more)
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+    /// Most recent entries to show (0 shows all)
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
+}
+
+#[derive(Debug, Args)]
+struct InstallExtensionHostArgs {
+    /// ID of the loaded extension, from chrome://extensions (repeat for several)
+    #[arg(long = "extension-id", value_name = "ID", required = true)]
+    extension_ids: Vec<String>,
+    /// Browser to register with (repeat for several) [default: every installed Chromium-based browser]
+    #[arg(long = "browser", value_name = "NAME", value_parser = parse_chromium_browser)]
+    browsers: Vec<ChromiumBrowser>,
+    /// Host binary to register [default: mcp-bookmark-native next to this executable]
+    #[arg(long, value_name = "PATH")]
+    host_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct TokenizeArgs {
+    /// Text to analyze
+    text: String,
+    /// Field whose analyzer to use (title, content, url, ngram, ...)
+    #[arg(long, default_value = "content")]
+    field: String,
+    /// Index whose analyzer to use
+    #[arg(long, env = "INDEX_NAME")]
+    index: Option<String>,
+}
+
+/// Process exit codes, stable so scripts and installers can rely on them
+mod exit_code {
+    pub const ERROR: i32 = 1;
+    /// Invalid arguments; also what clap uses for parse errors
+    pub const USAGE: i32 = 2;
+    /// The requested index does not exist
+    pub const NOT_FOUND: i32 = 3;
+    /// Another process is writing the index (retry, or pass --wait)
+    pub const LOCKED: i32 = 4;
+}
+
+/// Errors that map to a specific exit code
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    IndexNotFound(String),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{message}"),
+            Self::IndexNotFound(name) => write!(f, "Index not found: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl CliError {
+    fn exit_code(error: &anyhow::Error) -> i32 {
+        match error.downcast_ref::<Self>() {
+            Some(Self::Usage(_)) => exit_code::USAGE,
+            Some(Self::IndexNotFound(_)) => exit_code::NOT_FOUND,
+            None if error.is::<IndexLockedError>() => exit_code::LOCKED,
+            None => exit_code::ERROR,
+        }
+    }
+}
+
+/// Lock an index for writing; `--wait` without a value waits indefinitely
+fn lock_index(index_dir: &Path, command: &str, wait: Option<u64>) -> Result<IndexLock> {
+    IndexLock::acquire(
+        index_dir,
+        &format!("mcp-bookmark {command}"),
+        lock_wait(wait),
+    )
+}
+
+/// How long `--wait` waits for the index lock
+fn lock_wait(wait: Option<u64>) -> Option<Duration> {
+    wait.map(|secs| match secs {
+        0 => Duration::MAX,
+        secs => Duration::from_secs(secs),
+    })
+}
+
+fn print_json(value: &serde_json::Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn parse_local_browser(name: &str) -> Result<Browser, String> {
+    Browser::parse(name)
+        .filter(Browser::is_local)
+        .ok_or_else(|| format!("supported: {}", browser_names(true)))
+}
+
+fn parse_chromium_browser(name: &str) -> Result<ChromiumBrowser, String> {
+    ChromiumBrowser::ALL
+        .into_iter()
+        .find(|browser| browser.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = ChromiumBrowser::ALL.iter().map(|b| b.name()).collect();
+            format!("supported: {}", names.join(", "))
+        })
+}
+
+fn parse_import_source(name: &str) -> Result<Browser, String> {
+    Browser::parse(name)
+        .filter(|b| !b.is_local())
+        .ok_or_else(|| format!("supported: {}", browser_names(false)))
+}
+
+fn browser_names(local: bool) -> String {
+    Browser::supported_names()
+        .iter()
+        .filter(|name| Browser::parse(name).is_some_and(|b| b.is_local() == local))
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn parse_format(name: &str) -> Result<ExportFormat, String> {
+    ExportFormat::parse(name).ok_or_else(|| "supported: text, json, csv, md".to_string())
+}
+
+fn parse_boost(value: &str) -> Result<(String, f32), String> {
+    let (field, boost) = value
+        .split_once('=')
+        .ok_or_else(|| "expected FIELD=BOOST, e.g. title=5".to_string())?;
+    let boost = boost
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid boost {boost:?}"))?;
+    Ok((field.trim().to_lowercase(), boost))
+}
+
+fn parse_folder_limit(value: &str) -> Result<(String, usize), String> {
+    let (folder, limit) = value
+        .rsplit_once('=')
+        .ok_or_else(|| "expected FOLDER=N, e.g. Reading=200".to_string())?;
+    let limit = limit
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid limit {limit:?}"))?;
+    Ok((folder.trim().trim_matches('/').to_string(), limit))
+}
+
+/// RFC 3339, a local date (from its midnight) or Unix seconds
+fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(time) = Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .earliest()
+        {
+            return Ok(time.with_timezone(&Utc));
+        }
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| {
+            "expected RFC 3339 (2024-05-01T12:00:00Z), a date or Unix seconds".to_string()
+        })
+}
+
+/// Ask which profile to use when a Chromium-based browser has several
+fn prompt_profile(browser: ChromiumBrowser) -> Result<Option<String>> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    let profiles = browser.list_profiles();
+    if profiles.len() < 2 || !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    println!("Multiple {browser:?} profiles found:");
+    for (i, profile) in profiles.iter().enumerate() {
+        println!(
+            "  {}. {} ({})",
+            i + 1,
+            profile.display_name,
+            profile.dir_name
+        );
+    }
+    println!("  {ALL_PROFILES}. Merge all profiles into one index");
+    print!("Select a profile [1]: ");
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    let choice = line.trim();
+    if choice.eq_ignore_ascii_case(ALL_PROFILES) {
+        return Ok(Some(ALL_PROFILES.to_string()));
+    }
+    let index = if choice.is_empty() {
+        0
+    } else {
+        match choice.parse::<usize>() {
+            Ok(n) if (1..=profiles.len()).contains(&n) => n - 1,
+            _ => anyhow::bail!("Invalid selection: {choice}"),
+        }
+    };
+
+    Ok(Some(profiles[index].dir_name.clone()))
+}
+
+/// Run a search and write the results to stdout or a file
+async fn run_search(mut args: SearchArgs, json: bool) -> Result<()> {
+    if json {
+        match args.format {
+            None | Some(ExportFormat::Json) => args.format = Some(ExportFormat::Json),
+            Some(_) => {
+                return Err(CliError::Usage("--json conflicts with --format".to_string()).into());
+            }
+        }
+    }
+    let (_, query_warnings) = QueryParser::parse_with_warnings(&args.query);
+    if args.strict && !query_warnings.is_empty() {
+        let messages: Vec<&str> = query_warnings.iter().map(|w| w.message.as_str()).collect();
+        return Err(CliError::Usage(format!("Invalid query: {}", messages.join("; "))).into());
+    }
+    for warning in &query_warnings {
+        eprintln!("Warning: {}", warning.message);
+    }
+    let manager = open_cli_index(args.index)?;
+
+    let mut params = SearchParams::new(&args.query).with_limit(args.limit);
+    if let Some(folder) = args.folder {
+        params = params.with_folder(folder);
+    }
+    if let Some(domain) = args.domain {
+        params = params.with_domain(domain);
+    }
+    if let Some(profile) = args.profile {
+        params = params.with_profile(profile);
+    }
+    let results = manager.search_advanced(&params).await?;
+
+    match args.output {
+        Some(path) => {
+            let file = std::fs::File::create(&path)
+                .with_context(|| format!("Failed to create {path:?}"))?;
+            let format = args.format.unwrap_or(ExportFormat::Json);
+            export::write_results(&results, format, std::io::BufWriter::new(file))?;
+            if json {
+                print_json(&serde_json::json!({
+                    "results": results.len(),
+                    "output": path,
+                }))?;
+            } else {
+                eprintln!("Wrote {} results to {}", results.len(), path.display());
+            }
+        }
+        None => {
+            use std::io::IsTerminal;
+
+            let stdout = std::io::stdout();
+            let terminal = stdout.is_terminal();
+            let default_format = if terminal {
+                ExportFormat::Text
+            } else {
+                ExportFormat::Json
+            };
+            match args.format.unwrap_or(default_format) {
+                ExportFormat::Text => {
+                    // NO_COLOR: https://no-color.org/
+                    let color = terminal && std::env::var_os("NO_COLOR").is_none();
+                    let highlighter = color.then(|| Highlighter::new(&args.query));
+                    export::write_text(&results, highlighter.as_ref(), stdout.lock())?;
+                    if results.is_empty() {
+                        eprintln!("No results for {:?}", args.query);
+                    }
+                }
+                format => export::write_results(&results, format, stdout.lock())?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Browse an index in the terminal
+async fn run_tui(args: TuiArgs) -> Result<()> {
+    let index_name = config::settings()
+        .resolve_index_name(args.index)
+        .ok_or_else(index_required)?;
+    let manager = open_cli_index(Some(index_name.clone()))?;
+    tui::run(manager, &index_name).await
+}
+
+/// Export indexed bookmarks as Markdown notes
+async fn run_export(args: ExportArgs, json: bool) -> Result<()> {
+    let manager = open_cli_index(args.index)?;
+    let count = export::export_vault(manager.as_ref(), &args.vault).await?;
+    if json {
+        print_json(&serde_json::json!({ "exported": count, "vault": args.vault }))?;
+    } else {
+        println!("Exported {count} bookmarks to {}", args.vault.display());
+    }
+    Ok(())
+}
+
+/// Print index and collection statistics as JSON
+fn run_stats(args: StatsArgs) -> Result<()> {
+    let manager = open_cli_index(args.index)?;
+    let stats = serde_json::json!({
+        "index": manager.get_stats()?,
+        "collection": manager.get_collection_stats()?,
+    });
+    print_json(&stats)
+}
+
+/// Run the health checks against an index and print the report
+async fn run_health(index: Option<String>, index_path: Option<PathBuf>, json: bool) -> Result<()> {
+    let (index, manager) = match index_path {
+        Some(index_dir) => {
+            let name = external_index_name(&index_dir)?;
+            let manager = SearchManager::open_readonly_at(&index_dir)
+                .map(|manager| Arc::new(manager) as Arc<dyn SearchManagerTrait>);
+            (Some(name), manager)
+        }
+        None => {
+            let index = config::settings().resolve_index_name(index);
+            (index.clone(), open_cli_index(index))
+        }
+    };
+    let report = HealthReport::run(index, manager, &config::data_dir()).await;
+    if json {
+        print_json(&serde_json::to_value(&report)?)?;
+    } else {
+        for check in &report.checks {
+            let status = if check.ok { "ok" } else { "FAIL" };
+            println!(
+                "{:<4}  {:<9}  {} ({:.1} ms)",
+                status, check.name, check.detail, check.ms
+            );
+        }
+    }
+    if !report.healthy {
+        std::process::exit(exit_code::ERROR);
+    }
+    Ok(())
+}
+
+/// Print an index's audit log, oldest of the shown entries first
+fn run_history(args: HistoryArgs, json: bool) -> Result<()> {
+    let name = config::settings()
+        .resolve_index_name(args.index)
+        .ok_or_else(index_required)?;
+    if !IndexRegistry::is_valid_index_name(&name) {
+        return Err(CliError::Usage(format!("Invalid index name: {name}")).into());
+    }
+    let mut entries = AuditLog::for_index(&name).entries()?;
+    if args.limit > 0 && entries.len() > args.limit {
+        entries.drain(..entries.len() - args.limit);
+    }
+
+    if json {
+        return print_json(&serde_json::json!({ "index": name, "history": entries }));
+    }
+    if entries.is_empty() {
+        println!("No recorded changes for {name}");
+    }
+    for entry in &entries {
+        let mut counts = Vec::new();
+        if let Some(added) = entry.added {
+            counts.push(format!("+{added}"));
+        }
+        if let Some(removed) = entry.removed {
+            counts.push(format!("-{removed}"));
+        }
+        println!(
+            "{}  {:<9}  {:<14}  {:<12}  {}",
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.source.name(),
+            entry.mutation.name(),
+            counts.join(" "),
+            entry.url.as_deref().unwrap_or("")
+        );
+    }
+    Ok(())
+}
+
+/// Print the daemon state file
+fn show_status(json: bool) -> Result<()> {
+    let now = chrono::Utc::now();
+    let Some(state) = DaemonState::load()? else {
+        if json {
+            return print_json(&serde_json::json!({ "running": false }));
+        }
+        println!("The daemon has not run yet; start it with `mcp-bookmark daemon --index NAME`");
+        return Ok(());
+    };
+    let running = state.is_running(now);
+    if json {
+        let mut value = serde_json::to_value(&state)?;
+        value["running"] = running.into();
+        return print_json(&value);
+    }
+
+    let local = |time: chrono::DateTime<chrono::Utc>| {
+        time.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    if running {
+        println!(
+            "Daemon running since {} (pid {}), serving {}",
+            local(state.started_at),
+            state.pid,
+            state.serving
+        );
+    } else if let Some(stopped) = state.stopped_at {
+        println!("Daemon stopped at {}", local(stopped));
+    } else {
+        println!(
+            "Daemon not responding since {} (pid {}); it may have been killed",
+            local(state.heartbeat),
+            state.pid
+        );
+    }
+    println!("Indexes: {}", state.indexes.join(", "));
+
+    if state.tasks.is_empty() {
+        println!("No tasks scheduled");
+    }
+    for (task, task_state) in &state.tasks {
+        let last = if task_state.is_running() {
+            "running now".to_string()
+        } else {
+            task_state.last_finished.map_or_else(
+                || "never run".to_string(),
+                |time| format!("last run {}", local(time)),
+            )
+        };
+        let next = match task_state.next_run {
+            Some(time) if running => format!(", next {}", local(time)),
+            _ => String::new(),
+        };
+        println!(
+            "{:<9} {:<14} {last}{next}",
+            task.name(),
+            task_state.schedule
+        );
+        for line in &task_state.last_result {
+            println!("    {line}");
+        }
+        for error in &task_state.last_errors {
+            println!("    Failed: {error}");
+        }
+    }
+
+    for info in &state.index_info {
+        println!(
+            "{}: {} bookmarks, {} documents, {:.1} MB",
+            info.name,
+            info.bookmark_count,
+            info.document_count,
+            info.size_bytes as f64 / 1024.0 / 1024.0
+        );
+    }
+    Ok(())
+}
+
+/// Write the native messaging host manifest for each browser
+fn run_install_extension_host(args: InstallExtensionHostArgs, json: bool) -> Result<()> {
+    for id in &args.extension_ids {
+        extension_host::validate_extension_id(id).map_err(|e| CliError::Usage(e.to_string()))?;
+    }
+    let browsers = if args.browsers.is_empty() {
+        ChromiumBrowser::ALL
+            .into_iter()
+            .filter(|browser| browser.user_data_dir().is_some_and(|dir| dir.is_dir()))
+            .collect()
+    } else {
+        args.browsers
+    };
+    if browsers.is_empty() {
+        return Err(
+            CliError::Usage("No Chromium-based browser found; pass --browser".to_string()).into(),
+        );
+    }
+
+    let host_path = match args.host_path {
+        Some(path) => std::path::absolute(path)?,
+        None => extension_host::default_host_path()?,
+    };
+    if !host_path.is_file() {
+        eprintln!("Warning: {} does not exist yet", host_path.display());
+    }
+    let manifest = HostManifest::new(host_path, &args.extension_ids);
+
+    let mut installed = Vec::new();
+    for browser in browsers {
+        let dir = extension_host::manifest_dir(browser)
+            .with_context(|| format!("Can't locate the {} user data directory", browser.name()))?;
+        installed.push(extension_host::install_manifest(browser, &dir, &manifest)?);
+    }
+
+    if json {
+        return print_json(&serde_json::json!({ "manifest": manifest, "installed": installed }));
+    }
+    for entry in &installed {
+        println!("{:<9} {}", entry.browser, entry.path.display());
+        if let Some(key) = &entry.registry_key {
+            println!(
+                "          register with: reg add \"{key}\" /ve /t REG_SZ /d \"{}\" /f",
+                entry.path.display()
+            );
+        }
+    }
+    println!("Host: {}", manifest.path.display());
+    println!("Reload the extension on chrome://extensions to connect.");
+    Ok(())
+}
+
+/// Print the terms an index's analyzer produces for some text
+fn run_tokenize(args: TokenizeArgs, json: bool) -> Result<()> {
+    let manager = open_cli_index(args.index)?;
+    let tokens = manager.tokenize(&args.text, &args.field)?;
+    if json {
+        return print_json(&serde_json::json!({
+            "field": args.field,
+            "text": args.text,
+            "tokens": tokens,
+        }));
+    }
+    for token in &tokens {
+        println!(
+            "{:>4}  {:<20}  [{}..{}]",
+            token.position, token.text, token.offset_from, token.offset_to
+        );
+    }
+    if tokens.is_empty() {
+        eprintln!("No terms for {:?} in {}", args.text, args.field);
+    }
+    Ok(())
+}
+
+/// Completion script for a shell, with index names completed from the data directory
+///
+/// clap_complete generates the static part; a small shell-specific hook is
+/// added that asks `complete-indexes` for the values of `--index`, `clear`
+/// and `restore`.
+fn completion_script(shell: CompletionShell) -> String {
+    let mut command = Cli::command();
+    let bin = command.get_name().to_string();
+    let mut script = Vec::new();
+    let generator = match shell {
+        CompletionShell::Bash => clap_complete::Shell::Bash,
+        CompletionShell::Zsh => clap_complete::Shell::Zsh,
+        CompletionShell::Fish => clap_complete::Shell::Fish,
+    };
+    clap_complete::generate(generator, &mut command, &bin, &mut script);
+    let mut script = String::from_utf8_lossy(&script).into_owned();
+
+    let indexes = format!("{bin} complete-indexes 2>/dev/null");
+    let trashed = format!("{bin} complete-indexes --trash 2>/dev/null");
+    match shell {
+        CompletionShell::Bash => {
+            let generated =
+                completion_function(&script, "complete -F ").unwrap_or_else(|| format!("_{bin}"));
+            script.push_str(&format!(
+                r#"
+_mcp_bookmark_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    if [[ "$prev" == "--index" ]]; then
+        COMPREPLY=($(compgen -W "$({indexes})" -- "$cur"))
+        return 0
+    fi
+    if [[ $COMP_CWORD -eq 2 && "$cur" != -* ]]; then
+        case "${{COMP_WORDS[1]}}" in
+            clear) COMPREPLY=($(compgen -W "$({indexes})" -- "$cur")); return 0 ;;
+            restore) COMPREPLY=($(compgen -W "$({trashed})" -- "$cur")); return 0 ;;
+        esac
+    fi
+    {generated} "$@"
+}}
+complete -F _mcp_bookmark_dynamic -o nosort -o bashdefault -o default {bin}
+"#
+            ));
+        }
+        CompletionShell::Zsh => {
+            let generated =
+                completion_function(&script, "compdef ").unwrap_or_else(|| format!("_{bin}"));
+            script.push_str(&format!(
+                r#"
+_mcp_bookmark_dynamic() {{
+    if [[ "${{words[CURRENT-1]}}" == "--index" ]]; then
+        compadd -- ${{(f)"$({indexes})"}}
+        return
+    fi
+    if (( CURRENT == 3 )) && [[ "${{words[CURRENT]}}" != -* ]]; then
+        case "${{words[2]}}" in
+            clear) compadd -- ${{(f)"$({indexes})"}}; return ;;
+            restore) compadd -- ${{(f)"$({trashed})"}}; return ;;
+        esac
+    fi
+    {generated} "$@"
+}}
+compdef _mcp_bookmark_dynamic {bin}
+"#
+            ));
+        }
+        CompletionShell::Fish => {
+            script.push_str(&format!(
+                r#"
+complete -c {bin} -l index -r -f -a '({indexes})'
+complete -c {bin} -n '__fish_seen_subcommand_from clear' -f -a '({indexes})'
+complete -c {bin} -n '__fish_seen_subcommand_from restore' -f -a '({trashed})'
+"#
+            ));
+        }
+    }
+    script
+}
+
+/// Name of the completion function registered by a `complete -F`/`compdef` line
+fn completion_function(script: &str, prefix: &str) -> Option<String> {
+    script
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix(prefix))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .find(|name| name.starts_with('_'))
+        .map(str::to_string)
+}
+
+/// Print index names (or trashed index names), one per line
+fn complete_indexes(trash: bool) {
+    let base_dir = config::data_dir();
+    let mut names: Vec<String> = if trash {
+        IndexTrash::new(&base_dir)
+            .list()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect()
+    } else {
+        std::fs::read_dir(&base_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| IndexRegistry::is_valid_index_name(name))
+            .filter(|name| base_dir.join(name).join("meta.json").exists())
+            .collect()
+    };
+    names.sort();
+    names.dedup();
+    for name in names {
+        println!("{name}");
+    }
+}
+
+/// Delete old log files
+fn run_logs_prune(args: PruneArgs, json: bool) -> Result<()> {
+    let settings = &config::settings().log;
+    let max_files = args.max_files.unwrap_or(settings.max_files);
+    let max_age_days = args.max_age_days.unwrap_or(settings.max_age_days);
+    let dir = logs::log_dir();
+
+    let removed = if args.dry_run {
+        logs::expired_log_files(&dir, max_files, max_age_days)
+    } else {
+        logs::prune(&dir, max_files, max_age_days)?
+    };
+
+    if json {
+        return print_json(&serde_json::json!({
+            "dry_run": args.dry_run,
+            "removed": removed,
+            "kept": logs::log_files(&dir).len() - if args.dry_run { removed.len() } else { 0 },
+        }));
+    }
+    let verb = if args.dry_run {
+        "Would delete"
+    } else {
+        "Deleted"
+    };
+    for path in &removed {
+        println!("{verb} {}", path.display());
+    }
+    println!("{verb} {} log files in {}", removed.len(), dir.display());
+    Ok(())
+}
+
+/// Print the settings in effect and where they were read from
+fn show_config(json: bool) -> Result<()> {
+    let settings = config::settings();
+    if json {
+        return print_json(&serde_json::json!({
+            "path": Settings::path().filter(|path| path.exists()),
+            "data_dir": settings.data_dir(),
+            "settings": settings,
+        }));
+    }
+
+    match Settings::path() {
+        Some(path) if path.exists() => println!("# {}", path.display()),
+        Some(path) => println!("# {} (not found, using defaults)", path.display()),
+        None => println!("# no config file location"),
+    }
+    println!("# data directory: {}", settings.data_dir().display());
+    print!("{}", settings.to_toml()?);
+    Ok(())
+}
+
+fn index_required() -> CliError {
+    CliError::Usage(
+        "--index, INDEX_NAME or index.default in the config file is required".to_string(),
+    )
+}
+
+/// Name of the index in the `--index-path` directory, after checking it holds one
+fn external_index_name(index_dir: &Path) -> Result<String> {
+    if !index_dir.is_absolute() {
+        return Err(CliError::Usage(format!(
+            "--index-path must be an absolute path: {}",
+            index_dir.display()
+        ))
+        .into());
+    }
+    if !index_dir.join("meta.json").exists() {
+        return Err(CliError::IndexNotFound(index_dir.display().to_string()).into());
+    }
+    Ok(index_dir.file_name().map_or_else(
+        || "index".to_string(),
+        |name| name.to_string_lossy().to_string(),
+    ))
+}
+
+/// Open the index (or comma-separated indexes) named on the command line
+fn open_cli_index(index_name: Option<String>) -> Result<Arc<dyn SearchManagerTrait>> {
+    let settings = config::settings();
+    let config = Config {
+        index_name: Some(
+            settings
+                .resolve_index_name(index_name)
+                .ok_or_else(index_required)?,
+        ),
+        ..settings.to_config()
+    };
+    let base_dir = config::data_dir();
+    if let Some(missing) = config
+        .parse_index_names()
+        .into_iter()
+        .find(|name| !base_dir.join(name).exists())
+    {
+        return Err(CliError::IndexNotFound(missing).into());
+    }
+    Ok(if config.is_multi_index() {
+        Arc::new(MultiIndexSearchManager::new(&config)?)
+    } else {
+        Arc::new(SearchManager::open_readonly(
+            &config.parse_index_names()[0],
+        )?)
+    })
+}
+
+/// Build an index from a browser's bookmarks
+async fn run_index(args: IndexArgs, json: bool) -> Result<()> {
+    match args.command {
+        Some(IndexCommand::Site(site_args)) => return run_index_site(site_args, json).await,
+        Some(IndexCommand::Diff(diff_args)) => return run_index_diff(diff_args, json),
+        Some(IndexCommand::Apply(apply_args)) => return run_index_apply(apply_args, json),
+        Some(IndexCommand::Defaults(defaults_args)) => {
+            return run_index_defaults(defaults_args, json);
+        }
+        None => {}
+    }
+    // clap requires both when there is no subcommand
+    let (Some(name), Some(browser)) = (args.name, args.browser) else {
+        return Err(CliError::Usage("index needs a NAME and --browser".to_string()).into());
+    };
+    let mut profile = args.profile;
+    if let (Browser::Chromium(chromium), None, None, false) =
+        (browser, &profile, &args.bookmarks_file, json)
+    {
+        profile = prompt_profile(chromium)?;
+    }
+
+    let bookmarks = browser.read_bookmarks(args.bookmarks_file.as_deref(), profile.as_deref())?;
+    let limits = args.limits.resolve();
+    build_index(&name, &bookmarks, &limits, args.dictionary, args.wait, json)
+}
+
+/// Crawl a documentation site and add its pages to an index
+async fn run_index_site(args: IndexSiteArgs, json: bool) -> Result<()> {
+    let name = config::settings()
+        .resolve_index_name(args.index)
+        .ok_or_else(index_required)?;
+    if !IndexRegistry::is_valid_index_name(&name) {
+        return Err(CliError::Usage(format!("Invalid index name: {name}")).into());
+    }
+    let mut crawl = SiteCrawl::new(&args.url, args.depth, args.max_pages)
+        .map_err(|e| CliError::Usage(format!("{e:#}")))?;
+    if args.no_sitemap {
+        crawl = crawl.without_sitemap();
+    }
+    crawl = crawl
+        .with_archive_fallback(args.archive_fallback || config::settings().fetch.archive_fallback);
+    let index_dir = config::data_dir().join(&name);
+    let dictionary = resolve_dictionary(&name, &index_dir, args.dictionary)?;
+
+    if !json {
+        println!("Crawling {}", crawl.root());
+    }
+    let fetcher = ContentFetcher::new()?;
+    let report = crawl.run(&fetcher).await;
+    if report.pages.is_empty() {
+        let reason = report
+            .failed
+            .first()
+            .map(|failure| format!(": {} ({})", failure.url, failure.error))
+            .unwrap_or_default();
+        anyhow::bail!("No pages could be indexed below {}{reason}", crawl.root());
+    }
+
+    // Only held for the write, not the crawl
+    let _lock = lock_index(&index_dir, "index site", args.wait)?;
+    let manager = SearchManager::new_with_dictionary(index_dir, dictionary)?;
+    let pages: Vec<_> = report
+        .pages
+        .iter()
+        .map(|page| (&page.bookmark, page.content.as_str(), &page.structure))
+        .collect();
+    let documents = manager.replace_pages(&pages)?;
+    AuditLog::record(
+        &name,
+        AuditEntry::new(MutationSource::Cli, Mutation::IndexSite)
+            .with_added(documents)
+            .with_url(crawl.root().as_str()),
+    );
+
+    if json {
+        return print_json(&serde_json::json!({
+            "index": name,
+            "root": crawl.root().as_str(),
+            "folder": crawl.folder(),
+            "pages": documents,
+            "discovery": report.discovery,
+            "skipped_robots": report.skipped_robots,
+            "archived": report.archived,
+            "failed": report.failed,
+        }));
+    }
+    let discovery = match report.discovery {
+        Discovery::Sitemap => "from the sitemap",
+        Discovery::Links => "by following links",
+    };
+    println!(
+        "Indexed {documents} pages {discovery} into {name} (folder {})",
+        crawl.folder()
+    );
+    if !report.skipped_robots.is_empty() {
+        println!(
+            "Skipped {} pages disallowed by robots.txt",
+            report.skipped_robots.len()
+        );
+    }
+    if !report.archived.is_empty() {
+        println!(
+            "Indexed {} dead pages from Internet Archive snapshots",
+            report.archived.len()
+        );
+    }
+    for failure in &report.failed {
+        println!("Failed: {}: {}", failure.url, failure.error);
+    }
+    Ok(())
+}
+
+/// Write the changes of an index since a time as a changeset
+fn run_index_diff(args: IndexDiffArgs, json: bool) -> Result<()> {
+    let index_dir = config::data_dir().join(&args.name);
+    if !IndexRegistry::is_valid_index_name(&args.name) || !index_dir.is_dir() {
+        return Err(CliError::IndexNotFound(args.name).into());
+    }
+    let changeset = Changeset::diff(&args.name, &index_dir, args.since)?;
+    let Some(output) = &args.output else {
+        println!("{}", serde_json::to_string(&changeset)?);
+        return Ok(());
+    };
+    std::fs::write(output, serde_json::to_string(&changeset)?)
+        .with_context(|| format!("Failed to write {output:?}"))?;
+
+    if json {
+        return print_json(&serde_json::json!({
+            "index": args.name,
+            "output": output,
+            "since": changeset.since,
+            "created_at": changeset.created_at,
+            "added": changeset.added.len(),
+            "updated": changeset.updated.len(),
+            "deleted": changeset.deleted.len(),
+        }));
+    }
+    println!(
+        "Wrote {} added, {} updated and {} deleted bookmarks of {} to {}",
+        changeset.added.len(),
+        changeset.updated.len(),
+        changeset.deleted.len(),
+        args.name,
+        output.display()
+    );
+    println!(
+        "Next time, pass --since {} to take only the changes after this one",
+        changeset
+            .created_at
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    );
+    Ok(())
+}
+
+/// Merge a changeset into an index
+fn run_index_apply(args: IndexApplyArgs, json: bool) -> Result<()> {
+    let content = if args.file == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).context("Failed to read standard input")?
+    } else {
+        std::fs::read_to_string(&args.file)
+            .with_context(|| format!("Failed to read {:?}", args.file))?
+    };
+    let changeset = Changeset::parse(&content).map_err(|e| CliError::Usage(format!("{e:#}")))?;
+    let name = config::settings()
+        .resolve_index_name(args.index)
+        .unwrap_or_else(|| changeset.index.clone());
+    if !IndexRegistry::is_valid_index_name(&name) {
+        return Err(CliError::Usage(format!("Invalid index name: {name}")).into());
+    }
+    let index_dir = config::data_dir().join(&name);
+    // The changeset's dictionary only matters for a new index; an existing one keeps its own
+    let requested = args
+        .dictionary
+        .or_else(|| changeset.dictionary.filter(|_| !index_dir.exists()));
+    let dictionary = resolve_dictionary(&name, &index_dir, requested)?;
+
+    let _lock = lock_index(&index_dir, "index apply", args.wait)?;
+    let manager = SearchManager::new_with_dictionary(index_dir.clone(), dictionary)?;
+    let report = changeset.apply(&manager, &index_dir)?;
+    AuditLog::record(
+        &name,
+        AuditEntry::new(MutationSource::Cli, Mutation::Apply)
+            .with_added(report.documents)
+            .with_removed(Some(report.deleted)),
+    );
+
+    if json {
+        return print_json(&serde_json::json!({
+            "index": name,
+            "from": changeset.index,
+            "since": changeset.since,
+            "created_at": changeset.created_at,
+            "added": report.added,
+            "updated": report.updated,
+            "deleted": report.deleted,
+            "skipped_newer": report.skipped_newer,
+            "documents": report.documents,
+        }));
+    }
+    println!(
+        "Applied changes of {} since {} to {name}: {} added, {} updated, {} deleted",
+        changeset.index,
+        changeset
+            .since
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M"),
+        report.added,
+        report.updated,
+        report.deleted
+    );
+    if report.skipped_newer > 0 {
+        println!(
+            "Kept {} bookmarks that were indexed here more recently",
+            report.skipped_newer
+        );
+    }
+    Ok(())
+}
+
+/// Show or change the search defaults stored with an index
+fn run_index_defaults(args: IndexDefaultsArgs, json: bool) -> Result<()> {
+    let index_dir = config::data_dir().join(&args.name);
+    if !IndexRegistry::is_valid_index_name(&args.name) || !index_dir.join("meta.json").exists() {
+        return Err(CliError::IndexNotFound(args.name).into());
+    }
+    let changed = args.reset
+        || !args.exclude_domain.is_empty()
+        || !args.include_domain.is_empty()
+        || args.snippet_length.is_some()
+        || !args.boost.is_empty();
+    let mut defaults = if args.reset {
+        IndexDefaults::default()
+    } else {
+        IndexDefaults::load(&index_dir)?
+    };
+    // Hosts are stored lowercase, as URLs have them
+    for host in &args.exclude_domain {
+        let host = host.trim().to_lowercase();
+        if !host.is_empty() && !defaults.exclude_domains.contains(&host) {
+            defaults.exclude_domains.push(host);
+        }
+    }
+    defaults.exclude_domains.retain(|domain| {
+        !args
+            .include_domain
+            .iter()
+            .any(|host| host.trim().eq_ignore_ascii_case(domain))
+    });
+    if let Some(length) = args.snippet_length {
+        defaults.snippet_length = (length > 0).then_some(length);
+    }
+    for (field, boost) in &args.boost {
+        defaults
+            .boosts
+            .set(field, *boost)
+            .map_err(|e| CliError::Usage(format!("{e:#}")))?;
+    }
+    if changed {
+        defaults.save(&index_dir)?;
+    }
+
+    let boosts = defaults.boosts.apply(config::settings().boosts);
+    let dictionary = index_dictionary(&index_dir).ok();
+    if json {
+        return print_json(&serde_json::json!({
+            "index": args.name,
+            "defaults": defaults,
+            "boosts": boosts,
+            "dictionary": dictionary,
+        }));
+    }
+    println!("Search defaults of {}", args.name);
+    if defaults.exclude_domains.is_empty() {
+        println!("  Excluded domains: none");
+    } else {
+        println!(
+            "  Excluded domains: {}",
+            defaults.exclude_domains.join(", ")
+        );
+    }
+    match defaults.snippet_length {
+        Some(length) => println!("  Snippet length:   {length}"),
+        None => println!(
+            "  Snippet length:   {} (configured)",
+            config::settings().snippet.max_length
+        ),
+    }
+    println!(
+        "  Boosts:           title {}, url {}, headings {}, content {}",
+        boosts.title, boosts.url, boosts.headings, boosts.content
+    );
+    if let Some(dictionary) = dictionary {
+        println!("  Dictionary:       {dictionary} (chosen when the index is built)");
+    }
+    if changed {
+        println!("Servers apply the change when they reopen the index (reload_index or a restart)");
+    }
+    Ok(())
+}
+
+/// Build an index from a bookmark manager export or API
+async fn run_import(args: ImportArgs, json: bool) -> Result<()> {
+    let bookmarks = match (args.from, &args.file, std::env::var(pinboard::TOKEN_ENV)) {
+        (Browser::Pinboard, None, Ok(token)) => {
+            pinboard::parse_posts(&pinboard::fetch_posts(&token).await?)?
+        }
+        (source, file, _) => source.read_bookmarks(file.as_deref(), None)?,
+    };
+    let limits = args.limits.resolve();
+    build_index(
+        &args.name,
+        &bookmarks,
+        &limits,
+        args.dictionary,
+        args.wait,
+        json,
+    )
+}
+
+/// Fetch the URLs of a list and add them to an index
+async fn run_ingest(args: IngestArgs, json: bool) -> Result<()> {
+    use std::io::{IsTerminal, Write};
+
+    let name = config::settings()
+        .resolve_index_name(args.index)
+        .ok_or_else(index_required)?;
+    if !IndexRegistry::is_valid_index_name(&name) {
+        return Err(CliError::Usage(format!("Invalid index name: {name}")).into());
+    }
+    if args.concurrency == 0 {
+        return Err(CliError::Usage("--concurrency must be at least 1".to_string()).into());
+    }
+    let text = if args.file == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).context("Failed to read standard input")?
+    } else {
+        std::fs::read_to_string(&args.file)
+            .with_context(|| format!("Failed to read {:?}", args.file))?
+    };
+    let entries = ingest::parse_url_list(&text);
+    if entries.is_empty() {
+        return Err(CliError::Usage(format!("No URLs in {}", args.file.display())).into());
+    }
+    let index_dir = config::data_dir().join(&name);
+    let dictionary = resolve_dictionary(&name, &index_dir, args.dictionary)?;
+    let folder_path: Vec<String> = args
+        .folder
+        .as_deref()
+        .unwrap_or("")
+        .split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let total = entries.len();
+    let show_progress = !json && std::io::stderr().is_terminal();
+    if !json {
+        println!("Fetching {total} URLs");
+    }
+    let fetcher = ContentFetcher::new()?;
+    let report = ingest::fetch_all(
+        &fetcher,
+        entries,
+        &folder_path,
+        args.concurrency,
+        |done, report| {
+            if show_progress {
+                eprint!(
+                    "\r{} {} failed",
+                    ingest::progress_bar(done, total, 30),
+                    report.failed.len() + report.skipped_robots.len()
+                );
+                let _ = std::io::stderr().flush();
+            }
+        },
+    )
+    .await;
+    if show_progress {
+        eprintln!();
+    }
+
+    if let Some(path) = &args.failed_urls {
+        let failed: String = report
+            .failed
+            .iter()
+            .map(|failure| failure.url.as_str())
+            .chain(report.skipped_robots.iter().map(String::as_str))
+            .map(|url| format!("{url}\n"))
+            .collect();
+        std::fs::write(path, failed).with_context(|| format!("Failed to write {path:?}"))?;
+    }
+    if report.pages.is_empty() {
+        let reason = report
+            .failed
+            .first()
+            .map(|failure| format!(": {} ({})", failure.url, failure.error))
+            .unwrap_or_default();
+        anyhow::bail!("None of the {total} URLs could be indexed{reason}");
+    }
+
+    // Only held for the write, not the fetches
+    let documents = ingest::write_pages(
+        &name,
+        dictionary,
+        &report.pages,
+        MutationSource::Cli,
+        Mutation::Ingest,
+        "mcp-bookmark ingest",
+        lock_wait(args.wait),
+    )?;
+
+    if json {
+        return print_json(&serde_json::json!({
+            "index": name,
+            "urls": total,
+            "pages": report.pages.len(),
+            "documents": documents,
+            "skipped_robots": report.skipped_robots,
+            "failed": report.failed,
+        }));
+    }
+    println!(
+        "Indexed {} of {total} URLs into {name} ({documents} documents)",
+        report.pages.len()
+    );
+    if !report.skipped_robots.is_empty() {
+        println!(
+            "Skipped {} URLs disallowed by robots.txt",
+            report.skipped_robots.len()
+        );
+    }
+    for failure in &report.failed {
+        println!("Failed: {}: {}", failure.url, failure.error);
+    }
+    Ok(())
+}
+
+/// Index named by `--index`, `INDEX_NAME` or the config file, checked for validity
+fn feed_index(index: Option<String>) -> Result<String> {
+    let name = config::settings()
+        .resolve_index_name(index)
+        .ok_or_else(index_required)?;
+    if !IndexRegistry::is_valid_index_name(&name) {
+        return Err(CliError::Usage(format!("Invalid index name: {name}")).into());
+    }
+    Ok(name)
+}
+
+async fn run_feeds(command: FeedsCommand, json: bool) -> Result<()> {
+    match command {
+        FeedsCommand::Add(args) => {
+            let name = feed_index(args.index)?;
+            let path = FeedList::path_for(&name);
+            let mut list = FeedList::load(&path)?;
+            let feed = feeds::fetch_feed(&ContentFetcher::new()?, &args.url).await?;
+            let subscription = Subscription {
+                title: feed.title.clone(),
+                ..Subscription::new(&args.url)
+            };
+            if !list.add(subscription) {
+                return Err(CliError::Usage(format!(
+                    "{name} is already subscribed to {}",
+                    args.url
+                ))
+                .into());
             }
-            "--list-indexes" => {
-                list_indexes();
-                std::process::exit(0);
+            list.save(&path)?;
+            if json {
+                return print_json(&serde_json::json!({
+                    "index": name,
+                    "url": args.url,
+                    "title": feed.title,
+                    "entries": feed.entries.len(),
+                }));
             }
-            "--clear-index" => {
-                if i + 1 < args.len() {
-                    i += 1; // Skip to the index name argument
-                    clear_index(Some(&args[i]));
-                    std::process::exit(0);
-                } else {
-                    println!("Error: --clear-index requires an index name");
-                    std::process::exit(1);
-                }
+            println!(
+                "Subscribed {name} to {} ({} entries); index them with `mcp-bookmark feeds refresh --index {name}`",
+                feed.title.as_deref().unwrap_or(&args.url),
+                feed.entries.len()
+            );
+        }
+        FeedsCommand::Remove(args) => {
+            let name = feed_index(args.index)?;
+            let path = FeedList::path_for(&name);
+            let mut list = FeedList::load(&path)?;
+            if !list.remove(&args.url) {
+                return Err(
+                    CliError::Usage(format!("{name} is not subscribed to {}", args.url)).into(),
+                );
             }
-            "--clear-all-indexes" => {
-                clear_all_indexes();
-                std::process::exit(0);
+            list.save(&path)?;
+            if json {
+                return print_json(&serde_json::json!({ "index": name, "removed": args.url }));
             }
-            _ => {
-                // Try to parse as number (max bookmarks)
-                if let Ok(max) = arg.parse::<usize>() {
-                    config.max_bookmarks = max;
+            println!("Unsubscribed {name} from {}", args.url);
+        }
+        FeedsCommand::List(args) => {
+            let names = match args.index {
+                Some(index) => vec![feed_index(Some(index))?],
+                None => FeedList::indexes(),
+            };
+            let mut lists = Vec::new();
+            for name in names {
+                let list = FeedList::load(&FeedList::path_for(&name))?;
+                lists.push((name, list));
+            }
+            if json {
+                let lists: serde_json::Map<_, _> = lists
+                    .into_iter()
+                    .map(|(name, list)| (name, serde_json::json!(list.feeds)))
+                    .collect();
+                return print_json(&serde_json::Value::Object(lists));
+            }
+            if lists.iter().all(|(_, list)| list.feeds.is_empty()) {
+                println!("No feeds. Subscribe with `mcp-bookmark feeds add URL --index NAME`");
+            }
+            for (name, list) in lists.iter().filter(|(_, list)| !list.feeds.is_empty()) {
+                println!("{name}:");
+                for feed in &list.feeds {
+                    let refreshed = feed.last_refreshed.map_or_else(
+                        || "never refreshed".to_string(),
+                        |time| format!("refreshed {}", time.format("%Y-%m-%d %H:%M UTC")),
+                    );
+                    println!(
+                        "  {} - {} ({} entries indexed, {refreshed})",
+                        feed.title.as_deref().unwrap_or("(untitled)"),
+                        feed.url,
+                        feed.seen.len()
+                    );
+                }
+            }
+        }
+        FeedsCommand::Refresh(args) => {
+            let names = if args.all {
+                FeedList::indexes()
+            } else {
+                vec![feed_index(args.index)?]
+            };
+            let fetcher = ContentFetcher::new()?;
+            let mut results = serde_json::Map::new();
+            for name in names {
+                if !json {
+                    println!("Refreshing the feeds of {name}");
                 }
+                let refreshes = feeds::refresh_index(
+                    &fetcher,
+                    &name,
+                    args.max_entries,
+                    MutationSource::Cli,
+                    lock_wait(args.wait),
+                )
+                .await?;
+                if json {
+                    results.insert(name, serde_json::json!(refreshes));
+                    continue;
+                }
+                if refreshes.is_empty() {
+                    println!("  No feeds");
+                }
+                for refresh in &refreshes {
+                    let title = refresh.title.as_deref().unwrap_or(&refresh.url);
+                    if let Some(error) = &refresh.error {
+                        println!("  {title}: {error}");
+                        continue;
+                    }
+                    println!(
+                        "  {title}: {} new, {} indexed",
+                        refresh.new_entries, refresh.indexed
+                    );
+                    for failure in &refresh.failed {
+                        println!("    Failed: {}: {}", failure.url, failure.error);
+                    }
+                }
+            }
+            if json {
+                return print_json(&serde_json::Value::Object(results));
             }
         }
-        i += 1;
+    }
+    Ok(())
+}
+
+fn build_index(
+    name: &str,
+    bookmarks: &[mcp_bookmark::bookmark::FlatBookmark],
+    limits: &BookmarkLimits,
+    dictionary: Option<LinderaDictionary>,
+    wait: Option<u64>,
+    json: bool,
+) -> Result<()> {
+    if !IndexRegistry::is_valid_index_name(name) {
+        return Err(CliError::Usage(format!("Invalid index name: {name}")).into());
+    }
+    let index_dir = config::data_dir().join(name);
+    let dictionary = resolve_dictionary(name, &index_dir, dictionary)?;
+    if !json {
+        println!("Read {} bookmarks", bookmarks.len());
     }
 
-    // Read INDEX_NAME from environment variable (required)
-    if let Ok(index_name) = env::var("INDEX_NAME") {
-        tracing::info!("Using index: {}", index_name);
-        config.index_name = Some(index_name);
+    let _lock = lock_index(&index_dir, "index", wait)?;
+    let manager = SearchManager::new_with_dictionary(index_dir.clone(), dictionary)?;
+    let previous = manager.get_stats().ok().map(|stats| stats.total_documents);
+    let skipped = manager.build_index_with_limits(bookmarks, limits)?;
+    AuditLog::record(
+        name,
+        AuditEntry::new(MutationSource::Cli, Mutation::Build)
+            .with_added(manager.get_stats()?.total_documents)
+            .with_removed(previous),
+    );
+
+    if json {
+        print_json(&serde_json::json!({
+            "index": name,
+            "bookmarks": bookmarks.len(),
+            "skipped": skipped,
+            "path": index_dir,
+            "dictionary": index_dictionary(&index_dir)?,
+        }))
     } else {
-        eprintln!("Error: INDEX_NAME environment variable is required");
-        eprintln!();
-        eprintln!("Please specify the index to use:");
-        eprintln!("  export INDEX_NAME=your_index_name");
-        eprintln!();
-        eprintln!("Available indexes:");
-        list_available_indexes();
-        std::process::exit(1);
+        if skipped > 0 {
+            println!("Left out {skipped} bookmarks over the limits");
+        }
+        println!("Index built: {name}");
+        println!("Start the server with: mcp-bookmark --index {name}");
+        Ok(())
     }
-
-    Ok(config)
 }
 
-/// Print help message
-fn print_help() {
-    println!("Chrome Bookmark MCP Server (Simplified)\n");
-    println!("Usage: mcp-bookmark [options]\n");
-    println!("Environment variables:");
-    println!("  INDEX_NAME       Name of the index to use (required)\n");
-    println!("Options:");
-    println!("  --help, -h            Show this help message");
-    println!("  --list-indexes        List all available indexes");
-    println!("  --clear-index <name>  Clear specific index");
-    println!("  --clear-all-indexes   Clear all indexes\n");
-    println!("Examples:");
-    println!("  INDEX_NAME=my_work_bookmarks mcp-bookmark");
-    println!("  INDEX_NAME=Extension_Development mcp-bookmark");
+/// The dictionary to open an index with: the requested one, or the config's for a new index
+fn resolve_dictionary(
+    name: &str,
+    index_dir: &Path,
+    requested: Option<LinderaDictionary>,
+) -> Result<LinderaDictionary> {
+    Ok(match requested {
+        // The dictionary of an existing index can't change without a rebuild
+        Some(requested) if index_dir.exists() => {
+            let recorded = index_dictionary(index_dir)?;
+            if requested != recorded {
+                return Err(CliError::Usage(format!(
+                    "Index {name} uses the {recorded} dictionary; clear it first to switch to {requested}"
+                ))
+                .into());
+            }
+            requested
+        }
+        Some(requested) => requested,
+        None => config::settings().tokenizer.dictionary,
+    })
 }
 
 /// List available indexes (simplified output)
 fn list_available_indexes() {
-    let base_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("mcp-bookmark");
+    let base_dir = config::data_dir();
 
     if !base_dir.exists() {
         println!("  No indexes found. Use the Chrome extension to create one.");
@@ -118,10 +1980,28 @@ fn list_available_indexes() {
 }
 
 /// List all available indexes
-fn list_indexes() {
-    let base_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("mcp-bookmark");
+fn list_indexes(json: bool) -> Result<()> {
+    let aliases = IndexAliases::load_configured()?;
+    if json {
+        let registry = IndexRegistry::new(1);
+        return print_json(&serde_json::json!({
+            "data_dir": config::data_dir(),
+            "indexes": registry.available_indexes(),
+            "aliases": aliases,
+        }));
+    }
+    list_indexes_text();
+    if !aliases.is_empty() {
+        println!("\nAliases:");
+        for (alias, members) in aliases.iter() {
+            println!("  {alias} = {}", members.join(", "));
+        }
+    }
+    Ok(())
+}
+
+fn list_indexes_text() {
+    let base_dir = config::data_dir();
 
     println!("Available indexes:");
     println!("==================\n");
@@ -180,60 +2060,219 @@ fn list_indexes() {
     }
 }
 
-/// Clear specific index
-fn clear_index(index_name: Option<&str>) {
-    let Some(name) = index_name else {
-        println!("Error: Index name is required");
-        return;
+/// Move one or all indexes to the trash (or delete them with --purge)
+fn run_clear(args: ClearArgs, json: bool) -> Result<()> {
+    let base_dir = config::data_dir();
+
+    let names = match args.name {
+        Some(name) => {
+            if !IndexRegistry::is_valid_index_name(&name)
+                || !base_dir.join(&name).join("meta.json").exists()
+            {
+                return Err(CliError::IndexNotFound(name).into());
+            }
+            vec![name]
+        }
+        None => {
+            let mut names: Vec<String> = std::fs::read_dir(&base_dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| IndexRegistry::is_valid_index_name(name))
+                .filter(|name| base_dir.join(name).join("meta.json").exists())
+                .collect();
+            names.sort();
+            names
+        }
     };
 
-    let base_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("mcp-bookmark");
+    if args.dry_run || names.is_empty() {
+        if json {
+            return print_json(&serde_json::json!({ "dry_run": args.dry_run, "cleared": names }));
+        }
+        if names.is_empty() {
+            println!("No indexes found.");
+        } else {
+            println!("Would clear {} indexes:", names.len());
+            for name in &names {
+                println!("  - {name}");
+            }
+        }
+        return Ok(());
+    }
 
-    let index_dir = base_dir.join(name);
+    if !args.yes {
+        let action = if args.purge {
+            "Permanently delete"
+        } else {
+            "Move to the trash"
+        };
+        let prompt = match names.as_slice() {
+            [name] => format!("{action} index '{name}'?"),
+            _ => format!("{action} {} indexes ({})?", names.len(), names.join(", ")),
+        };
+        if !confirm(&prompt)? {
+            return Err(CliError::Usage("Aborted".to_string()).into());
+        }
+    }
 
-    if !index_dir.exists() {
-        println!("Index not found: {name}");
-        return;
+    let trash = IndexTrash::new(&base_dir);
+    let mut cleared = Vec::new();
+    let mut failed = 0;
+    let single = names.len() == 1;
+    for name in names {
+        // Held only while checking: a directory with an open lock file can't be moved on Windows
+        if let Err(e) = lock_index(&base_dir.join(&name), "clear", args.wait) {
+            if single {
+                return Err(e);
+            }
+            eprintln!("Failed to clear {name}: {e:#}");
+            failed += 1;
+            continue;
+        }
+        let documents = IndexInfo::read(&name, &base_dir.join(&name))
+            .ok()
+            .map(|info| info.document_count);
+        let result = if args.purge {
+            std::fs::remove_dir_all(base_dir.join(&name)).map_err(anyhow::Error::from)
+        } else {
+            trash.trash(&name).map(|_| ())
+        };
+        match result {
+            Ok(()) => {
+                let mutation = if args.purge {
+                    Mutation::Purge
+                } else {
+                    Mutation::Clear
+                };
+                AuditLog::record(
+                    &name,
+                    AuditEntry::new(MutationSource::Cli, mutation).with_removed(documents),
+                );
+                cleared.push(name);
+            }
+            Err(e) => {
+                eprintln!("Failed to clear {name}: {e:#}");
+                failed += 1;
+            }
+        }
     }
 
-    match std::fs::remove_dir_all(&index_dir) {
-        Ok(_) => println!("Index cleared: {name}"),
-        Err(e) => println!("Failed to clear index: {e}"),
+    if json {
+        print_json(&serde_json::json!({ "cleared": cleared, "purged": args.purge }))?;
+    } else {
+        for name in &cleared {
+            println!("Index cleared: {name}");
+        }
+        if !args.purge && !cleared.is_empty() {
+            println!("Restore with: mcp-bookmark restore <name>");
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("Failed to clear {failed} indexes");
     }
+    Ok(())
 }
 
-/// Clear all indexes
-fn clear_all_indexes() {
-    let base_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("mcp-bookmark");
+/// Ask a yes/no question on the terminal; refuses when there is no terminal
+fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::{BufRead, IsTerminal, Write};
 
-    if !base_dir.exists() {
-        println!("No indexes found.");
-        return;
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::Usage(
+            "Confirmation required; pass --yes to run without a terminal".to_string(),
+        )
+        .into());
     }
+    print!("{prompt} [y/N]: ");
+    std::io::stdout().flush()?;
 
-    let mut cleared = 0;
-    if let Ok(entries) = std::fs::read_dir(&base_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() && path.file_name().unwrap() != "logs" {
-                if let Err(e) = std::fs::remove_dir_all(&path) {
-                    println!("Failed to clear {path:?}: {e}");
-                } else {
-                    cleared += 1;
-                }
-            }
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Restore a cleared index or list the trash
+fn run_restore(args: RestoreArgs, json: bool) -> Result<()> {
+    let trash = IndexTrash::new(&config::data_dir());
+
+    let Some(name) = args.name else {
+        let trashed = trash.list();
+        if json {
+            return print_json(&serde_json::json!({ "trash": trashed }));
+        }
+        if trashed.is_empty() {
+            println!("Trash is empty.");
+        }
+        for entry in trashed {
+            println!("  {} (cleared {})", entry.name, entry.deleted_at);
         }
+        return Ok(());
+    };
+
+    if !trash.list().iter().any(|entry| entry.name == name) {
+        return Err(CliError::IndexNotFound(name).into());
+    }
+    let entry = trash.restore(&name)?;
+    let mut audit = AuditEntry::new(MutationSource::Cli, Mutation::Restore);
+    if let Ok(info) = IndexInfo::read(&name, &config::data_dir().join(&name)) {
+        audit = audit.with_added(info.document_count);
+    }
+    AuditLog::record(&name, audit);
+    if json {
+        print_json(&serde_json::json!({ "restored": entry.name, "deleted_at": entry.deleted_at }))
+    } else {
+        println!("Index restored: {name}");
+        Ok(())
+    }
+}
+
+/// Serve the MCP server over Streamable HTTP so multiple clients can share it
+async fn serve_http(server: BookmarkServer, addr: &str, prometheus: bool) -> Result<()> {
+    let metrics_server = server.clone();
+    let service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+
+    let mut router = axum::Router::new().nest_service("/mcp", service);
+    if prometheus {
+        router = router.route(
+            "/metrics",
+            axum::routing::get(move || {
+                let server = metrics_server.clone();
+                async move {
+                    (
+                        [(
+                            axum::http::header::CONTENT_TYPE,
+                            "text/plain; version=0.0.4",
+                        )],
+                        server.prometheus_metrics(),
+                    )
+                }
+            }),
+        );
+    }
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    tracing::info!("Serving MCP over HTTP at http://{}/mcp", addr);
+    if prometheus {
+        tracing::info!("Serving Prometheus metrics at http://{}/metrics", addr);
     }
 
-    println!("Cleared {cleared} indexes.");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
 }
 
 /// Get directory size recursively
-fn get_dir_size(path: &std::path::Path) -> Result<u64> {
+fn get_dir_size(path: &Path) -> Result<u64> {
     let mut size = 0;
     if path.is_dir() {
         for entry in std::fs::read_dir(path)? {
@@ -249,60 +2288,61 @@ fn get_dir_size(path: &std::path::Path) -> Result<u64> {
     Ok(size)
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging with file output
-    let log_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("mcp-bookmark")
-        .join("logs");
-
-    // Create log directory if it doesn't exist
-    std::fs::create_dir_all(&log_dir).ok();
-
-    // Create file appender with daily rotation
-    let file_appender = rolling::daily(log_dir.clone(), "mcp-bookmark.log");
-    let (non_blocking_file, _guard) = non_blocking(file_appender);
-
-    // Create console writer for stderr
-    let (non_blocking_console, _guard2) = non_blocking(std::io::stderr());
-
-    // Set up logging to both file and console
-    let env_filter = EnvFilter::from_default_env()
-        .add_directive(tracing::Level::INFO.into())
-        .add_directive("tantivy=warn".parse().unwrap())
-        .add_directive("mcp_bookmark::search::indexer=debug".parse().unwrap())
-        .add_directive("mcp_bookmark::search::content_index=info".parse().unwrap());
-
-    let file_layer = fmt::layer()
-        .with_writer(non_blocking_file)
-        .with_ansi(false)
-        .with_target(true)
-        .with_thread_ids(false)
-        .with_thread_names(false);
-
-    let console_layer = fmt::layer()
-        .with_writer(non_blocking_console)
-        .with_ansi(false)
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_thread_names(false);
-
-    tracing_subscriber::registry()
-        .with(env_filter)
-        .with(file_layer)
-        .with(console_layer)
-        .init();
-
-    tracing::debug!("Logging to: {}", log_dir.display());
-
-    // Parse command-line arguments
-    let config = parse_args()?;
+/// Start the MCP server over stdio or Streamable HTTP, with the daemon's tasks if `daemon`
+async fn serve(args: ServeArgs, daemon: bool) -> Result<()> {
+    let settings = config::settings();
+    let index_name = match &args.index_path {
+        Some(index_dir) => Some(external_index_name(index_dir)?),
+        None => settings.resolve_index_name(args.index),
+    };
+    let Some(index_name) = index_name else {
+        eprintln!("Error: no index selected");
+        eprintln!();
+        eprintln!("Please specify the index to use (first match wins):");
+        eprintln!("  mcp-bookmark --index your_index_name    (comma-separated for multi-index)");
+        eprintln!("  mcp-bookmark --index-path /mnt/shared/index_dir");
+        eprintln!("  export INDEX_NAME=your_index_name");
+        eprintln!(
+            "  [index] default = \"your_index_name\"   in ~/.config/mcp-bookmark/config.toml"
+        );
+        eprintln!();
+        eprintln!("Available indexes:");
+        list_available_indexes();
+        std::process::exit(exit_code::USAGE);
+    };
+    let prometheus = args.metrics;
+    let config = Config {
+        index_name: Some(index_name),
+        index_path: args.index_path,
+        max_bookmarks: args.max_bookmarks,
+        http_addr: args.http,
+        ..settings.to_config()
+    };
+    let base_dir = config::data_dir();
+    // An --index-path directory was checked above
+    if let Some(missing) = config
+        .parse_index_names()
+        .into_iter()
+        .find(|name| config.index_path.is_none() && !base_dir.join(name).exists())
+    {
+        if IndexRegistry::is_index_pattern(&missing) {
+            eprintln!("Error: No index matches {missing}");
+        } else {
+            eprintln!("Error: Index not found: {missing}");
+        }
+        eprintln!();
+        eprintln!("Available indexes:");
+        list_available_indexes();
+        std::process::exit(exit_code::NOT_FOUND);
+    }
 
     tracing::info!("Starting Chrome Bookmark MCP Server (Simplified)");
     if let Some(index_name) = &config.index_name {
         tracing::info!("Using index: {}", index_name);
     }
+    if let Some(index_dir) = &config.index_path {
+        tracing::info!("Index directory: {}", index_dir.display());
+    }
     if config.max_bookmarks > 0 {
         tracing::debug!("Max bookmarks: {}", config.max_bookmarks);
     }
@@ -313,7 +2353,19 @@ async fn main() -> Result<()> {
     // Initialize search manager (always use read-only mode for pre-built indexes)
     tracing::debug!("Initializing search index...");
 
-    let search_manager: Arc<dyn SearchManagerTrait> = if config.is_multi_index() {
+    let search_manager: Arc<dyn SearchManagerTrait> = if let Some(index_dir) = &config.index_path {
+        match SearchManager::open_readonly_at(index_dir) {
+            Ok(manager) => Arc::new(manager),
+            Err(e) => {
+                tracing::error!("Failed to open index: {}", e);
+                eprintln!(
+                    "Error: Failed to open index at {}: {e:#}",
+                    index_dir.display()
+                );
+                std::process::exit(exit_code::ERROR);
+            }
+        }
+    } else if config.is_multi_index() {
         // Use multi-index search manager
         tracing::info!("Initializing multi-index search");
         match mcp_bookmark::search::MultiIndexSearchManager::new(&config) {
@@ -327,17 +2379,18 @@ async fn main() -> Result<()> {
                 eprintln!("Error: Failed to initialize multi-index search: {e}");
                 eprintln!("\nPlease check:");
                 eprintln!(
-                    "  1. All specified indices exist (use --list-indexes to see available indexes)"
+                    "  1. All specified indices exist (use `mcp-bookmark list` to see available indexes)"
                 );
                 eprintln!("  2. The indices were created using the Chrome extension");
                 eprintln!("  3. The index names are correct");
-                std::process::exit(1);
+                std::process::exit(exit_code::ERROR);
             }
         }
     } else {
-        // Single index mode
+        // Single index mode (the name may be an alias or pattern of one index)
+        let names = config.parse_index_names();
         match mcp_bookmark::search::SearchManager::open_readonly(
-            config.index_name.as_deref().unwrap(),
+            names.first().map_or("", String::as_str),
         ) {
             Ok(manager) => {
                 tracing::info!("Using index in read-only mode (lock-free)");
@@ -351,22 +2404,647 @@ async fn main() -> Result<()> {
                     e
                 );
                 eprintln!("\nPlease check:");
-                eprintln!("  1. The index exists (use --list-indexes to see available indexes)");
+                eprintln!(
+                    "  1. The index exists (use `mcp-bookmark list` to see available indexes)"
+                );
                 eprintln!("  2. The index was created using the Chrome extension");
                 eprintln!("  3. The index name is correct");
-                std::process::exit(1);
+                std::process::exit(exit_code::ERROR);
             }
         }
     };
 
+    // Startup self-test: problems are logged, the server starts anyway
+    let report = HealthReport::run(
+        config.index_name.clone(),
+        Ok(search_manager.clone()),
+        &base_dir,
+    )
+    .await;
+    for check in report.failures() {
+        tracing::warn!("Health check '{}' failed: {}", check.name, check.detail);
+    }
+
     tracing::info!("Server ready");
     tracing::info!("{}", search_manager.get_indexing_status());
 
-    let server = BookmarkServer::new(reader, search_manager);
+    let server = BookmarkServer::with_config(reader, search_manager, config.clone());
+
+    let daemon = if daemon {
+        let serving = config
+            .http_addr
+            .as_ref()
+            .map_or_else(|| "stdio".to_string(), |addr| format!("http://{addr}/mcp"));
+        Some(Daemon::start(
+            config.parse_index_names(),
+            serving,
+            &settings.daemon,
+        )?)
+    } else {
+        None
+    };
 
     // Serve the MCP server
+    let result = if let Some(addr) = &config.http_addr {
+        serve_http(server, addr, prometheus).await
+    } else {
+        serve_stdio(server).await
+    };
+
+    if let Some(daemon) = daemon {
+        daemon.stop()?;
+    }
+    result
+}
+
+/// Serve the MCP server over stdio until the client disconnects or Ctrl-C
+async fn serve_stdio(server: BookmarkServer) -> Result<()> {
     let service = server.serve(stdio()).await?;
-    service.waiting().await?;
+    tokio::select! {
+        result = service.waiting() => {
+            result?;
+        }
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            std::process::exit(exit_code::ERROR);
+        }
+    };
+    config::init(settings);
+
+    let json = cli.json;
+    let command = if cli.list_indexes {
+        Command::List
+    } else if cli.clear_all_indexes {
+        Command::Clear(ClearArgs {
+            name: None,
+            all: true,
+            dry_run: false,
+            yes: false,
+            purge: false,
+            wait: None,
+        })
+    } else if let Some(name) = cli.clear_index {
+        Command::Clear(ClearArgs {
+            name: Some(name),
+            all: false,
+            dry_run: false,
+            yes: false,
+            purge: false,
+            wait: None,
+        })
+    } else {
+        cli.command.unwrap_or(Command::Serve(cli.serve))
+    };
+
+    // Initialize logging
+    let log_settings = &config::settings().log;
+    let log_dir = logs::log_dir();
+
+    // The TUI owns the terminal and the completion helpers print to the shell,
+    // so they only log to the file
+    let quiet = matches!(
+        command,
+        Command::Tui(_) | Command::Completions(_) | Command::CompleteIndexes { .. }
+    );
+
+    let (file_writer, _file_guard) = if log_settings.destination.to_file() {
+        std::fs::create_dir_all(&log_dir).ok();
+        // Age-based retention isn't handled by the appender itself
+        if let Err(e) = logs::prune(&log_dir, 0, log_settings.max_age_days) {
+            eprintln!("Warning: {e:#}");
+        }
+        let mut builder = rolling::Builder::new()
+            .rotation(rolling::Rotation::DAILY)
+            .filename_prefix(logs::LOG_FILE_PREFIX);
+        if log_settings.max_files > 0 {
+            builder = builder.max_log_files(log_settings.max_files);
+        }
+        match builder.build(&log_dir) {
+            Ok(appender) => {
+                let (writer, guard) = non_blocking(appender);
+                (Some(writer), Some(guard))
+            }
+            Err(e) => {
+                eprintln!("Warning: file logging disabled: {e}");
+                (None, None)
+            }
+        }
+    } else {
+        (None, None)
+    };
+
+    let (console_writer, _console_guard) = if log_settings.destination.to_console() && !quiet {
+        let (writer, guard) = non_blocking(std::io::stderr());
+        (Some(writer), Some(guard))
+    } else {
+        (None, None)
+    };
+
+    let env_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(config::settings().log_level()))
+        .add_directive("tantivy=warn".parse().unwrap())
+        .add_directive("mcp_bookmark::search::indexer=debug".parse().unwrap())
+        .add_directive("mcp_bookmark::search::content_index=info".parse().unwrap());
+
+    let file_layer = file_writer.map(|writer| {
+        fmt::layer()
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_target(true)
+            .with_thread_ids(false)
+            .with_thread_names(false)
+    });
+
+    let console_layer = console_writer.map(|writer| {
+        fmt::layer()
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_target(false)
+            .with_thread_ids(false)
+            .with_thread_names(false)
+    });
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(file_layer)
+        .with(console_layer)
+        .init();
+
+    if log_settings.destination.to_file() {
+        tracing::debug!("Logging to: {}", log_dir.display());
+    }
+
+    let result = match command {
+        Command::Serve(args) if args.health => run_health(args.index, args.index_path, json).await,
+        Command::Serve(args) => serve(args, false).await,
+        Command::Daemon(args) => {
+            let args = ServeArgs {
+                index: args.index,
+                index_path: None,
+                http: args.http,
+                metrics: args.metrics,
+                max_bookmarks: 0,
+                health: false,
+            };
+            serve(args, true).await
+        }
+        Command::Status => show_status(json),
+        Command::Index(args) => run_index(args, json).await,
+        Command::Import(args) => run_import(args, json).await,
+        Command::Ingest(args) => run_ingest(args, json).await,
+        Command::Feeds(command) => run_feeds(command, json).await,
+        Command::Search(args) => run_search(args, json).await,
+        Command::Tui(args) => run_tui(args).await,
+        Command::List => list_indexes(json),
+        Command::Clear(args) => run_clear(args, json),
+        Command::Restore(args) => run_restore(args, json),
+        Command::Export(args) => run_export(args, json).await,
+        Command::Stats(args) => run_stats(args),
+        Command::History(args) => run_history(args, json),
+        Command::Tokenize(args) => run_tokenize(args, json),
+        Command::InstallExtensionHost(args) => run_install_extension_host(args, json),
+        Command::Config(ConfigCommand::Show) => show_config(json),
+        Command::Logs(LogsCommand::Prune(args)) => run_logs_prune(args, json),
+        Command::Completions(args) => {
+            print!("{}", completion_script(args.shell));
+            Ok(())
+        }
+        Command::CompleteIndexes { trash } => {
+            complete_indexes(trash);
+            Ok(())
+        }
+    };
 
+    if let Err(e) = result {
+        let code = CliError::exit_code(&e);
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({ "error": format!("{e:#}"), "exit_code": code })
+            );
+        } else {
+            eprintln!("Error: {e:#}");
+        }
+        std::process::exit(code);
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_scripts() {
+        let bash = completion_script(CompletionShell::Bash);
+        assert!(bash.contains("complete -F _mcp_bookmark_dynamic"));
+        assert!(bash.contains("mcp-bookmark complete-indexes --trash"));
+
+        let zsh = completion_script(CompletionShell::Zsh);
+        assert!(zsh.starts_with("#compdef mcp-bookmark"));
+        assert!(zsh.contains("compdef _mcp_bookmark_dynamic mcp-bookmark"));
+
+        let fish = completion_script(CompletionShell::Fish);
+        assert!(fish.contains("-l index -r -f -a '(mcp-bookmark complete-indexes 2>/dev/null)'"));
+    }
+
+    #[test]
+    fn test_completion_function() {
+        let script = "_foo() {\n}\ncomplete -F _foo -o nosort foo\n";
+        assert_eq!(
+            completion_function(script, "complete -F "),
+            Some("_foo".to_string())
+        );
+        assert_eq!(completion_function("compdef foo", "compdef "), None);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let not_found: anyhow::Error = CliError::IndexNotFound("work".to_string()).into();
+        assert_eq!(CliError::exit_code(&not_found), exit_code::NOT_FOUND);
+        assert_eq!(
+            CliError::exit_code(&not_found.context("Failed to open")),
+            exit_code::NOT_FOUND
+        );
+        assert_eq!(
+            CliError::exit_code(&index_required().into()),
+            exit_code::USAGE
+        );
+        assert_eq!(
+            CliError::exit_code(&anyhow::anyhow!("disk full")),
+            exit_code::ERROR
+        );
+        let locked = IndexLockedError {
+            index_dir: PathBuf::from("/data/work"),
+            holder: None,
+        };
+        assert_eq!(CliError::exit_code(&locked.into()), exit_code::LOCKED);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let expected = chrono::DateTime::from_timestamp(1_714_564_800, 0).unwrap();
+        assert_eq!(parse_since("2024-05-01T12:00:00Z").unwrap(), expected);
+        assert_eq!(parse_since("2024-05-01T21:00:00+09:00").unwrap(), expected);
+        assert_eq!(parse_since("1714564800").unwrap(), expected);
+        assert!(parse_since("2024-05-01").is_ok());
+        assert!(parse_since("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_external_index_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_dir = dir.path().join("team_docs");
+        let missing = external_index_name(&index_dir).unwrap_err();
+        assert_eq!(CliError::exit_code(&missing), exit_code::NOT_FOUND);
+
+        std::fs::create_dir(&index_dir).unwrap();
+        std::fs::write(index_dir.join("meta.json"), "{}").unwrap();
+        assert_eq!(external_index_name(&index_dir).unwrap(), "team_docs");
+        let relative = external_index_name(Path::new("team_docs")).unwrap_err();
+        assert_eq!(CliError::exit_code(&relative), exit_code::USAGE);
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::parse_from(["mcp-bookmark", "--http", "127.0.0.1:8080"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.serve.http.as_deref(), Some("127.0.0.1:8080"));
+        assert!(!cli.serve.metrics);
+        assert!(Cli::try_parse_from(["mcp-bookmark", "--metrics"]).is_err());
+
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "daemon",
+            "--index",
+            "work,notes",
+            "--http",
+            "127.0.0.1:8080",
+            "--metrics",
+        ]);
+        let Some(Command::Daemon(args)) = cli.command else {
+            panic!("expected daemon");
+        };
+        assert_eq!(args.index.as_deref(), Some("work,notes"));
+        assert!(args.metrics);
+        assert!(Cli::try_parse_from(["mcp-bookmark", "daemon", "--metrics"]).is_err());
+        assert!(matches!(
+            Cli::parse_from(["mcp-bookmark", "status"]).command,
+            Some(Command::Status)
+        ));
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "install-extension-host",
+            "--extension-id",
+            "abcdefghijklmnopabcdefghijklmnop",
+            "--browser",
+            "edge",
+            "--browser",
+            "Chrome",
+        ]);
+        let Some(Command::InstallExtensionHost(args)) = cli.command else {
+            panic!("expected install-extension-host");
+        };
+        assert_eq!(
+            args.browsers,
+            vec![ChromiumBrowser::Edge, ChromiumBrowser::Chrome]
+        );
+        assert!(Cli::try_parse_from(["mcp-bookmark", "install-extension-host"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "mcp-bookmark",
+                "install-extension-host",
+                "--extension-id",
+                "abcdefghijklmnopabcdefghijklmnop",
+                "--browser",
+                "firefox"
+            ])
+            .is_err()
+        );
+
+        let cli = Cli::parse_from(["mcp-bookmark", "history", "--index", "work"]);
+        let Some(Command::History(args)) = cli.command else {
+            panic!("expected history");
+        };
+        assert_eq!(args.limit, 50);
+        let cli = Cli::parse_from(["mcp-bookmark", "--health", "--index", "work"]);
+        assert!(cli.serve.health);
+        assert!(
+            Cli::try_parse_from(["mcp-bookmark", "--health", "--http", "127.0.0.1:8080"]).is_err()
+        );
+        let cli = Cli::parse_from(["mcp-bookmark", "--index-path", "/mnt/shared/docs"]);
+        assert!(cli.command.is_none());
+        assert_eq!(
+            cli.serve.index_path,
+            Some(PathBuf::from("/mnt/shared/docs"))
+        );
+
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "search",
+            "rust",
+            "--index",
+            "work",
+            "--format",
+            "md",
+            "--strict",
+        ]);
+        let Some(Command::Search(args)) = cli.command else {
+            panic!("expected search");
+        };
+        assert_eq!(args.format, Some(ExportFormat::Markdown));
+        assert_eq!(args.limit, 20);
+        assert!(args.strict);
+
+        assert!(
+            Cli::try_parse_from(["mcp-bookmark", "index", "ff", "--browser", "pocket"]).is_err()
+        );
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "index",
+            "kr",
+            "--browser",
+            "chrome",
+            "--dictionary",
+            "ko-dic",
+            "--max-per-folder",
+            "50",
+            "--folder-limit",
+            "Bookmarks bar/Reading/=200",
+        ]);
+        let Some(Command::Index(args)) = cli.command else {
+            panic!("expected index");
+        };
+        assert_eq!(args.dictionary, Some(LinderaDictionary::KoDic));
+        assert!(args.command.is_none());
+        assert_eq!(args.limits.max_bookmarks, None);
+        assert_eq!(args.limits.max_per_folder, Some(50));
+        assert_eq!(
+            args.limits.folder_limit,
+            [("Bookmarks bar/Reading".to_string(), 200)]
+        );
+        assert!(Cli::try_parse_from(["mcp-bookmark", "index", "kr"]).is_err());
+
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "index",
+            "site",
+            "https://docs.example.com/guide/",
+            "--index",
+            "docs",
+            "--depth",
+            "3",
+            "--archive-fallback",
+        ]);
+        let Some(Command::Index(IndexArgs {
+            command: Some(IndexCommand::Site(args)),
+            ..
+        })) = cli.command
+        else {
+            panic!("expected index site");
+        };
+        assert_eq!(args.url, "https://docs.example.com/guide/");
+        assert_eq!(args.index.as_deref(), Some("docs"));
+        assert_eq!(args.depth, 3);
+        assert_eq!(args.max_pages, site::DEFAULT_MAX_PAGES);
+        assert!(!args.no_sitemap);
+        assert!(args.archive_fallback);
+
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "index",
+            "diff",
+            "work",
+            "--since",
+            "2024-05-01T12:00:00Z",
+            "-o",
+            "work.json",
+        ]);
+        let Some(Command::Index(IndexArgs {
+            command: Some(IndexCommand::Diff(args)),
+            ..
+        })) = cli.command
+        else {
+            panic!("expected index diff");
+        };
+        assert_eq!(args.name, "work");
+        assert_eq!(args.since.timestamp(), 1_714_564_800);
+        assert_eq!(args.output, Some(PathBuf::from("work.json")));
+        assert!(Cli::try_parse_from(["mcp-bookmark", "index", "diff", "work"]).is_err());
+
+        let cli = Cli::parse_from(["mcp-bookmark", "index", "apply", "-", "--index", "home"]);
+        let Some(Command::Index(IndexArgs {
+            command: Some(IndexCommand::Apply(args)),
+            ..
+        })) = cli.command
+        else {
+            panic!("expected index apply");
+        };
+        assert_eq!(args.file, PathBuf::from("-"));
+        assert_eq!(args.index.as_deref(), Some("home"));
+        assert!(args.wait.is_none());
+
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "index",
+            "defaults",
+            "work",
+            "--exclude-domain",
+            "ads.example.com",
+            "--boost",
+            "Title=5",
+            "--snippet-length",
+            "300",
+        ]);
+        let Some(Command::Index(IndexArgs {
+            command: Some(IndexCommand::Defaults(args)),
+            ..
+        })) = cli.command
+        else {
+            panic!("expected index defaults");
+        };
+        assert_eq!(args.exclude_domain, ["ads.example.com"]);
+        assert_eq!(args.boost, [("title".to_string(), 5.0)]);
+        assert_eq!(args.snippet_length, Some(300));
+        assert!(!args.reset);
+        assert!(
+            Cli::try_parse_from([
+                "mcp-bookmark",
+                "index",
+                "defaults",
+                "work",
+                "--boost",
+                "title"
+            ])
+            .is_err()
+        );
+
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "ingest",
+            "urls.txt",
+            "--index",
+            "research",
+            "--concurrency",
+            "8",
+            "--failed-urls",
+            "retry.txt",
+        ]);
+        let Some(Command::Ingest(args)) = cli.command else {
+            panic!("expected ingest");
+        };
+        assert_eq!(args.file, PathBuf::from("urls.txt"));
+        assert_eq!(args.index.as_deref(), Some("research"));
+        assert_eq!(args.concurrency, 8);
+        assert_eq!(args.failed_urls, Some(PathBuf::from("retry.txt")));
+        assert_eq!(args.folder, None);
+
+        let cli = Cli::parse_from(["mcp-bookmark", "feeds", "refresh", "--all"]);
+        let Some(Command::Feeds(FeedsCommand::Refresh(args))) = cli.command else {
+            panic!("expected feeds refresh");
+        };
+        assert!(args.all);
+        assert_eq!(args.max_entries, feeds::DEFAULT_MAX_ENTRIES);
+        assert!(
+            Cli::try_parse_from([
+                "mcp-bookmark",
+                "feeds",
+                "refresh",
+                "--all",
+                "--index",
+                "research"
+            ])
+            .is_err()
+        );
+        let cli = Cli::parse_from([
+            "mcp-bookmark",
+            "feeds",
+            "add",
+            "https://blog.example.com/feed.xml",
+            "--index",
+            "research",
+        ]);
+        let Some(Command::Feeds(FeedsCommand::Add(args))) = cli.command else {
+            panic!("expected feeds add");
+        };
+        assert_eq!(args.url, "https://blog.example.com/feed.xml");
+        assert_eq!(args.index.as_deref(), Some("research"));
+        assert!(
+            Cli::try_parse_from([
+                "mcp-bookmark",
+                "index",
+                "site",
+                "https://docs.example.com/",
+                "--browser",
+                "chrome"
+            ])
+            .is_err()
+        );
+        assert!(
+            Cli::try_parse_from([
+                "mcp-bookmark",
+                "import",
+                "p",
+                "--from",
+                "pocket",
+                "--dictionary",
+                "jieba"
+            ])
+            .is_err()
+        );
+        assert!(Cli::try_parse_from(["mcp-bookmark", "clear"]).is_err());
+        assert!(Cli::try_parse_from(["mcp-bookmark", "restore"]).is_err());
+
+        let cli = Cli::parse_from(["mcp-bookmark", "clear", "--all", "--dry-run", "-y"]);
+        let Some(Command::Clear(args)) = cli.command else {
+            panic!("expected clear");
+        };
+        assert!(args.all && args.dry_run && args.yes && !args.purge);
+        assert_eq!(args.wait, None);
+
+        let cli = Cli::parse_from(["mcp-bookmark", "clear", "--wait", "work"]);
+        let Some(Command::Clear(args)) = cli.command else {
+            panic!("expected clear");
+        };
+        assert_eq!(args.wait, Some(0));
+        assert_eq!(args.name.as_deref(), Some("work"));
+        let cli = Cli::parse_from(["mcp-bookmark", "clear", "--wait=30", "work"]);
+        let Some(Command::Clear(args)) = cli.command else {
+            panic!("expected clear");
+        };
+        assert_eq!(args.wait, Some(30));
+
+        let cli = Cli::parse_from(["mcp-bookmark", "tokenize", "ＡＰＩ", "--index", "work"]);
+        let Some(Command::Tokenize(args)) = cli.command else {
+            panic!("expected tokenize");
+        };
+        assert_eq!(args.field, "content");
+        assert_eq!(args.index.as_deref(), Some("work"));
+
+        let cli = Cli::parse_from(["mcp-bookmark", "list", "--json"]);
+        assert!(cli.json);
+        assert!(matches!(cli.command, Some(Command::List)));
+
+        let cli = Cli::parse_from(["mcp-bookmark", "config", "show"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Config(ConfigCommand::Show))
+        ));
+    }
+}
@@ -8,11 +8,48 @@ use rmcp::{
 };
 use serde::Deserialize;
 use serde_json::json;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Instant;
 
-use crate::bookmark::BookmarkReader;
+use crate::audit::{Mutation, MutationSource};
+use crate::bookmark::{BookmarkNode, BookmarkReader};
 use crate::config::Config;
-use crate::search::{SearchParams, search_manager_trait::SearchManagerTrait};
+use crate::content::{ContentFetcher, LinkState};
+use crate::health::HealthReport;
+use crate::ingest::{self, IngestedPage};
+use crate::link_status::LinkStatusStore;
+use crate::metrics::metrics;
+use crate::query_log::{QueryFilters, QueryLog, QueryLogEntry};
+use crate::search::{
+    IndexRegistry, MultiIndexSearchManager, SearchManager, SearchParams,
+    clustering::cluster_bookmarks,
+    common::{PageRangeError, content_window, page_start_offset},
+    context_builder::{
+        CONTEXT_HYBRID_WEIGHT, CONTEXT_PASSAGE_CHARS, CONTEXT_SEARCH_RESULTS,
+        CONTEXT_SNIPPETS_PER_RESULT, DEFAULT_CONTEXT_MAX_CHARS, build_context,
+        passages_from_results, question_query,
+    },
+    duplicates::{DEFAULT_NEAR_DUPLICATE_THRESHOLD, find_duplicate_groups, find_near_duplicates},
+    index_aliases::IndexAliases,
+    indexer::{format_timestamp, parse_timestamp},
+    query_parser::QueryParser,
+    search_manager_trait::SearchManagerTrait,
+    timings::{self, Stage},
+};
+
+/// Number of bookmark resources returned per list_resources page
+const RESOURCE_PAGE_SIZE: usize = 100;
+
+/// Characters returned on each side of the position by get_context_around
+const DEFAULT_CONTEXT_CHARS: usize = 1000;
+
+/// Number of link checks run concurrently by check_links
+const LINK_CHECK_CONCURRENCY: usize = 8;
+
+/// How long add_bookmark waits for a CLI rebuild or the extension writing the same index
+const ADD_BOOKMARK_LOCK_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
 
 // Tool request/response types
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -25,8 +62,78 @@ pub struct FullTextSearchRequest {
     pub folder: Option<String>,
     #[schemars(description = "Filter results to specific domain (e.g., 'github.com') (optional)")]
     pub domain: Option<String>,
+    #[schemars(
+        description = "Filter results to bookmarks from a specific browser profile (e.g., 'Work') (optional)"
+    )]
+    pub profile: Option<String>,
+    #[schemars(
+        description = "Filter results to bookmarks with this tag or extracted keyword, see get_topics (optional)"
+    )]
+    pub tag: Option<String>,
     #[schemars(description = "Maximum number of search results to return (default: 20)")]
     pub limit: Option<usize>,
+    #[schemars(description = "Exclude bookmarks that check_links found dead (404/410) (optional)")]
+    pub exclude_dead_links: Option<bool>,
+    #[schemars(
+        description = "Include each result's full indexed content in full_content (default: false; prefer get_bookmark_content for single pages)"
+    )]
+    pub include_content: Option<bool>,
+    #[schemars(
+        description = "Snippets per result, best first; extra ones are returned in more_snippets (default: 1)"
+    )]
+    pub snippet_count: Option<usize>,
+    #[schemars(description = "Maximum length of each snippet in bytes (default: 600)")]
+    pub snippet_length: Option<usize>,
+    #[schemars(
+        description = "Context kept around each match in bytes (default: a third of snippet_length)"
+    )]
+    pub context_chars: Option<usize>,
+    #[schemars(
+        description = "Blend in semantic similarity from local embeddings: 0.0 keeps keyword ranking, 1.0 ranks by meaning only (e.g. 0.5; needs [embeddings] enabled)"
+    )]
+    pub hybrid_weight: Option<f32>,
+    #[schemars(
+        description = "Merge hits on different parts of one split PDF into a single result listing each part's snippet and pages in part_hits (default: false)"
+    )]
+    pub merge_parts: Option<bool>,
+    #[schemars(
+        description = "Add a timings breakdown (parse, search, snippets, semantic, serialize, per-index latency) in milliseconds to diagnose slow searches (default: false)"
+    )]
+    pub timings: Option<bool>,
+    #[schemars(
+        description = "Fail on reserved characters (: ~ ( ) ...), stray quotes or backslashes instead of searching them as text and listing what was changed in query_warnings (default: false)"
+    )]
+    pub strict: Option<bool>,
+    #[schemars(
+        description = "Name of the index (or alias of several) to search instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SemanticSearchRequest {
+    #[schemars(description = "Natural-language description of what to find")]
+    pub query: String,
+    #[schemars(description = "Maximum number of bookmarks to return (default: 10)")]
+    pub limit: Option<usize>,
+    #[schemars(
+        description = "Name of the index (or alias of several) to search instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BuildContextRequest {
+    #[schemars(description = "Question to gather supporting passages for")]
+    pub question: String,
+    #[schemars(
+        description = "Maximum size of the assembled context in characters (default: 6000)"
+    )]
+    pub max_chars: Option<usize>,
+    #[schemars(
+        description = "Name of the index (or alias of several) to search instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -35,42 +142,423 @@ pub struct GetBookmarkContentRequest {
         description = "Exact URL of the bookmark to retrieve full indexed page content from the local Tantivy search index"
     )]
     pub url: String,
+    #[schemars(
+        description = "Content format: text (default) or markdown, which keeps headings, lists, links and code. Markdown is only stored for pages the extension indexed with markup; others return text"
+    )]
+    pub format: Option<String>,
+    #[schemars(
+        description = "Character offset to start reading from (optional, default: 0). Pass the previous response's next_offset to continue"
+    )]
+    pub offset: Option<usize>,
+    #[schemars(
+        description = "Return at most this many characters (optional, default: the whole document). Use for huge documents; the response's next_offset continues after the slice"
+    )]
+    pub max_chars: Option<usize>,
+    #[schemars(
+        description = "Name of the index to read from instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetBookmarkContentRangeRequest {
-    #[schemars(description = "Exact URL of the PDF bookmark")]
+    #[schemars(description = "Exact URL of the PDF or video bookmark")]
+    pub url: String,
+    #[schemars(
+        description = "Start page number of a PDF (1-indexed, inclusive). For single page, set start_page = end_page"
+    )]
+    pub start_page: Option<usize>,
+    #[schemars(
+        description = "End page number of a PDF (1-indexed, inclusive). For single page, set start_page = end_page"
+    )]
+    pub end_page: Option<usize>,
+    #[schemars(
+        description = "Start of a video transcript window, as seconds (90) or m:ss / h:mm:ss (1:30). Use instead of pages for YouTube bookmarks"
+    )]
+    pub start_time: Option<String>,
+    #[schemars(
+        description = "End of the video transcript window, same format as start_time (optional, default: end of the video)"
+    )]
+    pub end_time: Option<String>,
+    #[schemars(
+        description = "Name of the index to read from instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetBookmarkHtmlRequest {
+    #[schemars(description = "Exact URL of the bookmark")]
+    pub url: String,
+    #[schemars(
+        description = "Name of the index to read from instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetContextAroundRequest {
+    #[schemars(description = "Exact URL of the bookmark")]
+    pub url: String,
+    #[schemars(
+        description = "Character offset in the content to center on, e.g. snippet_range.start of a search result"
+    )]
+    pub offset: Option<usize>,
+    #[schemars(description = "PDF page (1-indexed) whose start to read from, instead of offset")]
+    pub page: Option<usize>,
+    #[schemars(description = "Characters to return before the position (default: 1000)")]
+    pub chars_before: Option<usize>,
+    #[schemars(description = "Characters to return after the position (default: 1000)")]
+    pub chars_after: Option<usize>,
+    #[schemars(
+        description = "Name of the index to read from instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetBookmarkContentSliceRequest {
+    #[schemars(description = "Exact URL of the bookmark")]
+    pub url: String,
+    #[schemars(description = "Character offset where the slice starts (0-indexed)")]
+    pub start_char: usize,
+    #[schemars(
+        description = "Character offset where the slice ends, exclusive (clamped to the content length)"
+    )]
+    pub end_char: usize,
+    #[schemars(
+        description = "Name of the index to read from instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct IndexingStatusRequest {
+    #[schemars(
+        description = "Name of the index to check instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetMetricsRequest {
+    #[schemars(description = "Index whose size to report (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct HealthRequest {
+    #[schemars(description = "Index to check (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetSearchAnalyticsRequest {
+    #[schemars(
+        description = "Only count searches from the last N days (default: every kept search)"
+    )]
+    pub days: Option<u32>,
+    #[schemars(description = "Number of queries in each list (default: 20)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Index whose searches to report (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct IndexStatsRequest {
+    #[schemars(description = "Index to inspect (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindDuplicatesRequest {
+    #[schemars(description = "Maximum number of duplicate groups to return (default: 50)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Index to scan (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindNearDuplicatesRequest {
+    #[schemars(
+        description = "Minimum share of matching SimHash bits, 0.0-1.0 (default: 0.9); lower finds looser matches"
+    )]
+    pub threshold: Option<f32>,
+    #[schemars(description = "Maximum number of groups to return (default: 50)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Index to scan (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ClusterBookmarksRequest {
+    #[schemars(
+        description = "Only cluster bookmarks in this folder and its subfolders (optional)"
+    )]
+    pub folder: Option<String>,
+    #[schemars(
+        description = "Number of clusters (default: about the square root of half the bookmark count, at most 20)"
+    )]
+    pub k: Option<usize>,
+    #[schemars(description = "Representative bookmarks listed per cluster (default: 5)")]
+    pub representatives: Option<usize>,
+    #[schemars(description = "Index to cluster (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CheckLinksRequest {
+    #[schemars(description = "Only check bookmarks in this folder and its subfolders (optional)")]
+    pub folder: Option<String>,
+    #[schemars(description = "Maximum number of bookmarks to check (default: 50)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Index to check (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CollectionStatsRequest {
+    #[schemars(description = "Number of top domains and folders to return (default: 20)")]
+    pub top: Option<usize>,
+    #[schemars(description = "Index to summarize (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetTopicsRequest {
+    #[schemars(description = "Number of topics to return (default: 30)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Index to list topics for (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct TypeaheadRequest {
+    #[schemars(
+        description = "Text typed so far; each word must start a word of the title (e.g. 'tok run' for 'Tokio runtime')"
+    )]
+    pub query: String,
+    #[schemars(description = "Maximum number of suggestions (default: 8)")]
+    pub limit: Option<usize>,
+    #[schemars(
+        description = "Name of the index (or alias of several) to suggest from instead of the server's default (optional)"
+    )]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DebugTokenizeRequest {
+    #[schemars(description = "Text to analyze, e.g. a query that doesn't match as expected")]
+    pub text: String,
+    #[schemars(
+        description = "Index field whose analyzer to use: title, content, url, ngram, ... (default: content)"
+    )]
+    pub field: Option<String>,
+    #[schemars(description = "Index whose analyzer to use (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SwitchIndexRequest {
+    #[schemars(
+        description = "Name of the index to make active (an alias, or comma-separated for multi-index search)"
+    )]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AddBookmarkRequest {
+    #[schemars(description = "URL of the page or PDF to add")]
     pub url: String,
+    #[schemars(description = "Title to index it under (defaults to the page's title)")]
+    pub title: Option<String>,
     #[schemars(
-        description = "Start page number (1-indexed, inclusive). For single page, set start_page = end_page"
+        description = "Folder to file it in, with '/' between levels (e.g., 'Research/LLM') (optional)"
     )]
-    pub start_page: usize,
+    pub folder: Option<String>,
     #[schemars(
-        description = "End page number (1-indexed, inclusive). For single page, set start_page = end_page"
+        description = "Fetch and index the page's content (default: true); false indexes only the URL and title"
     )]
-    pub end_page: usize,
+    pub fetch_content: Option<bool>,
+    #[schemars(description = "Name of the index to add it to (defaults to the active index)")]
+    pub index: Option<String>,
+}
+
+/// Index served when a tool call doesn't name one explicitly
+#[derive(Debug)]
+struct ActiveIndex {
+    name: Option<String>,
+    manager: Arc<dyn SearchManagerTrait>,
 }
 
 #[derive(Debug, Clone)]
 pub struct BookmarkServer {
     #[allow(dead_code)]
     pub reader: Arc<BookmarkReader>,
-    pub search_manager: Arc<dyn SearchManagerTrait>,
+    active: Arc<RwLock<ActiveIndex>>,
     pub config: Config,
+    pub index_registry: Arc<IndexRegistry>,
+    /// Link check results per index directory, loaded on first use
+    link_status: Arc<Mutex<HashMap<PathBuf, LinkStatusStore>>>,
     tool_router: ToolRouter<Self>,
 }
 
 #[tool_router]
 impl BookmarkServer {
     pub fn new(reader: Arc<BookmarkReader>, search_manager: Arc<dyn SearchManagerTrait>) -> Self {
+        Self::with_config(reader, search_manager, Config::default())
+    }
+
+    /// Create a server using the given configuration
+    pub fn with_config(
+        reader: Arc<BookmarkReader>,
+        search_manager: Arc<dyn SearchManagerTrait>,
+        config: Config,
+    ) -> Self {
+        let index_registry = Arc::new(IndexRegistry::new(config.max_open_indexes));
+        let active = Arc::new(RwLock::new(ActiveIndex {
+            name: config.index_name.clone(),
+            manager: search_manager,
+        }));
         Self {
             reader,
-            search_manager,
-            config: Config::default(),
+            active,
+            config,
+            index_registry,
+            link_status: Arc::default(),
             tool_router: Self::tool_router(),
         }
     }
 
+    /// Search manager of the currently active index
+    pub fn search_manager(&self) -> Arc<dyn SearchManagerTrait> {
+        self.active.read().unwrap().manager.clone()
+    }
+
+    /// Name of the currently active index
+    pub fn active_index_name(&self) -> Option<String> {
+        self.active.read().unwrap().name.clone()
+    }
+
+    /// Resolve the search manager for a tool call
+    ///
+    /// Uses the active index unless another index is named explicitly. An alias,
+    /// pattern or comma-separated list searches its indexes together.
+    fn resolve_manager(&self, index: Option<&str>) -> Result<Arc<dyn SearchManagerTrait>, String> {
+        let name = match index.map(str::trim).filter(|name| !name.is_empty()) {
+            None => return Ok(self.search_manager()),
+            Some(name) if self.active_index_name().as_deref() == Some(name) => {
+                return Ok(self.search_manager());
+            }
+            Some(name) => name,
+        };
+        let names = Config {
+            index_name: Some(name.to_string()),
+            ..Default::default()
+        }
+        .parse_index_names();
+        let mut indexes = Vec::new();
+        for index_name in names {
+            match self.index_registry.get(&index_name) {
+                Ok(manager) => indexes.push((index_name, manager)),
+                Err(e) => return Err(format!("Failed to open index '{index_name}': {e}")),
+            }
+        }
+        if indexes.len() == 1 {
+            return Ok(indexes.remove(0).1);
+        }
+        MultiIndexSearchManager::from_managers(indexes)
+            .map(|manager| Arc::new(manager) as Arc<dyn SearchManagerTrait>)
+            .map_err(|e| format!("Failed to open index '{name}': {e}"))
+    }
+
+    /// Name of the index a tool call's searches are logged under
+    fn query_log_name(&self, index: Option<&str>) -> String {
+        index
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .or_else(|| self.active_index_name())
+            .unwrap_or_else(|| "default".to_string())
+    }
+
+    /// Append a search to the index's query log, unless `[query_log]` is disabled
+    fn log_query(&self, index: Option<&str>, entry: QueryLogEntry) {
+        let settings = &crate::config::settings().query_log;
+        if !settings.enabled {
+            return;
+        }
+        let mut log = QueryLog::load(QueryLog::path_for(&self.query_log_name(index)));
+        if let Err(e) = log.record(entry, settings) {
+            tracing::warn!("Failed to save query log: {}", e);
+        }
+    }
+
+    /// Metrics in the Prometheus text format, with the active index's size
+    pub fn prometheus_metrics(&self) -> String {
+        let index_size = self
+            .search_manager()
+            .get_stats()
+            .ok()
+            .map(|s| s.index_size_bytes);
+        metrics().prometheus_text(index_size)
+    }
+
+    /// Open an index (or comma-separated list of indexes) in read-only mode
+    fn open_index(&self, name: &str) -> anyhow::Result<Arc<dyn SearchManagerTrait>> {
+        if let Some(index_dir) = self.external_index_dir(name) {
+            return Ok(Arc::new(SearchManager::open_readonly_at(index_dir)?));
+        }
+        let config = Config {
+            index_name: Some(name.to_string()),
+            ..self.config.clone()
+        };
+
+        let names = config.parse_index_names();
+        if names.is_empty() {
+            anyhow::bail!("Index name is required");
+        }
+        if let Some(invalid) = names
+            .iter()
+            .find(|n| !IndexRegistry::is_valid_index_name(n))
+        {
+            if IndexRegistry::is_index_pattern(invalid) {
+                anyhow::bail!("No index matches '{}'", invalid);
+            }
+            anyhow::bail!("Invalid index name: '{}'", invalid);
+        }
+
+        if config.is_multi_index() {
+            Ok(Arc::new(MultiIndexSearchManager::new(&config)?))
+        } else {
+            Ok(Arc::new(SearchManager::open_readonly(&names[0])?))
+        }
+    }
+
+    /// Directory of the index served from `--index-path`, if `name` is that index
+    fn external_index_dir(&self, name: &str) -> Option<&std::path::Path> {
+        self.config
+            .index_path
+            .as_deref()
+            .filter(|_| self.config.index_name.as_deref() == Some(name))
+    }
+
+    /// URLs `check_links` found dead in the indexes at `index_dirs`
+    fn dead_links(&self, index_dirs: &[PathBuf]) -> HashSet<String> {
+        let mut stores = self
+            .link_status
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut dead = HashSet::new();
+        for index_dir in index_dirs {
+            let store = stores
+                .entry(index_dir.clone())
+                .or_insert_with_key(|index_dir| LinkStatusStore::load_for(index_dir));
+            dead.extend(store.dead_urls().map(str::to_string));
+        }
+        dead
+    }
+
     fn _create_resource(&self, uri: &str, name: &str, description: &str) -> Resource {
         let mut resource = RawResource::new(uri, name.to_string());
         resource.description = Some(description.to_string());
@@ -85,51 +573,155 @@ impl BookmarkServer {
         &self,
         Parameters(req): Parameters<FullTextSearchRequest>,
     ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let (_, query_warnings) = QueryParser::parse_with_warnings(&req.query);
+        if req.strict.unwrap_or(false) && !query_warnings.is_empty() {
+            let messages: Vec<&str> = query_warnings.iter().map(|w| w.message.as_str()).collect();
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid query: {}",
+                messages.join("; ")
+            ))]));
+        }
+
         // Build search parameters
-        let results = if req.folder.is_some() || req.domain.is_some() {
+        let started = Instant::now();
+        let filters = QueryFilters {
+            folder: req.folder.clone(),
+            domain: req.domain.clone(),
+            profile: req.profile.clone(),
+            tag: req.tag.clone(),
+        };
+        let include_content = req.include_content.unwrap_or(false);
+        let merge_parts = req.merge_parts.unwrap_or(false);
+        let limit = req.limit.unwrap_or(20);
+        // Dead links are dropped after the search, so ask for enough results to
+        // still have `limit` live ones
+        let exclude_dead_links = req.exclude_dead_links.unwrap_or(false);
+        let dead_links = if exclude_dead_links {
+            self.dead_links(&manager.index_dirs())
+        } else {
+            HashSet::new()
+        };
+        let fetch_limit = limit.saturating_add(dead_links.len());
+        let custom_snippets = req.snippet_count.is_some()
+            || req.snippet_length.is_some()
+            || req.context_chars.is_some();
+        let params = if req.folder.is_some()
+            || req.domain.is_some()
+            || req.profile.is_some()
+            || req.tag.is_some()
+            || req.hybrid_weight.is_some()
+            || include_content
+            || merge_parts
+            || custom_snippets
+        {
             // Search with filters
-            let mut params = SearchParams::new(&req.query);
+            let mut params = SearchParams::new(&req.query)
+                .with_content(include_content)
+                .with_merged_parts(merge_parts);
+            if let Some(count) = req.snippet_count {
+                params = params.with_snippet_count(count);
+            }
+            if let Some(length) = req.snippet_length {
+                params = params.with_snippet_length(length);
+            }
+            if let Some(context_chars) = req.context_chars {
+                params = params.with_context_chars(context_chars);
+            }
+            if let Some(weight) = req.hybrid_weight {
+                params = params.with_hybrid_weight(weight);
+            }
             if let Some(folder) = req.folder {
                 params = params.with_folder(folder);
             }
             if let Some(domain) = req.domain {
                 params = params.with_domain(domain);
             }
-            if let Some(limit) = req.limit {
-                params = params.with_limit(limit);
+            if let Some(profile) = req.profile {
+                params = params.with_profile(profile);
             }
-            self.search_manager.search_advanced(&params).await
+            if let Some(tag) = req.tag {
+                params = params.with_tag(tag);
+            }
+            Some(params.with_limit(fetch_limit))
         } else {
-            // Normal search
-            self.search_manager
-                .search(&req.query, req.limit.unwrap_or(20))
-                .await
+            None
         };
+        let search = async {
+            match &params {
+                Some(params) => manager.search_advanced(params).await,
+                // Normal search
+                None => manager.search(&req.query, fetch_limit).await,
+            }
+        };
+        let (results, timings) = if req.timings.unwrap_or(false) {
+            let (results, timings) = timings::collect(search).await;
+            (results, Some(timings))
+        } else {
+            (search.await, None)
+        };
+        let results = results.map(|mut results| {
+            if exclude_dead_links {
+                results.retain(|r| !dead_links.contains(&r.url));
+                results.truncate(limit);
+            }
+            results
+        });
+        metrics().record_query(
+            "search_bookmarks_fulltext",
+            started.elapsed(),
+            results.as_ref().ok().map(Vec::len),
+        );
+        if let Ok(results) = &results {
+            self.log_query(
+                req.index.as_deref(),
+                QueryLogEntry::new(
+                    "search_bookmarks_fulltext",
+                    &req.query,
+                    filters,
+                    results.len(),
+                    started.elapsed(),
+                ),
+            );
+        }
 
         match results {
             Ok(mut results) => {
                 // Include indexing status
-                let status = self.search_manager.get_indexing_status();
-                let is_complete = self.search_manager.is_indexing_complete();
+                let status = manager.get_indexing_status();
+                let is_complete = manager.is_indexing_complete();
 
                 // Limit response size for MCP to avoid token limits
-                let max_snippet_length = self.config.max_snippet_length;
+                let max_snippet_length = req
+                    .snippet_length
+                    .or_else(|| manager.default_snippet_length())
+                    .unwrap_or(self.config.max_snippet_length);
                 for result in &mut results {
                     // Limit snippet text (UTF-8 safe)
-                    if result.snippet.len() > max_snippet_length {
-                        let mut end = max_snippet_length;
-                        while end > 0 && !result.snippet.is_char_boundary(end) {
-                            end -= 1;
-                        }
-                        result.snippet.truncate(end);
-                        if !result.snippet.ends_with("...") {
-                            result.snippet.push_str("...");
+                    for snippet in
+                        std::iter::once(&mut result.snippet).chain(result.more_snippets.iter_mut())
+                    {
+                        if snippet.len() > max_snippet_length {
+                            let mut end = max_snippet_length;
+                            while end > 0 && !snippet.is_char_boundary(end) {
+                                end -= 1;
+                            }
+                            snippet.truncate(end);
+                            if !snippet.ends_with("...") {
+                                snippet.push_str("...");
+                            }
                         }
                     }
                 }
 
-                let response = json!({
-                    "results": results,
+                let serialize_started = Instant::now();
+                let results_json = serde_json::to_value(&results).unwrap_or_default();
+                let mut response = json!({
+                    "results": results_json,
                     "total_results": results.len(),
                     "indexing_status": status,
                     "indexing_complete": is_complete,
@@ -139,6 +731,14 @@ impl BookmarkServer {
                         ""
                     }
                 });
+                if !query_warnings.is_empty() {
+                    response["query_warnings"] = json!(query_warnings);
+                }
+                if let Some(mut timings) = timings {
+                    timings.add(Stage::Serialize, serialize_started.elapsed());
+                    timings.total_ms = timings::millis(started.elapsed());
+                    response["timings"] = json!(timings);
+                }
 
                 let content = serde_json::to_string_pretty(&response)
                     .unwrap_or_else(|e| format!("Error serializing results: {e}"));
@@ -151,49 +751,229 @@ impl BookmarkServer {
     }
 
     #[tool(
-        description = "Get the current status of the bookmark content indexing process and check if indexing is complete"
+        description = "Find bookmarks by meaning rather than exact words, using local embeddings of page contents. Needs [embeddings] enabled when the extension indexes pages; each result's offset can be passed to get_context_around"
     )]
-    fn get_indexing_status(&self) -> Result<CallToolResult, McpError> {
-        let status = self.search_manager.get_indexing_status();
-        let is_complete = self.search_manager.is_indexing_complete();
-
-        let response = json!({
-            "status": status,
-            "is_complete": is_complete,
-        });
+    async fn semantic_search(
+        &self,
+        Parameters(req): Parameters<SemanticSearchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
 
-        let content =
-            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
-        Ok(CallToolResult::success(vec![Content::text(content)]))
+        let started = Instant::now();
+        let results = manager
+            .semantic_search(&req.query, req.limit.unwrap_or(10))
+            .await;
+        metrics().record_query(
+            "semantic_search",
+            started.elapsed(),
+            results.as_ref().ok().map(Vec::len),
+        );
+        if let Ok(results) = &results {
+            self.log_query(
+                req.index.as_deref(),
+                QueryLogEntry::new(
+                    "semantic_search",
+                    &req.query,
+                    QueryFilters::default(),
+                    results.len(),
+                    started.elapsed(),
+                ),
+            );
+        }
+        match results {
+            Ok(results) => {
+                let response = json!({
+                    "results": results,
+                    "total_results": results.len(),
+                    "note": if results.is_empty() {
+                        "No embeddings found. Enable [embeddings] in the config and re-index pages with the extension."
+                    } else {
+                        ""
+                    }
+                });
+                let content = serde_json::to_string_pretty(&response)
+                    .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+                Ok(CallToolResult::success(vec![Content::text(content)]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error running semantic search: {e}"
+            ))])),
+        }
     }
 
     #[tool(
-        description = "Retrieve complete indexed webpage content for a specific bookmark URL from the local Tantivy search index. For large PDF files, consider using get_bookmark_content_range instead to retrieve specific pages."
+        description = "Gather passages that help answer a question into one context block: searches the bookmarks (keyword, plus semantic when embeddings are enabled), picks high-scoring passages across several documents, drops overlapping text and numbers each source"
     )]
-    async fn get_bookmark_content(
+    async fn build_context(
         &self,
-        Parameters(req): Parameters<GetBookmarkContentRequest>,
+        Parameters(req): Parameters<BuildContextRequest>,
     ) -> Result<CallToolResult, McpError> {
-        // Get content from URL (from index or new fetch)
-        match self.search_manager.get_content_by_url(&req.url).await {
-            Ok(Some(content)) => {
-                // Check content size and warn if too large
-                const WARNING_THRESHOLD: usize = 100_000; // 100k characters
-                let size_warning = if content.len() > WARNING_THRESHOLD {
-                    Some(format!(
-                        "⚠️ Large content detected ({} chars). For better performance with large PDFs, consider using get_bookmark_content_range to retrieve specific pages instead of the entire document.",
-                        content.len()
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let query = question_query(&req.question);
+        if query.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Question must contain at least one word",
+            )]));
+        }
+        let started = Instant::now();
+        let params = SearchParams::new(&query)
+            .with_limit(CONTEXT_SEARCH_RESULTS)
+            .with_snippet_count(CONTEXT_SNIPPETS_PER_RESULT)
+            .with_snippet_length(CONTEXT_PASSAGE_CHARS);
+
+        // Blend in semantic matches when pages were embedded; fall back to keywords alone
+        let mut results = None;
+        if crate::config::settings().embeddings.enabled {
+            match manager
+                .search_advanced(&params.clone().with_hybrid_weight(CONTEXT_HYBRID_WEIGHT))
+                .await
+            {
+                Ok(hybrid) => results = Some(hybrid),
+                Err(e) => tracing::warn!("Semantic search unavailable for build_context: {}", e),
+            }
+        }
+        let results = match results {
+            Some(results) => results,
+            None => match manager.search_advanced(&params).await {
+                Ok(results) => results,
+                Err(e) => {
+                    metrics().record_query("build_context", started.elapsed(), None);
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Error searching bookmarks: {e}"
+                    ))]));
+                }
+            },
+        };
+
+        let max_chars = req.max_chars.unwrap_or(DEFAULT_CONTEXT_MAX_CHARS);
+        let built = build_context(passages_from_results(&results), max_chars);
+        metrics().record_query(
+            "build_context",
+            started.elapsed(),
+            Some(built.sources.len()),
+        );
+        let response = json!({
+            "context": built.context,
+            "sources": built.sources,
+            "total_chars": built.total_chars,
+            "truncated": built.truncated,
+            "note": if built.sources.is_empty() {
+                "No matching passages found."
+            } else {
+                ""
+            }
+        });
+
+        let content = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Get the current status of the bookmark content indexing process and check if indexing is complete"
+    )]
+    fn get_indexing_status(
+        &self,
+        Parameters(req): Parameters<IndexingStatusRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let status = manager.get_indexing_status();
+        let is_complete = manager.is_indexing_complete();
+
+        let response = json!({
+            "status": status,
+            "is_complete": is_complete,
+            // Counts of the extension's current or last run, written by the native host
+            "progress": manager.indexing_progress(),
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Retrieve complete indexed webpage content for a specific bookmark URL from the local Tantivy search index. For huge documents, read it in slices with offset/max_chars and the returned next_offset; for large PDF files, get_bookmark_content_range retrieves specific pages."
+    )]
+    async fn get_bookmark_content(
+        &self,
+        Parameters(req): Parameters<GetBookmarkContentRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        if req.max_chars == Some(0) {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "max_chars must be at least 1",
+            )]));
+        }
+
+        let want_markdown = match req.format.as_deref() {
+            None | Some("text") => false,
+            Some("markdown") => true,
+            Some(other) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Unknown format: {other} (expected text or markdown)"
+                ))]));
+            }
+        };
+        let markdown = if want_markdown {
+            manager
+                .get_markdown_by_url(&req.url)
+                .await
+                .unwrap_or_default()
+        } else {
+            None
+        };
+        let format = if markdown.is_some() {
+            "markdown"
+        } else {
+            "text"
+        };
+
+        // Get content from URL (from index or new fetch)
+        let content = match markdown {
+            Some(markdown) => Ok(Some(markdown)),
+            None => manager.get_content_by_url(&req.url).await,
+        };
+        match content {
+            Ok(Some(content)) => {
+                // Slice of the content when reading it piece by piece
+                let sliced = (req.offset.is_some() || req.max_chars.is_some()).then(|| {
+                    content_window(
+                        &content,
+                        req.offset.unwrap_or(0),
+                        0,
+                        req.max_chars.unwrap_or(usize::MAX),
+                    )
+                });
+
+                // Check content size and warn if too large
+                const WARNING_THRESHOLD: usize = 100_000; // 100k characters
+                let total_chars = content.chars().count();
+                let size_warning = if sliced.is_none() && total_chars > WARNING_THRESHOLD {
+                    Some(format!(
+                        "⚠️ Large content detected ({total_chars} chars). Read it in slices with offset/max_chars, or use get_bookmark_content_range to retrieve specific pages of large PDFs instead of the entire document."
                     ))
                 } else {
                     None
                 };
 
                 // Also get bookmark information
-                let search_results = self
-                    .search_manager
-                    .search(&req.url, 1)
-                    .await
-                    .unwrap_or_default();
+                let search_results = manager.search(&req.url, 1).await.unwrap_or_default();
 
                 let (title, folder_path) = if let Some(result) = search_results.first() {
                     if result.url == req.url {
@@ -205,17 +985,38 @@ impl BookmarkServer {
                     ("Unknown".to_string(), None)
                 };
 
-                let mut response = json!({
-                    "url": req.url,
-                    "title": title,
-                    "folder_path": folder_path,
-                    "content": content,
-                    "content_length": content.len(),
-                });
+                let mut response = match &sliced {
+                    Some(window) => json!({
+                        "url": req.url,
+                        "title": title,
+                        "folder_path": folder_path,
+                        "format": format,
+                        "content": window.text,
+                        "content_length": window.char_len(),
+                        "offset": window.start,
+                        "next_offset": (window.end < window.total_chars).then_some(window.end),
+                        "total_chars": window.total_chars,
+                        "page_number": window.page_number,
+                    }),
+                    None => json!({
+                        "url": req.url,
+                        "title": title,
+                        "folder_path": folder_path,
+                        "format": format,
+                        "content": content,
+                        "content_length": total_chars,
+                        "total_chars": total_chars,
+                    }),
+                };
 
                 if let Some(warning) = size_warning {
                     response["warning"] = json!(warning);
                 }
+                if want_markdown && format == "text" {
+                    response["note"] = json!(
+                        "No Markdown is stored for this page (PDF or indexed without markup); returning plain text"
+                    );
+                }
 
                 let content_json = serde_json::to_string_pretty(&response)
                     .unwrap_or_else(|e| format!("Error serializing response: {e}"));
@@ -236,31 +1037,82 @@ impl BookmarkServer {
     }
 
     #[tool(
-        description = "Retrieve specific page(s) from a PDF bookmark. For single page, set start_page = end_page. For range, set start_page < end_page. Page numbers are 1-indexed."
+        description = "Retrieve specific page(s) from a PDF bookmark, or a time window of a YouTube bookmark's transcript. For PDFs set start_page and end_page (1-indexed; for a single page, start_page = end_page). For videos set start_time and optionally end_time; each caption line starts with its [m:ss] timestamp."
     )]
     async fn get_bookmark_content_range(
         &self,
         Parameters(req): Parameters<GetBookmarkContentRangeRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match self
-            .search_manager
-            .get_page_range_content(&req.url, req.start_page, req.end_page)
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        if req.start_time.is_some() || req.end_time.is_some() {
+            let parse = |value: &Option<String>| match value {
+                Some(value) => parse_timestamp(value).map(Some).ok_or_else(|| {
+                    format!("Invalid time {value:?}: use seconds (90) or m:ss / h:mm:ss (1:30)")
+                }),
+                None => Ok(None),
+            };
+            let (start_ms, end_ms) = match (parse(&req.start_time), parse(&req.end_time)) {
+                (Ok(start_ms), Ok(end_ms)) => (start_ms.unwrap_or(0), end_ms),
+                (Err(e), _) | (_, Err(e)) => {
+                    return Ok(CallToolResult::error(vec![Content::text(e)]));
+                }
+            };
+            return match manager
+                .get_transcript_range_content(&req.url, start_ms, end_ms)
+                .await
+            {
+                Ok(Some(content)) => {
+                    let response = json!({
+                        "url": req.url,
+                        "start_time": format_timestamp(start_ms),
+                        "end_time": end_ms.map(format_timestamp),
+                        "content": content,
+                        "content_length": content.chars().count(),
+                    });
+                    let content_json = serde_json::to_string_pretty(&response)
+                        .unwrap_or_else(|e| format!("Error serializing response: {e}"));
+                    Ok(CallToolResult::success(vec![Content::text(content_json)]))
+                }
+                Ok(None) => Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Content not found for URL: {}. The bookmark may not exist in the index.",
+                    req.url
+                ))])),
+                Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error retrieving transcript for URL {}: {}",
+                    req.url, e
+                ))])),
+            };
+        }
+
+        let (Some(start_page), Some(end_page)) = (req.start_page, req.end_page) else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Set start_page and end_page for a PDF, or start_time for a video transcript"
+                    .to_string(),
+            )]));
+        };
+
+        match manager
+            .get_page_range_content(&req.url, start_page, end_page)
             .await
         {
             Ok(Some(content)) => {
-                let page_desc = if req.start_page == req.end_page {
-                    format!("page {}", req.start_page)
+                let page_desc = if start_page == end_page {
+                    format!("page {start_page}")
                 } else {
-                    format!("pages {}-{}", req.start_page, req.end_page)
+                    format!("pages {start_page}-{end_page}")
                 };
 
                 let response = json!({
                     "url": req.url,
-                    "start_page": req.start_page,
-                    "end_page": req.end_page,
+                    "start_page": start_page,
+                    "end_page": end_page,
                     "page_range": page_desc,
                     "content": content,
-                    "content_length": content.len(),
+                    "content_length": content.chars().count(),
                 });
 
                 let content_json = serde_json::to_string_pretty(&response)
@@ -271,12 +1123,938 @@ impl BookmarkServer {
                 "Content not found for URL: {}. The bookmark may not exist in the index.",
                 req.url
             ))])),
+            Err(e) => match e.downcast_ref::<PageRangeError>() {
+                // Tell the client what it can ask for instead
+                Some(range) => {
+                    let response = json!({
+                        "error": range.to_string(),
+                        "url": req.url,
+                        "start_page": start_page,
+                        "end_page": end_page,
+                        "page_count": range.page_count,
+                        "content_type": range.content_type,
+                        "closest_range": range.closest.map(|(start, end)| json!({
+                            "start_page": start,
+                            "end_page": end,
+                        })),
+                    });
+                    let content_json = serde_json::to_string_pretty(&response)
+                        .unwrap_or_else(|e| format!("Error serializing response: {e}"));
+                    Ok(CallToolResult::error(vec![Content::text(content_json)]))
+                }
+                None => Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error retrieving pages {}-{} for URL {}: {}",
+                    start_page, end_page, req.url, e
+                ))])),
+            },
+        }
+    }
+
+    #[tool(
+        description = "Retrieve the original HTML stored for a bookmark, for re-extracting content with other tools. Only available for pages indexed by the extension while [index] store_html was enabled."
+    )]
+    async fn get_bookmark_html(
+        &self,
+        Parameters(req): Parameters<GetBookmarkHtmlRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        match manager.get_html_by_url(&req.url).await {
+            Ok(Some(html)) => {
+                let response = json!({
+                    "url": req.url,
+                    "html": html,
+                    "html_length": html.len(),
+                });
+                let content_json = serde_json::to_string_pretty(&response)
+                    .unwrap_or_else(|e| format!("Error serializing response: {e}"));
+                Ok(CallToolResult::success(vec![Content::text(content_json)]))
+            }
+            Ok(None) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "No HTML stored for URL: {}. Enable store_html in the [index] config section and re-index the page with the extension.",
+                req.url
+            ))])),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Error retrieving pages {}-{} for URL {}: {}",
-                req.start_page, req.end_page, req.url, e
+                "Error reading HTML for URL {}: {}",
+                req.url, e
             ))])),
         }
     }
+
+    #[tool(
+        description = "Read the stored content of a bookmark around a character offset (e.g. a search result's snippet_range) or the start of a PDF page, to expand a snippet without fetching the whole document"
+    )]
+    async fn get_context_around(
+        &self,
+        Parameters(req): Parameters<GetContextAroundRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let content = match manager.get_content_by_url(&req.url).await {
+            Ok(Some(content)) => content,
+            Ok(None) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Content not found for URL: {}. The bookmark may not exist in the index.",
+                    req.url
+                ))]));
+            }
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error fetching content for URL {}: {}",
+                    req.url, e
+                ))]));
+            }
+        };
+
+        let center = match (req.offset, req.page) {
+            (Some(offset), None) => offset,
+            (None, Some(page)) => match page_start_offset(&content, page) {
+                Some(offset) => offset,
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Page {page} not found in {}",
+                        req.url
+                    ))]));
+                }
+            },
+            _ => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Pass exactly one of offset or page",
+                )]));
+            }
+        };
+
+        let window = content_window(
+            &content,
+            center,
+            req.chars_before.unwrap_or(DEFAULT_CONTEXT_CHARS),
+            req.chars_after.unwrap_or(DEFAULT_CONTEXT_CHARS),
+        );
+        let response = json!({
+            "url": req.url,
+            "start": window.start,
+            "end": window.end,
+            "total_chars": window.total_chars,
+            "page_number": window.page_number,
+            "content": window.text,
+        });
+
+        let content_json = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing response: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content_json)]))
+    }
+
+    #[tool(
+        description = "Read characters start_char..end_char of a bookmark's stored content. Works for any document, unlike get_bookmark_content_range which needs PDF pages"
+    )]
+    async fn get_bookmark_content_slice(
+        &self,
+        Parameters(req): Parameters<GetBookmarkContentSliceRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if req.start_char > req.end_char {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "start_char ({}) must not be greater than end_char ({})",
+                req.start_char, req.end_char
+            ))]));
+        }
+
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let content = match manager.get_content_by_url(&req.url).await {
+            Ok(Some(content)) => content,
+            Ok(None) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Content not found for URL: {}. The bookmark may not exist in the index.",
+                    req.url
+                ))]));
+            }
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error fetching content for URL {}: {}",
+                    req.url, e
+                ))]));
+            }
+        };
+
+        // Offsets count characters, so the slice always falls on UTF-8 boundaries
+        let window = content_window(&content, req.start_char, 0, req.end_char - req.start_char);
+        let response = json!({
+            "url": req.url,
+            "start": window.start,
+            "end": window.end,
+            "total_chars": window.total_chars,
+            "page_number": window.page_number,
+            "content": window.text,
+        });
+
+        let content_json = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing response: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content_json)]))
+    }
+
+    #[tool(
+        description = "Find duplicate bookmarks in the index: identical canonical URLs, near-identical titles, or identical content"
+    )]
+    async fn find_duplicate_bookmarks(
+        &self,
+        Parameters(req): Parameters<FindDuplicatesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let bookmarks = match manager.list_bookmarks().await {
+            Ok(bookmarks) => bookmarks,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error listing bookmarks: {e}"
+                ))]));
+            }
+        };
+
+        let mut groups = find_duplicate_groups(&bookmarks);
+        let total_groups = groups.len();
+        groups.truncate(req.limit.unwrap_or(50));
+
+        let response = json!({
+            "groups": groups,
+            "total_groups": total_groups,
+            "scanned_bookmarks": bookmarks.len(),
+        });
+
+        let content = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Find bookmarks with nearly identical content (mirrors, reposted articles, lightly edited copies) by comparing content SimHashes, for cleanup"
+    )]
+    async fn find_near_duplicates(
+        &self,
+        Parameters(req): Parameters<FindNearDuplicatesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let bookmarks = match manager.list_bookmarks().await {
+            Ok(bookmarks) => bookmarks,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error listing bookmarks: {e}"
+                ))]));
+            }
+        };
+
+        let threshold = req
+            .threshold
+            .unwrap_or(DEFAULT_NEAR_DUPLICATE_THRESHOLD)
+            .clamp(0.0, 1.0);
+        let mut groups = find_near_duplicates(&bookmarks, threshold);
+        let total_groups = groups.len();
+        groups.truncate(req.limit.unwrap_or(50));
+        let fingerprinted = bookmarks.iter().filter(|b| b.simhash != 0).count();
+
+        let response = json!({
+            "groups": groups,
+            "total_groups": total_groups,
+            "threshold": threshold,
+            "scanned_bookmarks": fingerprinted,
+            "note": if fingerprinted < bookmarks.len() {
+                "Bookmarks indexed before SimHashes were stored, or without content, are skipped; re-index to include them."
+            } else {
+                ""
+            }
+        });
+
+        let content = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Cluster bookmarks by the terms of their content (TF-IDF, k-means) and return labeled clusters with representative bookmarks, to discover structure in large unorganized folders"
+    )]
+    async fn cluster_bookmarks(
+        &self,
+        Parameters(req): Parameters<ClusterBookmarksRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let bookmarks = match manager.list_bookmarks().await {
+            Ok(bookmarks) => bookmarks,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error listing bookmarks: {e}"
+                ))]));
+            }
+        };
+
+        let mut documents = Vec::new();
+        for bookmark in bookmarks.into_iter().filter(|b| match &req.folder {
+            Some(folder) => {
+                b.folder_path == *folder || b.folder_path.starts_with(&format!("{folder}/"))
+            }
+            None => true,
+        }) {
+            let content = match manager.get_content_by_url(&bookmark.url).await {
+                Ok(content) => content.unwrap_or_default(),
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Error reading content for {}: {e}",
+                        bookmark.url
+                    ))]));
+                }
+            };
+            documents.push((bookmark, content));
+        }
+
+        let clustering = cluster_bookmarks(&documents, req.k, req.representatives.unwrap_or(5));
+        let response = json!({
+            "clusters": clustering.clusters,
+            "total_clusters": clustering.clusters.len(),
+            "scanned_bookmarks": documents.len(),
+            "unclustered": clustering.unclustered,
+            "note": if documents.len() < 2 {
+                "Clustering needs at least two bookmarks with content."
+            } else {
+                ""
+            }
+        });
+
+        let content = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Check whether bookmarked URLs are still reachable and report dead links (404/410), timeouts and errors. Results are remembered so search_bookmarks_fulltext can exclude dead links."
+    )]
+    async fn check_links(
+        &self,
+        Parameters(req): Parameters<CheckLinksRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let timeout = std::time::Duration::from_secs(self.config.fetch_timeout_secs);
+        let fetcher = match ContentFetcher::with_timeout(timeout) {
+            Ok(fetcher) => fetcher,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e.to_string())])),
+        };
+
+        let bookmarks = match manager.list_bookmarks().await {
+            Ok(bookmarks) => bookmarks,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error listing bookmarks: {e}"
+                ))]));
+            }
+        };
+
+        let urls: Vec<String> = bookmarks
+            .into_iter()
+            .filter(|b| match &req.folder {
+                Some(folder) => {
+                    b.folder_path == *folder || b.folder_path.starts_with(&format!("{folder}/"))
+                }
+                None => true,
+            })
+            .map(|b| b.url)
+            .take(req.limit.unwrap_or(50))
+            .collect();
+
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(LINK_CHECK_CONCURRENCY));
+        let mut tasks = tokio::task::JoinSet::new();
+        for url in urls {
+            let fetcher = fetcher.clone();
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                fetcher.check_link(&url).await
+            });
+        }
+
+        let mut checks = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            if let Ok(check) = result {
+                checks.push(check);
+            }
+        }
+        checks.sort_by(|a, b| a.url.cmp(&b.url));
+
+        {
+            let mut stores = self
+                .link_status
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            for index_dir in manager.index_dirs() {
+                let store = stores
+                    .entry(index_dir)
+                    .or_insert_with_key(|index_dir| LinkStatusStore::load_for(index_dir));
+                for check in &checks {
+                    store.record(check.clone());
+                }
+                if let Err(e) = store.save() {
+                    tracing::warn!("Failed to save link status: {}", e);
+                }
+            }
+        }
+
+        let by_state = |state: LinkState| -> Vec<_> {
+            checks
+                .iter()
+                .filter(|c| c.state == state)
+                .map(|c| json!({ "url": c.url, "http_status": c.http_status, "error": c.error }))
+                .collect()
+        };
+
+        let response = json!({
+            "checked": checks.len(),
+            "ok_count": checks.iter().filter(|c| c.state == LinkState::Ok).count(),
+            "dead": by_state(LinkState::Dead),
+            "timeouts": by_state(LinkState::Timeout),
+            "errors": by_state(LinkState::Error),
+        });
+
+        let content = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Get collection statistics: bookmarks per domain, folder and content type, content size distribution, and bookmarks added per month"
+    )]
+    async fn get_collection_stats(
+        &self,
+        Parameters(req): Parameters<CollectionStatsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let stats = match manager.get_collection_stats() {
+            Ok(stats) => stats,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error computing collection stats: {e}"
+                ))]));
+            }
+        };
+
+        // Largest groups first, truncated to `top`
+        let top = req.top.unwrap_or(20);
+        let ranked = |counts: &std::collections::BTreeMap<String, usize>| {
+            let mut ranked: Vec<_> = counts.iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            ranked
+                .into_iter()
+                .take(top)
+                .map(|(name, count)| json!({ "name": name, "count": count }))
+                .collect::<Vec<_>>()
+        };
+
+        let response = json!({
+            "total_bookmarks": stats.total_bookmarks,
+            "total_documents": stats.total_documents,
+            "distinct_domains": stats.by_domain.len(),
+            "distinct_folders": stats.by_folder.len(),
+            "top_domains": ranked(&stats.by_domain),
+            "top_folders": ranked(&stats.by_folder),
+            "by_content_type": stats.by_content_type,
+            "by_size": stats.by_size,
+            "added_per_month": stats.added_per_month,
+        });
+
+        let content = serde_json::to_string_pretty(&response)
+            .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "List the collection's dominant topics (bookmark tags and keywords extracted at index time) with how many bookmarks carry each"
+    )]
+    fn get_topics(
+        &self,
+        Parameters(req): Parameters<GetTopicsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        match manager.get_topics(req.limit.unwrap_or(30)) {
+            Ok(topics) => {
+                let response = json!({
+                    "topics": topics,
+                    "total_topics": topics.len(),
+                    "note": if topics.is_empty() {
+                        "No tags or keywords found. Keywords are extracted when pages are indexed; re-index to add them."
+                    } else {
+                        ""
+                    }
+                });
+                let content = serde_json::to_string_pretty(&response)
+                    .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+                Ok(CallToolResult::success(vec![Content::text(content)]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error listing topics: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Suggest bookmark titles completing what the user has typed so far, for search-as-you-type; matches word prefixes in titles only and returns no snippets, so it answers quickly"
+    )]
+    fn typeahead(
+        &self,
+        Parameters(req): Parameters<TypeaheadRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        // Not written to the query log: every keystroke would land there
+        let started = Instant::now();
+        let suggestions = manager.typeahead(&req.query, req.limit.unwrap_or(8));
+        metrics().record_query(
+            "typeahead",
+            started.elapsed(),
+            suggestions.as_ref().ok().map(Vec::len),
+        );
+        match suggestions {
+            Ok(suggestions) => {
+                let response = json!({
+                    "suggestions": suggestions,
+                    "total_suggestions": suggestions.len(),
+                });
+                let content = serde_json::to_string_pretty(&response)
+                    .unwrap_or_else(|e| format!("Error serializing results: {e}"));
+                Ok(CallToolResult::success(vec![Content::text(content)]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error suggesting titles: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Show how the index's analyzer splits text into terms for a field, to understand why a query does or doesn't match"
+    )]
+    fn debug_tokenize(
+        &self,
+        Parameters(req): Parameters<DebugTokenizeRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let field = req.field.as_deref().unwrap_or("content");
+        let tokens = match manager.tokenize(&req.text, field) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error tokenizing text: {e:#}"
+                ))]));
+            }
+        };
+
+        let response = json!({
+            "field": field,
+            "text": req.text,
+            "token_count": tokens.len(),
+            "tokens": tokens,
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "List all indexes available under the data directory with bookmark counts, sizes and last update times"
+    )]
+    async fn list_indexes(&self) -> Result<CallToolResult, McpError> {
+        let active = self.active_index_name();
+        let active_names = Config {
+            index_name: active.clone(),
+            ..Default::default()
+        }
+        .parse_index_names();
+
+        let indexes: Vec<_> = self
+            .index_registry
+            .available_indexes()
+            .into_iter()
+            .map(|info| {
+                let is_active = active_names.contains(&info.name);
+                let mut entry = json!(info);
+                entry["active"] = json!(is_active);
+                entry
+            })
+            .collect();
+
+        let response = json!({
+            "indexes": indexes,
+            "total_indexes": indexes.len(),
+            "active_index": active,
+            "aliases": IndexAliases::load_configured().unwrap_or_default(),
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Get detailed statistics (documents, bookmarks, size) for the active index or a named one"
+    )]
+    async fn get_index_stats(
+        &self,
+        Parameters(req): Parameters<IndexStatsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let manager = match self.resolve_manager(req.index.as_deref()) {
+            Ok(manager) => manager,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+
+        let stats = match manager.get_stats() {
+            Ok(stats) => stats,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error getting index stats: {e}"
+                ))]));
+            }
+        };
+
+        let index = req
+            .index
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| self.active_index_name());
+
+        let response = json!({
+            "index": index,
+            "total_documents": stats.total_documents,
+            "bookmark_count": stats.bookmark_count,
+            "index_size_bytes": stats.index_size_bytes,
+            "indexing_status": manager.get_indexing_status(),
+            "indexing_complete": manager.is_indexing_complete(),
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Get server metrics for today (UTC) and since startup: query latency, result counts, open-index cache hits, indexing throughput, plus the index's size"
+    )]
+    fn get_metrics(
+        &self,
+        Parameters(req): Parameters<GetMetricsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let index_stats = self
+            .resolve_manager(req.index.as_deref())
+            .ok()
+            .and_then(|manager| manager.get_stats().ok());
+
+        let response = json!({
+            "metrics": metrics().report(),
+            "index_documents": index_stats.as_ref().map(|s| s.total_documents),
+            "index_size_bytes": index_stats.as_ref().map(|s| s.index_size_bytes),
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Check that the server works: the index opens, its tokenizer analyzes text, a trivial query runs and the data directory is writable. Returns each check's status and timing"
+    )]
+    async fn health(
+        &self,
+        Parameters(req): Parameters<HealthRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let index = req
+            .index
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| self.active_index_name());
+        let manager = self
+            .resolve_manager(req.index.as_deref())
+            .map_err(anyhow::Error::msg);
+        let report = HealthReport::run(index, manager, &crate::config::data_dir()).await;
+
+        let content =
+            serde_json::to_string_pretty(&report).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Show what gets searched in an index: most frequent queries and queries whose last search found nothing (what the index may be missing). Searches are logged locally under [query_log] retention limits"
+    )]
+    fn get_search_analytics(
+        &self,
+        Parameters(req): Parameters<GetSearchAnalyticsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let settings = &crate::config::settings().query_log;
+        let name = self.query_log_name(req.index.as_deref());
+        let mut log = QueryLog::load(QueryLog::path_for(&name));
+        let now = chrono::Utc::now();
+        // Expired queries are dropped even when nothing new is logged
+        if log.prune(settings, now) {
+            log.save()
+                .unwrap_or_else(|e| tracing::warn!("Failed to save query log: {}", e));
+        }
+
+        let since = req
+            .days
+            .map(|days| now - chrono::Duration::days(days as i64));
+        let response = json!({
+            "index": name,
+            "query_log_enabled": settings.enabled,
+            "max_entries": settings.max_entries,
+            "max_age_days": settings.max_age_days,
+            "analytics": log.analytics(since, req.limit.unwrap_or(20)),
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Switch the active index used when a tool call doesn't specify one. Accepts a comma-separated list for multi-index search."
+    )]
+    async fn switch_index(
+        &self,
+        Parameters(req): Parameters<SwitchIndexRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let name = req.name.trim().to_string();
+        let manager = match self.open_index(&name) {
+            Ok(manager) => manager,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to switch to index '{name}': {e}"
+                ))]));
+            }
+        };
+
+        let status = manager.get_indexing_status();
+        let previous = {
+            let mut active = self.active.write().unwrap();
+            let previous = active.name.replace(name.clone());
+            active.manager = manager;
+            previous
+        };
+        tracing::info!("Switched active index: {:?} -> {}", previous, name);
+
+        let response = json!({
+            "status": "switched",
+            "index": name,
+            "previous_index": previous,
+            "indexing_status": status,
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Reload the active index from disk to pick up documents indexed by the Chrome extension since the server started"
+    )]
+    async fn reload_index(&self) -> Result<CallToolResult, McpError> {
+        let Some(name) = self.active_index_name() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "No active index to reload. Use switch_index to select one.".to_string(),
+            )]));
+        };
+
+        let manager = match self.open_index(&name) {
+            Ok(manager) => manager,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to reload index '{name}': {e}"
+                ))]));
+            }
+        };
+
+        let status = manager.get_indexing_status();
+        self.active.write().unwrap().manager = manager;
+        // Indexes opened for per-call `index` arguments may be stale too
+        self.index_registry.clear();
+        tracing::info!("Reloaded index: {}", name);
+
+        let response = json!({
+            "status": "reloaded",
+            "index": name,
+            "indexing_status": status,
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(
+        description = "Fetch a web page or PDF and add it to an index as a new bookmark, so it shows up in later searches"
+    )]
+    async fn add_bookmark(
+        &self,
+        Parameters(req): Parameters<AddBookmarkRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(name) = req
+            .index
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .or_else(|| self.active_index_name())
+        else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "No index to add to. Pass index or use switch_index to select one.".to_string(),
+            )]));
+        };
+        if !IndexRegistry::is_valid_index_name(&name) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid index name: '{name}' (bookmarks are added to a single index)"
+            ))]));
+        }
+        if IndexAliases::load_configured().is_ok_and(|aliases| aliases.contains(&name)) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "'{name}' is an index alias; pass the index to add to"
+            ))]));
+        }
+        if let Some(index_dir) = self.external_index_dir(&name) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Index '{name}' is served read-only from {}; pass index to add to an index in the data directory",
+                index_dir.display()
+            ))]));
+        }
+        if !matches!(url::Url::parse(&req.url), Ok(url) if matches!(url.scheme(), "http" | "https"))
+        {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Not an http(s) URL: {}",
+                req.url
+            ))]));
+        }
+
+        let folder_path: Vec<String> = req
+            .folder
+            .as_deref()
+            .unwrap_or("")
+            .split('/')
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect();
+        let title = req
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        let page = if req.fetch_content.unwrap_or(true) {
+            let timeout = std::time::Duration::from_secs(self.config.fetch_timeout_secs);
+            let fetched = match ContentFetcher::with_timeout(timeout) {
+                Ok(fetcher) => ingest::fetch_page(&fetcher, &req.url, title, folder_path).await,
+                Err(e) => Err(e),
+            };
+            match fetched {
+                Ok(page) => page,
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to fetch {}: {e:#}",
+                        req.url
+                    ))]));
+                }
+            }
+        } else {
+            IngestedPage::without_content(&req.url, title, folder_path)
+        };
+
+        let written = {
+            let name = name.clone();
+            let page = page.clone();
+            tokio::task::spawn_blocking(move || {
+                ingest::write_pages(
+                    &name,
+                    crate::config::settings().tokenizer.dictionary,
+                    std::slice::from_ref(&page),
+                    MutationSource::Server,
+                    Mutation::AddBookmark,
+                    "the MCP server (add_bookmark)",
+                    Some(ADD_BOOKMARK_LOCK_WAIT),
+                )
+            })
+            .await
+        };
+        let documents = match written {
+            Ok(Ok(documents)) => documents,
+            Ok(Err(e)) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to index {} into '{name}': {e:#}",
+                    page.bookmark.url
+                ))]));
+            }
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Indexing task failed: {e}"
+                ))]));
+            }
+        };
+
+        // Searches through the server see the new documents right away
+        let reloaded =
+            self.index_registry
+                .reload(&name)
+                .and_then(|()| match self.active_index_name() {
+                    Some(active) if active == name => self.search_manager().reload(),
+                    _ => Ok(()),
+                });
+        if let Err(e) = reloaded {
+            tracing::warn!("Failed to reload index '{}': {}", name, e);
+        }
+        tracing::info!("Added {} to index {}", page.bookmark.url, name);
+
+        let response = json!({
+            "status": "added",
+            "index": name,
+            "url": page.bookmark.url,
+            "title": page.bookmark.name,
+            "folder_path": page.bookmark.folder_path,
+            "documents": documents,
+            "content_type": page.content_type(),
+            "content_length": page.content.chars().count(),
+            "page_count": page.page_info.as_ref().map(|page_info| page_info.page_count),
+        });
+
+        let content =
+            serde_json::to_string_pretty(&response).unwrap_or_else(|e| format!("Error: {e}"));
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
 }
 
 #[tool_handler]
@@ -298,24 +2076,48 @@ impl ServerHandler for BookmarkServer {
 
     async fn list_resources(
         &self,
-        _request: Option<PaginatedRequestParam>,
+        request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListResourcesResult, McpError> {
-        let resources = vec![
+        // Cursor is the offset into the bookmark listing
+        let offset = request
+            .and_then(|r| r.cursor)
+            .and_then(|c| c.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        let mut resources = Vec::new();
+        if offset == 0 {
             // Resource: bookmark://tree
-            self._create_resource(
+            resources.push(self._create_resource(
                 "bookmark://tree",
                 "Bookmark Tree",
-                "Full Chrome bookmark tree",
-            ),
-        ];
+                "Bookmark folder tree rebuilt from the index",
+            ));
+        }
+
+        let bookmarks = self.search_manager().list_bookmarks().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to list bookmarks: {e}"), None)
+        })?;
 
-        // Folder resources not available with INDEX_NAME approach
-        // All bookmarks are accessed through search tools
+        // Resource: bookmark://<id> for each indexed bookmark
+        for entry in bookmarks.iter().skip(offset).take(RESOURCE_PAGE_SIZE) {
+            let mut resource =
+                RawResource::new(format!("bookmark://{}", entry.id), entry.title.clone());
+            resource.description = Some(entry.url.clone());
+            resource.mime_type = Some("text/plain".to_string());
+            resources.push(resource.no_annotation());
+        }
+
+        let next_offset = offset + RESOURCE_PAGE_SIZE;
+        let next_cursor = if next_offset < bookmarks.len() {
+            Some(next_offset.to_string())
+        } else {
+            None
+        };
 
         Ok(ListResourcesResult {
             resources,
-            next_cursor: None,
+            next_cursor,
         })
     }
 
@@ -325,19 +2127,51 @@ impl ServerHandler for BookmarkServer {
         _context: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, McpError> {
         if uri == "bookmark://tree" {
-            // Tree view not available with INDEX_NAME approach
-            // Use search tools to access bookmarks
-            Err(McpError::resource_not_found(
-                "Bookmark tree is not available when using INDEX_NAME. Use search tools instead."
-                    .to_string(),
-                Some(json!({ "uri": uri })),
-            ))
+            // Rebuild the tree from the folder paths stored in the index
+            let bookmarks = self.search_manager().list_bookmarks().await.map_err(|e| {
+                McpError::internal_error(format!("Failed to list bookmarks: {e}"), None)
+            })?;
+            let tree = BookmarkNode::from_indexed_entries(&bookmarks);
+
+            let tree_json = serde_json::to_string_pretty(&tree).map_err(|e| {
+                McpError::internal_error(format!("Error serializing tree: {e}"), None)
+            })?;
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(tree_json, uri.clone())],
+            })
         } else if uri.starts_with("bookmark://folder/") {
             // Folder resources not available with INDEX_NAME approach
             Err(McpError::resource_not_found(
                 "Folder resources are not available when using INDEX_NAME. Use search tools instead.".to_string(),
                 Some(json!({ "uri": uri })),
             ))
+        } else if let Some(id) = uri.strip_prefix("bookmark://") {
+            let manager = self.search_manager();
+            let entry = manager
+                .get_bookmark_by_id(id)
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to read bookmark: {e}"), None)
+                })?
+                .ok_or_else(|| {
+                    McpError::resource_not_found(
+                        format!("Bookmark not found: {id}"),
+                        Some(json!({ "uri": uri })),
+                    )
+                })?;
+
+            let content = manager
+                .get_content_by_url(&entry.url)
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to read content: {e}"), None)
+                })?
+                .unwrap_or_default();
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(content, uri.clone())],
+            })
         } else {
             Err(McpError::resource_not_found(
                 format!("Unknown resource: {uri}"),
@@ -0,0 +1,384 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::info;
+
+/// Upper bounds (ms) of the query latency histogram buckets; slower queries fall in +Inf
+pub const LATENCY_BUCKETS_MS: &[f64] =
+    &[5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0];
+
+/// Counters for one period (the current day or everything since startup)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Counters {
+    pub queries: u64,
+    pub failed_queries: u64,
+    pub empty_queries: u64,
+    pub results_returned: u64,
+    pub total_query_ms: f64,
+    pub max_query_ms: f64,
+    /// Query counts per latency bucket, the last one being +Inf
+    pub latency_buckets: Vec<u64>,
+    pub queries_by_tool: BTreeMap<String, u64>,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub documents_indexed: u64,
+    pub bytes_indexed: u64,
+    pub indexing_secs: f64,
+}
+
+/// Derived view of `Counters` as reported by `get_metrics`
+#[derive(Debug, Clone, Serialize)]
+pub struct CountersReport {
+    pub queries: u64,
+    pub failed_queries: u64,
+    /// Successful queries that returned nothing
+    pub empty_queries: u64,
+    pub avg_results: f64,
+    pub avg_latency_ms: f64,
+    /// Upper bound of the bucket holding the 95th percentile (None above the last bucket)
+    pub p95_latency_ms: Option<f64>,
+    pub max_latency_ms: f64,
+    pub queries_by_tool: BTreeMap<String, u64>,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub cache_hit_rate: f64,
+    pub documents_indexed: u64,
+    pub bytes_indexed: u64,
+    pub documents_per_sec: f64,
+}
+
+/// Metrics for the current day and since startup
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsReport {
+    pub started_at: DateTime<Utc>,
+    /// UTC day `today` covers; it restarts at midnight, like the daily log files
+    pub day: NaiveDate,
+    pub today: CountersReport,
+    pub since_start: CountersReport,
+}
+
+#[derive(Debug)]
+struct State {
+    started_at: DateTime<Utc>,
+    day: NaiveDate,
+    today: Counters,
+    total: Counters,
+}
+
+/// Process-wide query, cache and indexing metrics
+#[derive(Debug)]
+pub struct Metrics {
+    state: Mutex<State>,
+}
+
+/// Metrics shared by the whole process
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new(Utc::now()))
+}
+
+impl Counters {
+    fn record_query(&mut self, tool: &str, elapsed_ms: f64, results: Option<usize>) {
+        self.queries += 1;
+        *self.queries_by_tool.entry(tool.to_string()).or_default() += 1;
+        self.total_query_ms += elapsed_ms;
+        self.max_query_ms = self.max_query_ms.max(elapsed_ms);
+        if self.latency_buckets.is_empty() {
+            self.latency_buckets = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        }
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| elapsed_ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency_buckets[bucket] += 1;
+        match results {
+            Some(0) => self.empty_queries += 1,
+            Some(count) => self.results_returned += count as u64,
+            None => self.failed_queries += 1,
+        }
+    }
+
+    fn record_cache(&mut self, hit: bool) {
+        if hit {
+            self.cache_hits += 1;
+        } else {
+            self.cache_misses += 1;
+        }
+    }
+
+    fn record_indexed(&mut self, bytes: usize, elapsed_secs: f64) {
+        self.documents_indexed += 1;
+        self.bytes_indexed += bytes as u64;
+        self.indexing_secs += elapsed_secs;
+    }
+
+    fn report(&self) -> CountersReport {
+        let ratio = |part: f64, whole: f64| if whole > 0.0 { part / whole } else { 0.0 };
+        let succeeded = self.queries - self.failed_queries;
+        CountersReport {
+            queries: self.queries,
+            failed_queries: self.failed_queries,
+            empty_queries: self.empty_queries,
+            avg_results: ratio(self.results_returned as f64, succeeded as f64),
+            avg_latency_ms: ratio(self.total_query_ms, self.queries as f64),
+            p95_latency_ms: self.latency_percentile(0.95),
+            max_latency_ms: self.max_query_ms,
+            queries_by_tool: self.queries_by_tool.clone(),
+            cache_hits: self.cache_hits,
+            cache_misses: self.cache_misses,
+            cache_hit_rate: ratio(
+                self.cache_hits as f64,
+                (self.cache_hits + self.cache_misses) as f64,
+            ),
+            documents_indexed: self.documents_indexed,
+            bytes_indexed: self.bytes_indexed,
+            documents_per_sec: ratio(self.documents_indexed as f64, self.indexing_secs),
+        }
+    }
+
+    /// Upper bound of the bucket holding the given quantile of query latencies
+    fn latency_percentile(&self, quantile: f64) -> Option<f64> {
+        if self.queries == 0 {
+            return Some(0.0);
+        }
+        let target = (self.queries as f64 * quantile).ceil() as u64;
+        let mut seen = 0;
+        for (i, count) in self.latency_buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return LATENCY_BUCKETS_MS.get(i).copied();
+            }
+        }
+        None
+    }
+}
+
+impl Metrics {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            state: Mutex::new(State {
+                started_at: now,
+                day: now.date_naive(),
+                today: Counters::default(),
+                total: Counters::default(),
+            }),
+        }
+    }
+
+    /// Run `update` on today's and the total counters, starting a new day first if the date changed
+    fn update(&self, now: DateTime<Utc>, update: impl Fn(&mut Counters)) {
+        let mut state = self.state.lock().unwrap();
+        state.roll_over(now);
+        update(&mut state.today);
+        update(&mut state.total);
+    }
+
+    /// Record a search tool call; `results` is None when it failed
+    pub fn record_query(&self, tool: &str, elapsed: Duration, results: Option<usize>) {
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        self.update(Utc::now(), |c| c.record_query(tool, elapsed_ms, results));
+    }
+
+    /// Record a lookup in a cache (e.g. the registry of open indexes)
+    pub fn record_cache(&self, hit: bool) {
+        self.update(Utc::now(), |c| c.record_cache(hit));
+    }
+
+    /// Record a document added to an index
+    pub fn record_indexed(&self, bytes: usize, elapsed: Duration) {
+        let elapsed_secs = elapsed.as_secs_f64();
+        self.update(Utc::now(), |c| c.record_indexed(bytes, elapsed_secs));
+    }
+
+    /// Current metrics
+    pub fn report(&self) -> MetricsReport {
+        self.report_at(Utc::now())
+    }
+
+    fn report_at(&self, now: DateTime<Utc>) -> MetricsReport {
+        let mut state = self.state.lock().unwrap();
+        state.roll_over(now);
+        MetricsReport {
+            started_at: state.started_at,
+            day: state.day,
+            today: state.today.report(),
+            since_start: state.total.report(),
+        }
+    }
+
+    /// Totals since startup in the Prometheus text exposition format
+    ///
+    /// `index_size_bytes` is included when known (it isn't tracked by the
+    /// counters themselves).
+    pub fn prometheus_text(&self, index_size_bytes: Option<u64>) -> String {
+        let total = self.state.lock().unwrap().total.clone();
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+            for (labels, value) in samples {
+                out.push_str(&format!("{name}{labels} {value}\n"));
+            }
+        };
+        let plain = |value: String| vec![(String::new(), value)];
+
+        let by_tool: Vec<(String, String)> = total
+            .queries_by_tool
+            .iter()
+            .map(|(tool, count)| (format!("{{tool=\"{tool}\"}}"), count.to_string()))
+            .collect();
+        metric(
+            "mcp_bookmark_queries_total",
+            "counter",
+            "Search tool calls",
+            &by_tool,
+        );
+        metric(
+            "mcp_bookmark_failed_queries_total",
+            "counter",
+            "Search tool calls that returned an error",
+            &plain(total.failed_queries.to_string()),
+        );
+        metric(
+            "mcp_bookmark_empty_queries_total",
+            "counter",
+            "Search tool calls that found nothing",
+            &plain(total.empty_queries.to_string()),
+        );
+        metric(
+            "mcp_bookmark_results_returned_total",
+            "counter",
+            "Results returned by search tools",
+            &plain(total.results_returned.to_string()),
+        );
+
+        let mut cumulative = 0;
+        let mut buckets = Vec::new();
+        for (i, count) in total.latency_buckets.iter().enumerate() {
+            cumulative += count;
+            let bound = LATENCY_BUCKETS_MS
+                .get(i)
+                .map_or("+Inf".to_string(), |ms| (ms / 1000.0).to_string());
+            buckets.push((format!("_bucket{{le=\"{bound}\"}}"), cumulative.to_string()));
+        }
+        if buckets.is_empty() {
+            buckets.push(("_bucket{le=\"+Inf\"}".to_string(), "0".to_string()));
+        }
+        buckets.push((
+            "_sum".to_string(),
+            (total.total_query_ms / 1000.0).to_string(),
+        ));
+        buckets.push(("_count".to_string(), total.queries.to_string()));
+        metric(
+            "mcp_bookmark_query_duration_seconds",
+            "histogram",
+            "Search tool latency",
+            &buckets,
+        );
+
+        metric(
+            "mcp_bookmark_cache_hits_total",
+            "counter",
+            "Lookups served by an already open index",
+            &plain(total.cache_hits.to_string()),
+        );
+        metric(
+            "mcp_bookmark_cache_misses_total",
+            "counter",
+            "Lookups that had to open an index",
+            &plain(total.cache_misses.to_string()),
+        );
+        metric(
+            "mcp_bookmark_documents_indexed_total",
+            "counter",
+            "Documents added to indexes by this process",
+            &plain(total.documents_indexed.to_string()),
+        );
+        metric(
+            "mcp_bookmark_bytes_indexed_total",
+            "counter",
+            "Content bytes added to indexes by this process",
+            &plain(total.bytes_indexed.to_string()),
+        );
+        if let Some(size) = index_size_bytes {
+            metric(
+                "mcp_bookmark_index_size_bytes",
+                "gauge",
+                "Size of the active index on disk",
+                &plain(size.to_string()),
+            );
+        }
+        out
+    }
+}
+
+impl State {
+    /// Start a new day's counters when the UTC date changes, logging the finished day
+    fn roll_over(&mut self, now: DateTime<Utc>) {
+        let day = now.date_naive();
+        if day == self.day {
+            return;
+        }
+        let finished = self.today.report();
+        info!(
+            "Metrics for {}: {} queries (avg {:.1} ms, {} failed), {} documents indexed, cache hit rate {:.2}",
+            self.day,
+            finished.queries,
+            finished.avg_latency_ms,
+            finished.failed_queries,
+            finished.documents_indexed,
+            finished.cache_hit_rate
+        );
+        self.day = day;
+        self.today = Counters::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_report() {
+        let start = DateTime::parse_from_rfc3339("2025-12-01T23:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let metrics = Metrics::new(start);
+        let record = |ms: u64, results: Option<usize>| {
+            let elapsed_ms = ms as f64;
+            metrics.update(start, |c| c.record_query("search", elapsed_ms, results));
+        };
+        record(3, Some(5));
+        record(40, Some(0));
+        record(3000, None);
+        metrics.update(start, |c| c.record_cache(true));
+        metrics.update(start, |c| c.record_indexed(1000, 0.5));
+
+        let report = metrics.report_at(start);
+        assert_eq!(report.today.queries, 3);
+        assert_eq!(report.today.failed_queries, 1);
+        assert_eq!(report.today.empty_queries, 1);
+        assert_eq!(report.today.avg_results, 2.5);
+        assert_eq!(report.today.p95_latency_ms, None);
+        assert_eq!(report.today.max_latency_ms, 3000.0);
+        assert_eq!(report.today.queries_by_tool["search"], 3);
+        assert_eq!(report.today.documents_per_sec, 2.0);
+
+        // A new UTC day starts fresh counters but keeps the totals
+        let next_day = start + chrono::Duration::hours(2);
+        let report = metrics.report_at(next_day);
+        assert_eq!(report.day.to_string(), "2025-12-02");
+        assert_eq!(report.today.queries, 0);
+        assert_eq!(report.today.p95_latency_ms, Some(0.0));
+        assert_eq!(report.since_start.queries, 3);
+        assert_eq!(report.since_start.cache_hit_rate, 1.0);
+
+        let text = metrics.prometheus_text(Some(4096));
+        assert!(text.contains("mcp_bookmark_queries_total{tool=\"search\"} 3\n"));
+        assert!(text.contains("mcp_bookmark_query_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("mcp_bookmark_query_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("mcp_bookmark_query_duration_seconds_count 3\n"));
+        assert!(text.contains("mcp_bookmark_index_size_bytes 4096\n"));
+    }
+}
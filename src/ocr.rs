@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+
+use crate::config::OcrSettings;
+
+/// Where the ocrs detection and recognition models are published
+pub const MODELS_URL: &str = "https://ocrs-models.s3-accelerate.amazonaws.com/";
+
+/// Whether scanned PDF pages can be OCRed: built with the `ocr` feature and enabled in `[ocr]`
+pub fn enabled() -> bool {
+    cfg!(feature = "ocr") && crate::config::settings().ocr.enabled
+}
+
+/// Recognize the text of a PNG image sent by the extension, one line per text line
+///
+/// `data` is the base64-encoded image, optionally as a `data:image/png;base64,` URL.
+pub fn recognize_image(data: &str) -> Result<String> {
+    let settings = &crate::config::settings().ocr;
+    anyhow::ensure!(
+        settings.enabled,
+        "OCR is disabled (set enabled = true in [ocr])"
+    );
+    engine::recognize(settings, strip_data_url(data)).context("OCR failed")
+}
+
+/// The base64 payload of a `data:` URL, or `data` itself
+fn strip_data_url(data: &str) -> &str {
+    match data.strip_prefix("data:") {
+        Some(rest) => rest.split_once(',').map_or(rest, |(_, payload)| payload),
+        None => data,
+    }
+}
+
+#[cfg(feature = "ocr")]
+mod engine {
+    use super::OcrSettings;
+    use anyhow::{Context, Result};
+    use base64::Engine as _;
+    use ocrs::{ImageSource, OcrEngine, OcrEngineParams};
+    use rten::Model;
+    use std::sync::OnceLock;
+
+    static ENGINE: OnceLock<OcrEngine> = OnceLock::new();
+
+    /// Process-wide engine for the configured models (loaded on first use)
+    fn engine(settings: &OcrSettings) -> Result<&'static OcrEngine> {
+        if let Some(engine) = ENGINE.get() {
+            return Ok(engine);
+        }
+        let load = |path: std::path::PathBuf| {
+            Model::load_file(&path).with_context(|| {
+                format!(
+                    "Failed to load OCR model {path:?} (download it from {})",
+                    super::MODELS_URL
+                )
+            })
+        };
+        let engine = OcrEngine::new(OcrEngineParams {
+            detection_model: Some(load(settings.detection_model())?),
+            recognition_model: Some(load(settings.recognition_model())?),
+            ..Default::default()
+        })?;
+        Ok(ENGINE.get_or_init(|| engine))
+    }
+
+    pub fn recognize(settings: &OcrSettings, base64: &str) -> Result<String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(base64.trim())
+            .context("Invalid base64 image")?;
+        let image = image::load_from_memory(&bytes)
+            .context("Invalid PNG image")?
+            .into_rgb8();
+        let engine = engine(settings)?;
+        let source = ImageSource::from_bytes(image.as_raw(), image.dimensions())?;
+        let input = engine.prepare_input(source)?;
+        engine.get_text(&input)
+    }
+}
+
+#[cfg(not(feature = "ocr"))]
+mod engine {
+    use super::OcrSettings;
+    use anyhow::Result;
+
+    pub fn recognize(_settings: &OcrSettings, _base64: &str) -> Result<String> {
+        anyhow::bail!("mcp-bookmark was built without the `ocr` feature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_data_url() {
+        assert_eq!(strip_data_url("data:image/png;base64,iVBORw0K"), "iVBORw0K");
+        assert_eq!(strip_data_url("iVBORw0K"), "iVBORw0K");
+        assert_eq!(strip_data_url("data:broken"), "broken");
+    }
+}
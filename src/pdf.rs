@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+
+use crate::search::indexer::PageInfo;
+
+/// Content of a PDF without a text layer, as the extension indexes it
+const NO_TEXT: &str = "PDF document (no extractable text)";
+
+/// Text of a PDF with `[PAGE:n]` markers and its page offsets
+///
+/// Fetched PDFs have no OCR; pages without a text layer are indexed empty.
+pub fn extract(bytes: &[u8]) -> Result<(String, PageInfo)> {
+    let pages = pdf_extract::extract_text_from_mem_by_pages(bytes)
+        .context("Failed to extract text from PDF")?;
+    Ok(join_pages(&pages))
+}
+
+/// Join page texts the way the extension's `buildPdfText` does
+///
+/// Each page starts with its `[PAGE:n]` marker, pages are separated by a
+/// blank line and offsets (in characters) point at the markers.
+pub fn join_pages(pages: &[String]) -> (String, PageInfo) {
+    let mut text = String::new();
+    let mut page_offsets = Vec::with_capacity(pages.len());
+    let mut offset = 0;
+    for (i, page) in pages.iter().enumerate() {
+        if i > 0 {
+            text.push_str("\n\n");
+            offset += 2;
+        }
+        page_offsets.push(offset);
+        let page = page.trim();
+        let page = if page.is_empty() {
+            format!("[PAGE:{}]", i + 1)
+        } else {
+            format!("[PAGE:{}]\n{page}", i + 1)
+        };
+        offset += page.chars().count();
+        text.push_str(&page);
+    }
+    let page_info = PageInfo {
+        page_count: pages.len(),
+        page_offsets,
+        content_type: "pdf".to_string(),
+        total_chars: offset,
+        ocr: false,
+        part: None,
+        truncated_from: None,
+    };
+    if text.is_empty() {
+        text = NO_TEXT.to_string();
+    }
+    (text, page_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_pages() {
+        let (text, info) = join_pages(&[
+            "Introduction\n".to_string(),
+            String::new(),
+            "数式の例".to_string(),
+        ]);
+        assert_eq!(
+            text,
+            "[PAGE:1]\nIntroduction\n\n[PAGE:2]\n\n[PAGE:3]\n数式の例"
+        );
+        assert_eq!(info.page_count, 3);
+        assert_eq!(info.page_offsets, vec![0, 23, 33]);
+        assert_eq!(info.total_chars, text.chars().count());
+        for (i, &offset) in info.page_offsets.iter().enumerate() {
+            let marker: String = text.chars().skip(offset).take(8).collect();
+            assert_eq!(marker, format!("[PAGE:{}]", i + 1));
+        }
+
+        let (text, info) = join_pages(&[]);
+        assert_eq!(text, NO_TEXT);
+        assert_eq!(info.page_count, 0);
+    }
+}
@@ -0,0 +1,298 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::config::QueryLogSettings;
+
+/// Directory under the data directory holding one query log per index
+pub const QUERY_LOG_DIR: &str = "query_logs";
+
+/// Filters a search was run with
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct QueryFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+impl QueryFilters {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// One recorded search
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QueryLogEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Tool that ran the search, e.g. `search_bookmarks_fulltext`
+    pub tool: String,
+    pub query: String,
+    #[serde(default, skip_serializing_if = "QueryFilters::is_empty")]
+    pub filters: QueryFilters,
+    pub result_count: usize,
+    pub latency_ms: u64,
+}
+
+impl QueryLogEntry {
+    /// Entry for a search finishing now
+    pub fn new(
+        tool: &str,
+        query: &str,
+        filters: QueryFilters,
+        result_count: usize,
+        elapsed: std::time::Duration,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            tool: tool.to_string(),
+            query: query.to_string(),
+            filters,
+            result_count,
+            latency_ms: elapsed.as_millis() as u64,
+        }
+    }
+}
+
+/// How often a query was searched and how it went
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryStat {
+    /// Query lowercased with whitespace collapsed
+    pub query: String,
+    pub count: usize,
+    pub avg_results: f32,
+    pub avg_latency_ms: f32,
+    /// Results of the most recent search
+    pub last_results: usize,
+    pub last_searched: DateTime<Utc>,
+}
+
+/// Summary of the logged searches
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchAnalytics {
+    pub total_queries: usize,
+    pub unique_queries: usize,
+    /// Searches that returned nothing
+    pub zero_result_searches: usize,
+    /// Oldest search covered
+    pub since: Option<DateTime<Utc>>,
+    /// Most frequent queries
+    pub top_queries: Vec<QueryStat>,
+    /// Queries whose most recent search returned nothing, most frequent first
+    pub zero_result_queries: Vec<QueryStat>,
+}
+
+/// Recent searches against one index, stored as JSON lines
+///
+/// The log never leaves the data directory. Retention is applied on every
+/// write: queries beyond `max_entries` or older than `max_age_days` are dropped.
+#[derive(Debug, Default)]
+pub struct QueryLog {
+    path: PathBuf,
+    entries: Vec<QueryLogEntry>,
+}
+
+impl QueryLog {
+    /// Log file for an index in the default data directory
+    pub fn path_for(index: &str) -> PathBuf {
+        crate::config::data_dir()
+            .join(QUERY_LOG_DIR)
+            .join(format!("{index}.jsonl"))
+    }
+
+    /// Load a log, skipping unreadable lines and starting empty if the file is missing
+    pub fn load(path: PathBuf) -> Self {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut skipped = 0;
+        let entries = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(_) => {
+                    skipped += 1;
+                    None
+                }
+            })
+            .collect();
+        if skipped > 0 {
+            warn!("Skipped {} unreadable lines in {:?}", skipped, path);
+        }
+        Self { path, entries }
+    }
+
+    pub fn entries(&self) -> &[QueryLogEntry] {
+        &self.entries
+    }
+
+    /// Append a search, apply retention and write the log back
+    pub fn record(&mut self, entry: QueryLogEntry, settings: &QueryLogSettings) -> Result<()> {
+        let now = entry.timestamp;
+        self.entries.push(entry);
+        self.prune(settings, now);
+        self.save()
+    }
+
+    /// Drop queries outside the retention limits, returning whether any were dropped
+    pub fn prune(&mut self, settings: &QueryLogSettings, now: DateTime<Utc>) -> bool {
+        let before = self.entries.len();
+        if settings.max_age_days > 0 {
+            let cutoff = now - Duration::days(settings.max_age_days as i64);
+            self.entries.retain(|e| e.timestamp >= cutoff);
+        }
+        if settings.max_entries > 0 && self.entries.len() > settings.max_entries {
+            let excess = self.entries.len() - settings.max_entries;
+            self.entries.drain(..excess);
+        }
+        self.entries.len() != before
+    }
+
+    /// Write the log to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {:?}", self.path))
+    }
+
+    /// Top queries and zero-result queries among searches since `since`
+    pub fn analytics(&self, since: Option<DateTime<Utc>>, limit: usize) -> SearchAnalytics {
+        let entries: Vec<&QueryLogEntry> = self
+            .entries
+            .iter()
+            .filter(|e| since.is_none_or(|since| e.timestamp >= since))
+            .collect();
+
+        let mut groups: HashMap<String, Vec<&QueryLogEntry>> = HashMap::new();
+        for entry in &entries {
+            groups
+                .entry(normalize_query(&entry.query))
+                .or_default()
+                .push(*entry);
+        }
+
+        let mut stats: Vec<QueryStat> = groups
+            .into_iter()
+            .filter_map(|(query, searches)| {
+                let count = searches.len();
+                let last = searches.iter().max_by_key(|e| e.timestamp)?;
+                Some(QueryStat {
+                    query,
+                    count,
+                    avg_results: searches.iter().map(|e| e.result_count).sum::<usize>() as f32
+                        / count as f32,
+                    avg_latency_ms: searches.iter().map(|e| e.latency_ms).sum::<u64>() as f32
+                        / count as f32,
+                    last_results: last.result_count,
+                    last_searched: last.timestamp,
+                })
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.last_searched.cmp(&a.last_searched))
+                .then_with(|| a.query.cmp(&b.query))
+        });
+
+        SearchAnalytics {
+            total_queries: entries.len(),
+            unique_queries: stats.len(),
+            zero_result_searches: entries.iter().filter(|e| e.result_count == 0).count(),
+            since: entries.iter().map(|e| e.timestamp).min(),
+            zero_result_queries: stats
+                .iter()
+                .filter(|s| s.last_results == 0)
+                .take(limit)
+                .cloned()
+                .collect(),
+            top_queries: stats.into_iter().take(limit).collect(),
+        }
+    }
+}
+
+/// Lowercase and collapse whitespace so trivially different spellings group together
+pub fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(query: &str, result_count: usize, minutes_ago: i64) -> QueryLogEntry {
+        QueryLogEntry {
+            timestamp: Utc::now() - Duration::minutes(minutes_ago),
+            tool: "search_bookmarks_fulltext".to_string(),
+            query: query.to_string(),
+            filters: QueryFilters::default(),
+            result_count,
+            latency_ms: 10,
+        }
+    }
+
+    #[test]
+    fn test_record_and_analytics() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(QUERY_LOG_DIR).join("work.jsonl");
+        let settings = QueryLogSettings {
+            enabled: true,
+            max_entries: 4,
+            max_age_days: 1,
+        };
+
+        let mut log = QueryLog::load(path.clone());
+        let mut old = entry("forgotten", 3, 0);
+        old.timestamp = Utc::now() - Duration::days(2);
+        log.entries.push(old);
+        log.record(entry("rust", 5, 40), &settings).unwrap();
+        log.record(entry("Rust ", 7, 30), &settings).unwrap();
+        log.record(entry("kubernetes operator", 0, 20), &settings)
+            .unwrap();
+        log.record(entry("tantivy", 0, 15), &settings).unwrap();
+        log.record(entry("tantivy", 2, 10), &settings).unwrap();
+
+        // The two-day-old query and the oldest "rust" search fall outside retention
+        let log = QueryLog::load(path);
+        assert_eq!(log.entries().len(), 4);
+
+        let analytics = log.analytics(None, 10);
+        assert_eq!(analytics.total_queries, 4);
+        assert_eq!(analytics.unique_queries, 3);
+        assert_eq!(analytics.zero_result_searches, 2);
+        assert_eq!(analytics.top_queries[0].query, "tantivy");
+        assert_eq!(analytics.top_queries[0].count, 2);
+        assert_eq!(analytics.top_queries[0].last_results, 2);
+        assert_eq!(analytics.top_queries[0].avg_results, 1.0);
+        assert_eq!(analytics.zero_result_queries.len(), 1);
+        assert_eq!(
+            analytics.zero_result_queries[0].query,
+            "kubernetes operator"
+        );
+
+        let recent = log.analytics(Some(Utc::now() - Duration::minutes(12)), 1);
+        assert_eq!(recent.total_queries, 1);
+        assert_eq!(recent.top_queries.len(), 1);
+        assert!(recent.zero_result_queries.is_empty());
+    }
+}
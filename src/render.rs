@@ -0,0 +1,137 @@
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::config::FetchSettings;
+use crate::robots;
+
+/// Whether pages can be rendered: built with the `render` feature
+pub fn available() -> bool {
+    cfg!(feature = "render")
+}
+
+/// Whether a page fetched with `text_chars` characters of text should be rendered
+///
+/// Pages of `render_domains` always are; other pages when `render_empty_pages`
+/// is on and their text is shorter than `render_min_chars`.
+pub fn wanted(settings: &FetchSettings, url: &str, text_chars: usize) -> bool {
+    let listed = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| robots::host_in(&host, &settings.render_domains));
+    listed || (settings.render_empty_pages && text_chars < settings.render_min_chars)
+}
+
+/// HTML of `url` once its scripts ran in headless Chrome, if the fetched page needs it
+///
+/// Returns None when rendering isn't wanted, isn't built in, fails, or gives
+/// less text than the fetched page had, which then stays as it is.
+pub async fn render_if_wanted(url: &str, text_chars: usize) -> Option<String> {
+    let settings = &crate::config::settings().fetch;
+    if !wanted(settings, url, text_chars) {
+        return None;
+    }
+    if !available() {
+        debug!("Not rendering {}: built without the `render` feature", url);
+        return None;
+    }
+    let timeout = Duration::from_secs(settings.timeout_secs.max(1));
+    let rendered = match tokio::time::timeout(timeout, engine::render(settings, url)).await {
+        Ok(Ok(html)) => html,
+        Ok(Err(e)) => {
+            warn!("Failed to render {}: {:#}", url, e);
+            return None;
+        }
+        Err(_) => {
+            warn!("Rendering {} timed out after {:?}", url, timeout);
+            return None;
+        }
+    };
+    let rendered_chars = crate::site::extract_html(&rendered).text.chars().count();
+    debug!(
+        "Rendered {}: {} characters of text instead of {}",
+        url, rendered_chars, text_chars
+    );
+    (rendered_chars >= text_chars).then_some(rendered)
+}
+
+#[cfg(feature = "render")]
+mod engine {
+    use super::FetchSettings;
+    use anyhow::{Context, Result};
+    use chromiumoxide::browser::{Browser, BrowserConfig};
+    use futures::StreamExt;
+
+    /// Load `url` in a fresh headless browser and return the resulting HTML
+    ///
+    /// The browser is closed again afterwards, so no Chrome process outlives a
+    /// CLI run; rendering is meant for the few pages that need it.
+    pub async fn render(settings: &FetchSettings, url: &str) -> Result<String> {
+        let mut builder = BrowserConfig::builder();
+        if let Some(path) = &settings.chrome_path {
+            builder = builder.chrome_executable(path);
+        }
+        if let Some(user_agent) = &settings.user_agent {
+            builder = builder.arg(format!("--user-agent={user_agent}"));
+        }
+        if let Some(proxy) = &settings.proxy {
+            builder = builder.arg(format!("--proxy-server={proxy}"));
+        }
+        let config = builder.build().map_err(anyhow::Error::msg)?;
+        let (mut browser, mut handler) = Browser::launch(config)
+            .await
+            .context("Failed to launch Chrome (set [fetch] chrome_path)")?;
+        let events = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+        let html = async {
+            let page = browser.new_page(url).await?;
+            page.wait_for_navigation().await?;
+            anyhow::Ok(page.content().await?)
+        }
+        .await;
+
+        let _ = browser.close().await;
+        let _ = browser.wait().await;
+        events.abort();
+        html
+    }
+}
+
+#[cfg(not(feature = "render"))]
+mod engine {
+    use super::FetchSettings;
+    use anyhow::Result;
+
+    pub async fn render(_settings: &FetchSettings, _url: &str) -> Result<String> {
+        anyhow::bail!("mcp-bookmark was built without the `render` feature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wanted() {
+        let settings = FetchSettings {
+            render_domains: vec!["app.example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(wanted(
+            &settings,
+            "https://docs.app.example.com/guide",
+            5_000
+        ));
+        assert!(!wanted(&settings, "https://example.com/guide", 0));
+
+        let settings = FetchSettings {
+            render_empty_pages: true,
+            ..settings
+        };
+        assert!(wanted(&settings, "https://example.com/guide", 0));
+        assert!(!wanted(
+            &settings,
+            "https://example.com/guide",
+            settings.render_min_chars
+        ));
+    }
+}
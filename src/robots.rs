@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+/// Product token matched against robots.txt `User-agent` lines
+pub const ROBOTS_AGENT: &str = "mcp-bookmark";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// The rules of one host's robots.txt that apply to mcp-bookmark
+///
+/// Follows RFC 9309: the group naming our product token wins over `*`,
+/// the longest matching pattern decides and `Allow` wins a tie. `*` and a
+/// trailing `$` are supported in patterns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// Rules allowing everything, used when a host has no robots.txt
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Rules disallowing everything, used when robots.txt can't be fetched
+    pub fn disallow_all() -> Self {
+        Self {
+            rules: vec![Rule {
+                allow: false,
+                pattern: "/".to_string(),
+            }],
+            crawl_delay: None,
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        // (agents, rules, crawl delay) per group; consecutive User-agent lines share a group
+        let mut groups: Vec<(Vec<String>, Self)> = Vec::new();
+        let mut in_agents = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            if key == "user-agent" {
+                if !in_agents {
+                    groups.push((Vec::new(), Self::default()));
+                    in_agents = true;
+                }
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_lowercase());
+                }
+                continue;
+            }
+            in_agents = false;
+            let Some((_, group)) = groups.last_mut() else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" | "disallow" if !value.is_empty() => group.rules.push(Rule {
+                    allow: key == "allow",
+                    pattern: value.to_string(),
+                }),
+                "crawl-delay" => {
+                    group.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(Duration::from_secs_f64);
+                }
+                _ => {}
+            }
+        }
+
+        let matching = |wanted: &dyn Fn(&str) -> bool| {
+            let mut merged: Option<Self> = None;
+            for (agents, group) in &groups {
+                if agents.iter().any(|agent| wanted(agent)) {
+                    let merged = merged.get_or_insert_with(Self::default);
+                    merged.rules.extend(group.rules.iter().cloned());
+                    merged.crawl_delay = merged.crawl_delay.or(group.crawl_delay);
+                }
+            }
+            merged
+        };
+        matching(&|agent| agent == ROBOTS_AGENT)
+            .or_else(|| matching(&|agent| agent == "*"))
+            .unwrap_or_default()
+    }
+
+    /// Whether a URL path (with its query) may be fetched
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// Delay the host asks for between requests
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// Match a robots.txt path pattern, where `*` is any run of characters and a trailing `$` anchors the end
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    match pattern.strip_suffix('$') {
+        Some(pattern) => glob(pattern.as_bytes(), path.as_bytes(), true),
+        None => glob(pattern.as_bytes(), path.as_bytes(), false),
+    }
+}
+
+/// Match `pattern` against the start of `path` (all of it when `anchored`)
+fn glob(pattern: &[u8], path: &[u8], anchored: bool) -> bool {
+    match pattern.split_first() {
+        None => !anchored || path.is_empty(),
+        Some((b'*', rest)) => (0..=path.len()).any(|i| glob(rest, &path[i..], anchored)),
+        Some((c, rest)) => path.first() == Some(c) && glob(rest, &path[1..], anchored),
+    }
+}
+
+/// Whether `host` is one of `domains` or a subdomain of one
+pub fn host_in(host: &str, domains: &[String]) -> bool {
+    let host = host.to_lowercase();
+    domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches('.').to_lowercase();
+        host == domain
+            || host
+                .strip_suffix(&domain)
+                .is_some_and(|rest| rest.ends_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_rules() {
+        let rules = RobotsRules::parse(
+            "# example\n\
+             User-agent: *\n\
+             Disallow: /private/\n\
+             Allow: /private/public-page\n\
+             Disallow: /*.pdf$\n\
+             Crawl-delay: 2\n\
+             \n\
+             User-agent: otherbot\n\
+             Disallow: /\n",
+        );
+        assert!(rules.allows("/docs/intro"));
+        assert!(!rules.allows("/private/notes"));
+        assert!(rules.allows("/private/public-page"));
+        assert!(!rules.allows("/files/report.pdf"));
+        assert!(rules.allows("/files/report.pdf?download=1"));
+        assert_eq!(rules.crawl_delay(), Some(Duration::from_secs(2)));
+
+        // Our own group replaces the `*` group
+        let rules = RobotsRules::parse(
+            "User-agent: *\nDisallow: /\n\nUser-agent: mcp-bookmark\nDisallow: /admin\n",
+        );
+        assert!(rules.allows("/docs"));
+        assert!(!rules.allows("/admin/users"));
+        assert_eq!(rules.crawl_delay(), None);
+
+        assert!(RobotsRules::parse("").allows("/anything"));
+        assert!(RobotsRules::parse("User-agent: *\nDisallow:\n").allows("/anything"));
+        assert!(!RobotsRules::disallow_all().allows("/"));
+
+        assert!(host_in("docs.example.com", &["example.com".to_string()]));
+        assert!(!host_in("badexample.com", &["example.com".to_string()]));
+    }
+}
@@ -0,0 +1,390 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tantivy::schema::{Document, Schema, Value};
+use tantivy::{Index, TantivyDocument};
+
+use super::common::{base_bookmark_id, timestamp_to_datetime};
+use super::indexer::{Heading, fenced_code_blocks};
+use super::schema::BookmarkSchema;
+use super::search_manager::SearchManager;
+use super::sync_metadata::{SyncMetadata, SyncedBookmark};
+use super::tokenizer::{LinderaDictionary, index_dictionary};
+
+/// Version of the changeset format, bumped on incompatible changes
+pub const CHANGESET_FORMAT: u32 = 1;
+
+/// Changes to an index since a point in time, written by `index diff` and read by `index apply`
+///
+/// Bookmarks carry their stored documents as they are, so the machine applying
+/// the changeset doesn't fetch anything again. Bookmarks are matched by ID and
+/// the copy indexed last wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Changeset {
+    pub format: u32,
+    /// Index the changes were taken from
+    pub index: String,
+    /// Dictionary of that index, used when `index apply` creates a new one
+    pub dictionary: Option<LinderaDictionary>,
+    pub since: DateTime<Utc>,
+    /// Pass as `--since` to the next `index diff`
+    pub created_at: DateTime<Utc>,
+    /// Bookmarks added to the browser since `since`
+    pub added: Vec<ChangedBookmark>,
+    /// Older bookmarks indexed again since `since`
+    pub updated: Vec<ChangedBookmark>,
+    /// Bookmarks the index no longer has
+    pub deleted: Vec<DeletedBookmark>,
+}
+
+/// A bookmark with its documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedBookmark {
+    pub id: String,
+    pub url: String,
+    /// When the source indexed it (Unix seconds, 0 if it wasn't recorded)
+    pub indexed_at: u64,
+    /// Its `index_metadata.json` record, if any
+    pub synced: Option<SyncedBookmark>,
+    /// Stored fields of each document (several for split PDFs) in tantivy's JSON form
+    pub documents: Vec<serde_json::Value>,
+}
+
+/// A bookmark recorded in the source's metadata whose documents are gone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedBookmark {
+    pub id: String,
+    pub url: String,
+    /// When the source last indexed it; newer copies on the other side are kept
+    pub indexed_at: u64,
+}
+
+/// What `Changeset::apply` did
+#[derive(Debug, Default, Serialize)]
+pub struct ApplyReport {
+    pub added: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    /// Bookmarks skipped because the target indexed them later
+    pub skipped_newer: usize,
+    /// Documents written
+    pub documents: usize,
+}
+
+impl Changeset {
+    /// Changes of the index at `index_dir` since `since`
+    ///
+    /// Bookmarks are selected by the index time recorded in
+    /// `index_metadata.json`; those without one (e.g. built by `index` from a
+    /// browser) are always included. Deletions have no time and are always
+    /// included too: applying them again is harmless.
+    pub fn diff(name: &str, index_dir: &Path, since: DateTime<Utc>) -> Result<Self> {
+        let index = Index::open_in_dir(index_dir)
+            .with_context(|| format!("Failed to open index at {index_dir:?}"))?;
+        let index_schema = index.schema();
+        let schema = BookmarkSchema::new();
+        let searcher = index.reader()?.searcher();
+        let metadata = SyncMetadata::load(index_dir)?;
+        let since_secs = u64::try_from(since.timestamp()).unwrap_or(0);
+
+        let mut bookmarks: BTreeMap<String, Vec<TantivyDocument>> = BTreeMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let store_reader = segment_reader.get_store_reader(1)?;
+            for doc_id in segment_reader.doc_ids_alive() {
+                let doc: TantivyDocument = store_reader.get(doc_id)?;
+                let Some(id) = doc.get_first(schema.id).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                bookmarks
+                    .entry(base_bookmark_id(id).to_string())
+                    .or_default()
+                    .push(doc);
+            }
+        }
+
+        // Recorded bookmarks without documents were deleted; when isn't known
+        let mut deleted: Vec<DeletedBookmark> = metadata
+            .bookmarks
+            .iter()
+            .filter(|(id, _)| !bookmarks.contains_key(id.as_str()))
+            .map(|(id, synced)| DeletedBookmark {
+                id: id.clone(),
+                url: synced.url.clone(),
+                indexed_at: synced.indexed_at,
+            })
+            .collect();
+        deleted.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut changeset = Self {
+            format: CHANGESET_FORMAT,
+            index: name.to_string(),
+            dictionary: index_dictionary(index_dir).ok(),
+            since,
+            created_at: Utc::now(),
+            added: Vec::new(),
+            updated: Vec::new(),
+            deleted,
+        };
+        for (id, mut documents) in bookmarks {
+            let synced = metadata.bookmarks.get(&id);
+            if synced.is_some_and(|synced| synced.indexed_at < since_secs) {
+                continue;
+            }
+            // Parts in order, so the first document is part 0
+            documents.sort_by_cached_key(|doc| {
+                let id = doc.get_first(schema.id).and_then(|v| v.as_str());
+                let part = id.and_then(|id| id.rsplit_once("_part_"));
+                part.and_then(|(_, n)| n.parse::<usize>().ok())
+            });
+            let first = &documents[0];
+            let url = first
+                .get_first(schema.url)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let date_added = first
+                .get_first(schema.date_added)
+                .and_then(|v| v.as_i64())
+                .and_then(timestamp_to_datetime);
+            let documents = documents
+                .iter()
+                .map(|doc| serde_json::from_str(&doc.to_json(&index_schema)))
+                .collect::<Result<_, _>>()?;
+            let bookmark = ChangedBookmark {
+                id,
+                url,
+                indexed_at: synced.map_or(0, |synced| synced.indexed_at),
+                synced: synced.cloned(),
+                documents,
+            };
+            if date_added.is_some_and(|added| added >= since) {
+                changeset.added.push(bookmark);
+            } else {
+                changeset.updated.push(bookmark);
+            }
+        }
+        Ok(changeset)
+    }
+
+    /// Read a changeset written by `index diff`
+    pub fn parse(content: &str) -> Result<Self> {
+        let changeset: Self = serde_json::from_str(content).context("Invalid changeset")?;
+        anyhow::ensure!(
+            changeset.format == CHANGESET_FORMAT,
+            "Changeset format {} is not supported (expected {CHANGESET_FORMAT})",
+            changeset.format
+        );
+        Ok(changeset)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+
+    /// Apply the changes to an index in one commit, keeping copies the target indexed later
+    ///
+    /// The caller holds the index lock. `index_metadata.json` takes the
+    /// source's records, so the changes aren't sent back by the target's next
+    /// diff unless they are re-indexed there.
+    pub fn apply(&self, manager: &SearchManager, index_dir: &Path) -> Result<ApplyReport> {
+        let mut metadata = SyncMetadata::load(index_dir)?;
+        let mut report = ApplyReport::default();
+        let newer_locally = |metadata: &SyncMetadata, id: &str, indexed_at: u64| {
+            metadata
+                .bookmarks
+                .get(id)
+                .is_some_and(|local| local.indexed_at > indexed_at)
+        };
+
+        let mut changes: Vec<(&str, &[serde_json::Value])> = Vec::new();
+        let changed = self
+            .added
+            .iter()
+            .map(|bookmark| (bookmark, true))
+            .chain(self.updated.iter().map(|bookmark| (bookmark, false)));
+        for (bookmark, added) in changed {
+            if newer_locally(&metadata, &bookmark.id, bookmark.indexed_at) {
+                report.skipped_newer += 1;
+                continue;
+            }
+            changes.push((&bookmark.id, &bookmark.documents));
+            match &bookmark.synced {
+                Some(synced) => metadata
+                    .bookmarks
+                    .insert(bookmark.id.clone(), synced.clone()),
+                None => metadata.bookmarks.remove(&bookmark.id),
+            };
+            if added {
+                report.added += 1;
+            } else {
+                report.updated += 1;
+            }
+        }
+        for bookmark in &self.deleted {
+            if newer_locally(&metadata, &bookmark.id, bookmark.indexed_at) {
+                report.skipped_newer += 1;
+                continue;
+            }
+            changes.push((&bookmark.id, &[]));
+            metadata.bookmarks.remove(&bookmark.id);
+            report.deleted += 1;
+        }
+
+        report.documents = manager.replace_stored(&changes)?;
+        metadata.save(index_dir)?;
+        Ok(report)
+    }
+}
+
+/// A document from its stored fields in tantivy's JSON form
+///
+/// The n-gram, heading, code, URL word and title prefix fields aren't stored,
+/// so they are rebuilt from the title, content, heading offsets and URL. Code from `<pre>` blocks only
+/// reaches the `code` field when it was fenced in the content.
+pub fn document_from_json(
+    value: &serde_json::Value,
+    index_schema: &Schema,
+    schema: &BookmarkSchema,
+) -> Result<TantivyDocument> {
+    let mut doc = TantivyDocument::parse_json(index_schema, &value.to_string())?;
+    let texts = |doc: &TantivyDocument, field| -> Vec<String> {
+        doc.get_all(field)
+            .filter_map(|v| v.as_str())
+            .map(str::to_string)
+            .collect()
+    };
+    let titles = texts(&doc, schema.title);
+    let contents = texts(&doc, schema.content);
+    let headings: Vec<Heading> = doc
+        .get_first(schema.heading_offsets)
+        .and_then(|v| v.as_bytes())
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+        .unwrap_or_default();
+
+    if index_schema.get_field("ngram").is_ok() {
+        for text in titles.iter().chain(&contents) {
+            doc.add_text(schema.ngram, text);
+        }
+    }
+    if index_schema.get_field("url_text").is_ok() {
+        for url in texts(&doc, schema.url) {
+            doc.add_text(schema.url_text, &url);
+        }
+    }
+    if index_schema.get_field("title_prefix").is_ok() {
+        for title in &titles {
+            doc.add_text(schema.title_prefix, title);
+        }
+    }
+    if index_schema.get_field("code").is_ok() {
+        for content in &contents {
+            for block in fenced_code_blocks(content) {
+                doc.add_text(schema.code, block);
+            }
+        }
+    }
+    if index_schema.get_field("headings").is_ok() {
+        for heading in headings
+            .iter()
+            .filter(|h| h.level <= Heading::MAX_INDEXED_LEVEL)
+        {
+            doc.add_text(schema.headings, &heading.text);
+        }
+    }
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmark::FlatBookmark;
+    use crate::search::indexer::PageStructure;
+    use crate::search::unified_searcher::SearchParams;
+    use tempfile::TempDir;
+
+    fn bookmark(id: &str, name: &str) -> FlatBookmark {
+        FlatBookmark {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: format!("https://example.com/{id}"),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec!["Notes".to_string()],
+            tags: Vec::new(),
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_and_apply() {
+        let source_dir = TempDir::new().unwrap();
+        let source = SearchManager::new_for_testing(source_dir.path()).unwrap();
+        let structure = PageStructure {
+            headings: vec![Heading {
+                level: 2,
+                text: "Installation".to_string(),
+                offset: 0,
+            }],
+            ..PageStructure::default()
+        };
+        let (kept, edited, removed) = (
+            bookmark("1", "Kept page"),
+            bookmark("2", "Edited page"),
+            bookmark("3", "Removed page"),
+        );
+        source
+            .replace_pages(&[
+                (&kept, "unchanged text", &structure),
+                (&edited, "Steps: ```cargo add tantivy```", &structure),
+            ])
+            .unwrap();
+        let mut metadata = SyncMetadata::default();
+        metadata.record(&kept, Some("unchanged text"));
+        metadata.record(&edited, Some("edited"));
+        metadata.record(&removed, Some("gone"));
+        metadata.bookmarks.get_mut("1").unwrap().indexed_at = 100;
+        metadata.save(source_dir.path()).unwrap();
+
+        let since = DateTime::from_timestamp(1000, 0).unwrap();
+        let changeset = Changeset::diff("work", source_dir.path(), since).unwrap();
+        assert!(changeset.added.is_empty());
+        let updated: Vec<&str> = changeset.updated.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(updated, ["2"]);
+        let deleted: Vec<&str> = changeset.deleted.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(deleted, ["3"]);
+        let changeset = Changeset::parse(&serde_json::to_string(&changeset).unwrap()).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let target = SearchManager::new_for_testing(target_dir.path()).unwrap();
+        target
+            .replace_pages(&[(&removed, "old copy", &PageStructure::default())])
+            .unwrap();
+        let report = changeset.apply(&target, target_dir.path()).unwrap();
+        assert_eq!(
+            (report.updated, report.deleted, report.documents),
+            (1, 1, 1)
+        );
+        assert_eq!(target.get_stats().unwrap().total_documents, 1);
+
+        // Unstored fields are rebuilt, so heading and code queries still match
+        for query in ["Installation", "code:tantivy"] {
+            let results = target
+                .search_with_filters(&SearchParams::new(query))
+                .unwrap();
+            assert_eq!(results.len(), 1, "{query}");
+            assert_eq!(results[0].id, "2");
+        }
+        let applied = SyncMetadata::load(target_dir.path()).unwrap();
+        assert!(applied.bookmarks.contains_key("2"));
+
+        // The target now has the same copy; a newer local one is kept
+        let mut newer = applied.clone();
+        newer.bookmarks.get_mut("2").unwrap().indexed_at = u64::MAX;
+        newer.save(target_dir.path()).unwrap();
+        let report = changeset.apply(&target, target_dir.path()).unwrap();
+        assert_eq!(report.skipped_newer, 1);
+        assert_eq!(report.documents, 0);
+    }
+}
@@ -0,0 +1,360 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::common::BookmarkEntry;
+use super::keywords::candidates;
+
+/// Only the start of long documents is used for clustering
+pub const MAX_CLUSTER_TEXT_CHARS: usize = 20_000;
+
+/// Terms kept per document vector (highest TF-IDF first)
+const MAX_DOCUMENT_TERMS: usize = 50;
+
+/// Terms kept per centroid, so centroids stay sparse
+const MAX_CENTROID_TERMS: usize = 200;
+
+/// Terms in a cluster's label
+const LABEL_TERMS: usize = 3;
+
+/// Upper bound on the number of clusters chosen automatically
+const MAX_AUTO_CLUSTERS: usize = 20;
+
+/// k-means rounds before giving up on convergence
+const MAX_ITERATIONS: usize = 20;
+
+/// Title terms count this many times in a document vector
+const TITLE_WEIGHT: usize = 3;
+
+/// Term ids and weights, unit length
+type SparseVector = Vec<(usize, f32)>;
+
+/// Centroid weights by term id, for fast dot products
+type Centroid = HashMap<usize, f32>;
+
+/// A bookmark in a cluster, with its similarity to the cluster's centroid
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMember {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub folder_path: String,
+    pub similarity: f32,
+}
+
+/// Group of similar bookmarks
+#[derive(Debug, Clone, Serialize)]
+pub struct Cluster {
+    /// Top terms joined with " / "
+    pub label: String,
+    /// Highest-weighted centroid terms
+    pub terms: Vec<String>,
+    pub size: usize,
+    /// Members closest to the centroid
+    pub representatives: Vec<ClusterMember>,
+}
+
+/// Clusters plus the bookmarks that had no usable terms
+#[derive(Debug, Clone, Serialize)]
+pub struct Clustering {
+    pub clusters: Vec<Cluster>,
+    /// Bookmarks without content or distinctive terms
+    pub unclustered: usize,
+}
+
+/// Cluster count picked when none is given: about sqrt(n / 2)
+pub fn default_cluster_count(documents: usize) -> usize {
+    ((documents as f32 / 2.0).sqrt().round() as usize).clamp(2, MAX_AUTO_CLUSTERS)
+}
+
+/// Cluster bookmarks by the TF-IDF vectors of their title and content
+///
+/// Runs spherical k-means (cosine similarity) from deterministic farthest-first
+/// seeds, so the same collection gives the same clusters. Terms found in a
+/// single document or in more than half of them are ignored. Each cluster is
+/// labeled with its top centroid terms and lists its `representatives` most
+/// central bookmarks; largest clusters come first.
+pub fn cluster_bookmarks(
+    documents: &[(BookmarkEntry, String)],
+    k: Option<usize>,
+    representatives: usize,
+) -> Clustering {
+    let (vectors, vocabulary) = tfidf_vectors(documents);
+    let usable: Vec<usize> = (0..vectors.len())
+        .filter(|&i| !vectors[i].is_empty())
+        .collect();
+    let unclustered = documents.len() - usable.len();
+    if usable.is_empty() {
+        return Clustering {
+            clusters: Vec::new(),
+            unclustered,
+        };
+    }
+
+    let k = k
+        .unwrap_or_else(|| default_cluster_count(usable.len()))
+        .clamp(1, usable.len());
+    let mut centroids = farthest_first_seeds(&vectors, &usable, k);
+    let mut assignment = vec![0; usable.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = usable
+            .iter()
+            .map(|&i| nearest(&vectors[i], &centroids).0)
+            .collect();
+        let converged = next == assignment;
+        assignment = next;
+        centroids = (0..k)
+            .map(|c| {
+                let members = usable
+                    .iter()
+                    .zip(&assignment)
+                    .filter(|(_, a)| **a == c)
+                    .map(|(&i, _)| &vectors[i]);
+                mean(members)
+            })
+            .collect();
+        if converged {
+            break;
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = centroids
+        .iter()
+        .enumerate()
+        .filter_map(|(c, centroid)| {
+            let mut members: Vec<ClusterMember> = usable
+                .iter()
+                .zip(&assignment)
+                .filter(|(_, a)| **a == c)
+                .map(|(&i, _)| {
+                    let entry = &documents[i].0;
+                    ClusterMember {
+                        id: entry.id.clone(),
+                        title: entry.title.clone(),
+                        url: entry.url.clone(),
+                        folder_path: entry.folder_path.clone(),
+                        similarity: dot(&vectors[i], centroid),
+                    }
+                })
+                .collect();
+            if members.is_empty() {
+                return None;
+            }
+            members.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+            let size = members.len();
+            members.truncate(representatives);
+
+            let mut weights: SparseVector = centroid.iter().map(|(t, w)| (*t, *w)).collect();
+            weights.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let terms: Vec<String> = weights
+                .iter()
+                .take(LABEL_TERMS)
+                .map(|(term, _)| vocabulary[*term].clone())
+                .collect();
+            Some(Cluster {
+                label: terms.join(" / "),
+                terms,
+                size,
+                representatives: members,
+            })
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.label.cmp(&b.label)));
+
+    Clustering {
+        clusters,
+        unclustered,
+    }
+}
+
+/// Unit-length TF-IDF vectors (sorted by weight, highest first) and the term list they index
+fn tfidf_vectors(documents: &[(BookmarkEntry, String)]) -> (Vec<SparseVector>, Vec<String>) {
+    let counts: Vec<HashMap<String, usize>> = documents
+        .iter()
+        .map(|(entry, text)| {
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            let scanned: String = text.chars().take(MAX_CLUSTER_TEXT_CHARS).collect();
+            for term in candidates(&scanned) {
+                *counts.entry(term).or_default() += 1;
+            }
+            for term in candidates(&entry.title) {
+                *counts.entry(term).or_default() += TITLE_WEIGHT;
+            }
+            counts
+        })
+        .collect();
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for doc in &counts {
+        for term in doc.keys() {
+            *document_frequency.entry(term.as_str()).or_default() += 1;
+        }
+    }
+
+    let total = documents.len() as f32;
+    let mut vocabulary: Vec<String> = Vec::new();
+    let mut term_ids: HashMap<&str, usize> = HashMap::new();
+    let mut vectors = Vec::new();
+    for doc in &counts {
+        let mut weights: Vec<(&str, f32)> = doc
+            .iter()
+            .filter_map(|(term, &count)| {
+                let df = document_frequency[term.as_str()];
+                if df < 2 || df as f32 > total / 2.0 {
+                    return None;
+                }
+                let weight = (1.0 + (count as f32).ln()) * (total / df as f32).ln();
+                Some((term.as_str(), weight))
+            })
+            .collect();
+        weights.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        weights.truncate(MAX_DOCUMENT_TERMS);
+
+        let vector = weights
+            .into_iter()
+            .map(|(term, weight)| {
+                let id = *term_ids.entry(term).or_insert_with(|| {
+                    vocabulary.push(term.to_string());
+                    vocabulary.len() - 1
+                });
+                (id, weight)
+            })
+            .collect();
+        vectors.push(normalize(vector));
+    }
+    (vectors, vocabulary)
+}
+
+/// First seed is the first usable document; each next seed is the one least
+/// similar to the seeds so far
+fn farthest_first_seeds(vectors: &[SparseVector], usable: &[usize], k: usize) -> Vec<Centroid> {
+    let mut seeds: Vec<Centroid> = vec![vectors[usable[0]].iter().copied().collect()];
+    while seeds.len() < k {
+        let farthest = usable
+            .iter()
+            .min_by(|&&a, &&b| {
+                let a = nearest(&vectors[a], &seeds).1;
+                let b = nearest(&vectors[b], &seeds).1;
+                a.total_cmp(&b)
+            })
+            .copied()
+            .unwrap_or(usable[0]);
+        seeds.push(vectors[farthest].iter().copied().collect());
+    }
+    seeds
+}
+
+/// Index of the most similar centroid and the similarity
+fn nearest(vector: &SparseVector, centroids: &[Centroid]) -> (usize, f32) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(c, centroid)| (c, dot(vector, centroid)))
+        .fold((0, f32::MIN), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+}
+
+fn dot(vector: &SparseVector, centroid: &Centroid) -> f32 {
+    vector
+        .iter()
+        .filter_map(|(term, weight)| centroid.get(term).map(|other| weight * other))
+        .sum()
+}
+
+/// Normalized mean of vectors, trimmed to its heaviest terms
+fn mean<'a>(vectors: impl Iterator<Item = &'a SparseVector>) -> Centroid {
+    let mut sum: HashMap<usize, f32> = HashMap::new();
+    for vector in vectors {
+        for (term, weight) in vector {
+            *sum.entry(*term).or_default() += weight;
+        }
+    }
+    let mut terms: SparseVector = sum.into_iter().collect();
+    terms.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    terms.truncate(MAX_CENTROID_TERMS);
+    normalize(terms).into_iter().collect()
+}
+
+fn normalize(mut vector: SparseVector) -> SparseVector {
+    let norm = vector.iter().map(|(_, w)| w * w).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for (_, weight) in &mut vector {
+            *weight /= norm;
+        }
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: &str, title: &str, content: &str) -> (BookmarkEntry, String) {
+        let entry = BookmarkEntry {
+            id: id.to_string(),
+            url: format!("https://example.com/{id}"),
+            title: title.to_string(),
+            folder_path: "Unsorted".to_string(),
+            content_hash: String::new(),
+            simhash: 0,
+            date_added: 0,
+            date_modified: 0,
+            tags: vec![],
+        };
+        (entry, content.to_string())
+    }
+
+    #[test]
+    fn test_cluster_bookmarks() {
+        let documents = vec![
+            document(
+                "1",
+                "Rust ownership",
+                "rust borrow checker ownership lifetimes compiler",
+            ),
+            document(
+                "2",
+                "Rust traits",
+                "rust traits generics compiler lifetimes",
+            ),
+            document("3", "Rust async", "rust async tokio compiler borrow"),
+            document(
+                "4",
+                "Sourdough bread",
+                "flour water yeast oven bread baking",
+            ),
+            document("5", "Pizza dough", "flour yeast oven dough baking"),
+            document("6", "Focaccia", "flour olive oven bread baking yeast"),
+            document("7", "Empty", ""),
+        ];
+
+        let clustering = cluster_bookmarks(&documents, Some(2), 2);
+        assert_eq!(clustering.unclustered, 1);
+        assert_eq!(clustering.clusters.len(), 2);
+        for cluster in &clustering.clusters {
+            assert_eq!(cluster.size, 3);
+            assert_eq!(cluster.representatives.len(), 2);
+            let ids: Vec<u32> = cluster
+                .representatives
+                .iter()
+                .map(|m| m.id.parse().unwrap())
+                .collect();
+            assert!(ids.iter().all(|&id| id <= 3) || ids.iter().all(|&id| id >= 4));
+        }
+        let labels: Vec<&str> = clustering
+            .clusters
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect();
+        assert!(labels.iter().any(|l| l.contains("compiler")));
+        assert!(labels.iter().any(|l| l.contains("flour")));
+
+        assert_eq!(default_cluster_count(4), 2);
+        assert_eq!(default_cluster_count(200), 10);
+        assert!(cluster_bookmarks(&[], None, 3).clusters.is_empty());
+    }
+}
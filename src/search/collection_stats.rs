@@ -0,0 +1,196 @@
+use anyhow::Result;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tantivy::{Searcher, TantivyDocument, schema::Value};
+
+use super::common::timestamp_to_datetime;
+use super::schema::BookmarkSchema;
+
+/// Content size buckets: (label, exclusive upper bound in bytes)
+const SIZE_BUCKETS: &[(&str, usize)] = &[
+    ("<1KB", 1_000),
+    ("1KB-10KB", 10_000),
+    ("10KB-100KB", 100_000),
+    ("100KB-1MB", 1_000_000),
+    (">=1MB", usize::MAX),
+];
+
+/// Number of bookmarks whose content falls in a size range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeBucket {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Aggregate statistics over the bookmarks in an index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionStats {
+    pub total_bookmarks: usize,
+    pub total_documents: usize,
+    pub by_domain: BTreeMap<String, usize>,
+    pub by_folder: BTreeMap<String, usize>,
+    pub by_content_type: BTreeMap<String, usize>,
+    pub by_size: Vec<SizeBucket>,
+    /// Bookmarks added per month ("YYYY-MM", or "unknown" without a date)
+    pub added_per_month: BTreeMap<String, usize>,
+}
+
+impl Default for CollectionStats {
+    fn default() -> Self {
+        Self {
+            total_bookmarks: 0,
+            total_documents: 0,
+            by_domain: BTreeMap::new(),
+            by_folder: BTreeMap::new(),
+            by_content_type: BTreeMap::new(),
+            by_size: SIZE_BUCKETS
+                .iter()
+                .map(|(label, _)| SizeBucket {
+                    label: label.to_string(),
+                    count: 0,
+                })
+                .collect(),
+            added_per_month: BTreeMap::new(),
+        }
+    }
+}
+
+impl CollectionStats {
+    /// Compute statistics from all documents in an index
+    ///
+    /// Split PDFs count as one bookmark; their parts' content is summed for the size buckets.
+    pub fn compute(searcher: &Searcher, schema: &BookmarkSchema) -> Result<Self> {
+        let mut stats = Self::default();
+        let mut content_sizes: HashMap<String, usize> = HashMap::new();
+
+        for segment_reader in searcher.segment_readers() {
+            let store_reader = segment_reader.get_store_reader(1)?;
+            let date_added = segment_reader.fast_fields().i64("date_added")?;
+
+            for doc_id in segment_reader.doc_ids_alive() {
+                let Ok(doc) = store_reader.get::<TantivyDocument>(doc_id) else {
+                    continue;
+                };
+                let get = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or("");
+
+                stats.total_documents += 1;
+
+                let id = get(schema.id);
+                let base_id = id.split("_part_").next().unwrap_or(id);
+                *content_sizes.entry(base_id.to_string()).or_default() += get(schema.content).len();
+
+                // Per-bookmark attributes are taken from the first part only
+                if base_id != id {
+                    continue;
+                }
+
+                stats.total_bookmarks += 1;
+                *stats
+                    .by_domain
+                    .entry(label_or_unknown(get(schema.domain)))
+                    .or_default() += 1;
+                *stats
+                    .by_folder
+                    .entry(label_or_unknown(get(schema.folder_path)))
+                    .or_default() += 1;
+                *stats
+                    .by_content_type
+                    .entry(label_or_unknown(get(schema.content_type)))
+                    .or_default() += 1;
+
+                let month = date_added
+                    .first(doc_id)
+                    .and_then(month_of)
+                    .unwrap_or_else(|| "unknown".to_string());
+                *stats.added_per_month.entry(month).or_default() += 1;
+            }
+        }
+
+        for size in content_sizes.values() {
+            let bucket = SIZE_BUCKETS
+                .iter()
+                .position(|(_, upper)| size < upper)
+                .unwrap_or(SIZE_BUCKETS.len() - 1);
+            stats.by_size[bucket].count += 1;
+        }
+
+        Ok(stats)
+    }
+
+    /// Add another index's statistics into this one
+    pub fn merge(&mut self, other: CollectionStats) {
+        self.total_bookmarks += other.total_bookmarks;
+        self.total_documents += other.total_documents;
+        for (map, other_map) in [
+            (&mut self.by_domain, other.by_domain),
+            (&mut self.by_folder, other.by_folder),
+            (&mut self.by_content_type, other.by_content_type),
+            (&mut self.added_per_month, other.added_per_month),
+        ] {
+            for (key, count) in other_map {
+                *map.entry(key).or_default() += count;
+            }
+        }
+        for (bucket, other_bucket) in self.by_size.iter_mut().zip(other.by_size) {
+            bucket.count += other_bucket.count;
+        }
+    }
+}
+
+fn label_or_unknown(value: &str) -> String {
+    if value.is_empty() {
+        "unknown".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Convert a stored date_added value to "YYYY-MM"
+fn month_of(timestamp: i64) -> Option<String> {
+    let datetime = timestamp_to_datetime(timestamp)?;
+    Some(format!("{:04}-{:02}", datetime.year(), datetime.month()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_of() {
+        // Chrome bookmark file timestamp
+        assert_eq!(
+            month_of(13_350_000_000_000_000),
+            Some("2024-01".to_string())
+        );
+        // Unix milliseconds (chrome.bookmarks API)
+        assert_eq!(month_of(1_234_567_890_000), Some("2009-02".to_string()));
+        // Unix seconds
+        assert_eq!(month_of(1_234_567_890), Some("2009-02".to_string()));
+        assert_eq!(month_of(0), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = CollectionStats {
+            total_bookmarks: 1,
+            ..Default::default()
+        };
+        a.by_domain.insert("example.com".to_string(), 1);
+        a.by_size[0].count = 1;
+
+        let mut b = CollectionStats {
+            total_bookmarks: 2,
+            ..Default::default()
+        };
+        b.by_domain.insert("example.com".to_string(), 2);
+        b.by_domain.insert("rust-lang.org".to_string(), 1);
+        b.by_size[0].count = 2;
+
+        a.merge(b);
+        assert_eq!(a.total_bookmarks, 3);
+        assert_eq!(a.by_domain["example.com"], 3);
+        assert_eq!(a.by_domain["rust-lang.org"], 1);
+        assert_eq!(a.by_size[0].count, 3);
+    }
+}
@@ -1,11 +1,14 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
-use tantivy::{TantivyDocument, schema::Value};
+use tantivy::{Searcher, SnippetGenerator, TantivyDocument, schema::Value};
 
+use super::indexer::Heading;
+use super::query_parser::QueryParser as CustomQueryParser;
 use super::schema::BookmarkSchema;
-use super::scored_snippet::ScoredSnippetGenerator;
-use super::unified_searcher::SearchResult;
+use super::scored_snippet::SnippetOptions;
+use super::unified_searcher::{SearchResult, SnippetRange};
 
 // ============================================================================
 // Constants (previously in constants.rs)
@@ -75,6 +78,14 @@ impl IndexingStatus {
         }
     }
 
+    /// Reset the counters before a rebuild
+    pub fn restart(&self) {
+        use std::sync::atomic::Ordering;
+        self.completed.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+        self.is_complete.store(false, Ordering::Relaxed);
+    }
+
     /// Get progress percentage (0.0 - 100.0)
     pub fn progress(&self) -> f32 {
         100.0 // Always 100% for read-only index
@@ -93,13 +104,38 @@ impl IndexingStatus {
     }
 }
 
+/// Count unique bookmarks by extracting base IDs from all documents
+/// Documents with IDs like "123_part_0", "123_part_1" are counted as one bookmark "123"
+pub fn count_unique_bookmarks(searcher: &Searcher, schema: &BookmarkSchema) -> Result<usize> {
+    let mut base_ids: HashSet<String> = HashSet::new();
+
+    // Iterate through all segments and documents directly (no limit)
+    for segment_reader in searcher.segment_readers() {
+        let store_reader = segment_reader.get_store_reader(1)?;
+
+        for doc_id in 0..segment_reader.num_docs() {
+            if let Ok(doc) = store_reader.get::<TantivyDocument>(doc_id) {
+                if let Some(id_value) = doc.get_first(schema.id) {
+                    if let Some(id_str) = id_value.as_str() {
+                        base_ids.insert(base_bookmark_id(id_str).to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(base_ids.len())
+}
+
 /// Common document to search result conversion
 pub fn doc_to_result(
     doc: &TantivyDocument,
     schema: &BookmarkSchema,
     score: f32,
     query: &str,
-    snippet_generator: &ScoredSnippetGenerator,
+    snippet_options: &SnippetOptions,
+    content_snippets: Option<&SnippetGenerator>,
+    include_content: bool,
 ) -> Result<SearchResult> {
     let id = doc
         .get_first(schema.id)
@@ -119,11 +155,12 @@ pub fn doc_to_result(
         .unwrap_or("")
         .to_string();
 
+    // Snippets are cut from the stored field in place; the body is only
+    // copied into the result when the caller asks for it
     let content = doc
         .get_first(schema.content)
         .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
+        .unwrap_or("");
 
     let folder_path = doc
         .get_first(schema.folder_path)
@@ -131,26 +168,121 @@ pub fn doc_to_result(
         .unwrap_or("")
         .to_string();
 
-    // Generate snippet with context detection
-    // Use config's max_snippet_length (default: 600)
-    let config = crate::config::Config::default();
-    let scored_snippet =
-        snippet_generator.generate_snippet(&content, query, config.max_snippet_length);
-
-    // Extract page number from snippet (for PDF content)
-    let page_number = extract_page_number_from_snippet(&scored_snippet.text, &content);
+    // Prefer the fragment tantivy picked from the query's terms; fall back to
+    // the sliding-window scan when it found none in the content or when
+    // several snippets or a specific context window were asked for
+    let snippet_generator = snippet_options.generator();
+    let native = content_snippets
+        .filter(|_| snippet_options.is_single_fragment())
+        .map(|generator| generator.snippet_from_doc(doc))
+        .filter(|snippet| !snippet.is_empty())
+        .map(|snippet| {
+            snippet_generator.snippet_from_fragment(
+                content,
+                snippet.fragment(),
+                snippet.highlighted().len(),
+            )
+        });
+    let mut snippets = match native {
+        Some(snippet) => vec![snippet],
+        None => snippet_generator.generate_snippets(
+            content,
+            query,
+            snippet_options.count,
+            snippet_options.length,
+        ),
+    };
+    let best = snippets.remove(0);
+
+    // Page the snippet starts on (for PDF content), from its markers or else the stored offsets
+    let snippet_start = content[..best.start].chars().count();
+    let page_number = page_number_at(content, best.start, best.end).or_else(|| {
+        let offsets: Vec<usize> = doc
+            .get_first(schema.page_offsets)
+            .and_then(|v| v.as_bytes())
+            .and_then(|bytes| serde_json::from_slice(bytes).ok())?;
+        // Later parts indexed before first_page was stored can't be placed
+        let first_page = doc
+            .get_first(schema.first_page)
+            .and_then(|v| v.as_u64())
+            .map(|page| page as usize)
+            .or((!is_part_id(&id)).then_some(1))?;
+        page_at_offset(&offsets, first_page, snippet_start)
+    });
+    let snippet_range = (best.end > best.start).then(|| SnippetRange {
+        start: snippet_start,
+        end: content[..best.end].chars().count(),
+    });
+
+    // Headings sent by the extension beat guessing from the flattened text
+    let headings: Vec<Heading> = doc
+        .get_first(schema.heading_offsets)
+        .and_then(|v| v.as_bytes())
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+        .unwrap_or_default();
+    let section = if headings.is_empty() {
+        best.section
+    } else {
+        Heading::enclosing(&headings, snippet_start).map(|h| h.text.clone())
+    };
+    let query_terms = CustomQueryParser::parse(query);
+    let mut terms = CustomQueryParser::extract_words(&query_terms);
+    terms.extend(CustomQueryParser::extract_phrases(&query_terms));
+    let matched_heading = Heading::matching(&headings, &terms).map(|h| h.text.clone());
+
+    let stored_date = |field| {
+        doc.get_first(field)
+            .and_then(|v| v.as_i64())
+            .and_then(timestamp_to_datetime)
+            .map(|date| date.to_rfc3339())
+    };
+
+    let summary = doc
+        .get_first(schema.summary)
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
 
     Ok(SearchResult {
         id,
         title,
+        domain: extract_domain(&url),
         url,
-        snippet: scored_snippet.text,
-        full_content: None, // Don't include full content in search results
+        summary,
+        snippet: best.text,
+        snippet_range,
+        section,
+        matched_heading,
+        more_snippets: snippets.into_iter().map(|s| s.text).collect(),
+        full_content: include_content.then(|| content.to_string()),
+        has_full_content: !content.is_empty(),
         score,
         folder_path,
         last_indexed: None,
-        context_type: Some(format!("{:?}", scored_snippet.context_type)),
+        date_added: stored_date(schema.date_added),
+        date_modified: stored_date(schema.date_modified),
+        context_type: Some(format!("{:?}", best.context_type)),
         page_number,
+        ocr: doc
+            .get_first(schema.ocr)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        source: doc
+            .get_first(schema.source)
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        archived_at: doc
+            .get_first(schema.archived_at)
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        truncated_from: doc
+            .get_first(schema.truncated_from)
+            .and_then(|v| v.as_u64())
+            .map(|chars| chars as usize),
+        part: doc
+            .get_first(schema.part_map)
+            .and_then(|v| v.as_bytes())
+            .and_then(|bytes| serde_json::from_slice(bytes).ok()),
+        part_hits: Vec::new(),
     })
 }
 
@@ -166,6 +298,162 @@ pub fn parse_date(date: &Option<String>) -> Option<i64> {
     date.as_ref()?.parse::<i64>().ok()
 }
 
+/// Convert a stored date value to a UTC datetime
+///
+/// Accepts Chrome's bookmark file format (microseconds since 1601-01-01) as
+/// well as Unix timestamps in milliseconds or seconds.
+pub fn timestamp_to_datetime(timestamp: i64) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::DateTime;
+
+    /// Microseconds between 1601-01-01 and 1970-01-01
+    const WEBKIT_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+    if timestamp <= 0 {
+        return None;
+    }
+
+    if timestamp > 10_000_000_000_000_000 {
+        DateTime::from_timestamp_micros(timestamp - WEBKIT_EPOCH_OFFSET_MICROS)
+    } else if timestamp > 100_000_000_000 {
+        DateTime::from_timestamp_millis(timestamp)
+    } else {
+        DateTime::from_timestamp(timestamp, 0)
+    }
+}
+
+/// Page of a PDF document the byte range `start..end` begins on
+///
+/// That's the last `[PAGE:n]` marker at or before `start`, or the first one
+/// inside the range when it precedes every marker.
+pub fn page_number_at(content: &str, start: usize, end: usize) -> Option<usize> {
+    if !content.contains("[PAGE:") {
+        return None;
+    }
+    let page_marker_re = regex::Regex::new(r"\[PAGE:(\d+)\]").ok()?;
+    let mut page = None;
+    for cap in page_marker_re.captures_iter(content) {
+        let marker_start = cap.get(0)?.start();
+        if marker_start > start {
+            if page.is_none() && marker_start < end {
+                page = cap[1].parse().ok();
+            }
+            break;
+        }
+        page = cap[1].parse().ok();
+    }
+    page
+}
+
+/// Page containing character `offset` of a document whose pages start at `page_offsets`
+///
+/// `first_page` is the number of the page at `page_offsets[0]`, above 1 for
+/// later parts of a split PDF.
+pub fn page_at_offset(page_offsets: &[usize], first_page: usize, offset: usize) -> Option<usize> {
+    if page_offsets.is_empty() {
+        return None;
+    }
+    let pages_started = page_offsets.partition_point(|&start| start <= offset);
+    Some(first_page + pages_started.saturating_sub(1))
+}
+
+/// Slice of a document's stored content, with offsets in characters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContentWindow {
+    pub start: usize,
+    pub end: usize,
+    pub total_chars: usize,
+    /// PDF page the window starts on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_number: Option<usize>,
+    pub text: String,
+}
+
+impl ContentWindow {
+    /// Characters in the window, the length to page by
+    pub fn char_len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// Up to `before` characters before and `after` characters after character offset `center`
+pub fn content_window(content: &str, center: usize, before: usize, after: usize) -> ContentWindow {
+    let total_chars = content.chars().count();
+    let center = center.min(total_chars);
+    let start = center.saturating_sub(before);
+    let end = center.saturating_add(after).min(total_chars);
+
+    let byte_at = |chars: usize| {
+        content
+            .char_indices()
+            .nth(chars)
+            .map_or(content.len(), |(i, _)| i)
+    };
+    let (start_byte, end_byte) = (byte_at(start), byte_at(end));
+
+    ContentWindow {
+        start,
+        end,
+        total_chars,
+        page_number: page_number_at(content, start_byte, end_byte),
+        text: content[start_byte..end_byte].to_string(),
+    }
+}
+
+/// Character offset where the text of PDF page `page` starts, just after its marker
+pub fn page_start_offset(content: &str, page: usize) -> Option<usize> {
+    let marker = format!("[PAGE:{page}]");
+    let end = content.find(&marker)? + marker.len();
+    Some(content[..end].chars().count())
+}
+
+/// A page range request that doesn't fit the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRangeError {
+    pub start_page: usize,
+    pub end_page: usize,
+    /// Pages in the document, 0 when it has no page markers
+    pub page_count: usize,
+    pub content_type: String,
+    /// Nearest range that can be read, `None` when the document has no pages
+    pub closest: Option<(usize, usize)>,
+}
+
+impl PageRangeError {
+    /// Error for `start_page..=end_page` of a document with pages 1..=`page_count`
+    pub fn new(start_page: usize, end_page: usize, page_count: usize, content_type: &str) -> Self {
+        let closest = (page_count > 0).then(|| {
+            let clamp = |page: usize| page.clamp(1, page_count);
+            (clamp(start_page), clamp(end_page.max(start_page)))
+        });
+        Self {
+            start_page,
+            end_page,
+            page_count,
+            content_type: content_type.to_string(),
+            closest,
+        }
+    }
+}
+
+impl std::fmt::Display for PageRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.closest {
+            Some((start, end)) => write!(
+                f,
+                "Requested pages {}-{} are outside the document's {} pages; the closest valid range is {start}-{end}",
+                self.start_page, self.end_page, self.page_count
+            ),
+            None => write!(
+                f,
+                "No page markers found. This {} bookmark may not be a PDF or was indexed before page support was added.",
+                self.content_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PageRangeError {}
+
 /// Extract page number from snippet by finding the closest [PAGE:n] marker
 /// in the full content before the snippet position
 pub fn extract_page_number_from_snippet(snippet: &str, full_content: &str) -> Option<usize> {
@@ -230,6 +518,113 @@ pub struct IndexStats {
     pub index_size_bytes: u64,
 }
 
+/// Lightweight listing entry for a bookmark stored in the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkEntry {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub folder_path: String,
+    /// Fingerprint of the stored content (empty when there is no content)
+    #[serde(default)]
+    pub content_hash: String,
+    /// SimHash of the content stored at index time (0 when missing), see `duplicates::simhash`
+    #[serde(default)]
+    pub simhash: u64,
+    /// Stored date_added value (0 when unknown); see `timestamp_to_datetime`
+    #[serde(default)]
+    pub date_added: i64,
+    #[serde(default)]
+    pub date_modified: i64,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl BookmarkEntry {
+    /// Build an entry from a stored document
+    pub fn from_doc(doc: &TantivyDocument, schema: &BookmarkSchema) -> Self {
+        let get = |field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let get_i64 = |field| doc.get_first(field).and_then(|v| v.as_i64()).unwrap_or(0);
+
+        Self {
+            id: get(schema.id),
+            url: get(schema.url),
+            title: get(schema.title),
+            folder_path: get(schema.folder_path),
+            content_hash: content_hash(&get(schema.content)),
+            simhash: doc
+                .get_first(schema.simhash)
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+            date_added: get_i64(schema.date_added),
+            date_modified: get_i64(schema.date_modified),
+            tags: doc
+                .get_all(schema.tags)
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// Fingerprint content for duplicate detection (empty content hashes to "")
+pub fn content_hash(content: &str) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let content = content.trim();
+    if content.is_empty() {
+        return String::new();
+    }
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Gzip-compress text for storage in a bytes field
+pub fn compress_text(text: &str) -> Result<Vec<u8>> {
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+/// Inverse of [`compress_text`]
+pub fn decompress_text(bytes: &[u8]) -> Result<String> {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    let mut text = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Check whether a document ID belongs to a later part of a split PDF
+pub fn is_part_id(id: &str) -> bool {
+    id.contains("_part_")
+}
+
+/// Bookmark ID for a page known only by its URL
+///
+/// Underscores are percent-encoded, which leaves an equivalent URL, so the
+/// ID of a page like `.../tutorial_part_2` can't be taken for a part of
+/// `.../tutorial` by [`is_part_id`] or deleted along with it.
+pub fn url_bookmark_id(url: &str) -> String {
+    url.replace('_', "%5F")
+}
+
+/// Bookmark ID of a document, without the `_part_N` suffix of a split PDF
+pub fn base_bookmark_id(id: &str) -> &str {
+    id.find("_part_").map_or(id, |pos| &id[..pos])
+}
+
 /// Common search configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommonSearchConfig {
@@ -256,6 +651,88 @@ impl Default for CommonSearchConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_url_bookmark_id() {
+        let id = url_bookmark_id("https://blog.example.com/rust_tutorial_part_2");
+        assert_eq!(id, "https://blog.example.com/rust%5Ftutorial%5Fpart%5F2");
+        assert!(!is_part_id(&id));
+        assert_eq!(base_bookmark_id(&id), id);
+        assert_eq!(
+            url_bookmark_id("https://example.com/guide"),
+            "https://example.com/guide"
+        );
+    }
+
+    #[test]
+    fn test_page_number_at() {
+        let content = "[PAGE:1]First page content.[PAGE:2]Second page content.[PAGE:3]Third.";
+        let second = content.find("Second").unwrap();
+        assert_eq!(page_number_at(content, second, second + 6), Some(2));
+        // A range spanning pages starts on the earlier one
+        let first = content.find("page content").unwrap();
+        assert_eq!(page_number_at(content, first, second), Some(1));
+        assert_eq!(page_number_at(content, 0, 5), Some(1));
+        // Before every marker, only a marker inside the range counts
+        assert_eq!(page_number_at("Intro [PAGE:4]text", 0, 3), None);
+        assert_eq!(page_number_at("No markers here", 0, 5), None);
+    }
+
+    #[test]
+    fn test_content_window() {
+        let content = "[PAGE:1]最初のページ。[PAGE:2]二番目のページ。";
+        let page2 = page_start_offset(content, 2).unwrap();
+        assert_eq!(page2, 23);
+        assert_eq!(page_start_offset(content, 3), None);
+
+        let window = content_window(content, page2, 0, 3);
+        assert_eq!(window.text, "二番目");
+        assert_eq!((window.start, window.end), (23, 26));
+        assert_eq!(window.char_len(), 3);
+        assert_eq!(window.text.len(), 9);
+        assert_eq!(window.total_chars, 31);
+        assert_eq!(window.page_number, Some(2));
+
+        // The window is clamped to the document
+        let window = content_window(content, 100, 4, 10);
+        assert_eq!(window.text, "ページ。");
+        assert_eq!((window.start, window.end), (27, 31));
+        let window = content_window(content, 2, 5, 6);
+        assert_eq!(window.text, "[PAGE:1]");
+        assert_eq!(window.page_number, Some(1));
+    }
+
+    #[test]
+    fn test_page_at_offset() {
+        let offsets = [0, 100, 250];
+        assert_eq!(page_at_offset(&offsets, 1, 0), Some(1));
+        assert_eq!(page_at_offset(&offsets, 1, 99), Some(1));
+        assert_eq!(page_at_offset(&offsets, 1, 100), Some(2));
+        assert_eq!(page_at_offset(&offsets, 41, 300), Some(43));
+        assert_eq!(page_at_offset(&[], 1, 10), None);
+    }
+
+    #[test]
+    fn test_page_range_error() {
+        let error = PageRangeError::new(8, 12, 10, "pdf");
+        assert_eq!(error.closest, Some((8, 10)));
+        assert!(error.to_string().contains("closest valid range is 8-10"));
+        assert_eq!(PageRangeError::new(0, 3, 10, "pdf").closest, Some((1, 3)));
+        assert_eq!(
+            PageRangeError::new(15, 20, 10, "pdf").closest,
+            Some((10, 10))
+        );
+        assert_eq!(PageRangeError::new(1, 2, 0, "html").closest, None);
+    }
+
+    #[test]
+    fn test_compress_text() {
+        let html = "<html><body><p>日本語のページ</p></body></html>".repeat(20);
+        let compressed = compress_text(&html).unwrap();
+        assert!(compressed.len() < html.len());
+        assert_eq!(decompress_text(&compressed).unwrap(), html);
+        assert!(decompress_text(b"not gzip").is_err());
+    }
+
     #[test]
     fn test_indexing_status_readonly() {
         let status = IndexingStatus::for_readonly(100, 10);
@@ -0,0 +1,320 @@
+use serde::Serialize;
+use std::collections::HashSet;
+
+use super::unified_searcher::{SearchResult, SnippetRange};
+
+/// Search results a context is built from
+pub const CONTEXT_SEARCH_RESULTS: usize = 10;
+
+/// Snippets requested per search result
+pub const CONTEXT_SNIPPETS_PER_RESULT: usize = 3;
+
+/// Length of each requested snippet
+pub const CONTEXT_PASSAGE_CHARS: usize = 500;
+
+/// Vector share when pages have embeddings (see `SearchParams::with_hybrid_weight`)
+pub const CONTEXT_HYBRID_WEIGHT: f32 = 0.5;
+
+/// Default size of an assembled context
+pub const DEFAULT_CONTEXT_MAX_CHARS: usize = 6000;
+
+/// Each passage already taken from a document scales the next one's score by this
+const DIVERSITY_DECAY: f32 = 0.5;
+
+/// Share of a passage's trigrams found in an already selected passage that makes it a duplicate
+const OVERLAP_THRESHOLD: f32 = 0.7;
+
+/// Characters that mean something to the query parser but not in a question
+const QUERY_SYNTAX_CHARS: &[char] = &[
+    '?', ':', '(', ')', '[', ']', '{', '}', '^', '~', '*', '!', '"', '\'', '\\', '/', '？', '！',
+];
+
+/// A candidate passage for a context
+#[derive(Debug, Clone)]
+pub struct Passage {
+    pub url: String,
+    pub title: String,
+    pub text: String,
+    pub score: f32,
+    pub range: Option<SnippetRange>,
+    pub page_number: Option<usize>,
+}
+
+/// A document cited in an assembled context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContextSource {
+    /// Number the context's `[n]` headers refer to
+    pub id: usize,
+    pub title: String,
+    pub url: String,
+    /// Passages taken from this document
+    pub passages: usize,
+}
+
+/// Context block assembled from passages, with the documents it cites
+#[derive(Debug, Clone, Serialize)]
+pub struct BuiltContext {
+    pub context: String,
+    pub sources: Vec<ContextSource>,
+    /// Characters in `context`
+    pub total_chars: usize,
+    /// More passages were available than fit in the budget
+    pub truncated: bool,
+}
+
+/// Strip query syntax from a natural-language question so every word is searched
+pub fn question_query(question: &str) -> String {
+    question
+        .split(|c: char| c.is_whitespace() || QUERY_SYNTAX_CHARS.contains(&c))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Passages from search results: each result's snippets, later snippets scored a little lower
+pub fn passages_from_results(results: &[SearchResult]) -> Vec<Passage> {
+    let mut passages = Vec::new();
+    for result in results {
+        let snippets = std::iter::once(&result.snippet).chain(&result.more_snippets);
+        for (i, snippet) in snippets.enumerate() {
+            let text = snippet.trim().trim_end_matches("...").trim();
+            if text.is_empty() {
+                continue;
+            }
+            passages.push(Passage {
+                url: result.url.clone(),
+                title: result.title.clone(),
+                text: text.to_string(),
+                score: result.score / (1.0 + i as f32 * 0.1),
+                range: if i == 0 { result.snippet_range } else { None },
+                page_number: result.page_number,
+            });
+        }
+    }
+    passages
+}
+
+/// Assemble the best passages into one source-attributed block of at most `max_chars`
+///
+/// Passages are picked greedily by score, with each document's score decaying
+/// for every passage already taken from it so the context spans several
+/// documents. Passages overlapping a selected one (same range or mostly the
+/// same text) are skipped. Passages from one document are grouped under a
+/// numbered header, in document order.
+pub fn build_context(mut passages: Vec<Passage>, max_chars: usize) -> BuiltContext {
+    let mut selected: Vec<Passage> = Vec::new();
+    let mut selected_trigrams: Vec<HashSet<String>> = Vec::new();
+    let mut used = 0;
+    let mut truncated = false;
+
+    while !passages.is_empty() {
+        let effective = |p: &Passage| {
+            let taken = selected.iter().filter(|s| s.url == p.url).count();
+            p.score * DIVERSITY_DECAY.powi(taken as i32)
+        };
+        let best = (0..passages.len())
+            .max_by(|&a, &b| effective(&passages[a]).total_cmp(&effective(&passages[b])))
+            .unwrap_or(0);
+        let mut passage = passages.swap_remove(best);
+
+        let overlaps_range = selected.iter().any(|s| {
+            s.url == passage.url
+                && matches!((s.range, passage.range), (Some(a), Some(b)) if a.start < b.end && b.start < a.end)
+        });
+        let passage_trigrams = trigrams(&passage.text);
+        let repeats_text = selected_trigrams
+            .iter()
+            .any(|seen| containment(&passage_trigrams, seen) >= OVERLAP_THRESHOLD);
+        if overlaps_range || repeats_text {
+            continue;
+        }
+
+        // Characters added around the text: a blank line between blocks, plus a
+        // header line for a document's first passage
+        let sources = count_sources(&selected);
+        let overhead = if selected.iter().any(|s| s.url == passage.url) {
+            2
+        } else {
+            let separator = if selected.is_empty() { 0 } else { 2 };
+            header(sources + 1, &passage).chars().count() + 1 + separator
+        };
+        let length = passage.text.chars().count();
+        if used + overhead + length > max_chars {
+            truncated = true;
+            // A first passage too long for the budget is cut rather than dropped
+            let room = max_chars.saturating_sub(overhead);
+            if !selected.is_empty() || room <= 3 {
+                continue;
+            }
+            let cut: String = passage.text.chars().take(room - 3).collect();
+            passage.text = format!("{cut}...");
+        }
+        used += overhead + passage.text.chars().count();
+        selected_trigrams.push(passage_trigrams);
+        selected.push(passage);
+    }
+
+    assemble(selected, truncated)
+}
+
+/// Render selected passages grouped by document, documents numbered by best passage
+fn assemble(selected: Vec<Passage>, truncated: bool) -> BuiltContext {
+    let mut sources: Vec<(Passage, Vec<Passage>)> = Vec::new();
+    for passage in selected {
+        match sources
+            .iter_mut()
+            .find(|(first, _)| first.url == passage.url)
+        {
+            Some((_, passages)) => passages.push(passage),
+            None => sources.push((passage.clone(), vec![passage])),
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut cited = Vec::new();
+    for (i, (first, mut passages)) in sources.into_iter().enumerate() {
+        passages.sort_by_key(|p| p.range.map_or(usize::MAX, |r| r.start));
+        let texts: Vec<&str> = passages.iter().map(|p| p.text.as_str()).collect();
+        blocks.push(format!("{}\n{}", header(i + 1, &first), texts.join("\n\n")));
+        cited.push(ContextSource {
+            id: i + 1,
+            title: first.title,
+            url: first.url,
+            passages: passages.len(),
+        });
+    }
+
+    let context = blocks.join("\n\n");
+    BuiltContext {
+        total_chars: context.chars().count(),
+        context,
+        sources: cited,
+        truncated,
+    }
+}
+
+fn count_sources(selected: &[Passage]) -> usize {
+    selected
+        .iter()
+        .map(|p| p.url.as_str())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+fn header(id: usize, passage: &Passage) -> String {
+    match passage.page_number {
+        Some(page) => format!("[{id}] {} ({}, page {page})", passage.title, passage.url),
+        None => format!("[{id}] {} ({})", passage.title, passage.url),
+    }
+}
+
+/// Lowercase character trigrams, whitespace collapsed
+fn trigrams(text: &str) -> HashSet<String> {
+    let chars: Vec<char> = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+/// Share of `a` also in `b`
+fn containment(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    if a.is_empty() {
+        return 1.0;
+    }
+    a.intersection(b).count() as f32 / a.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passage(url: &str, text: &str, score: f32, start: Option<usize>) -> Passage {
+        Passage {
+            url: url.to_string(),
+            title: format!("Title of {url}"),
+            text: text.to_string(),
+            score,
+            range: start.map(|start| SnippetRange {
+                start,
+                end: start + text.chars().count(),
+            }),
+            page_number: None,
+        }
+    }
+
+    #[test]
+    fn test_question_query() {
+        assert_eq!(
+            question_query("How does tantivy's (BM25) scoring work?"),
+            "How does tantivy s BM25 scoring work"
+        );
+        assert_eq!(question_query("全文検索とは？"), "全文検索とは");
+    }
+
+    #[test]
+    fn test_build_context() {
+        let passages = vec![
+            passage(
+                "https://a.com",
+                "Tantivy scores documents with BM25.",
+                10.0,
+                Some(500),
+            ),
+            passage(
+                "https://a.com",
+                "Segments are merged in the background.",
+                9.0,
+                Some(20),
+            ),
+            passage(
+                "https://a.com",
+                "Tantivy scores documents with BM25!",
+                8.0,
+                None,
+            ),
+            passage(
+                "https://a.com",
+                "Overlapping range text here.",
+                7.0,
+                Some(510),
+            ),
+            passage(
+                "https://b.com",
+                "BM25 weighs term frequency against document length.",
+                6.0,
+                None,
+            ),
+        ];
+        let built = build_context(passages.clone(), 10_000);
+
+        // b.com's passage outranks a.com's second one after the diversity decay
+        assert_eq!(built.sources.len(), 2);
+        assert_eq!(built.sources[0].url, "https://a.com");
+        assert_eq!(built.sources[0].passages, 2);
+        assert_eq!(built.sources[1].passages, 1);
+        assert!(!built.truncated);
+        assert!(
+            built
+                .context
+                .starts_with("[1] Title of https://a.com (https://a.com)\n")
+        );
+        let segments = built.context.find("Segments").unwrap();
+        assert!(segments < built.context.find("BM25.").unwrap());
+        assert!(!built.context.contains("BM25!"));
+        assert!(!built.context.contains("Overlapping"));
+        assert_eq!(built.total_chars, built.context.chars().count());
+
+        let small = build_context(passages.clone(), 90);
+        assert!(small.truncated);
+        assert_eq!(small.sources.len(), 1);
+        assert!(small.total_chars <= 90);
+
+        let tiny = build_context(passages, 50);
+        assert!(tiny.total_chars <= 50);
+        assert!(tiny.context.ends_with("..."));
+    }
+}
@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use super::common::BookmarkEntry;
+
+/// Query parameters that only track where a visitor came from
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "mc_cid", "mc_eid", "ref", "ref_src"];
+
+/// Tokens per shingle hashed into a SimHash
+const SHINGLE_SIZE: usize = 2;
+
+/// Default share of matching SimHash bits for near-duplicates (at most 6 of 64 bits differ)
+pub const DEFAULT_NEAR_DUPLICATE_THRESHOLD: f32 = 0.9;
+
+/// Why a group of bookmarks is considered duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// Same URL after canonicalization
+    Url,
+    /// Same title after normalization
+    Title,
+    /// Same stored content
+    Content,
+}
+
+/// Bookmarks that point to the same page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub reason: DuplicateReason,
+    /// Canonical URL, normalized title or content hash shared by the group
+    pub key: String,
+    pub bookmarks: Vec<BookmarkEntry>,
+}
+
+/// Bookmarks whose content is nearly identical (mirrors, reposts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearDuplicateGroup {
+    /// Lowest similarity between linked bookmarks in the group
+    pub similarity: f32,
+    pub bookmarks: Vec<BookmarkEntry>,
+}
+
+/// Canonicalize a URL for comparison
+///
+/// Ignores scheme, `www.`, fragment, trailing slash, tracking parameters and
+/// query parameter order.
+pub fn canonical_url(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url.trim()) else {
+        return url.trim().to_lowercase();
+    };
+
+    let host = parsed.host_str().unwrap_or("").to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let mut canonical = host.to_string();
+    if let Some(port) = parsed.port() {
+        canonical.push_str(&format!(":{port}"));
+    }
+    canonical.push_str(parsed.path().trim_end_matches('/'));
+
+    let mut params: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(k, _)| !k.starts_with("utm_") && !TRACKING_PARAMS.contains(&k.as_ref()))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if !params.is_empty() {
+        params.sort();
+        let query: Vec<String> = params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        canonical.push('?');
+        canonical.push_str(&query.join("&"));
+    }
+
+    canonical
+}
+
+/// Normalize a title for comparison
+///
+/// Lowercases, drops punctuation and the " [Pages a-b]" suffix added to split PDFs.
+pub fn normalize_title(title: &str) -> String {
+    let title = match title.rfind(" [Pages ") {
+        Some(pos) if title.ends_with(']') => &title[..pos],
+        _ => title,
+    };
+
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Find groups of duplicate bookmarks
+///
+/// URL groups are reported first; title and content groups are only reported
+/// when they span more than one canonical URL, so the same pair isn't listed twice.
+pub fn find_duplicate_groups(entries: &[BookmarkEntry]) -> Vec<DuplicateGroup> {
+    let mut groups = Vec::new();
+
+    for (key, bookmarks) in group_by(entries, |e| canonical_url(&e.url)) {
+        if bookmarks.len() > 1 {
+            groups.push(DuplicateGroup {
+                reason: DuplicateReason::Url,
+                key,
+                bookmarks,
+            });
+        }
+    }
+
+    let secondary: [(DuplicateReason, fn(&BookmarkEntry) -> String); 2] = [
+        (DuplicateReason::Title, |e| normalize_title(&e.title)),
+        (DuplicateReason::Content, |e| e.content_hash.clone()),
+    ];
+    for (reason, key_fn) in secondary {
+        for (key, bookmarks) in group_by(entries, key_fn) {
+            let urls: HashSet<String> = bookmarks.iter().map(|e| canonical_url(&e.url)).collect();
+            if urls.len() > 1 {
+                groups.push(DuplicateGroup {
+                    reason,
+                    key,
+                    bookmarks,
+                });
+            }
+        }
+    }
+
+    groups
+}
+
+/// SimHash fingerprint of content (0 for empty content)
+///
+/// Shingles of consecutive words (single characters for CJK text) are hashed
+/// and each bit set by majority vote, so small edits flip only a few bits.
+pub fn simhash(content: &str) -> u64 {
+    let tokens = fingerprint_tokens(content);
+    if tokens.is_empty() {
+        return 0;
+    }
+
+    let shingles: Vec<&[String]> = if tokens.len() < SHINGLE_SIZE {
+        vec![&tokens[..]]
+    } else {
+        tokens.windows(SHINGLE_SIZE).collect()
+    };
+    let mut votes = [0i64; 64];
+    for shingle in shingles {
+        let hash = fnv1a(shingle);
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            *vote += if (hash >> bit) & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    votes
+        .iter()
+        .enumerate()
+        .filter(|(_, vote)| **vote > 0)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// Share of matching bits between two SimHashes (1.0 = identical)
+pub fn simhash_similarity(a: u64, b: u64) -> f32 {
+    1.0 - (a ^ b).count_ones() as f32 / 64.0
+}
+
+/// Lowercase words, with CJK characters as tokens of their own
+fn fingerprint_tokens(content: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for c in content.chars() {
+        let cjk = matches!(c,
+            '\u{3040}'..='\u{30FF}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}'
+        );
+        if c.is_alphanumeric() && !cjk {
+            word.extend(c.to_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            tokens.push(std::mem::take(&mut word));
+        }
+        if cjk {
+            tokens.push(c.to_string());
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+/// 64-bit FNV-1a, stable across builds so stored fingerprints stay comparable
+fn fnv1a(tokens: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, token) in tokens.iter().enumerate() {
+        let separator = if i == 0 { &[][..] } else { &[b' '][..] };
+        for byte in separator.iter().chain(token.as_bytes()) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// Group bookmarks whose SimHash similarity is at least `threshold`
+///
+/// Similar pairs are linked transitively, so a group can hold bookmarks that
+/// are each close to another member. Bookmarks without a SimHash (indexed
+/// before it was stored, or without content) are skipped. Most similar groups
+/// come first.
+pub fn find_near_duplicates(entries: &[BookmarkEntry], threshold: f32) -> Vec<NearDuplicateGroup> {
+    let hashed: Vec<&BookmarkEntry> = entries.iter().filter(|e| e.simhash != 0).collect();
+    let mut parent: Vec<usize> = (0..hashed.len()).collect();
+
+    let mut links = Vec::new();
+    for i in 0..hashed.len() {
+        for j in i + 1..hashed.len() {
+            let similarity = simhash_similarity(hashed[i].simhash, hashed[j].simhash);
+            if similarity >= threshold {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[b] = a;
+                links.push((i, similarity));
+            }
+        }
+    }
+
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..hashed.len() {
+        let group = root(&mut parent, i);
+        members.entry(group).or_default().push(i);
+    }
+    let mut lowest: BTreeMap<usize, f32> = BTreeMap::new();
+    for (i, similarity) in links {
+        let group = root(&mut parent, i);
+        let entry = lowest.entry(group).or_insert(1.0);
+        *entry = entry.min(similarity);
+    }
+
+    let mut groups: Vec<NearDuplicateGroup> = members
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(group, members)| NearDuplicateGroup {
+            similarity: lowest.get(&group).copied().unwrap_or(1.0),
+            bookmarks: members.into_iter().map(|i| hashed[i].clone()).collect(),
+        })
+        .collect();
+    groups.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    groups
+}
+
+/// Union-find root of `i`, halving paths on the way
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Group entries by a key, skipping entries whose key is empty
+fn group_by<F>(entries: &[BookmarkEntry], key_fn: F) -> BTreeMap<String, Vec<BookmarkEntry>>
+where
+    F: Fn(&BookmarkEntry) -> String,
+{
+    let mut groups: BTreeMap<String, Vec<BookmarkEntry>> = BTreeMap::new();
+    for entry in entries {
+        let key = key_fn(entry);
+        if !key.is_empty() {
+            groups.entry(key).or_default().push(entry.clone());
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, url: &str, title: &str, content_hash: &str) -> BookmarkEntry {
+        BookmarkEntry {
+            id: id.to_string(),
+            url: url.to_string(),
+            title: title.to_string(),
+            folder_path: String::new(),
+            content_hash: content_hash.to_string(),
+            simhash: 0,
+            date_added: 0,
+            date_modified: 0,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(
+            canonical_url("https://www.Example.com/docs/?utm_source=x&b=2&a=1#intro"),
+            "example.com/docs?a=1&b=2"
+        );
+        assert_eq!(
+            canonical_url("http://example.com/docs"),
+            canonical_url("https://example.com/docs/")
+        );
+        assert_ne!(
+            canonical_url("https://example.com/a"),
+            canonical_url("https://example.com/b")
+        );
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(
+            normalize_title("Rust Book - Chapter 1!"),
+            "rust book chapter 1"
+        );
+        assert_eq!(normalize_title("Manual.pdf [Pages 1-20]"), "manual pdf");
+        assert_eq!(normalize_title("日本語 タイトル"), "日本語 タイトル");
+    }
+
+    #[test]
+    fn test_find_duplicate_groups() {
+        let entries = vec![
+            entry("1", "https://example.com/docs", "Docs", "aaa"),
+            entry("2", "https://www.example.com/docs/", "Docs", "aaa"),
+            entry("3", "https://mirror.example.org/docs", "Docs!", "bbb"),
+            entry("4", "https://other.com", "Other", "bbb"),
+            entry("5", "https://unique.com", "Unique", ""),
+        ];
+
+        let groups = find_duplicate_groups(&entries);
+        assert_eq!(groups.len(), 3);
+
+        assert_eq!(groups[0].reason, DuplicateReason::Url);
+        assert_eq!(groups[0].bookmarks.len(), 2);
+
+        assert_eq!(groups[1].reason, DuplicateReason::Title);
+        assert_eq!(groups[1].key, "docs");
+        assert_eq!(groups[1].bookmarks.len(), 3);
+
+        assert_eq!(groups[2].reason, DuplicateReason::Content);
+        let ids: Vec<_> = groups[2].bookmarks.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["3", "4"]);
+    }
+
+    const ARTICLE: &str = "Tantivy is a full-text search engine library inspired by Apache Lucene \
+        and written in Rust. It builds an inverted index of the documents it is given, supports \
+        phrase queries, boolean queries and range queries, and can return highlighted snippets. \
+        Indexing is multithreaded and an index of millions of documents can be searched in a few \
+        milliseconds. Segments are merged in the background and deletes are applied lazily.";
+
+    #[test]
+    fn test_simhash() {
+        assert_eq!(simhash(""), 0);
+        assert_eq!(simhash(ARTICLE), simhash(&ARTICLE.to_uppercase()));
+
+        let edited = ARTICLE.replace("a few milliseconds", "several milliseconds");
+        assert!(simhash_similarity(simhash(ARTICLE), simhash(&edited)) >= 0.9);
+
+        let other = "Recipes for a weeknight dinner: roast the vegetables, cook the rice and \
+            serve everything with a spoon of yogurt and fresh herbs from the garden.";
+        assert!(simhash_similarity(simhash(ARTICLE), simhash(other)) < 0.8);
+
+        let japanese = "全文検索エンジンは文書から転置インデックスを作り、クエリに一致する文書を素早く返します。";
+        let edited = "全文検索エンジンは文書から転置インデックスを作成し、クエリに一致する文書を素早く返します。";
+        assert!(simhash_similarity(simhash(japanese), simhash(edited)) > 0.75);
+    }
+
+    #[test]
+    fn test_find_near_duplicates() {
+        let with_content = |id: &str, url: &str, content: &str| BookmarkEntry {
+            simhash: simhash(content),
+            ..entry(id, url, id, "")
+        };
+        let repost = format!("{ARTICLE} Originally published on the project blog.");
+        let entries = vec![
+            with_content("1", "https://example.com/tantivy", ARTICLE),
+            with_content("2", "https://mirror.example.org/tantivy", &repost),
+            with_content(
+                "3",
+                "https://other.com",
+                "Unrelated page about gardening and herbs.",
+            ),
+            entry("4", "https://empty.com", "Empty", ""),
+        ];
+
+        let groups = find_near_duplicates(&entries, DEFAULT_NEAR_DUPLICATE_THRESHOLD);
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<_> = groups[0].bookmarks.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2"]);
+        assert!(groups[0].similarity >= DEFAULT_NEAR_DUPLICATE_THRESHOLD);
+
+        assert_eq!(find_near_duplicates(&entries, 1.01).len(), 0);
+    }
+}
@@ -0,0 +1,844 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::common::content_hash;
+use crate::config::{EmbeddingProvider, EmbeddingSettings};
+
+/// File beside the tantivy index holding the chunk vectors
+pub const EMBEDDINGS_FILE: &str = "embeddings.json";
+
+/// File under the data directory caching vectors of already embedded texts
+pub const EMBEDDING_CACHE_FILE: &str = "embedding_cache.json";
+
+/// Characters per embedded chunk (roughly what small models read in one pass)
+pub const CHUNK_CHARS: usize = 1000;
+
+/// Characters of chunk text kept in the store for results
+const PREVIEW_CHARS: usize = 300;
+
+/// Computes text embeddings
+pub trait Embedder: Send + Sync {
+    /// Model the vectors come from
+    fn model(&self) -> &str;
+
+    /// Vectors for page chunks
+    fn embed_documents(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
+
+    /// Vector for a search query
+    fn embed_query(&self, query: &str) -> Result<Vec<f32>>;
+}
+
+/// One embedded chunk of a bookmark's content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedChunk {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// Character offset of the chunk in the bookmark's content
+    pub offset: usize,
+    /// PDF page the chunk is on (chunks never cross a page boundary)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_number: Option<usize>,
+    pub preview: String,
+    pub vector: Vec<f32>,
+}
+
+/// A bookmark matched by semantic search (best chunk per URL)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticHit {
+    pub url: String,
+    pub title: String,
+    pub score: f32,
+    /// Character offset of the matching chunk, usable with get_context_around
+    pub offset: usize,
+    /// PDF page of the matching chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_number: Option<usize>,
+    pub snippet: String,
+}
+
+/// Flat vector store: every query is compared against every chunk
+///
+/// Bookmark collections are small enough that an exact scan stays fast and
+/// avoids keeping an approximate index in sync with tantivy.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingStore {
+    pub model: String,
+    pub chunks: Vec<EmbeddedChunk>,
+}
+
+impl EmbeddingStore {
+    /// Store location for an index directory
+    pub fn path(index_dir: &Path) -> PathBuf {
+        index_dir.join(EMBEDDINGS_FILE)
+    }
+
+    /// Load the store of an index, or an empty one if none was written yet
+    pub fn load(index_dir: &Path) -> Result<Self> {
+        let path = Self::path(index_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read {path:?}"))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid embeddings file {path:?}"))
+    }
+
+    /// Write the store next to the index (through a temporary file)
+    pub fn save(&self, index_dir: &Path) -> Result<()> {
+        let path = Self::path(index_dir);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("Failed to write {tmp:?}"))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {path:?}"))?;
+        Ok(())
+    }
+
+    /// Replace every chunk of a bookmark
+    pub fn replace(&mut self, id: &str, chunks: Vec<EmbeddedChunk>) {
+        self.remove(id);
+        self.chunks.extend(chunks);
+    }
+
+    /// Drop every chunk of a bookmark
+    pub fn remove(&mut self, id: &str) {
+        self.chunks.retain(|chunk| chunk.id != id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Bookmarks ranked by cosine similarity of their best chunk
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<SemanticHit> {
+        let mut hits: Vec<SemanticHit> = Vec::new();
+        for chunk in &self.chunks {
+            let score = cosine_similarity(query, &chunk.vector);
+            match hits.iter_mut().find(|hit| hit.url == chunk.url) {
+                Some(hit) if hit.score >= score => {}
+                Some(hit) => {
+                    hit.score = score;
+                    hit.offset = chunk.offset;
+                    hit.page_number = chunk.page_number;
+                    hit.snippet = chunk.preview.clone();
+                }
+                None => hits.push(SemanticHit {
+                    url: chunk.url.clone(),
+                    title: chunk.title.clone(),
+                    score,
+                    offset: chunk.offset,
+                    page_number: chunk.page_number,
+                    snippet: chunk.preview.clone(),
+                }),
+            }
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Vectors of embedded chunk texts, keyed by model and content hash
+///
+/// Shared by all indexes, so re-indexing or rebuilding an index doesn't
+/// re-embed pages that haven't changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingCache {
+    entries: HashMap<String, Vec<f32>>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl EmbeddingCache {
+    /// Cache location under the data directory
+    pub fn path() -> PathBuf {
+        crate::config::data_dir().join(EMBEDDING_CACHE_FILE)
+    }
+
+    /// Load a cache file, or start an empty cache if there is none
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid embedding cache {path:?}"))
+    }
+
+    /// Write the cache if anything was added since it was loaded
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("Failed to write {tmp:?}"))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {path:?}"))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn key(model: &str, text: &str) -> String {
+        format!("{model}:{}", content_hash(text))
+    }
+
+    pub fn get(&self, model: &str, text: &str) -> Option<&Vec<f32>> {
+        self.entries.get(&Self::key(model, text))
+    }
+
+    pub fn insert(&mut self, model: &str, text: &str, vector: Vec<f32>) {
+        self.entries.insert(Self::key(model, text), vector);
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Cosine similarity, 0.0 for mismatched or zero vectors
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Split content into chunks of at most `max_chars`, with their character offsets
+///
+/// Chunks end after the last sentence end or whitespace in their second half
+/// when there is one, so words and sentences are rarely cut.
+pub fn chunk_text(content: &str, max_chars: usize) -> Vec<(usize, String)> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + max_chars.max(1)).min(chars.len());
+        if end < chars.len() {
+            let min_end = start + max_chars / 2;
+            if let Some(pos) = (min_end..end)
+                .rev()
+                .find(|&i| matches!(chars[i], '。' | '.' | '!' | '?') || chars[i].is_whitespace())
+            {
+                end = pos + 1;
+            }
+        }
+        let text: String = chars[start..end].iter().collect();
+        if !text.trim().is_empty() {
+            chunks.push((start, text.trim().to_string()));
+        }
+        start = end;
+    }
+    chunks
+}
+
+/// Chunk each PDF page separately, returning `(offset, text, page number)`
+///
+/// `page_offsets` are the character offsets where pages start, as in `PageInfo`.
+pub fn chunk_pages(
+    content: &str,
+    page_offsets: &[usize],
+    max_chars: usize,
+) -> Vec<(usize, String, usize)> {
+    let chars: Vec<char> = content.chars().collect();
+    page_offsets
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &start)| {
+            let start = start.min(chars.len());
+            let end = page_offsets
+                .get(i + 1)
+                .copied()
+                .unwrap_or(chars.len())
+                .clamp(start, chars.len());
+            let page: String = chars[start..end].iter().collect();
+            chunk_text(&page, max_chars)
+                .into_iter()
+                .map(move |(offset, text)| (start + offset, text, i + 1))
+        })
+        .collect()
+}
+
+/// Embed a bookmark's title and content into store chunks
+///
+/// With `page_offsets` (PDFs), chunks follow page boundaries and carry their
+/// page number. Only chunks missing from `cache` are sent to the embedder.
+pub fn embed_bookmark(
+    embedder: &dyn Embedder,
+    cache: &mut EmbeddingCache,
+    id: &str,
+    url: &str,
+    title: &str,
+    content: &str,
+    page_offsets: Option<&[usize]>,
+) -> Result<Vec<EmbeddedChunk>> {
+    // Chunk previews are stored and remote providers see the text
+    let content = match super::redaction::Redactor::configured()? {
+        Some(redactor) => redactor.redact(content),
+        None => std::borrow::Cow::Borrowed(content),
+    };
+    let mut chunks: Vec<(usize, String, Option<usize>)> = match page_offsets {
+        Some(offsets) if !offsets.is_empty() => chunk_pages(&content, offsets, CHUNK_CHARS)
+            .into_iter()
+            .map(|(offset, text, page)| (offset, text, Some(page)))
+            .collect(),
+        _ => chunk_text(&content, CHUNK_CHARS)
+            .into_iter()
+            .map(|(offset, text)| (offset, text, None))
+            .collect(),
+    };
+    if chunks.is_empty() {
+        // Bookmarks without content are still findable by title
+        chunks.push((0, title.to_string(), None));
+    }
+    let texts: Vec<String> = chunks
+        .iter()
+        .map(|(_, text, _)| format!("{title}\n{text}"))
+        .collect();
+    let missing: Vec<String> = texts
+        .iter()
+        .filter(|text| cache.get(embedder.model(), text).is_none())
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        let vectors = embedder.embed_documents(missing.clone())?;
+        for (text, vector) in missing.iter().zip(vectors) {
+            cache.insert(embedder.model(), text, vector);
+        }
+    }
+    let vectors = texts
+        .iter()
+        .map(|text| {
+            cache
+                .get(embedder.model(), text)
+                .cloned()
+                .context("Embedding model returned too few vectors")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(chunks
+        .into_iter()
+        .zip(vectors)
+        .map(|((offset, text, page_number), vector)| EmbeddedChunk {
+            id: id.to_string(),
+            url: url.to_string(),
+            title: title.to_string(),
+            offset,
+            page_number,
+            preview: text.chars().take(PREVIEW_CHARS).collect(),
+            vector,
+        })
+        .collect())
+}
+
+static SHARED_EMBEDDER: OnceLock<Box<dyn Embedder>> = OnceLock::new();
+
+/// Process-wide embedder for the configured model (loaded on first use)
+pub fn shared_embedder() -> Result<&'static dyn Embedder> {
+    if let Some(embedder) = SHARED_EMBEDDER.get() {
+        return Ok(embedder.as_ref());
+    }
+    let embedder = create_embedder(&crate::config::settings().embeddings)?;
+    Ok(SHARED_EMBEDDER.get_or_init(|| embedder).as_ref())
+}
+
+/// Embedder for the configured provider
+pub fn create_embedder(settings: &EmbeddingSettings) -> Result<Box<dyn Embedder>> {
+    match settings.provider {
+        EmbeddingProvider::Local => local_embedder(settings),
+        EmbeddingProvider::OpenAi => Ok(Box::new(remote::RemoteEmbedder::new(settings))),
+    }
+}
+
+/// Local ONNX embedder for the configured model
+#[cfg(feature = "embeddings")]
+pub fn local_embedder(settings: &EmbeddingSettings) -> Result<Box<dyn Embedder>> {
+    Ok(Box::new(local::FastEmbedder::new(&settings.model)?))
+}
+
+#[cfg(not(feature = "embeddings"))]
+pub fn local_embedder(_settings: &EmbeddingSettings) -> Result<Box<dyn Embedder>> {
+    anyhow::bail!(
+        "mcp-bookmark was built without the `embeddings` feature (use provider = \"openai\" for a remote endpoint)"
+    )
+}
+
+#[cfg(feature = "embeddings")]
+mod local {
+    use super::Embedder;
+    use anyhow::{Context, Result};
+    use fastembed::{InitOptions, TextEmbedding};
+
+    /// Chunks embedded per ONNX run
+    const BATCH_SIZE: usize = 16;
+
+    pub struct FastEmbedder {
+        model: String,
+        embedding: TextEmbedding,
+        /// E5 models expect "query: " / "passage: " prefixes
+        e5_prefixes: bool,
+    }
+
+    impl FastEmbedder {
+        pub fn new(model: &str) -> Result<Self> {
+            let info = TextEmbedding::list_supported_models()
+                .into_iter()
+                .find(|info| info.model_code.eq_ignore_ascii_case(model))
+                .with_context(|| format!("Unsupported embedding model: {model}"))?;
+            let options = InitOptions::new(info.model)
+                .with_cache_dir(crate::config::data_dir().join("models"));
+            let embedding = TextEmbedding::try_new(options)
+                .with_context(|| format!("Failed to load embedding model {model}"))?;
+            Ok(Self {
+                model: info.model_code,
+                embedding,
+                e5_prefixes: model.to_lowercase().contains("e5"),
+            })
+        }
+    }
+
+    impl Embedder for FastEmbedder {
+        fn model(&self) -> &str {
+            &self.model
+        }
+
+        fn embed_documents(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+            let texts = if self.e5_prefixes {
+                texts.into_iter().map(|t| format!("passage: {t}")).collect()
+            } else {
+                texts
+            };
+            self.embedding.embed(texts, Some(BATCH_SIZE))
+        }
+
+        fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+            let query = if self.e5_prefixes {
+                format!("query: {query}")
+            } else {
+                query.to_string()
+            };
+            self.embedding
+                .embed(vec![query], None)?
+                .pop()
+                .context("Embedding model returned no vector")
+        }
+    }
+}
+
+mod remote {
+    use super::Embedder;
+    use crate::config::EmbeddingSettings;
+    use anyhow::{Context, Result};
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use std::time::Duration;
+
+    /// Requests tried per batch before giving up
+    const MAX_ATTEMPTS: u32 = 3;
+
+    /// Delay before the first retry, doubled after each attempt
+    const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+    #[derive(Deserialize)]
+    struct EmbeddingsResponse {
+        data: Vec<EmbeddingData>,
+    }
+
+    #[derive(Deserialize)]
+    struct EmbeddingData {
+        index: usize,
+        embedding: Vec<f32>,
+    }
+
+    /// A failed request and whether trying again may help
+    struct RequestError {
+        retryable: bool,
+        error: anyhow::Error,
+    }
+
+    /// OpenAI-compatible `/v1/embeddings` endpoint
+    pub struct RemoteEmbedder {
+        url: String,
+        model: String,
+        api_key: Option<String>,
+        batch_size: usize,
+    }
+
+    impl RemoteEmbedder {
+        pub fn new(settings: &EmbeddingSettings) -> Self {
+            Self {
+                url: settings.url.clone(),
+                model: settings.model.clone(),
+                api_key: settings.api_key.clone(),
+                batch_size: settings.batch_size.max(1),
+            }
+        }
+
+        /// Embed one batch, retrying rate limits, server errors and timeouts
+        ///
+        /// Runs on its own thread and runtime, so it can be called both from the
+        /// synchronous native host and from inside the server's tokio runtime.
+        fn request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            std::thread::scope(|scope| {
+                scope
+                    .spawn(|| {
+                        let runtime = tokio::runtime::Builder::new_current_thread()
+                            .enable_all()
+                            .build()
+                            .context("Failed to start the embeddings request runtime")?;
+                        runtime.block_on(self.request_with_retry(texts))
+                    })
+                    .join()
+                    .map_err(|_| anyhow::anyhow!("Embeddings request thread panicked"))?
+            })
+        }
+
+        async fn request_with_retry(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let client = reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent(concat!("mcp-bookmark/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .context("Failed to create HTTP client")?;
+
+            let mut delay = RETRY_DELAY;
+            let mut attempt = 1;
+            loop {
+                match self.send(&client, texts).await {
+                    Ok(vectors) => return Ok(vectors),
+                    Err(e) if e.retryable && attempt < MAX_ATTEMPTS => {
+                        tracing::debug!("Embeddings request failed, retrying: {:#}", e.error);
+                        tokio::time::sleep(delay).await;
+                        delay *= 2;
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e.error),
+                }
+            }
+        }
+
+        async fn send(
+            &self,
+            client: &reqwest::Client,
+            texts: &[String],
+        ) -> std::result::Result<Vec<Vec<f32>>, RequestError> {
+            let fatal = |error: anyhow::Error| RequestError {
+                retryable: false,
+                error,
+            };
+            let body = serde_json::json!({ "model": self.model, "input": texts });
+            let mut request = client
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .body(serde_json::to_vec(&body).map_err(|e| fatal(e.into()))?);
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+
+            let response = request.send().await.map_err(|e| RequestError {
+                retryable: e.is_timeout() || e.is_connect() || e.is_request(),
+                error: anyhow::Error::new(e).context("Failed to reach the embeddings endpoint"),
+            })?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(RequestError {
+                    retryable: status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+                    error: anyhow::anyhow!(
+                        "Embeddings endpoint returned {status} (check [embeddings] url and api_key)"
+                    ),
+                });
+            }
+            let bytes = response.bytes().await.map_err(|e| RequestError {
+                retryable: true,
+                error: anyhow::Error::new(e).context("Failed to read the embeddings response"),
+            })?;
+            let vectors = parse_response(&bytes).map_err(fatal)?;
+            if vectors.len() != texts.len() {
+                return Err(fatal(anyhow::anyhow!(
+                    "Embeddings endpoint returned {} vectors for {} inputs",
+                    vectors.len(),
+                    texts.len()
+                )));
+            }
+            Ok(vectors)
+        }
+    }
+
+    /// Vectors from a `/v1/embeddings` response, in input order
+    pub fn parse_response(body: &[u8]) -> Result<Vec<Vec<f32>>> {
+        let mut response: EmbeddingsResponse =
+            serde_json::from_slice(body).context("Invalid embeddings response")?;
+        response.data.sort_by_key(|data| data.index);
+        Ok(response
+            .data
+            .into_iter()
+            .map(|data| data.embedding)
+            .collect())
+    }
+
+    impl Embedder for RemoteEmbedder {
+        fn model(&self) -> &str {
+            &self.model
+        }
+
+        fn embed_documents(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+            let mut vectors = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(self.batch_size) {
+                vectors.extend(self.request(batch)?);
+            }
+            Ok(vectors)
+        }
+
+        fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+            self.request(&[query.to_string()])?
+                .pop()
+                .context("Embeddings endpoint returned no vector")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        /// Serve one response per status, echoing a vector per input
+        fn serve(statuses: Vec<u16>) -> (String, std::thread::JoinHandle<Vec<usize>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/v1/embeddings", listener.local_addr().unwrap());
+            let handle = std::thread::spawn(move || {
+                let mut batch_sizes = Vec::new();
+                for status in statuses {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                            length = value.trim().parse().unwrap();
+                        }
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let inputs = request["input"].as_array().unwrap().len();
+                    batch_sizes.push(inputs);
+
+                    let data: Vec<_> = (0..inputs)
+                        .rev()
+                        .map(|i| serde_json::json!({"index": i, "embedding": [i as f32, 1.0]}))
+                        .collect();
+                    let body = serde_json::json!({ "data": data }).to_string();
+                    let response = format!(
+                        "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    reader.get_mut().write_all(response.as_bytes()).unwrap();
+                }
+                batch_sizes
+            });
+            (url, handle)
+        }
+
+        #[test]
+        fn test_batches_and_retries() {
+            let (url, server) = serve(vec![503, 200, 200]);
+            let embedder = RemoteEmbedder::new(&EmbeddingSettings {
+                url,
+                model: "test-model".to_string(),
+                batch_size: 2,
+                ..Default::default()
+            });
+
+            let texts: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+            let vectors = embedder.embed_documents(texts).unwrap();
+            assert_eq!(
+                vectors,
+                vec![vec![0.0, 1.0], vec![1.0, 1.0], vec![0.0, 1.0]]
+            );
+            assert_eq!(server.join().unwrap(), vec![2, 2, 1]);
+
+            let (url, _server) = serve(vec![401]);
+            let embedder = RemoteEmbedder::new(&EmbeddingSettings {
+                url,
+                ..Default::default()
+            });
+            assert!(embedder.embed_query("a").is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts a few keywords, enough to rank chunks deterministically
+    #[derive(Default)]
+    struct KeywordEmbedder {
+        embedded: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Embedder for KeywordEmbedder {
+        fn model(&self) -> &str {
+            "keywords"
+        }
+
+        fn embed_documents(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+            self.embedded
+                .fetch_add(texts.len(), std::sync::atomic::Ordering::Relaxed);
+            Ok(texts.iter().map(|t| keyword_vector(t)).collect())
+        }
+
+        fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+            Ok(keyword_vector(query))
+        }
+    }
+
+    fn keyword_vector(text: &str) -> Vec<f32> {
+        ["rust", "python", "cooking"]
+            .iter()
+            .map(|word| text.matches(word).count() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn test_chunk_text() {
+        let content = "one two three. four five six. seven";
+        let chunks = chunk_text(content, 16);
+        assert_eq!(chunks[0], (0, "one two three.".to_string()));
+        assert!(chunks.iter().all(|(_, text)| text.chars().count() <= 16));
+        let (offset, text) = &chunks[1];
+        assert!(content[*offset..].trim_start().starts_with(text.as_str()));
+
+        assert_eq!(chunk_text("日本語のテキスト", 4).len(), 2);
+        assert!(chunk_text("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_page_chunks() {
+        let content = "[PAGE:1]\nrust intro\n[PAGE:2]\npython notes\n[PAGE:3]\nrust again";
+        let offsets: Vec<usize> = content.match_indices("[PAGE:").map(|(i, _)| i).collect();
+        let chunks = chunk_pages(content, &offsets, 1000);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].0, offsets[1]);
+        assert_eq!(chunks[1].2, 2);
+        assert!(chunks[1].1.contains("python") && !chunks[1].1.contains("rust"));
+
+        let embedder = KeywordEmbedder::default();
+        let mut cache = EmbeddingCache::default();
+        let store = EmbeddingStore {
+            model: embedder.model().to_string(),
+            chunks: embed_bookmark(
+                &embedder,
+                &mut cache,
+                "pdf",
+                "https://a/doc.pdf",
+                "Manual",
+                content,
+                Some(&offsets),
+            )
+            .unwrap(),
+        };
+        let hits = store.search(&embedder.embed_query("python").unwrap(), 1);
+        assert_eq!(hits[0].page_number, Some(2));
+        assert_eq!(hits[0].offset, offsets[1]);
+    }
+
+    #[test]
+    fn test_store_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let embedder = KeywordEmbedder::default();
+        let mut store = EmbeddingStore {
+            model: embedder.model().to_string(),
+            ..Default::default()
+        };
+        let long = format!("{}rust rust", "python ".repeat(150));
+        let mut cache = EmbeddingCache::default();
+        let chunks =
+            embed_bookmark(&embedder, &mut cache, "1", "https://a", "A", &long, None).unwrap();
+        assert!(chunks.len() > 1);
+        store.replace("1", chunks);
+        store.replace(
+            "2",
+            embed_bookmark(
+                &embedder,
+                &mut cache,
+                "2",
+                "https://b",
+                "cooking notes",
+                "",
+                None,
+            )
+            .unwrap(),
+        );
+        store.save(dir.path()).unwrap();
+
+        let store = EmbeddingStore::load(dir.path()).unwrap();
+        let hits = store.search(&embedder.embed_query("rust").unwrap(), 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].url, "https://a");
+        assert!(hits[0].offset > 0);
+        assert!(hits[0].snippet.contains("rust"));
+
+        let hits = store.search(&embedder.embed_query("cooking").unwrap(), 1);
+        assert_eq!(hits[0].url, "https://b");
+
+        let mut store = store;
+        store.remove("1");
+        assert!(store.chunks.iter().all(|chunk| chunk.id == "2"));
+        assert!(
+            EmbeddingStore::load(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_cache_skips_unchanged_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(EMBEDDING_CACHE_FILE);
+        let embedder = KeywordEmbedder::default();
+        let embedded = || embedder.embedded.load(std::sync::atomic::Ordering::Relaxed);
+
+        let mut cache = EmbeddingCache::load(&path).unwrap();
+        let content = format!("{}rust", "python ".repeat(150));
+        embed_bookmark(&embedder, &mut cache, "1", "https://a", "A", &content, None).unwrap();
+        assert_eq!(embedded(), 2);
+        cache.save(&path).unwrap();
+
+        let mut cache = EmbeddingCache::load(&path).unwrap();
+        assert_eq!(cache.len(), 2);
+        let changed = format!("{}go", "python ".repeat(150));
+        let chunks =
+            embed_bookmark(&embedder, &mut cache, "1", "https://a", "A", &changed, None).unwrap();
+        assert_eq!(chunks.len(), 2);
+        // Only the edited chunk is embedded again
+        assert_eq!(embedded(), 3);
+    }
+}
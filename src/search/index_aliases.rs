@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::index_registry::IndexRegistry;
+
+/// Friendly names for groups of indexes
+///
+/// Loaded from a TOML file of the form
+///
+/// ```toml
+/// research = ["papers", "blogs", "docs"]
+/// work = ["work_*"]
+/// everything = ["research", "work"]
+/// ```
+///
+/// Members can be index names, patterns such as `work_*` or other aliases.
+/// An alias is accepted wherever an index name is and stands for all of its
+/// members; it takes precedence over an index of the same name.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct IndexAliases {
+    aliases: BTreeMap<String, Vec<String>>,
+}
+
+impl IndexAliases {
+    /// Default location: `aliases.toml` next to the config file
+    pub fn default_path() -> Option<PathBuf> {
+        Some(
+            crate::config::Settings::path()?
+                .parent()?
+                .join("aliases.toml"),
+        )
+    }
+
+    /// The file from the settings (or the default location), empty if there is none
+    pub fn load_configured() -> Result<Self> {
+        match crate::config::settings()
+            .index
+            .aliases_file
+            .clone()
+            .or_else(Self::default_path)
+        {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load an aliases file, returning no aliases if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+        };
+        Self::parse(&content).with_context(|| format!("Invalid aliases file {path:?}"))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let aliases: BTreeMap<String, Vec<String>> = toml::from_str(content)?;
+        let aliases = Self { aliases };
+        for (alias, members) in &aliases.aliases {
+            if !IndexRegistry::is_valid_index_name(alias) {
+                anyhow::bail!("Invalid alias name: {alias:?}");
+            }
+            if members.is_empty() {
+                anyhow::bail!("Alias {alias} has no indexes");
+            }
+            if let Some(member) = members
+                .iter()
+                .find(|member| member.trim().is_empty() || member.contains(','))
+            {
+                anyhow::bail!("Invalid index {member:?} in alias {alias}");
+            }
+            aliases.resolve(alias, &mut Vec::new(), &mut Vec::new())?;
+        }
+        Ok(aliases)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Whether `name` is an alias rather than an index name
+    pub fn contains(&self, name: &str) -> bool {
+        self.aliases.contains_key(name)
+    }
+
+    /// Aliases with their members as written in the file, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.aliases
+            .iter()
+            .map(|(alias, members)| (alias.as_str(), members.as_slice()))
+    }
+
+    /// Replace aliases with their members, listing each name once
+    pub fn expand(&self, names: Vec<String>) -> Vec<String> {
+        let mut expanded = Vec::new();
+        for name in names {
+            // Cycles were rejected when the file was parsed
+            let _ = self.resolve(&name, &mut Vec::new(), &mut expanded);
+        }
+        expanded
+    }
+
+    fn resolve(&self, name: &str, chain: &mut Vec<String>, out: &mut Vec<String>) -> Result<()> {
+        let Some(members) = self.aliases.get(name) else {
+            if !out.iter().any(|n| n == name) {
+                out.push(name.to_string());
+            }
+            return Ok(());
+        };
+        if chain.iter().any(|n| n == name) {
+            anyhow::bail!(
+                "Alias {name} refers to itself: {} -> {name}",
+                chain.join(" -> ")
+            );
+        }
+        chain.push(name.to_string());
+        for member in members {
+            self.resolve(member.trim(), chain, out)?;
+        }
+        chain.pop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_aliases() {
+        let aliases = IndexAliases::parse(
+            "research = [\"papers\", \"blogs\", \"docs\"]\n\
+             work = [\"work_*\"]\n\
+             everything = [\"research\", \"work\", \"papers\"]\n",
+        )
+        .unwrap();
+        assert!(aliases.contains("research"));
+        assert!(!aliases.contains("papers"));
+
+        let expand = |names: &[&str]| aliases.expand(names.iter().map(|n| n.to_string()).collect());
+        assert_eq!(expand(&["research"]), ["papers", "blogs", "docs"]);
+        assert_eq!(expand(&["work", "personal"]), ["work_*", "personal"]);
+        assert_eq!(
+            expand(&["everything"]),
+            ["papers", "blogs", "docs", "work_*"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_aliases() {
+        assert!(IndexAliases::parse("a = [\"b\"]\nb = [\"c\", \"a\"]\n").is_err());
+        assert!(IndexAliases::parse("empty = []\n").is_err());
+        assert!(IndexAliases::parse("\"work_*\" = [\"work\"]\n").is_err());
+        assert!(IndexAliases::parse("pair = [\"a,b\"]\n").is_err());
+        assert!(IndexAliases::parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::SearchBoosts;
+
+/// File in an index directory holding its search defaults
+pub const INDEX_DEFAULTS_FILE: &str = "index_defaults.json";
+
+/// Search defaults of one index, kept in its directory
+///
+/// They travel with the index, so everyone searching a shared index gets the
+/// same results without configuring their own server. Arguments of a search
+/// take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexDefaults {
+    /// Hosts left out of results unless a search filters on one of them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_domains: Vec<String>,
+    /// Snippet length for searches that don't pass one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet_length: Option<usize>,
+    /// Field boosts used instead of those in `[boosts]`
+    #[serde(skip_serializing_if = "BoostOverrides::is_empty")]
+    pub boosts: BoostOverrides,
+}
+
+/// Boosts of single fields; the others keep the configured boost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoostOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headings: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<f32>,
+}
+
+impl BoostOverrides {
+    /// Names accepted by `set`
+    pub const FIELDS: [&str; 4] = ["title", "url", "headings", "content"];
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `boosts` with these fields replaced
+    pub fn apply(&self, boosts: SearchBoosts) -> SearchBoosts {
+        SearchBoosts {
+            title: self.title.unwrap_or(boosts.title),
+            url: self.url.unwrap_or(boosts.url),
+            headings: self.headings.unwrap_or(boosts.headings),
+            content: self.content.unwrap_or(boosts.content),
+        }
+    }
+
+    /// Set the boost of a field by name
+    pub fn set(&mut self, field: &str, boost: f32) -> Result<()> {
+        if !boost.is_finite() || boost < 0.0 {
+            anyhow::bail!("Boost for {field} must be a number of at least 0");
+        }
+        let target = match field {
+            "title" => &mut self.title,
+            "url" => &mut self.url,
+            "headings" => &mut self.headings,
+            "content" => &mut self.content,
+            _ => anyhow::bail!(
+                "Unknown field {field:?} (expected {})",
+                Self::FIELDS.join(", ")
+            ),
+        };
+        *target = Some(boost);
+        Ok(())
+    }
+}
+
+impl IndexDefaults {
+    pub fn path(index_dir: &Path) -> PathBuf {
+        index_dir.join(INDEX_DEFAULTS_FILE)
+    }
+
+    /// Read the defaults of an index, empty if it has none
+    pub fn load(index_dir: &Path) -> Result<Self> {
+        let path = Self::path(index_dir);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+        };
+        serde_json::from_str(&content).with_context(|| format!("Invalid index defaults {path:?}"))
+    }
+
+    /// Like `load`, but a broken file is logged and ignored so the index stays searchable
+    pub fn load_or_default(index_dir: &Path) -> Self {
+        Self::load(index_dir).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring index defaults: {:#}", e);
+            Self::default()
+        })
+    }
+
+    /// Write the defaults, removing the file when there are none
+    pub fn save(&self, index_dir: &Path) -> Result<()> {
+        let path = Self::path(index_dir);
+        if self.is_empty() {
+            return match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(e).with_context(|| format!("Failed to remove {path:?}"))
+                }
+                _ => Ok(()),
+            };
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {tmp:?}"))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {path:?}"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Hosts to leave out of a search that filters on `domain_filter`
+    pub fn excluded_domains<'a>(
+        &'a self,
+        domain_filter: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a str> {
+        self.exclude_domains
+            .iter()
+            .map(String::as_str)
+            .filter(move |domain| Some(*domain) != domain_filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        assert!(IndexDefaults::load(dir.path()).unwrap().is_empty());
+
+        let mut defaults = IndexDefaults {
+            exclude_domains: vec!["ads.example.com".to_string()],
+            snippet_length: Some(300),
+            ..Default::default()
+        };
+        defaults.boosts.set("title", 5.0).unwrap();
+        assert!(defaults.boosts.set("body", 1.0).is_err());
+        assert!(defaults.boosts.set("url", -1.0).is_err());
+        defaults.save(dir.path()).unwrap();
+
+        let loaded = IndexDefaults::load(dir.path()).unwrap();
+        assert_eq!(loaded, defaults);
+        let boosts = loaded.boosts.apply(SearchBoosts::default());
+        assert_eq!(boosts.title, 5.0);
+        assert_eq!(boosts.url, SearchBoosts::default().url);
+        assert_eq!(
+            loaded.excluded_domains(None).collect::<Vec<_>>(),
+            ["ads.example.com"]
+        );
+        assert_eq!(loaded.excluded_domains(Some("ads.example.com")).count(), 0);
+
+        IndexDefaults::default().save(dir.path()).unwrap();
+        assert!(!IndexDefaults::path(dir.path()).exists());
+    }
+}
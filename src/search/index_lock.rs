@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::fs::{File, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Lock file in each index directory, held by whichever process writes the index
+pub const LOCK_FILE: &str = ".mcp-bookmark.lock";
+
+/// Delay between attempts while waiting for a lock
+const RETRY_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Another process is writing the index
+#[derive(Debug)]
+pub struct IndexLockedError {
+    pub index_dir: PathBuf,
+    /// What the holder wrote into the lock file, e.g. `mcp-bookmark index (pid 4242)`
+    pub holder: Option<String>,
+}
+
+impl std::fmt::Display for IndexLockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self
+            .index_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match &self.holder {
+            Some(holder) => write!(
+                f,
+                "Index {name} is being written by {holder}; try again once it finishes"
+            ),
+            None => write!(
+                f,
+                "Index {name} is being written by another process; try again once it finishes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IndexLockedError {}
+
+/// Advisory lock that keeps writers of one index from running concurrently
+///
+/// Only one `IndexLock` per index exists across processes; the operating
+/// system releases it when the holder exits, so a crash never leaves a
+/// stale lock behind. Readers don't take it.
+#[derive(Debug)]
+pub struct IndexLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl IndexLock {
+    /// Lock an index directory, creating it if needed
+    ///
+    /// With `wait`, retries until the lock frees or the time runs out
+    /// (`Duration::MAX` waits indefinitely); otherwise fails right away with
+    /// an [`IndexLockedError`] naming the holder.
+    pub fn acquire(index_dir: &Path, holder: &str, wait: Option<Duration>) -> Result<Self> {
+        std::fs::create_dir_all(index_dir)
+            .with_context(|| format!("Failed to create {index_dir:?}"))?;
+        let path = index_dir.join(LOCK_FILE);
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open {path:?}"))?;
+
+        let deadline = wait.and_then(|wait| Instant::now().checked_add(wait));
+        let mut announced = false;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    let locked = IndexLockedError {
+                        index_dir: index_dir.to_path_buf(),
+                        holder: read_holder(&mut file),
+                    };
+                    let expired = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                    if wait.is_none() || expired {
+                        return Err(locked.into());
+                    }
+                    if !announced {
+                        info!("Waiting for the lock on {:?}", index_dir);
+                        announced = true;
+                    }
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).with_context(|| format!("Failed to lock {path:?}"));
+                }
+            }
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{holder} (pid {})", std::process::id())?;
+        Ok(Self { file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Holder description left in the lock file, if readable
+fn read_holder(file: &mut File) -> Option<String> {
+    let mut holder = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut holder).ok()?;
+    let holder = holder.trim();
+    (!holder.is_empty()).then(|| holder.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_excludes_second_writer() {
+        let temp_dir = TempDir::new().unwrap();
+        let index_dir = temp_dir.path().join("work");
+
+        let lock = IndexLock::acquire(&index_dir, "mcp-bookmark index", None).unwrap();
+        assert!(lock.path().exists());
+
+        let err = IndexLock::acquire(&index_dir, "native host", None).unwrap_err();
+        let locked = err.downcast_ref::<IndexLockedError>().unwrap();
+        assert!(
+            locked
+                .holder
+                .as_deref()
+                .unwrap()
+                .starts_with("mcp-bookmark index (pid ")
+        );
+        assert!(
+            err.to_string()
+                .starts_with("Index work is being written by")
+        );
+
+        let err = IndexLock::acquire(&index_dir, "native host", Some(Duration::from_millis(300)))
+            .unwrap_err();
+        assert!(err.downcast_ref::<IndexLockedError>().is_some());
+
+        let waiter = std::thread::spawn({
+            let index_dir = index_dir.clone();
+            move || IndexLock::acquire(&index_dir, "native host", Some(Duration::MAX)).is_ok()
+        });
+        std::thread::sleep(Duration::from_millis(300));
+        drop(lock);
+        assert!(waiter.join().unwrap());
+    }
+}
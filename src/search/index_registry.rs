@@ -0,0 +1,390 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tantivy::Index;
+use tracing::{debug, info};
+
+use super::common::{INDEX_METADATA_FILE, count_unique_bookmarks};
+use super::index_trash::TRASH_DIR;
+use super::schema::BookmarkSchema;
+use super::search_manager::SearchManager;
+use super::tokenizer::{LinderaDictionary, index_dictionary};
+
+/// Default number of read-only indexes kept open at the same time
+pub const DEFAULT_MAX_OPEN_INDEXES: usize = 4;
+
+/// Index name that selects every index under the data directory
+pub const ALL_INDEXES: &str = "ALL";
+
+/// Directories under the data directory that hold other data than indexes
+const RESERVED_DIR_NAMES: &[&str] = &[
+    "logs",
+    "models",
+    "native-messaging",
+    TRASH_DIR,
+    crate::audit::AUDIT_DIR,
+    crate::feeds::FEEDS_DIR,
+    crate::query_log::QUERY_LOG_DIR,
+];
+
+/// Summary of an index available under the data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub bookmark_count: usize,
+    pub document_count: usize,
+    pub size_bytes: u64,
+    /// Modification time of the index metadata (RFC 3339)
+    pub last_updated: Option<String>,
+    /// Tokenizer dictionary the index was built with (None if unsupported by this build)
+    pub dictionary: Option<LinderaDictionary>,
+}
+
+impl IndexInfo {
+    /// Read summary information from an index directory
+    ///
+    /// Opens the raw tantivy index without registering the tokenizer, so this
+    /// stays cheap even for indexes that aren't being served.
+    pub fn read(name: &str, index_dir: &Path) -> Result<Self> {
+        let index = Index::open_in_dir(index_dir)?;
+        let searcher = index.reader()?.searcher();
+        let schema = BookmarkSchema::new();
+
+        let last_updated = std::fs::metadata(index_dir.join(INDEX_METADATA_FILE))
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|t| DateTime::<Local>::from(t).to_rfc3339());
+
+        Ok(Self {
+            name: name.to_string(),
+            bookmark_count: count_unique_bookmarks(&searcher, &schema)?,
+            document_count: searcher.num_docs() as usize,
+            size_bytes: dir_size(index_dir),
+            last_updated,
+            dictionary: index_dictionary(index_dir).ok(),
+        })
+    }
+}
+
+/// Match a whole name against a pattern where `*` is any run of characters and `?` one character
+fn wildcard_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|i| wildcard_matches(rest, &name[i..])),
+        Some((b'?', rest)) => !name.is_empty() && wildcard_matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && wildcard_matches(rest, &name[1..]),
+    }
+}
+
+/// Total size of the files in a directory, recursively
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Registry of lazily opened read-only indexes with LRU eviction
+///
+/// Lets a single server answer tool calls for any index under the data
+/// directory without loading every index (and its tokenizer) up front.
+#[derive(Debug)]
+pub struct IndexRegistry {
+    base_dir: PathBuf,
+    capacity: usize,
+    /// Open indexes ordered from least to most recently used
+    open: Mutex<Vec<(String, Arc<SearchManager>)>>,
+}
+
+impl IndexRegistry {
+    /// Create a registry over the default data directory
+    pub fn new(capacity: usize) -> Self {
+        let base_dir = crate::config::data_dir();
+        Self::with_base_dir(base_dir, capacity)
+    }
+
+    /// Create a registry over a custom base directory
+    pub fn with_base_dir(base_dir: PathBuf, capacity: usize) -> Self {
+        Self {
+            base_dir,
+            capacity: capacity.max(1),
+            open: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Check that an index name refers to a directory directly under the base directory
+    ///
+    /// Patterns (`ALL` and names with wildcards) are reserved for selecting indexes,
+    /// and the names of the data directory's other directories can't be used.
+    pub fn is_valid_index_name(name: &str) -> bool {
+        !name.is_empty()
+            && !RESERVED_DIR_NAMES.contains(&name)
+            && name != "."
+            && name != ".."
+            && !name.contains('/')
+            && !name.contains('\\')
+            && !Self::is_index_pattern(name)
+    }
+
+    /// Whether an index name is `ALL` or has `*`/`?` wildcards
+    pub fn is_index_pattern(name: &str) -> bool {
+        name == ALL_INDEXES || name.contains(['*', '?'])
+    }
+
+    /// Replace `ALL` and wildcard patterns with the indexes under `base_dir` they match
+    ///
+    /// Matches are sorted by name and names are only listed once. A pattern
+    /// that matches nothing is kept, so it is reported as a missing index.
+    pub fn expand_index_names(names: Vec<String>, base_dir: &Path) -> Vec<String> {
+        if !names.iter().any(|name| Self::is_index_pattern(name)) {
+            return names;
+        }
+        let mut available: Vec<String> = std::fs::read_dir(base_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| Self::is_valid_index_name(name))
+            .filter(|name| base_dir.join(name).join(INDEX_METADATA_FILE).exists())
+            .collect();
+        available.sort();
+
+        let mut expanded: Vec<String> = Vec::new();
+        for name in names {
+            let matches: Vec<String> = if Self::is_index_pattern(&name) {
+                available
+                    .iter()
+                    .filter(|index| {
+                        name == ALL_INDEXES || wildcard_matches(name.as_bytes(), index.as_bytes())
+                    })
+                    .cloned()
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let matches = if matches.is_empty() {
+                vec![name]
+            } else {
+                matches
+            };
+            for index in matches {
+                if !expanded.contains(&index) {
+                    expanded.push(index);
+                }
+            }
+        }
+        expanded
+    }
+
+    /// Get a read-only manager for the named index, opening it if needed
+    pub fn get(&self, name: &str) -> Result<Arc<SearchManager>> {
+        if !Self::is_valid_index_name(name) {
+            anyhow::bail!("Invalid index name: '{}'", name);
+        }
+
+        if let Some(manager) = self.touch(name) {
+            debug!("Index registry hit: {}", name);
+            crate::metrics::metrics().record_cache(true);
+            return Ok(manager);
+        }
+        crate::metrics::metrics().record_cache(false);
+
+        let index_dir = self.base_dir.join(name);
+        if !index_dir.join(INDEX_METADATA_FILE).exists() {
+            anyhow::bail!(
+                "Index '{}' not found. Use `mcp-bookmark list` to see available indexes.",
+                name
+            );
+        }
+
+        // Open outside the lock: loading the tokenizer dictionary is slow
+        let manager = Arc::new(SearchManager::open_readonly_at(&index_dir)?);
+
+        let mut open = self.open.lock().unwrap();
+        if let Some(pos) = open.iter().position(|(n, _)| n == name) {
+            // Another call opened it concurrently; keep the existing one
+            let entry = open.remove(pos);
+            let existing = entry.1.clone();
+            open.push(entry);
+            return Ok(existing);
+        }
+
+        open.push((name.to_string(), manager.clone()));
+        while open.len() > self.capacity {
+            let (evicted, _) = open.remove(0);
+            info!("Evicted index from registry: {}", evicted);
+        }
+
+        Ok(manager)
+    }
+
+    /// Summaries of all valid indexes under the base directory, sorted by name
+    pub fn available_indexes(&self) -> Vec<IndexInfo> {
+        let Ok(entries) = std::fs::read_dir(&self.base_dir) else {
+            return Vec::new();
+        };
+
+        let mut indexes: Vec<IndexInfo> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path.file_name()?.to_str()?.to_string();
+                if !path.is_dir()
+                    || !Self::is_valid_index_name(&name)
+                    || !path.join(INDEX_METADATA_FILE).exists()
+                {
+                    return None;
+                }
+                match IndexInfo::read(&name, &path) {
+                    Ok(info) => Some(info),
+                    Err(e) => {
+                        debug!("Skipping unreadable index {}: {}", name, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        indexes
+    }
+
+    /// Names of currently open indexes, least recently used first
+    pub fn open_index_names(&self) -> Vec<String> {
+        self.open
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Reload the readers of an index if it is open, to see new commits
+    pub fn reload(&self, name: &str) -> Result<()> {
+        let manager = self
+            .open
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, manager)| manager.clone());
+        manager.map_or(Ok(()), |manager| manager.reload())
+    }
+
+    /// Close all open indexes so the next call reopens them from disk
+    pub fn clear(&self) {
+        self.open.lock().unwrap().clear();
+    }
+
+    /// Mark an open index as most recently used and return it
+    fn touch(&self, name: &str) -> Option<Arc<SearchManager>> {
+        let mut open = self.open.lock().unwrap();
+        let pos = open.iter().position(|(n, _)| n == name)?;
+        let entry = open.remove(pos);
+        let manager = entry.1.clone();
+        open.push(entry);
+        Some(manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_index(base: &std::path::Path, name: &str) {
+        let manager = SearchManager::new_for_testing(base.join(name)).unwrap();
+        manager.commit().unwrap();
+    }
+
+    #[test]
+    fn test_index_name_validation() {
+        assert!(IndexRegistry::is_valid_index_name("work"));
+        assert!(IndexRegistry::is_valid_index_name("Extension_Bookmarks"));
+        assert!(!IndexRegistry::is_valid_index_name(""));
+        assert!(!IndexRegistry::is_valid_index_name("logs"));
+        assert!(!IndexRegistry::is_valid_index_name("audit"));
+        assert!(!IndexRegistry::is_valid_index_name("query_logs"));
+        assert!(!IndexRegistry::is_valid_index_name(".trash"));
+        assert!(!IndexRegistry::is_valid_index_name(".."));
+        assert!(!IndexRegistry::is_valid_index_name("../etc"));
+        assert!(!IndexRegistry::is_valid_index_name("a/b"));
+        assert!(!IndexRegistry::is_valid_index_name("work_*"));
+        assert!(!IndexRegistry::is_valid_index_name("ALL"));
+    }
+
+    #[test]
+    fn test_expand_index_names() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["work_b", "work_a", "personal"] {
+            create_index(temp_dir.path(), name);
+        }
+        std::fs::create_dir_all(temp_dir.path().join("work_empty")).unwrap();
+        let expand = |names: &[&str]| {
+            let names = names.iter().map(|name| name.to_string()).collect();
+            IndexRegistry::expand_index_names(names, temp_dir.path())
+        };
+
+        assert_eq!(expand(&["work_*"]), ["work_a", "work_b"]);
+        assert_eq!(expand(&["ALL"]), ["personal", "work_a", "work_b"]);
+        assert_eq!(expand(&["work_a", "work_?"]), ["work_a", "work_b"]);
+        assert_eq!(expand(&["personal", "missing"]), ["personal", "missing"]);
+        // Kept so that it is reported as not found
+        assert_eq!(expand(&["research_*"]), ["research_*"]);
+    }
+
+    #[test]
+    fn test_missing_index_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = IndexRegistry::with_base_dir(temp_dir.path().to_path_buf(), 2);
+        assert!(registry.get("missing").is_err());
+    }
+
+    #[test]
+    fn test_available_indexes() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["b", "a"] {
+            create_index(temp_dir.path(), name);
+        }
+        std::fs::create_dir_all(temp_dir.path().join("logs")).unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("not_an_index")).unwrap();
+
+        let registry = IndexRegistry::with_base_dir(temp_dir.path().to_path_buf(), 2);
+        let indexes = registry.available_indexes();
+
+        let names: Vec<_> = indexes.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(indexes[0].bookmark_count, 0);
+        assert!(indexes[0].last_updated.is_some());
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["a", "b", "c"] {
+            create_index(temp_dir.path(), name);
+        }
+
+        let registry = IndexRegistry::with_base_dir(temp_dir.path().to_path_buf(), 2);
+        registry.get("a").unwrap();
+        registry.get("b").unwrap();
+        // Touch "a" so "b" becomes least recently used
+        registry.get("a").unwrap();
+        registry.get("c").unwrap();
+
+        assert_eq!(registry.open_index_names(), vec!["a", "c"]);
+
+        registry.clear();
+        assert!(registry.open_index_names().is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Directory under the data directory that holds cleared indexes
+pub const TRASH_DIR: &str = ".trash";
+
+/// Timestamp format of the per-deletion directories in the trash
+const DELETED_AT_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// An index that was moved to the trash
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedIndex {
+    pub name: String,
+    /// When it was cleared, as `YYYYmmdd-HHMMSS` local time
+    pub deleted_at: String,
+    pub path: PathBuf,
+}
+
+/// Cleared indexes kept under `<data dir>/.trash/<deleted at>/<name>`
+///
+/// Each clear gets its own timestamped directory, so the same index can be
+/// cleared repeatedly and any index name stays usable.
+#[derive(Debug, Clone)]
+pub struct IndexTrash {
+    base_dir: PathBuf,
+}
+
+impl IndexTrash {
+    /// Trash for the indexes under `base_dir`
+    pub fn new(base_dir: &Path) -> Self {
+        Self {
+            base_dir: base_dir.to_path_buf(),
+        }
+    }
+
+    fn trash_dir(&self) -> PathBuf {
+        self.base_dir.join(TRASH_DIR)
+    }
+
+    /// Move an index directory into the trash
+    pub fn trash(&self, name: &str) -> Result<TrashedIndex> {
+        let source = self.base_dir.join(name);
+        let deleted_at = Local::now().format(DELETED_AT_FORMAT).to_string();
+        let target_dir = self.trash_dir().join(&deleted_at);
+        std::fs::create_dir_all(&target_dir)
+            .with_context(|| format!("Failed to create {target_dir:?}"))?;
+
+        let target = target_dir.join(name);
+        if target.exists() {
+            anyhow::bail!("{target:?} already exists");
+        }
+        std::fs::rename(&source, &target)
+            .with_context(|| format!("Failed to move {source:?} to the trash"))?;
+
+        debug!("Moved index {} to {:?}", name, target);
+        Ok(TrashedIndex {
+            name: name.to_string(),
+            deleted_at,
+            path: target,
+        })
+    }
+
+    /// Trashed indexes, most recently cleared first
+    pub fn list(&self) -> Vec<TrashedIndex> {
+        let Ok(batches) = std::fs::read_dir(self.trash_dir()) else {
+            return Vec::new();
+        };
+
+        let mut trashed: Vec<TrashedIndex> = batches
+            .flatten()
+            .filter(|batch| batch.path().is_dir())
+            .flat_map(|batch| {
+                let deleted_at = batch.file_name().to_string_lossy().to_string();
+                std::fs::read_dir(batch.path())
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir())
+                    .map(move |entry| TrashedIndex {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        deleted_at: deleted_at.clone(),
+                        path: entry.path(),
+                    })
+            })
+            .collect();
+
+        trashed.sort_by(|a, b| {
+            b.deleted_at
+                .cmp(&a.deleted_at)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        trashed
+    }
+
+    /// Move the most recently trashed copy of an index back into place
+    pub fn restore(&self, name: &str) -> Result<TrashedIndex> {
+        let target = self.base_dir.join(name);
+        if target.exists() {
+            anyhow::bail!("Index already exists: {name} (clear it first)");
+        }
+        let entry = self
+            .list()
+            .into_iter()
+            .find(|entry| entry.name == name)
+            .with_context(|| format!("No trashed index named {name}"))?;
+
+        std::fs::rename(&entry.path, &target)
+            .with_context(|| format!("Failed to restore {:?}", entry.path))?;
+        // Drop the batch directory once its last index is restored
+        if let Some(batch) = entry.path.parent() {
+            std::fs::remove_dir(batch).ok();
+        }
+
+        debug!("Restored index {} from {:?}", name, entry.path);
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = IndexTrash::new(dir.path());
+        std::fs::create_dir_all(dir.path().join("work")).unwrap();
+        std::fs::write(dir.path().join("work/meta.json"), "{}").unwrap();
+
+        let trashed = trash.trash("work").unwrap();
+        assert!(!dir.path().join("work").exists());
+        assert!(trashed.path.join("meta.json").exists());
+        assert_eq!(trash.list().len(), 1);
+        assert_eq!(trash.list()[0].name, "work");
+
+        std::fs::create_dir_all(dir.path().join("work")).unwrap();
+        assert!(trash.restore("work").is_err());
+        std::fs::remove_dir(dir.path().join("work")).unwrap();
+
+        trash.restore("work").unwrap();
+        assert!(dir.path().join("work/meta.json").exists());
+        assert!(trash.list().is_empty());
+        assert!(trash.restore("work").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tantivy::IndexReader;
+use tracing::{debug, info, warn};
+
+use super::common::INDEX_METADATA_FILE;
+
+/// File extensions written by tantivy for segment data
+const SEGMENT_EXTENSIONS: &[&str] = &["idx", "pos", "term", "store", "fast", "fieldnorm", "del"];
+
+/// Record of reloads triggered by index changes on disk
+#[derive(Debug, Default)]
+pub struct RefreshState {
+    count: AtomicUsize,
+    last_refreshed: Mutex<Option<DateTime<Local>>>,
+}
+
+impl RefreshState {
+    /// Number of reloads since the index was opened
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Time of the most recent reload
+    pub fn last_refreshed(&self) -> Option<DateTime<Local>> {
+        *self.last_refreshed.lock().unwrap()
+    }
+
+    fn mark_refreshed(&self) {
+        *self.last_refreshed.lock().unwrap() = Some(Local::now());
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Watches a read-only index directory and reloads the reader when the
+/// Chrome extension commits new data
+pub struct IndexWatcher {
+    _watcher: RecommendedWatcher,
+    state: Arc<RefreshState>,
+}
+
+impl std::fmt::Debug for IndexWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndexWatcher")
+            .field("refresh_count", &self.state.count())
+            .finish()
+    }
+}
+
+impl IndexWatcher {
+    /// Start watching the index directory; the watcher stops when dropped
+    pub fn start<P: AsRef<Path>>(index_dir: P, reader: IndexReader) -> Result<Self> {
+        let index_dir = index_dir.as_ref();
+        let state = Arc::new(RefreshState::default());
+        let handler_state = state.clone();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Index watch error: {}", e);
+                    return;
+                }
+            };
+
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            if !event.paths.iter().any(|p| is_index_file(p)) {
+                return;
+            }
+
+            debug!("Index files changed: {:?}", event.paths);
+            match reader.reload() {
+                Ok(()) => handler_state.mark_refreshed(),
+                Err(e) => warn!("Failed to reload index reader: {}", e),
+            }
+        })
+        .context("Failed to create index watcher")?;
+
+        watcher
+            .watch(index_dir, RecursiveMode::NonRecursive)
+            .context("Failed to watch index directory")?;
+
+        info!("Watching index for changes: {:?}", index_dir);
+
+        Ok(Self {
+            _watcher: watcher,
+            state,
+        })
+    }
+
+    /// Shared refresh state updated by the watcher
+    pub fn state(&self) -> Arc<RefreshState> {
+        self.state.clone()
+    }
+}
+
+/// Check whether a path is tantivy metadata or segment data
+fn is_index_file(path: &Path) -> bool {
+    if path.file_name().and_then(|n| n.to_str()) == Some(INDEX_METADATA_FILE) {
+        return true;
+    }
+
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| SEGMENT_EXTENSIONS.contains(&ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_index_file() {
+        assert!(is_index_file(Path::new("/idx/meta.json")));
+        assert!(is_index_file(Path::new(
+            "/idx/0a1b2c3d4e5f60718293a4b5c6d7e8f9.store"
+        )));
+        assert!(is_index_file(Path::new("/idx/segment.del")));
+        assert!(!is_index_file(Path::new("/idx/.tantivy-writer.lock")));
+        assert!(!is_index_file(Path::new("/idx/.managed.json")));
+        assert!(!is_index_file(Path::new("/idx/notes.txt")));
+    }
+
+    #[test]
+    fn test_refresh_state() {
+        let state = RefreshState::default();
+        assert_eq!(state.count(), 0);
+        assert!(state.last_refreshed().is_none());
+
+        state.mark_refreshed();
+        assert_eq!(state.count(), 1);
+        assert!(state.last_refreshed().is_some());
+    }
+}
@@ -2,6 +2,8 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::ops::Range;
+use std::time::Instant;
 use tantivy::{Index, IndexWriter, TantivyDocument};
 use tracing::{debug, warn};
 
@@ -20,10 +22,22 @@ fn log_to_file_indexer(message: &str) {
     }
 }
 
-use super::common::{DEFAULT_WRITER_HEAP_SIZE, MIN_WRITER_HEAP_SIZE, extract_domain, parse_date};
+use super::common::{
+    DEFAULT_WRITER_HEAP_SIZE, MIN_WRITER_HEAP_SIZE, compress_text, extract_domain, parse_date,
+};
+use super::duplicates::simhash;
+use super::keywords::extract_keywords;
+use super::redaction::Redactor;
 use super::schema::BookmarkSchema;
+use super::summary::summarize;
 use crate::bookmark::FlatBookmark;
 
+/// Default characters per document above which multi-page PDFs are split into parts
+///
+/// Keeps Lindera from hanging on huge documents: about 300KB of Japanese
+/// text in UTF-8, which tokenizes in reasonable time.
+pub const MAX_CHARS_PER_DOC: usize = 100_000;
+
 /// Page information for chunked content (PDFs)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageInfo {
@@ -31,6 +45,224 @@ pub struct PageInfo {
     pub page_offsets: Vec<usize>,
     pub content_type: String,
     pub total_chars: usize,
+    /// Some pages had no text layer and were recognized by OCR
+    #[serde(default)]
+    pub ocr: bool,
+    /// Where this document sits in a PDF split into several documents
+    #[serde(skip)]
+    pub part: Option<DocumentPart>,
+    /// Characters the content had before it was cut, see `truncate_page`
+    #[serde(skip)]
+    pub truncated_from: Option<usize>,
+}
+
+impl PageInfo {
+    /// Page (1-indexed) this document starts on: 1 unless it is a later part of a split PDF
+    pub fn first_page(&self) -> usize {
+        self.part
+            .as_ref()
+            .and_then(|part| part.pages.get(part.index))
+            .map_or(1, |&(first, _)| first)
+    }
+}
+
+/// One document of a PDF indexed as several, with the map of all its parts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentPart {
+    /// ID of the bookmark the PDF belongs to
+    pub parent_id: String,
+    /// 0-indexed position of this part
+    pub index: usize,
+    /// Number of parts the PDF was split into
+    pub count: usize,
+    /// Pages in the whole PDF
+    pub page_count: usize,
+    /// First and last page (1-indexed) of each part
+    pub pages: Vec<(usize, usize)>,
+}
+
+/// Section heading of an HTML page, sent by the extension
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heading {
+    /// 1 for `<h1>` through 6 for `<h6>`
+    pub level: u8,
+    pub text: String,
+    /// Character offset of the heading in the indexed content
+    pub offset: usize,
+}
+
+impl Heading {
+    /// Deepest heading level indexed into the boosted `headings` field
+    pub const MAX_INDEXED_LEVEL: u8 = 3;
+
+    /// The heading whose section contains character `offset`: the last one at or before it
+    pub fn enclosing(headings: &[Heading], offset: usize) -> Option<&Heading> {
+        headings.iter().rev().find(|h| h.offset <= offset)
+    }
+
+    /// The first indexed heading containing one of `terms`, ignoring case
+    pub fn matching<'a>(headings: &'a [Heading], terms: &[String]) -> Option<&'a Heading> {
+        headings
+            .iter()
+            .filter(|h| h.level <= Self::MAX_INDEXED_LEVEL)
+            .find(|h| {
+                let text = h.text.to_lowercase();
+                terms
+                    .iter()
+                    .any(|term| !term.is_empty() && text.contains(&term.to_lowercase()))
+            })
+    }
+}
+
+/// Start of one caption in a video transcript, sent by the extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptCue {
+    /// Milliseconds from the start of the video
+    pub start_ms: u64,
+    /// Character offset of the caption in the indexed content
+    pub offset: usize,
+}
+
+impl TranscriptCue {
+    /// Indices of the cues spoken between `start_ms` and `end_ms` (to the end when `None`)
+    ///
+    /// Starts at the cue already playing at `start_ms`; `cues` must be sorted by time.
+    pub fn window(cues: &[TranscriptCue], start_ms: u64, end_ms: Option<u64>) -> Range<usize> {
+        let first = cues
+            .partition_point(|cue| cue.start_ms <= start_ms)
+            .saturating_sub(1);
+        let last = match end_ms {
+            Some(end_ms) => cues.partition_point(|cue| cue.start_ms < end_ms),
+            None => cues.len(),
+        };
+        first..last.max(first)
+    }
+}
+
+/// Parse a video timestamp: seconds (`90`) or `m:ss` / `h:mm:ss`, in milliseconds
+pub fn parse_timestamp(value: &str) -> Option<u64> {
+    let mut seconds: u64 = 0;
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    for (i, part) in parts.iter().enumerate() {
+        let n: u64 = part.parse().ok()?;
+        // Minutes and seconds after a colon stay below 60
+        if i > 0 && n >= 60 {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(n)?;
+    }
+    seconds.checked_mul(1000)
+}
+
+/// Format milliseconds as `m:ss`, or `h:mm:ss` past an hour
+pub fn format_timestamp(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// Structure the extension extracts from an HTML page alongside its text
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageStructure {
+    #[serde(default)]
+    pub headings: Vec<Heading>,
+    /// Text of the page's `<pre>` blocks
+    #[serde(default)]
+    pub code_blocks: Vec<String>,
+    /// Markup of the page's main content, stored converted to Markdown
+    #[serde(default)]
+    pub html: Option<String>,
+    /// The page's original HTML, stored compressed as is
+    #[serde(default)]
+    pub raw_html: Option<String>,
+    /// Caption start times of a video's transcript, when the content is one
+    #[serde(default)]
+    pub transcript: Vec<TranscriptCue>,
+    /// Capture time (RFC 3339) when the content is an Internet Archive snapshot of a dead page
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    /// Characters the content had before it was cut, see `truncate_page`
+    #[serde(skip)]
+    pub truncated_from: Option<usize>,
+}
+
+/// Page content cut to a size limit, see `truncate_page`
+#[derive(Debug, Clone)]
+pub struct TruncatedPage {
+    pub content: String,
+    pub page_info: Option<PageInfo>,
+    pub structure: PageStructure,
+}
+
+impl TruncatedPage {
+    /// Characters the content had before it was cut
+    pub fn original_chars(&self) -> usize {
+        self.structure.truncated_from.unwrap_or_default()
+    }
+}
+
+/// Cut `content` to `max_chars` characters, None when it fits
+///
+/// Pages, headings and captions starting past the cut are dropped, and the
+/// original length is recorded so the indexed document says it is incomplete.
+pub fn truncate_page(
+    content: &str,
+    page_info: Option<&PageInfo>,
+    structure: &PageStructure,
+    max_chars: usize,
+) -> Option<TruncatedPage> {
+    let original_chars = content.chars().count();
+    if original_chars <= max_chars {
+        return None;
+    }
+    let page_info = page_info.map(|page_info| {
+        let mut page_info = page_info.clone();
+        page_info.page_offsets.retain(|&offset| offset < max_chars);
+        if !page_info.page_offsets.is_empty() {
+            page_info.page_count = page_info.page_offsets.len();
+        }
+        page_info.total_chars = max_chars;
+        page_info.truncated_from = Some(original_chars);
+        page_info
+    });
+    let mut structure = structure.clone();
+    structure
+        .headings
+        .retain(|heading| heading.offset < max_chars);
+    structure.transcript.retain(|cue| cue.offset < max_chars);
+    structure.truncated_from = Some(original_chars);
+    Some(TruncatedPage {
+        content: content.chars().take(max_chars).collect(),
+        page_info,
+        structure,
+    })
+}
+
+/// Bodies of the ```-fenced blocks in `content`, including any language tag
+pub fn fenced_code_blocks(content: &str) -> Vec<&str> {
+    let mut parts: Vec<&str> = content.split("```").collect();
+    // Text after an unclosed fence isn't treated as code
+    if parts.len() % 2 == 0 {
+        parts.pop();
+    }
+    parts
+        .into_iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|block| !block.trim().is_empty())
+        .collect()
+}
+
+/// Count a document towards the indexing throughput metrics
+fn record_indexed(content: Option<&str>, started: Instant) {
+    crate::metrics::metrics().record_indexed(content.map_or(0, str::len), started.elapsed());
 }
 
 /// Handles indexing operations for bookmarks
@@ -72,8 +304,10 @@ impl BookmarkIndexer {
         bookmark: &FlatBookmark,
         content: Option<&str>,
     ) -> Result<()> {
+        let started = Instant::now();
         let doc = self.create_document(bookmark, content, None)?;
         writer.add_document(doc)?;
+        record_indexed(content, started);
         Ok(())
     }
 
@@ -86,10 +320,83 @@ impl BookmarkIndexer {
         page_info: Option<&PageInfo>,
     ) -> Result<()> {
         log_to_file_indexer("index_bookmark_with_page_info: creating document...");
+        let started = Instant::now();
         let doc = self.create_document(bookmark, content, page_info)?;
         log_to_file_indexer("index_bookmark_with_page_info: document created, adding to writer...");
         writer.add_document(doc)?;
         log_to_file_indexer("index_bookmark_with_page_info: document added to writer");
+        record_indexed(content, started);
+        Ok(())
+    }
+
+    /// Index a single bookmark with the headings, code blocks and markup of its page
+    pub fn index_bookmark_with_structure(
+        &self,
+        writer: &mut IndexWriter,
+        bookmark: &FlatBookmark,
+        content: Option<&str>,
+        structure: &PageStructure,
+    ) -> Result<()> {
+        let started = Instant::now();
+        let mut doc = self.create_document(bookmark, content, None)?;
+        let redacted;
+        let structure = match Redactor::configured()? {
+            Some(redactor) => {
+                redacted = redactor.redact_structure(structure);
+                &redacted
+            }
+            None => structure,
+        };
+        let headings = &structure.headings;
+        // Indexes created before the heading and code fields were added can't store them
+        let index_schema = self.index.schema();
+        if index_schema.get_field("code").is_ok() {
+            for block in &structure.code_blocks {
+                doc.add_text(self.schema.code, block);
+            }
+        }
+        if let (Some(html), Ok(_)) = (&structure.html, index_schema.get_field("content_markdown")) {
+            let markdown = html2md::parse_html(html);
+            if !markdown.trim().is_empty() {
+                doc.add_text(self.schema.content_markdown, &markdown);
+            }
+        }
+        if let (Some(raw_html), Ok(_)) = (&structure.raw_html, index_schema.get_field("raw_html")) {
+            doc.add_bytes(self.schema.raw_html, &compress_text(raw_html)?);
+        }
+        if !headings.is_empty() && index_schema.get_field("heading_offsets").is_ok() {
+            let mut headings = headings.to_vec();
+            headings.sort_by_key(|h| h.offset);
+            doc.add_bytes(self.schema.heading_offsets, &serde_json::to_vec(&headings)?);
+        }
+        if let (Some(archived_at), Ok(_)) =
+            (&structure.archived_at, index_schema.get_field("source"))
+        {
+            doc.add_text(self.schema.source, "archive");
+            doc.add_text(self.schema.archived_at, archived_at);
+        }
+        if let (Some(chars), Ok(_)) = (
+            structure.truncated_from,
+            index_schema.get_field("truncated_from"),
+        ) {
+            doc.add_u64(self.schema.truncated_from, chars as u64);
+        }
+        if !structure.transcript.is_empty() && index_schema.get_field("transcript_offsets").is_ok()
+        {
+            let mut cues = structure.transcript.clone();
+            cues.sort_by_key(|cue| (cue.start_ms, cue.offset));
+            doc.add_bytes(self.schema.transcript_offsets, &serde_json::to_vec(&cues)?);
+        }
+        if index_schema.get_field("headings").is_ok() {
+            for heading in headings
+                .iter()
+                .filter(|h| h.level <= Heading::MAX_INDEXED_LEVEL)
+            {
+                doc.add_text(self.schema.headings, &heading.text);
+            }
+        }
+        writer.add_document(doc)?;
+        record_indexed(content, started);
         Ok(())
     }
 
@@ -102,6 +409,12 @@ impl BookmarkIndexer {
     ) -> Result<TantivyDocument> {
         log_to_file_indexer("create_document: START");
         let domain = extract_domain(&bookmark.url).unwrap_or_default();
+        // Personal data never reaches the index when `[redaction]` is enabled
+        let redacted = match (Redactor::configured()?, content) {
+            (Some(redactor), Some(content)) => Some(redactor.redact(content)),
+            _ => None,
+        };
+        let content = redacted.as_deref().or(content);
 
         let date_added = parse_date(&bookmark.date_added).unwrap_or(0);
         let date_modified = parse_date(&bookmark.date_modified).unwrap_or(0);
@@ -128,6 +441,56 @@ impl BookmarkIndexer {
         doc.add_i64(self.schema.date_added, date_added);
         doc.add_i64(self.schema.date_modified, date_modified);
 
+        // Indexes created before the tags/profile/ngram fields were added can't store them
+        let index_schema = self.index.schema();
+        if index_schema.get_field("tags").is_ok() {
+            for tag in &bookmark.tags {
+                doc.add_text(self.schema.tags, tag);
+            }
+            let max_keywords = crate::config::settings().index.keywords;
+            for keyword in extract_keywords(&bookmark.name, content.unwrap_or(""), max_keywords) {
+                if !bookmark
+                    .tags
+                    .iter()
+                    .any(|tag| tag.eq_ignore_ascii_case(&keyword))
+                {
+                    doc.add_text(self.schema.tags, &keyword);
+                }
+            }
+        }
+        if let (Some(profile), Ok(_)) = (&bookmark.profile, index_schema.get_field("profile")) {
+            doc.add_text(self.schema.profile, profile);
+        }
+        if index_schema.get_field("url_text").is_ok() {
+            doc.add_text(self.schema.url_text, &bookmark.url);
+        }
+        if index_schema.get_field("title_prefix").is_ok() {
+            doc.add_text(self.schema.title_prefix, &bookmark.name);
+        }
+        if index_schema.get_field("ngram").is_ok() {
+            doc.add_text(self.schema.ngram, &bookmark.name);
+            if let Some(content_text) = content {
+                doc.add_text(self.schema.ngram, content_text);
+            }
+        }
+        if let (Some(content_text), Ok(_)) = (content, index_schema.get_field("code")) {
+            for block in fenced_code_blocks(content_text) {
+                doc.add_text(self.schema.code, block);
+            }
+        }
+        if let (Some(summary), Ok(_)) = (
+            content.and_then(summarize),
+            index_schema.get_field("summary"),
+        ) {
+            doc.add_text(self.schema.summary, &summary);
+        }
+        if let (Some(content_text), Ok(_)) = (content, index_schema.get_field("simhash")) {
+            let fingerprint = simhash(content_text);
+            if fingerprint != 0 {
+                doc.add_u64(self.schema.simhash, fingerprint);
+            }
+        }
+
         // Add page information if available (for PDFs)
         if let Some(page_info) = page_info {
             log_to_file_indexer(&format!(
@@ -140,6 +503,23 @@ impl BookmarkIndexer {
             // Serialize page offsets as JSON bytes
             let offsets_json = serde_json::to_vec(&page_info.page_offsets)?;
             doc.add_bytes(self.schema.page_offsets, &offsets_json);
+            if page_info.ocr && self.index.schema().get_field("ocr").is_ok() {
+                doc.add_bool(self.schema.ocr, true);
+            }
+            if let (Some(part), Ok(_)) =
+                (&page_info.part, self.index.schema().get_field("part_map"))
+            {
+                doc.add_bytes(self.schema.part_map, &serde_json::to_vec(part)?);
+            }
+            if self.index.schema().get_field("first_page").is_ok() {
+                doc.add_u64(self.schema.first_page, page_info.first_page() as u64);
+            }
+            if let (Some(chars), Ok(_)) = (
+                page_info.truncated_from,
+                self.index.schema().get_field("truncated_from"),
+            ) {
+                doc.add_u64(self.schema.truncated_from, chars as u64);
+            }
             log_to_file_indexer("create_document: page_info added");
         } else {
             // Add default values for non-PDF content
@@ -288,7 +668,8 @@ impl BookmarkIndexer {
         let content_chars: Vec<char> = content.chars().collect();
         let total_chars = content_chars.len();
 
-        let mut part_num = 0;
+        // (start page, end page, start char, end char) of each part, pages 0-indexed and exclusive
+        let mut ranges = Vec::new();
         let mut current_start_page = 0;
         let mut current_start_char = 0;
 
@@ -321,6 +702,20 @@ impl BookmarkIndexer {
                 }
             }
 
+            ranges.push((current_start_page, end_page, current_start_char, end_char));
+            current_start_page = end_page;
+            current_start_char = end_char;
+        }
+
+        // Every part carries the map of all parts, so a hit on one leads to the rest
+        let pages: Vec<(usize, usize)> = ranges
+            .iter()
+            .map(|&(start_page, end_page, _, _)| (start_page + 1, end_page))
+            .collect();
+
+        for (part_num, &(current_start_page, end_page, current_start_char, end_char)) in
+            ranges.iter().enumerate()
+        {
             // Extract content for this part
             let part_content: String = content_chars[current_start_char..end_char].iter().collect();
             let part_pages = end_page - current_start_page;
@@ -349,6 +744,15 @@ impl BookmarkIndexer {
                     .collect(),
                 content_type: page_info.content_type.clone(),
                 total_chars: part_content.chars().count(),
+                ocr: page_info.ocr,
+                part: Some(DocumentPart {
+                    parent_id: bookmark.id.clone(),
+                    index: part_num,
+                    count: ranges.len(),
+                    page_count: page_info.page_count,
+                    pages: pages.clone(),
+                }),
+                truncated_from: page_info.truncated_from,
             };
 
             // Create part bookmark with modified ID
@@ -371,17 +775,14 @@ impl BookmarkIndexer {
                 Some(&part_content),
                 Some(&part_page_info),
             )?;
-
-            part_num += 1;
-            current_start_page = end_page;
-            current_start_char = end_char;
         }
 
         log_to_file_indexer(&format!(
-            "index_bookmark_with_page_splitting: DONE - created {part_num} documents"
+            "index_bookmark_with_page_splitting: DONE - created {} documents",
+            ranges.len()
         ));
 
-        Ok(part_num)
+        Ok(ranges.len())
     }
 }
 
@@ -389,6 +790,7 @@ impl BookmarkIndexer {
 mod tests {
     use super::*;
     use tantivy::directory::MmapDirectory;
+    use tantivy::schema::Value;
     use tempfile::TempDir;
 
     fn create_test_index() -> (Index, BookmarkSchema, TempDir) {
@@ -398,16 +800,9 @@ mod tests {
         let index = Index::create(dir, schema.schema.clone(), Default::default()).unwrap();
 
         // Register Lindera tokenizer for tests
-        use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
-        use lindera::mode::{Mode, Penalty};
-        use lindera::segmenter::Segmenter;
-        use lindera_tantivy::tokenizer::LinderaTokenizer;
+        use crate::search::tokenizer::{LinderaDictionary, register_lindera_tokenizer_with};
 
-        let dictionary = load_dictionary_from_kind(DictionaryKind::IPADIC).unwrap();
-        let mode = Mode::Decompose(Penalty::default());
-        let segmenter = Segmenter::new(mode, dictionary, None);
-        let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
-        index.tokenizers().register("lang_ja", tokenizer);
+        register_lindera_tokenizer_with(&index, LinderaDictionary::default()).unwrap();
 
         (index, schema, temp_dir)
     }
@@ -420,6 +815,8 @@ mod tests {
             date_added: Some("1234567890000".to_string()),
             date_modified: None,
             folder_path: vec!["Bookmarks Bar".to_string(), "Tech".to_string()],
+            tags: vec![],
+            profile: None,
         }
     }
 
@@ -430,7 +827,11 @@ mod tests {
         let bookmark = create_test_bookmark();
 
         let doc = indexer
-            .create_document(&bookmark, Some("test content"), None)
+            .create_document(
+                &bookmark,
+                Some("Test content about indexing. More test content follows here."),
+                None,
+            )
             .unwrap();
 
         // Verify document has all required fields
@@ -438,6 +839,21 @@ mod tests {
         assert!(doc.get_first(schema.url).is_some());
         assert!(doc.get_first(schema.title).is_some());
         assert!(doc.get_first(schema.content).is_some());
+        assert!(
+            doc.get_first(schema.summary)
+                .and_then(|v| v.as_str())
+                .is_some_and(|summary| summary.starts_with("Test content"))
+        );
+        let tags: Vec<&str> = doc
+            .get_all(schema.tags)
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert!(tags.contains(&"test") && tags.contains(&"content"));
+        assert!(
+            doc.get_first(schema.simhash)
+                .and_then(|v| v.as_u64())
+                .is_some()
+        );
     }
 
     #[test]
@@ -453,6 +869,56 @@ mod tests {
         writer.commit().unwrap();
     }
 
+    #[test]
+    fn test_heading_enclosing() {
+        let heading = |text: &str, offset| Heading {
+            level: 2,
+            text: text.to_string(),
+            offset,
+        };
+        let headings = vec![heading("Install", 0), heading("Usage", 40)];
+        assert_eq!(Heading::enclosing(&headings, 10).unwrap().text, "Install");
+        assert_eq!(Heading::enclosing(&headings, 40).unwrap().text, "Usage");
+        assert_eq!(Heading::enclosing(&headings[1..], 10), None);
+
+        let terms = vec!["usage".to_string()];
+        assert_eq!(Heading::matching(&headings, &terms).unwrap().text, "Usage");
+        let deep = vec![Heading {
+            level: 4,
+            ..heading("Usage", 0)
+        }];
+        assert_eq!(Heading::matching(&deep, &terms), None);
+    }
+
+    #[test]
+    fn test_transcript_window() {
+        let cue = |start_ms, offset| TranscriptCue { start_ms, offset };
+        let cues = vec![cue(0, 0), cue(4_000, 20), cue(9_500, 45), cue(15_000, 80)];
+        assert_eq!(TranscriptCue::window(&cues, 0, Some(5_000)), 0..2);
+        // The cue playing at the start time is included
+        assert_eq!(TranscriptCue::window(&cues, 10_000, Some(15_000)), 2..3);
+        assert_eq!(TranscriptCue::window(&cues, 10_000, None), 2..4);
+        assert_eq!(TranscriptCue::window(&cues, 60_000, None), 3..4);
+        assert_eq!(TranscriptCue::window(&cues, 9_000, Some(1_000)), 1..1);
+        assert!(TranscriptCue::window(&[], 0, None).is_empty());
+
+        assert_eq!(parse_timestamp("90"), Some(90_000));
+        assert_eq!(parse_timestamp("1:30"), Some(90_000));
+        assert_eq!(parse_timestamp("1:02:03"), Some(3_723_000));
+        assert_eq!(parse_timestamp("1:75"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("soon"), None);
+        assert_eq!(format_timestamp(90_500), "1:30");
+        assert_eq!(format_timestamp(3_723_000), "1:02:03");
+    }
+
+    #[test]
+    fn test_fenced_code_blocks() {
+        let content = "Intro ```rust let x = 1;``` middle ``` ``` end ```unclosed";
+        assert_eq!(fenced_code_blocks(content), vec!["rust let x = 1;"]);
+        assert!(fenced_code_blocks("no code here").is_empty());
+    }
+
     #[test]
     fn test_extract_domain() {
         assert_eq!(
@@ -487,6 +953,9 @@ mod tests {
             page_offsets: vec![0, 100, 200],
             content_type: "pdf".to_string(),
             total_chars: 300,
+            ocr: true,
+            part: None,
+            truncated_from: None,
         };
 
         let mut writer = indexer.create_writer(10_000_000).unwrap();
@@ -499,6 +968,14 @@ mod tests {
             )
             .unwrap();
         writer.commit().unwrap();
+
+        let searcher = indexer.index().reader().unwrap().searcher();
+        let doc: TantivyDocument = searcher.doc(tantivy::DocAddress::new(0, 0)).unwrap();
+        assert_eq!(
+            doc.get_first(indexer.schema().ocr)
+                .and_then(|v| v.as_bool()),
+            Some(true)
+        );
     }
 
     #[test]
@@ -514,6 +991,9 @@ mod tests {
             page_offsets: vec![0, 20],
             content_type: "pdf".to_string(),
             total_chars: content.chars().count(),
+            ocr: false,
+            part: None,
+            truncated_from: None,
         };
 
         let mut writer = indexer.create_writer(10_000_000).unwrap();
@@ -544,6 +1024,9 @@ mod tests {
             page_offsets: vec![0, 50, 100],
             content_type: "pdf".to_string(),
             total_chars: 150,
+            ocr: false,
+            part: None,
+            truncated_from: None,
         };
 
         let mut writer = indexer.create_writer(10_000_000).unwrap();
@@ -576,6 +1059,9 @@ mod tests {
             page_offsets: vec![0, 25, 50, 75],
             content_type: "pdf".to_string(),
             total_chars: 100,
+            ocr: false,
+            part: None,
+            truncated_from: None,
         };
 
         let mut writer = indexer.create_writer(10_000_000).unwrap();
@@ -587,6 +1073,91 @@ mod tests {
 
         // Should create 2 documents (pages 1-2 and pages 3-4)
         assert_eq!(doc_count, 2);
+
+        // Each part knows its place in the whole PDF
+        let searcher = indexer.index().reader().unwrap().searcher();
+        let mut parts: Vec<DocumentPart> = (0..2)
+            .map(|doc_id| {
+                let doc: TantivyDocument =
+                    searcher.doc(tantivy::DocAddress::new(0, doc_id)).unwrap();
+                serde_json::from_slice(
+                    doc.get_first(indexer.schema().part_map)
+                        .and_then(|v| v.as_bytes())
+                        .unwrap(),
+                )
+                .unwrap()
+            })
+            .collect();
+        parts.sort_by_key(|part| part.index);
+        assert_eq!(parts[1].parent_id, "test-1");
+        assert_eq!((parts[1].index, parts[1].count), (1, 2));
+        assert_eq!(parts[1].page_count, 4);
+        assert_eq!(parts[0].pages, vec![(1, 2), (3, 4)]);
+        let first_pages: Vec<u64> = (0..2)
+            .map(|doc_id| {
+                let doc: TantivyDocument =
+                    searcher.doc(tantivy::DocAddress::new(0, doc_id)).unwrap();
+                doc.get_first(indexer.schema().first_page)
+                    .and_then(|v| v.as_u64())
+                    .unwrap()
+            })
+            .collect();
+        assert!(first_pages.contains(&1) && first_pages.contains(&3));
+    }
+
+    #[test]
+    fn test_truncate_page() {
+        let (index, schema, _temp) = create_test_index();
+        let indexer = BookmarkIndexer::new(index, schema.clone());
+        let bookmark = create_test_bookmark();
+
+        let content = format!("{}{}{}", "A".repeat(50), "B".repeat(50), "C".repeat(50));
+        let page_info = PageInfo {
+            page_count: 3,
+            page_offsets: vec![0, 50, 100],
+            content_type: "pdf".to_string(),
+            total_chars: 150,
+            ocr: false,
+            part: None,
+            truncated_from: None,
+        };
+        let structure = PageStructure {
+            headings: vec![
+                Heading {
+                    level: 1,
+                    text: "Start".to_string(),
+                    offset: 0,
+                },
+                Heading {
+                    level: 1,
+                    text: "End".to_string(),
+                    offset: 120,
+                },
+            ],
+            ..PageStructure::default()
+        };
+        assert!(truncate_page(&content, Some(&page_info), &structure, 150).is_none());
+
+        let page = truncate_page(&content, Some(&page_info), &structure, 80).unwrap();
+        assert_eq!(page.content.chars().count(), 80);
+        assert_eq!(page.original_chars(), 150);
+        assert_eq!(page.structure.headings.len(), 1);
+        let truncated_info = page.page_info.unwrap();
+        assert_eq!(truncated_info.page_offsets, [0, 50]);
+        assert_eq!(truncated_info.page_count, 2);
+
+        let doc = indexer
+            .create_document(&bookmark, Some(&page.content), Some(&truncated_info))
+            .unwrap();
+        assert_eq!(
+            doc.get_first(schema.truncated_from)
+                .and_then(|v| v.as_u64()),
+            Some(150)
+        );
+        let doc = indexer
+            .create_document(&bookmark, Some(&content), Some(&page_info))
+            .unwrap();
+        assert!(doc.get_first(schema.truncated_from).is_none());
     }
 
     #[test]
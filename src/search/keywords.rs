@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::summary::STOP_WORDS;
+
+/// Only the start of long documents is scanned for keywords
+const MAX_SCANNED_CHARS: usize = 50_000;
+
+/// A title occurrence counts as this many occurrences in the body
+const TITLE_WEIGHT: usize = 3;
+
+/// Longest kanji run taken as one term (longer runs are usually whole clauses)
+const MAX_KANJI_RUN: usize = 8;
+
+/// A tag and how many bookmarks carry it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TopicCount {
+    pub topic: String,
+    pub count: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Script {
+    Word,
+    Katakana,
+    Kanji,
+    Other,
+}
+
+fn script(c: char) -> Script {
+    match c {
+        '\u{3040}'..='\u{309F}' => Script::Other, // Hiragana
+        '\u{30A0}'..='\u{30FF}' => Script::Katakana,
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => Script::Kanji,
+        c if c.is_alphanumeric() || c == '-' || c == '+' || c == '#' => Script::Word,
+        _ => Script::Other,
+    }
+}
+
+/// Candidate terms: lowercase words, katakana runs and kanji compounds
+///
+/// Runs of one script are a cheap stand-in for Japanese segmentation:
+/// katakana runs are mostly loanwords and kanji runs mostly compound nouns,
+/// while hiragana mostly carries grammar and is skipped.
+pub fn candidates(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut run = String::new();
+    let mut run_script = Script::Other;
+
+    let mut flush = |run: &mut String, run_script: Script| {
+        let term = run.trim_matches(|c| c == '-' || c == '+' || c == '#');
+        let len = term.chars().count();
+        let keep = match run_script {
+            Script::Word => {
+                len >= 3 && !term.chars().all(|c| c.is_ascii_digit()) && !STOP_WORDS.contains(&term)
+            }
+            Script::Katakana => len >= 2 && term != "ー",
+            Script::Kanji => (2..=MAX_KANJI_RUN).contains(&len),
+            Script::Other => false,
+        };
+        if keep {
+            terms.push(term.to_string());
+        }
+        run.clear();
+    };
+
+    for c in text.chars() {
+        let current = script(c);
+        if current != run_script {
+            flush(&mut run, run_script);
+            run_script = current;
+        }
+        if current != Script::Other {
+            run.extend(c.to_lowercase());
+        }
+    }
+    flush(&mut run, run_script);
+    terms
+}
+
+/// Up to `max` keywords for a page, best first
+///
+/// Terms score by frequency in the body plus title matches, favoring longer
+/// terms a little. Body-only terms need at least two occurrences.
+pub fn extract_keywords(title: &str, content: &str, max: usize) -> Vec<String> {
+    if max == 0 {
+        return Vec::new();
+    }
+    let scanned: String = content.chars().take(MAX_SCANNED_CHARS).collect();
+
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for term in candidates(&scanned) {
+        counts.entry(term).or_default().0 += 1;
+    }
+    for term in candidates(title) {
+        counts.entry(term).or_default().1 += 1;
+    }
+
+    let mut scored: Vec<(f32, String)> = counts
+        .into_iter()
+        .filter(|(_, (body, title))| *title > 0 || *body >= 2)
+        .map(|(term, (body, title))| {
+            let frequency = (body + TITLE_WEIGHT * title) as f32;
+            let length = 1.0 + (term.chars().count() as f32).ln() / 4.0;
+            (frequency * length, term)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.into_iter().take(max).map(|(_, term)| term).collect()
+}
+
+/// Most common tags across bookmarks, with ties in alphabetical order
+pub fn count_topics<'a>(
+    tags: impl IntoIterator<Item = &'a [String]>,
+    limit: usize,
+) -> Vec<TopicCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for bookmark_tags in tags {
+        let mut seen: Vec<&str> = bookmark_tags.iter().map(String::as_str).collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for tag in seen {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    merge_topics(
+        counts.into_iter().map(|(topic, count)| TopicCount {
+            topic: topic.to_string(),
+            count,
+        }),
+        limit,
+    )
+}
+
+/// Add up topic counts (e.g. from several indexes) and keep the top `limit`
+pub fn merge_topics(topics: impl IntoIterator<Item = TopicCount>, limit: usize) -> Vec<TopicCount> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for topic in topics {
+        *counts.entry(topic.topic).or_default() += topic.count;
+    }
+    let mut topics: Vec<TopicCount> = counts
+        .into_iter()
+        .map(|(topic, count)| TopicCount { topic, count })
+        .collect();
+    topics.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.topic.cmp(&b.topic)));
+    topics.truncate(limit);
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_keywords() {
+        let keywords = extract_keywords(
+            "Tantivy query parser",
+            "The Tantivy query parser turns text into queries. Each query is parsed \
+             by the parser, and the parser supports phrase queries. Released in 2024, 2024.",
+            3,
+        );
+        assert_eq!(keywords, vec!["parser", "query", "tantivy"]);
+
+        let keywords = extract_keywords(
+            "全文検索エンジン",
+            "データベースの全文検索では、データベースに索引を作ります。索引があると検索が速い。",
+            4,
+        );
+        assert!(keywords.contains(&"データベース".to_string()));
+        assert!(keywords.contains(&"索引".to_string()));
+        assert!(keywords.contains(&"全文検索".to_string()));
+
+        assert!(extract_keywords("Title", "body", 0).is_empty());
+    }
+
+    #[test]
+    fn test_count_topics() {
+        let tags = [
+            vec!["rust".to_string(), "search".to_string()],
+            vec!["rust".to_string(), "rust".to_string()],
+            vec!["search".to_string(), "web".to_string()],
+        ];
+        let topics = count_topics(tags.iter().map(Vec::as_slice), 2);
+        assert_eq!(
+            topics,
+            vec![
+                TopicCount {
+                    topic: "rust".to_string(),
+                    count: 2
+                },
+                TopicCount {
+                    topic: "search".to_string(),
+                    count: 2
+                },
+            ]
+        );
+
+        let merged = merge_topics(
+            topics.into_iter().chain(count_topics([&tags[2][..]], 10)),
+            1,
+        );
+        assert_eq!(merged[0].topic, "search");
+        assert_eq!(merged[0].count, 3);
+    }
+}
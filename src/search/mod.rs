@@ -1,18 +1,41 @@
 // Module declarations
+pub mod changeset;
+pub mod clustering;
+pub mod collection_stats;
 pub mod common;
+pub mod context_builder;
+pub mod duplicates;
+pub mod embeddings;
+pub mod index_aliases;
+pub mod index_defaults;
+pub mod index_lock;
+pub mod index_registry;
+pub mod index_trash;
+pub mod index_watcher;
 pub mod indexer;
+pub mod keywords;
 pub mod multi_index;
 pub mod query_parser;
+pub mod redaction;
 pub mod schema;
 pub mod scored_snippet;
 pub mod search_manager;
 pub mod search_manager_trait;
+pub mod summary;
+pub mod sync_metadata;
+pub mod timings;
 pub mod tokenizer;
 pub mod unified_searcher;
 
 // Re-export public APIs
-pub use common::IndexStats;
+pub use collection_stats::CollectionStats;
+pub use common::{BookmarkEntry, IndexStats};
+pub use embeddings::SemanticHit;
+pub use index_lock::IndexLock;
+pub use index_registry::IndexRegistry;
+pub use index_trash::IndexTrash;
 pub use indexer::PageInfo;
+pub use keywords::TopicCount;
 pub use multi_index::MultiIndexSearchManager;
 pub use search_manager::SearchManager;
-pub use unified_searcher::{SearchParams, SearchResult};
+pub use unified_searcher::{PartHit, SearchParams, SearchResult, SnippetRange, Suggestion};
@@ -2,17 +2,28 @@ use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::{info, warn};
 
 use crate::config::Config;
+use crate::search::keywords::merge_topics;
 use crate::search::search_manager_trait::SearchManagerTrait;
-use crate::search::{SearchManager, SearchParams, SearchResult};
+use crate::search::sync_metadata::IndexingProgress;
+use crate::search::timings::record_index;
+use crate::search::tokenizer::AnalyzedToken;
+use crate::search::{
+    BookmarkEntry, CollectionStats, IndexStats, SearchManager, SearchParams, SearchResult,
+    SemanticHit, Suggestion, TopicCount,
+};
 
 /// Multi-index search manager for searching across multiple indices
 #[derive(Debug)]
 pub struct MultiIndexSearchManager {
-    managers: Vec<SearchManager>,
+    managers: Vec<Arc<SearchManager>>,
     index_names: Vec<String>,
+    /// Keep the most recently modified copy of a URL found in several indexes
+    prefer_newer: bool,
 }
 
 impl MultiIndexSearchManager {
@@ -37,7 +48,7 @@ impl MultiIndexSearchManager {
             match SearchManager::open_readonly(name) {
                 Ok(manager) => {
                     info!("Successfully loaded index: {}", name);
-                    managers.push(manager);
+                    managers.push(Arc::new(manager));
                 }
                 Err(e) => {
                     warn!("Failed to load index '{}': {}", name, e);
@@ -68,6 +79,20 @@ impl MultiIndexSearchManager {
                 .filter(|n| !failed_indices.contains(n))
                 .cloned()
                 .collect(),
+            prefer_newer: crate::config::settings().index.prefer_newer_duplicates,
+        })
+    }
+
+    /// Search already opened indexes, e.g. those of an alias from the index registry
+    pub fn from_managers(indexes: Vec<(String, Arc<SearchManager>)>) -> Result<Self> {
+        if indexes.is_empty() {
+            anyhow::bail!("No index names provided");
+        }
+        let (index_names, managers) = indexes.into_iter().unzip();
+        Ok(Self {
+            managers,
+            index_names,
+            prefer_newer: crate::config::settings().index.prefer_newer_duplicates,
         })
     }
 
@@ -80,8 +105,10 @@ impl MultiIndexSearchManager {
             let index_name = &self.index_names[idx];
             info!("Searching in index: {}", index_name);
 
+            let started = std::time::Instant::now();
             match manager.search(query, limit * 2) {
                 Ok(results) => {
+                    record_index(index_name, started.elapsed(), results.len());
                     info!("Found {} results in {}", results.len(), index_name);
                     all_results.extend(results);
                 }
@@ -99,17 +126,28 @@ impl MultiIndexSearchManager {
     }
 
     /// Merge results from multiple indices
+    ///
+    /// A URL found in several indexes is listed once. With `prefer_newer` the
+    /// copy with the latest `date_modified` is kept, ranked by the best score
+    /// of all copies; otherwise (or when the dates don't differ) the copy with
+    /// the highest score wins.
     fn merge_results(&self, results: Vec<SearchResult>, limit: usize) -> Vec<SearchResult> {
-        // Use HashMap to deduplicate by URL, keeping highest score
         let mut url_map: HashMap<String, SearchResult> = HashMap::new();
 
         for result in results {
             url_map
                 .entry(result.url.clone())
                 .and_modify(|existing| {
-                    if result.score > existing.score {
+                    let freshness = if self.prefer_newer {
+                        modified_at(&result).cmp(&modified_at(existing))
+                    } else {
+                        std::cmp::Ordering::Equal
+                    };
+                    let score = existing.score.max(result.score);
+                    if freshness.is_gt() || (freshness.is_eq() && result.score > existing.score) {
                         *existing = result.clone();
                     }
+                    existing.score = score;
                 })
                 .or_insert(result);
         }
@@ -147,6 +185,14 @@ impl MultiIndexSearchManager {
     }
 }
 
+/// Modification time of a result, `None` (older than any date) when unknown
+fn modified_at(result: &SearchResult) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    result
+        .date_modified
+        .as_deref()
+        .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+}
+
 #[async_trait]
 impl SearchManagerTrait for MultiIndexSearchManager {
     async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
@@ -160,6 +206,16 @@ impl SearchManagerTrait for MultiIndexSearchManager {
         self.search_multi(query, params.limit)
     }
 
+    async fn semantic_search(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>> {
+        let mut hits = Vec::new();
+        for manager in &self.managers {
+            hits.extend(manager.semantic_search(query, limit).await?);
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
     async fn get_content_by_url(&self, url: &str) -> Result<Option<String>> {
         // Try to get content from any index that has it
         for manager in &self.managers {
@@ -170,6 +226,24 @@ impl SearchManagerTrait for MultiIndexSearchManager {
         Ok(None)
     }
 
+    async fn get_markdown_by_url(&self, url: &str) -> Result<Option<String>> {
+        for manager in &self.managers {
+            if let Ok(Some(markdown)) = manager.get_markdown_by_url(url).await {
+                return Ok(Some(markdown));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_html_by_url(&self, url: &str) -> Result<Option<String>> {
+        for manager in &self.managers {
+            if let Ok(Some(html)) = manager.get_html_by_url(url).await {
+                return Ok(Some(html));
+            }
+        }
+        Ok(None)
+    }
+
     async fn get_page_range_content(
         &self,
         url: &str,
@@ -207,6 +281,110 @@ impl SearchManagerTrait for MultiIndexSearchManager {
         Ok(None)
     }
 
+    async fn get_transcript_range_content(
+        &self,
+        url: &str,
+        start_ms: u64,
+        end_ms: Option<u64>,
+    ) -> Result<Option<String>> {
+        // Same as pages: report a range error only if no index has the window
+        let mut range_error: Option<anyhow::Error> = None;
+
+        for manager in &self.managers {
+            match manager
+                .get_transcript_range_content(url, start_ms, end_ms)
+                .await
+            {
+                Ok(Some(content)) => return Ok(Some(content)),
+                Ok(None) => continue,
+                Err(e) => {
+                    let err_msg = e.to_string().to_lowercase();
+                    if err_msg.contains("transcript") || err_msg.contains("time") {
+                        range_error = Some(e);
+                    }
+                }
+            }
+        }
+
+        if let Some(e) = range_error {
+            return Err(e);
+        }
+
+        Ok(None)
+    }
+
+    async fn list_bookmarks(&self) -> Result<Vec<BookmarkEntry>> {
+        let mut entries = Vec::new();
+        for manager in &self.managers {
+            entries.extend(manager.list_bookmarks().await?);
+        }
+        Ok(entries)
+    }
+
+    async fn get_bookmark_by_id(&self, id: &str) -> Result<Option<BookmarkEntry>> {
+        for manager in &self.managers {
+            if let Some(entry) = manager.get_bookmark_by_id(id).await? {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    fn get_stats(&self) -> Result<IndexStats> {
+        let mut total = IndexStats {
+            total_documents: 0,
+            bookmark_count: 0,
+            index_size_bytes: 0,
+        };
+        for manager in &self.managers {
+            let stats = manager.get_stats()?;
+            total.total_documents += stats.total_documents;
+            total.bookmark_count += stats.bookmark_count;
+            total.index_size_bytes += stats.index_size_bytes;
+        }
+        Ok(total)
+    }
+
+    fn get_collection_stats(&self) -> Result<CollectionStats> {
+        let mut total = CollectionStats::default();
+        for manager in &self.managers {
+            total.merge(SearchManagerTrait::get_collection_stats(manager.as_ref())?);
+        }
+        Ok(total)
+    }
+
+    fn get_topics(&self, limit: usize) -> Result<Vec<TopicCount>> {
+        let mut topics = Vec::new();
+        for manager in &self.managers {
+            topics.extend(SearchManagerTrait::get_topics(
+                manager.as_ref(),
+                usize::MAX,
+            )?);
+        }
+        Ok(merge_topics(topics, limit))
+    }
+
+    fn typeahead(&self, query: &str, limit: usize) -> Result<Vec<Suggestion>> {
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for manager in &self.managers {
+            for suggestion in SearchManagerTrait::typeahead(manager.as_ref(), query, limit)? {
+                match suggestions.iter_mut().find(|s| s.url == suggestion.url) {
+                    Some(existing) if existing.score < suggestion.score => *existing = suggestion,
+                    Some(_) => {}
+                    None => suggestions.push(suggestion),
+                }
+            }
+        }
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+
+    fn tokenize(&self, text: &str, field: &str) -> Result<Vec<AnalyzedToken>> {
+        // Indexes can use different dictionaries; the first one is shown
+        SearchManagerTrait::tokenize(self.managers[0].as_ref(), text, field)
+    }
+
     fn get_indexing_status(&self) -> String {
         self.get_indexing_status_string()
     }
@@ -214,6 +392,31 @@ impl SearchManagerTrait for MultiIndexSearchManager {
     fn is_indexing_complete(&self) -> bool {
         true // Multi-index always uses pre-built indices
     }
+
+    fn indexing_progress(&self) -> Option<IndexingProgress> {
+        None // Reported per index
+    }
+
+    fn default_snippet_length(&self) -> Option<usize> {
+        // Each index makes its own snippets; keep the longest
+        self.managers
+            .iter()
+            .filter_map(|manager| manager.index_defaults().snippet_length)
+            .max()
+    }
+
+    fn reload(&self) -> Result<()> {
+        self.managers
+            .iter()
+            .try_for_each(|manager| manager.reload())
+    }
+
+    fn index_dirs(&self) -> Vec<PathBuf> {
+        self.managers
+            .iter()
+            .map(|manager| manager.index_path().to_path_buf())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -281,6 +484,7 @@ mod tests {
         let manager = MultiIndexSearchManager {
             managers: vec![],
             index_names: vec![],
+            prefer_newer: true,
         };
 
         let results = vec![
@@ -291,10 +495,8 @@ mod tests {
                 snippet: "Snippet 1".to_string(),
                 score: 0.8,
                 context_type: Some("ImportantNote".to_string()),
-                full_content: None,
                 folder_path: "folder1".to_string(),
-                last_indexed: None,
-                page_number: None,
+                ..Default::default()
             },
             SearchResult {
                 id: "2".to_string(),
@@ -303,10 +505,8 @@ mod tests {
                 snippet: "Snippet 2".to_string(),
                 score: 0.9, // Higher score
                 context_type: Some("ImportantNote".to_string()),
-                full_content: None,
                 folder_path: "folder2".to_string(),
-                last_indexed: None,
-                page_number: None,
+                ..Default::default()
             },
             SearchResult {
                 id: "3".to_string(),
@@ -315,10 +515,8 @@ mod tests {
                 snippet: "Other snippet".to_string(),
                 score: 0.7,
                 context_type: Some("RegularText".to_string()),
-                full_content: None,
                 folder_path: "folder3".to_string(),
-                last_indexed: None,
-                page_number: None,
+                ..Default::default()
             },
         ];
 
@@ -336,11 +534,46 @@ mod tests {
         assert_eq!(merged[1].url, "http://other.com");
     }
 
+    #[test]
+    fn test_merge_results_prefers_newer() {
+        let result = |title: &str, score: f32, date_modified: Option<&str>| SearchResult {
+            id: title.to_string(),
+            url: "http://example.com".to_string(),
+            title: title.to_string(),
+            score,
+            date_modified: date_modified.map(str::to_string),
+            ..Default::default()
+        };
+        let results = || {
+            vec![
+                result("Stale", 0.9, Some("2024-01-01T00:00:00+00:00")),
+                result("Fresh", 0.6, Some("2025-06-01T00:00:00+00:00")),
+                result("Undated", 0.95, None),
+            ]
+        };
+
+        let mut manager = MultiIndexSearchManager {
+            managers: vec![],
+            index_names: vec![],
+            prefer_newer: true,
+        };
+        let merged = manager.merge_results(results(), 10);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].title, "Fresh");
+        // Ranked by the best copy
+        assert_eq!(merged[0].score, 0.95);
+
+        manager.prefer_newer = false;
+        let merged = manager.merge_results(results(), 10);
+        assert_eq!(merged[0].title, "Undated");
+    }
+
     #[test]
     fn test_merge_results_limit() {
         let manager = MultiIndexSearchManager {
             managers: vec![],
             index_names: vec![],
+            prefer_newer: true,
         };
 
         let mut results = Vec::new();
@@ -352,10 +585,8 @@ mod tests {
                 snippet: format!("Snippet {i}"),
                 score: (10 - i) as f32 / 10.0,
                 context_type: Some("RegularText".to_string()),
-                full_content: None,
                 folder_path: format!("folder{i}"),
-                last_indexed: None,
-                page_number: None,
+                ..Default::default()
             });
         }
 
@@ -1,5 +1,66 @@
+use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt;
 
+/// Prefix scoping a word or phrase to code blocks, e.g. `code:IndexWriter::commit`
+pub const CODE_SCOPE: &str = "code:";
+
+/// Characters with a meaning in tantivy's query syntax (as does a leading `-`)
+///
+/// Words holding them are searched as plain text, see `QueryParser::escape`.
+pub const RESERVED_CHARS: &[char] = &[
+    ':', '~', '(', ')', '[', ']', '{', '}', '^', '!', '*', '?', '+', '"', '\\', '/', '<', '>', '=',
+    '`',
+];
+
+/// Something in a query that was worked around instead of failing the search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryWarning {
+    pub kind: QueryWarningKind,
+    /// The part of the query concerned
+    pub term: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWarningKind {
+    /// A word with reserved characters or an operator name was searched as plain text
+    Escaped,
+    /// A word made of reserved characters only was left out
+    Ignored,
+    /// A phrase without its closing quote runs to the end of the query
+    UnclosedPhrase,
+    /// A backslash at the end of the query had nothing to escape
+    TrailingEscape,
+}
+
+impl QueryWarning {
+    fn new(kind: QueryWarningKind, term: &str) -> Self {
+        let message = match kind {
+            QueryWarningKind::Escaped => format!(
+                "{term:?} was searched as plain text; field:, ~, parentheses and operators are not query syntax here"
+            ),
+            QueryWarningKind::Ignored => {
+                format!("{term:?} has nothing to search for and was ignored")
+            }
+            QueryWarningKind::UnclosedPhrase => {
+                format!(
+                    "Phrase {term:?} has no closing quote; it was searched up to the end of the query"
+                )
+            }
+            QueryWarningKind::TrailingEscape => {
+                "The backslash at the end of the query escapes nothing and was ignored".to_string()
+            }
+        };
+        Self {
+            kind,
+            term: term.to_string(),
+            message,
+        }
+    }
+}
+
 /// Represents a parsed query term
 #[derive(Debug, Clone, PartialEq)]
 pub enum QueryTerm {
@@ -7,6 +68,8 @@ pub enum QueryTerm {
     Phrase(String),
     /// A single word or token
     Word(String),
+    /// An identifier or snippet searched in code blocks (`code:` prefix)
+    Code(String),
 }
 
 impl fmt::Display for QueryTerm {
@@ -14,6 +77,10 @@ impl fmt::Display for QueryTerm {
         match self {
             QueryTerm::Phrase(phrase) => write!(f, "\"{phrase}\""),
             QueryTerm::Word(word) => write!(f, "{word}"),
+            QueryTerm::Code(code) if code.contains(char::is_whitespace) => {
+                write!(f, "{CODE_SCOPE}\"{code}\"")
+            }
+            QueryTerm::Code(code) => write!(f, "{CODE_SCOPE}{code}"),
         }
     }
 }
@@ -23,7 +90,7 @@ pub struct QueryParser;
 
 impl QueryParser {
     /// Parse a query string into query terms
-    /// Supports phrases in double quotes and regular words
+    /// Supports phrases in double quotes, regular words and `code:` scopes
     ///
     /// # Examples
     /// ```
@@ -35,10 +102,21 @@ impl QueryParser {
     /// assert_eq!(terms[2], QueryTerm::Word("documentation".to_string()));
     /// ```
     pub fn parse(query: &str) -> Vec<QueryTerm> {
+        Self::parse_with_warnings(query).0
+    }
+
+    /// Like `parse`, also reporting what was worked around
+    ///
+    /// Parsing is lenient: reserved characters are searched as text, stray
+    /// ones are dropped and an unclosed phrase ends with the query. Callers
+    /// wanting strict syntax can treat any warning as an error.
+    pub fn parse_with_warnings(query: &str) -> (Vec<QueryTerm>, Vec<QueryWarning>) {
         let mut terms = Vec::new();
+        let mut warnings = Vec::new();
         let chars = query.chars().peekable();
         let mut current = String::new();
         let mut in_phrase = false;
+        let mut code_phrase = false;
         let mut escape_next = false;
 
         for ch in chars {
@@ -56,18 +134,23 @@ impl QueryParser {
                     if in_phrase {
                         // End of phrase
                         if !current.trim().is_empty() {
-                            terms.push(QueryTerm::Phrase(current.trim().to_string()));
+                            terms.push(Self::phrase_term(current.trim(), code_phrase));
                         }
                         current.clear();
                         in_phrase = false;
                     } else {
-                        // Start of phrase - save any accumulated word first
-                        if !current.trim().is_empty() {
-                            for word in current.split_whitespace() {
-                                if !word.is_empty() {
-                                    terms.push(QueryTerm::Word(word.to_string()));
-                                }
-                            }
+                        // Start of phrase - save any accumulated word first,
+                        // except a bare `code:` that scopes the phrase
+                        code_phrase = current.ends_with(CODE_SCOPE)
+                            && current[..current.len() - CODE_SCOPE.len()]
+                                .chars()
+                                .last()
+                                .is_none_or(char::is_whitespace);
+                        if code_phrase {
+                            current.truncate(current.len() - CODE_SCOPE.len());
+                        }
+                        for word in current.split_whitespace() {
+                            Self::push_word(word, &mut terms, &mut warnings);
                         }
                         current.clear();
                         in_phrase = true;
@@ -80,7 +163,7 @@ impl QueryParser {
                     } else {
                         // End of word
                         if !current.trim().is_empty() {
-                            terms.push(QueryTerm::Word(current.trim().to_string()));
+                            Self::push_word(current.trim(), &mut terms, &mut warnings);
                         }
                         current.clear();
                     }
@@ -91,20 +174,81 @@ impl QueryParser {
             }
         }
 
+        if escape_next {
+            warnings.push(QueryWarning::new(QueryWarningKind::TrailingEscape, "\\"));
+        }
+
         // Handle any remaining content
         if in_phrase && !current.trim().is_empty() {
             // Unclosed phrase - treat as phrase anyway
-            terms.push(QueryTerm::Phrase(current.trim().to_string()));
+            warnings.push(QueryWarning::new(
+                QueryWarningKind::UnclosedPhrase,
+                current.trim(),
+            ));
+            terms.push(Self::phrase_term(current.trim(), code_phrase));
         } else if !current.trim().is_empty() {
             // Remaining words
             for word in current.split_whitespace() {
-                if !word.is_empty() {
-                    terms.push(QueryTerm::Word(word.to_string()));
-                }
+                Self::push_word(word, &mut terms, &mut warnings);
             }
         }
 
-        terms
+        (terms, warnings)
+    }
+
+    /// Add a word, leaving out those with nothing but reserved characters
+    fn push_word(word: &str, terms: &mut Vec<QueryTerm>, warnings: &mut Vec<QueryWarning>) {
+        let term = Self::word_term(word);
+        if let QueryTerm::Word(word) = &term {
+            if word
+                .chars()
+                .all(|c| c == '-' || RESERVED_CHARS.contains(&c))
+            {
+                warnings.push(QueryWarning::new(QueryWarningKind::Ignored, word));
+                return;
+            }
+            if Self::needs_escaping(word) {
+                warnings.push(QueryWarning::new(QueryWarningKind::Escaped, word));
+            }
+        }
+        terms.push(term);
+    }
+
+    fn needs_escaping(word: &str) -> bool {
+        word.contains(RESERVED_CHARS)
+            || word.starts_with('-')
+            || matches!(word, "AND" | "OR" | "NOT")
+    }
+
+    /// `word` as input for tantivy's query parser that matches it literally
+    ///
+    /// Words with reserved characters become a quoted phrase, analyzed like
+    /// any other text; `"` and `\` inside it are dropped.
+    pub fn escape(word: &str) -> Cow<'_, str> {
+        if !Self::needs_escaping(word) {
+            return Cow::Borrowed(word);
+        }
+        let text: String = word
+            .chars()
+            .map(|c| if matches!(c, '"' | '\\') { ' ' } else { c })
+            .collect();
+        Cow::Owned(format!("\"{}\"", text.trim()))
+    }
+
+    /// A word, or a code term when it carries the `code:` prefix
+    fn word_term(word: &str) -> QueryTerm {
+        match word.strip_prefix(CODE_SCOPE) {
+            Some(code) if !code.is_empty() => QueryTerm::Code(code.to_string()),
+            _ => QueryTerm::Word(word.to_string()),
+        }
+    }
+
+    fn phrase_term(phrase: &str, code: bool) -> QueryTerm {
+        if code {
+            QueryTerm::Code(phrase.to_string())
+        } else {
+            QueryTerm::Phrase(phrase.to_string())
+        }
     }
 
     /// Check if the query contains any phrase terms
@@ -125,6 +269,17 @@ impl QueryParser {
             .collect()
     }
 
+    /// Extract all code-scoped terms
+    pub fn extract_code(terms: &[QueryTerm]) -> Vec<String> {
+        terms
+            .iter()
+            .filter_map(|term| match term {
+                QueryTerm::Code(code) => Some(code.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Extract all words from query terms
     pub fn extract_words(terms: &[QueryTerm]) -> Vec<String> {
         terms
@@ -216,6 +371,61 @@ mod tests {
         assert_eq!(terms[3], QueryTerm::Word("phrase".to_string()));
     }
 
+    #[test]
+    fn test_parse_code_scope() {
+        let terms = QueryParser::parse(r#"code:IndexWriter::commit tantivy code:"let mut writer""#);
+
+        assert_eq!(terms.len(), 3);
+        assert_eq!(terms[0], QueryTerm::Code("IndexWriter::commit".to_string()));
+        assert_eq!(terms[1], QueryTerm::Word("tantivy".to_string()));
+        assert_eq!(terms[2], QueryTerm::Code("let mut writer".to_string()));
+        assert_eq!(terms[2].to_string(), r#"code:"let mut writer""#);
+        assert_eq!(
+            QueryParser::extract_code(&terms),
+            vec!["IndexWriter::commit", "let mut writer"]
+        );
+
+        // A bare or embedded prefix stays a plain word
+        let terms = QueryParser::parse(r#"code: barcode:"x""#);
+        assert_eq!(terms[0], QueryTerm::Word("code:".to_string()));
+        assert_eq!(terms[1], QueryTerm::Word("barcode:".to_string()));
+        assert_eq!(terms[2], QueryTerm::Phrase("x".to_string()));
+    }
+
+    #[test]
+    fn test_parse_reserved_characters() {
+        let (terms, warnings) =
+            QueryParser::parse_with_warnings(r#"std::mem::take ( -rust ~ AND "unclosed \"#);
+
+        assert_eq!(
+            terms,
+            vec![
+                QueryTerm::Word("std::mem::take".to_string()),
+                QueryTerm::Word("-rust".to_string()),
+                QueryTerm::Word("AND".to_string()),
+                QueryTerm::Phrase("unclosed".to_string()),
+            ]
+        );
+        let kinds: Vec<_> = warnings.iter().map(|w| (w.kind, w.term.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (QueryWarningKind::Escaped, "std::mem::take"),
+                (QueryWarningKind::Ignored, "("),
+                (QueryWarningKind::Escaped, "-rust"),
+                (QueryWarningKind::Ignored, "~"),
+                (QueryWarningKind::Escaped, "AND"),
+                (QueryWarningKind::TrailingEscape, "\\"),
+                (QueryWarningKind::UnclosedPhrase, "unclosed"),
+            ]
+        );
+        assert!(QueryParser::parse_with_warnings("React hooks").1.is_empty());
+
+        assert_eq!(QueryParser::escape("hooks"), "hooks");
+        assert_eq!(QueryParser::escape("std::mem"), "\"std::mem\"");
+        assert_eq!(QueryParser::escape("say\"hi"), "\"say hi\"");
+    }
+
     #[test]
     fn test_has_phrases() {
         let terms = vec![
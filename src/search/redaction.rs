@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use super::indexer::PageStructure;
+use crate::config::RedactionSettings;
+
+const EMAIL_PATTERN: &str =
+    r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b";
+
+/// International numbers, numbers with an area code in parentheses, and
+/// bare numbers in three dash-separated groups (dates have too few digits)
+const PHONE_PATTERN: &str = r"\+\d{1,3}[ .-]?(?:\(\d{1,4}\)[ .-]?)?\d{1,4}(?:[ .-]?\d{2,4}){1,3}\b|\(\d{1,4}\)[ .-]?\d{2,4}[ .-]?\d{3,4}\b|\b\d{2,4}-\d{2,4}-\d{3,4}\b";
+
+/// 13 to 19 digits, optionally grouped by spaces or dashes; checked with Luhn
+const CARD_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+
+/// Replaces personal data in page content before it is indexed or embedded
+///
+/// Matches become a label such as `[email]` padded with `*` to the length of
+/// the match, so character offsets into the content (PDF pages, headings,
+/// snippet ranges) stay valid.
+#[derive(Debug)]
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    label: &'static str,
+    /// Only replace matches that pass the Luhn check
+    luhn: bool,
+}
+
+static CONFIGURED: OnceLock<Option<Redactor>> = OnceLock::new();
+
+impl Redactor {
+    pub fn new(settings: &RedactionSettings) -> Result<Self> {
+        let mut rules = Vec::new();
+        // Card numbers first, so their digits aren't taken for phone numbers
+        if settings.credit_cards {
+            rules.push(Rule::builtin(CARD_PATTERN, "[card]", true));
+        }
+        if settings.emails {
+            rules.push(Rule::builtin(EMAIL_PATTERN, "[email]", false));
+        }
+        if settings.phone_numbers {
+            rules.push(Rule::builtin(PHONE_PATTERN, "[phone]", false));
+        }
+        for pattern in &settings.patterns {
+            rules.push(Rule {
+                regex: Regex::new(pattern)
+                    .with_context(|| format!("Invalid redaction pattern {pattern:?}"))?,
+                label: "[redacted]",
+                luhn: false,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// The redactor for `[redaction]` in the settings, None when it is disabled
+    ///
+    /// Fails on invalid custom patterns, so content is never indexed unredacted.
+    pub fn configured() -> Result<Option<&'static Self>> {
+        if let Some(redactor) = CONFIGURED.get() {
+            return Ok(redactor.as_ref());
+        }
+        let settings = &crate::config::settings().redaction;
+        let redactor = settings.enabled.then(|| Self::new(settings)).transpose()?;
+        Ok(CONFIGURED.get_or_init(|| redactor).as_ref())
+    }
+
+    /// `text` with every match replaced, borrowed when nothing matched
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for rule in &self.rules {
+            let redacted = rule.regex.replace_all(&text, |caps: &regex::Captures| {
+                let found = &caps[0];
+                if rule.luhn && !passes_luhn(found) {
+                    return found.to_string();
+                }
+                mask(found, rule.label)
+            });
+            // Card-like numbers failing the Luhn check come back unchanged
+            if redacted != text {
+                text = Cow::Owned(redacted.into_owned());
+            }
+        }
+        text
+    }
+
+    /// The text of a page's headings, code blocks, markup and HTML, redacted
+    pub fn redact_structure(&self, structure: &PageStructure) -> PageStructure {
+        let mut redacted = structure.clone();
+        for heading in &mut redacted.headings {
+            heading.text = self.redact(&heading.text).into_owned();
+        }
+        for block in &mut redacted.code_blocks {
+            *block = self.redact(block).into_owned();
+        }
+        for html in [&mut redacted.html, &mut redacted.raw_html]
+            .into_iter()
+            .flatten()
+        {
+            *html = self.redact(html).into_owned();
+        }
+        redacted
+    }
+}
+
+impl Rule {
+    fn builtin(pattern: &str, label: &'static str, luhn: bool) -> Self {
+        Self {
+            regex: Regex::new(pattern).expect("built-in redaction pattern"),
+            label,
+            luhn,
+        }
+    }
+}
+
+/// `label` padded with `*` to as many characters as `found` (all `*` if it is shorter)
+fn mask(found: &str, label: &str) -> String {
+    let len = found.chars().count();
+    let label_len = label.chars().count();
+    if len < label_len {
+        return "*".repeat(len);
+    }
+    format!("{label}{}", "*".repeat(len - label_len))
+}
+
+/// Whether the digits of a possible card number have a valid Luhn checksum
+fn passes_luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let redactor = Redactor::new(&RedactionSettings {
+            enabled: true,
+            patterns: vec![r"EMP-\d{6}".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let text = "Contact jane.doe@example.co.jp or +81 3-1234-5678, (555) 123-4567 \
+                    and 090-1234-5678. Card 4111 1111 1111 1111, order 1234567890123. \
+                    Employee EMP-004211 joined on 2024-05-01.";
+        let redacted = redactor.redact(text);
+        assert_eq!(redacted.chars().count(), text.chars().count());
+        for hidden in [
+            "jane.doe",
+            "1234-5678",
+            "123-4567",
+            "090-",
+            "4111",
+            "EMP-004211",
+        ] {
+            assert!(!redacted.contains(hidden), "{hidden} in {redacted}");
+        }
+        assert!(redacted.contains("Contact [email]**"));
+        assert!(redacted.contains("Card [card]*"));
+        assert!(redacted.contains("Employee [redacted]"));
+        // Numbers failing the Luhn check and dates are kept
+        assert!(redacted.contains("order 1234567890123"));
+        assert!(redacted.contains("2024-05-01"));
+
+        assert!(matches!(redactor.redact("Nothing here"), Cow::Borrowed(_)));
+        assert_eq!(mask("a@b.io", "[email]"), "******");
+
+        let invalid = RedactionSettings {
+            patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(Redactor::new(&invalid).is_err());
+    }
+}
@@ -2,7 +2,10 @@ use tantivy::schema::{
     FAST, Field, IndexRecordOption, STORED, STRING, Schema, TextFieldIndexing, TextOptions,
 };
 
-use super::tokenizer::JAPANESE_TOKENIZER_NAME;
+use super::tokenizer::{
+    CODE_TOKENIZER_NAME, EDGE_NGRAM_TOKENIZER_NAME, JAPANESE_TOKENIZER_NAME, NGRAM_TOKENIZER_NAME,
+    URL_TOKENIZER_NAME,
+};
 
 /// Bookmark index schema definition
 #[derive(Clone, Debug)]
@@ -20,6 +23,44 @@ pub struct BookmarkSchema {
     pub page_count: Field,
     pub page_offsets: Field,
     pub content_type: Field,
+    // Tags from read-later services and other importers (one value per tag)
+    pub tags: Field,
+    // Source browser profile when several profiles share one index
+    pub profile: Field,
+    // Character n-grams of title and content, queried when the Lindera fields find nothing
+    pub ngram: Field,
+    // Section headings sent by the extension, JSON-encoded like page_offsets
+    pub heading_offsets: Field,
+    // Text of the top-level (h1-h3) headings, boosted between title and content
+    pub headings: Field,
+    // Fenced and <pre> code blocks, tokenized into whole identifiers for `code:` queries
+    pub code: Field,
+    // Markdown rendition of the page's HTML, stored for get_bookmark_content
+    pub content_markdown: Field,
+    // Gzip-compressed original HTML, kept when `[index] store_html` is set
+    pub raw_html: Field,
+    // Extractive summary (top-scored sentences) computed at index time
+    pub summary: Field,
+    // SimHash of the content for near-duplicate detection
+    pub simhash: Field,
+    // Caption start times of a video transcript, JSON-encoded like page_offsets
+    pub transcript_offsets: Field,
+    // Set on PDFs whose text was recognized from scanned pages
+    pub ocr: Field,
+    // "archive" when the content came from the Internet Archive instead of the live page
+    pub source: Field,
+    // Capture time of that snapshot (RFC 3339)
+    pub archived_at: Field,
+    /// Position of a split PDF's document among its parts, with the page map of all parts (JSON)
+    pub part_map: Field,
+    /// Page (1-indexed) that `page_offsets[0]` belongs to, so split parts keep absolute page numbers
+    pub first_page: Field,
+    /// Words of the URL (split on `/`, `.`, `-`, `_` ...) for phrase and partial URL matches
+    pub url_text: Field,
+    /// Prefixes of the title's words, matched by typeahead
+    pub title_prefix: Field,
+    /// Characters the content had before it was cut to the `[content]` limit
+    pub truncated_from: Field,
 }
 
 impl BookmarkSchema {
@@ -63,6 +104,63 @@ impl BookmarkSchema {
         let page_offsets = builder.add_bytes_field("page_offsets", STORED);
         let content_type = builder.add_text_field("content_type", STRING | STORED);
 
+        // Tags for exact-match filtering (added last so existing field ids stay stable)
+        let tags = builder.add_text_field("tags", STRING | STORED);
+        let profile = builder.add_text_field("profile", STRING | STORED);
+
+        // N-gram fallback for scripts and symbols the dictionary doesn't handle (not stored)
+        let ngram_indexing = TextFieldIndexing::default()
+            .set_tokenizer(NGRAM_TOKENIZER_NAME)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let ngram = builder.add_text_field(
+            "ngram",
+            TextOptions::default().set_indexing_options(ngram_indexing),
+        );
+        let heading_offsets = builder.add_bytes_field("heading_offsets", STORED);
+        let headings = builder.add_text_field(
+            "headings",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(JAPANESE_TOKENIZER_NAME)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let code = builder.add_text_field(
+            "code",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(CODE_TOKENIZER_NAME)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let content_markdown = builder.add_text_field("content_markdown", STORED);
+        let raw_html = builder.add_bytes_field("raw_html", STORED);
+        let summary = builder.add_text_field("summary", STORED);
+        let simhash = builder.add_u64_field("simhash", STORED);
+        let transcript_offsets = builder.add_bytes_field("transcript_offsets", STORED);
+        let ocr = builder.add_bool_field("ocr", STORED);
+        let source = builder.add_text_field("source", STRING | STORED);
+        let archived_at = builder.add_text_field("archived_at", STORED);
+        let part_map = builder.add_bytes_field("part_map", STORED);
+        let first_page = builder.add_u64_field("first_page", STORED);
+        let url_text = builder.add_text_field(
+            "url_text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(URL_TOKENIZER_NAME)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let title_prefix = builder.add_text_field(
+            "title_prefix",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(EDGE_NGRAM_TOKENIZER_NAME)
+                    .set_index_option(IndexRecordOption::WithFreqs),
+            ),
+        );
+        let truncated_from = builder.add_u64_field("truncated_from", STORED);
+
         let schema = builder.build();
 
         Self {
@@ -78,6 +176,25 @@ impl BookmarkSchema {
             page_count,
             page_offsets,
             content_type,
+            tags,
+            profile,
+            ngram,
+            heading_offsets,
+            headings,
+            code,
+            content_markdown,
+            raw_html,
+            summary,
+            simhash,
+            transcript_offsets,
+            ocr,
+            source,
+            archived_at,
+            part_map,
+            first_page,
+            url_text,
+            title_prefix,
+            truncated_from,
         }
     }
 
@@ -114,6 +231,25 @@ mod tests {
         assert!(schema.schema.get_field("page_count").is_ok());
         assert!(schema.schema.get_field("page_offsets").is_ok());
         assert!(schema.schema.get_field("content_type").is_ok());
+        assert!(schema.schema.get_field("tags").is_ok());
+        assert!(schema.schema.get_field("profile").is_ok());
+        assert!(schema.schema.get_field("ngram").is_ok());
+        assert!(schema.schema.get_field("heading_offsets").is_ok());
+        assert!(schema.schema.get_field("headings").is_ok());
+        assert!(schema.schema.get_field("code").is_ok());
+        assert!(schema.schema.get_field("content_markdown").is_ok());
+        assert!(schema.schema.get_field("raw_html").is_ok());
+        assert!(schema.schema.get_field("summary").is_ok());
+        assert!(schema.schema.get_field("simhash").is_ok());
+        assert!(schema.schema.get_field("transcript_offsets").is_ok());
+        assert!(schema.schema.get_field("ocr").is_ok());
+        assert!(schema.schema.get_field("source").is_ok());
+        assert!(schema.schema.get_field("archived_at").is_ok());
+        assert!(schema.schema.get_field("part_map").is_ok());
+        assert!(schema.schema.get_field("first_page").is_ok());
+        assert!(schema.schema.get_field("url_text").is_ok());
+        assert!(schema.schema.get_field("title_prefix").is_ok());
+        assert!(schema.schema.get_field("truncated_from").is_ok());
     }
 
     #[test]
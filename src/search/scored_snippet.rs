@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::cmp::min;
 
+use super::query_parser::CODE_SCOPE;
+
 /// Phase 2.2: Scored snippet with relevance information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoredSnippet {
@@ -13,6 +15,10 @@ pub struct ScoredSnippet {
     /// Position in the document (character offset)
     pub position: usize,
 
+    /// Byte range in the document of the text shown (without added ellipses)
+    pub start: usize,
+    pub end: usize,
+
     /// Type of content in the snippet
     pub context_type: ContextType,
 
@@ -42,6 +48,43 @@ pub enum ContextType {
     Mixed,
 }
 
+/// Snippet settings for one search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnippetOptions {
+    /// Snippets returned per result
+    pub count: usize,
+    /// Maximum length of each snippet (UTF-8 bytes, cut at a character boundary)
+    pub length: usize,
+    /// Context kept around a match; defaults to a third of `length`
+    pub context_chars: Option<usize>,
+}
+
+impl Default for SnippetOptions {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            length: crate::config::Config::default().max_snippet_length,
+            context_chars: None,
+        }
+    }
+}
+
+impl SnippetOptions {
+    /// Whether tantivy's single best fragment satisfies these options
+    pub fn is_single_fragment(&self) -> bool {
+        self.count <= 1 && self.context_chars.is_none()
+    }
+
+    /// Scored generator sized for these options
+    pub fn generator(&self) -> ScoredSnippetGenerator {
+        ScoredSnippetGenerator::with_config(
+            self.length + self.length / 2,
+            self.count.max(1),
+            self.context_chars.unwrap_or(self.length / 3),
+        )
+    }
+}
+
 /// Enhanced snippet generator with scoring (Phase 2.2)
 #[derive(Debug)]
 pub struct ScoredSnippetGenerator {
@@ -80,18 +123,7 @@ impl ScoredSnippetGenerator {
         let snippets = self.generate_scored_snippets(content, query);
 
         if let Some(mut best) = snippets.into_iter().next() {
-            // Truncate if needed
-            if best.text.len() > max_len {
-                // Find safe UTF-8 boundary
-                let mut truncate_pos = max_len;
-                while truncate_pos > 0 && !best.text.is_char_boundary(truncate_pos) {
-                    truncate_pos -= 1;
-                }
-                best.text.truncate(truncate_pos);
-                if !best.text.ends_with("...") {
-                    best.text.push_str("...");
-                }
-            }
+            truncate_snippet(&mut best, max_len);
             best
         } else {
             // Return fallback snippet if no matches
@@ -99,6 +131,54 @@ impl ScoredSnippetGenerator {
         }
     }
 
+    /// Generate up to `count` snippets, best first, each at most `max_len` long
+    pub fn generate_snippets(
+        &self,
+        content: &str,
+        query: &str,
+        count: usize,
+        max_len: usize,
+    ) -> Vec<ScoredSnippet> {
+        let mut snippets = self.generate_scored_snippets(content, query);
+        if snippets.is_empty() {
+            snippets.push(self.create_fallback_snippet(content));
+        }
+        snippets.truncate(count.max(1));
+        for snippet in &mut snippets {
+            truncate_snippet(snippet, max_len);
+        }
+        snippets
+    }
+
+    /// Snippet for a fragment of `content` chosen by tantivy's SnippetGenerator
+    pub fn snippet_from_fragment(
+        &self,
+        content: &str,
+        fragment: &str,
+        match_count: usize,
+    ) -> ScoredSnippet {
+        let position = content.find(fragment).unwrap_or(0);
+        let (start, end) = trimmed_range(content, position, position + fragment.len());
+        let text = with_ellipses(content, start, end);
+
+        let context_type = self.detect_context_type(fragment);
+        let density = match_count as f32 / (fragment.len().max(1) as f32 / 100.0);
+        // Every fragment contains query terms, so only density and context vary
+        let relevance =
+            (density * 0.4 + 0.4 + self.get_context_boost(&context_type) * 0.2).min(1.0);
+
+        ScoredSnippet {
+            text,
+            relevance_score: relevance,
+            position,
+            start,
+            end,
+            context_type,
+            match_density: density,
+            section: self.find_section_heading(content, position),
+        }
+    }
+
     /// Generate scored snippets from content
     pub fn generate_scored_snippets(&self, content: &str, query: &str) -> Vec<ScoredSnippet> {
         if content.is_empty() || query.is_empty() {
@@ -109,7 +189,7 @@ impl ScoredSnippetGenerator {
         let query_terms: Vec<String> = query
             .to_lowercase()
             .split_whitespace()
-            .map(|s| s.to_string())
+            .map(|s| s.strip_prefix(CODE_SCOPE).unwrap_or(s).to_string())
             .collect();
 
         // Find all match positions with detailed scoring
@@ -153,7 +233,6 @@ impl ScoredSnippetGenerator {
 
     /// Find matches with detailed scoring information
     fn find_detailed_matches(&self, content: &str, query_terms: &[String]) -> Vec<MatchInfo> {
-        let content_lower = content.to_lowercase();
         let mut matches = Vec::new();
 
         // Sliding window analysis - use configured snippet length
@@ -176,11 +255,13 @@ impl ScoredSnippetGenerator {
                 end_byte += 1;
             }
 
-            let window_text = &content_lower[start_byte..end_byte];
+            // Lowercasing can change byte lengths, so lowercase each window
+            // rather than slicing a lowercased copy with the original offsets
             let original_window = &content[start_byte..end_byte];
+            let window_text = original_window.to_lowercase();
 
             // Calculate match score for this window
-            let (match_count, unique_terms) = self.count_matches(window_text, query_terms);
+            let (match_count, unique_terms) = self.count_matches(&window_text, query_terms);
 
             if match_count > 0 {
                 let density = match_count as f32 / (window_size as f32 / 100.0);
@@ -192,8 +273,17 @@ impl ScoredSnippetGenerator {
                 let relevance =
                     (density * 0.4 + term_coverage * 0.4 + context_boost * 0.2).min(1.0);
 
+                // Center the snippet on the first match rather than the window
+                // start (when lowercasing kept the offsets comparable)
+                let position = query_terms
+                    .iter()
+                    .filter_map(|term| window_text.find(term.as_str()))
+                    .min()
+                    .filter(|_| window_text.len() == original_window.len())
+                    .map_or(start_byte, |offset| start_byte + offset);
+
                 matches.push(MatchInfo {
-                    position: start_byte,
+                    position,
                     relevance,
                     match_count,
                     context_type,
@@ -315,16 +405,9 @@ impl ScoredSnippetGenerator {
             return None;
         }
 
-        // Extract text
-        let mut text = content[start_byte..end_byte].trim().to_string();
-
-        // Add ellipsis if needed
-        if start_byte > 0 {
-            text = format!("...{text}");
-        }
-        if end_byte < content.len() {
-            text = format!("{text}...");
-        }
+        // Extract text, adding ellipses where it was cut
+        let (start, end) = trimmed_range(content, start_byte, end_byte);
+        let text = with_ellipses(content, start, end);
 
         // Detect section heading if possible
         let section = self.find_section_heading(content, match_info.position);
@@ -333,6 +416,8 @@ impl ScoredSnippetGenerator {
             text: self.highlight_terms(&text, query_terms),
             relevance_score: match_info.relevance,
             position: match_info.position,
+            start,
+            end,
             context_type: match_info.context_type.clone(),
             match_density: match_info.density,
             section,
@@ -347,16 +432,18 @@ impl ScoredSnippetGenerator {
             end_byte -= 1;
         }
 
-        let text = if content.len() > max_len {
-            format!("{}...", &content[..end_byte])
+        let (text, end) = if content.len() > max_len {
+            (format!("{}...", &content[..end_byte]), end_byte)
         } else {
-            content.to_string()
+            (content.to_string(), content.len())
         };
 
         ScoredSnippet {
             text,
             relevance_score: 0.1,
             position: 0,
+            start: 0,
+            end,
             context_type: ContextType::Content,
             match_density: 0.0,
             section: None,
@@ -424,56 +511,51 @@ impl ScoredSnippetGenerator {
         false
     }
 
-    /// Find sentence start
+    /// Find sentence start: just after the sentence or clause boundary before `position`
     fn find_sentence_start(&self, content: &str, position: usize) -> usize {
-        if position == 0 {
-            return 0;
+        let mut position = position.min(content.len());
+        while !content.is_char_boundary(position) {
+            position -= 1;
         }
 
-        let bytes = content.as_bytes();
-        let mut pos = position;
-
-        while pos > 0 {
-            if pos >= 2 {
-                let prev_char = bytes[pos - 1];
-                let prev_prev_char = bytes[pos - 2];
-
-                if (prev_prev_char == b'.' || prev_prev_char == b'!' || prev_prev_char == b'?')
-                    && prev_char == b' '
-                {
-                    return pos;
-                }
+        // The character following the one being looked at
+        let mut following: Option<char> = None;
+        for (i, c) in content[..position].char_indices().rev() {
+            if c == '\n' || is_cjk_boundary(c) {
+                return i + c.len_utf8();
             }
-
-            if pos >= 1 && bytes[pos - 1] == b'\n' {
-                return pos;
+            // ASCII punctuation only ends a sentence when followed by a space
+            if is_ascii_terminator(c) && following == Some(' ') {
+                return i + c.len_utf8() + 1;
             }
-
-            pos = pos.saturating_sub(1);
+            following = Some(c);
         }
 
         0
     }
 
-    /// Find sentence end
+    /// Find sentence end: just after the sentence or clause boundary from `position` on
     fn find_sentence_end(&self, content: &str, position: usize) -> usize {
-        let bytes = content.as_bytes();
-        let mut pos = position;
-
-        while pos < content.len() {
-            if bytes[pos] == b'.' || bytes[pos] == b'!' || bytes[pos] == b'?' {
-                if pos + 1 >= content.len() {
-                    return content.len();
-                } else if bytes[pos + 1] == b' ' || bytes[pos + 1] == b'\n' {
-                    return pos + 1;
-                }
-            }
+        let mut position = position.min(content.len());
+        while !content.is_char_boundary(position) {
+            position += 1;
+        }
 
-            if bytes[pos] == b'\n' {
-                return pos;
+        let mut chars = content[position..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let end = position + i + c.len_utf8();
+            if c == '\n' {
+                return position + i;
+            }
+            if is_cjk_boundary(c) {
+                return end;
+            }
+            if is_ascii_terminator(c) {
+                match chars.peek() {
+                    None | Some((_, ' ' | '\n')) => return end,
+                    Some(_) => {}
+                }
             }
-
-            pos += 1;
         }
 
         content.len()
@@ -493,6 +575,64 @@ impl Default for ScoredSnippetGenerator {
     }
 }
 
+/// Shorten a snippet to `max_len` bytes at a character boundary, marking the cut
+fn truncate_snippet(snippet: &mut ScoredSnippet, max_len: usize) {
+    let text = &mut snippet.text;
+    if text.len() <= max_len {
+        return;
+    }
+    let mut truncate_pos = max_len;
+    while truncate_pos > 0 && !text.is_char_boundary(truncate_pos) {
+        truncate_pos -= 1;
+    }
+    // Keep the range in step with the document text that's left
+    let prefix = if snippet.start > 0 && text.starts_with("...") {
+        "...".len()
+    } else {
+        0
+    };
+    snippet.end = snippet
+        .end
+        .min(snippet.start + truncate_pos.saturating_sub(prefix));
+    text.truncate(truncate_pos);
+    if !text.ends_with("...") {
+        text.push_str("...");
+    }
+}
+
+/// `start..end` with surrounding whitespace excluded
+fn trimmed_range(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let slice = &content[start..end];
+    let start = start + (slice.len() - slice.trim_start().len());
+    (start, start + slice.trim().len())
+}
+
+/// Document text in `start..end`, with ellipses where it was cut
+fn with_ellipses(content: &str, start: usize, end: usize) -> String {
+    let prefix = if content[..start].trim().is_empty() {
+        ""
+    } else {
+        "..."
+    };
+    let suffix = if content[end..].trim().is_empty() {
+        ""
+    } else {
+        "..."
+    };
+    format!("{prefix}{}{suffix}", &content[start..end])
+}
+
+/// `.`, `!` and `?`, which end a sentence when followed by whitespace
+fn is_ascii_terminator(c: char) -> bool {
+    matches!(c, '.' | '!' | '?')
+}
+
+/// Full-width (and half-width katakana) punctuation that ends a sentence or
+/// clause without a following space
+fn is_cjk_boundary(c: char) -> bool {
+    matches!(c, '。' | '、' | '！' | '？' | '．' | '，' | '｡' | '､')
+}
+
 /// Internal match information
 #[derive(Debug)]
 struct MatchInfo {
@@ -539,6 +679,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_snippet_from_fragment() {
+        let generator = ScoredSnippetGenerator::new();
+        let content = "# Setup\nInstall the tool. Configure the database connection. Done.";
+
+        let snippet = generator.snippet_from_fragment(content, "Configure the database", 1);
+        assert_eq!(snippet.text, "...Configure the database...");
+        assert_eq!(snippet.position, content.find("Configure").unwrap());
+        assert_eq!(
+            &content[snippet.start..snippet.end],
+            "Configure the database"
+        );
+        assert_eq!(snippet.section.as_deref(), Some("Setup"));
+        assert!(snippet.relevance_score > 0.0 && snippet.relevance_score <= 1.0);
+
+        let whole = generator.snippet_from_fragment(content, content, 1);
+        assert_eq!(whole.text, content);
+    }
+
+    #[test]
+    fn test_japanese_sentence_boundaries() {
+        let generator = ScoredSnippetGenerator::new();
+        let content = "最初の文です。東京タワーは赤い、とても高い塔です！次の文";
+        let tower = content.find("東京").unwrap();
+
+        let start = generator.find_sentence_start(content, tower + 3);
+        assert_eq!(&content[start..tower + 3], "東");
+        let end = generator.find_sentence_end(content, tower);
+        assert_eq!(&content[tower..end], "東京タワーは赤い、");
+        let end = generator.find_sentence_end(content, content.find("とても").unwrap());
+        assert!(content[..end].ends_with("塔です！"));
+
+        // Positions inside a multibyte character don't panic
+        let start = generator.find_sentence_start(content, tower + 1);
+        assert_eq!(start, content.find("。").unwrap() + "。".len());
+        assert_eq!(
+            generator.find_sentence_end(content, tower + 1),
+            end_of(content, "、")
+        );
+
+        let ascii = "One. Two, three! Four";
+        assert_eq!(
+            generator.find_sentence_start(ascii, ascii.find("Four").unwrap()),
+            17
+        );
+        assert_eq!(
+            generator.find_sentence_end(ascii, 5),
+            ascii.find("!").unwrap() + 1
+        );
+        assert_eq!(generator.find_sentence_start(ascii, 3), 0);
+    }
+
+    fn end_of(content: &str, pattern: &str) -> usize {
+        content.find(pattern).unwrap() + pattern.len()
+    }
+
+    #[test]
+    fn test_snippet_options() {
+        let content = "Rust is fast. ".repeat(50) + &"Other text. ".repeat(50) + "Rust again.";
+        let options = SnippetOptions {
+            count: 3,
+            length: 80,
+            context_chars: Some(20),
+        };
+        let snippets =
+            options
+                .generator()
+                .generate_snippets(&content, "rust", options.count, options.length);
+        assert!(!snippets.is_empty() && snippets.len() <= 3);
+        for snippet in &snippets {
+            assert!(snippet.text.len() <= 83);
+            // The range covers exactly the document text shown
+            let shown = snippet
+                .text
+                .trim_start_matches("...")
+                .trim_end_matches("...");
+            assert_eq!(&content[snippet.start..snippet.end], shown);
+        }
+
+        assert!(SnippetOptions::default().is_single_fragment());
+        assert!(!options.is_single_fragment());
+        let none = ScoredSnippetGenerator::new().generate_snippets("", "rust", 2, 80);
+        assert_eq!(none.len(), 1);
+    }
+
     #[test]
     fn test_context_type_detection() {
         let generator = ScoredSnippetGenerator::new();
@@ -2,20 +2,37 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use tantivy::{Index, IndexWriter, directory::MmapDirectory};
 use tracing::{debug, info};
 
+use super::changeset::document_from_json;
+use super::collection_stats::CollectionStats;
 use super::common::{
-    DEFAULT_INDEX_NAME, DEFAULT_WRITER_HEAP_SIZE, INDEX_METADATA_FILE, IndexStats, IndexingStatus,
+    BookmarkEntry, DEFAULT_INDEX_NAME, DEFAULT_WRITER_HEAP_SIZE, INDEX_METADATA_FILE, IndexStats,
+    IndexingStatus, PageRangeError, content_window, extract_domain,
 };
-use super::indexer::BookmarkIndexer;
+use super::embeddings::{EmbeddingStore, SemanticHit, shared_embedder};
+use super::index_defaults::IndexDefaults;
+use super::index_watcher::{IndexWatcher, RefreshState};
+use super::indexer::{
+    BookmarkIndexer, PageInfo, PageStructure, TranscriptCue, format_timestamp, truncate_page,
+};
+use super::keywords::TopicCount;
 use super::schema::BookmarkSchema;
 use super::search_manager_trait::SearchManagerTrait;
-use super::tokenizer::register_lindera_tokenizer;
-use super::unified_searcher::{SearchParams, SearchResult, UnifiedSearcher};
+use super::sync_metadata::{IndexingProgress, SyncMetadata, unix_now};
+use super::timings::{Stage, stage};
+use super::tokenizer::{
+    AnalyzedToken, LinderaDictionary, register_index_tokenizer, register_lindera_tokenizer,
+    register_lindera_tokenizer_with, save_index_analyzer,
+};
+use super::unified_searcher::{
+    HYBRID_CANDIDATE_FACTOR, PART_CANDIDATE_FACTOR, SearchParams, SearchResult, Suggestion,
+    UnifiedSearcher, merge_part_results,
+};
 
-use crate::bookmark::FlatBookmark;
+use crate::bookmark::{BookmarkLimits, FlatBookmark};
 use crate::config::Config;
 
 /// Index metadata
@@ -31,6 +48,9 @@ pub struct IndexMetadata {
 }
 
 /// Main search manager that coordinates indexing and searching
+///
+/// Writing goes through a mutex around the index writer, so a writable
+/// manager can be shared (e.g. in an `Arc`) and indexed into through `&self`.
 pub struct SearchManager {
     #[allow(dead_code)]
     index: Option<Index>, // None for read-only mode
@@ -40,9 +60,11 @@ pub struct SearchManager {
     indexer: Option<BookmarkIndexer>, // None for read-only mode
     searcher: UnifiedSearcher,
     index_path: PathBuf,
-    writer: Option<IndexWriter>,
+    writer: Option<Mutex<IndexWriter>>,
     indexing_status: Arc<IndexingStatus>,
     read_only: bool,
+    /// Reloads the reader when the extension commits (read-only mode only)
+    watcher: Option<IndexWatcher>,
 }
 
 impl std::fmt::Debug for SearchManager {
@@ -65,22 +87,20 @@ impl SearchManager {
 
     /// Get index path from config
     fn get_index_path_from_config(config: &Config) -> PathBuf {
-        dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("mcp-bookmark")
-            .join(Self::get_index_key(config))
+        crate::config::data_dir().join(Self::get_index_key(config))
     }
 
     /// Create a new search manager
     pub fn new(index_path: Option<PathBuf>) -> Result<Self> {
-        let index_path = index_path.unwrap_or_else(|| {
-            dirs::data_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("mcp-bookmark")
-                .join("index")
-        });
+        let index_path = index_path.unwrap_or_else(|| crate::config::data_dir().join("index"));
 
-        Self::new_internal(index_path, None)
+        let dictionary = crate::config::settings().tokenizer.dictionary;
+        Self::new_internal(index_path, None, dictionary)
+    }
+
+    /// Create a search manager whose index, if new, uses the given dictionary
+    pub fn new_with_dictionary(index_path: PathBuf, dictionary: LinderaDictionary) -> Result<Self> {
+        Self::new_internal(index_path, None, dictionary)
     }
 
     /// Create a new search manager with config
@@ -94,15 +114,19 @@ impl SearchManager {
         info!("  Index path: ~/...mcp-bookmark/{}/", index_key);
         info!("=================================================");
 
-        Self::new_internal(index_path, Some(config))
+        Self::new_internal(index_path, Some(config), config.dictionary)
     }
 
     /// Open as read-only index (compatible with Chrome extension index)
     pub fn open_readonly(index_name: &str) -> Result<Self> {
-        let index_dir = dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("mcp-bookmark")
-            .join(index_name);
+        let index_dir = crate::config::data_dir().join(index_name);
+
+        Self::open_readonly_at(index_dir)
+    }
+
+    /// Open a read-only index located at the given directory
+    pub fn open_readonly_at<P: AsRef<Path>>(index_dir: P) -> Result<Self> {
+        let index_dir = index_dir.as_ref().to_path_buf();
 
         info!("Opening read-only index at: {:?}", index_dir);
 
@@ -122,6 +146,15 @@ impl SearchManager {
 
         let indexing_status = Arc::new(IndexingStatus::for_readonly(doc_count, bookmark_count));
 
+        // Pick up documents indexed by the extension after startup
+        let watcher = match IndexWatcher::start(&index_dir, searcher.reader()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                tracing::warn!("Index auto-reload disabled: {:#}", e);
+                None
+            }
+        };
+
         Ok(Self {
             index: None,
             schema: None,
@@ -131,11 +164,24 @@ impl SearchManager {
             writer: None,
             indexing_status,
             read_only: true,
+            watcher,
         })
     }
 
+    /// Auto-reload state, if the index directory is being watched
+    pub fn refresh_state(&self) -> Option<Arc<RefreshState>> {
+        self.watcher.as_ref().map(|w| w.state())
+    }
+
     /// Internal constructor
-    fn new_internal(index_path: PathBuf, config: Option<&Config>) -> Result<Self> {
+    ///
+    /// `dictionary` only applies when the index is created; existing indexes
+    /// keep the dictionary recorded for them.
+    fn new_internal(
+        index_path: PathBuf,
+        config: Option<&Config>,
+        dictionary: LinderaDictionary,
+    ) -> Result<Self> {
         std::fs::create_dir_all(&index_path).context("Failed to create index directory")?;
 
         let schema = BookmarkSchema::new();
@@ -154,8 +200,8 @@ impl SearchManager {
             }
 
             let index = Index::open_in_dir(&index_path).context("Failed to open existing index")?;
-            // Register Lindera tokenizer for existing index
-            register_lindera_tokenizer(&index)?;
+            // Register the tokenizer the index was built with
+            register_index_tokenizer(&index, &index_path)?;
             index
         } else {
             info!("Creating new index: {:?}", index_path);
@@ -171,14 +217,16 @@ impl SearchManager {
             let index = Index::create(mmap_directory, schema.schema.clone(), Default::default())
                 .context("Failed to create new index")?;
 
-            // Register Lindera tokenizer for new index
-            register_lindera_tokenizer(&index)?;
+            // Register Lindera tokenizer for new index and remember its dictionary
+            register_lindera_tokenizer_with(&index, dictionary)?;
+            save_index_analyzer(&index_path, dictionary)?;
             index
         };
 
         let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
-        let searcher = UnifiedSearcher::new(index.clone(), schema.clone())?;
-        let writer = Some(indexer.create_writer(DEFAULT_WRITER_HEAP_SIZE)?);
+        let mut searcher = UnifiedSearcher::new(index.clone(), schema.clone())?;
+        searcher.set_defaults(IndexDefaults::load_or_default(&index_path));
+        let writer = Some(Mutex::new(indexer.create_writer(DEFAULT_WRITER_HEAP_SIZE)?));
 
         // Get document count for indexing status
         let doc_count = searcher.get_stats()?.total_documents;
@@ -198,6 +246,7 @@ impl SearchManager {
             writer,
             indexing_status,
             read_only: false,
+            watcher: None,
         })
     }
 
@@ -231,6 +280,7 @@ impl SearchManager {
         let index = Index::create_in_dir(&index_path, schema.schema.clone())?;
 
         // Register tokenizer
+        save_index_analyzer(&index_path, LinderaDictionary::Ipadic)?;
         register_lindera_tokenizer(&index)?;
 
         let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
@@ -243,60 +293,192 @@ impl SearchManager {
             indexer: Some(indexer),
             searcher,
             index_path,
-            writer: Some(writer),
+            writer: Some(Mutex::new(writer)),
             indexing_status: Arc::new(IndexingStatus::new(0)),
             read_only: false,
+            watcher: None,
         })
     }
 
+    /// Directory of the index
+    pub fn index_path(&self) -> &Path {
+        &self.index_path
+    }
+
+    /// Whether the manager holds a writer (false for read-only indexes)
+    pub fn is_writable(&self) -> bool {
+        !self.read_only && self.writer.is_some()
+    }
+
+    /// Lock the index writer, waiting for other writes through this manager to finish
+    fn lock_writer(&self) -> Option<(MutexGuard<'_, IndexWriter>, &BookmarkIndexer)> {
+        let writer = self.writer.as_ref()?;
+        let indexer = self.indexer.as_ref()?;
+        // A panic mid-write leaves uncommitted changes that the next commit or rollback settles
+        let guard = writer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Some((guard, indexer))
+    }
+
     /// Index a single bookmark
-    pub fn index_bookmark(&mut self, bookmark: &FlatBookmark) -> Result<()> {
+    pub fn index_bookmark(&self, bookmark: &FlatBookmark) -> Result<()> {
         if self.read_only {
             return Err(anyhow::anyhow!("Cannot index bookmark in read-only mode"));
         }
-        if let (Some(writer), Some(indexer)) = (&mut self.writer, &self.indexer) {
-            indexer.index_bookmark(writer, bookmark, None)?;
+        if let Some((mut writer, indexer)) = self.lock_writer() {
+            indexer.index_bookmark(&mut writer, bookmark, None)?;
         }
         Ok(())
     }
 
     /// Index a single bookmark with content
     pub fn index_bookmark_with_content(
-        &mut self,
+        &self,
         bookmark: &FlatBookmark,
         content: Option<&str>,
     ) -> Result<()> {
         if self.read_only {
             return Err(anyhow::anyhow!("Cannot index bookmark in read-only mode"));
         }
-        if let (Some(writer), Some(indexer)) = (&mut self.writer, &self.indexer) {
-            indexer.index_bookmark(writer, bookmark, content)?;
+        if let Some((mut writer, indexer)) = self.lock_writer() {
+            indexer.index_bookmark(&mut writer, bookmark, content)?;
         }
         Ok(())
     }
 
     /// Index bookmarks with content
     pub fn index_bookmarks_with_content(
-        &mut self,
+        &self,
         bookmarks: &[FlatBookmark],
         content_map: &std::collections::HashMap<String, String>,
     ) -> Result<()> {
         if self.read_only {
             return Err(anyhow::anyhow!("Cannot index bookmarks in read-only mode"));
         }
-        if let (Some(writer), Some(indexer)) = (&mut self.writer, &self.indexer) {
+        if let Some((mut writer, indexer)) = self.lock_writer() {
             for bookmark in bookmarks {
                 let content = content_map.get(&bookmark.url).map(|s| s.as_str());
-                indexer.index_bookmark(writer, bookmark, content)?;
+                indexer.index_bookmark(&mut writer, bookmark, content)?;
             }
             writer.commit()?;
         }
         Ok(())
     }
 
+    /// Index pages with their text and structure in one commit
+    ///
+    /// Earlier documents with the same ids, including the parts of split
+    /// pages, are replaced. Returns the number of documents written.
+    pub fn replace_pages(&self, pages: &[(&FlatBookmark, &str, &PageStructure)]) -> Result<usize> {
+        let documents: Vec<_> = pages
+            .iter()
+            .map(|&(bookmark, content, structure)| (bookmark, content, structure, None))
+            .collect();
+        self.replace_documents(&documents)
+    }
+
+    /// Like `replace_pages`, with the page offsets of PDFs
+    ///
+    /// Content over the `[content]` limit for its type is cut off, and PDFs
+    /// over `max_chars_per_doc` characters are split into parts by page, as
+    /// the native host does. Returns the number of documents written.
+    pub fn replace_documents(
+        &self,
+        documents: &[(&FlatBookmark, &str, &PageStructure, Option<&PageInfo>)],
+    ) -> Result<usize> {
+        if self.read_only {
+            return Err(anyhow::anyhow!("Cannot index pages in read-only mode"));
+        }
+        let Some((mut writer, indexer)) = self.lock_writer() else {
+            return Ok(0);
+        };
+        let limits = &crate::config::settings().content;
+        let mut written = 0;
+        for &(bookmark, content, structure, page_info) in documents {
+            delete_with_parts(&writer, indexer.schema().id, &bookmark.id);
+            let content_type = page_info.map_or("html", |pi| pi.content_type.as_str());
+            let truncated = limits
+                .max_chars_for(content_type)
+                .and_then(|max_chars| truncate_page(content, page_info, structure, max_chars));
+            let (content, structure, page_info) = match &truncated {
+                Some(page) => (
+                    page.content.as_str(),
+                    &page.structure,
+                    page.page_info.as_ref(),
+                ),
+                None => (content, structure, page_info),
+            };
+            written += match page_info {
+                Some(page_info)
+                    if page_info.page_count > 1
+                        && content.chars().count() > limits.max_chars_per_doc =>
+                {
+                    indexer.index_bookmark_with_page_splitting(
+                        &mut writer,
+                        bookmark,
+                        content,
+                        page_info,
+                        limits.max_chars_per_doc,
+                    )?
+                }
+                Some(page_info) => {
+                    indexer.index_bookmark_with_page_info(
+                        &mut writer,
+                        bookmark,
+                        Some(content),
+                        Some(page_info),
+                    )?;
+                    1
+                }
+                None => {
+                    indexer.index_bookmark_with_structure(
+                        &mut writer,
+                        bookmark,
+                        Some(content),
+                        structure,
+                    )?;
+                    1
+                }
+            };
+        }
+        writer.commit().context("Failed to commit index")?;
+        self.searcher.reload()?;
+        Ok(written)
+    }
+
+    /// Replace bookmarks' documents with stored documents from a changeset
+    ///
+    /// Each change pairs a bookmark ID with the JSON of its documents (several
+    /// for split PDFs); an empty list deletes the bookmark. Fields that aren't
+    /// stored are rebuilt, see `changeset::document_from_json`. Returns the
+    /// number of documents written.
+    pub fn replace_stored(&self, changes: &[(&str, &[serde_json::Value])]) -> Result<usize> {
+        if self.read_only {
+            return Err(anyhow::anyhow!("Cannot index pages in read-only mode"));
+        }
+        let index_schema = self.index.as_ref().context("Index is read-only")?.schema();
+        let Some((mut writer, indexer)) = self.lock_writer() else {
+            return Ok(0);
+        };
+        let mut written = 0;
+        for &(id, documents) in changes {
+            delete_with_parts(&writer, indexer.schema().id, id);
+            for document in documents {
+                let document = document_from_json(document, &index_schema, indexer.schema())
+                    .with_context(|| format!("Invalid document of {id}"))?;
+                writer.add_document(document)?;
+                written += 1;
+            }
+        }
+        writer.commit().context("Failed to commit index")?;
+        self.searcher.reload()?;
+        Ok(written)
+    }
+
     /// Commit pending changes
-    pub fn commit(&mut self) -> Result<()> {
-        if let Some(ref mut writer) = self.writer {
+    pub fn commit(&self) -> Result<()> {
+        if let Some((mut writer, _)) = self.lock_writer() {
             writer.commit()?;
             // Reload searcher to see new changes
             self.searcher.reload()?;
@@ -315,7 +497,58 @@ impl SearchManager {
 
     /// Search with filters
     pub fn search_with_filters(&self, params: &SearchParams) -> Result<Vec<SearchResult>> {
-        self.searcher.search_with_params(params)
+        if params.merge_parts {
+            // Fetch extra hits so merging sibling parts still fills the limit
+            let candidates = SearchParams {
+                limit: params.limit * PART_CANDIDATE_FACTOR,
+                merge_parts: false,
+                ..params.clone()
+            };
+            let mut results = merge_part_results(self.search_with_filters(&candidates)?);
+            results.truncate(params.limit);
+            return Ok(results);
+        }
+        match (params.hybrid_weight, params.query.as_deref()) {
+            (Some(_), Some(query)) if !query.trim().is_empty() => {
+                let hits = stage(Stage::Semantic, || {
+                    self.semantic_hits(query, params.limit * HYBRID_CANDIDATE_FACTOR)
+                })?;
+                self.searcher.search_hybrid(params, &hits)
+            }
+            _ => self.searcher.search_with_params(params),
+        }
+    }
+
+    /// Bookmarks closest in meaning to the query (empty when nothing was embedded)
+    pub fn semantic_hits(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>> {
+        let store = EmbeddingStore::load(&self.index_path)?;
+        if store.is_empty() {
+            return Ok(Vec::new());
+        }
+        let embedder = shared_embedder()?;
+        if store.model != embedder.model() {
+            anyhow::bail!(
+                "Index was embedded with {}, but the configured model is {} (re-index to switch models)",
+                store.model,
+                embedder.model()
+            );
+        }
+        let mut hits = store.search(&embedder.embed_query(query)?, limit);
+        let defaults = self.searcher.defaults();
+        if !defaults.exclude_domains.is_empty() {
+            hits.retain(|hit| {
+                extract_domain(&hit.url)
+                    .is_none_or(|domain| !defaults.exclude_domains.contains(&domain))
+            });
+        }
+        let domains = &crate::config::settings().domains;
+        hits.retain(|hit| !domains.hides(&hit.url));
+        Ok(hits)
+    }
+
+    /// Search defaults stored with the index
+    pub fn index_defaults(&self) -> &IndexDefaults {
+        self.searcher.defaults()
     }
 
     /// Get full content by URL
@@ -333,11 +566,6 @@ impl SearchManager {
         use regex::Regex;
 
         // Validate page range
-        if start_page == 0 || end_page == 0 {
-            return Err(anyhow::anyhow!(
-                "Page numbers must be 1-indexed (start from 1)"
-            ));
-        }
         if start_page > end_page {
             return Err(anyhow::anyhow!(
                 "start_page ({}) must be <= end_page ({})",
@@ -370,10 +598,30 @@ impl SearchManager {
             .collect();
 
         if page_positions.is_empty() {
+            // Without markers, the stored page offsets can still locate the pages
+            if let Some(offsets) = self
+                .searcher
+                .get_page_offsets_by_url(url)?
+                .filter(|offsets| !offsets.is_empty())
+            {
+                if start_page == 0 || end_page > offsets.len() {
+                    return Err(
+                        PageRangeError::new(start_page, end_page, offsets.len(), "pdf").into(),
+                    );
+                }
+                let start = offsets[start_page - 1];
+                let len = offsets
+                    .get(end_page)
+                    .map_or(usize::MAX, |end| end.saturating_sub(start));
+                return Ok(Some(content_window(&content, start, 0, len).text));
+            }
+
             // No page markers found - this is not a PDF
-            return Err(anyhow::anyhow!(
-                "No page markers found. This bookmark may not be a PDF or was indexed before page support was added."
-            ));
+            let content_type = self
+                .searcher
+                .get_content_type_by_url(url)?
+                .unwrap_or_else(|| "html".to_string());
+            return Err(PageRangeError::new(start_page, end_page, 0, &content_type).into());
         }
 
         // Check if requested pages exist
@@ -388,15 +636,9 @@ impl SearchManager {
             .max()
             .unwrap_or(0);
 
-        // Check if requested range is within available pages
-        if start_page < min_page || end_page > max_page {
-            return Err(anyhow::anyhow!(
-                "Requested pages {}-{} are outside available range. Available pages: {}-{}",
-                start_page,
-                end_page,
-                min_page,
-                max_page
-            ));
+        // Check if requested range is within available pages (pages are 1-indexed)
+        if start_page == 0 || start_page < min_page || end_page > max_page {
+            return Err(PageRangeError::new(start_page, end_page, max_page, "pdf").into());
         }
 
         // Sort by position
@@ -434,6 +676,62 @@ impl SearchManager {
         }
     }
 
+    /// Get the captions of a video bookmark spoken between two times, one `[m:ss] text` line each
+    pub fn get_transcript_range_from_index(
+        &self,
+        url: &str,
+        start_ms: u64,
+        end_ms: Option<u64>,
+    ) -> Result<Option<String>> {
+        if let Some(end_ms) = end_ms {
+            if start_ms >= end_ms {
+                return Err(anyhow::anyhow!(
+                    "start_time ({}) must be before end_time ({})",
+                    format_timestamp(start_ms),
+                    format_timestamp(end_ms)
+                ));
+            }
+        }
+
+        let content = match self.get_full_content_by_url(url)? {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let cues = self.searcher.get_transcript_by_url(url)?;
+        let (Some(first), Some(last)) = (cues.first(), cues.last()) else {
+            return Err(anyhow::anyhow!(
+                "No transcript timestamps found. This bookmark may not be a video or was indexed before transcript support was added."
+            ));
+        };
+
+        let window = TranscriptCue::window(&cues, start_ms, end_ms);
+        if window.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No transcript between the requested times. The transcript runs from {} to {}",
+                format_timestamp(first.start_ms),
+                format_timestamp(last.start_ms)
+            ));
+        }
+
+        // Cue offsets are in characters; each caption runs until the next one starts
+        let chars: Vec<char> = content.chars().collect();
+        let mut lines = Vec::with_capacity(window.len());
+        for i in window {
+            let start = cues[i].offset.min(chars.len());
+            let end = cues
+                .get(i + 1)
+                .map_or(chars.len(), |next| next.offset.min(chars.len()))
+                .max(start);
+            let text: String = chars[start..end].iter().collect();
+            lines.push(format!(
+                "[{}] {}",
+                format_timestamp(cues[i].start_ms),
+                text.trim()
+            ));
+        }
+        Ok(Some(lines.join("\n")))
+    }
+
     /// Get index statistics
     pub fn get_stats(&self) -> Result<IndexStats> {
         let stats = self.searcher.get_stats()?;
@@ -452,18 +750,35 @@ impl SearchManager {
         self.index_path.join(INDEX_METADATA_FILE).exists()
     }
 
-    /// Build the entire index from bookmarks
-    pub fn build_index(&mut self, bookmarks: &[FlatBookmark]) -> Result<()> {
+    /// Build the entire index from bookmarks, within the limits in the `[index]` settings
+    pub fn build_index(&self, bookmarks: &[FlatBookmark]) -> Result<()> {
+        self.build_index_with_limits(bookmarks, &BookmarkLimits::from_settings())
+            .map(|_| ())
+    }
+
+    /// Build the entire index from the bookmarks within `limits`
+    ///
+    /// Returns the number of bookmarks left out.
+    pub fn build_index_with_limits(
+        &self,
+        bookmarks: &[FlatBookmark],
+        limits: &BookmarkLimits,
+    ) -> Result<usize> {
         if self.read_only {
             return Err(anyhow::anyhow!("Cannot build index in read-only mode"));
         }
 
-        debug!("Building index for {} bookmarks", bookmarks.len());
+        let selected = limits.select(bookmarks);
+        let skipped = bookmarks.len() - selected.len();
+        if skipped > 0 {
+            info!("Leaving out {} bookmarks over the index limits", skipped);
+        }
+        debug!("Building index for {} bookmarks", selected.len());
 
         // Reset indexing status
-        self.indexing_status = Arc::new(IndexingStatus::new(bookmarks.len()));
+        self.indexing_status.restart();
 
-        if let (Some(writer), Some(indexer)) = (&mut self.writer, &self.indexer) {
+        if let Some((mut writer, indexer)) = self.lock_writer() {
             // Clear existing documents
             writer.delete_all_documents()?;
 
@@ -471,8 +786,8 @@ impl SearchManager {
             let mut success_count = 0;
             let mut error_count = 0;
 
-            for bookmark in bookmarks {
-                match indexer.index_bookmark(writer, bookmark, None) {
+            for bookmark in selected {
+                match indexer.index_bookmark(&mut writer, bookmark, None) {
                     Ok(_) => {
                         success_count += 1;
                         self.indexing_status
@@ -507,7 +822,7 @@ impl SearchManager {
             }
         }
 
-        Ok(())
+        Ok(skipped)
     }
 
     /// Calculate index directory size
@@ -528,8 +843,8 @@ impl SearchManager {
     }
 
     /// Clear the index
-    pub fn clear_index(&mut self) -> Result<()> {
-        if let Some(ref mut writer) = self.writer {
+    pub fn clear_index(&self) -> Result<()> {
+        if let Some((mut writer, _)) = self.lock_writer() {
             writer.delete_all_documents()?;
             writer.commit()?;
             info!("Index cleared");
@@ -537,12 +852,42 @@ impl SearchManager {
         Ok(())
     }
 
+    /// Merge all segments into one and delete the files they leave behind
+    ///
+    /// Returns the number of segments before the merge.
+    pub fn merge_segments(&self) -> Result<usize> {
+        let index = self.index.as_ref().context("Index is read-only")?;
+        let segment_ids = index.searchable_segment_ids()?;
+        if segment_ids.len() > 1 {
+            if let Some((mut writer, _)) = self.lock_writer() {
+                writer
+                    .merge(&segment_ids)
+                    .wait()
+                    .context("Failed to merge segments")?;
+                writer.garbage_collect_files().wait()?;
+                info!("Merged {} segments", segment_ids.len());
+            }
+            self.searcher.reload()?;
+        }
+        Ok(segment_ids.len())
+    }
+
     /// Reload the searcher to see new changes
-    pub fn reload(&mut self) -> Result<()> {
+    pub fn reload(&self) -> Result<()> {
         self.searcher.reload()
     }
 }
 
+/// Delete a bookmark's document and the parts of a split PDF
+fn delete_with_parts(writer: &IndexWriter, id_field: tantivy::schema::Field, id: &str) {
+    writer.delete_term(tantivy::Term::from_field_text(id_field, id));
+    // Matches the 1000 parts delete_bookmark_parts covers
+    for part_num in 0..1000 {
+        let part_id = format!("{id}_part_{part_num}");
+        writer.delete_term(tantivy::Term::from_field_text(id_field, &part_id));
+    }
+}
+
 // Implement SearchManagerTrait for SearchManager
 #[async_trait]
 impl SearchManagerTrait for SearchManager {
@@ -554,10 +899,22 @@ impl SearchManagerTrait for SearchManager {
         self.search_with_filters(params)
     }
 
+    async fn semantic_search(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>> {
+        self.semantic_hits(query, limit)
+    }
+
     async fn get_content_by_url(&self, url: &str) -> Result<Option<String>> {
         self.get_full_content_by_url(url)
     }
 
+    async fn get_markdown_by_url(&self, url: &str) -> Result<Option<String>> {
+        self.searcher.get_markdown_by_url(url)
+    }
+
+    async fn get_html_by_url(&self, url: &str) -> Result<Option<String>> {
+        self.searcher.get_html_by_url(url)
+    }
+
     async fn get_page_range_content(
         &self,
         url: &str,
@@ -567,22 +924,74 @@ impl SearchManagerTrait for SearchManager {
         self.get_page_range_from_index(url, start_page, end_page)
     }
 
+    async fn get_transcript_range_content(
+        &self,
+        url: &str,
+        start_ms: u64,
+        end_ms: Option<u64>,
+    ) -> Result<Option<String>> {
+        self.get_transcript_range_from_index(url, start_ms, end_ms)
+    }
+
+    async fn list_bookmarks(&self) -> Result<Vec<BookmarkEntry>> {
+        self.searcher.list_bookmarks()
+    }
+
+    async fn get_bookmark_by_id(&self, id: &str) -> Result<Option<BookmarkEntry>> {
+        self.searcher.get_bookmark_by_id(id)
+    }
+
+    fn get_stats(&self) -> Result<IndexStats> {
+        SearchManager::get_stats(self)
+    }
+
+    fn get_collection_stats(&self) -> Result<CollectionStats> {
+        self.searcher.collection_stats()
+    }
+
+    fn get_topics(&self, limit: usize) -> Result<Vec<TopicCount>> {
+        self.searcher.topics(limit)
+    }
+
+    fn typeahead(&self, query: &str, limit: usize) -> Result<Vec<Suggestion>> {
+        self.searcher.typeahead(query, limit)
+    }
+
+    fn tokenize(&self, text: &str, field: &str) -> Result<Vec<AnalyzedToken>> {
+        self.searcher.tokenize(text, field)
+    }
+
     fn get_indexing_status(&self) -> String {
-        if self.read_only {
-            let doc_count = self.indexing_status.doc_count;
-            let bookmark_count = self.indexing_status.bookmark_count;
+        let status = if self.read_only {
+            let mut doc_count = self.indexing_status.doc_count;
+            let mut bookmark_count = self.indexing_status.bookmark_count;
+            let mut state = "loaded".to_string();
+
+            // Counts captured at open time are stale once the reader has reloaded
+            if let Some(refreshed) = self.refresh_state().and_then(|r| r.last_refreshed()) {
+                if let Ok(stats) = self.searcher.get_stats() {
+                    doc_count = stats.total_documents;
+                    bookmark_count = stats.bookmark_count;
+                }
+                state = format!("refreshed at {}", refreshed.format("%H:%M:%S"));
+            }
 
             if bookmark_count > 0 && bookmark_count != doc_count {
                 format!(
-                    "✅ Chrome Extension index loaded: {bookmark_count} bookmarks ({doc_count} documents) ready (read-only)"
+                    "✅ Chrome Extension index {state}: {bookmark_count} bookmarks ({doc_count} documents) ready (read-only)"
                 )
             } else {
                 format!(
-                    "✅ Chrome Extension index loaded: {doc_count} documents ready (read-only)"
+                    "✅ Chrome Extension index {state}: {doc_count} documents ready (read-only)"
                 )
             }
         } else {
             self.indexing_status.summary()
+        };
+        // The native host counts the extension's runs in index_metadata.json
+        match self.indexing_progress() {
+            Some(progress) => format!("{status}; {}", progress.summary(unix_now())),
+            None => status,
         }
     }
 
@@ -590,6 +999,25 @@ impl SearchManagerTrait for SearchManager {
         self.indexing_status
             .is_complete
             .load(std::sync::atomic::Ordering::Relaxed)
+            && !self
+                .indexing_progress()
+                .is_some_and(|progress| progress.is_running(unix_now()))
+    }
+
+    fn indexing_progress(&self) -> Option<IndexingProgress> {
+        SyncMetadata::load_progress(&self.index_path)
+    }
+
+    fn default_snippet_length(&self) -> Option<usize> {
+        self.index_defaults().snippet_length
+    }
+
+    fn reload(&self) -> Result<()> {
+        SearchManager::reload(self)
+    }
+
+    fn index_dirs(&self) -> Vec<PathBuf> {
+        vec![self.index_path.clone()]
     }
 }
 
@@ -610,6 +1038,216 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_shared_writable_manager() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(SearchManager::new_for_testing(temp_dir.path()).unwrap());
+        assert!(manager.is_writable());
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let manager = Arc::clone(&manager);
+                std::thread::spawn(move || {
+                    let bookmark = FlatBookmark {
+                        id: i.to_string(),
+                        name: format!("Shared page {i}"),
+                        url: format!("https://example.com/{i}"),
+                        folder_path: vec![],
+                        tags: vec![],
+                        profile: None,
+                        date_added: None,
+                        date_modified: None,
+                    };
+                    manager
+                        .index_bookmark_with_content(&bookmark, Some("written from a thread"))
+                        .unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        manager.commit().unwrap();
+
+        assert_eq!(manager.get_stats().unwrap().total_documents, 4);
+        assert_eq!(
+            manager
+                .get_full_content_by_url("https://example.com/2")
+                .unwrap()
+                .as_deref(),
+            Some("written from a thread")
+        );
+    }
+
+    #[test]
+    fn test_replace_documents_splits_large_pdfs() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SearchManager::new_for_testing(temp_dir.path()).unwrap();
+        let bookmark = FlatBookmark {
+            id: "https://example.com/report.pdf".to_string(),
+            name: "Report".to_string(),
+            url: "https://example.com/report.pdf".to_string(),
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+            date_added: None,
+            date_modified: None,
+        };
+        let pages: Vec<String> = (0..3).map(|_| "chapter ".repeat(5_000)).collect();
+        let (content, page_info) = crate::pdf::join_pages(&pages);
+        let structure = PageStructure::default();
+
+        let written = manager
+            .replace_documents(&[(&bookmark, &content, &structure, Some(&page_info))])
+            .unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(manager.get_stats().unwrap().total_documents, 2);
+
+        // Re-adding the page as a small document drops both parts
+        let written = manager
+            .replace_documents(&[(&bookmark, "now a short page", &structure, None)])
+            .unwrap();
+        assert_eq!(written, 1);
+        assert_eq!(manager.get_stats().unwrap().total_documents, 1);
+    }
+
+    #[test]
+    fn test_replace_pages_keeps_urls_like_parts() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SearchManager::new_for_testing(temp_dir.path()).unwrap();
+        let page = |url: &str| FlatBookmark {
+            id: crate::search::common::url_bookmark_id(url),
+            name: url.to_string(),
+            url: url.to_string(),
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+            date_added: None,
+            date_modified: None,
+        };
+        let structure = PageStructure::default();
+        let second = page("https://blog.example.com/rust_tutorial_part_2");
+        manager
+            .replace_pages(&[(&second, "the second part", &structure)])
+            .unwrap();
+
+        // Adding the first page keeps the page whose URL looks like its part
+        let first = page("https://blog.example.com/rust_tutorial");
+        manager
+            .replace_pages(&[(&first, "the first part", &structure)])
+            .unwrap();
+        assert_eq!(manager.get_stats().unwrap().total_documents, 2);
+        assert_eq!(
+            manager
+                .get_full_content_by_url("https://blog.example.com/rust_tutorial_part_2")
+                .unwrap()
+                .as_deref(),
+            Some("the second part")
+        );
+    }
+
+    #[test]
+    fn test_merge_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SearchManager::new_for_testing(temp_dir.path()).unwrap();
+        for i in 0..3 {
+            let bookmark = FlatBookmark {
+                id: format!("{i}"),
+                name: format!("Page {i}"),
+                url: format!("https://example.com/{i}"),
+                folder_path: vec![],
+                tags: vec![],
+                profile: None,
+                date_added: None,
+                date_modified: None,
+            };
+            manager.index_bookmark(&bookmark).unwrap();
+            manager.commit().unwrap();
+        }
+
+        assert_eq!(manager.merge_segments().unwrap(), 3);
+        assert_eq!(manager.merge_segments().unwrap(), 1);
+        assert_eq!(manager.get_stats().unwrap().total_documents, 3);
+    }
+
+    #[test]
+    fn test_build_index_with_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SearchManager::new_for_testing(temp_dir.path()).unwrap();
+        let bookmarks: Vec<_> = (0..6)
+            .map(|i| FlatBookmark {
+                id: format!("{i}"),
+                name: format!("Page {i}"),
+                url: format!("https://example.com/{i}"),
+                folder_path: vec![if i < 4 { "Huge" } else { "Small" }.to_string()],
+                tags: vec![],
+                profile: None,
+                date_added: None,
+                date_modified: None,
+            })
+            .collect();
+        let limits = BookmarkLimits {
+            max_per_folder: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            manager
+                .build_index_with_limits(&bookmarks, &limits)
+                .unwrap(),
+            2
+        );
+        assert_eq!(manager.get_stats().unwrap().total_documents, 4);
+    }
+
+    #[test]
+    fn test_index_defaults_exclude_domains() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SearchManager::new_for_testing(temp_dir.path()).unwrap();
+        let urls = [
+            "https://docs.example.com/rust",
+            "https://ads.example.com/rust",
+        ];
+        for (i, url) in urls.iter().enumerate() {
+            let bookmark = FlatBookmark {
+                id: format!("{i}"),
+                name: "Rust guide".to_string(),
+                url: url.to_string(),
+                folder_path: vec![],
+                tags: vec![],
+                profile: None,
+                date_added: None,
+                date_modified: None,
+            };
+            manager.index_bookmark(&bookmark).unwrap();
+        }
+        manager.commit().unwrap();
+        let defaults = IndexDefaults {
+            exclude_domains: vec!["ads.example.com".to_string()],
+            snippet_length: Some(120),
+            ..Default::default()
+        };
+        defaults.save(temp_dir.path()).unwrap();
+
+        let manager = SearchManager::open_readonly_at(temp_dir.path()).unwrap();
+        assert_eq!(manager.default_snippet_length(), Some(120));
+        let found = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|result| result.url).collect()
+        };
+        assert_eq!(found(manager.search("rust", 10).unwrap()), [urls[0]]);
+        let params = SearchParams::new("rust");
+        assert_eq!(
+            found(manager.search_with_filters(&params).unwrap()),
+            [urls[0]]
+        );
+        // Filtering on an excluded domain still finds it
+        let params = params.with_domain("ads.example.com".to_string());
+        assert_eq!(
+            found(manager.search_with_filters(&params).unwrap()),
+            [urls[1]]
+        );
+    }
+
     #[test]
     fn test_index_key_generation() {
         let config = Config {
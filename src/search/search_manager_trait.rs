@@ -1,8 +1,14 @@
-use super::{SearchParams, SearchResult};
+use super::sync_metadata::IndexingProgress;
+use super::tokenizer::AnalyzedToken;
+use super::{
+    BookmarkEntry, CollectionStats, IndexStats, SearchParams, SearchResult, SemanticHit,
+    Suggestion, TopicCount,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::path::PathBuf;
 
 /// Metadata about a bookmark's page structure (for PDFs)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,9 +30,18 @@ pub trait SearchManagerTrait: Send + Sync + Debug {
     /// Advanced search with filters
     async fn search_advanced(&self, params: &SearchParams) -> Result<Vec<SearchResult>>;
 
+    /// Bookmarks whose embedded chunks are closest in meaning to the query
+    async fn semantic_search(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>>;
+
     /// Get content by URL
     async fn get_content_by_url(&self, url: &str) -> Result<Option<String>>;
 
+    /// Get the stored Markdown rendition of a page by URL
+    async fn get_markdown_by_url(&self, url: &str) -> Result<Option<String>>;
+
+    /// Get the stored original HTML of a page by URL
+    async fn get_html_by_url(&self, url: &str) -> Result<Option<String>>;
+
     /// Get page range content from a PDF bookmark (for single page, use start_page = end_page)
     async fn get_page_range_content(
         &self,
@@ -35,9 +50,50 @@ pub trait SearchManagerTrait: Send + Sync + Debug {
         end_page: usize,
     ) -> Result<Option<String>>;
 
+    /// Get the transcript of a video bookmark between two times (to the end when `end_ms` is `None`)
+    async fn get_transcript_range_content(
+        &self,
+        url: &str,
+        start_ms: u64,
+        end_ms: Option<u64>,
+    ) -> Result<Option<String>>;
+
+    /// List all bookmarks stored in the index
+    async fn list_bookmarks(&self) -> Result<Vec<BookmarkEntry>>;
+
+    /// Find a bookmark by its ID
+    async fn get_bookmark_by_id(&self, id: &str) -> Result<Option<BookmarkEntry>>;
+
+    /// Get index statistics
+    fn get_stats(&self) -> Result<IndexStats>;
+
+    /// Get per-domain, per-folder, size and date statistics
+    fn get_collection_stats(&self) -> Result<CollectionStats>;
+
+    /// Most common tags across bookmarks, with how many bookmarks carry each
+    fn get_topics(&self, limit: usize) -> Result<Vec<TopicCount>>;
+
+    /// Titles completing what has been typed so far, best first
+    fn typeahead(&self, query: &str, limit: usize) -> Result<Vec<Suggestion>>;
+
+    /// Show how the index's analyzer splits `text` for a field
+    fn tokenize(&self, text: &str, field: &str) -> Result<Vec<AnalyzedToken>>;
+
     /// Get indexing status
     fn get_indexing_status(&self) -> String;
 
     /// Check if indexing is complete
     fn is_indexing_complete(&self) -> bool;
+
+    /// Progress of the extension's current or last indexing run into the index
+    fn indexing_progress(&self) -> Option<IndexingProgress>;
+
+    /// Snippet length stored with the index, for searches that don't pass one
+    fn default_snippet_length(&self) -> Option<usize>;
+
+    /// Reload the index readers to see documents committed since they were opened
+    fn reload(&self) -> Result<()>;
+
+    /// Directories of the indexes searched, for their sidecar files
+    fn index_dirs(&self) -> Vec<PathBuf>;
 }
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+
+/// Sentences kept in a summary
+pub const SUMMARY_SENTENCES: usize = 3;
+
+/// Upper bound on a summary's length in characters
+pub const MAX_SUMMARY_CHARS: usize = 400;
+
+/// Shorter sentences are skipped (menu entries, labels, captions)
+const MIN_SENTENCE_CHARS: usize = 10;
+
+/// Only the start of long documents is summarized
+const MAX_SCANNED_CHARS: usize = 50_000;
+
+/// Common English words that say nothing about a page's topic
+pub const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "can", "her", "was", "one", "our",
+    "out", "has", "have", "had", "this", "that", "with", "from", "they", "will", "would", "there",
+    "their", "what", "about", "which", "when", "your", "were", "been", "into", "more", "also",
+    "than", "then", "them", "these", "some", "such", "its", "may", "use", "using", "how",
+];
+
+/// Split text into sentences at sentence-ending punctuation and line breaks
+pub fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let ends = match c {
+            '。' | '！' | '？' | '\n' => true,
+            '.' | '!' | '?' => next.is_none_or(char::is_whitespace),
+            _ => false,
+        };
+        if ends {
+            let end = i + c.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+/// Terms a sentence is scored by: lowercase words, and character bigrams
+/// for scripts written without spaces
+fn terms(sentence: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+
+    let flush_word = |word: &mut String, terms: &mut Vec<String>| {
+        if word.chars().count() >= 3 && !STOP_WORDS.contains(&word.as_str()) {
+            terms.push(std::mem::take(word));
+        }
+        word.clear();
+    };
+    let flush_cjk = |run: &mut Vec<char>, terms: &mut Vec<String>| {
+        terms.extend(run.windows(2).map(|pair| pair.iter().collect()));
+        run.clear();
+    };
+
+    for c in sentence.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut terms);
+            cjk_run.push(c);
+        } else if c.is_alphanumeric() {
+            flush_cjk(&mut cjk_run, &mut terms);
+            word.extend(c.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut terms);
+            flush_cjk(&mut cjk_run, &mut terms);
+        }
+    }
+    flush_word(&mut word, &mut terms);
+    flush_cjk(&mut cjk_run, &mut terms);
+    terms
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' // CJK ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul
+    )
+}
+
+/// Short extractive summary: the highest-scoring sentences in document order
+///
+/// Sentences score by how often their terms occur in the whole document,
+/// divided by the square root of their term count so long sentences don't win
+/// by size alone, with a small bonus for appearing early. Returns `None` when
+/// the content has no sentence worth keeping.
+pub fn summarize(content: &str) -> Option<String> {
+    let scanned: String = content.chars().take(MAX_SCANNED_CHARS).collect();
+    let page_marker = regex::Regex::new(r"\[PAGE:\d+\]").ok()?;
+    let scanned = page_marker.replace_all(&scanned, "\n");
+
+    let candidates: Vec<(usize, &str, Vec<String>)> = sentences(&scanned)
+        .into_iter()
+        .filter(|s| s.chars().count() >= MIN_SENTENCE_CHARS)
+        .enumerate()
+        .map(|(i, s)| (i, s, terms(s)))
+        .filter(|(_, _, terms)| !terms.is_empty())
+        .collect();
+
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    for (_, _, terms) in &candidates {
+        for term in terms {
+            *frequency.entry(term.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut scored: Vec<(f32, usize, &str)> = candidates
+        .iter()
+        .map(|(i, sentence, terms)| {
+            let unique: HashSet<&str> = terms.iter().map(String::as_str).collect();
+            let weight: usize = unique.iter().map(|term| frequency[term]).sum();
+            let position_bonus = 1.0 + 1.0 / (*i as f32 + 2.0);
+            let score = weight as f32 / (terms.len() as f32).sqrt() * position_bonus;
+            (score, *i, *sentence)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.truncate(SUMMARY_SENTENCES);
+    scored.sort_by_key(|(_, i, _)| *i);
+
+    let summary = scored
+        .iter()
+        .map(|(_, _, sentence)| *sentence)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if summary.is_empty() {
+        return None;
+    }
+    if summary.chars().count() <= MAX_SUMMARY_CHARS {
+        return Some(summary);
+    }
+    let truncated: String = summary.chars().take(MAX_SUMMARY_CHARS - 3).collect();
+    Some(format!("{}...", truncated.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences() {
+        assert_eq!(
+            sentences("Version 1.2 is out. Try it!\nMenu\n日本語です。次の文"),
+            vec![
+                "Version 1.2 is out.",
+                "Try it!",
+                "Menu",
+                "日本語です。",
+                "次の文"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let content = "Home | About | Contact\n\
+            Tantivy is a full-text search engine library written in Rust. \
+            It is inspired by Lucene and fast to start. \
+            The weather was nice when this page was written, unrelated to anything. \
+            Tantivy indexes documents and runs search queries over the indexed text. \
+            Search queries in Tantivy support phrases and boolean search operators. \
+            Thanks for reading.";
+        let summary = summarize(content).unwrap();
+        assert!(summary.starts_with("Tantivy is a full-text search engine"));
+        assert!(summary.contains("search queries"));
+        assert!(!summary.contains("weather"));
+        assert!(!summary.contains("Home"));
+
+        let japanese = "検索エンジンは文書を索引します。今日は晴れでした、特に関係はありません。\
+            全文検索エンジンは索引から文書を検索します。";
+        let summary = summarize(japanese).unwrap();
+        assert!(summary.contains("全文検索エンジン"));
+
+        assert_eq!(summarize(""), None);
+        assert_eq!(summarize("Short.\nMenu"), None);
+        let long = format!("{}.", "searching an index ".repeat(40));
+        let summary = summarize(&long).unwrap();
+        assert_eq!(summary.chars().count(), MAX_SUMMARY_CHARS);
+        assert!(summary.ends_with("..."));
+    }
+}
@@ -0,0 +1,373 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::bookmark::FlatBookmark;
+
+/// File in an index directory recording what was indexed and when
+pub const SYNC_METADATA_FILE: &str = "index_metadata.json";
+
+/// The indexed state of one bookmark
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedBookmark {
+    pub url: String,
+    pub date_modified: Option<String>,
+    /// Unix time in seconds
+    pub indexed_at: u64,
+    pub content_hash: Option<String>,
+    /// Fetched by the server (`add_bookmark`, `ingest`, feeds) rather than sent by
+    /// the extension, so the daemon may fetch it again
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fetched: bool,
+}
+
+/// Seconds without an update after which a run is taken to have stopped
+pub const STALLED_AFTER_SECS: u64 = 600;
+
+/// Failed bookmarks listed with their error per run; later failures are only counted
+pub const MAX_LISTED_FAILURES: usize = 100;
+
+/// Progress of an indexing run of the extension, counted by the native host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexingProgress {
+    /// Chosen by the extension for each run; a new ID starts counting again
+    pub run_id: String,
+    /// Bookmarks in the run
+    pub total: usize,
+    /// Skipped as indexed before and unchanged
+    pub known: usize,
+    pub indexed: usize,
+    /// Pages the extension couldn't fetch or the host couldn't index
+    pub errored: usize,
+    /// Unix time in seconds
+    pub started_at: u64,
+    /// Unix time in seconds of the last bookmark counted
+    pub updated_at: u64,
+    /// Bookmarks that failed and haven't been indexed since, so the extension can retry them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<FailedBookmark>,
+    /// URLs of every bookmark counted in `errored`, so a retry is counted once
+    /// even when its failure is past `MAX_LISTED_FAILURES`
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub failed_urls: BTreeSet<String>,
+}
+
+/// A bookmark of a run that couldn't be fetched or indexed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedBookmark {
+    pub url: String,
+    pub error: String,
+}
+
+/// What became of one bookmark of an indexing run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    Known,
+    Indexed,
+    /// Failed with this error
+    Errored(String),
+}
+
+impl IndexingProgress {
+    /// Bookmarks counted so far
+    pub fn processed(&self) -> usize {
+        self.known + self.indexed + self.errored
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.processed() >= self.total
+    }
+
+    /// Still going at Unix time `now`: incomplete and updated within `STALLED_AFTER_SECS`
+    pub fn is_running(&self, now: u64) -> bool {
+        !self.is_complete() && now.saturating_sub(self.updated_at) < STALLED_AFTER_SECS
+    }
+
+    /// One line for status tools, as of Unix time `now`
+    pub fn summary(&self, now: u64) -> String {
+        let counts = format!(
+            "{} indexed, {} unchanged, {} errors",
+            self.indexed, self.known, self.errored
+        );
+        if self.is_complete() {
+            format!(
+                "last extension run finished: {} bookmarks, {counts}",
+                self.total
+            )
+        } else if self.is_running(now) {
+            format!(
+                "extension indexing in progress: {}/{} ({counts})",
+                self.processed(),
+                self.total
+            )
+        } else {
+            format!(
+                "extension run stopped at {}/{} ({counts})",
+                self.processed(),
+                self.total
+            )
+        }
+    }
+}
+
+/// `index_metadata.json`, keyed by bookmark ID
+///
+/// Written by the native messaging host and the `add_bookmark` tool so that
+/// unchanged bookmarks can be skipped on the next sync. The host also keeps
+/// the progress of the extension's current run here for the server to report.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncMetadata {
+    pub bookmarks: HashMap<String, SyncedBookmark>,
+    pub last_full_sync: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<IndexingProgress>,
+}
+
+/// Current Unix time in seconds
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl SyncMetadata {
+    pub fn path(index_dir: &Path) -> PathBuf {
+        index_dir.join(SYNC_METADATA_FILE)
+    }
+
+    /// Metadata of an index, empty if it has none yet
+    pub fn load(index_dir: &Path) -> Result<Self> {
+        let path = Self::path(index_dir);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                serde_json::from_str(&content).with_context(|| format!("Invalid {path:?}"))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {path:?}")),
+        }
+    }
+
+    /// Just the progress recorded in an index's metadata, skipping the bookmark records
+    pub fn load_progress(index_dir: &Path) -> Option<IndexingProgress> {
+        #[derive(Deserialize)]
+        struct ProgressOnly {
+            #[serde(default)]
+            progress: Option<IndexingProgress>,
+        }
+        let content = std::fs::read_to_string(Self::path(index_dir)).ok()?;
+        serde_json::from_str::<ProgressOnly>(&content)
+            .ok()?
+            .progress
+    }
+
+    /// Write the metadata, replacing the file at once so readers never see half of it
+    pub fn save(&self, index_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(index_dir)?;
+        let path = Self::path(index_dir);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {tmp:?}"))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {path:?}"))
+    }
+
+    /// Record a bookmark as indexed now with the given content
+    pub fn record(&mut self, bookmark: &FlatBookmark, content: Option<&str>) {
+        let indexed_at = unix_now();
+        self.bookmarks.insert(
+            bookmark.id.clone(),
+            SyncedBookmark {
+                url: bookmark.url.clone(),
+                date_modified: bookmark.date_modified.clone(),
+                indexed_at,
+                content_hash: Some(content_hash(content)),
+                fetched: false,
+            },
+        );
+    }
+
+    /// Record a page the server fetched itself as indexed now
+    pub fn record_fetched(&mut self, bookmark: &FlatBookmark, content: Option<&str>) {
+        self.record(bookmark, content);
+        if let Some(synced) = self.bookmarks.get_mut(&bookmark.id) {
+            synced.fetched = true;
+        }
+    }
+
+    /// Server-fetched bookmarks indexed before `cutoff` (Unix seconds), oldest first
+    pub fn fetched_before(&self, cutoff: u64) -> Vec<(&str, &SyncedBookmark)> {
+        let mut stale: Vec<_> = self
+            .bookmarks
+            .iter()
+            .filter(|(_, synced)| synced.fetched && synced.indexed_at < cutoff)
+            .map(|(id, synced)| (id.as_str(), synced))
+            .collect();
+        stale.sort_by_key(|(id, synced)| (synced.indexed_at, *id));
+        stale
+    }
+
+    /// Count bookmark `url` towards run `run_id` of `total` bookmarks; another ID starts a new run
+    ///
+    /// A bookmark that failed earlier in the run is counted once, by its latest outcome.
+    pub fn record_progress(&mut self, run_id: &str, total: usize, url: &str, outcome: RunOutcome) {
+        let now = unix_now();
+        if self
+            .progress
+            .as_ref()
+            .is_none_or(|progress| progress.run_id != run_id)
+        {
+            self.progress = Some(IndexingProgress {
+                run_id: run_id.to_string(),
+                started_at: now,
+                ..Default::default()
+            });
+        }
+        let progress = self.progress.as_mut().expect("progress was just set");
+        progress.total = total;
+        progress.updated_at = now;
+        if progress.failed_urls.remove(url) {
+            progress.failed.retain(|failed| failed.url != url);
+            progress.errored = progress.errored.saturating_sub(1);
+        }
+        match outcome {
+            RunOutcome::Known => progress.known += 1,
+            RunOutcome::Indexed => progress.indexed += 1,
+            RunOutcome::Errored(error) => {
+                progress.errored += 1;
+                progress.failed_urls.insert(url.to_string());
+                if progress.failed.len() < MAX_LISTED_FAILURES {
+                    progress.failed.push(FailedBookmark {
+                        url: url.to_string(),
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Whether a bookmark is recorded with the same modification date and content
+    pub fn is_unchanged(&self, bookmark: &FlatBookmark, content: Option<&str>) -> bool {
+        self.bookmarks.get(&bookmark.id).is_some_and(|existing| {
+            existing.date_modified == bookmark.date_modified
+                && existing.content_hash.as_deref() == Some(content_hash(content).as_str())
+        })
+    }
+}
+
+/// Hash of a bookmark's content, stable within a build
+pub fn content_hash(content: Option<&str>) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    content.unwrap_or("").hash(&mut hasher);
+    hasher.finish().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_metadata_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            SyncMetadata::load(dir.path()).unwrap(),
+            SyncMetadata::default()
+        );
+
+        let bookmark = FlatBookmark {
+            id: "https://example.com/new".to_string(),
+            name: "New page".to_string(),
+            url: "https://example.com/new".to_string(),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec!["Research".to_string()],
+            tags: Vec::new(),
+            profile: None,
+        };
+        let mut metadata = SyncMetadata::default();
+        metadata.record(&bookmark, Some("text"));
+        metadata.save(dir.path()).unwrap();
+
+        let loaded = SyncMetadata::load(dir.path()).unwrap();
+        assert_eq!(loaded, metadata);
+        assert!(loaded.is_unchanged(&bookmark, Some("text")));
+        assert!(!loaded.is_unchanged(&bookmark, Some("edited")));
+        assert!(loaded.fetched_before(u64::MAX).is_empty());
+
+        metadata.record_fetched(&bookmark, Some("text"));
+        let stale = metadata.fetched_before(u64::MAX);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].0, "https://example.com/new");
+        assert!(metadata.fetched_before(0).is_empty());
+    }
+
+    #[test]
+    fn test_record_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut metadata = SyncMetadata::default();
+        metadata.record_progress("run-1", 3, "https://a.example", RunOutcome::Indexed);
+        metadata.record_progress("run-1", 3, "https://b.example", RunOutcome::Known);
+        metadata.save(dir.path()).unwrap();
+
+        let progress = SyncMetadata::load_progress(dir.path()).unwrap();
+        assert_eq!(
+            (progress.indexed, progress.known, progress.processed()),
+            (1, 1, 2)
+        );
+        let now = progress.updated_at;
+        assert!(progress.is_running(now));
+        assert!(!progress.is_running(now + STALLED_AFTER_SECS));
+        assert!(progress.summary(now).contains("in progress: 2/3"));
+
+        let timeout = RunOutcome::Errored("Timeout".to_string());
+        metadata.record_progress("run-1", 3, "https://c.example", timeout);
+        let progress = metadata.progress.as_ref().unwrap();
+        assert!(progress.is_complete() && !progress.is_running(now));
+        assert!(progress.summary(now).contains("1 errors"));
+        assert_eq!(progress.failed[0].url, "https://c.example");
+
+        // A successful retry replaces the failure
+        metadata.record_progress("run-1", 3, "https://c.example", RunOutcome::Indexed);
+        let progress = metadata.progress.as_ref().unwrap();
+        assert_eq!(
+            (progress.indexed, progress.errored, progress.processed()),
+            (2, 0, 3)
+        );
+        assert!(progress.failed.is_empty());
+
+        metadata.record_progress("run-2", 10, "https://a.example", RunOutcome::Indexed);
+        let progress = metadata.progress.as_ref().unwrap();
+        assert_eq!(
+            (progress.run_id.as_str(), progress.processed()),
+            ("run-2", 1)
+        );
+    }
+
+    #[test]
+    fn test_record_progress_retries_past_listed_failures() {
+        let mut metadata = SyncMetadata::default();
+        let total = MAX_LISTED_FAILURES + 20;
+        let urls: Vec<String> = (0..total)
+            .map(|i| format!("https://example.com/{i}"))
+            .collect();
+        for url in &urls {
+            let outcome = RunOutcome::Errored("Timeout".to_string());
+            metadata.record_progress("run-1", total, url, outcome);
+        }
+        let progress = metadata.progress.as_ref().unwrap();
+        assert_eq!(progress.errored, total);
+        assert_eq!(progress.failed.len(), MAX_LISTED_FAILURES);
+
+        for url in &urls {
+            metadata.record_progress("run-1", total, url, RunOutcome::Indexed);
+        }
+        let progress = metadata.progress.as_ref().unwrap();
+        assert_eq!(
+            (progress.indexed, progress.errored, progress.processed()),
+            (total, 0, total)
+        );
+        assert!(progress.failed.is_empty() && progress.failed_urls.is_empty());
+    }
+}
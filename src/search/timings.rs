@@ -0,0 +1,159 @@
+use serde::Serialize;
+use std::cell::RefCell;
+use std::future::Future;
+use std::time::Duration;
+use tracing::debug;
+
+/// Parts of a search request that are timed separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Turning the query text into a tantivy query
+    Parse,
+    /// Running the query against the index
+    Search,
+    /// Loading matched documents and generating their snippets
+    Snippets,
+    /// Embedding the query and scanning stored vectors (hybrid ranking)
+    Semantic,
+    /// Converting results to JSON
+    Serialize,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::Search => "search",
+            Self::Snippets => "snippets",
+            Self::Semantic => "semantic",
+            Self::Serialize => "serialize",
+        }
+    }
+}
+
+/// Latency of one index in a multi-index search
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexTiming {
+    pub index: String,
+    pub ms: f64,
+    pub results: usize,
+}
+
+/// Where the time of one search request went, in milliseconds
+///
+/// Stages run once per index (and twice when the n-gram fallback kicks in),
+/// so each field is the sum over all runs.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchTimings {
+    pub parse_ms: f64,
+    pub search_ms: f64,
+    pub snippets_ms: f64,
+    pub semantic_ms: f64,
+    pub serialize_ms: f64,
+    pub total_ms: f64,
+    /// Per-index latency in multi-index mode
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<IndexTiming>,
+}
+
+impl SearchTimings {
+    /// Add time spent in a stage
+    pub fn add(&mut self, stage: Stage, elapsed: Duration) {
+        let total = match stage {
+            Stage::Parse => &mut self.parse_ms,
+            Stage::Search => &mut self.search_ms,
+            Stage::Snippets => &mut self.snippets_ms,
+            Stage::Semantic => &mut self.semantic_ms,
+            Stage::Serialize => &mut self.serialize_ms,
+        };
+        *total = round(*total + millis(elapsed));
+    }
+}
+
+tokio::task_local! {
+    static TIMINGS: RefCell<SearchTimings>;
+}
+
+/// Run `future`, collecting the stage timings of every search it makes
+pub async fn collect<F: Future>(future: F) -> (F::Output, SearchTimings) {
+    TIMINGS
+        .scope(RefCell::new(SearchTimings::default()), async move {
+            let output = future.await;
+            (output, TIMINGS.with(RefCell::take))
+        })
+        .await
+}
+
+/// Run a stage inside a `search_stage` tracing span and time it
+///
+/// The time is added to the request being collected, if any, and logged at
+/// debug level either way.
+pub fn stage<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    let span = tracing::debug_span!("search_stage", stage = stage.name());
+    let _entered = span.enter();
+    let started = std::time::Instant::now();
+    let output = f();
+    let elapsed = started.elapsed();
+    debug!(elapsed_ms = millis(elapsed), "Search stage finished");
+    let _ = TIMINGS.try_with(|timings| timings.borrow_mut().add(stage, elapsed));
+    output
+}
+
+/// Record one index's share of a multi-index search
+pub fn record_index(index: &str, elapsed: Duration, results: usize) {
+    debug!(
+        index,
+        elapsed_ms = millis(elapsed),
+        results,
+        "Index searched"
+    );
+    let _ = TIMINGS.try_with(|timings| {
+        timings.borrow_mut().indexes.push(IndexTiming {
+            index: index.to_string(),
+            ms: round(millis(elapsed)),
+            results,
+        })
+    });
+}
+
+pub fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+/// Keep two decimals, enough for sub-millisecond stages
+fn round(ms: f64) -> f64 {
+    (ms * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_timings() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let (output, timings) = runtime.block_on(collect(async {
+            stage(Stage::Parse, || {
+                std::thread::sleep(Duration::from_millis(2))
+            });
+            stage(Stage::Search, || {
+                std::thread::sleep(Duration::from_millis(2))
+            });
+            stage(Stage::Search, || ());
+            record_index("work", Duration::from_millis(5), 3);
+            42
+        }));
+        assert_eq!(output, 42);
+        assert!(timings.parse_ms >= 2.0);
+        assert!(timings.search_ms >= 2.0);
+        assert_eq!(timings.snippets_ms, 0.0);
+        assert_eq!(timings.indexes.len(), 1);
+        assert_eq!(timings.indexes[0].results, 3);
+
+        // Outside `collect` stages still run and nothing is recorded
+        assert_eq!(stage(Stage::Parse, || 1), 1);
+        record_index("work", Duration::from_millis(1), 0);
+    }
+}
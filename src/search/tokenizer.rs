@@ -1,37 +1,742 @@
 use anyhow::{Context, Result};
-use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
+use lindera::character_filter::BoxCharacterFilter;
+use lindera::character_filter::unicode_normalize::{
+    UnicodeNormalizeCharacterFilter, UnicodeNormalizeKind,
+};
+use lindera::dictionary::{
+    Dictionary, DictionaryKind, load_dictionary_from_kind, load_user_dictionary_from_csv,
+};
 use lindera::mode::{Mode, Penalty};
 use lindera::segmenter::Segmenter;
+use lindera::token_filter::BoxTokenFilter;
+use lindera::token_filter::lowercase::LowercaseTokenFilter;
 use lindera_tantivy::tokenizer::LinderaTokenizer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
 use tantivy::Index;
+use tantivy::tokenizer::{LowerCaser, RegexTokenizer, TextAnalyzer, Token, TokenStream, Tokenizer};
 use tracing::{debug, info};
+use unicode_normalization::UnicodeNormalization;
 
-/// Tokenizer name for Japanese text
+/// Tokenizer name for CJK text (the name predates Korean and Chinese support
+/// and is kept so existing index schemas stay valid)
 pub const JAPANESE_TOKENIZER_NAME: &str = "lang_ja";
 
-/// Register Lindera tokenizer for Japanese text processing
-pub fn register_lindera_tokenizer(index: &Index) -> Result<()> {
-    debug!("Registering Lindera tokenizer for Japanese text processing");
+/// Tokenizer name for the n-gram fallback field
+pub const NGRAM_TOKENIZER_NAME: &str = "ngram";
+
+/// Tokenizer name for the code block field
+pub const CODE_TOKENIZER_NAME: &str = "code";
+
+/// Tokenizer name for the words of a URL
+pub const URL_TOKENIZER_NAME: &str = "url";
+
+/// Tokenizer name for the title prefixes searched by typeahead
+pub const EDGE_NGRAM_TOKENIZER_NAME: &str = "edge_ngram";
+
+/// Longest word prefix indexed for typeahead; longer typed words are cut to it
+pub const MAX_PREFIX_CHARS: usize = 20;
+
+/// An identifier, optionally joined into a path by `::`, `.` or `->`
+const CODE_TOKEN_PATTERN: &str = r"[\w$]+(?:(?:::|\.|->)[\w$]+)*";
+
+/// A run of letters and digits; `/`, `.`, `-`, `_` and other punctuation separate them
+const URL_TOKEN_PATTERN: &str = r"[^\W_]+";
+
+/// Dictionaries loaded so far, shared by every tokenizer registration in the process
+static DICTIONARIES: OnceLock<Mutex<HashMap<LinderaDictionary, Dictionary>>> = OnceLock::new();
+
+/// Per-index file recording the dictionary the index was built with
+pub const TOKENIZER_METADATA_FILE: &str = "tokenizer.json";
+
+/// Lindera dictionary used for CJK text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinderaDictionary {
+    /// Japanese
+    #[default]
+    Ipadic,
+    /// Japanese; requires building with the `unidic` feature
+    Unidic,
+    /// Korean; requires building with the `ko-dic` feature
+    #[serde(rename = "ko-dic")]
+    KoDic,
+    /// Chinese; requires building with the `cc-cedict` feature
+    #[serde(rename = "cc-cedict")]
+    CcCedict,
+}
+
+impl LinderaDictionary {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ipadic => "IPADIC",
+            Self::Unidic => "UniDic",
+            Self::KoDic => "ko-dic",
+            Self::CcCedict => "CC-CEDICT",
+        }
+    }
+
+    fn kind(self) -> Result<DictionaryKind> {
+        match self {
+            Self::Ipadic => Ok(DictionaryKind::IPADIC),
+            #[cfg(feature = "unidic")]
+            Self::Unidic => Ok(DictionaryKind::UniDic),
+            #[cfg(not(feature = "unidic"))]
+            Self::Unidic => anyhow::bail!(
+                "UniDic is not available: mcp-bookmark was built without the `unidic` feature"
+            ),
+            #[cfg(feature = "ko-dic")]
+            Self::KoDic => Ok(DictionaryKind::KoDic),
+            #[cfg(not(feature = "ko-dic"))]
+            Self::KoDic => anyhow::bail!(
+                "ko-dic is not available: mcp-bookmark was built without the `ko-dic` feature"
+            ),
+            #[cfg(feature = "cc-cedict")]
+            Self::CcCedict => Ok(DictionaryKind::CcCedict),
+            #[cfg(not(feature = "cc-cedict"))]
+            Self::CcCedict => anyhow::bail!(
+                "CC-CEDICT is not available: mcp-bookmark was built without the `cc-cedict` feature"
+            ),
+        }
+    }
+}
+
+impl FromStr for LinderaDictionary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "ipadic" => Ok(Self::Ipadic),
+            "unidic" => Ok(Self::Unidic),
+            "ko-dic" | "kodic" => Ok(Self::KoDic),
+            "cc-cedict" | "cccedict" => Ok(Self::CcCedict),
+            _ => Err(format!(
+                "unknown dictionary: {s} (expected ipadic, unidic, ko-dic or cc-cedict)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LinderaDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Version of the analysis chain; bump when tokenization changes so indexes
+/// built by older releases are rejected instead of mis-searched
+///
+/// 2: NFKC normalization and lowercasing
+pub const ANALYZER_VERSION: u32 = 2;
+
+/// Version of indexes built before the analyzer was recorded
+const LEGACY_ANALYZER_VERSION: u32 = 1;
+
+/// Analyzer configuration an index was built with, kept in `tokenizer.json`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AnalyzerConfig {
+    pub dictionary: LinderaDictionary,
+    /// Hash of the user dictionary CSV, if one was used
+    #[serde(default)]
+    pub user_dictionary: Option<String>,
+    #[serde(default = "default_analyzer_version")]
+    pub version: u32,
+}
+
+fn default_analyzer_version() -> u32 {
+    LEGACY_ANALYZER_VERSION
+}
+
+impl AnalyzerConfig {
+    /// Configuration the current settings produce for `dictionary`
+    pub fn current(dictionary: LinderaDictionary) -> Result<Self> {
+        let user_dictionary = match &crate::config::settings().tokenizer.user_dictionary {
+            Some(path) => Some(hash_file(path)?),
+            None => None,
+        };
+        Ok(Self {
+            dictionary,
+            user_dictionary,
+            version: ANALYZER_VERSION,
+        })
+    }
+
+    /// Fail if an index recorded with `self` would be searched with `current`
+    pub fn check(&self, current: &Self) -> Result<()> {
+        let mut differences = Vec::new();
+        if self.version != current.version {
+            differences.push(format!(
+                "analyzer version {} (this build uses {})",
+                self.version, current.version
+            ));
+        }
+        if self.user_dictionary != current.user_dictionary {
+            differences.push(
+                match (&self.user_dictionary, &current.user_dictionary) {
+                    (None, Some(_)) => "no user dictionary (one is configured now)",
+                    (Some(_), None) => "a user dictionary (none is configured now)",
+                    _ => "a different user dictionary",
+                }
+                .to_string(),
+            );
+        }
+        if differences.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "index was built with {}; clear and rebuild it to search with the current tokenizer settings",
+            differences.join(" and ")
+        )
+    }
+}
 
-    // Load IPADIC dictionary
-    let dictionary = load_dictionary_from_kind(DictionaryKind::IPADIC)
-        .context("Failed to load IPADIC dictionary")?;
+fn hash_file(path: &Path) -> Result<String> {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let content =
+        std::fs::read(path).with_context(|| format!("Failed to read user dictionary {path:?}"))?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// Analyzer an index was built with (indexes without a record are legacy IPADIC ones)
+pub fn index_analyzer(index_dir: &Path) -> Result<AnalyzerConfig> {
+    let path = index_dir.join(TOKENIZER_METADATA_FILE);
+    if !path.exists() {
+        return Ok(AnalyzerConfig {
+            dictionary: LinderaDictionary::Ipadic,
+            user_dictionary: None,
+            version: LEGACY_ANALYZER_VERSION,
+        });
+    }
+    let content =
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {path:?}"))?;
+    // A dictionary this build doesn't know must not fall back to IPADIC,
+    // which would silently return wrong matches
+    serde_json::from_str(&content)
+        .with_context(|| format!("Unsupported tokenizer recorded in {path:?}"))
+}
+
+/// Dictionary an index was built with (indexes without a record use IPADIC)
+pub fn index_dictionary(index_dir: &Path) -> Result<LinderaDictionary> {
+    Ok(index_analyzer(index_dir)?.dictionary)
+}
+
+/// Record the analyzer a new index is built with
+pub fn save_index_analyzer(index_dir: &Path, dictionary: LinderaDictionary) -> Result<()> {
+    let path = index_dir.join(TOKENIZER_METADATA_FILE);
+    let content = serde_json::to_string_pretty(&AnalyzerConfig::current(dictionary)?)?;
+    std::fs::write(&path, content).with_context(|| format!("Failed to write {path:?}"))
+}
+
+/// Create a Lindera tokenizer for the given dictionary
+///
+/// `user_dictionary` is a Lindera user dictionary CSV
+/// (`surface,part of speech,reading` per line) whose terms are kept as single tokens.
+pub fn create_lindera_tokenizer(
+    dictionary: LinderaDictionary,
+    user_dictionary: Option<&Path>,
+) -> Result<LinderaTokenizer> {
+    let lindera_dictionary = load_dictionary(dictionary)?;
 
     // Use Decompose mode for better search results
     let mode = Mode::Decompose(Penalty::default());
-    let user_dictionary = None;
+    let user_dictionary = match user_dictionary {
+        Some(path) => {
+            debug!("Loading user dictionary from {:?}", path);
+            Some(
+                load_user_dictionary_from_csv(dictionary.kind()?, path)
+                    .with_context(|| format!("Failed to load user dictionary {path:?}"))?,
+            )
+        }
+        None => None,
+    };
 
     // Create Segmenter with the dictionary
-    let segmenter = Segmenter::new(mode, dictionary, user_dictionary);
+    let segmenter = Segmenter::new(mode, lindera_dictionary, user_dictionary);
+
+    // Create Lindera tokenizer from segmenter; NFKC folds full-width and
+    // half-width variants (ＡＰＩ, ｶﾀｶﾅ) before segmentation, then tokens are lowercased
+    let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    tokenizer
+        .append_character_filter(BoxCharacterFilter::from(
+            UnicodeNormalizeCharacterFilter::new(UnicodeNormalizeKind::NFKC),
+        ))
+        .append_token_filter(BoxTokenFilter::from(LowercaseTokenFilter::new()));
+    Ok(tokenizer)
+}
+
+/// Load a dictionary once per process and hand out copies of it
+///
+/// Decoding the embedded dictionary is the slow part of opening an index, and
+/// multi-index mode and the tests would otherwise repeat it for every index.
+fn load_dictionary(dictionary: LinderaDictionary) -> Result<Dictionary> {
+    // Holding the lock while loading keeps concurrent opens from decoding twice
+    let mut cache = DICTIONARIES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(loaded) = cache.get(&dictionary) {
+        return Ok(loaded.clone());
+    }
+
+    debug!("Loading {} dictionary", dictionary.name());
+    let loaded = load_dictionary_from_kind(dictionary.kind()?)
+        .with_context(|| format!("Failed to load {} dictionary", dictionary.name()))?;
+    cache.insert(dictionary, loaded.clone());
+    Ok(loaded)
+}
 
-    // Create Lindera tokenizer from segmenter
-    let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// Register Lindera tokenizer for Japanese text processing
+pub fn register_lindera_tokenizer(index: &Index) -> Result<()> {
+    register_lindera_tokenizer_with(index, LinderaDictionary::Ipadic)
+}
+
+/// Register Lindera tokenizer using a specific dictionary
+///
+/// The user dictionary from the `[tokenizer]` settings is applied, so indexing
+/// and querying segment custom terms the same way.
+pub fn register_lindera_tokenizer_with(index: &Index, dictionary: LinderaDictionary) -> Result<()> {
+    debug!(
+        "Registering Lindera tokenizer ({}) for CJK text processing",
+        dictionary.name()
+    );
+
+    let user_dictionary = crate::config::settings()
+        .tokenizer
+        .user_dictionary
+        .as_deref();
+    let tokenizer = create_lindera_tokenizer(dictionary, user_dictionary)?;
 
     // Register the tokenizer with name "lang_ja"
     index
         .tokenizers()
         .register(JAPANESE_TOKENIZER_NAME, tokenizer);
+    index
+        .tokenizers()
+        .register(NGRAM_TOKENIZER_NAME, CharNgramTokenizer);
+    index
+        .tokenizers()
+        .register(CODE_TOKENIZER_NAME, create_code_tokenizer()?);
+    index
+        .tokenizers()
+        .register(URL_TOKENIZER_NAME, create_url_tokenizer()?);
+    index
+        .tokenizers()
+        .register(EDGE_NGRAM_TOKENIZER_NAME, EdgeNgramTokenizer);
 
     info!("Lindera tokenizer registered successfully");
     Ok(())
 }
+
+/// Register the tokenizer recorded for an index, after checking that the
+/// current settings still analyze text the same way
+pub fn register_index_tokenizer(index: &Index, index_dir: &Path) -> Result<()> {
+    let recorded = index_analyzer(index_dir)?;
+    recorded
+        .check(&AnalyzerConfig::current(recorded.dictionary)?)
+        .with_context(|| format!("Tokenizer mismatch for index {index_dir:?}"))?;
+    register_lindera_tokenizer_with(index, recorded.dictionary).with_context(|| {
+        format!(
+            "Index {index_dir:?} was built with the {} dictionary",
+            recorded.dictionary
+        )
+    })
+}
+
+/// Lowercased identifiers and paths such as `IndexWriter::commit` or `self.index`
+///
+/// Underscores and separators stay inside the token, so a `code:` query for a
+/// path matches it as one term instead of as loose words.
+pub fn create_code_tokenizer() -> Result<TextAnalyzer> {
+    let tokenizer = RegexTokenizer::new(CODE_TOKEN_PATTERN)
+        .map_err(|e| anyhow::anyhow!("Invalid code token pattern: {e}"))?;
+    Ok(TextAnalyzer::builder(tokenizer).filter(LowerCaser).build())
+}
+
+/// Lowercased words of a URL, so `github.com/tokio-rs` matches as a phrase
+pub fn create_url_tokenizer() -> Result<TextAnalyzer> {
+    let tokenizer = RegexTokenizer::new(URL_TOKEN_PATTERN)
+        .map_err(|e| anyhow::anyhow!("Invalid URL token pattern: {e}"))?;
+    Ok(TextAnalyzer::builder(tokenizer).filter(LowerCaser).build())
+}
+
+/// A term produced by an index's analyzer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnalyzedToken {
+    pub text: String,
+    pub position: usize,
+    /// Byte offsets into the analyzed text
+    pub offset_from: usize,
+    pub offset_to: usize,
+}
+
+/// Terms the analyzer registered for `field_name` produces for `text`
+///
+/// These are the terms documents are indexed under and queries are matched
+/// against, so comparing a query's terms with a document's explains a miss.
+pub fn analyze(index: &Index, field_name: &str, text: &str) -> Result<Vec<AnalyzedToken>> {
+    let field = index
+        .schema()
+        .get_field(field_name)
+        .with_context(|| format!("Unknown field: {field_name}"))?;
+    let mut analyzer = index
+        .tokenizer_for_field(field)
+        .with_context(|| format!("Field {field_name} is not tokenized"))?;
+
+    let mut stream = analyzer.token_stream(text);
+    let mut tokens = Vec::new();
+    while stream.advance() {
+        let token = stream.token();
+        tokens.push(AnalyzedToken {
+            text: token.text.clone(),
+            position: token.position,
+            offset_from: token.offset_from,
+            offset_to: token.offset_to,
+        });
+    }
+    Ok(tokens)
+}
+
+/// NFKC-normalized, lowercased character unigrams and bigrams, skipping whitespace
+///
+/// Backs the n-gram fallback field, so text the dictionary can't segment
+/// (other scripts, emoji, symbols) is still searchable as substrings. A token's
+/// position is the index of its first character, so a query's bigrams can be
+/// matched as a phrase and single characters as plain terms. Offsets refer to
+/// the normalized text; the field isn't stored, so nothing highlights with them.
+#[derive(Debug, Clone, Default)]
+pub struct CharNgramTokenizer;
+
+impl Tokenizer for CharNgramTokenizer {
+    type TokenStream<'a> = CharNgramTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        CharNgramTokenStream {
+            tokens: char_ngrams(text),
+            next: 0,
+        }
+    }
+}
+
+pub struct CharNgramTokenStream {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl TokenStream for CharNgramTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.next < self.tokens.len() {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.next - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.next - 1]
+    }
+}
+
+fn char_ngrams(text: &str) -> Vec<Token> {
+    let normalized: String = text.nfkc().collect();
+    let chars: Vec<(usize, char)> = normalized.char_indices().collect();
+    let token = |position: usize, offset_to: usize, grams: &[char]| Token {
+        offset_from: chars[position].0,
+        offset_to,
+        position,
+        text: grams.iter().flat_map(|c| c.to_lowercase()).collect(),
+        position_length: 1,
+    };
+
+    let mut tokens = Vec::new();
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+        tokens.push(token(i, offset + c.len_utf8(), &[c]));
+        if let Some(&(next_offset, next)) = chars.get(i + 1).filter(|(_, n)| !n.is_whitespace()) {
+            tokens.push(token(i, next_offset + next.len_utf8(), &[c, next]));
+        }
+    }
+    tokens
+}
+
+/// NFKC-normalized, lowercased prefixes of every word, up to `MAX_PREFIX_CHARS`
+///
+/// Backs the typeahead field: `Tokio runtime` is indexed as `t`, `to`, ...,
+/// `tokio`, `r`, `ru`, ..., `runtime`, all prefixes of a word sharing its
+/// position. Words are runs of letters and digits, so CJK text without spaces
+/// only completes from the start of each run.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeNgramTokenizer;
+
+impl Tokenizer for EdgeNgramTokenizer {
+    type TokenStream<'a> = CharNgramTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        let mut tokens = Vec::new();
+        for (position, word) in prefix_words(text).into_iter().enumerate() {
+            for (end, _) in word.char_indices().skip(1) {
+                tokens.push(prefix_token(position, &word[..end]));
+            }
+            tokens.push(prefix_token(position, &word));
+        }
+        CharNgramTokenStream { tokens, next: 0 }
+    }
+}
+
+/// Normalized words of `text` as the typeahead field indexes them, each cut to `MAX_PREFIX_CHARS`
+pub fn prefix_words(text: &str) -> Vec<String> {
+    let normalized: String = text.nfkc().flat_map(char::to_lowercase).collect();
+    normalized
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.chars().take(MAX_PREFIX_CHARS).collect())
+        .collect()
+}
+
+fn prefix_token(position: usize, prefix: &str) -> Token {
+    // The field isn't stored, so offsets are never used for highlighting
+    Token {
+        offset_from: 0,
+        offset_to: prefix.len(),
+        position,
+        text: prefix.to_string(),
+        position_length: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_dictionary() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            index_dictionary(dir.path()).unwrap(),
+            LinderaDictionary::Ipadic
+        );
+
+        save_index_analyzer(dir.path(), LinderaDictionary::Unidic).unwrap();
+        assert_eq!(
+            index_dictionary(dir.path()).unwrap(),
+            LinderaDictionary::Unidic
+        );
+
+        assert_eq!("UniDic".parse(), Ok(LinderaDictionary::Unidic));
+        assert_eq!("ko_dic".parse(), Ok(LinderaDictionary::KoDic));
+        assert_eq!("CC-CEDICT".parse(), Ok(LinderaDictionary::CcCedict));
+        assert!("mecab".parse::<LinderaDictionary>().is_err());
+
+        // Unknown records fail instead of falling back to IPADIC
+        std::fs::write(
+            dir.path().join(TOKENIZER_METADATA_FILE),
+            r#"{"dictionary": "jieba"}"#,
+        )
+        .unwrap();
+        assert!(index_dictionary(dir.path()).is_err());
+        #[cfg(not(feature = "unidic"))]
+        assert!(create_lindera_tokenizer(LinderaDictionary::Unidic, None).is_err());
+    }
+
+    #[test]
+    fn test_analyzer_check() {
+        let recorded = AnalyzerConfig::current(LinderaDictionary::Ipadic).unwrap();
+        assert!(recorded.check(&recorded.clone()).is_ok());
+
+        let with_user_dictionary = AnalyzerConfig {
+            user_dictionary: Some("0123456789abcdef".to_string()),
+            ..recorded.clone()
+        };
+        let error = recorded.check(&with_user_dictionary).unwrap_err();
+        assert!(error.to_string().contains("no user dictionary"));
+        assert!(error.to_string().contains("rebuild"));
+
+        let older = AnalyzerConfig {
+            version: 0,
+            ..recorded.clone()
+        };
+        assert!(older.check(&recorded).is_err());
+
+        // Records written before the analyzer fields existed must be rebuilt
+        let legacy: AnalyzerConfig = serde_json::from_str(r#"{"dictionary": "ipadic"}"#).unwrap();
+        assert_eq!(legacy.version, LEGACY_ANALYZER_VERSION);
+        assert!(legacy.check(&recorded).is_err());
+    }
+
+    #[test]
+    fn test_dictionary_cache() {
+        load_dictionary(LinderaDictionary::Ipadic).unwrap();
+        load_dictionary(LinderaDictionary::Ipadic).unwrap();
+        let cache = DICTIONARIES.get().unwrap().lock().unwrap();
+        assert!(cache.contains_key(&LinderaDictionary::Ipadic));
+    }
+
+    fn tokens(tokenizer: &mut LinderaTokenizer, text: &str) -> Vec<String> {
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_char_ngram_tokenizer() {
+        let mut tokenizer = CharNgramTokenizer;
+        let mut stream = tokenizer.token_stream("Ａ😀c 한");
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            let token = stream.token();
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("a".to_string(), 0),
+                ("a😀".to_string(), 0),
+                ("😀".to_string(), 1),
+                ("😀c".to_string(), 1),
+                ("c".to_string(), 2),
+                ("한".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_tokenizer() {
+        let mut tokenizer = create_url_tokenizer().unwrap();
+        let mut stream =
+            tokenizer.token_stream("https://GitHub.com/tokio-rs/tokio_util?tab=readme");
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        assert_eq!(
+            tokens,
+            [
+                "https", "github", "com", "tokio", "rs", "tokio", "util", "tab", "readme"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edge_ngram_tokenizer() {
+        let mut tokenizer = EdgeNgramTokenizer;
+        let mut stream = tokenizer.token_stream("Ｔｏｋｉｏ: 非同期");
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            let token = stream.token();
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("t".to_string(), 0),
+                ("to".to_string(), 0),
+                ("tok".to_string(), 0),
+                ("toki".to_string(), 0),
+                ("tokio".to_string(), 0),
+                ("非".to_string(), 1),
+                ("非同".to_string(), 1),
+                ("非同期".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            prefix_words(&"a".repeat(30)),
+            ["a".repeat(MAX_PREFIX_CHARS)]
+        );
+    }
+
+    #[test]
+    fn test_normalization() {
+        let mut tokenizer = create_lindera_tokenizer(LinderaDictionary::Ipadic, None).unwrap();
+        assert_eq!(
+            tokens(&mut tokenizer, "ＡＰＩ"),
+            tokens(&mut tokenizer, "api")
+        );
+        assert_eq!(
+            tokens(&mut tokenizer, "ｶﾀｶﾅ"),
+            tokens(&mut tokenizer, "カタカナ")
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let index = Index::create_in_ram(crate::search::schema::BookmarkSchema::new().schema);
+        register_lindera_tokenizer(&index).unwrap();
+
+        let title = analyze(&index, "title", "ＡＰＩ").unwrap();
+        assert_eq!(title.len(), 1);
+        assert_eq!(title[0].text, "api");
+        assert_eq!(title[0].position, 0);
+
+        // Raw fields keep the whole value as one term
+        let url = analyze(&index, "url", "https://Example.com/").unwrap();
+        assert_eq!(url.len(), 1);
+        assert_eq!(url[0].text, "https://Example.com/");
+
+        let ngram: Vec<String> = analyze(&index, "ngram", "ab")
+            .unwrap()
+            .into_iter()
+            .map(|token| token.text)
+            .collect();
+        assert_eq!(ngram, vec!["a", "ab", "b"]);
+
+        let code: Vec<String> = analyze(
+            &index,
+            "code",
+            "let w = IndexWriter::commit(self.mem_size);",
+        )
+        .unwrap()
+        .into_iter()
+        .map(|token| token.text)
+        .collect();
+        assert_eq!(
+            code,
+            vec!["let", "w", "indexwriter::commit", "self.mem_size"]
+        );
+
+        assert!(analyze(&index, "body", "text").is_err());
+        assert!(analyze(&index, "date_added", "text").is_err());
+    }
+
+    #[test]
+    fn test_user_dictionary() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("userdic.csv");
+        std::fs::write(
+            &csv,
+            "東京スカイツリー,カスタム名詞,トウキョウスカイツリー\n",
+        )
+        .unwrap();
+
+        let mut tokenizer =
+            create_lindera_tokenizer(LinderaDictionary::Ipadic, Some(&csv)).unwrap();
+        assert!(
+            tokens(&mut tokenizer, "東京スカイツリーの最寄り駅")
+                .contains(&"東京スカイツリー".to_string())
+        );
+
+        let mut tokenizer = create_lindera_tokenizer(LinderaDictionary::Ipadic, None).unwrap();
+        assert!(
+            !tokens(&mut tokenizer, "東京スカイツリーの最寄り駅")
+                .contains(&"東京スカイツリー".to_string())
+        );
+
+        let missing = dir.path().join("missing.csv");
+        assert!(create_lindera_tokenizer(LinderaDictionary::Ipadic, Some(&missing)).is_err());
+    }
+}
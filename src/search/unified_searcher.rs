@@ -2,35 +2,64 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tantivy::{
-    Index, IndexReader, TantivyDocument, Term,
+    Index, IndexReader, Searcher, SnippetGenerator, TantivyDocument, Term,
     collector::TopDocs,
     directory::MmapDirectory,
     query::{
-        BooleanQuery, BoostQuery, EmptyQuery, Occur, PhraseQuery, Query, QueryParser, TermQuery,
+        BooleanQuery, BoostQuery, EmptyQuery, Occur, PhraseQuery, Query, QueryParser, RegexQuery,
+        TermQuery,
     },
     schema::Value,
+    tokenizer::TokenStream,
 };
 use tracing::debug;
 
-use super::common::{INDEX_METADATA_FILE, IndexStats, doc_to_result};
+use super::collection_stats::CollectionStats;
+use super::common::{
+    BookmarkEntry, INDEX_METADATA_FILE, IndexStats, count_unique_bookmarks, decompress_text,
+    doc_to_result, is_part_id,
+};
+use super::embeddings::SemanticHit;
+use super::index_defaults::IndexDefaults;
+use super::indexer::{DocumentPart, TranscriptCue};
+use super::keywords::{TopicCount, count_topics};
 use super::query_parser::{QueryParser as CustomQueryParser, QueryTerm};
 use super::schema::BookmarkSchema;
-use super::scored_snippet::ScoredSnippetGenerator;
-use super::tokenizer::register_lindera_tokenizer;
+use super::scored_snippet::SnippetOptions;
+use super::timings::{Stage, stage};
+use super::tokenizer::{
+    AnalyzedToken, CODE_TOKENIZER_NAME, NGRAM_TOKENIZER_NAME, analyze, prefix_words,
+    register_index_tokenizer,
+};
+use crate::config::SearchBoosts;
+
+/// Candidates fetched from each side per requested hybrid result
+pub const HYBRID_CANDIDATE_FACTOR: usize = 3;
+
+/// Candidates fetched per requested result when sibling parts are merged
+pub const PART_CANDIDATE_FACTOR: usize = 3;
+
+/// Candidates fetched per requested suggestion, so parts of one PDF can be folded
+const TYPEAHEAD_CANDIDATE_FACTOR: usize = 3;
 
 /// Unified searcher that combines all search functionality
 pub struct UnifiedSearcher {
     index: Index,
     schema: BookmarkSchema,
     reader: IndexReader,
-    scored_snippet_generator: ScoredSnippetGenerator,
     enable_boosting: bool,
+    boosts: SearchBoosts,
+    defaults: IndexDefaults,
+    /// Denied domains (and their subdomains) left out of every search
+    hidden_domains: Vec<String>,
 }
 
 impl std::fmt::Debug for UnifiedSearcher {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("UnifiedSearcher")
             .field("enable_boosting", &self.enable_boosting)
+            .field("boosts", &self.boosts)
+            .field("defaults", &self.defaults)
             .finish()
     }
 }
@@ -50,11 +79,24 @@ impl UnifiedSearcher {
             index,
             schema,
             reader,
-            scored_snippet_generator: ScoredSnippetGenerator::new(),
             enable_boosting: true,
+            boosts: crate::config::settings().boosts,
+            defaults: IndexDefaults::default(),
+            hidden_domains: crate::config::settings().domains.hidden().to_vec(),
         })
     }
 
+    /// Apply the search defaults stored with the index
+    pub fn set_defaults(&mut self, defaults: IndexDefaults) {
+        self.boosts = defaults.boosts.apply(crate::config::settings().boosts);
+        self.defaults = defaults;
+    }
+
+    /// Search defaults stored with the index
+    pub fn defaults(&self) -> &IndexDefaults {
+        &self.defaults
+    }
+
     /// Open an existing index in read-only mode
     pub fn open_readonly<P: AsRef<Path>>(index_path: P) -> Result<Self> {
         let index_path = index_path.as_ref();
@@ -68,14 +110,21 @@ impl UnifiedSearcher {
         let index = Index::open(mmap_directory).context("Failed to open index")?;
         let schema = BookmarkSchema::new();
 
-        // Register Lindera tokenizer for read-only index
-        register_lindera_tokenizer(&index)?;
+        // Register the tokenizer the index was built with
+        register_index_tokenizer(&index, index_path)?;
+
+        let mut searcher = Self::new(index, schema)?;
+        searcher.set_defaults(IndexDefaults::load_or_default(index_path));
+        Ok(searcher)
+    }
 
-        Self::new(index, schema)
+    /// Handle to the index reader, shared with the index watcher
+    pub fn reader(&self) -> IndexReader {
+        self.reader.clone()
     }
 
     /// Reload the index reader to see new changes
-    pub fn reload(&mut self) -> Result<()> {
+    pub fn reload(&self) -> Result<()> {
         self.reader.reload()?;
         Ok(())
     }
@@ -89,42 +138,157 @@ impl UnifiedSearcher {
 
         let searcher = self.reader.searcher();
 
-        let parsed_query = if self.enable_boosting {
-            self.create_boosted_query(query)?
+        let mut parsed_query = stage(Stage::Parse, || self.create_text_query(query))?;
+        parsed_query = self.without_excluded_domains(parsed_query, None);
+        let mut top_docs = stage(Stage::Search, || {
+            searcher.search(&parsed_query, &TopDocs::with_limit(limit))
+        })
+        .context("Search failed")?;
+
+        // Retry text that Lindera can't segment against the n-gram field
+        let fallback_query = if top_docs.is_empty() {
+            stage(Stage::Parse, || self.create_ngram_query(query))?
+                .map(|fallback| self.without_excluded_domains(fallback, None))
         } else {
-            self.create_simple_query(query)?
+            None
         };
-
-        let top_docs = searcher
-            .search(&parsed_query, &TopDocs::with_limit(limit))
+        if let Some(fallback_query) = fallback_query {
+            debug!("No Lindera matches, falling back to n-gram search");
+            top_docs = stage(Stage::Search, || {
+                searcher.search(&fallback_query, &TopDocs::with_limit(limit))
+            })
             .context("Search failed")?;
+            parsed_query = fallback_query;
+        }
 
         debug!("Search executed, got {} results", top_docs.len());
 
-        let mut results = Vec::new();
-        for (score, doc_address) in top_docs {
-            let doc = searcher.doc(doc_address)?;
-            results.push(self.doc_to_result(&doc, score, query)?);
+        stage(Stage::Snippets, || -> Result<Vec<SearchResult>> {
+            let defaults = SnippetOptions::default();
+            let snippet_options = SnippetOptions {
+                length: self.defaults.snippet_length.unwrap_or(defaults.length),
+                ..defaults
+            };
+            let content_snippets =
+                self.content_snippet_generator(&searcher, parsed_query.as_ref(), &snippet_options);
+            let mut results = Vec::new();
+            for (score, doc_address) in top_docs {
+                let doc = searcher.doc(doc_address)?;
+                results.push(doc_to_result(
+                    &doc,
+                    &self.schema,
+                    score,
+                    query,
+                    &snippet_options,
+                    content_snippets.as_ref(),
+                    false,
+                )?);
+            }
+            Ok(results)
+        })
+    }
+
+    /// Search with specific parameters and filters
+    pub fn search_with_params(&self, params: &SearchParams) -> Result<Vec<SearchResult>> {
+        let query_text = params.query.as_deref().filter(|q| !q.is_empty());
+        let text_query = stage(Stage::Parse, || {
+            query_text.map(|q| self.create_text_query(q)).transpose()
+        })?;
+        let results = self.search_filtered(params, text_query)?;
+
+        if !results.is_empty() {
+            return Ok(results);
+        }
+
+        // Retry text that Lindera can't segment against the n-gram field
+        let Some(query_text) = query_text else {
+            return Ok(results);
+        };
+        match stage(Stage::Parse, || self.create_ngram_query(query_text))? {
+            Some(fallback_query) => {
+                debug!("No Lindera matches, falling back to n-gram search");
+                self.search_filtered(params, Some(fallback_query))
+            }
+            None => Ok(results),
+        }
+    }
+
+    /// Fuse keyword results with semantic hits, weighted by `params.hybrid_weight`
+    ///
+    /// BM25 scores are divided by the best one so both sides are in 0..=1; the
+    /// fused score is `(1 - weight) * keyword + weight * similarity`. Pages found
+    /// only by meaning still have to pass the folder/domain/profile filters, and
+    /// get the matching chunk as their snippet.
+    pub fn search_hybrid(
+        &self,
+        params: &SearchParams,
+        semantic_hits: &[SemanticHit],
+    ) -> Result<Vec<SearchResult>> {
+        let weight = params.hybrid_weight.unwrap_or(0.0).clamp(0.0, 1.0);
+        let candidates = SearchParams {
+            limit: params.limit * HYBRID_CANDIDATE_FACTOR,
+            ..params.clone()
+        };
+        let mut results = self.search_with_params(&candidates)?;
+        let max_score = results.iter().map(|r| r.score).fold(0.0, f32::max);
+        for result in &mut results {
+            let keyword = if max_score > 0.0 {
+                result.score / max_score
+            } else {
+                0.0
+            };
+            result.score = (1.0 - weight) * keyword;
+        }
+
+        for hit in semantic_hits {
+            let similarity = weight * hit.score.max(0.0);
+            if let Some(result) = results.iter_mut().find(|r| r.url == hit.url) {
+                result.score += similarity;
+                continue;
+            }
+            if similarity <= 0.0 {
+                continue;
+            }
+            let url_query: Box<dyn Query> = Box::new(TermQuery::new(
+                Term::from_field_text(self.schema.url, &hit.url),
+                tantivy::schema::IndexRecordOption::Basic,
+            ));
+            let lookup = SearchParams {
+                limit: 1,
+                ..params.clone()
+            };
+            if let Some(mut result) = self.search_filtered(&lookup, Some(url_query))?.pop() {
+                result.score = similarity;
+                result.snippet = hit.snippet.clone();
+                result.snippet_range = Some(SnippetRange {
+                    start: hit.offset,
+                    end: hit.offset + hit.snippet.chars().count(),
+                });
+                result.page_number = hit.page_number.or(result.page_number);
+                result.section = None;
+                result.matched_heading = None;
+                result.more_snippets.clear();
+                results.push(result);
+            }
         }
 
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(params.limit);
         Ok(results)
     }
 
-    /// Search with specific parameters and filters
-    pub fn search_with_params(&self, params: &SearchParams) -> Result<Vec<SearchResult>> {
+    /// Run a text query (if any) combined with the filters in `params`
+    fn search_filtered(
+        &self,
+        params: &SearchParams,
+        text_query: Option<Box<dyn Query>>,
+    ) -> Result<Vec<SearchResult>> {
         let searcher = self.reader.searcher();
         let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
 
         // Add text query
-        if let Some(query_text) = &params.query {
-            if !query_text.is_empty() {
-                let text_query = if self.enable_boosting {
-                    self.create_boosted_query(query_text)?
-                } else {
-                    self.create_simple_query(query_text)?
-                };
-                subqueries.push((Occur::Must, text_query));
-            }
+        if let Some(text_query) = text_query {
+            subqueries.push((Occur::Must, text_query));
         }
 
         // Add folder filter
@@ -147,6 +311,33 @@ impl UnifiedSearcher {
             subqueries.push((Occur::Must, domain_query));
         }
 
+        // Add profile filter
+        if let Some(profile) = &params.profile_filter {
+            // Indexes created before the profile field existed have no tagged documents
+            if self.index.schema().get_field("profile").is_err() {
+                return Ok(Vec::new());
+            }
+            let term = Term::from_field_text(self.schema.profile, profile);
+            let profile_query: Box<dyn Query> = Box::new(TermQuery::new(
+                term,
+                tantivy::schema::IndexRecordOption::Basic,
+            ));
+            subqueries.push((Occur::Must, profile_query));
+        }
+
+        // Add tag filter
+        if let Some(tag) = &params.tag_filter {
+            if self.index.schema().get_field("tags").is_err() {
+                return Ok(Vec::new());
+            }
+            let term = Term::from_field_text(self.schema.tags, tag);
+            let tag_query: Box<dyn Query> = Box::new(TermQuery::new(
+                term,
+                tantivy::schema::IndexRecordOption::Basic,
+            ));
+            subqueries.push((Occur::Must, tag_query));
+        }
+
         // Build final query
         let query: Box<dyn Query> = if subqueries.is_empty() {
             Box::new(tantivy::query::AllQuery)
@@ -155,17 +346,67 @@ impl UnifiedSearcher {
         } else {
             Box::new(BooleanQuery::new(subqueries))
         };
+        let query = self.without_excluded_domains(query, params.domain_filter.as_deref());
 
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(params.limit))?;
+        let top_docs = stage(Stage::Search, || {
+            searcher.search(&query, &TopDocs::with_limit(params.limit))
+        })?;
 
-        let mut results = Vec::new();
-        let query_str = params.query.as_deref().unwrap_or("");
-        for (score, doc_address) in top_docs {
-            let doc: TantivyDocument = searcher.doc(doc_address)?;
-            results.push(self.doc_to_result(&doc, score, query_str)?);
-        }
+        stage(Stage::Snippets, || -> Result<Vec<SearchResult>> {
+            let mut snippet_options = params.snippet_options();
+            if let (None, Some(length)) = (params.snippet_length, self.defaults.snippet_length) {
+                snippet_options.length = length;
+            }
+            let content_snippets =
+                self.content_snippet_generator(&searcher, query.as_ref(), &snippet_options);
+            let mut results = Vec::new();
+            let query_str = params.query.as_deref().unwrap_or("");
+            for (score, doc_address) in top_docs {
+                let doc: TantivyDocument = searcher.doc(doc_address)?;
+                results.push(doc_to_result(
+                    &doc,
+                    &self.schema,
+                    score,
+                    query_str,
+                    &snippet_options,
+                    content_snippets.as_ref(),
+                    params.include_content,
+                )?);
+            }
+            Ok(results)
+        })
+    }
 
-        Ok(results)
+    /// Leave out the index's excluded domains, unless `domain_filter` asks for one,
+    /// and always the hidden ones
+    fn without_excluded_domains(
+        &self,
+        query: Box<dyn Query>,
+        domain_filter: Option<&str>,
+    ) -> Box<dyn Query> {
+        let mut subqueries: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, query)];
+        for domain in self.defaults.excluded_domains(domain_filter) {
+            let term = Term::from_field_text(self.schema.domain, domain);
+            subqueries.push((
+                Occur::MustNot,
+                Box::new(TermQuery::new(
+                    term,
+                    tantivy::schema::IndexRecordOption::Basic,
+                )),
+            ));
+        }
+        for domain in &self.hidden_domains {
+            let domain = domain.trim_start_matches('.').to_lowercase();
+            let pattern = format!("(.*\\.)?{}", regex::escape(&domain));
+            match RegexQuery::from_pattern(&pattern, self.schema.domain) {
+                Ok(query) => subqueries.push((Occur::MustNot, Box::new(query))),
+                Err(e) => debug!("Not hiding domain {:?}: {}", domain, e),
+            }
+        }
+        if subqueries.len() == 1 {
+            return subqueries.pop().unwrap().1;
+        }
+        Box::new(BooleanQuery::new(subqueries))
     }
 
     /// Get full content by URL from index
@@ -185,34 +426,34 @@ impl UnifiedSearcher {
             let inverted_index = segment_reader.inverted_index(self.schema.url)?;
 
             if let Some(_term_info) = inverted_index.get_term_info(&term)? {
-                let postings_opt =
-                    inverted_index.read_postings(&term, tantivy::schema::IndexRecordOption::Basic)?;
+                let postings_opt = inverted_index
+                    .read_postings(&term, tantivy::schema::IndexRecordOption::Basic)?;
 
                 if let Some(mut postings) = postings_opt {
                     let store_reader = segment_reader.get_store_reader(1)?;
 
-                loop {
-                    let doc_id = postings.doc();
-                    if doc_id == TERMINATED {
-                        break;
-                    }
-
-                    if let Ok(doc) = store_reader.get::<TantivyDocument>(doc_id) {
-                        let id = doc
-                            .get_first(self.schema.id)
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
+                    loop {
+                        let doc_id = postings.doc();
+                        if doc_id == TERMINATED {
+                            break;
+                        }
 
-                        if let Some(content_value) = doc.get_first(self.schema.content) {
-                            if let Some(content_text) = content_value.as_str() {
-                                parts.push((id, content_text.to_string()));
+                        if let Ok(doc) = store_reader.get::<TantivyDocument>(doc_id) {
+                            let id = doc
+                                .get_first(self.schema.id)
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("")
+                                .to_string();
+
+                            if let Some(content_value) = doc.get_first(self.schema.content) {
+                                if let Some(content_text) = content_value.as_str() {
+                                    parts.push((id, content_text.to_string()));
+                                }
                             }
                         }
-                    }
 
-                    postings.advance();
-                }
+                        postings.advance();
+                    }
                 }
             }
         }
@@ -272,33 +513,245 @@ impl UnifiedSearcher {
     /// Count unique bookmarks by extracting base IDs from all documents
     /// Documents with IDs like "123_part_0", "123_part_1" are counted as one bookmark "123"
     pub fn count_unique_bookmarks(&self) -> Result<usize> {
-        use std::collections::HashSet;
+        count_unique_bookmarks(&self.reader.searcher(), &self.schema)
+    }
 
+    /// Compute aggregate statistics over the whole collection
+    pub fn collection_stats(&self) -> Result<CollectionStats> {
+        CollectionStats::compute(&self.reader.searcher(), &self.schema)
+    }
+
+    /// Most common tags in the index, extracted keywords included
+    pub fn topics(&self, limit: usize) -> Result<Vec<TopicCount>> {
+        let entries = self.list_bookmarks()?;
+        Ok(count_topics(
+            entries.iter().map(|entry| entry.tags.as_slice()),
+            limit,
+        ))
+    }
+
+    /// List all bookmarks in the index, one entry per bookmark (split parts excluded)
+    pub fn list_bookmarks(&self) -> Result<Vec<BookmarkEntry>> {
         let searcher = self.reader.searcher();
-        let mut base_ids: HashSet<String> = HashSet::new();
+        let mut entries = Vec::new();
 
-        // Iterate through all segments and documents directly (no limit)
         for segment_reader in searcher.segment_readers() {
             let store_reader = segment_reader.get_store_reader(1)?;
 
-            for doc_id in 0..segment_reader.num_docs() {
+            for doc_id in segment_reader.doc_ids_alive() {
                 if let Ok(doc) = store_reader.get::<TantivyDocument>(doc_id) {
-                    if let Some(id_value) = doc.get_first(self.schema.id) {
-                        if let Some(id_str) = id_value.as_str() {
-                            // Extract base ID by removing _part_N suffix
-                            let base_id = if let Some(pos) = id_str.find("_part_") {
-                                &id_str[..pos]
-                            } else {
-                                id_str
-                            };
-                            base_ids.insert(base_id.to_string());
-                        }
+                    let entry = BookmarkEntry::from_doc(&doc, &self.schema);
+                    if !entry.id.is_empty() && !is_part_id(&entry.id) {
+                        entries.push(entry);
                     }
                 }
             }
         }
 
-        Ok(base_ids.len())
+        // Stable ordering so paginated listings don't shift between calls
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(entries)
+    }
+
+    /// Markdown rendition stored for a URL, if its page was indexed with markup
+    pub fn get_markdown_by_url(&self, url: &str) -> Result<Option<String>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.url, url);
+        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+
+        for (_, doc_address) in searcher.search(&query, &TopDocs::with_limit(1))? {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            if let Some(markdown) = doc
+                .get_first(self.schema.content_markdown)
+                .and_then(|v| v.as_str())
+            {
+                return Ok(Some(markdown.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Original HTML stored for a URL, if HTML storage was enabled when it was indexed
+    pub fn get_html_by_url(&self, url: &str) -> Result<Option<String>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.url, url);
+        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+
+        for (_, doc_address) in searcher.search(&query, &TopDocs::with_limit(1))? {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            if let Some(bytes) = doc
+                .get_first(self.schema.raw_html)
+                .and_then(|v| v.as_bytes())
+            {
+                return decompress_text(bytes).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Character offsets where each page of a PDF starts in its combined content
+    ///
+    /// Split parts store offsets relative to themselves; they are shifted back
+    /// by the lengths of the parts before them, ordered by `first_page`. None
+    /// when the URL has no page offsets or a part predates `first_page`.
+    pub fn get_page_offsets_by_url(&self, url: &str) -> Result<Option<Vec<usize>>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.url, url);
+        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+
+        // (first page, offsets, content length in chars) of each part
+        let mut parts: Vec<(u64, Vec<usize>, usize)> = Vec::new();
+        for doc_address in searcher.search(&query, &tantivy::collector::DocSetCollector)? {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            let Some(bytes) = doc
+                .get_first(self.schema.page_offsets)
+                .and_then(|v| v.as_bytes())
+            else {
+                return Ok(None);
+            };
+            let Some(first_page) = doc
+                .get_first(self.schema.first_page)
+                .and_then(|v| v.as_u64())
+            else {
+                return Ok(None);
+            };
+            let offsets: Vec<usize> =
+                serde_json::from_slice(bytes).context("Invalid stored page offsets")?;
+            let chars = doc
+                .get_first(self.schema.content)
+                .and_then(|v| v.as_str())
+                .map_or(0, |content| content.chars().count());
+            parts.push((first_page, offsets, chars));
+        }
+        if parts.is_empty() {
+            return Ok(None);
+        }
+
+        parts.sort_by_key(|(first_page, _, _)| *first_page);
+        let mut base = 0;
+        let mut global = Vec::new();
+        for (_, offsets, chars) in parts {
+            global.extend(offsets.iter().map(|offset| base + offset));
+            base += chars;
+        }
+        Ok(Some(global))
+    }
+
+    /// Content type stored for a URL (`pdf`, `html`, ...)
+    pub fn get_content_type_by_url(&self, url: &str) -> Result<Option<String>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.url, url);
+        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+
+        for (_, doc_address) in searcher.search(&query, &TopDocs::with_limit(1))? {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            if let Some(content_type) = doc
+                .get_first(self.schema.content_type)
+                .and_then(|v| v.as_str())
+            {
+                return Ok(Some(content_type.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Transcript cues stored for a URL, empty unless it was indexed as a video transcript
+    pub fn get_transcript_by_url(&self, url: &str) -> Result<Vec<TranscriptCue>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.url, url);
+        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+
+        for (_, doc_address) in searcher.search(&query, &TopDocs::with_limit(1))? {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            if let Some(bytes) = doc
+                .get_first(self.schema.transcript_offsets)
+                .and_then(|v| v.as_bytes())
+            {
+                return serde_json::from_slice(bytes).context("Invalid stored transcript");
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Bookmarks whose title has a word starting with each word of `query`
+    ///
+    /// Only the prefix field is searched and no snippets are built, so this stays
+    /// fast enough to run on every keystroke. Indexes created before the field
+    /// existed return no suggestions.
+    pub fn typeahead(&self, query: &str, limit: usize) -> Result<Vec<Suggestion>> {
+        if self.index.schema().get_field("title_prefix").is_err() {
+            debug!("Index has no title_prefix field; rebuild it for typeahead");
+            return Ok(Vec::new());
+        }
+        let words = prefix_words(query);
+        if words.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let clauses: Vec<(Occur, Box<dyn Query>)> = words
+            .iter()
+            .map(|word| {
+                let term = Term::from_field_text(self.schema.title_prefix, word);
+                let query: Box<dyn Query> = Box::new(TermQuery::new(
+                    term,
+                    tantivy::schema::IndexRecordOption::WithFreqs,
+                ));
+                (Occur::Must, query)
+            })
+            .collect();
+        let query = self.without_excluded_domains(Box::new(BooleanQuery::new(clauses)), None);
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(
+            &query,
+            &TopDocs::with_limit(limit.saturating_mul(TYPEAHEAD_CANDIDATE_FACTOR)),
+        )?;
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for (score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            let text = |field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string()
+            };
+            let url = text(self.schema.url);
+            // Parts of a split PDF share the title and URL
+            if suggestions.iter().any(|s| s.url == url) {
+                continue;
+            }
+            suggestions.push(Suggestion {
+                id: text(self.schema.id),
+                title: text(self.schema.title),
+                url,
+                score,
+            });
+            if suggestions.len() == limit {
+                break;
+            }
+        }
+        Ok(suggestions)
+    }
+
+    /// Terms the index's analyzer produces for `text` in `field`
+    pub fn tokenize(&self, text: &str, field: &str) -> Result<Vec<AnalyzedToken>> {
+        analyze(&self.index, field, text)
+    }
+
+    /// Find a bookmark by its ID
+    pub fn get_bookmark_by_id(&self, id: &str) -> Result<Option<BookmarkEntry>> {
+        let searcher = self.reader.searcher();
+        let term = Term::from_field_text(self.schema.id, id);
+        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        match top_docs.into_iter().next() {
+            Some((_, doc_address)) => {
+                let doc: TantivyDocument = searcher.doc(doc_address)?;
+                Ok(Some(BookmarkEntry::from_doc(&doc, &self.schema)))
+            }
+            None => Ok(None),
+        }
     }
 
     /// Parse query and return terms, or empty query if needed
@@ -316,6 +769,79 @@ impl UnifiedSearcher {
         Ok((terms, false))
     }
 
+    /// Query for the Lindera-analyzed fields, boosted unless disabled
+    fn create_text_query(&self, query: &str) -> Result<Box<dyn Query>> {
+        if self.enable_boosting {
+            self.create_boosted_query(query)
+        } else {
+            self.create_simple_query(query)
+        }
+    }
+
+    /// Fallback query matching every term as a substring via the n-gram field
+    ///
+    /// Returns None for indexes created before the field existed and for
+    /// queries with `code:` terms.
+    fn create_ngram_query(&self, query: &str) -> Result<Option<Box<dyn Query>>> {
+        if self.index.schema().get_field("ngram").is_err() {
+            return Ok(None);
+        }
+        let (terms, should_return_empty) = self.parse_query_terms(query)?;
+        if should_return_empty {
+            return Ok(None);
+        }
+
+        let mut tokenizer = self
+            .index
+            .tokenizers()
+            .get(NGRAM_TOKENIZER_NAME)
+            .context("N-gram tokenizer is not registered")?;
+        let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for term in terms {
+            let text = match term {
+                QueryTerm::Phrase(text) | QueryTerm::Word(text) => text,
+                // `code:` asks for whole identifiers; substrings would defeat it
+                QueryTerm::Code(_) => return Ok(None),
+            };
+            let mut stream = tokenizer.token_stream(&text);
+            let mut grams: Vec<(usize, String)> = Vec::new();
+            while stream.advance() {
+                let token = stream.token();
+                grams.push((token.position, token.text.clone()));
+            }
+            // Bigrams pin down the order; keep only unigrams no bigram covers
+            let bigram_positions: std::collections::HashSet<usize> = grams
+                .iter()
+                .filter(|(_, gram)| gram.chars().count() > 1)
+                .map(|(position, _)| *position)
+                .collect();
+            grams.retain(|(position, gram)| {
+                gram.chars().count() > 1
+                    || !(bigram_positions.contains(position)
+                        || position
+                            .checked_sub(1)
+                            .is_some_and(|previous| bigram_positions.contains(&previous)))
+            });
+            let mut grams: Vec<(usize, Term)> = grams
+                .into_iter()
+                .map(|(position, gram)| (position, Term::from_field_text(self.schema.ngram, &gram)))
+                .collect();
+
+            let gram_query: Box<dyn Query> = match grams.len() {
+                0 => continue,
+                1 => Box::new(TermQuery::new(
+                    grams.remove(0).1,
+                    tantivy::schema::IndexRecordOption::WithFreqs,
+                )),
+                _ => Box::new(PhraseQuery::new_with_offset(grams)),
+            };
+            subqueries.push((Occur::Must, gram_query));
+        }
+
+        Ok((!subqueries.is_empty())
+            .then(|| Box::new(BooleanQuery::new(subqueries)) as Box<dyn Query>))
+    }
+
     /// Create a simple query without boosting (supports phrases)
     fn create_simple_query(&self, query: &str) -> Result<Box<dyn Query>> {
         let (terms, should_return_empty) = self.parse_query_terms(query)?;
@@ -354,12 +880,19 @@ impl UnifiedSearcher {
                         continue;
                     }
 
-                    // Use regular query parser for individual words
+                    // Use regular query parser for individual words, with
+                    // reserved characters searched as text
+                    let word = CustomQueryParser::escape(&word);
                     let query_parser = QueryParser::for_index(&self.index, text_fields.clone());
                     if let Ok(word_query) = query_parser.parse_query(&word) {
                         subqueries.push((Occur::Should, word_query));
                     }
                 }
+                QueryTerm::Code(code) => {
+                    if let Some(code_query) = self.create_code_query(&code)? {
+                        subqueries.push((Occur::Must, code_query));
+                    }
+                }
             }
         }
 
@@ -379,12 +912,11 @@ impl UnifiedSearcher {
         field: tantivy::schema::Field,
         phrase: &str,
     ) -> Result<Box<dyn Query>> {
-        // Tokenize the phrase to get individual terms
+        // Tokenize the phrase the way the field was indexed
         let mut tokenizer = self
             .index
-            .tokenizers()
-            .get("lang_ja")
-            .ok_or_else(|| anyhow::anyhow!("Tokenizer not found"))?;
+            .tokenizer_for_field(field)
+            .context("Tokenizer not found")?;
 
         let mut token_stream = tokenizer.token_stream(phrase);
         let mut terms = Vec::new();
@@ -394,11 +926,48 @@ impl UnifiedSearcher {
             terms.push(term);
         }
 
-        if terms.is_empty() {
-            return Err(anyhow::anyhow!("No terms found in phrase"));
+        match terms.len() {
+            0 => Err(anyhow::anyhow!("No terms found in phrase")),
+            // A phrase query needs two terms
+            1 => Ok(Box::new(TermQuery::new(
+                terms.remove(0),
+                tantivy::schema::IndexRecordOption::WithFreqs,
+            ))),
+            _ => Ok(Box::new(PhraseQuery::new(terms))),
+        }
+    }
+
+    /// Query for a `code:` term: its identifiers, in order, in a code block
+    ///
+    /// Indexes created before the code field existed match it as a content phrase
+    /// instead. Returns None when the term has no identifiers.
+    fn create_code_query(&self, code: &str) -> Result<Option<Box<dyn Query>>> {
+        if self.index.schema().get_field("code").is_err() {
+            return Ok(self.create_phrase_query(self.schema.content, code).ok());
+        }
+
+        let mut tokenizer = self
+            .index
+            .tokenizers()
+            .get(CODE_TOKENIZER_NAME)
+            .context("Code tokenizer is not registered")?;
+        let mut stream = tokenizer.token_stream(code);
+        let mut terms = Vec::new();
+        while stream.advance() {
+            terms.push(Term::from_field_text(
+                self.schema.code,
+                &stream.token().text,
+            ));
         }
 
-        Ok(Box::new(PhraseQuery::new(terms)))
+        Ok(match terms.len() {
+            0 => None,
+            1 => Some(Box::new(TermQuery::new(
+                terms.remove(0),
+                tantivy::schema::IndexRecordOption::WithFreqs,
+            ))),
+            _ => Some(Box::new(PhraseQuery::new(terms))),
+        })
     }
 
     /// Create a boosted query with field-specific weights (supports phrases)
@@ -408,6 +977,9 @@ impl UnifiedSearcher {
             return Ok(Box::new(EmptyQuery));
         }
 
+        // Indexes created before the headings and URL word fields existed can't be queried on them
+        let has_headings = self.index.schema().get_field("headings").is_ok();
+        let has_url_text = self.index.schema().get_field("url_text").is_ok();
         let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
 
         for term in terms {
@@ -419,19 +991,38 @@ impl UnifiedSearcher {
                     }
 
                     // Create boosted phrase queries for fields that support position indexing
-                    // URL field is STRING type and doesn't support phrase queries
+                    // URL field is STRING type, so phrases match its words in url_text
                     let mut phrase_field_queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
 
                     if let Ok(title_phrase) = self.create_phrase_query(self.schema.title, &phrase) {
                         let boosted_title: Box<dyn Query> =
-                            Box::new(BoostQuery::new(title_phrase, 3.0));
+                            Box::new(BoostQuery::new(title_phrase, self.boosts.title));
                         phrase_field_queries.push((Occur::Should, boosted_title));
                     }
 
+                    let headings_phrase = has_headings
+                        .then(|| self.create_phrase_query(self.schema.headings, &phrase).ok())
+                        .flatten();
+                    if let Some(headings_phrase) = headings_phrase {
+                        let boosted_headings: Box<dyn Query> =
+                            Box::new(BoostQuery::new(headings_phrase, self.boosts.headings));
+                        phrase_field_queries.push((Occur::Should, boosted_headings));
+                    }
+
+                    let url_phrase = has_url_text
+                        .then(|| self.create_phrase_query(self.schema.url_text, &phrase).ok())
+                        .flatten();
+                    if let Some(url_phrase) = url_phrase {
+                        let boosted_url: Box<dyn Query> =
+                            Box::new(BoostQuery::new(url_phrase, self.boosts.url));
+                        phrase_field_queries.push((Occur::Should, boosted_url));
+                    }
+
                     if let Ok(content_phrase) =
                         self.create_phrase_query(self.schema.content, &phrase)
                     {
-                        let content_query: Box<dyn Query> = content_phrase;
+                        let content_query: Box<dyn Query> =
+                            Box::new(BoostQuery::new(content_phrase, self.boosts.content));
                         phrase_field_queries.push((Occur::Should, content_query));
                     }
 
@@ -448,25 +1039,63 @@ impl UnifiedSearcher {
                         continue;
                     }
 
-                    // Title query with 3x boost
+                    // Reserved characters are searched as text, not query syntax
+                    let word = CustomQueryParser::escape(&word);
+
+                    // Title and URL matches weigh more than content by default
                     let title_parser = QueryParser::for_index(&self.index, vec![self.schema.title]);
                     if let Ok(title_query) = title_parser.parse_query(&word) {
-                        let boosted_title_query = Box::new(BoostQuery::new(title_query, 3.0));
+                        let boosted_title_query =
+                            Box::new(BoostQuery::new(title_query, self.boosts.title));
                         subqueries.push((Occur::Should, boosted_title_query));
                     }
 
-                    // URL query with 2x boost
                     let url_parser = QueryParser::for_index(&self.index, vec![self.schema.url]);
                     if let Ok(url_query) = url_parser.parse_query(&word) {
-                        let boosted_url_query = Box::new(BoostQuery::new(url_query, 2.0));
+                        let boosted_url_query =
+                            Box::new(BoostQuery::new(url_query, self.boosts.url));
                         subqueries.push((Occur::Should, boosted_url_query));
                     }
 
-                    // Content query with normal weight (1x)
+                    // Words of the URL, e.g. `tokio-rs` or `github.com/tokio-rs` as a phrase
+                    let url_text_query = has_url_text
+                        .then(|| {
+                            QueryParser::for_index(&self.index, vec![self.schema.url_text])
+                                .parse_query(&word)
+                                .ok()
+                        })
+                        .flatten();
+                    if let Some(url_text_query) = url_text_query {
+                        let boosted_url_text_query =
+                            Box::new(BoostQuery::new(url_text_query, self.boosts.url));
+                        subqueries.push((Occur::Should, boosted_url_text_query));
+                    }
+
+                    // Headings weigh between the title and the body
+                    let headings_query = has_headings
+                        .then(|| {
+                            QueryParser::for_index(&self.index, vec![self.schema.headings])
+                                .parse_query(&word)
+                                .ok()
+                        })
+                        .flatten();
+                    if let Some(headings_query) = headings_query {
+                        let boosted_headings_query =
+                            Box::new(BoostQuery::new(headings_query, self.boosts.headings));
+                        subqueries.push((Occur::Should, boosted_headings_query));
+                    }
+
                     let content_parser =
                         QueryParser::for_index(&self.index, vec![self.schema.content]);
                     if let Ok(content_query) = content_parser.parse_query(&word) {
-                        subqueries.push((Occur::Should, content_query));
+                        let boosted_content_query =
+                            Box::new(BoostQuery::new(content_query, self.boosts.content));
+                        subqueries.push((Occur::Should, boosted_content_query));
+                    }
+                }
+                QueryTerm::Code(code) => {
+                    if let Some(code_query) = self.create_code_query(&code)? {
+                        subqueries.push((Occur::Must, code_query));
                     }
                 }
             }
@@ -480,20 +1109,30 @@ impl UnifiedSearcher {
         }
     }
 
-    /// Convert document to search result
-    fn doc_to_result(
+    /// tantivy's snippet generator for the content field, which picks fragments
+    /// by the query's analyzed terms and their token offsets
+    ///
+    /// Queries without content terms (filters only, n-gram fallback) get no
+    /// fragments, so their results use the scored snippet generator instead.
+    fn content_snippet_generator(
         &self,
-        doc: &TantivyDocument,
-        score: f32,
-        query: &str,
-    ) -> Result<SearchResult> {
-        doc_to_result(
-            doc,
-            &self.schema,
-            score,
-            query,
-            &self.scored_snippet_generator,
-        )
+        searcher: &Searcher,
+        query: &dyn Query,
+        options: &SnippetOptions,
+    ) -> Option<SnippetGenerator> {
+        if !options.is_single_fragment() {
+            return None;
+        }
+        match SnippetGenerator::create(searcher, query, self.schema.content) {
+            Ok(mut generator) => {
+                generator.set_max_num_chars(options.length);
+                Some(generator)
+            }
+            Err(e) => {
+                debug!("Snippet generator unavailable: {}", e);
+                None
+            }
+        }
     }
 }
 
@@ -503,7 +1142,29 @@ pub struct SearchParams {
     pub query: Option<String>,
     pub folder_filter: Option<String>,
     pub domain_filter: Option<String>,
+    pub profile_filter: Option<String>,
+    /// Only bookmarks carrying this tag (user tags and extracted keywords)
+    #[serde(default)]
+    pub tag_filter: Option<String>,
     pub limit: usize,
+    /// Copy each hit's full stored content into `full_content`
+    #[serde(default)]
+    pub include_content: bool,
+    /// Snippets per result (default: 1)
+    #[serde(default)]
+    pub snippet_count: Option<usize>,
+    /// Maximum snippet length (default: the configured snippet length)
+    #[serde(default)]
+    pub snippet_length: Option<usize>,
+    /// Context kept around each match (default: a third of the snippet length)
+    #[serde(default)]
+    pub context_chars: Option<usize>,
+    /// Share of the vector score in hybrid ranking, 0.0-1.0 (None: keyword only)
+    #[serde(default)]
+    pub hybrid_weight: Option<f32>,
+    /// Merge hits on parts of the same split PDF into one result
+    #[serde(default)]
+    pub merge_parts: bool,
 }
 
 impl SearchParams {
@@ -513,7 +1174,15 @@ impl SearchParams {
             query: Some(query.to_string()),
             folder_filter: None,
             domain_filter: None,
+            profile_filter: None,
+            tag_filter: None,
             limit: 20,
+            include_content: false,
+            snippet_count: None,
+            snippet_length: None,
+            context_chars: None,
+            hybrid_weight: None,
+            merge_parts: false,
         }
     }
 
@@ -529,11 +1198,69 @@ impl SearchParams {
         self
     }
 
+    /// Set browser profile filter
+    pub fn with_profile(mut self, profile: String) -> Self {
+        self.profile_filter = Some(profile);
+        self
+    }
+
+    /// Set tag filter
+    pub fn with_tag(mut self, tag: String) -> Self {
+        self.tag_filter = Some(tag);
+        self
+    }
+
     /// Set limit
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
     }
+
+    /// Include the full stored content in each result
+    pub fn with_content(mut self, include: bool) -> Self {
+        self.include_content = include;
+        self
+    }
+
+    /// Set the number of snippets per result
+    pub fn with_snippet_count(mut self, count: usize) -> Self {
+        self.snippet_count = Some(count);
+        self
+    }
+
+    /// Set the maximum length of each snippet
+    pub fn with_snippet_length(mut self, length: usize) -> Self {
+        self.snippet_length = Some(length);
+        self
+    }
+
+    /// Set the context kept around each match
+    pub fn with_context_chars(mut self, context_chars: usize) -> Self {
+        self.context_chars = Some(context_chars);
+        self
+    }
+
+    /// Rank by keyword and semantic similarity, `weight` being the vector share
+    pub fn with_hybrid_weight(mut self, weight: f32) -> Self {
+        self.hybrid_weight = Some(weight);
+        self
+    }
+
+    /// Merge hits on parts of the same split PDF into one result
+    pub fn with_merged_parts(mut self, merge: bool) -> Self {
+        self.merge_parts = merge;
+        self
+    }
+
+    /// Snippet settings, with defaults for anything not set
+    pub fn snippet_options(&self) -> SnippetOptions {
+        let defaults = SnippetOptions::default();
+        SnippetOptions {
+            count: self.snippet_count.unwrap_or(defaults.count).max(1),
+            length: self.snippet_length.unwrap_or(defaults.length),
+            context_chars: self.context_chars,
+        }
+    }
 }
 
 impl Default for SearchParams {
@@ -542,42 +1269,189 @@ impl Default for SearchParams {
             query: None,
             folder_filter: None,
             domain_filter: None,
+            profile_filter: None,
+            tag_filter: None,
             limit: 20,
+            include_content: false,
+            snippet_count: None,
+            snippet_length: None,
+            context_chars: None,
+            hybrid_weight: None,
+            merge_parts: false,
         }
     }
 }
 
+/// Character range of a snippet in the indexed document
+///
+/// For PDFs split into parts, offsets are relative to the part the result came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnippetRange {
+    /// Offset of the first character, counted in Unicode scalar values
+    pub start: usize,
+    /// Offset just past the last character
+    pub end: usize,
+}
+
 /// Search result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: String,
     pub title: String,
     pub url: String,
+    /// Host of `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// Extractive summary of the page, stored at index time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     pub snippet: String,
+    /// Where the snippet text sits in the indexed document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet_range: Option<SnippetRange>,
+    /// Heading of the section the snippet is in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Page heading that matched the query, when the headings field contributed to the hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_heading: Option<String>,
+    /// Further snippets, best first, when more than one was requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub more_snippets: Vec<String>,
+    /// Only set when requested with `SearchParams::with_content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub full_content: Option<String>,
+    /// The page's content is indexed, so `get_bookmark_content` returns it
+    #[serde(default)]
+    pub has_full_content: bool,
     pub score: f32,
     pub folder_path: String,
     pub last_indexed: Option<String>,
+    /// When the bookmark was added (RFC 3339), if its source records it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_added: Option<String>,
+    /// When the bookmark was last modified (RFC 3339), if its source records it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<String>,
     pub context_type: Option<String>,
     pub page_number: Option<usize>,
+    /// The text was recognized from scanned PDF pages and may contain OCR errors
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ocr: bool,
+    /// "archive" when the page was dead and an Internet Archive snapshot was indexed instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Capture time of that snapshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
+    /// Characters the page had before its content was cut to the `[content]` limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated_from: Option<usize>,
+    /// The hit is one part of a PDF indexed as several documents; read the rest by page range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<DocumentPart>,
+    /// Hits on each part of a split PDF, when sibling parts were merged into this result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub part_hits: Vec<PartHit>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::search::schema::BookmarkSchema;
-    use crate::search::tokenizer::register_lindera_tokenizer;
-    use tantivy::doc;
-    use tempfile::TempDir;
-
-    #[test]
-    fn test_unified_searcher_creation() {
-        let temp_dir = TempDir::new().unwrap();
-        let schema = BookmarkSchema::new();
-        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+/// Title completion returned by `typeahead`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub score: f32,
+}
 
-        let searcher = UnifiedSearcher::new(index, schema);
-        assert!(searcher.is_ok());
+/// Match in one part of a split PDF, kept when its siblings are merged into one result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartHit {
+    /// 0-indexed part the match is in
+    pub part_index: usize,
+    /// First and last page of that part
+    pub pages: Option<(usize, usize)>,
+    pub page_number: Option<usize>,
+    pub snippet: String,
+    /// Offsets relative to the part
+    pub snippet_range: Option<SnippetRange>,
+    pub score: f32,
+}
+
+/// Merge hits on parts of the same split PDF into the best-scoring one
+///
+/// The merged result takes the parent bookmark's id and untagged title and
+/// lists every part's snippet in `part_hits`, in page order. Results keep
+/// their ranking order; other hits pass through untouched.
+pub fn merge_part_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut merged: Vec<SearchResult> = Vec::new();
+    for result in results {
+        let Some(hit) = PartHit::of(&result) else {
+            merged.push(result);
+            continue;
+        };
+        let parent_id = result.part.as_ref().map(|part| part.parent_id.as_str());
+        let sibling = merged.iter_mut().find(|other| {
+            other.url == result.url
+                && other.part.as_ref().map(|part| part.parent_id.as_str()) == parent_id
+        });
+        let Some(best) = sibling else {
+            merged.push(result);
+            continue;
+        };
+        if best.part_hits.is_empty() {
+            let first = PartHit::of(best);
+            best.part_hits.extend(first);
+            if let Some(parent_id) = parent_id {
+                best.id = parent_id.to_string();
+            }
+            best.title = strip_page_suffix(&best.title).to_string();
+        }
+        best.part_hits.push(hit);
+        best.part_hits.sort_by_key(|hit| hit.part_index);
+    }
+    merged
+}
+
+impl PartHit {
+    /// The match of a result on one part of a split PDF
+    fn of(result: &SearchResult) -> Option<Self> {
+        let part = result.part.as_ref()?;
+        Some(Self {
+            part_index: part.index,
+            pages: part.pages.get(part.index).copied(),
+            page_number: result.page_number,
+            snippet: result.snippet.clone(),
+            snippet_range: result.snippet_range,
+            score: result.score,
+        })
+    }
+}
+
+/// Title without the ` [Page n]` / ` [Pages n-m]` suffix added to split parts
+fn strip_page_suffix(title: &str) -> &str {
+    match title.rfind(" [Page") {
+        Some(pos) if title.ends_with(']') => &title[..pos],
+        _ => title,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::schema::BookmarkSchema;
+    use crate::search::tokenizer::register_lindera_tokenizer;
+    use tantivy::doc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unified_searcher_creation() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema);
+        assert!(searcher.is_ok());
     }
 
     #[test]
@@ -587,6 +1461,610 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_profile_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        for (id, profile) in [("1", "Work"), ("2", "Personal")] {
+            index_writer
+                .add_document(doc!(
+                    schema.id => id,
+                    schema.title => "Rust documentation",
+                    schema.url => format!("https://example.com/{id}"),
+                    schema.profile => profile
+                ))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        let params = SearchParams::new("Rust").with_profile("Work".to_string());
+        let results = searcher.search_with_params(&params).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "1");
+    }
+
+    #[test]
+    fn test_hybrid_ranking() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        for (id, title, profile) in [
+            ("1", "Rust async runtime", "Work"),
+            ("2", "Concurrency without threads", "Work"),
+            ("3", "Green threads overview", "Personal"),
+        ] {
+            index_writer
+                .add_document(doc!(
+                    schema.id => id,
+                    schema.title => title,
+                    schema.url => format!("https://example.com/{id}"),
+                    schema.profile => profile
+                ))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+
+        let hit = |id: &str, score: f32| SemanticHit {
+            url: format!("https://example.com/{id}"),
+            title: String::new(),
+            score,
+            offset: 0,
+            page_number: None,
+            snippet: "a paraphrase".to_string(),
+        };
+        let hits = [hit("2", 0.9), hit("3", 0.8), hit("1", 0.2)];
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.id).collect()
+        };
+
+        let keyword_only = SearchParams::new("async").with_hybrid_weight(0.0);
+        assert_eq!(
+            ids(searcher.search_hybrid(&keyword_only, &hits).unwrap()),
+            vec!["1"]
+        );
+
+        let params = SearchParams::new("async").with_hybrid_weight(0.7);
+        let results = searcher.search_hybrid(&params, &hits).unwrap();
+        assert_eq!(ids(results.clone()), vec!["2", "3", "1"]);
+        assert_eq!(results[0].snippet, "a paraphrase");
+        assert!((results[2].score - (0.3 + 0.7 * 0.2)).abs() < 1e-4);
+
+        let filtered = params.with_profile("Work".to_string());
+        assert_eq!(
+            ids(searcher.search_hybrid(&filtered, &hits).unwrap()),
+            vec!["2", "1"]
+        );
+    }
+
+    #[test]
+    fn test_snippet_from_query_terms() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let filler = "関係のない文章が続きます。".repeat(200);
+        let content = format!(
+            "{filler}東京タワーは1958年に完成しました。{filler}東京タワーの高さは333メートルです。{filler}"
+        );
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        index_writer
+            .add_document(doc!(
+                schema.id => "1",
+                schema.title => "観光ガイド",
+                schema.url => "https://example.com/1",
+                schema.content => content.as_str()
+            ))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        let results = searcher.search("東京タワー", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].snippet.contains("東京タワー"));
+        assert!(results[0].snippet.len() < content.len() / 10);
+        assert!(results[0].more_snippets.is_empty());
+        let range = results[0].snippet_range.unwrap();
+        let shown: String = content
+            .chars()
+            .skip(range.start)
+            .take(range.end - range.start)
+            .collect();
+        assert!(results[0].snippet.contains(shown.trim()));
+
+        let params = SearchParams::new("東京タワー")
+            .with_snippet_count(2)
+            .with_snippet_length(120);
+        let results = searcher.search_with_params(&params).unwrap();
+        assert_eq!(results[0].more_snippets.len(), 1);
+        for snippet in std::iter::once(&results[0].snippet).chain(&results[0].more_snippets) {
+            assert!(snippet.contains("東京タワー"));
+            assert!(snippet.len() <= 123);
+        }
+    }
+
+    #[test]
+    fn test_full_content_is_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        index_writer
+            .add_document(doc!(
+                schema.id => "1",
+                schema.title => "Rust documentation",
+                schema.url => "https://example.com/1",
+                schema.content => "The Rust book covers ownership and borrowing.",
+                schema.date_added => 1_700_000_000_000i64
+            ))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        let results = searcher.search("ownership", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].full_content.is_none());
+        assert!(results[0].has_full_content);
+        assert!(results[0].snippet.contains("ownership"));
+        assert_eq!(results[0].domain.as_deref(), Some("example.com"));
+        assert_eq!(
+            results[0].date_added.as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+        assert!(results[0].date_modified.is_none());
+        let json = serde_json::to_value(&results[0]).unwrap();
+        assert!(json.get("full_content").is_none());
+        assert!(json.get("date_modified").is_none());
+
+        let params = SearchParams::new("ownership").with_content(true);
+        let results = searcher.search_with_params(&params).unwrap();
+        assert_eq!(
+            results[0].full_content.as_deref(),
+            Some("The Rust book covers ownership and borrowing.")
+        );
+    }
+
+    #[test]
+    fn test_reserved_characters_are_searched_as_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        index_writer
+            .add_document(doc!(
+                schema.id => "1",
+                schema.title => "Moving values out",
+                schema.url => "https://example.com/take",
+                schema.content => "Call std::mem::take (or mem::replace) to move out of a reference."
+            ))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        for query in [
+            "std::mem::take",
+            "https://example.com/take",
+            "(or",
+            "replace) ~",
+        ] {
+            assert_eq!(searcher.search(query, 10).unwrap().len(), 1, "{query}");
+        }
+    }
+
+    #[test]
+    fn test_url_words() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::BookmarkIndexer;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let bookmark = FlatBookmark {
+            id: "1".to_string(),
+            name: "Asynchronous runtime".to_string(),
+            url: "https://github.com/tokio-rs/tokio".to_string(),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+        };
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        index_writer
+            .add_document(
+                indexer
+                    .create_document(
+                        &bookmark,
+                        Some("Build reliable network applications."),
+                        None,
+                    )
+                    .unwrap(),
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        for query in ["github.com/tokio-rs", "tokio-rs", "\"rs tokio\"", "github"] {
+            assert_eq!(searcher.search(query, 10).unwrap().len(), 1, "{query}");
+        }
+        assert!(searcher.search("tokio-rs/mio", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hidden_domains() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::BookmarkIndexer;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let urls = [
+            "https://bank.example.com/statements",
+            "https://login.bank.example.com/statements",
+            "https://mybank.example.com/statements",
+        ];
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        for (i, url) in urls.iter().enumerate() {
+            let bookmark = FlatBookmark {
+                id: i.to_string(),
+                name: "Monthly statements".to_string(),
+                url: url.to_string(),
+                date_added: None,
+                date_modified: None,
+                folder_path: vec![],
+                tags: vec![],
+                profile: None,
+            };
+            index_writer
+                .add_document(indexer.create_document(&bookmark, None, None).unwrap())
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+
+        let mut searcher = UnifiedSearcher::new(index, schema).unwrap();
+        searcher.hidden_domains = vec!["bank.example.com".to_string()];
+        let found: Vec<String> = searcher
+            .search("statements", 10)
+            .unwrap()
+            .into_iter()
+            .map(|result| result.url)
+            .collect();
+        assert_eq!(found, [urls[2]]);
+        // Filtering on a hidden domain doesn't bring it back
+        let params = SearchParams::new("statements").with_domain("bank.example.com".to_string());
+        assert!(searcher.search_with_params(&params).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_typeahead() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::BookmarkIndexer;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        for (id, name) in [
+            ("1", "Tokio runtime internals"),
+            ("2", "Tokenizers in tantivy"),
+            ("3", "Rust async book"),
+        ] {
+            let bookmark = FlatBookmark {
+                id: id.to_string(),
+                name: name.to_string(),
+                url: format!("https://example.com/{id}"),
+                date_added: None,
+                date_modified: None,
+                folder_path: vec![],
+                tags: vec![],
+                profile: None,
+            };
+            index_writer
+                .add_document(indexer.create_document(&bookmark, None, None).unwrap())
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        let titles = |query: &str| -> Vec<String> {
+            let mut titles: Vec<String> = searcher
+                .typeahead(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.title)
+                .collect();
+            titles.sort();
+            titles
+        };
+        assert_eq!(
+            titles("tok"),
+            ["Tokenizers in tantivy", "Tokio runtime internals"]
+        );
+        assert_eq!(titles("TOK run"), ["Tokio runtime internals"]);
+        assert_eq!(titles("as"), ["Rust async book"]);
+        assert!(titles("untime").is_empty());
+        assert!(titles("  ").is_empty());
+        assert_eq!(searcher.typeahead("t", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_ngram_fallback() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::BookmarkIndexer;
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let bookmark = FlatBookmark {
+            id: "1".to_string(),
+            name: "Release notes".to_string(),
+            url: "https://example.com/release".to_string(),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+        };
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        index_writer
+            .add_document(
+                indexer
+                    .create_document(&bookmark, Some("Launch party 🎉🎊 ກະລຸນາ"), None)
+                    .unwrap(),
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        assert_eq!(searcher.search("ກະລຸ", 10).unwrap().len(), 1);
+        assert_eq!(searcher.search("🎊", 10).unwrap().len(), 1);
+        let params = SearchParams::new("\"ty 🎉\"");
+        assert_eq!(searcher.search_with_params(&params).unwrap().len(), 1);
+        assert!(searcher.search("🎈", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_section_from_stored_headings() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::{BookmarkIndexer, Heading, PageStructure};
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let install = format!("Install {}", "Run cargo install to set it up. ".repeat(40));
+        let content = format!(
+            "{install}Usage {}Results list matching bookmarks.",
+            "Pass a query to search. ".repeat(40)
+        );
+        let structure = PageStructure {
+            headings: vec![
+                Heading {
+                    level: 2,
+                    text: "Usage".to_string(),
+                    offset: install.chars().count(),
+                },
+                Heading {
+                    level: 2,
+                    text: "Install".to_string(),
+                    offset: 0,
+                },
+            ],
+            ..PageStructure::default()
+        };
+        let bookmark = FlatBookmark {
+            id: "1".to_string(),
+            name: "CLI guide".to_string(),
+            url: "https://example.com/guide".to_string(),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+        };
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        indexer
+            .index_bookmark_with_structure(&mut index_writer, &bookmark, Some(&content), &structure)
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        let results = searcher.search("matching", 10).unwrap();
+        assert_eq!(results[0].section.as_deref(), Some("Usage"));
+        assert_eq!(results[0].matched_heading, None);
+        let results = searcher.search("cargo", 10).unwrap();
+        assert_eq!(results[0].section.as_deref(), Some("Install"));
+        let results = searcher.search("usage", 10).unwrap();
+        assert_eq!(results[0].matched_heading.as_deref(), Some("Usage"));
+    }
+
+    #[test]
+    fn test_code_scope() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::{BookmarkIndexer, PageStructure};
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let bookmark = |id: &str| FlatBookmark {
+            id: id.to_string(),
+            name: format!("Notes {id}"),
+            url: format!("https://example.com/{id}"),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+        };
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        let fenced = "Commit often. ```rust let mut w = index.writer(heap)?; IndexWriter::commit(&mut w)?; ```";
+        indexer
+            .index_bookmark(&mut index_writer, &bookmark("1"), Some(fenced))
+            .unwrap();
+        let structure = PageStructure {
+            code_blocks: vec!["fn max_doc_count() -> u32".to_string()],
+            ..PageStructure::default()
+        };
+        indexer
+            .index_bookmark_with_structure(
+                &mut index_writer,
+                &bookmark("2"),
+                Some("Counting documents"),
+                &structure,
+            )
+            .unwrap();
+        indexer
+            .index_bookmark(
+                &mut index_writer,
+                &bookmark("3"),
+                Some("Call commit on the IndexWriter when done."),
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        let ids = |query: &str| -> Vec<String> {
+            let mut ids: Vec<String> = searcher
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("code:IndexWriter::commit"), vec!["1"]);
+        assert_eq!(ids("code:index.writer"), vec!["1"]);
+        assert_eq!(ids("code:max_doc_count"), vec!["2"]);
+        assert_eq!(ids(r#"code:"fn max_doc_count""#), vec!["2"]);
+        assert!(ids("code:commit").is_empty());
+        assert_eq!(ids("IndexWriter commit"), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn test_stored_renditions() {
+        use crate::bookmark::FlatBookmark;
+        use crate::search::indexer::{BookmarkIndexer, PageStructure, TranscriptCue};
+
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let bookmark = FlatBookmark {
+            id: "1".to_string(),
+            name: "Guide".to_string(),
+            url: "https://example.com/guide".to_string(),
+            date_added: None,
+            date_modified: None,
+            folder_path: vec![],
+            tags: vec![],
+            profile: None,
+        };
+        let structure = PageStructure {
+            html: Some("<h2>Setup</h2><p>Run <code>cargo build</code> first.</p>".to_string()),
+            raw_html: Some("<html><body><h2>Setup</h2></body></html>".to_string()),
+            ..PageStructure::default()
+        };
+        let video = FlatBookmark {
+            id: "2".to_string(),
+            name: "Talk".to_string(),
+            url: "https://www.youtube.com/watch?v=abc".to_string(),
+            ..bookmark.clone()
+        };
+        let cues = vec![
+            TranscriptCue {
+                start_ms: 0,
+                offset: 0,
+            },
+            TranscriptCue {
+                start_ms: 4_200,
+                offset: 15,
+            },
+        ];
+        let talk = PageStructure {
+            transcript: cues.clone(),
+            ..PageStructure::default()
+        };
+        let indexer = BookmarkIndexer::new(index.clone(), schema.clone());
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        indexer
+            .index_bookmark_with_structure(
+                &mut index_writer,
+                &bookmark,
+                Some("Setup Run cargo build first."),
+                &structure,
+            )
+            .unwrap();
+        indexer
+            .index_bookmark_with_structure(
+                &mut index_writer,
+                &video,
+                Some("Hello everyone\ntoday we talk about search"),
+                &talk,
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        assert_eq!(
+            searcher
+                .get_html_by_url("https://example.com/guide")
+                .unwrap(),
+            Some("<html><body><h2>Setup</h2></body></html>".to_string())
+        );
+        let markdown = searcher
+            .get_markdown_by_url("https://example.com/guide")
+            .unwrap()
+            .unwrap();
+        assert!(markdown.contains("Setup"));
+        assert!(markdown.contains("`cargo build`"));
+        assert!(!markdown.contains("<p>"));
+        assert_eq!(
+            searcher
+                .get_markdown_by_url("https://example.com/other")
+                .unwrap(),
+            None
+        );
+        assert_eq!(searcher.get_transcript_by_url(&video.url).unwrap(), cues);
+        assert!(
+            searcher
+                .get_transcript_by_url("https://example.com/guide")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_phrase_search() {
         let temp_dir = TempDir::new().unwrap();
@@ -815,4 +2293,91 @@ mod tests {
         assert_eq!(stats.total_documents, 4); // 1 + 3 parts
         assert_eq!(stats.bookmark_count, 2); // 2 unique bookmarks (100 and 200)
     }
+
+    #[test]
+    fn test_page_offsets_across_parts() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema = BookmarkSchema::new();
+        let index = Index::create_in_dir(temp_dir.path(), schema.schema.clone()).unwrap();
+        register_lindera_tokenizer(&index).unwrap();
+
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        // Pages 1-2 and 3-4 of a 40-char PDF, offsets relative to each part
+        for (id, first_page, content) in [
+            ("300", 1u64, "A".repeat(20)),
+            ("300_part_1", 3, "B".repeat(20)),
+        ] {
+            index_writer
+                .add_document(doc!(
+                    schema.id => id,
+                    schema.url => "https://example.com/scan.pdf",
+                    schema.content => content,
+                    schema.page_offsets => serde_json::to_vec(&[0usize, 10]).unwrap(),
+                    schema.first_page => first_page
+                ))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+
+        let searcher = UnifiedSearcher::new(index, schema).unwrap();
+        assert_eq!(
+            searcher
+                .get_page_offsets_by_url("https://example.com/scan.pdf")
+                .unwrap(),
+            Some(vec![0, 10, 20, 30])
+        );
+        assert_eq!(
+            searcher
+                .get_page_offsets_by_url("https://example.com/missing.pdf")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_merge_part_results() {
+        let hit = |id: &str, title: &str, score: f32, part: Option<usize>| SearchResult {
+            id: id.to_string(),
+            title: title.to_string(),
+            url: format!(
+                "https://example.com/{}",
+                if part.is_some() { "doc.pdf" } else { id }
+            ),
+            snippet: format!("snippet of {id}"),
+            score,
+            folder_path: "test".to_string(),
+            part: part.map(|index| DocumentPart {
+                parent_id: "200".to_string(),
+                index,
+                count: 3,
+                page_count: 300,
+                pages: vec![(1, 100), (101, 200), (201, 300)],
+            }),
+            ..Default::default()
+        };
+        let merged = merge_part_results(vec![
+            hit("200_part_2", "PDF Document [Pages 201-300]", 3.0, Some(2)),
+            hit("100", "Regular Bookmark", 2.0, None),
+            hit("200", "PDF Document [Pages 1-100]", 1.0, Some(0)),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].id, "200");
+        assert_eq!(merged[0].title, "PDF Document");
+        assert_eq!(merged[0].score, 3.0);
+        let parts: Vec<_> = merged[0]
+            .part_hits
+            .iter()
+            .map(|hit| (hit.part_index, hit.pages, hit.snippet.as_str()))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                (0, Some((1, 100)), "snippet of 200"),
+                (2, Some((201, 300)), "snippet of 200_part_2"),
+            ]
+        );
+        assert_eq!(merged[1].id, "100");
+        assert!(merged[1].part_hits.is_empty());
+    }
 }
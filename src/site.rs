@@ -0,0 +1,806 @@
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::archive;
+use crate::bookmark::FlatBookmark;
+use crate::browser::{chrome_timestamp_from_rfc3339, chrome_timestamp_from_unix_micros};
+use crate::content::{ContentFetcher, FetchedPage, MAX_FETCH_BYTES};
+use crate::search::common::url_bookmark_id;
+use crate::search::indexer::{Heading, PageStructure};
+
+/// Default number of levels below the root `index site` follows
+pub const DEFAULT_DEPTH: usize = 2;
+
+/// Default number of pages `index site` stops at
+pub const DEFAULT_MAX_PAGES: usize = 500;
+
+/// Pause between requests when robots.txt doesn't ask for one
+const DEFAULT_REQUEST_DELAY: Duration = Duration::from_millis(250);
+
+/// Characters of text kept per page unless `[content]` sets a limit for HTML
+const MAX_PAGE_CHARS: usize = 100_000;
+
+/// Sitemap files read per candidate, counting those listed by sitemap indexes
+const MAX_SITEMAP_FILES: usize = 20;
+
+/// Links to files like these are never pages worth fetching
+const ASSET_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "ico", "css", "js", "mjs", "json", "xml", "txt",
+    "zip", "gz", "tgz", "tar", "pdf", "mp3", "mp4", "webm", "woff", "woff2", "ttf", "eot",
+];
+
+static IGNORED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<!--.*?-->|<script\b.*?</script\s*>|<style\b.*?</style\s*>|<noscript\b.*?</noscript\s*>|<template\b.*?</template\s*>|<svg\b.*?</svg\s*>",
+    )
+    .expect("valid regex")
+});
+static CHROME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<head\b.*?</head\s*>|<nav\b.*?</nav\s*>|<header\b.*?</header\s*>|<footer\b.*?</footer\s*>|<aside\b.*?</aside\s*>",
+    )
+    .expect("valid regex")
+});
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").expect("valid regex"));
+static MAIN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<main\b[^>]*>(.*)</main\s*>").expect("valid regex"));
+static ARTICLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<article\b[^>]*>(.*)</article\s*>").expect("valid regex"));
+static BODY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").expect("valid regex"));
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<a\s[^>]*?\bhref\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+});
+static HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").expect("valid regex"));
+static PRE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<pre\b[^>]*>(.*?)</pre\s*>").expect("valid regex"));
+static BLOCK_BREAK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)<br\s*/?>|</?(?:p|div|li|ul|ol|dl|dt|dd|tr|table|section|article|blockquote|pre|h[1-6])\b[^>]*>",
+    )
+    .expect("valid regex")
+});
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").expect("valid regex"));
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);").expect("valid regex"));
+static SITEMAP_URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<url\b[^>]*>(.*?)</url\s*>").expect("valid regex"));
+static SITEMAP_CHILD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<sitemap\b[^>]*>(.*?)</sitemap\s*>").expect("valid regex"));
+static LOC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<loc\b[^>]*>\s*(?:<!\[CDATA\[)?(.*?)(?:\]\]>)?\s*</loc\s*>")
+        .expect("valid regex")
+});
+static LASTMOD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<lastmod\b[^>]*>\s*(.*?)\s*</lastmod\s*>").expect("valid regex")
+});
+
+/// Text and structure extracted from an HTML page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractedPage {
+    pub title: Option<String>,
+    /// Text of the main content, one block per line
+    pub text: String,
+    pub structure: PageStructure,
+    /// `href` of every link on the page, navigation included, as written
+    pub links: Vec<String>,
+}
+
+/// Extract the title, main text, headings, code blocks and links of a page
+///
+/// Uses the page's `<main>` (or `<article>`, or `<body>`) without scripts,
+/// navigation, headers, footers and sidebars, like the extension does.
+pub fn extract_html(html: &str) -> ExtractedPage {
+    let html = IGNORED.replace_all(html, "");
+    let title = TITLE
+        .captures(&html)
+        .map(|caps| single_line(&fragment_text(&caps[1])))
+        .filter(|title| !title.is_empty());
+    let links = LINK
+        .captures_iter(&html)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|href| unescape_html(href.as_str().trim()))
+        .filter(|href| !href.is_empty())
+        .collect();
+
+    let main = [&*MAIN, &*ARTICLE, &*BODY]
+        .iter()
+        .find_map(|regex| regex.captures(&html))
+        .map_or(&*html, |caps| caps.get(1).map_or("", |m| m.as_str()));
+    let main = CHROME.replace_all(main, "").into_owned();
+
+    // Headings are placed at the character offset where their text starts
+    let mut text = String::new();
+    let mut chars = 0;
+    let mut headings = Vec::new();
+    let mut last = 0;
+    for caps in HEADING.captures_iter(&main) {
+        let whole = caps.get(0).expect("match");
+        push_block(
+            &mut text,
+            &mut chars,
+            &fragment_text(&main[last..whole.start()]),
+        );
+        let heading = single_line(&fragment_text(&caps[2]));
+        if let Some(offset) = push_block(&mut text, &mut chars, &heading) {
+            headings.push(Heading {
+                level: caps[1].parse().unwrap_or(1),
+                text: heading,
+                offset,
+            });
+        }
+        last = whole.end();
+    }
+    push_block(&mut text, &mut chars, &fragment_text(&main[last..]));
+
+    let max_chars = crate::config::settings()
+        .content
+        .max_chars_for("html")
+        .unwrap_or(MAX_PAGE_CHARS);
+    let truncated_from = (chars > max_chars).then_some(chars);
+    if truncated_from.is_some() {
+        text = text.chars().take(max_chars).collect();
+        headings.retain(|heading| heading.offset < max_chars);
+    }
+    let code_blocks = PRE
+        .captures_iter(&main)
+        .map(|caps| {
+            let code = BLOCK_BREAK.replace_all(&caps[1], "\n");
+            unescape_html(&TAG.replace_all(&code, ""))
+                .trim()
+                .to_string()
+        })
+        .filter(|code| !code.is_empty())
+        .collect();
+
+    ExtractedPage {
+        title: title.or_else(|| {
+            headings
+                .iter()
+                .find(|heading| heading.level == 1)
+                .map(|heading| heading.text.clone())
+        }),
+        text,
+        structure: PageStructure {
+            headings,
+            code_blocks,
+            html: Some(main),
+            truncated_from,
+            ..PageStructure::default()
+        },
+        links,
+    }
+}
+
+/// Append a block of text on its own line, returning the character offset it starts at
+fn push_block(text: &mut String, chars: &mut usize, block: &str) -> Option<usize> {
+    if block.is_empty() {
+        return None;
+    }
+    if !text.is_empty() {
+        text.push('\n');
+        *chars += 1;
+    }
+    let offset = *chars;
+    text.push_str(block);
+    *chars += block.chars().count();
+    Some(offset)
+}
+
+/// Visible text of an HTML fragment, one non-empty line per block
+pub(crate) fn fragment_text(fragment: &str) -> String {
+    let text = BLOCK_BREAK.replace_all(fragment, "\n");
+    let text = unescape_html(&TAG.replace_all(&text, ""));
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub(crate) fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub(crate) fn unescape_html(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |caps: &Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "hellip" => Some('…'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                "laquo" => Some('«'),
+                "raquo" => Some('»'),
+                "copy" => Some('©'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+/// Page URLs and nested sitemaps listed by one sitemap file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sitemap {
+    /// `<loc>` and `<lastmod>` of each `<url>`
+    pub urls: Vec<(String, Option<String>)>,
+    /// `<loc>` of each `<sitemap>` in a sitemap index
+    pub sitemaps: Vec<String>,
+}
+
+impl Sitemap {
+    pub fn parse(xml: &str) -> Self {
+        let loc = |entry: &str| {
+            LOC.captures(entry)
+                .map(|caps| unescape_html(caps[1].trim()))
+                .filter(|loc| !loc.is_empty())
+        };
+        Self {
+            urls: SITEMAP_URL
+                .captures_iter(xml)
+                .filter_map(|caps| {
+                    let lastmod = LASTMOD.captures(&caps[1]).map(|m| m[1].to_string());
+                    Some((loc(&caps[1])?, lastmod))
+                })
+                .collect(),
+            sitemaps: SITEMAP_CHILD
+                .captures_iter(xml)
+                .filter_map(|caps| loc(&caps[1]))
+                .collect(),
+        }
+    }
+}
+
+/// How the pages of a site were found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Discovery {
+    /// Listed in the site's sitemap.xml
+    Sitemap,
+    /// Followed from link to link, starting at the root
+    #[default]
+    Links,
+}
+
+/// A crawled page, ready to index
+#[derive(Debug, Clone)]
+pub struct SitePage {
+    pub bookmark: FlatBookmark,
+    pub content: String,
+    pub structure: PageStructure,
+}
+
+/// A page that was found but not indexed
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlFailure {
+    pub url: String,
+    pub error: String,
+}
+
+/// Outcome of a crawl
+#[derive(Debug, Default, Serialize)]
+pub struct CrawlReport {
+    pub discovery: Discovery,
+    #[serde(skip)]
+    pub pages: Vec<SitePage>,
+    /// Pages robots.txt asked crawlers to leave alone
+    pub skipped_robots: Vec<String>,
+    /// Dead pages indexed from an Internet Archive snapshot instead
+    pub archived: Vec<String>,
+    pub failed: Vec<CrawlFailure>,
+}
+
+/// Crawl of the documentation site below a bookmarked root URL
+///
+/// Pages come from the site's sitemap.xml when it lists any under the root,
+/// otherwise from following links from the root. Only pages on the root's
+/// origin whose path starts with the root's directory are fetched, each at
+/// most once, honouring robots.txt and its `Crawl-delay`.
+#[derive(Debug, Clone)]
+pub struct SiteCrawl {
+    root: Url,
+    /// Path every page shares with the root, ending in `/`
+    prefix: String,
+    depth: usize,
+    max_pages: usize,
+    use_sitemap: bool,
+    archive_fallback: bool,
+}
+
+impl SiteCrawl {
+    /// Crawl `depth` levels below `root`, stopping after `max_pages` pages
+    ///
+    /// Levels are link hops when following links and path segments below the
+    /// root for sitemap entries.
+    pub fn new(root: &str, depth: usize, max_pages: usize) -> Result<Self> {
+        let mut root = Url::parse(root).with_context(|| format!("Invalid URL {root:?}"))?;
+        anyhow::ensure!(
+            matches!(root.scheme(), "http" | "https"),
+            "Only http and https sites can be crawled: {root}"
+        );
+        root.set_fragment(None);
+        let path = root.path();
+        let prefix = match path.rfind('/') {
+            _ if path.ends_with('/') => path.to_string(),
+            // A page like /guide/index.html stands for its directory
+            Some(slash) if path[slash..].contains('.') => path[..=slash].to_string(),
+            _ => format!("{path}/"),
+        };
+        Ok(Self {
+            root,
+            prefix,
+            depth,
+            max_pages,
+            use_sitemap: true,
+            archive_fallback: false,
+        })
+    }
+
+    /// Follow links even if the site has a sitemap
+    pub fn without_sitemap(mut self) -> Self {
+        self.use_sitemap = false;
+        self
+    }
+
+    /// Index the latest Internet Archive snapshot of pages that return 403, 404 or 410
+    pub fn with_archive_fallback(mut self, enabled: bool) -> Self {
+        self.archive_fallback = enabled;
+        self
+    }
+
+    pub fn root(&self) -> &Url {
+        &self.root
+    }
+
+    /// Folder the pages are filed under: the root's host and directory
+    pub fn folder(&self) -> String {
+        format!(
+            "{}{}",
+            self.root.host_str().unwrap_or_default(),
+            self.prefix.trim_end_matches('/')
+        )
+    }
+
+    /// Whether a URL is the root or below it on the same origin
+    pub fn contains(&self, url: &Url) -> bool {
+        url.origin() == self.root.origin()
+            && (url.path() == self.root.path() || url.path().starts_with(&self.prefix))
+    }
+
+    /// Path segments of a URL below the root's directory
+    fn depth_of(&self, url: &Url) -> usize {
+        url.path()
+            .strip_prefix(&self.prefix)
+            .map_or(0, |rest| rest.split('/').filter(|s| !s.is_empty()).count())
+    }
+
+    /// Fetch the site's pages
+    pub async fn run(&self, fetcher: &ContentFetcher) -> CrawlReport {
+        let mut crawler = Crawler {
+            site: self,
+            fetcher,
+            report: CrawlReport::default(),
+            fetched_any: false,
+        };
+        // All pages share the root's origin
+        if !crate::config::settings()
+            .domains
+            .allows_content(self.root.as_str())
+        {
+            crawler.fail::<()>(&self.root, "Domain excluded by [domains]".to_string());
+            return crawler.report;
+        }
+
+        let entries = if self.use_sitemap {
+            crawler.sitemap_entries().await
+        } else {
+            Vec::new()
+        };
+        if entries.is_empty() {
+            crawler.follow_links().await;
+        } else {
+            info!(
+                "Found {} pages in the sitemap of {}",
+                entries.len(),
+                self.root
+            );
+            crawler.report.discovery = Discovery::Sitemap;
+            for (url, lastmod) in entries {
+                if crawler.report.pages.len() >= self.max_pages {
+                    break;
+                }
+                if let Some((page, _)) = crawler.visit(&url, lastmod.as_deref()).await {
+                    crawler.report.pages.push(page);
+                }
+            }
+        }
+        crawler.report
+    }
+}
+
+struct Crawler<'a> {
+    site: &'a SiteCrawl,
+    fetcher: &'a ContentFetcher,
+    report: CrawlReport,
+    fetched_any: bool,
+}
+
+impl Crawler<'_> {
+    /// Pages below the root listed by the first sitemap that has any, root first
+    async fn sitemap_entries(&mut self) -> Vec<(Url, Option<String>)> {
+        let origin = self.site.root.origin().ascii_serialization();
+        let mut candidates = vec![format!("{origin}{}sitemap.xml", self.site.prefix)];
+        let top = format!("{origin}/sitemap.xml");
+        if !candidates.contains(&top) {
+            candidates.push(top);
+        }
+
+        for candidate in candidates {
+            let mut entries = vec![(self.site.root.clone(), None)];
+            let mut seen = HashSet::from([self.site.root.to_string()]);
+            let mut pending = vec![candidate];
+            let mut files = 0;
+            while let Some(sitemap_url) = pending.pop() {
+                if files >= MAX_SITEMAP_FILES {
+                    break;
+                }
+                files += 1;
+                let Some(xml) = self.fetch_sitemap(&sitemap_url).await else {
+                    continue;
+                };
+                let sitemap = Sitemap::parse(&xml);
+                // An index may list sitemaps anywhere; only the site's own are read
+                pending.extend(
+                    sitemap
+                        .sitemaps
+                        .into_iter()
+                        .filter(|loc| same_origin(loc, &self.site.root)),
+                );
+                for (loc, lastmod) in sitemap.urls {
+                    let Some(url) = page_url(&loc) else {
+                        continue;
+                    };
+                    if self.site.contains(&url)
+                        && self.site.depth_of(&url) <= self.site.depth
+                        && seen.insert(url.to_string())
+                    {
+                        entries.push((url, lastmod));
+                    }
+                }
+            }
+            if entries.len() > 1 {
+                return entries;
+            }
+        }
+        Vec::new()
+    }
+
+    /// A sitemap's XML, gunzipped if needed; None if it can't be fetched
+    async fn fetch_sitemap(&self, url: &str) -> Option<String> {
+        let page = match self.fetcher.fetch(url).await {
+            Ok(page) if page.is_success() => page,
+            Ok(page) => {
+                debug!("No sitemap at {} (HTTP {})", url, page.status);
+                return None;
+            }
+            Err(e) => {
+                debug!("No sitemap at {}: {:#}", url, e);
+                return None;
+            }
+        };
+        if !page.body.starts_with(&[0x1f, 0x8b]) {
+            return Some(page.text());
+        }
+        let mut xml = String::new();
+        flate2::read::GzDecoder::new(page.body.as_slice())
+            .take(MAX_FETCH_BYTES as u64)
+            .read_to_string(&mut xml)
+            .ok()?;
+        Some(xml)
+    }
+
+    /// Breadth-first crawl from the root, up to the depth in link hops
+    async fn follow_links(&mut self) {
+        let mut queue = VecDeque::from([(self.site.root.clone(), 0)]);
+        let mut seen = HashSet::from([self.site.root.to_string()]);
+        while let Some((url, hops)) = queue.pop_front() {
+            if self.report.pages.len() >= self.site.max_pages {
+                break;
+            }
+            let Some((page, links)) = self.visit(&url, None).await else {
+                continue;
+            };
+            if hops < self.site.depth {
+                for link in links {
+                    if seen.insert(link.to_string()) {
+                        queue.push_back((link, hops + 1));
+                    }
+                }
+            }
+            self.report.pages.push(page);
+        }
+    }
+
+    /// Fetch and extract one page, returning it with the site links it contains
+    async fn visit(&mut self, url: &Url, lastmod: Option<&str>) -> Option<(SitePage, Vec<Url>)> {
+        if !self.fetcher.robots_allows(url.as_str()).await {
+            debug!("robots.txt disallows {}", url);
+            self.report.skipped_robots.push(url.to_string());
+            return None;
+        }
+        if self.fetched_any {
+            let delay = self.fetcher.crawl_delay(url.as_str()).await;
+            tokio::time::sleep(delay.unwrap_or(DEFAULT_REQUEST_DELAY)).await;
+        }
+        self.fetched_any = true;
+
+        let mut fetched = match self.fetcher.fetch(url.as_str()).await {
+            Ok(fetched) => fetched,
+            Err(e) => return self.fail(url, format!("{e:#}")),
+        };
+        let mut archived_at = None;
+        if self.site.archive_fallback && archive::is_fallback_status(fetched.status) {
+            match archive::fetch_latest(self.fetcher, url.as_str()).await {
+                Ok(Some((snapshot, page))) => {
+                    info!(
+                        "{} returned HTTP {}, using the archived copy",
+                        url, fetched.status
+                    );
+                    // The snapshot stands in for the page at its own URL
+                    fetched = FetchedPage {
+                        url: url.to_string(),
+                        ..page
+                    };
+                    archived_at = snapshot.captured_at();
+                    self.report.archived.push(url.to_string());
+                }
+                Ok(None) => {
+                    let error = format!("HTTP {} (no archived snapshot)", fetched.status);
+                    return self.fail(url, error);
+                }
+                Err(e) => {
+                    let error = format!("HTTP {} (archive: {e:#})", fetched.status);
+                    return self.fail(url, error);
+                }
+            }
+        }
+        if !fetched.is_success() {
+            return self.fail(url, format!("HTTP {}", fetched.status));
+        }
+        if !fetched.is_html() {
+            let kind = fetched.content_type.unwrap_or_default();
+            return self.fail(url, format!("Not an HTML page ({kind})"));
+        }
+        let final_url = Url::parse(&fetched.url).unwrap_or_else(|_| url.clone());
+        if !self.site.contains(&final_url) {
+            return self.fail(url, format!("Redirected outside the site to {final_url}"));
+        }
+
+        let mut html = fetched.text();
+        let mut extracted = extract_html(&html);
+        let text_chars = extracted.text.chars().count();
+        if let Some(rendered) =
+            crate::render::render_if_wanted(final_url.as_str(), text_chars).await
+        {
+            extracted = extract_html(&rendered);
+            html = rendered;
+        }
+        let links = extracted
+            .links
+            .iter()
+            .filter_map(|href| final_url.join(href).ok())
+            .filter_map(|link| page_url(link.as_str()))
+            .filter(|link| self.site.contains(link))
+            .collect();
+        info!("Fetched {}", final_url);
+
+        let now = chrono::Utc::now().timestamp_micros();
+        let bookmark = FlatBookmark {
+            // Crawled pages have no bookmark ids; the URL is unique within a site
+            id: url_bookmark_id(final_url.as_str()),
+            name: extracted.title.unwrap_or_else(|| final_url.to_string()),
+            url: final_url.to_string(),
+            date_added: Some(chrome_timestamp_from_unix_micros(now)),
+            date_modified: lastmod.and_then(lastmod_timestamp),
+            folder_path: vec![self.site.folder()],
+            tags: Vec::new(),
+            profile: None,
+        };
+        let mut structure = extracted.structure;
+        // Only kept when `[index] store_html` is enabled
+        structure.raw_html = crate::config::settings().index.store_html.then_some(html);
+        structure.archived_at = archived_at;
+        let page = SitePage {
+            bookmark,
+            content: extracted.text,
+            structure,
+        };
+        Some((page, links))
+    }
+
+    fn fail<T>(&mut self, url: &Url, error: String) -> Option<T> {
+        debug!("Skipping {}: {}", url, error);
+        self.report.failed.push(CrawlFailure {
+            url: url.to_string(),
+            error,
+        });
+        None
+    }
+}
+
+/// An http(s) URL without its fragment, unless it points at an asset rather than a page
+fn page_url(url: &str) -> Option<Url> {
+    let mut url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    let extension = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|last| last.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_lowercase());
+    if extension.is_some_and(|extension| ASSET_EXTENSIONS.contains(&extension.as_str())) {
+        return None;
+    }
+    Some(url)
+}
+
+/// Whether `url` parses and has the same scheme, host and port as `root`
+fn same_origin(url: &str, root: &Url) -> bool {
+    Url::parse(url).is_ok_and(|url| url.origin() == root.origin())
+}
+
+/// Sitemap `<lastmod>` (a W3C datetime or just a date) in Chrome's bookmark format
+fn lastmod_timestamp(lastmod: &str) -> Option<String> {
+    chrome_timestamp_from_rfc3339(lastmod)
+        .or_else(|| chrome_timestamp_from_rfc3339(&format!("{lastmod}T00:00:00Z")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_html() {
+        let page = extract_html(
+            r#"<!DOCTYPE html>
+<html><head><title>Getting started &mdash; Docs</title><script>var x = "<h1>no</h1>";</script></head>
+<body>
+<nav><a href="/docs/">Docs</a> <a href='install.html#linux'>Install</a></nav>
+<main>
+<h1>Getting started</h1>
+<p>Install the <b>CLI</b> &amp; run it.</p>
+<h2 id="config">Configuration</h2>
+<pre><code>mcp-bookmark --index work
+&lt;done&gt;</code></pre>
+<footer>Copyright</footer>
+</main>
+</body></html>"#,
+        );
+        assert_eq!(page.title.as_deref(), Some("Getting started — Docs"));
+        assert_eq!(
+            page.text,
+            "Getting started\nInstall the CLI & run it.\nConfiguration\nmcp-bookmark --index work\n<done>"
+        );
+        assert_eq!(page.links, vec!["/docs/", "install.html#linux"]);
+
+        let headings = &page.structure.headings;
+        assert_eq!(headings.len(), 2);
+        assert_eq!((headings[0].level, headings[0].offset), (1, 0));
+        assert_eq!(headings[1].text, "Configuration");
+        assert_eq!(
+            page.text
+                .chars()
+                .skip(headings[1].offset)
+                .take(13)
+                .collect::<String>(),
+            "Configuration"
+        );
+        assert_eq!(
+            page.structure.code_blocks,
+            vec!["mcp-bookmark --index work\n<done>"]
+        );
+        assert!(!page.text.contains("Copyright"));
+
+        // Without a <title> the first <h1> names the page
+        let page = extract_html("<body><h1>Only &#x48;eading</h1></body>");
+        assert_eq!(page.title.as_deref(), Some("Only Heading"));
+    }
+
+    #[test]
+    fn test_parse_sitemap() {
+        let sitemap = Sitemap::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://docs.example.com/guide/</loc><lastmod>2024-05-01</lastmod></url>
+  <url><loc><![CDATA[https://docs.example.com/guide/a?x=1&y=2]]></loc></url>
+  <url><loc>https://docs.example.com/guide/b?x=1&amp;y=2</loc></url>
+</urlset>"#,
+        );
+        assert_eq!(sitemap.urls.len(), 3);
+        assert_eq!(sitemap.urls[0].1.as_deref(), Some("2024-05-01"));
+        assert_eq!(
+            sitemap.urls[1].0,
+            "https://docs.example.com/guide/a?x=1&y=2"
+        );
+        assert_eq!(
+            sitemap.urls[2].0,
+            "https://docs.example.com/guide/b?x=1&y=2"
+        );
+        assert!(sitemap.sitemaps.is_empty());
+
+        let index = Sitemap::parse(
+            "<sitemapindex><sitemap><loc>https://docs.example.com/sitemap-1.xml.gz</loc></sitemap></sitemapindex>",
+        );
+        assert_eq!(
+            index.sitemaps,
+            vec!["https://docs.example.com/sitemap-1.xml.gz"]
+        );
+        assert!(index.urls.is_empty());
+        assert!(lastmod_timestamp("2024-05-01").is_some());
+        assert!(lastmod_timestamp("2024-05-01T10:00:00+09:00").is_some());
+    }
+
+    #[test]
+    fn test_site_scope() {
+        let site = SiteCrawl::new("https://docs.example.com/guide/index.html#top", 1, 10).unwrap();
+        assert_eq!(site.folder(), "docs.example.com/guide");
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert!(site.contains(&url("https://docs.example.com/guide/install")));
+        assert!(site.contains(&url("https://docs.example.com/guide/index.html")));
+        assert!(!site.contains(&url("https://docs.example.com/blog/")));
+        assert!(!site.contains(&url("http://docs.example.com/guide/install")));
+        assert!(!site.contains(&url("https://docs.example.com/guidebook")));
+        assert!(same_origin(
+            "https://docs.example.com/sitemap-1.xml.gz",
+            &site.root
+        ));
+        assert!(!same_origin("https://evil.example/sitemap.xml", &site.root));
+        assert!(!same_origin(
+            "http://docs.example.com/sitemap.xml",
+            &site.root
+        ));
+        assert!(!same_origin("not a url", &site.root));
+        assert_eq!(site.depth_of(&url("https://docs.example.com/guide/a/b")), 2);
+
+        let site = SiteCrawl::new("https://docs.example.com/guide", 1, 10).unwrap();
+        assert!(site.contains(&url("https://docs.example.com/guide")));
+        assert!(site.contains(&url("https://docs.example.com/guide/install")));
+
+        assert!(SiteCrawl::new("file:///tmp/docs", 1, 10).is_err());
+        assert!(page_url("https://docs.example.com/logo.PNG").is_none());
+        assert_eq!(
+            page_url("https://docs.example.com/a#part")
+                .unwrap()
+                .as_str(),
+            "https://docs.example.com/a"
+        );
+        assert!(page_url("mailto:docs@example.com").is_none());
+    }
+}
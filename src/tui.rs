@@ -0,0 +1,362 @@
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::search::search_manager_trait::SearchManagerTrait;
+use crate::search::{BookmarkEntry, SearchResult};
+
+/// Results shown for a query
+const RESULT_LIMIT: usize = 100;
+
+/// Bookmarks listed while the query is empty
+const BROWSE_LIMIT: usize = 500;
+
+/// Keystrokes within this interval are batched into one search
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Lines scrolled by PageUp/PageDown in the preview
+const PREVIEW_SCROLL_STEP: u16 = 10;
+
+/// One row of the result list
+#[derive(Debug, Clone)]
+struct Item {
+    title: String,
+    url: String,
+    folder_path: String,
+}
+
+impl From<SearchResult> for Item {
+    fn from(result: SearchResult) -> Self {
+        Self {
+            title: result.title,
+            url: result.url,
+            folder_path: result.folder_path,
+        }
+    }
+}
+
+impl From<BookmarkEntry> for Item {
+    fn from(entry: BookmarkEntry) -> Self {
+        Self {
+            title: entry.title,
+            url: entry.url,
+            folder_path: entry.folder_path,
+        }
+    }
+}
+
+/// What the event loop should do after a key press
+#[derive(Debug, PartialEq)]
+enum Action {
+    None,
+    Search,
+    Open(String),
+    Quit,
+}
+
+#[derive(Debug, Default)]
+struct App {
+    index_name: String,
+    query: String,
+    items: Vec<Item>,
+    list_state: ListState,
+    /// URL and stored content of the previewed bookmark
+    preview: Option<(String, Option<String>)>,
+    preview_scroll: u16,
+    status: String,
+}
+
+impl App {
+    fn new(index_name: &str) -> Self {
+        Self {
+            index_name: index_name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn selected(&self) -> Option<&Item> {
+        self.list_state.selected().and_then(|i| self.items.get(i))
+    }
+
+    fn set_items(&mut self, items: Vec<Item>) {
+        self.items = items;
+        self.list_state
+            .select((!self.items.is_empty()).then_some(0));
+        self.preview_scroll = 0;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let last = self.items.len() as isize - 1;
+        self.list_state
+            .select(Some((current + delta).clamp(0, last) as usize));
+        self.preview_scroll = 0;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => Action::Quit,
+            KeyCode::Char('c') if ctrl => Action::Quit,
+            KeyCode::Enter => self
+                .selected()
+                .map_or(Action::None, |item| Action::Open(item.url.clone())),
+            KeyCode::Up => self.moved(-1),
+            KeyCode::Char('p') if ctrl => self.moved(-1),
+            KeyCode::Down => self.moved(1),
+            KeyCode::Char('n') if ctrl => self.moved(1),
+            KeyCode::PageUp => {
+                self.preview_scroll = self.preview_scroll.saturating_sub(PREVIEW_SCROLL_STEP);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.preview_scroll = self.preview_scroll.saturating_add(PREVIEW_SCROLL_STEP);
+                Action::None
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                Action::Search
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                Action::Search
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                Action::Search
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn moved(&mut self, delta: isize) -> Action {
+        self.move_selection(delta);
+        Action::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [input_area, main_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main_area);
+
+        let input = Paragraph::new(self.query.as_str())
+            .block(Block::bordered().title(format!(" Search {} ", self.index_name)));
+        frame.render_widget(input, input_area);
+        frame.set_cursor_position(Position::new(
+            input_area.x + 1 + self.query.chars().count() as u16,
+            input_area.y + 1,
+        ));
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| {
+                let title = if item.title.is_empty() {
+                    &item.url
+                } else {
+                    &item.title
+                };
+                ListItem::new(vec![
+                    Line::from(title.as_str()),
+                    Line::from(Span::from(item.folder_path.as_str()).dim()),
+                ])
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" {} results ", self.items.len())))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let mut lines = Vec::new();
+        if let Some(item) = self.selected() {
+            lines.push(Line::from(item.title.as_str().bold()));
+            lines.push(Line::from(item.url.as_str().cyan()));
+            lines.push(Line::from(""));
+            match &self.preview {
+                Some((url, Some(content))) if *url == item.url => {
+                    lines.extend(content.lines().map(Line::from));
+                }
+                Some((url, None)) if *url == item.url => {
+                    lines.push(Line::from("No stored content".dim()));
+                }
+                _ => {}
+            }
+        }
+        let preview = Paragraph::new(lines)
+            .block(Block::bordered().title(" Preview "))
+            .wrap(Wrap { trim: false })
+            .scroll((self.preview_scroll, 0));
+        frame.render_widget(preview, preview_area);
+
+        let help = if self.status.is_empty() {
+            "↑/↓ select  Enter open  PgUp/PgDn scroll preview  Ctrl-U clear  Esc quit"
+        } else {
+            self.status.as_str()
+        };
+        frame.render_widget(Paragraph::new(help).dim(), help_area);
+    }
+}
+
+/// Run the interactive browser until the user quits
+pub async fn run(manager: Arc<dyn SearchManagerTrait>, index_name: &str) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, manager, index_name).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    manager: Arc<dyn SearchManagerTrait>,
+    index_name: &str,
+) -> Result<()> {
+    let mut app = App::new(index_name);
+    let mut pending_search = true;
+
+    loop {
+        if app.selected().map(|item| &item.url) != app.preview.as_ref().map(|(url, _)| url) {
+            app.preview = match app.selected() {
+                Some(item) => {
+                    // A failed lookup just shows as missing content
+                    let content = manager.get_content_by_url(&item.url).await.ok().flatten();
+                    Some((item.url.clone(), content))
+                }
+                None => None,
+            };
+        }
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if !event::poll(SEARCH_DEBOUNCE)? {
+            if pending_search {
+                pending_search = false;
+                let items = search(manager.as_ref(), &app.query).await;
+                match items {
+                    Ok(items) => {
+                        app.status.clear();
+                        app.set_items(items);
+                    }
+                    Err(e) => app.status = format!("Search failed: {e}"),
+                }
+            }
+            continue;
+        }
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key) {
+            Action::None => {}
+            Action::Search => pending_search = true,
+            Action::Open(url) => {
+                app.status = match open_url(&url) {
+                    Ok(()) => format!("Opened {url}"),
+                    Err(e) => format!("Failed to open {url}: {e}"),
+                };
+            }
+            Action::Quit => return Ok(()),
+        }
+    }
+}
+
+/// Search results for a query, or the first bookmarks when it is empty
+async fn search(manager: &dyn SearchManagerTrait, query: &str) -> Result<Vec<Item>> {
+    if query.trim().is_empty() {
+        let mut entries = manager.list_bookmarks().await?;
+        entries.truncate(BROWSE_LIMIT);
+        return Ok(entries.into_iter().map(Item::from).collect());
+    }
+    let results = manager.search(query, RESULT_LIMIT).await?;
+    Ok(results.into_iter().map(Item::from).collect())
+}
+
+/// Open a URL in the default browser
+fn open_url(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .context("Failed to launch the browser")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(n: usize) -> Item {
+        Item {
+            title: format!("Bookmark {n}"),
+            url: format!("https://example.com/{n}"),
+            folder_path: "Bookmarks Bar".to_string(),
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_selection_is_clamped() {
+        let mut app = App::new("work");
+        app.move_selection(1);
+        assert_eq!(app.selected(), None);
+
+        app.set_items((0..3).map(item).collect());
+        assert_eq!(app.list_state.selected(), Some(0));
+        app.move_selection(-1);
+        assert_eq!(app.list_state.selected(), Some(0));
+        app.move_selection(5);
+        assert_eq!(app.list_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut app = App::new("work");
+        assert_eq!(app.handle_key(key(KeyCode::Char('r'))), Action::Search);
+        assert_eq!(app.handle_key(key(KeyCode::Char('s'))), Action::Search);
+        assert_eq!(app.query, "rs");
+        assert_eq!(app.handle_key(key(KeyCode::Backspace)), Action::Search);
+        assert_eq!(app.query, "r");
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::None);
+
+        app.set_items((0..2).map(item).collect());
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            Action::Open("https://example.com/1".to_string())
+        );
+
+        let ctrl_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(app.handle_key(ctrl_u), Action::Search);
+        assert!(app.query.is_empty());
+        assert_eq!(app.handle_key(key(KeyCode::Esc)), Action::Quit);
+    }
+}
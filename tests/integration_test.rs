@@ -19,7 +19,7 @@ mod tests {
         println!("Found {} bookmarks", bookmarks.len());
 
         // Create search manager and index bookmarks
-        let mut search_manager = SearchManager::new(None).unwrap();
+        let search_manager = SearchManager::new(None).unwrap();
         search_manager.build_index(&bookmarks).unwrap();
 
         // Test search
@@ -12,6 +12,8 @@ mod japanese_search_tests {
             name: title.to_string(),
             url: format!("https://example.com/{id}"),
             folder_path: vec!["test".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: Some("2024-01-01".to_string()),
             date_modified: Some("2024-01-01".to_string()),
         }
@@ -30,7 +32,7 @@ mod japanese_search_tests {
         };
 
         // Create search manager
-        let mut manager = SearchManager::new(Some(index_path)).unwrap();
+        let manager = SearchManager::new(Some(index_path)).unwrap();
 
         // Create test bookmarks with Japanese content
         let bookmarks = vec![
@@ -110,7 +112,7 @@ mod japanese_search_tests {
         let temp_dir = TempDir::new().unwrap();
         let index_path = temp_dir.path().to_path_buf();
 
-        let mut manager = SearchManager::new(Some(index_path)).unwrap();
+        let manager = SearchManager::new(Some(index_path)).unwrap();
 
         // Create bookmarks with mixed Japanese/English content
         let bookmarks = vec![
@@ -171,7 +173,7 @@ mod japanese_search_tests {
         let temp_dir = TempDir::new().unwrap();
         let index_path = temp_dir.path().to_path_buf();
 
-        let mut manager = SearchManager::new(Some(index_path)).unwrap();
+        let manager = SearchManager::new(Some(index_path)).unwrap();
 
         let bookmarks = vec![
             create_japanese_bookmark("1", "東京都の天気", "東京都の今日の天気は晴れです。"),
@@ -214,7 +216,7 @@ mod japanese_search_tests {
         let temp_dir = TempDir::new().unwrap();
         let index_path = temp_dir.path().to_path_buf();
 
-        let mut manager = SearchManager::new(Some(index_path)).unwrap();
+        let manager = SearchManager::new(Some(index_path)).unwrap();
 
         // Simulate the Notion page content
         let _content = "各種設定情報 データベース関連 レポート info \
@@ -227,6 +229,8 @@ mod japanese_search_tests {
             name: "だれでもAIメーカー関連".to_string(),
             url: "https://www.notion.so/AI-fd5e608496a94ccaabd62821096a4992".to_string(),
             folder_path: vec!["new-index-test".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: Some("2024-01-01".to_string()),
             date_modified: Some("2024-01-01".to_string()),
         }];
@@ -1,11 +1,8 @@
 #[cfg(test)]
 mod lindera_integration_tests {
-    use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
-    use lindera::mode::{Mode, Penalty};
-    use lindera::segmenter::Segmenter;
-    use lindera_tantivy::tokenizer::LinderaTokenizer;
     use mcp_bookmark::bookmark::FlatBookmark;
     use mcp_bookmark::search::SearchManager;
+    use mcp_bookmark::search::tokenizer::{LinderaDictionary, create_lindera_tokenizer};
     use tantivy::collector::TopDocs;
     use tantivy::query::QueryParser;
     use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value};
@@ -34,10 +31,7 @@ mod lindera_integration_tests {
         let index = Index::create_in_ram(schema.clone());
 
         // Configure and register Lindera tokenizer
-        let dictionary = load_dictionary_from_kind(DictionaryKind::IPADIC).unwrap();
-        let mode = Mode::Decompose(Penalty::default());
-        let segmenter = Segmenter::new(mode, dictionary, None);
-        let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+        let tokenizer = create_lindera_tokenizer(LinderaDictionary::default(), None).unwrap();
 
         // Register tokenizer with name "lang_ja"
         index.tokenizers().register("lang_ja", tokenizer);
@@ -113,7 +107,7 @@ mod lindera_integration_tests {
         let index_path = temp_dir.path().to_path_buf();
 
         // Create search manager
-        let mut manager = SearchManager::new(Some(index_path)).unwrap();
+        let manager = SearchManager::new(Some(index_path)).unwrap();
 
         // Create test bookmarks - note that content is in the title field
         let bookmarks = vec![
@@ -122,6 +116,8 @@ mod lindera_integration_tests {
                 name: "田中さんの出社日について確認します。基本的に平日はSlack OK。".to_string(),
                 url: "https://example.com/1".to_string(),
                 folder_path: vec!["test".to_string()],
+                tags: vec![],
+                profile: None,
                 date_added: Some("2024-01-01".to_string()),
                 date_modified: Some("2024-01-01".to_string()),
             },
@@ -130,6 +126,8 @@ mod lindera_integration_tests {
                 name: "明日の会議は田中さんも参加予定です。".to_string(),
                 url: "https://example.com/2".to_string(),
                 folder_path: vec!["test".to_string()],
+                tags: vec![],
+                profile: None,
                 date_added: Some("2024-01-01".to_string()),
                 date_modified: Some("2024-01-01".to_string()),
             },
@@ -139,6 +137,8 @@ mod lindera_integration_tests {
                     .to_string(),
                 url: "https://example.com/3".to_string(),
                 folder_path: vec!["test".to_string()],
+                tags: vec![],
+                profile: None,
                 date_added: Some("2024-01-01".to_string()),
                 date_modified: Some("2024-01-01".to_string()),
             },
@@ -16,6 +16,8 @@ fn test_basic_phrase_search() -> Result<()> {
             name: "React hooks documentation".to_string(),
             url: "https://example.com/react-hooks".to_string(),
             folder_path: vec!["Development".to_string(), "React".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -24,6 +26,8 @@ fn test_basic_phrase_search() -> Result<()> {
             name: "Vue composition API".to_string(),
             url: "https://example.com/vue".to_string(),
             folder_path: vec!["Development".to_string(), "Vue".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -32,6 +36,8 @@ fn test_basic_phrase_search() -> Result<()> {
             name: "Custom React and hooks tutorial".to_string(),
             url: "https://example.com/tutorial".to_string(),
             folder_path: vec!["Development".to_string(), "Tutorials".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -54,7 +60,7 @@ fn test_basic_phrase_search() -> Result<()> {
     ];
 
     // Create and populate search manager
-    let mut manager = SearchManager::new_for_testing(index_path)?;
+    let manager = SearchManager::new_for_testing(index_path)?;
 
     for (bookmark, (url, content)) in bookmarks.iter().zip(content_map.iter()) {
         assert_eq!(&bookmark.url, url);
@@ -102,6 +108,8 @@ fn test_phrase_search_with_special_chars() -> Result<()> {
             name: "Error handling".to_string(),
             url: "https://example.com/errors".to_string(),
             folder_path: vec!["Development".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -110,6 +118,8 @@ fn test_phrase_search_with_special_chars() -> Result<()> {
             name: "JavaScript errors".to_string(),
             url: "https://example.com/js-errors".to_string(),
             folder_path: vec!["Development".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -126,7 +136,7 @@ fn test_phrase_search_with_special_chars() -> Result<()> {
         ),
     ];
 
-    let mut manager = SearchManager::new_for_testing(index_path)?;
+    let manager = SearchManager::new_for_testing(index_path)?;
 
     for (bookmark, (url, content)) in bookmarks.iter().zip(content_map.iter()) {
         assert_eq!(&bookmark.url, url);
@@ -154,6 +164,8 @@ fn test_japanese_phrase_search() -> Result<()> {
             name: "React フック入門".to_string(),
             url: "https://example.com/react-hooks-ja".to_string(),
             folder_path: vec!["Development".to_string(), "日本語".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -162,6 +174,8 @@ fn test_japanese_phrase_search() -> Result<()> {
             name: "JavaScript基礎".to_string(),
             url: "https://example.com/js-basics-ja".to_string(),
             folder_path: vec!["Development".to_string(), "日本語".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -178,7 +192,7 @@ fn test_japanese_phrase_search() -> Result<()> {
         ),
     ];
 
-    let mut manager = SearchManager::new_for_testing(index_path)?;
+    let manager = SearchManager::new_for_testing(index_path)?;
 
     for (bookmark, (url, content)) in bookmarks.iter().zip(content_map.iter()) {
         assert_eq!(&bookmark.url, url);
@@ -206,11 +220,13 @@ fn test_empty_phrase_search() -> Result<()> {
         name: "Test document".to_string(),
         url: "https://example.com/test".to_string(),
         folder_path: vec!["Test".to_string()],
+        tags: vec![],
+        profile: None,
         date_added: None,
         date_modified: None,
     }];
 
-    let mut manager = SearchManager::new_for_testing(index_path)?;
+    let manager = SearchManager::new_for_testing(index_path)?;
     manager.index_bookmark_with_content(&bookmarks[0], Some("Test content"))?;
     manager.commit()?;
 
@@ -240,11 +256,13 @@ fn test_unclosed_phrase_search() -> Result<()> {
         name: "React hooks useState".to_string(),
         url: "https://example.com/test".to_string(),
         folder_path: vec!["Test".to_string()],
+        tags: vec![],
+        profile: None,
         date_added: None,
         date_modified: None,
     }];
 
-    let mut manager = SearchManager::new_for_testing(index_path)?;
+    let manager = SearchManager::new_for_testing(index_path)?;
     manager.index_bookmark_with_content(
         &bookmarks[0],
         Some("Learn about React hooks useState and useEffect"),
@@ -271,6 +289,8 @@ fn test_multiple_phrases_search() -> Result<()> {
             name: "React Server Components and Client Components".to_string(),
             url: "https://example.com/rsc".to_string(),
             folder_path: vec!["Development".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -279,6 +299,8 @@ fn test_multiple_phrases_search() -> Result<()> {
             name: "Next.js documentation".to_string(),
             url: "https://example.com/nextjs".to_string(),
             folder_path: vec!["Development".to_string()],
+            tags: vec![],
+            profile: None,
             date_added: None,
             date_modified: None,
         },
@@ -295,7 +317,7 @@ fn test_multiple_phrases_search() -> Result<()> {
         ),
     ];
 
-    let mut manager = SearchManager::new_for_testing(index_path)?;
+    let manager = SearchManager::new_for_testing(index_path)?;
 
     for (bookmark, (url, content)) in bookmarks.iter().zip(content_map.iter()) {
         assert_eq!(&bookmark.url, url);
@@ -1,9 +1,6 @@
 #[cfg(test)]
 mod simple_tests {
-    use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
-    use lindera::mode::{Mode, Penalty};
-    use lindera::segmenter::Segmenter;
-    use lindera_tantivy::tokenizer::LinderaTokenizer;
+    use mcp_bookmark::search::tokenizer::{LinderaDictionary, create_lindera_tokenizer};
     use tantivy::collector::TopDocs;
     use tantivy::query::QueryParser;
     use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
@@ -32,10 +29,7 @@ mod simple_tests {
 
         // Register Lindera tokenizer BEFORE indexing
         println!("Registering Lindera tokenizer...");
-        let dictionary = load_dictionary_from_kind(DictionaryKind::IPADIC).unwrap();
-        let mode = Mode::Decompose(Penalty::default());
-        let segmenter = Segmenter::new(mode, dictionary, None);
-        let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+        let tokenizer = create_lindera_tokenizer(LinderaDictionary::default(), None).unwrap();
         index.tokenizers().register("lindera", tokenizer);
         println!("Lindera tokenizer registered!");
 
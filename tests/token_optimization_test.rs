@@ -11,12 +11,10 @@ fn test_search_result_no_duplicate_content() {
         title: "Test Title".to_string(),
         url: "https://example.com".to_string(),
         snippet: "This is a test snippet".to_string(),
-        full_content: None,
         score: 0.95,
         folder_path: "/test/path".to_string(),
-        last_indexed: None,
         context_type: Some("Content".to_string()),
-        page_number: None,
+        ..Default::default()
     };
 
     // Serialize to JSON to verify structure
@@ -102,12 +100,9 @@ fn test_memory_efficiency() {
         title: "Memory Test".to_string(),
         url: "https://example.com/memory".to_string(),
         snippet: snippet_text.to_string(),
-        full_content: None,
         score: 0.85,
         folder_path: "/test".to_string(),
-        last_indexed: None,
-        context_type: None,
-        page_number: None,
+        ..Default::default()
     };
 
     // Calculate approximate memory usage